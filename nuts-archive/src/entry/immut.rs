@@ -32,8 +32,12 @@ use crate::entry::{populate_mode_api, populate_tstamp_api, Inner};
 use crate::error::{ArchiveResult, Error};
 use crate::id::Id;
 use crate::pager::Pager;
+use crate::query::{find_entry, Query};
 use crate::tree::Tree;
 
+/// Maximum number of content bytes, which are fetched at once.
+const READ_MANY_SIZE: usize = 1024 * 1024;
+
 /// An entry of the archive.
 ///
 /// An instance of `Entry` represents an entry of the archive.
@@ -65,6 +69,15 @@ impl<'a, B: Backend> Entry<'a, B> {
         }
     }
 
+    /// Returns the next entry in the archive, which is selected by the given
+    /// `query`.
+    ///
+    /// If there are no further entries matching the query, [`None`] is
+    /// returned.
+    pub fn find_next(self, query: &Query) -> Option<ArchiveResult<Entry<'a, B>, B>> {
+        find_entry(self.next(), query)
+    }
+
     /// Returns the name of the entry.
     pub fn name(&self) -> &str {
         &self.inner_entry().inner.name
@@ -201,6 +214,12 @@ impl<'a, B: Backend> Entry<'a, B> {
         }
     }
 
+    pub(crate) fn position(&self) -> (usize, &Id<B>) {
+        let entry = self.inner_entry();
+
+        (entry.idx, &entry.id)
+    }
+
     fn inner_entry(&'a self) -> &InnerEntry<'a, B> {
         match self {
             Self::File(inner) => &inner.0,
@@ -410,7 +429,9 @@ pub struct InnerEntry<'a, B: Backend> {
     tree: &'a mut Tree<B>,
     inner: Inner,
     idx: usize,
+    id: Id<B>,
     rcache: Vec<u8>,
+    rpos: usize,
     ridx: usize,
 }
 
@@ -428,7 +449,9 @@ impl<'a, B: Backend> InnerEntry<'a, B> {
             tree,
             inner,
             idx,
+            id: id.clone(),
             rcache: vec![],
+            rpos: 0,
             ridx: 0,
         })
     }
@@ -482,50 +505,66 @@ impl<'a, B: Backend> InnerEntry<'a, B> {
     }
 
     fn read(&mut self, buf: &mut [u8]) -> ArchiveResult<usize, B> {
-        if self.rcache.is_empty() {
-            let blocks = self.content_blocks();
+        if self.rpos >= self.rcache.len() {
+            let blocks = self.content_blocks() as usize;
 
             debug!("fill cache: idx={}, blocks={}", self.ridx, blocks);
 
-            if self.ridx >= blocks as usize {
+            if self.ridx >= blocks {
                 return Ok(0);
             }
 
+            // Fetch the data blocks of the entry with a single read, but
+            // limit the amount of memory needed for large entries.
             let block_size = self.pager.block_size() as usize;
+            let batch = cmp::min(blocks - self.ridx, cmp::max(READ_MANY_SIZE / block_size, 1));
+            let first = self.idx + self.ridx + 1;
+            let mut ids = Vec::with_capacity(batch);
+
+            for idx in first..first + batch {
+                match self.tree.lookup(self.pager, idx) {
+                    Some(Ok(id)) => ids.push(id.clone()),
+                    Some(Err(err)) => return Err(err),
+                    None => {
+                        warn!("premature end of archive, no block at {}", idx);
+                        break;
+                    }
+                };
+            }
+
+            if ids.is_empty() {
+                return Ok(0);
+            }
+
             let remaining = self.inner.size as usize - self.ridx * block_size;
-            let cache_size = cmp::min(remaining, block_size);
+            let cache_size = cmp::min(remaining, ids.len() * block_size);
 
             debug!(
-                "fill cache: remaining={}, cache_size={}",
-                remaining, cache_size
+                "fill cache: remaining={}, cache_size={}, nblocks={}",
+                remaining,
+                cache_size,
+                ids.len()
             );
 
-            self.rcache.resize(cache_size, 0);
-
-            let idx = self.idx + self.ridx + 1;
-
-            match self.tree.lookup(self.pager, idx) {
-                Some(Ok(id)) => {
-                    let n = self.pager.read(id, self.rcache.as_mut_slice())?;
+            self.rcache.clear();
+            self.rpos = 0;
 
-                    assert_eq!(n, cache_size);
+            for block in self.pager.read_many(&ids)? {
+                assert_eq!(block.len(), block_size);
+                self.rcache.extend_from_slice(&block);
+            }
 
-                    self.ridx += 1;
-                }
-                Some(Err(err)) => return Err(err),
-                None => {
-                    warn!("premature end of archive, no block at {}", idx);
-                    return Ok(0);
-                }
-            };
+            self.rcache.truncate(cache_size);
+            self.ridx += ids.len();
         }
 
-        let len = cmp::min(self.rcache.len(), buf.len());
+        // A read does not cross the end of a block
+        let block_size = self.pager.block_size() as usize;
+        let block_end = cmp::min((self.rpos / block_size + 1) * block_size, self.rcache.len());
+        let len = cmp::min(block_end - self.rpos, buf.len());
 
-        self.rcache
-            .drain(..len)
-            .enumerate()
-            .for_each(|(i, n)| buf[i] = n);
+        buf[..len].copy_from_slice(&self.rcache[self.rpos..self.rpos + len]);
+        self.rpos += len;
 
         Ok(len)
     }
@@ -40,6 +40,8 @@ fn setup_archive(num: u8) -> Archive<MemoryBackend> {
     if num > 0 {
         entry.write_all(&(0..num).collect::<Vec<u8>>()).unwrap();
     }
+    entry.flush().unwrap();
+    drop(entry);
 
     archive
 }
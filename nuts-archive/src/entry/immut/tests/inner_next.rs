@@ -57,11 +57,15 @@ macro_rules! mk_test {
             $(
                 let mut entry = archive.append_file($fname).build().unwrap();
                 entry.write_all(&BYTES[..$nbytes]).unwrap();
+                entry.flush().unwrap();
+                drop(entry);
             )*
 
             $(
                 let mut entry = archive.append_file($last_fname).build().unwrap();
                 entry.write_all(&BYTES[..$last_nbytes]).unwrap();
+                entry.flush().unwrap();
+                drop(entry);
             )*
 
             let entry = InnerEntry::first(&mut archive.pager, &mut archive.tree).unwrap().unwrap();
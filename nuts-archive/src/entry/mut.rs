@@ -23,7 +23,7 @@
 #[cfg(test)]
 mod tests;
 
-use log::debug;
+use log::{debug, error};
 use nuts_backend::Backend;
 use std::cmp;
 
@@ -41,7 +41,7 @@ macro_rules! impl_new {
         pub(crate) fn new(
             pager: &'a mut Pager<B>,
             header_id: &'a Id<B>,
-            header: &'a mut Header,
+            header: &'a mut Header<B>,
             tree: &'a mut Tree<B>,
             name: String,
         ) -> $type<'a, B> {
@@ -130,7 +130,7 @@ impl<'a, B: Backend> SymlinkBuilder<'a, B> {
     pub(crate) fn new(
         pager: &'a mut Pager<B>,
         header_id: &'a Id<B>,
-        header: &'a mut Header,
+        header: &'a mut Header<B>,
         tree: &'a mut Tree<B>,
         name: String,
         target: String,
@@ -145,11 +145,11 @@ impl<'a, B: Backend> SymlinkBuilder<'a, B> {
 
     /// Finally, creates the new symlink entry at the end of the archive.
     pub fn build(self) -> ArchiveResult<(), B> {
-        let mut entry = self.builder.build()?;
+        let target = self.target;
 
-        entry.write_all(self.target.as_bytes())?;
-
-        Ok(())
+        self.builder
+            .build_with(|entry| entry.write_all(target.as_bytes()))
+            .map(|_| ())
     }
 
     fn inner(&self) -> &Inner {
@@ -164,7 +164,7 @@ impl<'a, B: Backend> SymlinkBuilder<'a, B> {
 struct InnerBuilder<'a, B: Backend> {
     pager: &'a mut Pager<B>,
     header_id: &'a Id<B>,
-    header: &'a mut Header,
+    header: &'a mut Header<B>,
     tree: &'a mut Tree<B>,
     entry: Inner,
 }
@@ -173,7 +173,7 @@ impl<'a, B: Backend> InnerBuilder<'a, B> {
     fn new(
         pager: &'a mut Pager<B>,
         header_id: &'a Id<B>,
-        header: &'a mut Header,
+        header: &'a mut Header<B>,
         tree: &'a mut Tree<B>,
         name: String,
         mode: Mode,
@@ -188,44 +188,121 @@ impl<'a, B: Backend> InnerBuilder<'a, B> {
     }
 
     fn build(self) -> ArchiveResult<EntryMut<'a, B>, B> {
-        let id = self.tree.aquire(self.pager)?.clone();
+        self.build_with(|_| Ok(()))
+    }
 
-        self.entry.flush(self.pager, &id)?;
+    /// Creates the entry and passes it to `f`, all in one transaction.
+    fn build_with<F: FnOnce(&mut EntryMut<B>) -> ArchiveResult<(), B>>(
+        self,
+        f: F,
+    ) -> ArchiveResult<EntryMut<'a, B>, B> {
+        let InnerBuilder {
+            pager,
+            header_id,
+            header,
+            tree,
+            mut entry,
+        } = self;
 
-        self.header.inc_files();
-        flush_header(self.pager, self.header_id, self.header, self.tree)?;
+        let saved_tree = tree.clone();
+        let saved_nfiles = header.nfiles;
 
-        Ok(EntryMut::new(
-            self.pager,
-            self.header_id,
-            self.header,
-            self.tree,
-            self.entry,
-            id,
-        ))
+        let began = pager.begin()?;
+
+        let id = match Self::create(pager, header_id, header, tree, &mut entry) {
+            Ok(id) => id,
+            Err(err) => {
+                if began {
+                    pager.rollback();
+                }
+
+                *tree = saved_tree;
+                header.nfiles = saved_nfiles;
+
+                return Err(err);
+            }
+        };
+
+        let mut entry = EntryMut::new(pager, header_id, header, tree, entry, id);
+
+        // The content written by `f` joins the transaction of the build
+        let result = f(&mut entry).and_then(|()| entry.flush()).and_then(|()| {
+            if began {
+                entry.pager.commit()
+            } else {
+                Ok(())
+            }
+        });
+
+        match result {
+            Ok(()) => Ok(entry),
+            Err(err) => {
+                if began {
+                    entry.pager.rollback();
+                }
+
+                *entry.tree = saved_tree;
+                entry.header.nfiles = saved_nfiles;
+
+                Err(err)
+            }
+        }
     }
+
+    fn create(
+        pager: &mut Pager<B>,
+        header_id: &Id<B>,
+        header: &mut Header<B>,
+        tree: &mut Tree<B>,
+        entry: &mut Inner,
+    ) -> ArchiveResult<Id<B>, B> {
+        let id = tree.aquire(pager)?.clone();
+
+        entry.flush(pager, &id)?;
+
+        header.inc_files();
+        flush_header(pager, header_id, header, tree)?;
+
+        Ok(id)
+    }
+}
+
+/// State of an [`EntryMut`] at the beginning of a transaction.
+///
+/// It is restored, if the transaction is rolled back.
+struct Checkpoint<B: Backend> {
+    began: bool,
+    tree: Tree<B>,
+    last: Id<B>,
+    size: u64,
+    cache: Vec<u8>,
 }
 
 /// A mutable entry of the archive.
 ///
 /// An `EntryMut` instance is returned by [`FileBuilder::build()`] and gives
 /// you the possibility to add content to the entry.
+///
+/// The content is appended in a transaction, which is committed by
+/// [`EntryMut::flush()`]. If the `EntryMut` is dropped, it is flushed as a
+/// last resort, but errors can only be logged there.
 pub struct EntryMut<'a, B: Backend> {
     pager: &'a mut Pager<B>,
     header_id: &'a Id<B>,
-    header: &'a mut Header,
+    header: &'a mut Header<B>,
     tree: &'a mut Tree<B>,
     entry: Inner,
     first: Id<B>,
     last: Id<B>,
     cache: Vec<u8>,
+    checkpoint: Option<Checkpoint<B>>,
 }
 
 impl<'a, B: Backend> EntryMut<'a, B> {
     fn new(
         pager: &'a mut Pager<B>,
         header_id: &'a Id<B>,
-        header: &'a mut Header,
+        header: &'a mut Header<B>,
         tree: &'a mut Tree<B>,
         entry: Inner,
         id: Id<B>,
@@ -239,6 +316,7 @@ impl<'a, B: Backend> EntryMut<'a, B> {
             first: id.clone(),
             last: id,
             cache: vec![],
+            checkpoint: None,
         }
     }
 
@@ -246,7 +324,90 @@ impl<'a, B: Backend> EntryMut<'a, B> {
     ///
     /// Note that the entire buffer is not necessarily written. The method
     /// returns the number of bytes that were actually written.
+    ///
+    /// The first write after a [flush](EntryMut::flush) starts a transaction.
+    /// If a write fails, the transaction is rolled back and the content
+    /// appended since the last flush is discarded.
     pub fn write(&mut self, buf: &[u8]) -> ArchiveResult<usize, B> {
+        if self.checkpoint.is_none() {
+            self.begin()?;
+        }
+
+        let result = self.append(buf);
+
+        if result.is_err() {
+            self.rollback();
+        }
+
+        result
+    }
+
+    pub fn write_all(&mut self, mut buf: &[u8]) -> ArchiveResult<(), B> {
+        while !buf.is_empty() {
+            let n = self.write(buf)?;
+
+            buf = &buf[n..]
+        }
+
+        Ok(())
+    }
+
+    /// Stores the content appended since the last flush in the archive.
+    ///
+    /// The content and the updated metadata of the entry are committed in
+    /// one transaction: Either everything is stored in the archive, or
+    /// nothing is changed.
+    pub fn flush(&mut self) -> ArchiveResult<(), B> {
+        let began = match self.checkpoint.as_ref() {
+            Some(checkpoint) => checkpoint.began,
+            None => return Ok(()),
+        };
+
+        let result =
+            self.flush_metadata()
+                .and_then(|()| if began { self.pager.commit() } else { Ok(()) });
+
+        match result {
+            Ok(()) => {
+                self.checkpoint = None;
+                Ok(())
+            }
+            Err(err) => {
+                self.rollback();
+                Err(err)
+            }
+        }
+    }
+
+    fn begin(&mut self) -> ArchiveResult<(), B> {
+        // Inside of a build the transaction of the builder is joined
+        let began = self.pager.begin()?;
+
+        self.checkpoint = Some(Checkpoint {
+            began,
+            tree: self.tree.clone(),
+            last: self.last.clone(),
+            size: self.entry.size,
+            cache: self.cache.clone(),
+        });
+
+        Ok(())
+    }
+
+    fn rollback(&mut self) {
+        if let Some(checkpoint) = self.checkpoint.take() {
+            if checkpoint.began {
+                self.pager.rollback();
+            }
+
+            *self.tree = checkpoint.tree;
+            self.last = checkpoint.last;
+            self.entry.size = checkpoint.size;
+            self.cache = checkpoint.cache;
+        }
+    }
+
+    fn append(&mut self, buf: &[u8]) -> ArchiveResult<usize, B> {
         let block_size = self.pager.block_size() as u64;
         let pos = (self.entry.size % block_size) as usize;
 
@@ -276,19 +437,94 @@ impl<'a, B: Backend> EntryMut<'a, B> {
         self.pager.write(&self.last, &self.cache)?;
 
         self.entry.size += nbytes as u64;
-        self.entry.flush(self.pager, &self.first)?;
-        flush_header(self.pager, self.header_id, self.header, self.tree)?;
 
         Ok(nbytes)
     }
 
-    pub fn write_all(&mut self, mut buf: &[u8]) -> ArchiveResult<(), B> {
-        while !buf.is_empty() {
-            let n = self.write(buf)?;
+    fn flush_metadata(&mut self) -> ArchiveResult<(), B> {
+        self.entry.flush(self.pager, &self.first)?;
+        flush_header(self.pager, self.header_id, self.header, self.tree)
+    }
+}
 
-            buf = &buf[n..]
+impl<'a, B: Backend> Drop for EntryMut<'a, B> {
+    fn drop(&mut self) {
+        // last resort, the entry is usually flushed before
+        if let Err(err) = self.flush() {
+            error!("failed to flush entry {}: {}", self.entry.name, err);
         }
+    }
+}
+
+/// A handle to change the metadata of an existing entry.
+///
+/// An `EntryHandle` instance is returned by
+/// [`Archive::lookup_mut()`](crate::Archive::lookup_mut). You can change the
+/// name, the permissions and the timestamps of the entry. The changes are
+/// written back into the archive by calling [`EntryHandle::flush()`].
+pub struct EntryHandle<'a, B: Backend> {
+    pager: &'a mut Pager<B>,
+    id: Id<B>,
+    entry: Inner,
+}
+
+impl<'a, B: Backend> EntryHandle<'a, B> {
+    pub(crate) fn load(pager: &'a mut Pager<B>, id: Id<B>) -> ArchiveResult<EntryHandle<'a, B>, B> {
+        let entry = Inner::load(pager, &id)?;
+
+        Ok(EntryHandle { pager, id, entry })
+    }
+
+    /// Returns the name of the entry.
+    pub fn name(&self) -> &str {
+        &self.entry.name
+    }
+
+    /// Updates the name of the entry.
+    pub fn set_name<N: AsRef<str>>(&mut self, name: N) {
+        self.entry.name = name.as_ref().to_string();
+    }
+
+    /// Returns the size of the entry.
+    pub fn size(&self) -> u64 {
+        self.entry.size
+    }
+
+    /// Tests whether this entry represents a file.
+    pub fn is_file(&self) -> bool {
+        self.entry.mode.is_file()
+    }
+
+    /// Tests whether this entry represents a directory.
+    pub fn is_directory(&self) -> bool {
+        self.entry.mode.is_directory()
+    }
+
+    /// Tests whether this entry represents a symlink.
+    pub fn is_symlink(&self) -> bool {
+        self.entry.mode.is_symlink()
+    }
+
+    populate_mode_api!();
+    populate_mode_api!(mut);
+    populate_tstamp_api!(mut);
+
+    /// Writes the modified metadata back into the archive.
+    ///
+    /// The entry is updated in place, its content is not touched.
+    pub fn flush(&mut self) -> ArchiveResult<(), B> {
+        self.entry.flush(self.pager, &self.id)?;
+
+        debug!("entry {} flushed at {}", self.entry.name, self.id);
 
         Ok(())
     }
+
+    fn inner(&self) -> &Inner {
+        &self.entry
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        &mut self.entry
+    }
 }
@@ -21,6 +21,7 @@
 // IN THE SOFTWARE.
 
 mod directory;
+mod flush;
 mod symlink;
 mod write;
 mod write_all;
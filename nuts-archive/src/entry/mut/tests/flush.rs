@@ -0,0 +1,102 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_memory::MemoryBackend;
+
+use crate::entry::r#mut::tests::{lookup, setup_file_builder};
+use crate::entry::{Inner, FULL};
+use crate::tests::setup_archive_with_bsize;
+use crate::Archive;
+
+fn read_size(archive: &mut Archive<MemoryBackend>) -> u64 {
+    let id = lookup(archive, 0).unwrap().clone();
+    let mut reader = archive.pager.read_buf(&id).unwrap();
+
+    reader.read::<Inner>().unwrap().size
+}
+
+#[test]
+fn transaction() {
+    let mut archive = setup_archive_with_bsize(FULL as u32);
+    let mut entry = setup_file_builder(&mut archive).build().unwrap();
+
+    assert!(!entry.pager.in_transaction());
+
+    entry.write_all(&[1; FULL as usize + 1]).unwrap();
+    assert!(entry.pager.in_transaction());
+
+    entry.flush().unwrap();
+    assert!(!entry.pager.in_transaction());
+
+    // nothing to commit
+    entry.flush().unwrap();
+    assert!(!entry.pager.in_transaction());
+
+    drop(entry);
+
+    assert_eq!(read_size(&mut archive), FULL as u64 + 1);
+}
+
+#[test]
+fn rollback() {
+    let mut archive = setup_archive_with_bsize(FULL as u32);
+    let mut entry = setup_file_builder(&mut archive).build().unwrap();
+
+    entry.write_all(&[1; FULL as usize]).unwrap();
+    entry.flush().unwrap();
+
+    entry.write_all(&[2; FULL as usize + 1]).unwrap();
+    entry.rollback();
+
+    // the content appended since the last flush is discarded
+    assert!(!entry.pager.in_transaction());
+    assert_eq!(entry.entry.size, FULL as u64);
+
+    entry.write_all(&[3; 1]).unwrap();
+    entry.flush().unwrap();
+    drop(entry);
+
+    assert_eq!(read_size(&mut archive), FULL as u64 + 1);
+
+    let id1 = lookup(&mut archive, 1).unwrap().clone();
+    let id2 = lookup(&mut archive, 2).unwrap().clone();
+    assert!(lookup(&mut archive, 3).is_none());
+
+    let buf = archive.pager.read_buf_raw(&id1).unwrap();
+    assert_eq!(buf, [1; FULL as usize]);
+
+    let buf = archive.pager.read_buf_raw(&id2).unwrap();
+    assert_eq!(buf[0], 3);
+    assert_eq!(buf[1..], [0; FULL as usize - 1]);
+}
+
+#[test]
+fn drop_flushes() {
+    let mut archive = setup_archive_with_bsize(FULL as u32);
+    let mut entry = setup_file_builder(&mut archive).build().unwrap();
+
+    entry.write_all(&[1; 3]).unwrap();
+    drop(entry);
+
+    assert!(!archive.pager.in_transaction());
+    assert_eq!(read_size(&mut archive), 3);
+}
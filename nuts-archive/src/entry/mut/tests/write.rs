@@ -46,6 +46,8 @@ macro_rules! make_tests {
                 entry.write(&(0..FULL).collect::<Vec<u8>>()).unwrap(),
                 FULL as usize
             );
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
@@ -69,6 +71,8 @@ macro_rules! make_tests {
             for i in 0..FULL {
                 assert_eq!(entry.write(&[i]).unwrap(), 1);
             }
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
@@ -92,6 +96,8 @@ macro_rules! make_tests {
             for i in 0..FULL + HALF {
                 assert_eq!(entry.write(&[i]).unwrap(), 1);
             }
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
@@ -123,6 +129,8 @@ macro_rules! make_tests {
             for i in 0..2 * FULL {
                 assert_eq!(entry.write(&[i]).unwrap(), 1);
             }
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
@@ -151,6 +159,8 @@ macro_rules! make_tests {
                 assert_eq!(buf.len(), 2);
                 assert_eq!(entry.write(buf).unwrap(), 2);
             }
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
@@ -175,6 +185,8 @@ macro_rules! make_tests {
                 assert_eq!(buf.len(), 2);
                 assert_eq!(entry.write(buf).unwrap(), 2);
             }
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
@@ -207,6 +219,8 @@ macro_rules! make_tests {
                 assert_eq!(buf.len(), 2);
                 assert_eq!(entry.write(buf).unwrap(), 2);
             }
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
@@ -237,6 +251,8 @@ macro_rules! make_tests {
             }
 
             assert_eq!(entry.write(&[FULL - 1, FULL, FULL + 1]).unwrap(), 1);
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
@@ -268,6 +284,8 @@ macro_rules! make_tests {
                 assert_eq!(buf.len(), 3);
                 assert_eq!(entry.write(buf).unwrap(), 3);
             }
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
@@ -314,6 +332,8 @@ macro_rules! make_tests {
                     .unwrap(),
                 1
             );
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
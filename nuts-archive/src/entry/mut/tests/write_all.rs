@@ -28,6 +28,8 @@ macro_rules! make_tests {
             let mut entry = $setup(&mut archive).build().unwrap();
 
             entry.write_all(&[]).unwrap();
+            entry.flush().unwrap();
+            drop(entry);
 
             let id = lookup(&mut archive, 0).unwrap().clone();
             assert!(lookup(&mut archive, 1).is_none());
@@ -45,6 +47,8 @@ macro_rules! make_tests {
             let mut entry = $setup(&mut archive).build().unwrap();
 
             entry.write_all(&(0..HALF).collect::<Vec<u8>>()).unwrap();
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
@@ -67,6 +71,8 @@ macro_rules! make_tests {
             let mut entry = $setup(&mut archive).build().unwrap();
 
             entry.write_all(&(0..FULL).collect::<Vec<u8>>()).unwrap();
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
@@ -90,6 +96,8 @@ macro_rules! make_tests {
             entry
                 .write_all(&(0..FULL + HALF).collect::<Vec<u8>>())
                 .unwrap();
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
@@ -121,6 +129,8 @@ macro_rules! make_tests {
             entry
                 .write_all(&(0..2 * FULL).collect::<Vec<u8>>())
                 .unwrap();
+            entry.flush().unwrap();
+            drop(entry);
 
             let id0 = lookup(&mut archive, 0).unwrap().clone();
             let id1 = lookup(&mut archive, 1).unwrap().clone();
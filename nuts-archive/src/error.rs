@@ -73,6 +73,22 @@ pub enum Error<B: Backend> {
     /// give block.
     #[error("could not detect the type of the entry {}", if let Some(id) = .0 { format!("stored in {}", id) } else { "in unknown block".to_string() })]
     InvalidType(Option<B::Id>),
+
+    /// A snapshot with the given name already exists.
+    #[error("the snapshot {0} already exists")]
+    SnapshotExists(String),
+
+    /// No snapshot with the given name exists.
+    #[error("no such snapshot: {0}")]
+    NoSuchSnapshot(String),
+
+    /// The entry cannot be modified because it is part of a snapshot.
+    #[error("the entry {0} is part of a snapshot")]
+    Snapshotted(String),
+
+    /// The property with the given key does not fit into a block.
+    #[error("the property {0} is too large")]
+    InvalidProperty(String),
 }
 
 impl<B: Backend> From<nuts_bytes::Error> for Error<B> {
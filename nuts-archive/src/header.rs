@@ -28,10 +28,12 @@ use nuts_backend::Backend;
 use nuts_bytes::{FromBytes, ToBytes};
 use thiserror::Error;
 
+use crate::id::Id;
 use crate::magic::{validate_magic, Magic, MagicErrorFactory, MAGIC};
 use crate::{datetime, ArchiveResult, Error};
 
-const CURRENT_REVISION: u16 = 2;
+const CURRENT_REVISION: u16 = 3;
+const BTREE_REVISION: u16 = 3;
 const UNSUPPORTED_REVISIONS: [(u16, &str); 1] = [(1, "0.4.3")];
 
 #[derive(Debug, Error)]
@@ -45,7 +47,7 @@ impl MagicErrorFactory for HeaderMagicError {
 }
 
 #[derive(Debug, FromBytes, ToBytes)]
-pub struct Header {
+pub struct Header<B: Backend> {
     #[nuts_bytes(map_from_bytes = validate_magic::<HeaderMagicError>)]
     magic: Magic,
     revision: u16,
@@ -54,10 +56,18 @@ pub struct Header {
     #[nuts_bytes(map = datetime)]
     pub modified: DateTime<Utc>,
     pub nfiles: u64,
+    // The head of the snapshot list is not part of the header itself but is
+    // stored behind the tree. Archives created without snapshot-support
+    // have zeros at this position, which decodes to `None`.
+    #[nuts_bytes(skip)]
+    pub snapshots: Option<Id<B>>,
+    // Same for the head of the property list, which follows the snapshots.
+    #[nuts_bytes(skip)]
+    pub properties: Option<Id<B>>,
 }
 
-impl Header {
-    pub fn create() -> Header {
+impl<B: Backend> Header<B> {
+    pub fn create() -> Header<B> {
         let now = Utc::now();
 
         Header {
@@ -66,10 +76,12 @@ impl Header {
             created: now,
             modified: now,
             nfiles: 0,
+            snapshots: None,
+            properties: None,
         }
     }
 
-    pub fn validate_revision<B: Backend>(&self) -> ArchiveResult<(), B> {
+    pub fn validate_revision(&self) -> ArchiveResult<(), B> {
         for (rev, version) in UNSUPPORTED_REVISIONS {
             if self.revision == rev {
                 return Err(Error::UnsupportedRevision(rev, version.to_string()));
@@ -79,6 +91,15 @@ impl Header {
         Ok(())
     }
 
+    /// Tests whether the archive still uses the indirect block layout.
+    pub fn is_legacy(&self) -> bool {
+        self.revision < BTREE_REVISION
+    }
+
+    pub fn upgrade(&mut self) {
+        self.revision = CURRENT_REVISION;
+    }
+
     pub fn inc_files(&mut self) {
         self.nfiles += 1;
         self.modified = Utc::now();
@@ -30,12 +30,14 @@ use crate::magic::MAGIC;
 
 #[test]
 fn ser() {
-    let header = Header {
+    let header = Header::<MemoryBackend> {
         magic: MAGIC,
         revision: 1,
         created: Utc.timestamp_millis_opt(2).unwrap(),
         modified: Utc.timestamp_millis_opt(3).unwrap(),
         nfiles: 4,
+        snapshots: None,
+        properties: None,
     };
     let mut writer = Writer::new(vec![]);
 
@@ -58,7 +60,7 @@ fn de() {
         ]
         .as_slice(),
     );
-    let header = reader.read::<Header>().unwrap();
+    let header = reader.read::<Header<MemoryBackend>>().unwrap();
 
     assert_eq!(header.magic, *b"nuts-archive");
     assert_eq!(header.revision, 1);
@@ -77,7 +79,7 @@ fn de_inval_magic() {
         .as_slice(),
     );
 
-    let err: Error<MemoryBackend> = reader.read::<Header>().unwrap_err().into();
+    let err: Error<MemoryBackend> = reader.read::<Header<MemoryBackend>>().unwrap_err().into();
 
     assert!(matches!(err, Error::InvalidHeader(ref cause)
             if matches!(cause, nuts_bytes::Error::Custom(cause2)
@@ -86,12 +88,14 @@ fn de_inval_magic() {
 
 #[test]
 fn inc_files() {
-    let mut header = Header {
+    let mut header = Header::<MemoryBackend> {
         magic: MAGIC,
         revision: 1,
         created: Utc.timestamp_millis_opt(2).unwrap(),
         modified: Utc.timestamp_millis_opt(3).unwrap(),
         nfiles: 4,
+        snapshots: None,
+        properties: None,
     };
 
     header.inc_files();
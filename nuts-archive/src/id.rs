@@ -20,6 +20,9 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
+#[cfg(test)]
+mod tests;
+
 use nuts_backend::{Backend, Binary, IdSize};
 use nuts_bytes::{FromBytes, PutBytes, TakeBytes, ToBytes};
 use std::{fmt, str::FromStr};
@@ -39,6 +42,32 @@ impl<B: Backend> Id<B> {
     pub fn new(id: B::Id) -> Id<B> {
         Id(id)
     }
+
+    /// Returns the id, which is placed `n` positions behind this id.
+    ///
+    /// The bytes of the id are interpreted as a big-endian number. Returns
+    /// [`None`] on an overflow or if the resulting bytes are not a valid id.
+    pub fn offset(&self, n: u64) -> Option<Id<B>> {
+        let mut bytes = self.0.as_bytes();
+        let mut carry = n;
+
+        for byte in bytes.iter_mut().rev() {
+            if carry == 0 {
+                break;
+            }
+
+            let sum = *byte as u64 + (carry & 0xff);
+
+            *byte = sum as u8;
+            carry = (carry >> 8) + (sum >> 8);
+        }
+
+        if carry == 0 {
+            <B::Id as Binary>::from_bytes(&bytes).map(Id)
+        } else {
+            None
+        }
+    }
 }
 
 impl<B: Backend> Binary for Id<B> {
@@ -0,0 +1,46 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_memory::MemoryBackend;
+
+use crate::id::Id;
+
+macro_rules! _id {
+    ($id:expr) => {
+        $id.parse::<Id<MemoryBackend>>().unwrap()
+    };
+}
+
+#[test]
+fn offset() {
+    assert_eq!(_id!("1").offset(0), Some(_id!("1")));
+    assert_eq!(_id!("1").offset(1), Some(_id!("2")));
+    assert_eq!(_id!("255").offset(1), Some(_id!("256")));
+    assert_eq!(_id!("65535").offset(65537), Some(_id!("131072")));
+    assert_eq!(_id!("1").offset(4294967294), Some(_id!("4294967295")));
+}
+
+#[test]
+fn offset_overflow() {
+    assert_eq!(_id!("4294967295").offset(1), None);
+    assert_eq!(_id!("0").offset(4294967296), None);
+}
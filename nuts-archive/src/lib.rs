@@ -126,9 +126,11 @@
 //!
 //! let mut archive = Container::open_service::<ArchiveFactory>(container, false).unwrap();
 //!
-//! // Append a new file entry
+//! // Append a new file entry, the content is stored on flush
 //! let mut entry = archive.append_file("sample file").build().unwrap();
 //! entry.write_all("some sample data".as_bytes()).unwrap();
+//! entry.flush().unwrap();
+//! drop(entry);
 //!
 //! // Append a new directory entry
 //! archive
@@ -207,6 +209,9 @@ mod id;
 mod magic;
 mod migration;
 mod pager;
+mod properties;
+mod query;
+mod snapshot;
 #[cfg(test)]
 mod tests;
 mod tree;
@@ -217,37 +222,48 @@ use log::debug;
 use nuts_backend::Backend;
 use nuts_bytes::PutBytesError;
 use nuts_container::{Container, Service, ServiceFactory};
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 
 pub use entry::immut::{DirectoryEntry, Entry, FileEntry, SymlinkEntry};
 pub use entry::mode::Group;
-pub use entry::r#mut::{DirectoryBuilder, EntryMut, FileBuilder, SymlinkBuilder};
+pub use entry::r#mut::{DirectoryBuilder, EntryHandle, EntryMut, FileBuilder, SymlinkBuilder};
 pub use error::{ArchiveResult, Error};
+pub use query::{EntryType, Query};
+pub use snapshot::{Snapshot, SnapshotInfo};
 
 use crate::entry::immut::InnerEntry;
 use crate::header::Header;
 use crate::migration::Migration;
 use crate::pager::Pager;
-use crate::tree::Tree;
+use crate::properties::Properties;
+use crate::snapshot::SnapshotNode;
+use crate::tree::{Indirect, Tree};
 
 const SID: u32 = 0x61 << 24 | 0x72 << 16 | 0x63 << 8 | 0x68; // "arch"
 
 fn flush_header<B: Backend>(
     pager: &mut Pager<B>,
     id: &Id<B>,
-    header: &Header,
+    header: &Header<B>,
     tree: &Tree<B>,
 ) -> ArchiveResult<(), B> {
     fn inner<B: Backend>(
         pager: &mut Pager<B>,
-        header: &Header,
+        header: &Header<B>,
         tree: &Tree<B>,
     ) -> Result<usize, nuts_bytes::Error> {
         let mut writer = pager.create_writer();
         let mut n = 0;
 
         n += writer.write(header)?;
-        n += writer.write(tree)?;
+        n += match tree {
+            // Older revisions only know the untagged indirect layout
+            Tree::Indirect(indirect) if header.is_legacy() => writer.write(indirect)?,
+            _ => writer.write(tree)?,
+        };
+        n += writer.write(&header.snapshots)?;
+        n += writer.write(&header.properties)?;
 
         Ok(n)
     }
@@ -270,6 +286,27 @@ fn flush_header<B: Backend>(
     }
 }
 
+fn lookup_entry<'a, B: Backend>(
+    mut entry_opt: Option<ArchiveResult<Entry<'a, B>, B>>,
+    name: &str,
+) -> Option<ArchiveResult<Entry<'a, B>, B>> {
+    loop {
+        match entry_opt {
+            Some(Ok(entry)) => {
+                if entry.name() == name {
+                    return Some(Ok(entry));
+                }
+
+                entry_opt = entry.next();
+            }
+            Some(Err(err)) => return Some(Err(err)),
+            None => break,
+        }
+    }
+
+    None
+}
+
 /// Information/statistics from the archive.
 #[derive(Debug)]
 pub struct Info {
@@ -290,8 +327,9 @@ pub struct Info {
 pub struct Archive<B: Backend> {
     pager: Pager<B>,
     header_id: Id<B>,
-    header: Header,
+    header: Header<B>,
     tree: Tree<B>,
+    properties: Properties<B>,
 }
 
 impl<B: Backend> Archive<B> {
@@ -305,6 +343,66 @@ impl<B: Backend> Archive<B> {
         }
     }
 
+    /// Returns the properties of the archive.
+    ///
+    /// Properties are arbitrary key/value pairs attached to the archive, e.g.
+    /// the hostname or the id of a backup job.
+    pub fn properties(&self) -> &BTreeMap<String, String> {
+        self.properties.map()
+    }
+
+    /// Assigns the property `key` to the given `value`.
+    ///
+    /// An already existing property with the same key is replaced. If a
+    /// single property does not fit into a block of the archive, an
+    /// [`Error::InvalidProperty`] error is returned.
+    pub fn set_property<K: AsRef<str>, V: AsRef<str>>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> ArchiveResult<(), B> {
+        let key = key.as_ref().to_string();
+        let prev = self
+            .properties
+            .insert(key.clone(), value.as_ref().to_string());
+
+        let result = self.flush_properties();
+
+        if result.is_err() {
+            match prev {
+                Some(value) => self.properties.insert(key, value),
+                None => self.properties.remove(&key),
+            };
+        }
+
+        result
+    }
+
+    /// Removes the property with the given `key`.
+    ///
+    /// Returns the value of the removed property. If no such property exists,
+    /// [`None`] is returned.
+    pub fn remove_property<K: AsRef<str>>(&mut self, key: K) -> ArchiveResult<Option<String>, B> {
+        let value = self.properties.remove(key.as_ref());
+
+        if value.is_some() {
+            self.flush_properties()?;
+        }
+
+        Ok(value)
+    }
+
+    fn flush_properties(&mut self) -> ArchiveResult<(), B> {
+        let head = self.properties.flush(&mut self.pager)?;
+
+        if head != self.header.properties {
+            self.header.properties = head;
+            flush_header(&mut self.pager, &self.header_id, &self.header, &self.tree)?;
+        }
+
+        Ok(())
+    }
+
     /// Returns the first entry in the archive.
     ///
     /// Next, you can use [`Entry::next()`] to traverse through the archive.
@@ -324,23 +422,52 @@ impl<B: Backend> Archive<B> {
     /// given name wrapped into a [`Some`]. If no such entry exists, [`None`]
     /// is returned.
     pub fn lookup<N: AsRef<str>>(&mut self, name: N) -> Option<ArchiveResult<Entry<B>, B>> {
-        let mut entry_opt = self.first();
+        lookup_entry(self.first(), name.as_ref())
+    }
 
-        loop {
-            match entry_opt {
-                Some(Ok(entry)) => {
-                    if entry.name() == name.as_ref() {
-                        return Some(Ok(entry));
-                    }
+    /// Returns the first entry, which is selected by the given `query`.
+    ///
+    /// Next, you can use [`Entry::find_next()`] to get the next entry
+    /// selected by the query.
+    ///
+    /// If no entry matches the query, [`None`] is returned.
+    pub fn find(&mut self, query: &Query) -> Option<ArchiveResult<Entry<'_, B>, B>> {
+        query::find_entry(self.first(), query)
+    }
 
-                    entry_opt = entry.next();
-                }
-                Some(Err(err)) => return Some(Err(err)),
-                None => break,
+    /// Searches for an entry with the given `name` and returns a handle to
+    /// modify it.
+    ///
+    /// Like [`Archive::lookup()`] it returns the first entry with the given
+    /// name. The returned [`EntryHandle`] can be used to change the name, the
+    /// permissions and the timestamps of the entry. If no such entry exists,
+    /// [`None`] is returned.
+    ///
+    /// Entries, which are part of a [snapshot](Archive::snapshot), cannot be
+    /// modified anymore. In this case an [`Error::Snapshotted`] error is
+    /// returned.
+    pub fn lookup_mut<N: AsRef<str>>(
+        &mut self,
+        name: N,
+    ) -> Option<ArchiveResult<EntryHandle<'_, B>, B>> {
+        let (idx, id) = match self.lookup(name.as_ref())? {
+            Ok(entry) => {
+                let (idx, id) = entry.position();
+                (idx, id.clone())
             }
+            Err(err) => return Some(Err(err)),
+        };
+
+        let snapshots = match snapshot::walk(&mut self.pager, self.header.snapshots.as_ref()) {
+            Ok(snapshots) => snapshots,
+            Err(err) => return Some(Err(err)),
+        };
+
+        if snapshots.iter().any(|(_, node)| node.covers(idx)) {
+            return Some(Err(Error::Snapshotted(name.as_ref().to_string())));
         }
 
-        None
+        Some(EntryHandle::load(&mut self.pager, id))
     }
 
     /// Appends a new file entry with the given `name` at the end of the
@@ -398,8 +525,156 @@ impl<B: Backend> Archive<B> {
         )
     }
 
+    /// Takes a snapshot of the archive with the given `name`.
+    ///
+    /// The snapshot captures all entries, which are currently stored in the
+    /// archive. Entries appended later are not visible in the snapshot. The
+    /// snapshot shares its blocks with the archive, only a single block is
+    /// allocated to store the snapshot itself.
+    ///
+    /// Snapshot names are unique, if a snapshot with the given `name` already
+    /// exists, an [`Error::SnapshotExists`] error is returned.
+    pub fn snapshot<N: AsRef<str>>(&mut self, name: N) -> ArchiveResult<(), B> {
+        let name = name.as_ref();
+        let snapshots = snapshot::walk(&mut self.pager, self.header.snapshots.as_ref())?;
+
+        if snapshots.iter().any(|(_, node)| node.name() == name) {
+            return Err(Error::SnapshotExists(name.to_string()));
+        }
+
+        let id = self.pager.aquire()?;
+        let node = SnapshotNode::new(name, &self.header, &self.tree);
+
+        node.flush(&mut self.pager, &id)?;
+
+        self.header.snapshots = Some(id);
+        flush_header(&mut self.pager, &self.header_id, &self.header, &self.tree)?;
+
+        debug!("snapshot {} created", name);
+
+        Ok(())
+    }
+
+    /// Opens the snapshot with the given `name`.
+    ///
+    /// The returned [`Snapshot`] gives you read-only access to the entries
+    /// captured by the snapshot. If no such snapshot exists, an
+    /// [`Error::NoSuchSnapshot`] error is returned.
+    pub fn open_snapshot<N: AsRef<str>>(&mut self, name: N) -> ArchiveResult<Snapshot<'_, B>, B> {
+        let name = name.as_ref();
+        let snapshots = snapshot::walk(&mut self.pager, self.header.snapshots.as_ref())?;
+
+        match snapshots.into_iter().find(|(_, node)| node.name() == name) {
+            Some((_, node)) => Ok(Snapshot::new(&mut self.pager, node)),
+            None => Err(Error::NoSuchSnapshot(name.to_string())),
+        }
+    }
+
+    /// Returns a list of all snapshots of the archive.
+    ///
+    /// The snapshots are ordered by their creation, the oldest snapshot comes
+    /// first.
+    pub fn snapshots(&mut self) -> ArchiveResult<Vec<SnapshotInfo>, B> {
+        let snapshots = snapshot::walk(&mut self.pager, self.header.snapshots.as_ref())?;
+
+        Ok(snapshots
+            .iter()
+            .rev()
+            .map(|(_, node)| node.info())
+            .collect())
+    }
+
+    /// Removes the snapshot with the given `name`.
+    ///
+    /// Only the snapshot itself is removed, the entries of the archive are
+    /// not touched. If no such snapshot exists, an [`Error::NoSuchSnapshot`]
+    /// error is returned.
+    pub fn remove_snapshot<N: AsRef<str>>(&mut self, name: N) -> ArchiveResult<(), B> {
+        let name = name.as_ref();
+        let mut snapshots = snapshot::walk(&mut self.pager, self.header.snapshots.as_ref())?;
+
+        let idx = match snapshots.iter().position(|(_, node)| node.name() == name) {
+            Some(idx) => idx,
+            None => return Err(Error::NoSuchSnapshot(name.to_string())),
+        };
+
+        let (id, node) = snapshots.remove(idx);
+
+        if idx == 0 {
+            self.header.snapshots = node.next;
+            flush_header(&mut self.pager, &self.header_id, &self.header, &self.tree)?;
+        } else {
+            let (prev_id, prev) = &mut snapshots[idx - 1];
+
+            prev.next = node.next;
+            prev.flush(&mut self.pager, prev_id)?;
+        }
+
+        self.pager.release(id)?;
+
+        debug!("snapshot {} removed", name);
+
+        Ok(())
+    }
+
+    /// Tests whether the archive was created with an older revision and
+    /// should be migrated.
+    ///
+    /// See [`Archive::migrate()`] for details.
+    pub fn needs_migration(&self) -> bool {
+        self.header.is_legacy()
+    }
+
+    /// Migrates the archive to the current revision.
+    ///
+    /// Archives prior to revision 3 are using an indirect block layout, which
+    /// limits the size of the archive. The migration moves all blocks into
+    /// the new layout, which has no such limitation. Block content is not
+    /// touched by the migration.
+    ///
+    /// Index nodes of the old layout are released, unless the archive has
+    /// snapshots, which still reference them.
+    ///
+    /// Does nothing, if the archive is already up to date.
+    pub fn migrate(&mut self) -> ArchiveResult<(), B> {
+        if !self.header.is_legacy() {
+            return Ok(());
+        }
+
+        let nodes = self.tree.migrate(&mut self.pager)?;
+
+        self.header.upgrade();
+        flush_header(&mut self.pager, &self.header_id, &self.header, &self.tree)?;
+
+        if self.header.snapshots.is_none() {
+            for id in nodes {
+                self.pager.release(id)?;
+            }
+        }
+
+        debug!("archive migrated, {} blocks", self.tree.nblocks());
+
+        Ok(())
+    }
+
+    /// Writes all pending changes into the container.
+    ///
+    /// Modified blocks are kept in a cache and are written back lazily. Call
+    /// this method or [`Archive::into_container()`] to get notified about
+    /// errors. The cache is also flushed when the archive is dropped, but
+    /// possible errors are only logged there.
+    pub fn flush(&mut self) -> ArchiveResult<(), B> {
+        self.pager.flush()
+    }
+
     /// Consumes this `Archive`, returning the underlying [`Container`].
-    pub fn into_container(self) -> Container<B> {
+    ///
+    /// Pending changes are written into the container before.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the pending changes cannot be written into the container.
+    pub fn into_container(self) -> ArchiveResult<Container<B>, B> {
         self.pager.into_container()
     }
 }
@@ -441,6 +716,7 @@ impl<B: Backend + 'static> ServiceFactory<B> for ArchiveFactory {
             header_id: top_id,
             header,
             tree,
+            properties: Properties::new(),
         };
 
         debug!("archive created, header: {}", archive.header_id);
@@ -453,17 +729,26 @@ impl<B: Backend + 'static> ServiceFactory<B> for ArchiveFactory {
         let top_id = pager.top_id_or_err()?;
 
         let mut reader = pager.read_buf(&top_id)?;
-        let header = reader.read::<Header>()?;
+        let mut header = reader.read::<Header<B>>()?;
 
         header.validate_revision()?;
 
-        let tree = reader.read::<Tree<B>>()?;
+        let tree = if header.is_legacy() {
+            Tree::Indirect(reader.read::<Indirect<B>>()?)
+        } else {
+            reader.read::<Tree<B>>()?
+        };
+        header.snapshots = reader.read::<Option<Id<B>>>()?;
+        header.properties = reader.read::<Option<Id<B>>>()?;
+
+        let properties = Properties::load(&mut pager, header.properties.as_ref())?;
 
         let archive = Archive {
             pager,
             header_id: top_id,
             header,
             tree,
+            properties,
         };
 
         debug!("archive opened, header: {}", archive.header_id);
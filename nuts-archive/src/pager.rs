@@ -20,27 +20,63 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
+mod cache;
 #[cfg(test)]
 mod tests;
 
+use log::{debug, error};
 use nuts_backend::Backend;
 use nuts_bytes::{Reader, Writer};
-use nuts_container::Container;
+use nuts_container::{Container, LATEST_REVISION};
 use std::ops::Deref;
+use std::{cmp, mem};
 
 use crate::error::{ArchiveResult, Error};
 use crate::id::Id;
+use crate::pager::cache::{Block, BlockCache};
 
+/// Default number of blocks kept in the block cache.
+pub const DEFAULT_CACHE_SIZE: usize = 64;
+
+/// Access to the blocks of the container.
+///
+/// Blocks are kept in a bounded LRU cache. Writes are collected in the
+/// cache and are written back into the container when a dirty block is
+/// evicted, on [`Pager::flush()`] and on [`Pager::into_container()`]. Both
+/// report a failed write back. When the pager is dropped, the cache is
+/// flushed as a last resort, but errors can only be logged there.
+///
+/// Changes, which must be applied atomically, are wrapped into a
+/// [transaction](Pager::transaction). If a transaction spans several calls,
+/// it is controlled with [`Pager::begin()`], [`Pager::commit()`] and
+/// [`Pager::rollback()`].
+///
+/// If the container is opened read-only, a write is rejected before it
+/// reaches the cache, thus the cache never holds a dirty block.
 pub struct Pager<B: Backend> {
-    container: Container<B>,
+    container: Option<Container<B>>,
+    cache: BlockCache<B>,
     buf: Vec<u8>,
+    journaling: bool,
 }
 
 impl<B: Backend> Pager<B> {
     pub fn new(container: Container<B>) -> Pager<B> {
+        Self::with_cache_size(container, DEFAULT_CACHE_SIZE)
+    }
+
+    pub fn with_cache_size(container: Container<B>, size: usize) -> Pager<B> {
         let buf = vec![0; container.block_size() as usize];
 
-        Pager { container, buf }
+        // Transactions are only available for an up-to-date container
+        let journaling = matches!(container.info(), Ok(info) if info.revision == LATEST_REVISION);
+
+        Pager {
+            container: Some(container),
+            cache: BlockCache::new(size),
+            buf,
+            journaling,
+        }
     }
 
     pub fn create_reader(&self) -> Reader<&[u8]> {
@@ -59,7 +95,12 @@ impl<B: Backend> Pager<B> {
     }
 
     pub fn read_buf_raw(&mut self, id: &Id<B>) -> ArchiveResult<&[u8], B> {
-        let n = self.container.read(id.as_ref(), &mut self.buf)?;
+        let mut buf = mem::take(&mut self.buf);
+        let result = self.read(id, &mut buf);
+
+        self.buf = buf;
+
+        let n = result?;
 
         assert_eq!(n, self.buf.len());
 
@@ -67,42 +108,232 @@ impl<B: Backend> Pager<B> {
     }
 
     pub fn write_buf(&mut self, id: &Id<B>) -> ArchiveResult<(), B> {
-        self.container.write(id.as_ref(), &self.buf)?;
-        Ok(())
+        let buf = self.buf.clone();
+
+        self.put(id, buf, true)
     }
 
     pub fn aquire(&mut self) -> ArchiveResult<Id<B>, B> {
-        let id = self.container.aquire()?;
+        let id = self.container_mut().aquire()?;
 
         Ok(Id::new(id))
     }
 
+    pub fn release(&mut self, id: Id<B>) -> ArchiveResult<(), B> {
+        // Pending changes of a released block are not of interest anymore
+        self.cache.remove(&id);
+        self.container_mut().release(id.as_ref().clone())?;
+
+        Ok(())
+    }
+
     pub fn read(&mut self, id: &Id<B>, buf: &mut [u8]) -> ArchiveResult<usize, B> {
-        self.container
-            .read(id.as_ref(), buf)
-            .map_err(|err| err.into())
+        if let Some(cached) = self.cache.get(id) {
+            let n = cmp::min(cached.len(), buf.len());
+            buf[..n].copy_from_slice(&cached[..n]);
+
+            return Ok(n);
+        }
+
+        let mut block = vec![0; self.block_size() as usize];
+        self.container_mut().read(id.as_ref(), &mut block)?;
+
+        let n = cmp::min(block.len(), buf.len());
+        buf[..n].copy_from_slice(&block[..n]);
+
+        self.put(id, block, false)?;
+
+        Ok(n)
+    }
+
+    /// Reads several blocks at once.
+    ///
+    /// Blocks, which are not cached, are fetched from the container with a
+    /// single [`Container::read_many()`] call. They are not put into the
+    /// cache, the method is used to stream the content of an entry.
+    pub fn read_many(&mut self, ids: &[Id<B>]) -> ArchiveResult<Vec<Vec<u8>>, B> {
+        let mut blocks = vec![vec![]; ids.len()];
+        let mut missing = vec![];
+
+        for (idx, id) in ids.iter().enumerate() {
+            match self.cache.get(id) {
+                Some(cached) => blocks[idx] = cached.to_vec(),
+                None => missing.push(idx),
+            }
+        }
+
+        if !missing.is_empty() {
+            let missing_ids: Vec<B::Id> = missing
+                .iter()
+                .map(|idx| ids[*idx].as_ref().clone())
+                .collect();
+            let fetched = self.container_mut().read_many(&missing_ids)?;
+
+            for (idx, block) in missing.into_iter().zip(fetched) {
+                blocks[idx] = block;
+            }
+        }
+
+        Ok(blocks)
     }
 
     pub fn write(&mut self, id: &Id<B>, buf: &[u8]) -> ArchiveResult<usize, B> {
-        self.container
-            .write(id.as_ref(), buf)
-            .map_err(|err| err.into())
+        // Same semantic as Container::write(): pad with zeros, truncate if
+        // the buffer is larger than a block
+        let mut block = vec![0; self.block_size() as usize];
+        let n = cmp::min(block.len(), buf.len());
+
+        block[..n].copy_from_slice(&buf[..n]);
+
+        self.put(id, block, true)?;
+
+        Ok(n)
+    }
+
+    /// Writes all dirty blocks of the cache back into the container and
+    /// [flushes](Container::flush) the container.
+    pub fn flush(&mut self) -> ArchiveResult<(), B> {
+        if self.container.is_none() {
+            return Ok(());
+        }
+
+        self.write_back_dirty()?;
+
+        Ok(self.container_mut().flush()?)
+    }
+
+    /// Runs `f` in a transaction of the container.
+    ///
+    /// The changes made by `f` are applied atomically: If `f` succeeds, the
+    /// changes are committed, otherwise the transaction is rolled back. A
+    /// transaction started inside `f` joins the already running transaction.
+    ///
+    /// If the container does not support transactions (its header is not
+    /// up-to-date), `f` is simply executed.
+    pub fn transaction<T, F: FnOnce(&mut Pager<B>) -> ArchiveResult<T, B>>(
+        &mut self,
+        f: F,
+    ) -> ArchiveResult<T, B> {
+        if !self.begin()? {
+            return f(self);
+        }
+
+        match f(self).and_then(|value| self.commit().map(|()| value)) {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                self.rollback();
+                Err(err)
+            }
+        }
+    }
+
+    /// Starts a transaction of the container.
+    ///
+    /// Pending changes of the cache are written before, they are not part of
+    /// the transaction. Returns `false` and starts nothing, if the container
+    /// does not support transactions or a transaction is already running.
+    /// The transaction is finished with [`Pager::commit()`] or
+    /// [`Pager::rollback()`].
+    pub fn begin(&mut self) -> ArchiveResult<bool, B> {
+        if !self.journaling || self.container().in_transaction() {
+            return Ok(false);
+        }
+
+        self.flush()?;
+        self.container_mut().begin()?;
+
+        Ok(true)
+    }
+
+    /// Writes back the dirty blocks of the cache and commits the running
+    /// transaction.
+    ///
+    /// If the blocks cannot be written back, the transaction is still
+    /// running and should be [rolled back](Pager::rollback).
+    pub fn commit(&mut self) -> ArchiveResult<(), B> {
+        self.write_back_dirty()?;
+
+        Ok(self.container_mut().commit()?)
+    }
+
+    /// Rolls back the running transaction.
+    ///
+    /// Errors are logged, the transaction is closed anyway.
+    pub fn rollback(&mut self) {
+        // The cache might contain blocks modified by the transaction
+        self.cache.clear();
+
+        if !self.container().in_transaction() {
+            return;
+        }
+
+        if let Err(cause) = self.container_mut().rollback() {
+            error!("failed to rollback the transaction: {}", cause);
+        }
     }
 
     pub fn top_id(&mut self) -> Option<Id<B>> {
-        self.container.top_id().map(|id| Id::new(id.clone()))
+        self.container().top_id().map(|id| Id::new(id.clone()))
     }
 
     pub fn top_id_or_err(&mut self) -> ArchiveResult<Id<B>, B> {
         self.top_id().ok_or(Error::NoTopId)
     }
 
+    fn put(&mut self, id: &Id<B>, buf: Vec<u8>, dirty: bool) -> ArchiveResult<(), B> {
+        if dirty && self.container().is_read_only() {
+            return Err(nuts_container::Error::ReadOnly.into());
+        }
+
+        if let Some(block) = self.cache.insert(id, buf, dirty) {
+            self.write_back(block)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_back_dirty(&mut self) -> ArchiveResult<(), B> {
+        let container = self.container.as_mut().unwrap();
+        let mut n = 0;
+
+        for block in self.cache.dirty() {
+            container.write(block.id.as_ref(), &block.buf)?;
+            block.dirty = false;
+            n += 1;
+        }
+
+        debug!("flush: {} blocks written", n);
+
+        Ok(())
+    }
+
+    fn write_back(&mut self, block: Block<B>) -> ArchiveResult<(), B> {
+        if block.dirty {
+            self.container_mut().write(block.id.as_ref(), &block.buf)?;
+        }
+
+        Ok(())
+    }
+
     fn whiteout(&mut self) {
         self.buf.iter_mut().for_each(|n| *n = 0)
     }
 
-    pub fn into_container(self) -> Container<B> {
-        self.container
+    fn container(&self) -> &Container<B> {
+        self.container.as_ref().unwrap()
+    }
+
+    fn container_mut(&mut self) -> &mut Container<B> {
+        self.container.as_mut().unwrap()
+    }
+
+    /// Writes back all dirty blocks and returns the container.
+    ///
+    /// Fails if the dirty blocks cannot be written back.
+    pub fn into_container(mut self) -> ArchiveResult<Container<B>, B> {
+        self.flush()?;
+
+        Ok(self.container.take().unwrap())
     }
 }
 
@@ -110,6 +341,15 @@ impl<B: Backend> Deref for Pager<B> {
     type Target = Container<B>;
 
     fn deref(&self) -> &Container<B> {
-        &self.container
+        self.container()
+    }
+}
+
+impl<B: Backend> Drop for Pager<B> {
+    fn drop(&mut self) {
+        // last resort, the cache is usually flushed before
+        if let Err(err) = self.flush() {
+            error!("failed to flush the block cache: {}", err);
+        }
     }
 }
@@ -0,0 +1,140 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use nuts_backend::Backend;
+use std::collections::VecDeque;
+
+use crate::id::Id;
+
+#[derive(Debug)]
+pub struct Block<B: Backend> {
+    pub id: Id<B>,
+    pub buf: Vec<u8>,
+    pub dirty: bool,
+}
+
+/// A bounded LRU cache of plaintext blocks.
+///
+/// The most recently used block is located at the front of the queue. If
+/// the cache is full, the least recently used block is evicted from the
+/// back of the queue. Evicted blocks, which are still dirty, must be written
+/// back by the caller.
+#[derive(Debug)]
+pub struct BlockCache<B: Backend> {
+    capacity: usize,
+    blocks: VecDeque<Block<B>>,
+}
+
+impl<B: Backend> BlockCache<B> {
+    pub fn new(capacity: usize) -> BlockCache<B> {
+        BlockCache {
+            capacity,
+            blocks: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Returns the cached block with the given `id` and marks it as most
+    /// recently used.
+    pub fn get(&mut self, id: &Id<B>) -> Option<&[u8]> {
+        let idx = self.position(id)?;
+
+        self.touch(idx);
+
+        self.blocks.front().map(|block| block.buf.as_slice())
+    }
+
+    /// Puts a block into the cache.
+    ///
+    /// An already cached block is replaced but keeps its dirty state. If the
+    /// cache exceeds its capacity, the least recently used block is evicted
+    /// and returned.
+    pub fn insert(&mut self, id: &Id<B>, buf: Vec<u8>, dirty: bool) -> Option<Block<B>> {
+        if self.capacity == 0 {
+            return Some(Block {
+                id: id.clone(),
+                buf,
+                dirty,
+            });
+        }
+
+        match self.position(id) {
+            Some(idx) => {
+                self.touch(idx);
+
+                if let Some(block) = self.blocks.front_mut() {
+                    block.buf = buf;
+                    block.dirty |= dirty;
+                }
+
+                None
+            }
+            None => {
+                self.blocks.push_front(Block {
+                    id: id.clone(),
+                    buf,
+                    dirty,
+                });
+
+                if self.blocks.len() > self.capacity {
+                    self.blocks.pop_back()
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Removes the block with the given `id` from the cache.
+    pub fn remove(&mut self, id: &Id<B>) -> Option<Block<B>> {
+        self.position(id).and_then(|idx| self.blocks.remove(idx))
+    }
+
+    /// Removes all blocks from the cache.
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+    }
+
+    /// Returns all dirty blocks, least recently used first.
+    pub fn dirty(&mut self) -> impl Iterator<Item = &mut Block<B>> {
+        self.blocks.iter_mut().rev().filter(|block| block.dirty)
+    }
+
+    fn position(&self, id: &Id<B>) -> Option<usize> {
+        self.blocks.iter().position(|block| &block.id == id)
+    }
+
+    fn touch(&mut self, idx: usize) {
+        if idx > 0 {
+            if let Some(block) = self.blocks.remove(idx) {
+                self.blocks.push_front(block);
+            }
+        }
+    }
+}
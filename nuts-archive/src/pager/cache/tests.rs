@@ -0,0 +1,143 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_memory::MemoryBackend;
+
+use crate::pager::cache::BlockCache;
+
+macro_rules! _id {
+    ($id:expr) => {
+        $id.parse::<crate::id::Id<nuts_memory::MemoryBackend>>()
+            .unwrap()
+    };
+}
+
+#[test]
+fn get_empty() {
+    let mut cache = BlockCache::<MemoryBackend>::new(2);
+
+    assert!(cache.get(&_id!("1")).is_none());
+}
+
+#[test]
+fn insert_get() {
+    let mut cache = BlockCache::<MemoryBackend>::new(2);
+
+    assert!(cache.insert(&_id!("1"), vec![1], false).is_none());
+    assert!(cache.insert(&_id!("2"), vec![2], true).is_none());
+
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&_id!("1")).unwrap(), [1]);
+    assert_eq!(cache.get(&_id!("2")).unwrap(), [2]);
+    assert!(cache.get(&_id!("3")).is_none());
+}
+
+#[test]
+fn insert_replace() {
+    let mut cache = BlockCache::<MemoryBackend>::new(2);
+
+    assert!(cache.insert(&_id!("1"), vec![1], true).is_none());
+    assert!(cache.insert(&_id!("1"), vec![2], false).is_none());
+
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.get(&_id!("1")).unwrap(), [2]);
+
+    // still dirty
+    let dirty: Vec<_> = cache.dirty().map(|block| block.id.clone()).collect();
+    assert_eq!(dirty, [_id!("1")]);
+}
+
+#[test]
+fn evict_lru() {
+    let mut cache = BlockCache::<MemoryBackend>::new(2);
+
+    assert!(cache.insert(&_id!("1"), vec![1], true).is_none());
+    assert!(cache.insert(&_id!("2"), vec![2], false).is_none());
+
+    // "1" becomes the most recently used block
+    cache.get(&_id!("1")).unwrap();
+
+    let block = cache.insert(&_id!("3"), vec![3], false).unwrap();
+    assert_eq!(block.id, _id!("2"));
+    assert_eq!(block.buf, [2]);
+    assert!(!block.dirty);
+
+    let block = cache.insert(&_id!("4"), vec![4], false).unwrap();
+    assert_eq!(block.id, _id!("1"));
+    assert_eq!(block.buf, [1]);
+    assert!(block.dirty);
+
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn zero_capacity() {
+    let mut cache = BlockCache::<MemoryBackend>::new(0);
+
+    let block = cache.insert(&_id!("1"), vec![1], true).unwrap();
+    assert_eq!(block.id, _id!("1"));
+    assert!(block.dirty);
+
+    assert_eq!(cache.len(), 0);
+    assert!(cache.get(&_id!("1")).is_none());
+}
+
+#[test]
+fn remove() {
+    let mut cache = BlockCache::<MemoryBackend>::new(2);
+
+    cache.insert(&_id!("1"), vec![1], true);
+    cache.insert(&_id!("2"), vec![2], true);
+
+    assert_eq!(cache.remove(&_id!("1")).unwrap().buf, [1]);
+    assert!(cache.remove(&_id!("1")).is_none());
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn clear() {
+    let mut cache = BlockCache::<MemoryBackend>::new(2);
+
+    cache.insert(&_id!("1"), vec![1], true);
+    cache.insert(&_id!("2"), vec![2], false);
+
+    cache.clear();
+
+    assert_eq!(cache.len(), 0);
+    assert!(cache.get(&_id!("1")).is_none());
+    assert_eq!(cache.dirty().count(), 0);
+}
+
+#[test]
+fn dirty() {
+    let mut cache = BlockCache::<MemoryBackend>::new(3);
+
+    cache.insert(&_id!("1"), vec![1], true);
+    cache.insert(&_id!("2"), vec![2], false);
+    cache.insert(&_id!("3"), vec![3], true);
+
+    let dirty: Vec<_> = cache.dirty().map(|block| block.id.clone()).collect();
+    assert_eq!(dirty, [_id!("1"), _id!("3")]);
+
+    cache.dirty().for_each(|block| block.dirty = false);
+    assert_eq!(cache.dirty().count(), 0);
+}
@@ -20,6 +20,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
+use crate::error::Error;
 use crate::id::Id;
 use crate::pager::Pager;
 use crate::tests::setup_container_with_bsize;
@@ -69,3 +70,283 @@ fn write() {
     assert_eq!(pager.read(&id, &mut buf).unwrap(), 12);
     assert_eq!(buf, [0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3]);
 }
+
+#[test]
+fn write_back() {
+    let mut pager = Pager::new(setup_container_with_bsize(12));
+    let id = pager.aquire().unwrap();
+    let mut buf = [0; 12];
+
+    assert_eq!(pager.write(&id, &[1, 2, 3]).unwrap(), 3);
+
+    // cached, not yet written into the container
+    let container = pager.container.as_mut().unwrap();
+    assert_eq!(container.read(id.as_ref(), &mut buf).unwrap(), 12);
+    assert_eq!(buf, [0; 12]);
+
+    assert_eq!(pager.read(&id, &mut buf).unwrap(), 12);
+    assert_eq!(buf, [1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+    pager.flush().unwrap();
+
+    let container = pager.container.as_mut().unwrap();
+    assert_eq!(container.read(id.as_ref(), &mut buf).unwrap(), 12);
+    assert_eq!(buf, [1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn write_back_evict() {
+    let mut pager = Pager::with_cache_size(setup_container_with_bsize(12), 1);
+    let id1 = pager.aquire().unwrap();
+    let id2 = pager.aquire().unwrap();
+    let mut buf = [0; 12];
+
+    pager.write(&id1, &[1]).unwrap();
+    pager.write(&id2, &[2]).unwrap();
+
+    // id1 was evicted and written back
+    let container = pager.container.as_mut().unwrap();
+    container.read(id1.as_ref(), &mut buf).unwrap();
+    assert_eq!(buf[0], 1);
+    container.read(id2.as_ref(), &mut buf).unwrap();
+    assert_eq!(buf[0], 0);
+}
+
+#[test]
+fn write_back_into_container() {
+    let mut pager = Pager::new(setup_container_with_bsize(12));
+    let id = pager.aquire().unwrap();
+    let mut buf = [0; 12];
+
+    pager.write(&id, &[1, 2, 3]).unwrap();
+
+    let mut container = pager.into_container().unwrap();
+    container.read(id.as_ref(), &mut buf).unwrap();
+    assert_eq!(buf, [1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn write_back_into_container_error() {
+    let mut pager = Pager::new(setup_container_with_bsize(12));
+    let id = pager.aquire().unwrap();
+
+    pager.write(&id, &[1, 2, 3]).unwrap();
+
+    // released behind the back of the pager, cannot be written back
+    let container = pager.container.as_mut().unwrap();
+    container.release(*id.as_ref()).unwrap();
+
+    pager.into_container().unwrap_err();
+}
+
+#[test]
+fn read_cached() {
+    let mut pager = Pager::new(setup_container_with_bsize(12));
+    let id = pager.aquire().unwrap();
+    let mut buf = [0; 4];
+
+    pager
+        .container
+        .as_mut()
+        .unwrap()
+        .write(id.as_ref(), &[1, 2, 3, 4, 5])
+        .unwrap();
+
+    assert_eq!(pager.read(&id, &mut buf).unwrap(), 4);
+    assert_eq!(buf, [1, 2, 3, 4]);
+
+    // modified behind the back of the pager, cached block is returned
+    pager
+        .container
+        .as_mut()
+        .unwrap()
+        .write(id.as_ref(), &[6, 7, 8, 9])
+        .unwrap();
+
+    assert_eq!(pager.read(&id, &mut buf).unwrap(), 4);
+    assert_eq!(buf, [1, 2, 3, 4]);
+}
+
+#[test]
+fn read_many() {
+    let mut pager = Pager::new(setup_container_with_bsize(12));
+    let id1 = pager.aquire().unwrap();
+    let id2 = pager.aquire().unwrap();
+
+    pager
+        .container
+        .as_mut()
+        .unwrap()
+        .write(id1.as_ref(), &[1, 2, 3])
+        .unwrap();
+
+    // dirty, only available in the cache
+    assert_eq!(pager.write(&id2, &[4, 5, 6]).unwrap(), 3);
+
+    let blocks = pager.read_many(&[id1.clone(), id2.clone(), id1]).unwrap();
+
+    assert_eq!(
+        blocks,
+        [
+            [1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            [4, 5, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            [1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        ]
+    );
+}
+
+#[test]
+fn release_dirty() {
+    let mut pager = Pager::new(setup_container_with_bsize(12));
+    let id = pager.aquire().unwrap();
+
+    pager.write(&id, &[1, 2, 3]).unwrap();
+    pager.release(id.clone()).unwrap();
+
+    // nothing to write back for the released block
+    pager.flush().unwrap();
+    assert!(pager.cache.get(&id).is_none());
+}
+
+#[test]
+fn transaction_commit() {
+    let mut pager = Pager::new(setup_container_with_bsize(512));
+    let id = pager.aquire().unwrap();
+    let mut buf = [0; 3];
+
+    let n = pager
+        .transaction(|pager| {
+            assert!(pager.in_transaction());
+            pager.write(&id, &[1, 2, 3])
+        })
+        .unwrap();
+
+    assert_eq!(n, 3);
+    assert!(!pager.in_transaction());
+
+    // committed into the container
+    let container = pager.container.as_mut().unwrap();
+    container.read(id.as_ref(), &mut buf).unwrap();
+    assert_eq!(buf, [1, 2, 3]);
+}
+
+#[test]
+fn transaction_rollback() {
+    let mut pager = Pager::new(setup_container_with_bsize(512));
+    let id = pager.aquire().unwrap();
+    let mut buf = [0; 3];
+
+    pager.write(&id, &[1, 2, 3]).unwrap();
+
+    let err = pager
+        .transaction(|pager| {
+            pager.write(&id, &[4, 5, 6])?;
+            pager.flush()?;
+            Err::<(), _>(Error::NoTopId)
+        })
+        .unwrap_err();
+
+    assert!(matches!(err, Error::NoTopId));
+    assert!(!pager.in_transaction());
+
+    // changes made before the transaction are kept
+    pager.read(&id, &mut buf).unwrap();
+    assert_eq!(buf, [1, 2, 3]);
+
+    let container = pager.container.as_mut().unwrap();
+    container.read(id.as_ref(), &mut buf).unwrap();
+    assert_eq!(buf, [1, 2, 3]);
+}
+
+#[test]
+fn transaction_rollback_aquired() {
+    let mut pager = Pager::new(setup_container_with_bsize(512));
+    let mut aquired = None;
+    let mut buf = [0; 1];
+
+    pager
+        .transaction(|pager| {
+            aquired = Some(pager.aquire()?);
+            Err::<(), _>(Error::NoTopId)
+        })
+        .unwrap_err();
+
+    // the block is released again
+    let id = aquired.unwrap();
+    let container = pager.container.as_mut().unwrap();
+    container.read(id.as_ref(), &mut buf).unwrap_err();
+}
+
+#[test]
+fn transaction_nested() {
+    let mut pager = Pager::new(setup_container_with_bsize(512));
+    let id1 = pager.aquire().unwrap();
+    let id2 = pager.aquire().unwrap();
+    let mut buf = [0; 1];
+
+    pager
+        .transaction(|pager| {
+            pager.write(&id1, &[1])?;
+            pager.transaction(|pager| pager.write(&id2, &[2]))?;
+
+            // still the outer transaction
+            assert!(pager.in_transaction());
+
+            Ok(())
+        })
+        .unwrap();
+
+    assert!(!pager.in_transaction());
+
+    let container = pager.container.as_mut().unwrap();
+    container.read(id1.as_ref(), &mut buf).unwrap();
+    assert_eq!(buf, [1]);
+    container.read(id2.as_ref(), &mut buf).unwrap();
+    assert_eq!(buf, [2]);
+}
+
+#[test]
+fn begin_commit() {
+    let mut pager = Pager::new(setup_container_with_bsize(512));
+    let id = pager.aquire().unwrap();
+    let mut buf = [0; 3];
+
+    assert!(pager.begin().unwrap());
+    assert!(pager.in_transaction());
+
+    // already running
+    assert!(!pager.begin().unwrap());
+
+    pager.write(&id, &[1, 2, 3]).unwrap();
+    pager.commit().unwrap();
+    assert!(!pager.in_transaction());
+
+    let container = pager.container.as_mut().unwrap();
+    container.read(id.as_ref(), &mut buf).unwrap();
+    assert_eq!(buf, [1, 2, 3]);
+}
+
+#[test]
+fn begin_rollback() {
+    let mut pager = Pager::new(setup_container_with_bsize(512));
+    let id = pager.aquire().unwrap();
+    let mut buf = [0; 3];
+
+    pager.write(&id, &[1, 2, 3]).unwrap();
+
+    assert!(pager.begin().unwrap());
+    pager.write(&id, &[4, 5, 6]).unwrap();
+    pager.rollback();
+    assert!(!pager.in_transaction());
+
+    pager.read(&id, &mut buf).unwrap();
+    assert_eq!(buf, [1, 2, 3]);
+}
+
+#[test]
+fn rollback_without_transaction() {
+    let mut pager = Pager::new(setup_container_with_bsize(512));
+
+    pager.rollback();
+    assert!(!pager.in_transaction());
+}
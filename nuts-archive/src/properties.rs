@@ -0,0 +1,171 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use log::debug;
+use nuts_backend::{Backend, IdSize};
+use nuts_bytes::{FromBytes, ToBytes, Writer};
+use std::collections::BTreeMap;
+
+use crate::error::{ArchiveResult, Error};
+use crate::id::Id;
+use crate::pager::Pager;
+
+#[derive(Debug, FromBytes, ToBytes)]
+struct Property {
+    key: String,
+    value: String,
+}
+
+/// A block with properties. If the properties do not fit into a single
+/// block, the blocks are chained together.
+#[derive(Debug, FromBytes, ToBytes)]
+struct Node<B: Backend> {
+    properties: Vec<Property>,
+    next: Option<Id<B>>,
+}
+
+impl<B: Backend> Node<B> {
+    /// Calculates the number of bytes needed to store the given properties
+    /// into a node, which links to another node.
+    fn size_of(properties: &[Property]) -> ArchiveResult<usize, B> {
+        let mut writer = Writer::new(vec![]);
+        let n = writer.write(&properties)?;
+
+        Ok(n + 1 + <B::Id as IdSize>::size())
+    }
+}
+
+/// The key/value properties of the archive.
+///
+/// The properties are kept in memory and stored in a chain of blocks. The
+/// head of the chain is stored in the header of the archive.
+pub struct Properties<B: Backend> {
+    map: BTreeMap<String, String>,
+    ids: Vec<Id<B>>,
+}
+
+impl<B: Backend> Properties<B> {
+    pub fn new() -> Properties<B> {
+        Properties {
+            map: BTreeMap::new(),
+            ids: vec![],
+        }
+    }
+
+    pub fn load(pager: &mut Pager<B>, head: Option<&Id<B>>) -> ArchiveResult<Properties<B>, B> {
+        let mut props = Properties::new();
+        let mut next = head.cloned();
+
+        while let Some(id) = next {
+            let node = pager.read_buf(&id)?.read::<Node<B>>()?;
+
+            for property in node.properties {
+                props.map.insert(property.key, property.value);
+            }
+
+            next = node.next;
+            props.ids.push(id);
+        }
+
+        debug!(
+            "{} properties loaded from {} blocks",
+            props.map.len(),
+            props.ids.len()
+        );
+
+        Ok(props)
+    }
+
+    pub fn map(&self) -> &BTreeMap<String, String> {
+        &self.map
+    }
+
+    pub fn insert(&mut self, key: String, value: String) -> Option<String> {
+        self.map.insert(key, value)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.map.remove(key)
+    }
+
+    /// Writes the properties into the archive.
+    ///
+    /// Blocks are aquired or released as needed. Returns the head of the
+    /// chain, which needs to be stored in the header.
+    pub fn flush(&mut self, pager: &mut Pager<B>) -> ArchiveResult<Option<Id<B>>, B> {
+        let block_size = pager.block_size() as usize;
+        let mut nodes: Vec<Vec<Property>> = vec![];
+
+        for (key, value) in self.map.iter() {
+            let property = Property {
+                key: key.clone(),
+                value: value.clone(),
+            };
+
+            if let Some(last) = nodes.last_mut() {
+                last.push(property);
+
+                if Node::<B>::size_of(last)? <= block_size {
+                    continue;
+                }
+
+                let property = last.pop().unwrap();
+                nodes.push(vec![property]);
+            } else {
+                nodes.push(vec![property]);
+            }
+
+            if Node::<B>::size_of(nodes.last().unwrap())? > block_size {
+                return Err(Error::InvalidProperty(key.clone()));
+            }
+        }
+
+        while self.ids.len() < nodes.len() {
+            self.ids.push(pager.aquire()?);
+        }
+
+        while self.ids.len() > nodes.len() {
+            let id = self.ids.pop().unwrap();
+            pager.release(id)?;
+        }
+
+        for (idx, properties) in nodes.into_iter().enumerate() {
+            let node = Node {
+                properties,
+                next: self.ids.get(idx + 1).cloned(),
+            };
+
+            let mut writer = pager.create_writer();
+            writer.write(&node)?;
+
+            pager.write_buf(&self.ids[idx])?;
+        }
+
+        debug!(
+            "{} properties written into {} blocks",
+            self.map.len(),
+            self.ids.len()
+        );
+
+        Ok(self.ids.first().cloned())
+    }
+}
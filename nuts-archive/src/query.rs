@@ -0,0 +1,296 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use chrono::{DateTime, Utc};
+use nuts_backend::Backend;
+use std::ops::{Bound, RangeBounds};
+
+use crate::error::ArchiveResult;
+use crate::Entry;
+
+type Range<T> = (Bound<T>, Bound<T>);
+
+fn unbounded<T>() -> Range<T> {
+    (Bound::Unbounded, Bound::Unbounded)
+}
+
+fn to_range<T: Clone, R: RangeBounds<T>>(range: R) -> Range<T> {
+    (range.start_bound().cloned(), range.end_bound().cloned())
+}
+
+/// Matches `name` against the glob `pattern`.
+///
+/// The pattern is matched from left to right. Only the position of the last
+/// `*` is remembered. If the pattern does not match, the `*` consumes one
+/// more character and matching continues behind it. Thus there is a single
+/// backtrack point and no exponential runtime for patterns with many `*`.
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut star = None;
+
+    while n < name.len() {
+        if pattern.get(p) == Some(&'*') {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some(len) = token_match(&pattern[p..], name[n]) {
+            p += len;
+            n += 1;
+        } else if let Some((star_p, star_n)) = star {
+            star = Some((star_p, star_n + 1));
+            p = star_p + 1;
+            n = star_n + 1;
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Matches `c` against the first token of `pattern`, which is not a `*`.
+///
+/// Returns the length of the token, if it matches.
+fn token_match(pattern: &[char], c: char) -> Option<usize> {
+    match pattern.split_first()? {
+        ('?', _) => Some(1),
+        ('[', rest) => match class_match(rest, Some(&c)) {
+            Some((true, rest)) => Some(pattern.len() - rest.len()),
+            Some((false, _)) => None,
+            None => (c == '[').then_some(1),
+        },
+        (p, _) => (*p == c).then_some(1),
+    }
+}
+
+/// Matches `c` against the character class at the beginning of `pattern`
+/// (without the opening bracket).
+///
+/// Returns the match result together with the remaining pattern behind the
+/// class. If the class is not terminated, [`None`] is returned and the
+/// bracket is treated as a literal character.
+fn class_match<'a>(pattern: &'a [char], c: Option<&char>) -> Option<(bool, &'a [char])> {
+    let (negate, pattern) = match pattern.split_first() {
+        Some(('!', rest)) | Some(('^', rest)) => (true, rest),
+        _ => (false, pattern),
+    };
+
+    // A closing bracket directly behind the opening bracket is part of the
+    // class.
+    let end = pattern
+        .iter()
+        .skip(1)
+        .position(|c| *c == ']')
+        .map(|n| n + 1)?;
+    let (class, rest) = (&pattern[..end], &pattern[end + 1..]);
+
+    let matched = match c {
+        Some(c) => {
+            let mut matched = false;
+            let mut idx = 0;
+
+            while idx < class.len() {
+                if idx + 2 < class.len() && class[idx + 1] == '-' {
+                    matched |= class[idx] <= *c && *c <= class[idx + 2];
+                    idx += 3;
+                } else {
+                    matched |= class[idx] == *c;
+                    idx += 1;
+                }
+            }
+
+            matched != negate
+        }
+        None => false,
+    };
+
+    Some((matched, rest))
+}
+
+/// The type of an [`Entry`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EntryType {
+    /// The entry is a file.
+    File,
+
+    /// The entry is a directory.
+    Directory,
+
+    /// The entry is a symlink.
+    Symlink,
+}
+
+/// A query over the entries of an archive.
+///
+/// Using the `with_*` methods you can add criteria to the query. An entry
+/// must match all criteria to be selected by the query. An empty query
+/// matches every entry of the archive.
+///
+/// The query is passed to [`Archive::find()`](crate::Archive::find) and
+/// [`Entry::find_next()`].
+///
+/// ```rust
+/// use chrono::{TimeZone, Utc};
+/// use nuts_archive::{EntryType, Query};
+///
+/// // files below etc/ modified since 2024
+/// let query = Query::new()
+///     .with_name("etc/*")
+///     .with_type(EntryType::File)
+///     .with_modified(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()..);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Query {
+    names: Vec<Vec<char>>,
+    types: Vec<EntryType>,
+    size: Range<u64>,
+    appended: Range<DateTime<Utc>>,
+    created: Range<DateTime<Utc>>,
+    changed: Range<DateTime<Utc>>,
+    modified: Range<DateTime<Utc>>,
+}
+
+impl Query {
+    /// Creates a new query, which matches every entry.
+    pub fn new() -> Query {
+        Query {
+            names: vec![],
+            types: vec![],
+            size: unbounded(),
+            appended: unbounded(),
+            created: unbounded(),
+            changed: unbounded(),
+            modified: unbounded(),
+        }
+    }
+
+    /// Selects entries, which names are matched by the given glob `pattern`.
+    ///
+    /// The pattern supports the wildcards `*` (any sequence of characters,
+    /// including `/`), `?` (any single character) and character classes like
+    /// `[abc]`, `[a-z]` or `[!abc]`.
+    ///
+    /// If called several times, an entry must match at least one of the
+    /// patterns.
+    pub fn with_name<P: AsRef<str>>(mut self, pattern: P) -> Self {
+        self.names.push(pattern.as_ref().chars().collect());
+        self
+    }
+
+    /// Selects entries of the given type.
+    ///
+    /// If called several times, an entry must match at least one of the
+    /// types.
+    pub fn with_type(mut self, entry_type: EntryType) -> Self {
+        self.types.push(entry_type);
+        self
+    }
+
+    /// Selects entries, which size is in the given `range`.
+    pub fn with_size<R: RangeBounds<u64>>(mut self, range: R) -> Self {
+        self.size = to_range(range);
+        self
+    }
+
+    /// Selects entries, which were appended to the archive in the given
+    /// `range`.
+    pub fn with_appended<R: RangeBounds<DateTime<Utc>>>(mut self, range: R) -> Self {
+        self.appended = to_range(range);
+        self
+    }
+
+    /// Selects entries, which creation time is in the given `range`.
+    pub fn with_created<R: RangeBounds<DateTime<Utc>>>(mut self, range: R) -> Self {
+        self.created = to_range(range);
+        self
+    }
+
+    /// Selects entries, which changed time is in the given `range`.
+    pub fn with_changed<R: RangeBounds<DateTime<Utc>>>(mut self, range: R) -> Self {
+        self.changed = to_range(range);
+        self
+    }
+
+    /// Selects entries, which modification time is in the given `range`.
+    pub fn with_modified<R: RangeBounds<DateTime<Utc>>>(mut self, range: R) -> Self {
+        self.modified = to_range(range);
+        self
+    }
+
+    /// Tests whether the given `entry` is selected by the query.
+    pub fn matches<B: Backend>(&self, entry: &Entry<B>) -> bool {
+        let entry_type = if entry.is_file() {
+            EntryType::File
+        } else if entry.is_directory() {
+            EntryType::Directory
+        } else {
+            EntryType::Symlink
+        };
+
+        self.match_name(entry.name())
+            && (self.types.is_empty() || self.types.contains(&entry_type))
+            && self.size.contains(&entry.size())
+            && self.appended.contains(entry.appended())
+            && self.created.contains(entry.created())
+            && self.changed.contains(entry.changed())
+            && self.modified.contains(entry.modified())
+    }
+
+    fn match_name(&self, name: &str) -> bool {
+        if self.names.is_empty() {
+            return true;
+        }
+
+        let name = name.chars().collect::<Vec<char>>();
+
+        self.names.iter().any(|pattern| glob_match(pattern, &name))
+    }
+}
+
+impl Default for Query {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn find_entry<'a, B: Backend>(
+    mut entry_opt: Option<ArchiveResult<Entry<'a, B>, B>>,
+    query: &Query,
+) -> Option<ArchiveResult<Entry<'a, B>, B>> {
+    loop {
+        match entry_opt {
+            Some(Ok(entry)) => {
+                if query.matches(&entry) {
+                    return Some(Ok(entry));
+                }
+
+                entry_opt = entry.next();
+            }
+            Some(Err(err)) => return Some(Err(err)),
+            None => break,
+        }
+    }
+
+    None
+}
@@ -0,0 +1,253 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use chrono::{TimeZone, Utc};
+
+use crate::query::{glob_match, EntryType, Query};
+use crate::tests::setup_archive_with_bsize;
+
+fn glob(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let name = name.chars().collect::<Vec<char>>();
+
+    glob_match(&pattern, &name)
+}
+
+#[test]
+fn glob_literal() {
+    assert!(glob("", ""));
+    assert!(glob("abc", "abc"));
+    assert!(!glob("abc", "ab"));
+    assert!(!glob("abc", "abcd"));
+    assert!(!glob("", "a"));
+}
+
+#[test]
+fn glob_star() {
+    assert!(glob("*", ""));
+    assert!(glob("*", "abc"));
+    assert!(glob("etc/*", "etc/passwd"));
+    assert!(glob("etc/*", "etc/ssh/sshd_config"));
+    assert!(glob("*.txt", "a/b.txt"));
+    assert!(glob("a*b*c", "aXbYc"));
+    assert!(!glob("etc/*", "usr/etc/passwd"));
+    assert!(!glob("*.txt", "a.txt.bak"));
+}
+
+#[test]
+fn glob_question() {
+    assert!(glob("?", "a"));
+    assert!(glob("a?c", "abc"));
+    assert!(!glob("?", ""));
+    assert!(!glob("a?c", "ac"));
+}
+
+#[test]
+fn glob_class() {
+    assert!(glob("[abc]", "b"));
+    assert!(glob("[a-c]x", "cx"));
+    assert!(glob("[!a-c]", "d"));
+    assert!(glob("[^a-c]", "d"));
+    assert!(glob("[]]", "]"));
+    assert!(!glob("[abc]", "d"));
+    assert!(!glob("[!a-c]", "b"));
+    assert!(!glob("[abc]", ""));
+}
+
+#[test]
+fn glob_pathological() {
+    let name = "a".repeat(100);
+
+    // exponential runtime with a backtracking matcher
+    assert!(!glob("*a*a*a*a*a*a*a*a*a*a*b", &name));
+    assert!(glob("*a*a*a*a*a*a*a*a*a*a*", &name));
+    assert!(glob("*a*a*a*a*a*a*a*a*a*a", &name));
+}
+
+#[test]
+fn glob_star_backtrack() {
+    assert!(glob("*ab", "aab"));
+    assert!(glob("a*b*c", "abcbc"));
+    assert!(glob("*[0-9].txt", "a1b2.txt"));
+    assert!(glob("**", "abc"));
+    assert!(!glob("*ab", "aba"));
+    assert!(!glob("a*", ""));
+}
+
+#[test]
+fn glob_unterminated_class() {
+    assert!(glob("[ab", "[ab"));
+    assert!(!glob("[ab", "a"));
+}
+
+#[test]
+fn empty_query() {
+    let mut archive = setup_archive_with_bsize(512);
+
+    archive.append_file("f1").build().unwrap();
+    archive.append_directory("d1").build().unwrap();
+
+    let entry = archive.find(&Query::new()).unwrap().unwrap();
+    assert_eq!(entry.name(), "f1");
+
+    let entry = entry.find_next(&Query::new()).unwrap().unwrap();
+    assert_eq!(entry.name(), "d1");
+
+    assert!(entry.find_next(&Query::new()).is_none());
+}
+
+#[test]
+fn name() {
+    let mut archive = setup_archive_with_bsize(512);
+
+    for name in ["etc/passwd", "usr/bin/ls", "etc/ssh/sshd_config", "etc"] {
+        archive.append_file(name).build().unwrap();
+    }
+
+    let query = Query::new().with_name("etc/*");
+
+    let entry = archive.find(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "etc/passwd");
+
+    let entry = entry.find_next(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "etc/ssh/sshd_config");
+
+    assert!(entry.find_next(&query).is_none());
+
+    let query = Query::new().with_name("etc").with_name("*/ls");
+
+    let entry = archive.find(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "usr/bin/ls");
+
+    let entry = entry.find_next(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "etc");
+}
+
+#[test]
+fn entry_type() {
+    let mut archive = setup_archive_with_bsize(512);
+
+    archive.append_file("f1").build().unwrap();
+    archive.append_directory("d1").build().unwrap();
+    archive.append_symlink("s1", "f1").build().unwrap();
+
+    for (entry_type, name) in [
+        (EntryType::File, "f1"),
+        (EntryType::Directory, "d1"),
+        (EntryType::Symlink, "s1"),
+    ] {
+        let query = Query::new().with_type(entry_type);
+        let entry = archive.find(&query).unwrap().unwrap();
+
+        assert_eq!(entry.name(), name);
+        assert!(entry.find_next(&query).is_none());
+    }
+
+    let query = Query::new()
+        .with_type(EntryType::Directory)
+        .with_type(EntryType::Symlink);
+    let entry = archive.find(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "d1");
+}
+
+#[test]
+fn size() {
+    let mut archive = setup_archive_with_bsize(512);
+
+    for (name, size) in [("f1", 10), ("f2", 100), ("f3", 1000)] {
+        let mut entry = archive.append_file(name).build().unwrap();
+        entry.write_all(&vec![0; size]).unwrap();
+    }
+
+    let query = Query::new().with_size(11..1000);
+    let entry = archive.find(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "f2");
+    assert!(entry.find_next(&query).is_none());
+
+    let query = Query::new().with_size(100..);
+    let entry = archive.find(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "f2");
+    let entry = entry.find_next(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "f3");
+
+    let query = Query::new().with_size(..=10);
+    let entry = archive.find(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "f1");
+    assert!(entry.find_next(&query).is_none());
+}
+
+#[test]
+fn tstamps() {
+    let mut archive = setup_archive_with_bsize(512);
+    let t = |secs| Utc.timestamp_opt(secs, 0).unwrap();
+
+    for (name, secs) in [("f1", 10), ("f2", 20), ("f3", 30)] {
+        let mut builder = archive.append_file(name);
+
+        builder.set_created(t(secs));
+        builder.set_changed(t(secs + 1));
+        builder.set_modified(t(secs + 2));
+        builder.build().unwrap();
+    }
+
+    let query = Query::new().with_created(t(20)..);
+    let entry = archive.find(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "f2");
+
+    let query = Query::new().with_changed(..t(21));
+    let entry = archive.find(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "f1");
+    assert!(entry.find_next(&query).is_none());
+
+    let query = Query::new().with_modified(t(12)..=t(22));
+    let entry = archive.find(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "f1");
+    let entry = entry.find_next(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "f2");
+    assert!(entry.find_next(&query).is_none());
+
+    let query = Query::new().with_appended(..t(0));
+    assert!(archive.find(&query).is_none());
+}
+
+#[test]
+fn combined() {
+    let mut archive = setup_archive_with_bsize(512);
+
+    archive.append_directory("etc").build().unwrap();
+
+    {
+        let mut entry = archive.append_file("etc/passwd").build().unwrap();
+        entry.write_all(b"root").unwrap();
+    }
+
+    archive.append_file("etc/group").build().unwrap();
+
+    let query = Query::new()
+        .with_name("etc*")
+        .with_type(EntryType::File)
+        .with_size(1..);
+
+    let entry = archive.find(&query).unwrap().unwrap();
+    assert_eq!(entry.name(), "etc/passwd");
+    assert!(entry.find_next(&query).is_none());
+}
@@ -0,0 +1,203 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use chrono::{DateTime, Utc};
+use log::debug;
+use nuts_backend::Backend;
+use nuts_bytes::{FromBytes, ToBytes};
+use std::convert::TryInto;
+
+use crate::entry::immut::InnerEntry;
+use crate::error::ArchiveResult;
+use crate::header::Header;
+use crate::id::Id;
+use crate::pager::Pager;
+use crate::query::{find_entry, Query};
+use crate::tree::Tree;
+use crate::{datetime, lookup_entry, Entry};
+
+/// Information about a snapshot of the archive.
+#[derive(Debug)]
+pub struct SnapshotInfo {
+    /// The name of the snapshot
+    pub name: String,
+
+    /// Time when the snapshot was taken
+    pub created: DateTime<Utc>,
+
+    /// Number of blocks referenced by the snapshot
+    pub blocks: u64,
+
+    /// Number of files captured by the snapshot
+    pub files: u64,
+}
+
+/// A snapshot node is stored in a block of its own. All snapshots of an
+/// archive are chained together, the head of the list is stored in the
+/// header of the archive.
+///
+/// The snapshot keeps a copy of the archive tree at the time the snapshot was
+/// taken. Content blocks and index nodes of the tree are shared with the
+/// archive, the number of blocks limits the snapshot to its own entries.
+#[derive(Debug, FromBytes, ToBytes)]
+pub struct SnapshotNode<B: Backend> {
+    name: String,
+    #[nuts_bytes(map = datetime)]
+    created: DateTime<Utc>,
+    nfiles: u64,
+    tree: Tree<B>,
+    pub next: Option<Id<B>>,
+}
+
+impl<B: Backend> SnapshotNode<B> {
+    pub fn new(name: &str, header: &Header<B>, tree: &Tree<B>) -> SnapshotNode<B> {
+        SnapshotNode {
+            name: name.to_string(),
+            created: Utc::now(),
+            nfiles: header.nfiles,
+            tree: tree.clone(),
+            next: header.snapshots.clone(),
+        }
+    }
+
+    pub fn load(pager: &mut Pager<B>, id: &Id<B>) -> ArchiveResult<SnapshotNode<B>, B> {
+        let mut reader = pager.read_buf(id)?;
+        let node = reader.read::<SnapshotNode<B>>()?;
+
+        debug!("snapshot {} loaded from {}", node.name, id);
+
+        Ok(node)
+    }
+
+    pub fn flush(&self, pager: &mut Pager<B>, id: &Id<B>) -> ArchiveResult<(), B> {
+        let mut writer = pager.create_writer();
+        let n = writer.write(self)?;
+
+        pager.write_buf(id)?;
+
+        debug!("snapshot {}: {} bytes written into {}", self.name, n, id);
+
+        Ok(())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Tests whether the block at the given tree index is part of the
+    /// snapshot.
+    pub fn covers(&self, idx: usize) -> bool {
+        (idx as u64) < self.tree.nblocks()
+    }
+
+    pub fn info(&self) -> SnapshotInfo {
+        SnapshotInfo {
+            name: self.name.clone(),
+            created: self.created,
+            blocks: self.tree.nblocks(),
+            files: self.nfiles,
+        }
+    }
+}
+
+pub type SnapshotList<B> = Vec<(Id<B>, SnapshotNode<B>)>;
+
+/// Collects all snapshots starting at `head`.
+///
+/// The snapshots are returned in the order of the list, which is the newest
+/// snapshot first.
+pub fn walk<B: Backend>(
+    pager: &mut Pager<B>,
+    head: Option<&Id<B>>,
+) -> ArchiveResult<SnapshotList<B>, B> {
+    let mut vec = vec![];
+    let mut next = head.cloned();
+
+    while let Some(id) = next {
+        let node = SnapshotNode::load(pager, &id)?;
+
+        next = node.next.clone();
+        vec.push((id, node));
+    }
+
+    Ok(vec)
+}
+
+/// A read-only view of the archive at the time the snapshot was taken.
+///
+/// A `Snapshot` is returned by
+/// [`Archive::open_snapshot()`](crate::Archive::open_snapshot).
+pub struct Snapshot<'a, B: Backend> {
+    pager: &'a mut Pager<B>,
+    node: SnapshotNode<B>,
+}
+
+impl<'a, B: Backend> Snapshot<'a, B> {
+    pub(crate) fn new(pager: &'a mut Pager<B>, node: SnapshotNode<B>) -> Snapshot<'a, B> {
+        Snapshot { pager, node }
+    }
+
+    /// Returns the name of the snapshot.
+    pub fn name(&self) -> &str {
+        self.node.name()
+    }
+
+    /// Returns the time when the snapshot was taken.
+    pub fn created(&self) -> &DateTime<Utc> {
+        &self.node.created
+    }
+
+    /// Fetches statistics/information from the snapshot.
+    pub fn info(&self) -> SnapshotInfo {
+        self.node.info()
+    }
+
+    /// Returns the first entry in the snapshot.
+    ///
+    /// Next, you can use [`Entry::next()`] to traverse through the snapshot.
+    ///
+    /// If the snapshot is empty, [`None`] is returned.
+    pub fn first(&mut self) -> Option<ArchiveResult<Entry<'_, B>, B>> {
+        match InnerEntry::first(self.pager, &mut self.node.tree) {
+            Some(Ok(inner)) => Some(inner.try_into()),
+            Some(Err(err)) => Some(Err(err)),
+            None => None,
+        }
+    }
+
+    /// Searches for an entry with the given `name`.
+    ///
+    /// It scans the whole snapshot and returns the first entry which has the
+    /// given name wrapped into a [`Some`]. If no such entry exists, [`None`]
+    /// is returned.
+    pub fn lookup<N: AsRef<str>>(&mut self, name: N) -> Option<ArchiveResult<Entry<'_, B>, B>> {
+        lookup_entry(self.first(), name.as_ref())
+    }
+
+    /// Returns the first entry of the snapshot, which is selected by the
+    /// given `query`.
+    ///
+    /// If no entry matches the query, [`None`] is returned.
+    pub fn find(&mut self, query: &Query) -> Option<ArchiveResult<Entry<'_, B>, B>> {
+        find_entry(self.first(), query)
+    }
+}
@@ -0,0 +1,321 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use log::debug;
+use nuts_backend::{Backend, IdSize};
+use nuts_bytes::{FromBytes, ToBytes};
+use nuts_container::Container;
+use std::mem;
+
+use crate::error::{ArchiveResult, Error};
+use crate::id::Id;
+use crate::pager::Pager;
+use crate::tree::cache::CachedNode;
+use crate::tree::node::Node;
+
+fn slots_per_node<B: Backend>(container: &Container<B>) -> usize {
+    let slot_size = <B::Id as IdSize>::size() + mem::size_of::<u64>();
+
+    (container.block_size() as usize - 2 * mem::size_of::<u32>()) / slot_size
+}
+
+/// A slot of a [`BTree`] node.
+///
+/// In a leaf node the slot describes an extent: `n` blocks with contiguous
+/// ids starting at `id`. In an inner node `id` is the child node and `n` the
+/// index of the first block referenced by the child.
+#[derive(Debug, FromBytes, ToBytes)]
+pub struct Slot<B: Backend> {
+    id: Id<B>,
+    n: u64,
+}
+
+/// The block layout of archives starting with revision 3.
+///
+/// The blocks are stored as extents (runs of contiguous ids) in the leaf
+/// nodes of a tree. All leaves have the same height. Blocks are only
+/// appended, thus new nodes are placed at the right edge of the tree.
+/// Whenever the tree is full, a new root node is placed on top of the
+/// current root. Thus, the number of blocks is not limited by the layout
+/// itself.
+#[derive(Debug, FromBytes, ToBytes)]
+pub struct BTree<B: Backend> {
+    root: Option<Id<B>>,
+    height: u32,
+    nblocks: u64,
+    #[nuts_bytes(skip)]
+    cache: Vec<CachedNode<B, Slot<B>>>,
+    #[nuts_bytes(skip)]
+    current: Option<Id<B>>,
+}
+
+impl<B: Backend> BTree<B> {
+    pub fn new() -> BTree<B> {
+        BTree {
+            root: None,
+            height: 0,
+            nblocks: 0,
+            cache: vec![],
+            current: None,
+        }
+    }
+
+    pub fn nblocks(&self) -> u64 {
+        self.nblocks
+    }
+
+    pub fn aquire(&mut self, pager: &mut Pager<B>) -> ArchiveResult<&Id<B>, B> {
+        let id = pager.aquire()?;
+
+        self.insert(pager, id)
+    }
+
+    pub fn insert(&mut self, pager: &mut Pager<B>, id: Id<B>) -> ArchiveResult<&Id<B>, B> {
+        if self.root.is_none() {
+            self.grow(pager)?;
+        }
+
+        self.load_right_edge(pager)?;
+
+        if !self.extend(pager, &id)? {
+            self.append(pager, &id)?;
+        }
+
+        self.nblocks += 1;
+
+        debug!(
+            "insert: height={}, nblocks={} => {}",
+            self.height, self.nblocks, id
+        );
+
+        Ok(self.current.insert(id))
+    }
+
+    pub fn lookup(&mut self, pager: &mut Pager<B>, idx: usize) -> Option<ArchiveResult<&Id<B>, B>> {
+        if idx >= self.nblocks as usize {
+            return None;
+        }
+
+        match self.resolve(pager, idx as u64) {
+            Ok(Some(id)) => {
+                debug!("lookup: idx={}, nblocks={} => {}", idx, self.nblocks, id);
+                Some(Ok(self.current.insert(id)))
+            }
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+
+    fn resolve(&mut self, pager: &mut Pager<B>, idx: u64) -> ArchiveResult<Option<Id<B>>, B> {
+        let mut id = match self.root.clone() {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+        let mut first = 0; // index of the first block below the node
+
+        self.cache
+            .resize_with(self.height as usize, CachedNode::new);
+
+        let (leaf, inner) = match self.cache.split_last_mut() {
+            Some(levels) => levels,
+            None => return Err(Error::InvalidNode(id.as_ref().clone())),
+        };
+
+        for entry in inner {
+            entry.refresh(&id, pager)?;
+
+            match entry.node.iter().rev().find(|slot| slot.n <= idx) {
+                Some(slot) => {
+                    id = slot.id.clone();
+                    first = slot.n;
+                }
+                None => return Err(Error::InvalidNode(id.as_ref().clone())),
+            }
+        }
+
+        leaf.refresh(&id, pager)?;
+
+        let mut offset = idx - first;
+
+        for slot in leaf.node.iter() {
+            if offset < slot.n {
+                return match slot.id.offset(offset) {
+                    Some(id) => Ok(Some(id)),
+                    None => Err(Error::InvalidNode(id.as_ref().clone())),
+                };
+            }
+
+            offset -= slot.n;
+        }
+
+        Err(Error::InvalidNode(id.as_ref().clone()))
+    }
+
+    /// Loads the nodes at the right edge of the tree into the cache.
+    ///
+    /// Slots behind the last block, which are left behind by an aborted
+    /// insert, are removed.
+    fn load_right_edge(&mut self, pager: &mut Pager<B>) -> ArchiveResult<(), B> {
+        let nblocks = self.nblocks;
+        let mut id = self.root.clone().unwrap();
+        let mut first = 0; // index of the first block below the node
+
+        self.cache
+            .resize_with(self.height as usize, CachedNode::new);
+
+        let (leaf, inner) = match self.cache.split_last_mut() {
+            Some(levels) => levels,
+            None => return Err(Error::InvalidNode(id.as_ref().clone())),
+        };
+
+        for entry in inner {
+            entry.refresh(&id, pager)?;
+
+            let len = entry
+                .node
+                .iter()
+                .take_while(|slot| slot.n < nblocks)
+                .count();
+            entry.node.truncate(len);
+
+            match entry.node.last() {
+                Some(slot) => {
+                    id = slot.id.clone();
+                    first = slot.n;
+                }
+                None => return Err(Error::InvalidNode(id.as_ref().clone())),
+            }
+        }
+
+        leaf.refresh(&id, pager)?;
+
+        let mut remaining = nblocks - first;
+        let mut len = 0;
+
+        for slot in leaf.node.iter_mut() {
+            if remaining == 0 {
+                break;
+            }
+
+            slot.n = slot.n.min(remaining);
+            remaining -= slot.n;
+            len += 1;
+        }
+
+        leaf.node.truncate(len);
+
+        Ok(())
+    }
+
+    /// Extends the last extent of the tree, if `id` follows the extent.
+    fn extend(&mut self, pager: &mut Pager<B>, id: &Id<B>) -> ArchiveResult<bool, B> {
+        let leaf = self.cache.last_mut().unwrap();
+
+        match leaf.node.last_mut() {
+            Some(slot) if slot.id.offset(slot.n).as_ref() == Some(id) => {
+                slot.n += 1;
+                leaf.flush(pager)?;
+
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Appends a new extent for `id` at the right edge of the tree.
+    fn append(&mut self, pager: &mut Pager<B>, id: &Id<B>) -> ArchiveResult<(), B> {
+        let spn = slots_per_node(pager);
+
+        // the deepest node with a free slot takes the new extent
+        let level = match self.cache.iter().rposition(|entry| entry.node.len() < spn) {
+            Some(level) => level,
+            None => {
+                self.grow(pager)?;
+                self.load_right_edge(pager)?;
+                0
+            }
+        };
+
+        let mut slot = Slot {
+            id: id.clone(),
+            n: 1,
+        };
+
+        // new nodes are needed below the node
+        for _ in level + 1..self.height as usize {
+            let node_id = pager.aquire()?;
+            let mut node = Node::<B, Slot<B>>::new();
+
+            node.push(slot);
+            node.flush(&node_id, pager)?;
+
+            slot = Slot {
+                id: node_id,
+                n: self.nblocks,
+            };
+        }
+
+        let entry = &mut self.cache[level];
+
+        entry.node.push(slot);
+        entry.flush(pager)
+    }
+
+    fn grow(&mut self, pager: &mut Pager<B>) -> ArchiveResult<(), B> {
+        let id = pager.aquire()?;
+        let mut node = Node::<B, Slot<B>>::new();
+
+        if let Some(root) = self.root.take() {
+            node.push(Slot { id: root, n: 0 });
+        }
+
+        node.flush(&id, pager)?;
+
+        self.root = Some(id);
+        self.height += 1;
+
+        debug!("grow: height={}, root={:?}", self.height, self.root);
+
+        Ok(())
+    }
+}
+
+impl<B: Backend> Clone for BTree<B> {
+    fn clone(&self) -> Self {
+        BTree {
+            root: self.root.clone(),
+            height: self.height,
+            nblocks: self.nblocks,
+            cache: vec![],
+            current: None,
+        }
+    }
+}
+
+impl<B: Backend> Default for BTree<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,296 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_bytes::{Reader, Writer};
+use nuts_memory::MemoryBackend;
+
+use crate::pager::Pager;
+use crate::tests::setup_container_with_bsize;
+use crate::tree::btree::{BTree, Slot};
+use crate::tree::node::Node;
+
+const BSIZE: u32 = 32; // 2 slots per node
+
+macro_rules! _id {
+    ($id:expr) => {
+        $id.parse::<crate::id::Id<nuts_memory::MemoryBackend>>()
+            .unwrap()
+    };
+}
+
+fn load_root(tree: &BTree<MemoryBackend>, pager: &mut Pager<MemoryBackend>) -> Vec<(u32, u64)> {
+    let mut node = Node::<MemoryBackend, Slot<MemoryBackend>>::new();
+
+    node.load(tree.root.as_ref().unwrap(), pager).unwrap();
+    node.iter()
+        .map(|slot| (slot.id.to_string().parse().unwrap(), slot.n))
+        .collect()
+}
+
+fn assert_lookup(tree: &mut BTree<MemoryBackend>, pager: &mut Pager<MemoryBackend>, ids: &[u32]) {
+    for (idx, id) in ids.iter().enumerate() {
+        assert_eq!(
+            tree.lookup(pager, idx).unwrap().unwrap(),
+            &_id!(id.to_string())
+        );
+    }
+
+    assert!(tree.lookup(pager, ids.len()).is_none());
+}
+
+#[test]
+fn ser_none() {
+    let tree = BTree::<MemoryBackend>::new();
+    let mut writer = Writer::new(vec![]);
+
+    assert_eq!(writer.write(&tree).unwrap(), 13);
+    assert_eq!(
+        writer.into_target(),
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn ser_some() {
+    let tree = BTree::<MemoryBackend> {
+        root: Some(_id!("1")),
+        height: 2,
+        nblocks: 3,
+        cache: vec![],
+        current: None,
+    };
+    let mut writer = Writer::new(vec![]);
+
+    assert_eq!(writer.write(&tree).unwrap(), 17);
+    assert_eq!(
+        writer.into_target(),
+        [1, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3]
+    );
+}
+
+#[test]
+fn de_none() {
+    let mut reader = Reader::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0].as_slice());
+    let tree = reader.read::<BTree<MemoryBackend>>().unwrap();
+
+    assert!(tree.root.is_none());
+    assert_eq!(tree.height, 0);
+    assert_eq!(tree.nblocks, 0);
+}
+
+#[test]
+fn de_some() {
+    let mut reader = Reader::new([1, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3].as_slice());
+    let tree = reader.read::<BTree<MemoryBackend>>().unwrap();
+
+    assert_eq!(tree.root, Some(_id!("1")));
+    assert_eq!(tree.height, 2);
+    assert_eq!(tree.nblocks, 3);
+}
+
+#[test]
+fn lookup_empty() {
+    let mut pager = Pager::new(setup_container_with_bsize(BSIZE));
+    let mut tree = BTree::<MemoryBackend>::new();
+
+    assert!(tree.lookup(&mut pager, 0).is_none());
+}
+
+#[test]
+fn aquire() {
+    let mut pager = Pager::new(setup_container_with_bsize(BSIZE));
+    let mut tree = BTree::<MemoryBackend>::new();
+    let mut ids = vec![];
+
+    for n in 0..9 {
+        ids.push(
+            tree.aquire(&mut pager)
+                .unwrap()
+                .to_string()
+                .parse()
+                .unwrap(),
+        );
+
+        assert_eq!(tree.nblocks, n + 1);
+        assert_eq!(tree.height, 1);
+    }
+
+    // data-block is aquired before the root node
+    assert_eq!(tree.root, Some(_id!("2")));
+    assert_eq!(load_root(&tree, &mut pager), [(1, 1), (3, 8)]);
+
+    assert_lookup(&mut tree, &mut pager, &ids);
+}
+
+#[test]
+fn insert() {
+    let mut pager = Pager::new(setup_container_with_bsize(BSIZE));
+    let mut tree = BTree::<MemoryBackend>::new();
+    let ids = (0..9).map(|n| 100 + 2 * n).collect::<Vec<u32>>();
+
+    for (id, height) in ids.iter().zip([1, 1, 2, 2, 3, 3, 3, 3, 4]) {
+        let id = _id!(id.to_string());
+
+        assert_eq!(tree.insert(&mut pager, id.clone()).unwrap(), &id);
+        assert_eq!(tree.height, height);
+    }
+
+    assert_eq!(tree.nblocks, 9);
+    assert_lookup(&mut tree, &mut pager, &ids);
+}
+
+#[test]
+fn insert_extents() {
+    let mut pager = Pager::new(setup_container_with_bsize(BSIZE));
+    let mut tree = BTree::<MemoryBackend>::new();
+    let ids = [100, 101, 102, 103, 104, 200, 201];
+
+    for id in ids {
+        tree.insert(&mut pager, _id!(id.to_string())).unwrap();
+    }
+
+    assert_eq!(tree.height, 1);
+    assert_eq!(load_root(&tree, &mut pager), [(100, 5), (200, 2)]);
+
+    assert_lookup(&mut tree, &mut pager, &ids);
+}
+
+#[test]
+fn insert_after_abort() {
+    let mut pager = Pager::new(setup_container_with_bsize(BSIZE));
+    let mut tree = BTree::<MemoryBackend>::new();
+
+    for id in [100, 101] {
+        tree.insert(&mut pager, _id!(id.to_string())).unwrap();
+    }
+
+    let mut saved = tree.clone();
+
+    for id in [102, 103, 300] {
+        tree.insert(&mut pager, _id!(id.to_string())).unwrap();
+    }
+
+    // the extent is extended and a slot is appended, which are not part of
+    // the saved tree
+    saved.insert(&mut pager, _id!("500")).unwrap();
+
+    assert_eq!(load_root(&saved, &mut pager), [(100, 2), (500, 1)]);
+    assert_lookup(&mut saved, &mut pager, &[100, 101, 500]);
+}
+
+#[test]
+fn insert_after_abort_inner() {
+    let mut pager = Pager::new(setup_container_with_bsize(BSIZE));
+    let mut tree = BTree::<MemoryBackend>::new();
+    let ids = (0..7).map(|n| 100 + 2 * n).collect::<Vec<u32>>();
+
+    for id in &ids[..5] {
+        tree.insert(&mut pager, _id!(id.to_string())).unwrap();
+    }
+
+    let mut saved = tree.clone();
+
+    // appends a new leaf to an inner node
+    for id in &ids[5..] {
+        tree.insert(&mut pager, _id!(id.to_string())).unwrap();
+    }
+
+    saved.insert(&mut pager, _id!("500")).unwrap();
+
+    assert_eq!(saved.height, 3);
+    assert_lookup(&mut saved, &mut pager, &[100, 102, 104, 106, 108, 500]);
+}
+
+#[test]
+fn large_contiguous() {
+    let mut pager = Pager::new(setup_container_with_bsize(512));
+    let mut tree = BTree::<MemoryBackend>::new();
+
+    for _ in 0..10_000 {
+        tree.aquire(&mut pager).unwrap();
+    }
+
+    // the whole entry is referenced by a single node
+    assert_eq!(tree.height, 1);
+    assert_eq!(tree.root, Some(_id!("2")));
+    assert_eq!(load_root(&tree, &mut pager), [(1, 1), (3, 9_999)]);
+    assert_eq!(pager.aquire().unwrap(), _id!("10002"));
+
+    assert_eq!(tree.lookup(&mut pager, 0).unwrap().unwrap(), &_id!("1"));
+    assert_eq!(tree.lookup(&mut pager, 1).unwrap().unwrap(), &_id!("3"));
+    assert_eq!(
+        tree.lookup(&mut pager, 9_999).unwrap().unwrap(),
+        &_id!("10001")
+    );
+}
+
+#[test]
+fn reopen() {
+    let mut pager = Pager::new(setup_container_with_bsize(BSIZE));
+    let mut tree = BTree::<MemoryBackend>::new();
+    let ids = (0..9).map(|n| 100 + 2 * n).collect::<Vec<u32>>();
+
+    for id in &ids[..7] {
+        tree.insert(&mut pager, _id!(id.to_string())).unwrap();
+    }
+
+    let mut writer = Writer::new(vec![]);
+    writer.write(&tree).unwrap();
+
+    let buf = writer.into_target();
+    let mut reader = Reader::new(buf.as_slice());
+    let mut tree = reader.read::<BTree<MemoryBackend>>().unwrap();
+
+    assert_lookup(&mut tree, &mut pager, &ids[..7]);
+
+    for id in &ids[7..] {
+        tree.insert(&mut pager, _id!(id.to_string())).unwrap();
+    }
+
+    assert_lookup(&mut tree, &mut pager, &ids);
+}
+
+#[test]
+fn clone() {
+    let mut pager = Pager::new(setup_container_with_bsize(BSIZE));
+    let mut tree = BTree::<MemoryBackend>::new();
+    let mut ids = vec![];
+
+    for _ in 0..3 {
+        ids.push(
+            tree.aquire(&mut pager)
+                .unwrap()
+                .to_string()
+                .parse()
+                .unwrap(),
+        );
+    }
+
+    let mut cloned = tree.clone();
+
+    for _ in 0..3 {
+        tree.aquire(&mut pager).unwrap();
+    }
+
+    assert_eq!(cloned.nblocks(), 3);
+    assert_lookup(&mut cloned, &mut pager, &ids);
+}
@@ -21,27 +21,30 @@
 // IN THE SOFTWARE.
 
 use nuts_backend::Backend;
+use nuts_bytes::{FromBytes, ToBytes};
 
 use crate::error::ArchiveResult;
 use crate::id::Id;
 use crate::pager::Pager;
 use crate::tree::node::Node;
 
+/// A node together with the id of the block, where it is stored.
 #[derive(Debug)]
-struct Inner<B: Backend> {
-    id: Option<Id<B>>,
-    node: Node<B>,
+pub struct CachedNode<B: Backend, T = Id<B>> {
+    pub id: Option<Id<B>>,
+    pub node: Node<B, T>,
 }
 
-impl<B: Backend> Inner<B> {
-    fn new() -> Inner<B> {
-        Inner {
+impl<B: Backend, T: FromBytes + ToBytes> CachedNode<B, T> {
+    pub fn new() -> CachedNode<B, T> {
+        CachedNode {
             id: None,
             node: Node::new(),
         }
     }
 
-    fn refresh(&mut self, id: &Id<B>, pager: &mut Pager<B>) -> ArchiveResult<(), B> {
+    /// Loads the node stored in `id`, if not already cached.
+    pub fn refresh(&mut self, id: &Id<B>, pager: &mut Pager<B>) -> ArchiveResult<(), B> {
         let must_refresh = match self.id.as_ref() {
             Some(in_id) => in_id != id,
             None => true,
@@ -55,7 +58,7 @@ impl<B: Backend> Inner<B> {
         Ok(())
     }
 
-    fn flush(&mut self, pager: &mut Pager<B>) -> ArchiveResult<(), B> {
+    pub fn flush(&mut self, pager: &mut Pager<B>) -> ArchiveResult<(), B> {
         if let Some(id) = self.id.as_ref() {
             self.node.flush(id, pager)?;
         }
@@ -65,7 +68,7 @@ impl<B: Backend> Inner<B> {
 }
 
 #[derive(Debug)]
-pub struct Cache<B: Backend>(Vec<Inner<B>>);
+pub struct Cache<B: Backend>(Vec<CachedNode<B>>);
 
 impl<B: Backend> Cache<B> {
     pub fn new() -> Cache<B> {
@@ -78,7 +81,7 @@ impl<B: Backend> Cache<B> {
         start: Option<&'a Id<B>>,
         idxs: &[usize],
     ) -> ArchiveResult<Option<&'a Id<B>>, B> {
-        self.0.resize_with(idxs.len(), || Inner::new());
+        self.0.resize_with(idxs.len(), CachedNode::new);
 
         let mut id_opt = start;
 
@@ -101,7 +104,7 @@ impl<B: Backend> Cache<B> {
         start: &'a Id<B>,
         idxs: &[usize],
     ) -> ArchiveResult<&'a Id<B>, B> {
-        self.0.resize_with(idxs.len(), || Inner::new());
+        self.0.resize_with(idxs.len(), CachedNode::new);
 
         let mut id = start;
 
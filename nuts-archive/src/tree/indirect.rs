@@ -0,0 +1,307 @@
+// MIT License
+//
+// Copyright (c) 2023,2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use log::debug;
+use nuts_backend::Backend;
+use nuts_bytes::{FromBytes, ToBytes};
+
+use crate::error::{ArchiveResult, Error};
+use crate::id::Id;
+use crate::pager::Pager;
+use crate::tree::cache::Cache;
+use crate::tree::ids_per_node;
+use crate::tree::node::Node;
+
+const NUM_DIRECT: u32 = 12;
+const IDX_INDIRECT: usize = NUM_DIRECT as usize;
+const IDX_D_INDIRECT: usize = IDX_INDIRECT + 1;
+const IDX_T_INDIRECT: usize = IDX_D_INDIRECT + 1;
+
+/// The block layout of archives prior to revision 3.
+///
+/// The first [`NUM_DIRECT`] blocks are referenced directly, followed by an
+/// indirect, a double-indirect and a triple-indirect node. This limits the
+/// number of blocks, which can be stored in the archive.
+#[derive(Debug, FromBytes, ToBytes)]
+pub struct Indirect<B: Backend> {
+    ids: Vec<Id<B>>,
+    nblocks: u64,
+    #[nuts_bytes(skip)]
+    cache: Cache<B>,
+}
+
+impl<B: Backend> Indirect<B> {
+    pub fn nblocks(&self) -> u64 {
+        self.nblocks
+    }
+
+    pub fn aquire(&mut self, pager: &mut Pager<B>) -> ArchiveResult<&Id<B>, B> {
+        let ipn = ids_per_node(pager) as u64; // ids per node
+
+        if self.nblocks < NUM_DIRECT as u64 {
+            self.aquire_direct(pager)
+        } else if self.nblocks < NUM_DIRECT as u64 + ipn {
+            self.aquire_indirect(pager)
+        } else if self.nblocks < NUM_DIRECT as u64 + ipn + ipn * ipn {
+            self.aquire_d_indirect(pager)
+        } else if self.nblocks < NUM_DIRECT as u64 + ipn + ipn * ipn + ipn * ipn * ipn {
+            self.aquire_t_indirect(pager)
+        } else {
+            Err(Error::Full)
+        }
+    }
+
+    pub fn lookup(&mut self, pager: &mut Pager<B>, idx: usize) -> Option<ArchiveResult<&Id<B>, B>> {
+        if idx >= self.nblocks as usize {
+            return None;
+        }
+
+        let ipn = ids_per_node(pager) as usize; // ids per node
+
+        let result = if idx < NUM_DIRECT as usize {
+            self.lookup_direct(idx)
+        } else if idx < NUM_DIRECT as usize + ipn {
+            self.lookup_indirect(pager, idx - NUM_DIRECT as usize)
+        } else if idx < NUM_DIRECT as usize + ipn + ipn * ipn {
+            self.lookup_d_indirect(pager, idx - NUM_DIRECT as usize - ipn)
+        } else {
+            self.lookup_t_indirect(pager, idx - NUM_DIRECT as usize - ipn - ipn * ipn)
+        };
+
+        match result {
+            Ok(Some(id)) => Some(Ok(id)),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+
+    fn lookup_direct(&mut self, idx: usize) -> ArchiveResult<Option<&Id<B>>, B> {
+        assert!(idx < NUM_DIRECT as usize);
+
+        let id = self.ids.get(idx);
+
+        debug!(
+            "lookup_direct: idx={}, nblocks={}, id={:?}",
+            idx, self.nblocks, id
+        );
+
+        Ok(id)
+    }
+
+    fn aquire_direct(&mut self, pager: &mut Pager<B>) -> ArchiveResult<&Id<B>, B> {
+        assert!(self.nblocks < NUM_DIRECT as u64);
+
+        self.ids.push(pager.aquire()?);
+        self.nblocks += 1;
+
+        let id = &self.ids[self.nblocks as usize - 1];
+
+        debug!("aquire_direct: nblocks={} => {}", self.nblocks, id);
+
+        Ok(id)
+    }
+
+    fn lookup_indirect(
+        &mut self,
+        pager: &mut Pager<B>,
+        idx: usize,
+    ) -> ArchiveResult<Option<&Id<B>>, B> {
+        let id = self
+            .cache
+            .resolve(pager, self.ids.get(IDX_INDIRECT), &[idx])?;
+
+        debug!(
+            "loopup_indirect: idx={}, nblocks={}, {:?}",
+            idx, self.nblocks, id
+        );
+
+        Ok(id)
+    }
+
+    fn aquire_indirect(&mut self, pager: &mut Pager<B>) -> ArchiveResult<&Id<B>, B> {
+        self.ensure_id(IDX_INDIRECT, pager)?;
+
+        let idx = self.nblocks as usize - NUM_DIRECT as usize;
+        let id = self.cache.aquire(pager, &self.ids[IDX_INDIRECT], &[idx])?;
+
+        self.nblocks += 1;
+
+        debug!(
+            "aquire_indirect: idx={}, nblocks={} => {}",
+            idx, self.nblocks, id
+        );
+
+        Ok(id)
+    }
+
+    fn lookup_d_indirect(
+        &mut self,
+        pager: &mut Pager<B>,
+        idx: usize,
+    ) -> ArchiveResult<Option<&Id<B>>, B> {
+        let ipn = ids_per_node(pager) as usize; // ids per node
+        let d_idx = [(idx / ipn) % ipn, idx % ipn];
+        let d_indirect = self.ids.get(IDX_D_INDIRECT);
+
+        let id = self.cache.resolve(pager, d_indirect, &d_idx)?;
+
+        debug!(
+            "loopup_d_indirect: idx={} => {:?}, nblocks={} => {:?}",
+            idx, d_idx, self.nblocks, id
+        );
+
+        Ok(id)
+    }
+
+    fn aquire_d_indirect(&mut self, pager: &mut Pager<B>) -> ArchiveResult<&Id<B>, B> {
+        self.ensure_id(IDX_D_INDIRECT, pager)?;
+
+        let ipn = ids_per_node(pager) as usize; // ids per node
+        let idx = self.nblocks as usize - NUM_DIRECT as usize - ipn;
+
+        let d_idx = [(idx / ipn) % ipn, idx % ipn];
+        let d_indirect = &self.ids[IDX_D_INDIRECT];
+
+        let id = self.cache.aquire(pager, d_indirect, &d_idx)?;
+
+        self.nblocks += 1;
+
+        debug!(
+            "aquire_d_indirect: idx={} => {:?}, nblocks={} => {}",
+            idx, d_idx, self.nblocks, id
+        );
+
+        Ok(id)
+    }
+
+    fn lookup_t_indirect(
+        &mut self,
+        pager: &mut Pager<B>,
+        idx: usize,
+    ) -> ArchiveResult<Option<&Id<B>>, B> {
+        let ipn = ids_per_node(pager) as usize; // ids per node
+        let t_idx = [(idx / (ipn * ipn)) % ipn, (idx / ipn) % ipn, idx % ipn];
+        let t_indirect = self.ids.get(IDX_T_INDIRECT);
+
+        let id = self.cache.resolve(pager, t_indirect, &t_idx)?;
+
+        debug!(
+            "loopup_t_indirect: idx={} => {:?}, nblocks={} => {:?}",
+            idx, t_idx, self.nblocks, id
+        );
+
+        Ok(id)
+    }
+
+    fn aquire_t_indirect(&mut self, pager: &mut Pager<B>) -> ArchiveResult<&Id<B>, B> {
+        self.ensure_id(IDX_T_INDIRECT, pager)?;
+
+        let ipn = ids_per_node(pager) as usize; // ids per node
+        let idx = self.nblocks as usize - NUM_DIRECT as usize - ipn - ipn * ipn;
+
+        let t_idx = [(idx / (ipn * ipn)) % ipn, (idx / ipn) % ipn, idx % ipn];
+        let t_indirect = &self.ids[IDX_T_INDIRECT];
+
+        let id = self.cache.aquire(pager, t_indirect, &t_idx)?;
+
+        self.nblocks += 1;
+
+        debug!(
+            "aquire_t_indirect: idx={} => {:?}, nblocks={} => {}",
+            idx, t_idx, self.nblocks, id
+        );
+
+        Ok(id)
+    }
+
+    fn ensure_id(&mut self, idx: usize, pager: &mut Pager<B>) -> ArchiveResult<(), B> {
+        while self.ids.get(idx).is_none() {
+            let id = pager.aquire()?;
+
+            Node::<B>::new().flush(&id, pager)?;
+
+            self.ids.push(id);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the ids of all index nodes of the tree.
+    ///
+    /// These are the nodes, which are not part of the content, but are
+    /// required to reference the content blocks.
+    pub fn nodes(&self, pager: &mut Pager<B>) -> ArchiveResult<Vec<Id<B>>, B> {
+        let mut nodes = vec![];
+
+        for (idx, depth) in [(IDX_INDIRECT, 0), (IDX_D_INDIRECT, 1), (IDX_T_INDIRECT, 2)] {
+            if let Some(id) = self.ids.get(idx) {
+                collect_nodes(pager, id, depth, &mut nodes)?;
+            }
+        }
+
+        Ok(nodes)
+    }
+}
+
+fn collect_nodes<B: Backend>(
+    pager: &mut Pager<B>,
+    id: &Id<B>,
+    depth: usize,
+    nodes: &mut Vec<Id<B>>,
+) -> ArchiveResult<(), B> {
+    nodes.push(id.clone());
+
+    if depth > 0 {
+        let mut node = Node::new();
+
+        node.load(id, pager)?;
+
+        for child in node.iter() {
+            collect_nodes(pager, child, depth - 1, nodes)?;
+        }
+    }
+
+    Ok(())
+}
+
+impl<B: Backend> Clone for Indirect<B> {
+    fn clone(&self) -> Self {
+        Indirect {
+            ids: self.ids.clone(),
+            nblocks: self.nblocks,
+            cache: Cache::new(),
+        }
+    }
+}
+
+impl<B: Backend> Default for Indirect<B> {
+    fn default() -> Self {
+        Indirect {
+            ids: vec![],
+            nblocks: 0,
+            cache: Cache::new(),
+        }
+    }
+}
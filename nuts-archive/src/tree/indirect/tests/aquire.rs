@@ -24,8 +24,8 @@ use nuts_memory::MemoryBackend;
 
 use crate::pager::Pager;
 use crate::tests::setup_container_with_bsize;
-use crate::tree::tests::{_id, assert_direct, make_tree, BSIZE};
-use crate::tree::Tree;
+use crate::tree::indirect::tests::{_id, assert_direct, make_tree, BSIZE};
+use crate::tree::indirect::Indirect;
 use crate::Error;
 
 macro_rules! assert_indirect {
@@ -57,19 +57,27 @@ macro_rules! assert_t_indirect {
     };
 }
 
-fn aquire_direct(num: usize, tree: &mut Tree<MemoryBackend>, pager: &mut Pager<MemoryBackend>) {
+fn aquire_direct(num: usize, tree: &mut Indirect<MemoryBackend>, pager: &mut Pager<MemoryBackend>) {
     for i in 0..num {
         assert_eq!(tree.aquire(pager).unwrap(), &_id!((i + 1).to_string()));
     }
 }
 
-fn aquire_indirect(num: usize, tree: &mut Tree<MemoryBackend>, pager: &mut Pager<MemoryBackend>) {
+fn aquire_indirect(
+    num: usize,
+    tree: &mut Indirect<MemoryBackend>,
+    pager: &mut Pager<MemoryBackend>,
+) {
     for i in 0..num {
         assert_eq!(tree.aquire(pager).unwrap(), &_id!((i + 14).to_string()));
     }
 }
 
-fn aquire_d_indirect(num: usize, tree: &mut Tree<MemoryBackend>, pager: &mut Pager<MemoryBackend>) {
+fn aquire_d_indirect(
+    num: usize,
+    tree: &mut Indirect<MemoryBackend>,
+    pager: &mut Pager<MemoryBackend>,
+) {
     let results = [_id!("18"), _id!("19"), _id!("21"), _id!("22")];
 
     for id in results.iter().take(num) {
@@ -77,7 +85,11 @@ fn aquire_d_indirect(num: usize, tree: &mut Tree<MemoryBackend>, pager: &mut Pag
     }
 }
 
-fn aquire_t_indirect(num: usize, tree: &mut Tree<MemoryBackend>, pager: &mut Pager<MemoryBackend>) {
+fn aquire_t_indirect(
+    num: usize,
+    tree: &mut Indirect<MemoryBackend>,
+    pager: &mut Pager<MemoryBackend>,
+) {
     let results = [
         _id!("26"),
         _id!("27"),
@@ -25,8 +25,8 @@ use nuts_memory::MemoryBackend;
 
 use crate::pager::Pager;
 use crate::tests::setup_container_with_bsize;
-use crate::tree::tests::{_id, assert_direct, make_tree, BSIZE};
-use crate::tree::Tree;
+use crate::tree::indirect::tests::{_id, assert_direct, make_tree, BSIZE};
+use crate::tree::indirect::Indirect;
 
 macro_rules! make_node {
     ($pager:expr, $parent:expr, $num:literal => $($id:expr),+) => {{
@@ -43,7 +43,7 @@ macro_rules! make_node {
     }};
 }
 
-fn make_indirect_tree(num: u64, pager: &mut Pager<MemoryBackend>) -> Tree<MemoryBackend> {
+fn make_indirect_tree(num: u64, pager: &mut Pager<MemoryBackend>) -> Indirect<MemoryBackend> {
     let mut tree = make_direct_tree(12, pager);
     let indirect = pager.aquire().unwrap();
 
@@ -55,7 +55,7 @@ fn make_indirect_tree(num: u64, pager: &mut Pager<MemoryBackend>) -> Tree<Memory
     tree
 }
 
-fn make_d_indirect_tree(num: u64, pager: &mut Pager<MemoryBackend>) -> Tree<MemoryBackend> {
+fn make_d_indirect_tree(num: u64, pager: &mut Pager<MemoryBackend>) -> Indirect<MemoryBackend> {
     let mut tree = make_indirect_tree(2, pager);
 
     //               id
@@ -85,7 +85,7 @@ fn make_d_indirect_tree(num: u64, pager: &mut Pager<MemoryBackend>) -> Tree<Memo
     tree
 }
 
-fn make_t_indirect_tree(num: u64, pager: &mut Pager<MemoryBackend>) -> Tree<MemoryBackend> {
+fn make_t_indirect_tree(num: u64, pager: &mut Pager<MemoryBackend>) -> Indirect<MemoryBackend> {
     let mut tree = make_d_indirect_tree(4, pager);
 
     //               id
@@ -147,7 +147,7 @@ macro_rules! assert_d_indirect {
     };
 }
 
-fn make_direct_tree(num: u64, pager: &mut Pager<MemoryBackend>) -> Tree<MemoryBackend> {
+fn make_direct_tree(num: u64, pager: &mut Pager<MemoryBackend>) -> Indirect<MemoryBackend> {
     let mut tree = make_tree();
 
     tree.nblocks = num;
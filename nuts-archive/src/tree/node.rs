@@ -24,8 +24,9 @@
 mod tests;
 
 use nuts_backend::Backend;
-use nuts_bytes::{PutBytesError, Reader, Writer};
-use std::ops::Deref;
+use nuts_bytes::{FromBytes, PutBytesError, Reader, ToBytes, Writer};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
 
 use crate::error::{ArchiveResult, Error};
 use crate::id::Id;
@@ -33,17 +34,22 @@ use crate::pager::Pager;
 
 const MAGIC: [u8; 4] = *b"node";
 
+/// An index node of the tree.
+///
+/// The node stores a list of slots, by default the ids of other blocks.
 #[derive(Debug)]
-pub struct Node<B: Backend> {
+pub struct Node<B: Backend, T = Id<B>> {
     buf: Vec<u8>,
-    vec: Vec<Id<B>>,
+    vec: Vec<T>,
+    data: PhantomData<B>,
 }
 
-impl<B: Backend> Node<B> {
-    pub fn new() -> Node<B> {
+impl<B: Backend, T: FromBytes + ToBytes> Node<B, T> {
+    pub fn new() -> Node<B, T> {
         Node {
             buf: vec![],
             vec: vec![],
+            data: PhantomData,
         }
     }
 
@@ -70,13 +76,12 @@ impl<B: Backend> Node<B> {
         Ok(())
     }
 
-    pub fn aquire(&mut self, pager: &mut Pager<B>) -> ArchiveResult<(), B> {
-        let id = pager.aquire()?;
-
-        Node::<B>::new().flush(&id, pager)?;
-        self.vec.push(id);
+    pub fn push(&mut self, slot: T) {
+        self.vec.push(slot);
+    }
 
-        Ok(())
+    pub fn truncate(&mut self, len: usize) {
+        self.vec.truncate(len);
     }
 
     pub fn flush(&mut self, id: &Id<B>, pager: &mut Pager<B>) -> ArchiveResult<(), B> {
@@ -101,18 +106,35 @@ impl<B: Backend> Node<B> {
         writer.write(&MAGIC)?;
         writer.write(&(self.vec.len() as u32))?;
 
-        for id in &self.vec {
-            writer.write(id)?;
+        for slot in &self.vec {
+            writer.write(slot)?;
         }
 
         Ok(())
     }
 }
 
-impl<B: Backend> Deref for Node<B> {
-    type Target = [Id<B>];
+impl<B: Backend> Node<B> {
+    pub fn aquire(&mut self, pager: &mut Pager<B>) -> ArchiveResult<(), B> {
+        let id = pager.aquire()?;
+
+        Node::<B>::new().flush(&id, pager)?;
+        self.vec.push(id);
+
+        Ok(())
+    }
+}
+
+impl<B: Backend, T> Deref for Node<B, T> {
+    type Target = [T];
 
-    fn deref(&self) -> &[Id<B>] {
+    fn deref(&self) -> &[T] {
         &self.vec
     }
 }
+
+impl<B: Backend, T> DerefMut for Node<B, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.vec
+    }
+}
@@ -51,7 +51,7 @@ fn load() {
 
     pager.write(&id, &writer.into_target()).unwrap();
 
-    let mut node = Node::new();
+    let mut node = Node::<MemoryBackend>::new();
     node.load(&id, &mut pager).unwrap();
 
     assert_eq!(node.vec.len(), 3);
@@ -75,7 +75,9 @@ fn load_inval_node() {
 
     pager.write(&id, &writer.into_target()).unwrap();
 
-    let err = Node::new().load(&id, &mut pager).unwrap_err();
+    let err = Node::<MemoryBackend>::new()
+        .load(&id, &mut pager)
+        .unwrap_err();
     assert!(matches!(err, Error::InvalidNode(err_id) if err_id == *id.as_ref()));
 }
 
@@ -84,7 +86,7 @@ fn flush() {
     let mut pager = Pager::new(setup_container_with_bsize(20));
     let id = pager.aquire().unwrap();
 
-    let mut node = Node::new();
+    let mut node = Node::<MemoryBackend>::new();
 
     node.vec.push("4711".parse().unwrap());
     node.vec.push("4712".parse().unwrap());
@@ -110,7 +112,7 @@ fn flush_nospace() {
     let mut pager = Pager::new(setup_container_with_bsize(19));
     let id = pager.aquire().unwrap();
 
-    let mut node = Node::new();
+    let mut node = Node::<MemoryBackend>::new();
 
     node.vec.push("4711".parse().unwrap());
     node.vec.push("4712".parse().unwrap());
@@ -1,6 +1,6 @@
 // MIT License
 //
-// Copyright (c) 2023,2024 Robin Doer
+// Copyright (c) 2024 Robin Doer
 //
 // Permission is hereby granted, free of charge, to any person obtaining a copy
 // of this software and associated documentation files (the "Software"), to
@@ -20,118 +20,89 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
-mod aquire;
-mod lookup;
-
 use nuts_bytes::{Reader, Writer};
 use nuts_memory::MemoryBackend;
+use std::collections::HashSet;
 
-use crate::tree::cache::Cache;
-use crate::tree::Tree;
+use crate::pager::Pager;
+use crate::tests::setup_container_with_bsize;
+use crate::tree::{Indirect, Tree};
 
-const BSIZE: u32 = 16;
+const BSIZE: u32 = 32; // 6 ids per indirect node
 
-macro_rules! _id {
-    ($id:expr) => {
-        $id.parse::<crate::id::Id<nuts_memory::MemoryBackend>>()
-            .unwrap()
-    };
+#[test]
+fn new() {
+    assert!(matches!(Tree::<MemoryBackend>::new(), Tree::BTree(_)));
 }
 
-macro_rules! assert_direct {
-    ($tree:expr, $pager:expr) => {
-        assert_eq!($tree.lookup(&mut $pager, 0).unwrap().unwrap(), &_id!("1"));
-        assert_eq!($tree.lookup(&mut $pager, 1).unwrap().unwrap(), &_id!("2"));
-        assert_eq!($tree.lookup(&mut $pager, 2).unwrap().unwrap(), &_id!("3"));
-        assert_eq!($tree.lookup(&mut $pager, 3).unwrap().unwrap(), &_id!("4"));
-        assert_eq!($tree.lookup(&mut $pager, 4).unwrap().unwrap(), &_id!("5"));
-        assert_eq!($tree.lookup(&mut $pager, 5).unwrap().unwrap(), &_id!("6"));
-        assert_eq!($tree.lookup(&mut $pager, 6).unwrap().unwrap(), &_id!("7"));
-        assert_eq!($tree.lookup(&mut $pager, 7).unwrap().unwrap(), &_id!("8"));
-        assert_eq!($tree.lookup(&mut $pager, 8).unwrap().unwrap(), &_id!("9"));
-        assert_eq!($tree.lookup(&mut $pager, 9).unwrap().unwrap(), &_id!("10"));
-        assert_eq!($tree.lookup(&mut $pager, 10).unwrap().unwrap(), &_id!("11"));
-        assert_eq!($tree.lookup(&mut $pager, 11).unwrap().unwrap(), &_id!("12"));
-    };
-}
+#[test]
+fn ser_de() {
+    let mut pager = Pager::new(setup_container_with_bsize(BSIZE));
+    let mut tree = Tree::<MemoryBackend>::new();
+    let mut ids = vec![];
 
-pub(crate) use {_id, assert_direct};
+    for _ in 0..5 {
+        ids.push(tree.aquire(&mut pager).unwrap().clone());
+    }
 
-fn make_tree() -> Tree<MemoryBackend> {
-    Tree::<MemoryBackend> {
-        ids: vec![],
-        nblocks: 0,
-        cache: Cache::new(),
+    let mut writer = Writer::new(vec![]);
+    writer.write(&tree).unwrap();
+
+    let buf = writer.into_target();
+    assert_eq!(buf[..4], [0, 0, 0, 1]); // tag: BTree
+
+    let mut reader = Reader::new(buf.as_slice());
+    let mut tree = reader.read::<Tree<MemoryBackend>>().unwrap();
+
+    assert_eq!(tree.nblocks(), 5);
+
+    for (idx, id) in ids.iter().enumerate() {
+        assert_eq!(tree.lookup(&mut pager, idx).unwrap().unwrap(), id);
     }
 }
 
 #[test]
-fn ser() {
-    let tree = Tree::<MemoryBackend> {
-        ids: vec![
-            _id!("1"),
-            _id!("2"),
-            _id!("3"),
-            _id!("4"),
-            _id!("5"),
-            _id!("6"),
-            _id!("7"),
-            _id!("8"),
-            _id!("9"),
-            _id!("10"),
-            _id!("11"),
-            _id!("12"),
-            _id!("13"),
-            _id!("14"),
-            _id!("15"),
-        ],
-        nblocks: 16,
-        cache: Cache::new(),
-    };
-    let mut writer = Writer::new(vec![]);
+fn migrate() {
+    let mut pager = Pager::new(setup_container_with_bsize(BSIZE));
+    let mut tree = Tree::Indirect(Indirect::default());
+    let mut ids = vec![];
 
-    assert_eq!(writer.write(&tree).unwrap(), 76);
-    assert_eq!(
-        writer.into_target(),
-        [
-            0, 0, 0, 0, 0, 0, 0, 15, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0,
-            0, 0, 6, 0, 0, 0, 7, 0, 0, 0, 8, 0, 0, 0, 9, 0, 0, 0, 10, 0, 0, 0, 11, 0, 0, 0, 12, 0,
-            0, 0, 13, 0, 0, 0, 14, 0, 0, 0, 15, 0, 0, 0, 0, 0, 0, 0, 16
-        ]
-    );
+    // direct, indirect, double-indirect and triple-indirect blocks
+    for _ in 0..55 {
+        ids.push(tree.aquire(&mut pager).unwrap().clone());
+    }
+
+    let nodes = tree.migrate(&mut pager).unwrap();
+
+    assert!(matches!(tree, Tree::BTree(_)));
+    assert_eq!(tree.nblocks(), 55);
+
+    for (idx, id) in ids.iter().enumerate() {
+        assert_eq!(tree.lookup(&mut pager, idx).unwrap().unwrap(), id);
+    }
+
+    assert!(tree.lookup(&mut pager, 55).is_none());
+
+    // indirect: 1, double-indirect: 1 + 6, triple-indirect: 1 + 1 + 1
+    let data: HashSet<_> = ids.iter().map(|id| id.to_string()).collect();
+    let nodes: HashSet<_> = nodes.iter().map(|id| id.to_string()).collect();
+
+    assert_eq!(nodes.len(), 11);
+    assert!(nodes.is_disjoint(&data));
+
+    // data can be appended to the migrated tree
+    let id = tree.aquire(&mut pager).unwrap().clone();
+    assert_eq!(tree.lookup(&mut pager, 55).unwrap().unwrap(), &id);
 }
 
 #[test]
-fn de() {
-    let mut reader = Reader::new(
-        [
-            0, 0, 0, 0, 0, 0, 0, 15, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0,
-            0, 0, 6, 0, 0, 0, 7, 0, 0, 0, 8, 0, 0, 0, 9, 0, 0, 0, 10, 0, 0, 0, 11, 0, 0, 0, 12, 0,
-            0, 0, 13, 0, 0, 0, 14, 0, 0, 0, 15, 0, 0, 0, 0, 0, 0, 0, 16,
-        ]
-        .as_slice(),
-    );
-    let tree = reader.read::<Tree<MemoryBackend>>().unwrap();
-
-    assert_eq!(
-        tree.ids,
-        [
-            _id!("1"),
-            _id!("2"),
-            _id!("3"),
-            _id!("4"),
-            _id!("5"),
-            _id!("6"),
-            _id!("7"),
-            _id!("8"),
-            _id!("9"),
-            _id!("10"),
-            _id!("11"),
-            _id!("12"),
-            _id!("13"),
-            _id!("14"),
-            _id!("15"),
-        ]
-    );
-    assert_eq!(tree.nblocks, 16);
+fn migrate_btree() {
+    let mut pager = Pager::new(setup_container_with_bsize(BSIZE));
+    let mut tree = Tree::<MemoryBackend>::new();
+
+    tree.aquire(&mut pager).unwrap();
+
+    assert!(tree.migrate(&mut pager).unwrap().is_empty());
+    assert!(matches!(tree, Tree::BTree(_)));
+    assert_eq!(tree.nblocks(), 1);
 }
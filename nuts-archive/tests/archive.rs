@@ -113,9 +113,11 @@ fn append() {
 
     let mut archive = Container::open_service::<ArchiveFactory>(container, false).unwrap();
 
-    // Append a new file entry
+    // Append a new file entry, the content is stored on flush
     let mut entry = archive.append_file("sample file").build().unwrap();
     entry.write_all("some sample data".as_bytes()).unwrap();
+    entry.flush().unwrap();
+    drop(entry);
 
     // Append a new directory entry
     archive
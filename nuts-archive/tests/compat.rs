@@ -20,7 +20,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
-use nuts_archive::ArchiveFactory;
+use nuts_archive::{Archive, ArchiveFactory};
 use nuts_container::{Cipher, Container, OpenOptionsBuilder};
 use nuts_memory::MemoryBackend;
 use std::fs::File;
@@ -62,13 +62,44 @@ fn fixture_path(name: &str) -> PathBuf {
 
 //         archive.append_symlink("f3", "f1").build().unwrap();
 
-//         let backend = archive.into_container().into_backend();
+//         let backend = archive.into_container().unwrap().into_backend();
 //         let file = File::create(fixture_path(path)).unwrap();
 
 //         serde_json::to_writer(file, &backend).unwrap();
 //     }
 // }
 
+fn open_archive(backend: MemoryBackend) -> Archive<MemoryBackend> {
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(password)
+        .build::<MemoryBackend>()
+        .unwrap();
+    let container = Container::open(backend, options).unwrap();
+
+    Container::open_service::<ArchiveFactory>(container, false).unwrap()
+}
+
+fn assert_content(archive: &mut Archive<MemoryBackend>) {
+    let mut entry = archive.first().unwrap().unwrap();
+    assert!(entry.is_file());
+    assert_eq!(entry.name(), "f1");
+    assert_eq!(
+        entry.as_file_mut().unwrap().read_vec().unwrap(),
+        b"content of f1"
+    );
+
+    let entry = entry.next().unwrap().unwrap();
+    assert!(entry.is_directory());
+    assert_eq!(entry.name(), "f2");
+
+    let entry = entry.next().unwrap().unwrap();
+    assert!(entry.is_symlink());
+    assert_eq!(entry.name(), "f3");
+    assert_eq!(entry.as_symlink().unwrap().target(), "f1");
+
+    assert!(entry.next().is_none());
+}
+
 macro_rules! make_test {
     ($name:ident, $path:literal, $cipher:ident) => {
         #[test]
@@ -76,13 +107,7 @@ macro_rules! make_test {
             let file = File::open(fixture_path($path)).unwrap();
             let backend: MemoryBackend = serde_json::from_reader(file).unwrap();
 
-            let options = OpenOptionsBuilder::new()
-                .with_password_callback(password)
-                .build::<MemoryBackend>()
-                .unwrap();
-            let container = Container::open(backend, options).unwrap();
-
-            let mut archive = Container::open_service::<ArchiveFactory>(container, false).unwrap();
+            let mut archive = open_archive(backend);
             let container = archive.as_ref();
 
             let cipher = container.info().unwrap().cipher;
@@ -91,24 +116,22 @@ macro_rules! make_test {
             let top_id = container.top_id().unwrap();
             assert_eq!(top_id.to_string(), "1");
 
-            let mut entry = archive.first().unwrap().unwrap();
-            assert!(entry.is_file());
-            assert_eq!(entry.name(), "f1");
-            assert_eq!(
-                entry.as_file_mut().unwrap().read_vec().unwrap(),
-                b"content of f1"
-            );
+            assert!(archive.needs_migration());
+            assert_content(&mut archive);
+
+            archive.migrate().unwrap();
+
+            assert!(!archive.needs_migration());
+            assert_content(&mut archive);
 
-            let entry = entry.next().unwrap().unwrap();
-            assert!(entry.is_directory());
-            assert_eq!(entry.name(), "f2");
+            let backend = archive.into_container().unwrap().into_backend();
+            let mut archive = open_archive(backend);
 
-            let entry = entry.next().unwrap().unwrap();
-            assert!(entry.is_symlink());
-            assert_eq!(entry.name(), "f3");
-            assert_eq!(entry.as_symlink().unwrap().target(), "f1");
+            assert!(!archive.needs_migration());
+            assert_content(&mut archive);
 
-            assert!(entry.next().is_none());
+            archive.append_file("f4").build().unwrap();
+            assert_eq!(archive.info().files, 4);
         }
     };
 }
@@ -0,0 +1,179 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use chrono::{TimeZone, Utc};
+use nuts_archive::{Archive, ArchiveFactory, Error, Group};
+use nuts_container::{Cipher, Container, CreateOptionsBuilder, OpenOptionsBuilder};
+use nuts_directory::{CreateOptions, DirectoryBackend, OpenOptions};
+use tempfile::{Builder, TempDir};
+
+fn setup_archive() -> TempDir {
+    let tmp_dir = Builder::new().prefix("nuts-archive").tempdir().unwrap();
+
+    let backend_options = CreateOptions::for_path(tmp_dir.path().to_owned());
+    let container_options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"123".to_vec()))
+        .build::<DirectoryBackend<&TempDir>>()
+        .unwrap();
+    let container = Container::create(backend_options, container_options).unwrap();
+
+    Container::create_service::<ArchiveFactory>(container).unwrap();
+
+    tmp_dir
+}
+
+fn open_archive(dir: TempDir) -> Archive<DirectoryBackend<TempDir>> {
+    let backend_options = OpenOptions::for_path(dir);
+    let container_options = OpenOptionsBuilder::new()
+        .with_password_callback(|| Ok(b"123".to_vec()))
+        .build::<DirectoryBackend<TempDir>>()
+        .unwrap();
+    let container = Container::open(backend_options, container_options).unwrap();
+
+    Container::open_service::<ArchiveFactory>(container, false).unwrap()
+}
+
+fn reopen_archive(
+    archive: Archive<DirectoryBackend<TempDir>>,
+) -> Archive<DirectoryBackend<TempDir>> {
+    let container = archive.into_container().unwrap();
+    Container::open_service::<ArchiveFactory>(container, false).unwrap()
+}
+
+#[test]
+fn no_such_entry() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    assert!(archive.lookup_mut("f1").is_none());
+
+    archive.append_file("f1").build().unwrap();
+
+    assert!(archive.lookup_mut("f2").is_none());
+}
+
+#[test]
+fn chmod() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    archive.append_file("f1").build().unwrap();
+
+    {
+        let mut handle = archive.lookup_mut("f1").unwrap().unwrap();
+
+        assert!(handle.is_file());
+        assert!(handle.can_execute(Group::User));
+        assert!(!handle.can_write(Group::Other));
+
+        handle.set_executable(Group::User, false);
+        handle.set_writable(Group::Other, true);
+        handle.set_readable(Group::Group, false);
+        handle.flush().unwrap();
+    }
+
+    let mut archive = reopen_archive(archive);
+    let entry = archive.lookup("f1").unwrap().unwrap();
+
+    assert!(entry.is_file());
+    assert!(!entry.can_execute(Group::User));
+    assert!(entry.can_write(Group::Other));
+    assert!(!entry.can_read(Group::Group));
+}
+
+#[test]
+fn touch() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    archive.append_directory("d1").build().unwrap();
+
+    let created = Utc.timestamp_opt(1, 0).unwrap();
+    let changed = Utc.timestamp_opt(2, 0).unwrap();
+    let modified = Utc.timestamp_opt(3, 0).unwrap();
+
+    {
+        let mut handle = archive.lookup_mut("d1").unwrap().unwrap();
+
+        handle.set_created(created);
+        handle.set_changed(changed);
+        handle.set_modified(modified);
+        handle.flush().unwrap();
+    }
+
+    let mut archive = reopen_archive(archive);
+    let entry = archive.lookup("d1").unwrap().unwrap();
+
+    assert!(entry.is_directory());
+    assert_eq!(*entry.created(), created);
+    assert_eq!(*entry.changed(), changed);
+    assert_eq!(*entry.modified(), modified);
+}
+
+#[test]
+fn rename() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    {
+        let mut entry = archive.append_file("f1").build().unwrap();
+        entry.write_all(&[1; 1000]).unwrap();
+    }
+
+    archive.append_symlink("f2", "f1").build().unwrap();
+
+    {
+        let mut handle = archive.lookup_mut("f1").unwrap().unwrap();
+
+        handle.set_name("f3");
+        handle.flush().unwrap();
+    }
+
+    let mut archive = reopen_archive(archive);
+
+    assert!(archive.lookup("f1").is_none());
+
+    let entry = archive.lookup("f3").unwrap().unwrap();
+    assert_eq!(entry.into_file().unwrap().read_vec().unwrap(), [1; 1000]);
+
+    let entry = archive.lookup("f2").unwrap().unwrap();
+    assert_eq!(entry.into_symlink().unwrap().target(), "f1");
+
+    assert_eq!(archive.info().files, 2);
+}
+
+#[test]
+fn snapshotted() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    archive.append_file("f1").build().unwrap();
+    archive.snapshot("s1").unwrap();
+    archive.append_file("f2").build().unwrap();
+
+    let err = archive.lookup_mut("f1").unwrap().err().unwrap();
+    assert!(matches!(err, Error::Snapshotted(name) if name == "f1"));
+
+    let mut handle = archive.lookup_mut("f2").unwrap().unwrap();
+    handle.set_name("f3");
+    handle.flush().unwrap();
+}
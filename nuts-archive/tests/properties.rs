@@ -0,0 +1,157 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_archive::{Archive, ArchiveFactory, Error};
+use nuts_container::{Cipher, Container, CreateOptionsBuilder, OpenOptionsBuilder};
+use nuts_directory::{CreateOptions, DirectoryBackend, OpenOptions};
+use tempfile::{Builder, TempDir};
+
+fn setup_archive() -> TempDir {
+    let tmp_dir = Builder::new().prefix("nuts-archive").tempdir().unwrap();
+
+    let backend_options = CreateOptions::for_path(tmp_dir.path().to_owned());
+    let container_options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"123".to_vec()))
+        .build::<DirectoryBackend<&TempDir>>()
+        .unwrap();
+    let container = Container::create(backend_options, container_options).unwrap();
+
+    Container::create_service::<ArchiveFactory>(container).unwrap();
+
+    tmp_dir
+}
+
+fn open_archive(dir: TempDir) -> Archive<DirectoryBackend<TempDir>> {
+    let backend_options = OpenOptions::for_path(dir);
+    let container_options = OpenOptionsBuilder::new()
+        .with_password_callback(|| Ok(b"123".to_vec()))
+        .build::<DirectoryBackend<TempDir>>()
+        .unwrap();
+    let container = Container::open(backend_options, container_options).unwrap();
+
+    Container::open_service::<ArchiveFactory>(container, false).unwrap()
+}
+
+fn reopen_archive(
+    archive: Archive<DirectoryBackend<TempDir>>,
+) -> Archive<DirectoryBackend<TempDir>> {
+    let container = archive.into_container().unwrap();
+    Container::open_service::<ArchiveFactory>(container, false).unwrap()
+}
+
+fn props(archive: &Archive<DirectoryBackend<TempDir>>) -> Vec<(&str, &str)> {
+    archive
+        .properties()
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect()
+}
+
+#[test]
+fn empty() {
+    let tmp_dir = setup_archive();
+    let archive = open_archive(tmp_dir);
+
+    assert!(archive.properties().is_empty());
+}
+
+#[test]
+fn set() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    archive.set_property("host", "foo").unwrap();
+    archive.set_property("job", "42").unwrap();
+    archive.set_property("host", "bar").unwrap();
+
+    assert_eq!(props(&archive), [("host", "bar"), ("job", "42")]);
+
+    let archive = reopen_archive(archive);
+    assert_eq!(props(&archive), [("host", "bar"), ("job", "42")]);
+}
+
+#[test]
+fn remove() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    archive.set_property("host", "foo").unwrap();
+    archive.set_property("job", "42").unwrap();
+
+    assert_eq!(archive.remove_property("host").unwrap().unwrap(), "foo");
+    assert!(archive.remove_property("host").unwrap().is_none());
+
+    let mut archive = reopen_archive(archive);
+    assert_eq!(props(&archive), [("job", "42")]);
+
+    assert_eq!(archive.remove_property("job").unwrap().unwrap(), "42");
+
+    let archive = reopen_archive(archive);
+    assert!(archive.properties().is_empty());
+}
+
+#[test]
+fn multiple_blocks() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    archive.append_file("f1").build().unwrap();
+
+    for i in 0..100 {
+        archive
+            .set_property(format!("key{:03}", i), "x".repeat(100))
+            .unwrap();
+    }
+
+    let mut archive = reopen_archive(archive);
+
+    assert_eq!(archive.properties().len(), 100);
+    assert!(archive.properties().values().all(|v| *v == "x".repeat(100)));
+
+    for i in 0..99 {
+        archive.remove_property(format!("key{:03}", i)).unwrap();
+    }
+
+    let mut archive = reopen_archive(archive);
+
+    assert_eq!(props(&archive), [("key099", "x".repeat(100).as_str())]);
+    assert_eq!(archive.lookup("f1").unwrap().unwrap().name(), "f1");
+}
+
+#[test]
+fn too_large() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    archive.set_property("host", "foo").unwrap();
+
+    let err = archive.set_property("big", "x".repeat(1000)).unwrap_err();
+    assert!(matches!(err, Error::InvalidProperty(key) if key == "big"));
+
+    let err = archive.set_property("host", "x".repeat(1000)).unwrap_err();
+    assert!(matches!(err, Error::InvalidProperty(key) if key == "host"));
+
+    assert_eq!(props(&archive), [("host", "foo")]);
+
+    let archive = reopen_archive(archive);
+    assert_eq!(props(&archive), [("host", "foo")]);
+}
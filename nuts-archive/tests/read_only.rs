@@ -0,0 +1,131 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_archive::{Archive, ArchiveFactory, Error};
+use nuts_container::{Cipher, Container, CreateOptionsBuilder, OpenOptionsBuilder};
+use nuts_directory::{CreateOptions, DirectoryBackend, OpenOptions};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::{Builder, TempDir};
+
+fn setup_archive() -> TempDir {
+    let tmp_dir = Builder::new().prefix("nuts-archive").tempdir().unwrap();
+
+    let backend_options = CreateOptions::for_path(tmp_dir.path().to_owned());
+    let container_options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"123".to_vec()))
+        .build::<DirectoryBackend<&TempDir>>()
+        .unwrap();
+    let container = Container::create(backend_options, container_options).unwrap();
+    let mut archive = Container::create_service::<ArchiveFactory>(container).unwrap();
+
+    let mut entry = archive.append_file("f1").build().unwrap();
+    entry.write_all(b"abc").unwrap();
+    entry.flush().unwrap();
+    drop(entry);
+
+    archive.set_property("host", "foo").unwrap();
+
+    tmp_dir
+}
+
+fn open_archive(dir: &TempDir) -> Archive<DirectoryBackend<PathBuf>> {
+    let backend_options = OpenOptions::for_path(dir.path().to_owned());
+    let container_options = OpenOptionsBuilder::new()
+        .with_password_callback(|| Ok(b"123".to_vec()))
+        .read_only(true)
+        .build::<DirectoryBackend<PathBuf>>()
+        .unwrap();
+    let container = Container::open(backend_options, container_options).unwrap();
+
+    Container::open_service::<ArchiveFactory>(container, true).unwrap()
+}
+
+fn snapshot(path: &Path) -> Vec<(String, Vec<u8>)> {
+    let mut files = vec![];
+    let mut dirs = vec![path.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                let content = fs::read(&path).unwrap();
+                files.push((path.display().to_string(), content));
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+fn assert_read_only<T: std::fmt::Debug>(result: Result<T, Error<DirectoryBackend<PathBuf>>>) {
+    let err = result.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Container(nuts_container::Error::ReadOnly)
+    ));
+}
+
+#[test]
+fn read() {
+    let tmp_dir = setup_archive();
+    let before = snapshot(tmp_dir.path());
+
+    {
+        let mut archive = open_archive(&tmp_dir);
+
+        assert_eq!(archive.info().files, 1);
+        assert_eq!(archive.properties().get("host").unwrap(), "foo");
+
+        let mut entry = archive.lookup("f1").unwrap().unwrap().into_file().unwrap();
+        assert_eq!(entry.read_vec().unwrap(), b"abc");
+    }
+
+    assert_eq!(snapshot(tmp_dir.path()), before);
+}
+
+#[test]
+fn modify() {
+    let tmp_dir = setup_archive();
+    let before = snapshot(tmp_dir.path());
+
+    {
+        let mut archive = open_archive(&tmp_dir);
+
+        assert_read_only(archive.append_file("f2").build().map(|_| ()));
+        assert_read_only(archive.append_directory("d1").build());
+        assert_read_only(archive.set_property("host", "bar"));
+        assert_read_only(archive.remove_property("host"));
+        assert_read_only(archive.snapshot("s1"));
+    }
+
+    assert_eq!(snapshot(tmp_dir.path()), before);
+
+    let mut archive = open_archive(&tmp_dir);
+    assert_eq!(archive.info().files, 1);
+    assert_eq!(archive.properties().get("host").unwrap(), "foo");
+    assert!(archive.snapshots().unwrap().is_empty());
+}
@@ -0,0 +1,195 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_archive::{Archive, ArchiveFactory, Error};
+use nuts_container::{Cipher, Container, CreateOptionsBuilder, OpenOptionsBuilder};
+use nuts_directory::{CreateOptions, DirectoryBackend, OpenOptions};
+use tempfile::{Builder, TempDir};
+
+fn setup_archive() -> TempDir {
+    let tmp_dir = Builder::new().prefix("nuts-archive").tempdir().unwrap();
+
+    let backend_options = CreateOptions::for_path(tmp_dir.path().to_owned());
+    let container_options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"123".to_vec()))
+        .build::<DirectoryBackend<&TempDir>>()
+        .unwrap();
+    let container = Container::create(backend_options, container_options).unwrap();
+
+    Container::create_service::<ArchiveFactory>(container).unwrap();
+
+    tmp_dir
+}
+
+fn open_archive(dir: TempDir) -> Archive<DirectoryBackend<TempDir>> {
+    let backend_options = OpenOptions::for_path(dir);
+    let container_options = OpenOptionsBuilder::new()
+        .with_password_callback(|| Ok(b"123".to_vec()))
+        .build::<DirectoryBackend<TempDir>>()
+        .unwrap();
+    let container = Container::open(backend_options, container_options).unwrap();
+
+    Container::open_service::<ArchiveFactory>(container, false).unwrap()
+}
+
+fn reopen_archive(
+    archive: Archive<DirectoryBackend<TempDir>>,
+) -> Archive<DirectoryBackend<TempDir>> {
+    let container = archive.into_container().unwrap();
+    Container::open_service::<ArchiveFactory>(container, false).unwrap()
+}
+
+fn names(archive: &mut Archive<DirectoryBackend<TempDir>>) -> Vec<String> {
+    archive
+        .snapshots()
+        .unwrap()
+        .into_iter()
+        .map(|info| info.name)
+        .collect()
+}
+
+#[test]
+fn no_snapshots() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    assert!(archive.snapshots().unwrap().is_empty());
+
+    let err = archive.open_snapshot("s1").err().unwrap();
+    assert!(matches!(err, Error::NoSuchSnapshot(name) if name == "s1"));
+
+    let err = archive.remove_snapshot("s1").unwrap_err();
+    assert!(matches!(err, Error::NoSuchSnapshot(name) if name == "s1"));
+}
+
+#[test]
+fn empty_snapshot() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    archive.snapshot("s1").unwrap();
+    archive.append_file("f1").build().unwrap();
+
+    let mut archive = reopen_archive(archive);
+    let mut snapshot = archive.open_snapshot("s1").unwrap();
+
+    assert_eq!(snapshot.name(), "s1");
+    assert_eq!(snapshot.info().blocks, 0);
+    assert_eq!(snapshot.info().files, 0);
+    assert!(snapshot.first().is_none());
+}
+
+#[test]
+fn shared_entries() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    {
+        // more than 12 blocks, the snapshot shares an indirect node
+        let mut entry = archive.append_file("f1").build().unwrap();
+        entry.write_all(&[1; 20 * 512]).unwrap();
+    }
+
+    archive.snapshot("s1").unwrap();
+
+    {
+        let mut entry = archive.append_file("f2").build().unwrap();
+        entry.write_all(&[2; 512]).unwrap();
+    }
+
+    archive.snapshot("s2").unwrap();
+
+    let mut archive = reopen_archive(archive);
+
+    assert_eq!(archive.info().files, 2);
+    assert_eq!(names(&mut archive), ["s1", "s2"]);
+
+    {
+        let mut snapshot = archive.open_snapshot("s1").unwrap();
+
+        assert_eq!(snapshot.info().files, 1);
+        assert!(snapshot.lookup("f2").is_none());
+
+        let entry = snapshot.first().unwrap().unwrap();
+        assert_eq!(entry.name(), "f1");
+        assert_eq!(
+            entry.into_file().unwrap().read_vec().unwrap(),
+            [1; 20 * 512]
+        );
+    }
+
+    {
+        let mut snapshot = archive.open_snapshot("s2").unwrap();
+
+        assert_eq!(snapshot.info().files, 2);
+
+        let entry = snapshot.lookup("f2").unwrap().unwrap();
+        assert_eq!(entry.into_file().unwrap().read_vec().unwrap(), [2; 512]);
+    }
+}
+
+#[test]
+fn duplicate() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    archive.snapshot("s1").unwrap();
+
+    let err = archive.snapshot("s1").unwrap_err();
+    assert!(matches!(err, Error::SnapshotExists(name) if name == "s1"));
+
+    assert_eq!(names(&mut archive), ["s1"]);
+}
+
+#[test]
+fn remove() {
+    let tmp_dir = setup_archive();
+    let mut archive = open_archive(tmp_dir);
+
+    for name in ["s1", "s2", "s3", "s4"] {
+        archive.append_directory(name).build().unwrap();
+        archive.snapshot(name).unwrap();
+    }
+
+    // in the middle
+    archive.remove_snapshot("s2").unwrap();
+    assert_eq!(names(&mut archive), ["s1", "s3", "s4"]);
+
+    // the newest snapshot (head of list)
+    archive.remove_snapshot("s4").unwrap();
+    assert_eq!(names(&mut archive), ["s1", "s3"]);
+
+    // the oldest snapshot (tail of list)
+    archive.remove_snapshot("s1").unwrap();
+    assert_eq!(names(&mut archive), ["s3"]);
+
+    let mut archive = reopen_archive(archive);
+    assert_eq!(names(&mut archive), ["s3"]);
+
+    let mut snapshot = archive.open_snapshot("s3").unwrap();
+    assert_eq!(snapshot.info().files, 3);
+    assert!(snapshot.lookup("s3").is_some());
+
+    archive.remove_snapshot("s3").unwrap();
+    assert!(archive.snapshots().unwrap().is_empty());
+    assert_eq!(archive.info().files, 4);
+}
@@ -0,0 +1,86 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::Backend;
+
+/// A boxed [`Future`], which can be sent to another thread.
+///
+/// The [`AsyncBackend`] trait returns its futures in this type.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Asynchronous flavor of the [`Backend`] trait.
+///
+/// A backend implements this trait, if the block-related operations can be
+/// performed without blocking the executor. Each method has the same semantic
+/// as its synchronous counterpart of the [`Backend`] trait but returns a
+/// [`BoxFuture`], which resolves to the result of the operation.
+///
+/// Creating and opening a backend is still performed by the synchronous
+/// [`Create`](crate::Create) and [`Open`](crate::Open) traits.
+pub trait AsyncBackend: Backend + Send {
+    /// Aquires a new block in the backend.
+    ///
+    /// See [`Backend::aquire`] for details.
+    ///
+    /// # Errors
+    ///
+    /// On any error a self-defined [`Backend::Err`] is returned.
+    fn aquire_async<'a>(&'a mut self, buf: &'a [u8]) -> BoxFuture<'a, Result<Self::Id, Self::Err>>;
+
+    /// Releases a block again.
+    ///
+    /// See [`Backend::release`] for details.
+    ///
+    /// # Errors
+    ///
+    /// On any error a self-defined [`Backend::Err`] is returned.
+    fn release_async(&mut self, id: Self::Id) -> BoxFuture<'_, Result<(), Self::Err>>;
+
+    /// Reads a block from the backend.
+    ///
+    /// See [`Backend::read`] for details.
+    ///
+    /// # Errors
+    ///
+    /// On any error a self-defined [`Backend::Err`] is returned.
+    fn read_async<'a>(
+        &'a mut self,
+        id: &'a Self::Id,
+        buf: &'a mut [u8],
+    ) -> BoxFuture<'a, Result<usize, Self::Err>>;
+
+    /// Writes a block into the backend.
+    ///
+    /// See [`Backend::write`] for details.
+    ///
+    /// # Errors
+    ///
+    /// On any error a self-defined [`Backend::Err`] is returned.
+    fn write_async<'a>(
+        &'a mut self,
+        id: &'a Self::Id,
+        buf: &'a [u8],
+    ) -> BoxFuture<'a, Result<usize, Self::Err>>;
+}
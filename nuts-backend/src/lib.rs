@@ -47,14 +47,43 @@
 //!
 //! The final [`Open::build()`] call creates the backend instance, which is
 //! used by the container.
+//!
+//! # Locking
+//!
+//! Once the backend instance is available, the container
+//! [locks](Backend::lock) it. A read-only container takes a
+//! [shared](LockMode::Shared) lock, a writable container an
+//! [exclusive](LockMode::Exclusive) lock.
+//!
+//! # Asynchronous backends
+//!
+//! With the `async` feature enabled, a backend can implement the
+//! `AsyncBackend` trait. It is an asynchronous flavor of the block-related
+//! operations of the [`Backend`] trait.
+
+#[cfg(feature = "async")]
+mod asynchronous;
 
 use std::error;
 use std::fmt::Display;
 use std::str::FromStr;
 
+#[cfg(feature = "async")]
+pub use asynchronous::{AsyncBackend, BoxFuture};
+
 // The maximun size of the header.
 pub const HEADER_MAX_SIZE: usize = 512;
 
+/// The mode of a lock, see [`Backend::lock()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockMode {
+    /// A shared lock, which can be held by several readers at the same time.
+    Shared,
+
+    /// An exclusive lock, which can be held by a single writer only.
+    Exclusive,
+}
+
 /// Trait for binary conversion.
 ///
 /// * [`Self::as_bytes`] is used to create a binary representation of this
@@ -97,6 +126,22 @@ pub trait ReceiveHeader<B: Backend> {
     ///
     /// The method should put the data into the `bytes` slice.
     fn get_header_bytes(&mut self, bytes: &mut [u8; HEADER_MAX_SIZE]) -> Result<(), B::Err>;
+
+    /// Receives the binary data of the backup header from the backend.
+    ///
+    /// A backend can keep a second copy of the header at another location.
+    /// The container falls back to the backup header, if the header received
+    /// by [`ReceiveHeader::get_header_bytes()`] is not usable.
+    ///
+    /// The method should put the data into the `bytes` slice and return
+    /// `true`. If there is no backup header, `false` is returned. The default
+    /// implementation has no backup header.
+    fn get_backup_header_bytes(
+        &mut self,
+        _bytes: &mut [u8; HEADER_MAX_SIZE],
+    ) -> Result<bool, B::Err> {
+        Ok(false)
+    }
 }
 
 /// Trait to configure the creation of a [`Backend`].
@@ -134,7 +179,11 @@ pub trait Create<B: Backend> {
     ///
     /// The `header` argument contains the binary data of the (possibly
     /// encrypted) header of the container. The method should persist the
-    /// header in the backend.
+    /// header in the backend. A backend, which supports
+    /// [locking](Backend::lock), should take an
+    /// [exclusive](LockMode::Exclusive) lock before the header is written.
+    /// Thus two processes creating the same backend cannot clobber each
+    /// other.
     ///
     /// If `overwrite` is `true`, then an existing backend instance should be
     /// overwritten. If `overwrite` is set to `false` and the requested backend
@@ -156,7 +205,18 @@ pub trait Create<B: Backend> {
 /// [`Backend`] instance. The resulting backend instance should be able to
 /// handle all operations on it. The [`Open::build()`] method should validate
 /// all its settings before returning the backend instance!
+///
+/// If the container is opened read-only, the container calls
+/// [`Open::set_read_only()`] before the header is received.
 pub trait Open<B: Backend>: ReceiveHeader<B> {
+    /// Tells the builder whether the backend is opened read-only.
+    ///
+    /// A read-only backend is never modified by the container. The backend
+    /// can use the information to open its resources read-only, e.g. files
+    /// or credentials with read-only access, and should reject any
+    /// modification. The default implementation ignores the information.
+    fn set_read_only(&mut self, _read_only: bool) {}
+
     /// Create an instance of the [`Backend`].
     ///
     /// The container calls [`Create::build()`] to create an instance of the
@@ -280,6 +340,53 @@ where
     /// On any error a self-defined [`Backend::Err`] is returned.
     fn write(&mut self, id: &Self::Id, buf: &[u8]) -> Result<usize, Self::Err>;
 
+    /// Reads several blocks from the backend.
+    ///
+    /// Reads the blocks with the given `ids` and returns their data in the
+    /// same order. Each entry holds the data of one block, which cannot be
+    /// larger than the [block-size](Backend::block_size).
+    ///
+    /// A backend, which is able to read several blocks more efficient than
+    /// block by block, should override the method. The default implementation
+    /// calls [`Backend::read()`] for each block.
+    ///
+    /// # Errors
+    ///
+    /// On any error a self-defined [`Backend::Err`] is returned.
+    fn read_many(&mut self, ids: &[Self::Id]) -> Result<Vec<Vec<u8>>, Self::Err> {
+        let block_size = self.block_size() as usize;
+
+        ids.iter()
+            .map(|id| {
+                let mut buf = vec![0; block_size];
+                let n = self.read(id, &mut buf)?;
+
+                buf.truncate(n);
+
+                Ok(buf)
+            })
+            .collect()
+    }
+
+    /// Writes several blocks into the backend.
+    ///
+    /// Each entry of `blocks` contains the id of the block and the data to be
+    /// written. The same rules as for [`Backend::write()`] apply to each
+    /// block. The method returns the number of bytes actually written for
+    /// each block, in the same order.
+    ///
+    /// A backend, which is able to write several blocks more efficient than
+    /// block by block, should override the method. The default implementation
+    /// calls [`Backend::write()`] for each block.
+    ///
+    /// # Errors
+    ///
+    /// On any error a self-defined [`Backend::Err`] is returned. Blocks in
+    /// front of the failed block might be written already.
+    fn write_many(&mut self, blocks: &[(Self::Id, &[u8])]) -> Result<Vec<usize>, Self::Err> {
+        blocks.iter().map(|(id, buf)| self.write(id, buf)).collect()
+    }
+
     /// Puts the given `buf` into the header of the backend.
     ///
     /// The container uses this method to ask the backend to put data into the
@@ -288,8 +395,65 @@ where
     /// [`HEADER_MAX_SIZE`] bytes can be stored in the header.
     fn write_header(&mut self, buf: &[u8; HEADER_MAX_SIZE]) -> Result<(), Self::Err>;
 
+    /// Puts the given `buf` into the backup header of the backend.
+    ///
+    /// The container writes the header twice: first with
+    /// [`Backend::write_header`], then with this method. A backend, which is
+    /// able to keep a second copy of the header, should store it at a
+    /// location different from the header. It is received again with
+    /// [`ReceiveHeader::get_backup_header_bytes()`].
+    ///
+    /// The default implementation has no backup header and does nothing.
+    fn write_backup_header(&mut self, _buf: &[u8; HEADER_MAX_SIZE]) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
+    /// Makes all previous modifications durable.
+    ///
+    /// A backend, which does not write its data to persistent storage
+    /// immediately, should do it now. The container flushes the backend, when
+    /// a transaction is committed and on request of the application.
+    ///
+    /// The default implementation does nothing.
+    fn flush(&mut self) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
+    /// Locks the backend against concurrent access of other processes.
+    ///
+    /// The container takes a [shared](LockMode::Shared) lock, if it is
+    /// opened read-only, otherwise an [exclusive](LockMode::Exclusive) lock.
+    /// The lock is held until the backend is dropped. Locking an already
+    /// locked backend again replaces the lock. The container locks the
+    /// backend exclusively also after it was [created](Create::build), which
+    /// should keep a lock taken there.
+    ///
+    /// If the lock is held by someone else in a conflicting mode, the method
+    /// should not wait but fail immediately.
+    ///
+    /// The default implementation does not lock at all.
+    fn lock(&mut self, _mode: LockMode) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
     /// Deletes the entire instance and all traces.
     ///
     /// The method must not fail!
     fn delete(self);
 }
+
+/// Trait for backends, which are able to read blocks concurrently.
+///
+/// A backend implements this trait, if a block can be read through a shared
+/// reference. This allows several threads to read from the backend at the
+/// same time.
+pub trait SharedRead: Backend {
+    /// Reads a block from the backend through a shared reference.
+    ///
+    /// The method has the same semantic as [`Backend::read`].
+    ///
+    /// # Errors
+    ///
+    /// On any error a self-defined [`Backend::Err`] is returned.
+    fn read_shared(&self, id: &Self::Id, buf: &mut [u8]) -> Result<usize, Self::Err>;
+}
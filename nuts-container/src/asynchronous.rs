@@ -0,0 +1,173 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::{AsyncBackend, Binary};
+
+use crate::error::{ContainerResult, Error};
+use crate::{Container, IntegrityError, JournalError};
+
+/// Asynchronous flavor of a [`Container`].
+///
+/// An `AsyncContainer` wraps a [`Container`] and performs the block-related
+/// operations on top of an [`AsyncBackend`]. The container is still
+/// [created](Container::create) resp. [opened](Container::open)
+/// synchronously and then passed to [`AsyncContainer::new`].
+///
+/// [Transactions](Container::begin) and
+/// [rollback protection](crate::CreateOptionsBuilder::with_rollback_protection)
+/// are not available for an `AsyncContainer`.
+#[derive(Debug)]
+pub struct AsyncContainer<B: AsyncBackend> {
+    container: Container<B>,
+}
+
+impl<B: AsyncBackend> AsyncContainer<B> {
+    /// Creates a new `AsyncContainer`, which wraps the given `container`.
+    ///
+    /// # Errors
+    ///
+    /// If the container has an active [transaction](Container::begin), a
+    /// [`JournalError::Active`] error is returned. For a container with
+    /// rollback protection an [`IntegrityError::Unsupported`] error is
+    /// returned.
+    pub fn new(container: Container<B>) -> ContainerResult<AsyncContainer<B>, B> {
+        if container.in_transaction() {
+            return Err(JournalError::Active.into());
+        }
+
+        if container.integrity.is_some() {
+            return Err(IntegrityError::Unsupported.into());
+        }
+
+        Ok(AsyncContainer { container })
+    }
+
+    /// Returns the wrapped container.
+    pub fn container(&self) -> &Container<B> {
+        &self.container
+    }
+
+    /// Consumes this `AsyncContainer`, returning the wrapped container.
+    pub fn into_container(self) -> Container<B> {
+        self.container
+    }
+
+    /// Returns the (net) block size of the container.
+    ///
+    /// See [`Container::block_size`] for details.
+    pub fn block_size(&self) -> u32 {
+        self.container.block_size()
+    }
+
+    /// Aquires a new block in the container.
+    ///
+    /// See [`Container::aquire`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Errors are listed in the [`Error`] type.
+    pub async fn aquire(&mut self) -> ContainerResult<B::Id, B> {
+        self.container.writable()?;
+
+        let header = &self.container.header;
+        let mut ctx = self.container.ciphers.get();
+
+        let ctext = if header.cipher().mac_digest().is_some() {
+            // zero placeholder, see Container::aquire_block()
+            ctx.inp_mut(self.container.backend().block_size() as usize)
+        } else {
+            ctx.copy_from_slice(self.container.block_size() as usize, &[]);
+            ctx.encrypt(header.key(), header.iv())?
+        };
+
+        self.container
+            .backend_mut()
+            .aquire_async(ctext)
+            .await
+            .map_err(Error::Backend)
+    }
+
+    /// Releases a block again.
+    ///
+    /// See [`Container::release`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Errors are listed in the [`Error`] type.
+    pub async fn release(&mut self, id: B::Id) -> ContainerResult<(), B> {
+        self.container.writable()?;
+
+        self.container
+            .backend_mut()
+            .release_async(id)
+            .await
+            .map_err(Error::Backend)
+    }
+
+    /// Reads a block from the container.
+    ///
+    /// See [`Container::read`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Errors are listed in the [`Error`] type.
+    pub async fn read(&mut self, id: &B::Id, buf: &mut [u8]) -> ContainerResult<usize, B> {
+        if let Some(n) = self.container.read_pending(id, buf) {
+            return Ok(n);
+        }
+
+        let backend = self.container.backend.as_mut().unwrap();
+        let mut ctx = self.container.ciphers.get();
+
+        let ctext = ctx.inp_mut(backend.block_size() as usize);
+        backend
+            .read_async(id, ctext)
+            .await
+            .map_err(Error::Backend)?;
+
+        self.container.decrypt_block(&mut ctx, id, buf)
+    }
+
+    /// Writes a block into the container.
+    ///
+    /// See [`Container::write`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Errors are listed in the [`Error`] type.
+    pub async fn write(&mut self, id: &B::Id, buf: &[u8]) -> ContainerResult<usize, B> {
+        self.container.writable()?;
+
+        let header = &self.container.header;
+        let mut ctx = self.container.ciphers.get();
+        let len = ctx.copy_from_slice(self.container.block_size() as usize, buf);
+
+        let ctext = ctx.encrypt_with_ad(header.key(), header.iv(), &id.as_bytes())?;
+
+        self.container
+            .backend_mut()
+            .write_async(id, ctext)
+            .await
+            .map_err(Error::Backend)
+            .map(|_| len)
+    }
+}
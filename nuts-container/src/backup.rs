@@ -0,0 +1,96 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use nuts_backend::HEADER_MAX_SIZE;
+use thiserror::Error;
+
+use crate::buffer::{Buffer, BufferError, BufferMut};
+use crate::crypto::{self, CryptoError};
+use crate::digest::Digest;
+
+const MAGIC: u32 = 0x6e68_626b; // nhbk
+const DIGEST: Digest = Digest::Sha256;
+const DATA_SIZE: usize = 4 + HEADER_MAX_SIZE;
+
+/// Errors coming from a header backup.
+#[derive(Debug, Error)]
+pub enum BackupError {
+    /// Invalid header backup, could not validate size or magic.
+    #[error("invalid header backup")]
+    InvalidBackup,
+
+    /// The checksum does not match, the header backup is corrupted.
+    #[error("checksum mismatch, the header backup is corrupted")]
+    InvalidChecksum,
+
+    /// Could not calculate the checksum.
+    #[error(transparent)]
+    Crypto(#[from] CryptoError),
+
+    /// Error while (de-) serializing binary data.
+    #[error(transparent)]
+    Buffer(#[from] BufferError),
+}
+
+fn checksum(data: &[u8]) -> Result<Vec<u8>, BackupError> {
+    Ok(crypto::hash(DIGEST, data)?)
+}
+
+/// Creates a backup of the given (encrypted) `header` bytes.
+///
+/// The backup contains a magic, the header bytes and a checksum over both.
+pub fn encode(header: &[u8; HEADER_MAX_SIZE]) -> Result<Vec<u8>, BackupError> {
+    let mut buf = vec![];
+
+    buf.put_u32(MAGIC)?;
+    buf.put_chunk(header)?;
+
+    let digest = checksum(&buf)?;
+    buf.put_chunk(&digest)?;
+
+    Ok(buf)
+}
+
+/// Extracts the header bytes from a `backup`.
+///
+/// The checksum of the backup is validated.
+pub fn decode(backup: &[u8]) -> Result<[u8; HEADER_MAX_SIZE], BackupError> {
+    if backup.len() != DATA_SIZE + DIGEST.size() {
+        return Err(BackupError::InvalidBackup);
+    }
+
+    let (data, digest) = backup.split_at(DATA_SIZE);
+    let mut buf = data;
+
+    if buf.get_u32()? != MAGIC {
+        return Err(BackupError::InvalidBackup);
+    }
+
+    if *checksum(data)? != *digest {
+        return Err(BackupError::InvalidChecksum);
+    }
+
+    Ok(buf.get_array::<HEADER_MAX_SIZE>()?)
+}
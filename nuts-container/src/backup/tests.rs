@@ -0,0 +1,98 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::HEADER_MAX_SIZE;
+
+use crate::backup::{decode, encode, BackupError};
+
+fn header() -> [u8; HEADER_MAX_SIZE] {
+    let mut header = [0; HEADER_MAX_SIZE];
+
+    header
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, n)| *n = i as u8);
+
+    header
+}
+
+#[test]
+fn encode_decode() {
+    let backup = encode(&header()).unwrap();
+
+    assert_eq!(backup.len(), 4 + HEADER_MAX_SIZE + 32);
+    assert_eq!(backup[..4], [0x6e, 0x68, 0x62, 0x6b]);
+    assert_eq!(backup[4..4 + HEADER_MAX_SIZE], header());
+
+    assert_eq!(decode(&backup).unwrap(), header());
+}
+
+#[test]
+fn decode_empty() {
+    let err = decode(&[]).unwrap_err();
+    assert!(matches!(err, BackupError::InvalidBackup));
+}
+
+#[test]
+fn decode_short() {
+    let backup = encode(&header()).unwrap();
+
+    let err = decode(&backup[..backup.len() - 1]).unwrap_err();
+    assert!(matches!(err, BackupError::InvalidBackup));
+}
+
+#[test]
+fn decode_long() {
+    let mut backup = encode(&header()).unwrap();
+    backup.push(0);
+
+    let err = decode(&backup).unwrap_err();
+    assert!(matches!(err, BackupError::InvalidBackup));
+}
+
+#[test]
+fn decode_inval_magic() {
+    let mut backup = encode(&header()).unwrap();
+    backup[0] += 1;
+
+    let err = decode(&backup).unwrap_err();
+    assert!(matches!(err, BackupError::InvalidBackup));
+}
+
+#[test]
+fn decode_inval_header() {
+    let mut backup = encode(&header()).unwrap();
+    backup[4] += 1;
+
+    let err = decode(&backup).unwrap_err();
+    assert!(matches!(err, BackupError::InvalidChecksum));
+}
+
+#[test]
+fn decode_inval_checksum() {
+    let mut backup = encode(&header()).unwrap();
+    let n = backup.len() - 1;
+    backup[n] ^= 0xff;
+
+    let err = decode(&backup).unwrap_err();
+    assert!(matches!(err, BackupError::InvalidChecksum));
+}
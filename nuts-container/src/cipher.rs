@@ -23,14 +23,15 @@
 #[cfg(test)]
 mod tests;
 
-use openssl::cipher as ossl_cipher;
-use openssl::cipher_ctx::CipherCtx;
-use openssl::error::ErrorStack;
+use std::ops::{Deref, DerefMut};
 use std::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError};
 use std::{cmp, fmt};
 use thiserror::Error;
 
 use crate::buffer::{Buffer, BufferError, BufferMut};
+use crate::crypto::{self, CryptoError};
+use crate::digest::Digest;
 use crate::svec::SecureVec;
 
 /// [`Cipher`] related error codes.
@@ -56,9 +57,16 @@ pub enum CipherError {
     #[error("the plaintext is not trustworthy")]
     NotTrustworthy,
 
-    /// An error in the OpenSSL library occured.
+    /// The message authentication code of a ciphertext mismatches.
+    ///
+    /// Raised by the [HMAC-ciphers](Cipher::mac_digest), if the ciphertext
+    /// was modified.
+    #[error("the authentication code mismatches, the ciphertext was modified")]
+    MacMismatch,
+
+    /// An error in the crypto provider occured.
     #[error(transparent)]
-    OpenSSL(#[from] ErrorStack),
+    Crypto(#[from] CryptoError),
 }
 
 /// Supported cipher algorithms.
@@ -84,30 +92,48 @@ pub enum Cipher {
 
     /// AES with a 256-bit key in GCM mode
     Aes256Gcm,
+
+    /// AES with a 128-bit key in CTR mode, authenticated with HMAC-SHA256
+    Aes128CtrHmac,
+
+    /// AES with a 192-bit key in CTR mode, authenticated with HMAC-SHA256
+    Aes192CtrHmac,
+
+    /// AES with a 256-bit key in CTR mode, authenticated with HMAC-SHA256
+    Aes256CtrHmac,
 }
 
 impl Cipher {
     /// Returns the block size of the cipher.
     pub fn block_size(&self) -> usize {
-        match self.to_openssl() {
-            None => 1,
-            Some(c) => c.block_size(),
-        }
+        // CTR and GCM turn AES into a stream cipher
+        1
     }
 
     /// Returns the key size of the cipher.
+    ///
+    /// For an [HMAC-cipher](Cipher::mac_digest) the key of the HMAC is
+    /// appended to the key of the cipher.
     pub fn key_len(&self) -> usize {
-        match self.to_openssl() {
-            None => 0,
-            Some(c) => c.key_length(),
-        }
+        let mac_len = self.mac_digest().map_or(0, |d| d.size());
+
+        let key_len = match self {
+            Cipher::None => 0,
+            Cipher::Aes128Ctr | Cipher::Aes128Gcm | Cipher::Aes128CtrHmac => 16,
+            Cipher::Aes192Ctr | Cipher::Aes192Gcm | Cipher::Aes192CtrHmac => 24,
+            Cipher::Aes256Ctr | Cipher::Aes256Gcm | Cipher::Aes256CtrHmac => 32,
+        };
+
+        key_len + mac_len
     }
 
     /// Returns the IV size of the cipher.
     pub fn iv_len(&self) -> usize {
-        match self.to_openssl() {
-            None => 0,
-            Some(c) => c.iv_length(),
+        match self {
+            Cipher::None => 0,
+            Cipher::Aes128Ctr | Cipher::Aes192Ctr | Cipher::Aes256Ctr => 16,
+            Cipher::Aes128CtrHmac | Cipher::Aes192CtrHmac | Cipher::Aes256CtrHmac => 16,
+            Cipher::Aes128Gcm | Cipher::Aes192Gcm | Cipher::Aes256Gcm => 12,
         }
     }
 
@@ -121,11 +147,31 @@ impl Cipher {
     /// Ciphertext and tag are both stored in a block of the container. Use
     /// this method to get the size of the tag. For a non-AE-cipher the
     /// tag-size is `0`.
+    ///
+    /// For an [HMAC-cipher](Cipher::mac_digest) the tag is the HMAC of the
+    /// ciphertext.
     pub fn tag_size(&self) -> u32 {
         match self {
             Cipher::None => 0,
             Cipher::Aes128Ctr | Cipher::Aes192Ctr | Cipher::Aes256Ctr => 0,
             Cipher::Aes128Gcm | Cipher::Aes192Gcm | Cipher::Aes256Gcm => 16,
+            Cipher::Aes128CtrHmac | Cipher::Aes192CtrHmac | Cipher::Aes256CtrHmac => {
+                self.mac_digest().map_or(0, |d| d.size() as u32)
+            }
+        }
+    }
+
+    /// Returns the message digest used to authenticate the ciphertext.
+    ///
+    /// The HMAC-ciphers follow the _Encrypt-then-MAC_ approach: the
+    /// ciphertext is authenticated with an HMAC, which is appended as a tag.
+    /// Returns [`None`] if the cipher is not an HMAC-cipher.
+    pub fn mac_digest(&self) -> Option<Digest> {
+        match self {
+            Cipher::Aes128CtrHmac | Cipher::Aes192CtrHmac | Cipher::Aes256CtrHmac => {
+                Some(Digest::Sha256)
+            }
+            _ => None,
         }
     }
 
@@ -140,6 +186,9 @@ impl Cipher {
             4 => Ok(Cipher::Aes256Ctr),
             5 => Ok(Cipher::Aes192Gcm),
             6 => Ok(Cipher::Aes256Gcm),
+            7 => Ok(Cipher::Aes128CtrHmac),
+            8 => Ok(Cipher::Aes192CtrHmac),
+            9 => Ok(Cipher::Aes256CtrHmac),
             _ => Err(BufferError::InvalidIndex("Cipher".to_string(), b)),
         }
     }
@@ -153,22 +202,13 @@ impl Cipher {
             Cipher::Aes256Ctr => 4,
             Cipher::Aes192Gcm => 5,
             Cipher::Aes256Gcm => 6,
+            Cipher::Aes128CtrHmac => 7,
+            Cipher::Aes192CtrHmac => 8,
+            Cipher::Aes256CtrHmac => 9,
         };
 
         buf.put_u32(b)
     }
-
-    fn to_openssl(self) -> Option<&'static ossl_cipher::CipherRef> {
-        match self {
-            Cipher::None => None,
-            Cipher::Aes128Ctr => Some(ossl_cipher::Cipher::aes_128_ctr()),
-            Cipher::Aes192Ctr => Some(ossl_cipher::Cipher::aes_192_ctr()),
-            Cipher::Aes256Ctr => Some(ossl_cipher::Cipher::aes_256_ctr()),
-            Cipher::Aes128Gcm => Some(ossl_cipher::Cipher::aes_128_gcm()),
-            Cipher::Aes192Gcm => Some(ossl_cipher::Cipher::aes_192_gcm()),
-            Cipher::Aes256Gcm => Some(ossl_cipher::Cipher::aes_256_gcm()),
-        }
-    }
 }
 
 impl fmt::Display for Cipher {
@@ -181,6 +221,9 @@ impl fmt::Display for Cipher {
             Cipher::Aes128Gcm => "aes128-gcm",
             Cipher::Aes192Gcm => "aes192-gcm",
             Cipher::Aes256Gcm => "aes256-gcm",
+            Cipher::Aes128CtrHmac => "aes128-ctr-hmac",
+            Cipher::Aes192CtrHmac => "aes192-ctr-hmac",
+            Cipher::Aes256CtrHmac => "aes256-ctr-hmac",
         };
 
         fmt.write_str(s)
@@ -199,6 +242,9 @@ impl FromStr for Cipher {
             "aes128-gcm" => Ok(Cipher::Aes128Gcm),
             "aes192-gcm" => Ok(Cipher::Aes192Gcm),
             "aes256-gcm" => Ok(Cipher::Aes256Gcm),
+            "aes128-ctr-hmac" => Ok(Cipher::Aes128CtrHmac),
+            "aes192-ctr-hmac" => Ok(Cipher::Aes192CtrHmac),
+            "aes256-ctr-hmac" => Ok(Cipher::Aes256CtrHmac),
             _ => Err(()),
         }
     }
@@ -215,8 +261,8 @@ impl CipherContext {
     pub(super) fn new(cipher: Cipher) -> CipherContext {
         CipherContext {
             cipher,
-            inp: vec![].into(),
-            outp: vec![].into(),
+            inp: SecureVec::new(),
+            outp: SecureVec::new(),
         }
     }
 
@@ -230,6 +276,17 @@ impl CipherContext {
         len
     }
 
+    /// Wipes the buffers, their memory is kept for the next operation.
+    fn clear(&mut self) {
+        self.inp.clear();
+        self.outp.clear();
+    }
+
+    /// Tests whether the input consists of zeros only.
+    pub fn is_zero(&self) -> bool {
+        self.inp.iter().all(|n| *n == 0)
+    }
+
     pub fn inp_mut(&mut self, buf_size: usize) -> &mut [u8] {
         self.copy_from_slice(buf_size, &[]); // whiteout
 
@@ -237,23 +294,30 @@ impl CipherContext {
     }
 
     pub fn encrypt(&mut self, key: &[u8], iv: &[u8]) -> Result<&[u8], CipherError> {
+        self.encrypt_with_ad(key, iv, &[])
+    }
+
+    /// Encrypts the input, the MAC additionally covers the associated data
+    /// `ad`.
+    ///
+    /// The associated data are not stored in the ciphertext, the same data
+    /// must be passed to [`CipherContext::decrypt_with_ad`].
+    pub fn encrypt_with_ad(
+        &mut self,
+        key: &[u8],
+        iv: &[u8],
+        ad: &[u8],
+    ) -> Result<&[u8], CipherError> {
         match self.cipher {
             Cipher::None => self.make_none(),
-            _ => self.encrypt_aad(None, key, iv).map(|_| ())?,
+            _ => self.encrypt_inner(key, iv, ad).map(|_| ())?,
         };
 
         Ok(self.outp.as_slice())
     }
 
-    fn encrypt_aad(
-        &mut self,
-        aad: Option<&[u8]>,
-        key: &[u8],
-        iv: &[u8],
-    ) -> Result<usize, CipherError> {
-        let key = key
-            .get(..self.cipher.key_len())
-            .ok_or(CipherError::InvalidKey)?;
+    fn encrypt_inner(&mut self, key: &[u8], iv: &[u8], ad: &[u8]) -> Result<usize, CipherError> {
+        let (key, mac_key) = self.split_key(key)?;
         let iv = iv
             .get(..self.cipher.iv_len())
             .ok_or(CipherError::InvalidIv)?;
@@ -267,7 +331,9 @@ impl CipherContext {
         // blocksize is 1 for all ciphers.
         let ctext_len = ptext_len;
 
-        if ptext_len == 0 {
+        // the MAC is calculated even for an empty input, decryption expects
+        // a tag
+        if ptext_len == 0 && self.cipher.mac_digest().is_none() {
             return Ok(0);
         }
 
@@ -275,45 +341,46 @@ impl CipherContext {
             return Err(CipherError::InvalidBlockSize);
         }
 
-        let mut ctx = CipherCtx::new()?;
+        self.outp
+            .resize(ctext_len + self.cipher.tag_size() as usize, 0);
 
-        ctx.encrypt_init(self.cipher.to_openssl(), Some(key), Some(iv))?;
-        ctx.set_padding(false);
+        let (ctext, tag) = self.outp.split_at_mut(ctext_len);
 
-        if let Some(buf) = aad {
-            ctx.cipher_update(buf, None)?;
+        if self.cipher.tag_size() > 0 && self.cipher.mac_digest().is_none() {
+            crypto::gcm_encrypt(self.cipher, key, iv, &self.inp[..ptext_len], ctext, tag)?;
+        } else if ptext_len > 0 {
+            crypto::ctr(self.cipher, key, iv, &self.inp[..ptext_len], ctext)?;
         }
 
-        self.outp
-            .resize(ctext_len + self.cipher.tag_size() as usize, 0);
-        ctx.cipher_update(&self.inp[..ptext_len], Some(&mut self.outp[..ctext_len]))?;
-
-        if self.cipher.tag_size() > 0 {
-            ctx.cipher_final(&mut [])?;
-            ctx.tag(&mut self.outp[ctext_len..])?;
+        if let Some(digest) = self.cipher.mac_digest() {
+            tag.copy_from_slice(&Self::mac(digest, mac_key, iv, ad, ctext)?);
         }
 
         Ok(ctext_len)
     }
 
     pub fn decrypt(&mut self, key: &[u8], iv: &[u8]) -> Result<&[u8], CipherError> {
+        self.decrypt_with_ad(key, iv, &[])
+    }
+
+    /// Decrypts the input, which was encrypted with
+    /// [`CipherContext::encrypt_with_ad`] and the same associated data `ad`.
+    pub fn decrypt_with_ad(
+        &mut self,
+        key: &[u8],
+        iv: &[u8],
+        ad: &[u8],
+    ) -> Result<&[u8], CipherError> {
         match self.cipher {
             Cipher::None => self.make_none(),
-            _ => self.decrypt_aad(None, key, iv).map(|_| ())?,
+            _ => self.decrypt_inner(key, iv, ad).map(|_| ())?,
         }
 
         Ok(self.outp.as_slice())
     }
 
-    fn decrypt_aad(
-        &mut self,
-        aad: Option<&[u8]>,
-        key: &[u8],
-        iv: &[u8],
-    ) -> Result<usize, CipherError> {
-        let key = key
-            .get(..self.cipher.key_len())
-            .ok_or(CipherError::InvalidKey)?;
+    fn decrypt_inner(&mut self, key: &[u8], iv: &[u8], ad: &[u8]) -> Result<usize, CipherError> {
+        let (key, mac_key) = self.split_key(key)?;
         let iv = iv
             .get(..self.cipher.iv_len())
             .ok_or(CipherError::InvalidIv)?;
@@ -328,7 +395,22 @@ impl CipherContext {
         // blocksize is 1 for all ciphers.
         let ptext_bytes = ctext_bytes;
 
+        if let Some(digest) = self.cipher.mac_digest() {
+            // a truncated input does not even contain the tag
+            if self.inp.len() < self.cipher.tag_size() as usize {
+                return Err(CipherError::MacMismatch);
+            }
+
+            // Encrypt-then-MAC: verify the ciphertext before decrypting it
+            let tag = Self::mac(digest, mac_key, iv, ad, &self.inp[..ctext_bytes])?;
+
+            if !crypto::memeq(&tag, &self.inp[ctext_bytes..]) {
+                return Err(CipherError::MacMismatch);
+            }
+        }
+
         if ctext_bytes == 0 {
+            self.outp.clear();
             return Ok(0);
         }
 
@@ -336,32 +418,131 @@ impl CipherContext {
             return Err(CipherError::InvalidBlockSize);
         }
 
-        let mut ctx = CipherCtx::new()?;
-
-        ctx.decrypt_init(self.cipher.to_openssl(), Some(key), Some(iv))?;
-        ctx.set_padding(false);
+        self.outp.resize(ptext_bytes, 0);
 
-        if let Some(buf) = aad {
-            ctx.cipher_update(buf, None)?;
-        }
+        let (ctext, tag) = self.inp.split_at(ctext_bytes);
+        let ptext = &mut self.outp[..ptext_bytes];
 
-        self.outp.resize(ptext_bytes, 0);
-        ctx.cipher_update(
-            &self.inp[..ctext_bytes],
-            Some(&mut self.outp[..ptext_bytes]),
-        )?;
-
-        if self.cipher.tag_size() > 0 {
-            ctx.set_tag(&self.inp[ctext_bytes..])?;
-            ctx.cipher_final(&mut [])
-                .map_err(|_| CipherError::NotTrustworthy)?;
+        if self.cipher.tag_size() > 0 && self.cipher.mac_digest().is_none() {
+            if !crypto::gcm_decrypt(self.cipher, key, iv, ctext, ptext, tag)? {
+                return Err(CipherError::NotTrustworthy);
+            }
+        } else {
+            crypto::ctr(self.cipher, key, iv, ctext, ptext)?;
         }
 
         Ok(ctext_bytes)
     }
 
+    /// Calculates the HMAC of `ctext`.
+    ///
+    /// Besides the ciphertext the MAC covers the `iv` and the associated data
+    /// `ad`. The length of `ad` is part of the input, so the boundary between
+    /// `ad` and `ctext` is unambiguous.
+    fn mac(
+        digest: Digest,
+        mac_key: &[u8],
+        iv: &[u8],
+        ad: &[u8],
+        ctext: &[u8],
+    ) -> Result<Vec<u8>, CipherError> {
+        let ad_len = (ad.len() as u32).to_be_bytes();
+
+        Ok(crypto::hmac(digest, mac_key, &[iv, &ad_len, ad, ctext])?)
+    }
+
+    /// Splits `key` into the key of the cipher and the key of the HMAC.
+    fn split_key<'a>(&self, key: &'a [u8]) -> Result<(&'a [u8], &'a [u8]), CipherError> {
+        let key = key
+            .get(..self.cipher.key_len())
+            .ok_or(CipherError::InvalidKey)?;
+        let mac_len = self.cipher.mac_digest().map_or(0, |d| d.size());
+
+        Ok(key.split_at(key.len() - mac_len))
+    }
+
     fn make_none(&mut self) {
         self.outp.clear();
         self.outp.extend_from_slice(&self.inp);
     }
 }
+
+/// A pool of [`CipherContext`] instances.
+///
+/// The buffers of a context are kept in secure memory, an allocation costs a
+/// page-aligned allocation and the `mlock`/`madvise` syscalls. A context is
+/// returned to the pool once it is dropped, thus the buffers are allocated
+/// once and reused for all block operations.
+pub(super) struct CipherPool {
+    cipher: Cipher,
+    contexts: Arc<Mutex<Vec<CipherContext>>>,
+}
+
+impl CipherPool {
+    pub(super) fn new(cipher: Cipher) -> CipherPool {
+        CipherPool {
+            cipher,
+            contexts: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Takes a context from the pool.
+    ///
+    /// A new context is created, if the pool is empty.
+    pub fn get(&self) -> PooledContext {
+        let ctx = self
+            .contexts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop()
+            .unwrap_or_else(|| CipherContext::new(self.cipher));
+
+        PooledContext {
+            contexts: Arc::clone(&self.contexts),
+            ctx: Some(ctx),
+        }
+    }
+}
+
+impl fmt::Debug for CipherPool {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("CipherPool")
+            .field("cipher", &self.cipher)
+            .finish()
+    }
+}
+
+/// A [`CipherContext`] taken from a [`CipherPool`].
+///
+/// The context is wiped and returned to the pool on drop.
+pub(super) struct PooledContext {
+    contexts: Arc<Mutex<Vec<CipherContext>>>,
+    ctx: Option<CipherContext>,
+}
+
+impl Deref for PooledContext {
+    type Target = CipherContext;
+
+    fn deref(&self) -> &CipherContext {
+        self.ctx.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledContext {
+    fn deref_mut(&mut self) -> &mut CipherContext {
+        self.ctx.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledContext {
+    fn drop(&mut self) {
+        if let Some(mut ctx) = self.ctx.take() {
+            ctx.clear();
+
+            self.contexts
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(ctx);
+        }
+    }
+}
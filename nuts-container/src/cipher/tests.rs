@@ -21,16 +21,20 @@
 // IN THE SOFTWARE.
 
 mod aes128_ctr;
+mod aes128_ctr_hmac;
 mod aes128_gcm;
 mod aes192_ctr;
+mod aes192_ctr_hmac;
 mod aes192_gcm;
 mod aes256_ctr;
+mod aes256_ctr_hmac;
 mod aes256_gcm;
 mod bytes;
 mod none;
+mod pool;
 mod string;
 
-const KEY: [u8; 32] = [b'x'; 32];
+const KEY: [u8; 64] = [b'x'; 64];
 const IV: [u8; 16] = [b'y'; 16];
 
 macro_rules! ctx_test {
@@ -0,0 +1,194 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use crate::cipher::{Cipher, CipherContext, CipherError};
+use crate::digest::Digest;
+
+use super::{ctx_test, IV, KEY};
+
+const KEY_LEN: usize = 16 + 32;
+
+#[test]
+fn block_size() {
+    assert_eq!(Cipher::Aes128CtrHmac.block_size(), 1);
+}
+
+#[test]
+fn key_len() {
+    assert_eq!(Cipher::Aes128CtrHmac.key_len(), KEY_LEN);
+}
+
+#[test]
+fn iv_len() {
+    assert_eq!(Cipher::Aes128CtrHmac.iv_len(), 16);
+}
+
+#[test]
+fn tag_size() {
+    assert_eq!(Cipher::Aes128CtrHmac.tag_size(), 32);
+}
+
+#[test]
+fn mac_digest() {
+    assert_eq!(Cipher::Aes128CtrHmac.mac_digest(), Some(Digest::Sha256));
+}
+
+#[test]
+fn ctx_decrypt_inval_key() {
+    let mut ctx = CipherContext::new(Cipher::Aes128CtrHmac);
+
+    ctx.copy_from_slice(
+        35,
+        &[
+            146, 140, 10, 189, 189, 71, 164, 142, 216, 50, 162, 191, 225, 7, 42, 191, 124, 151, 44,
+            158, 142, 160, 227, 219, 182, 221, 90, 27, 192, 163, 49, 11, 206, 57, 242,
+        ],
+    );
+
+    let err = ctx.decrypt(&KEY[..KEY_LEN - 1], &IV).unwrap_err();
+    assert!(matches!(err, CipherError::InvalidKey));
+}
+
+#[test]
+fn ctx_decrypt_inval_iv() {
+    let mut ctx = CipherContext::new(Cipher::Aes128CtrHmac);
+
+    ctx.copy_from_slice(
+        35,
+        &[
+            146, 140, 10, 189, 189, 71, 164, 142, 216, 50, 162, 191, 225, 7, 42, 191, 124, 151, 44,
+            158, 142, 160, 227, 219, 182, 221, 90, 27, 192, 163, 49, 11, 206, 57, 242,
+        ],
+    );
+
+    let err = ctx.decrypt(&KEY[..KEY_LEN], &IV[..15]).unwrap_err();
+    assert!(matches!(err, CipherError::InvalidIv));
+}
+
+#[test]
+fn ctx_decrypt_modified_ctext() {
+    let mut ctx = CipherContext::new(Cipher::Aes128CtrHmac);
+
+    ctx.copy_from_slice(
+        35,
+        &[
+            147, 140, 10, 189, 189, 71, 164, 142, 216, 50, 162, 191, 225, 7, 42, 191, 124, 151, 44,
+            158, 142, 160, 227, 219, 182, 221, 90, 27, 192, 163, 49, 11, 206, 57, 242,
+        ],
+    );
+
+    let err = ctx.decrypt(&KEY[..KEY_LEN], &IV).unwrap_err();
+    assert!(matches!(err, CipherError::MacMismatch));
+}
+
+#[test]
+fn ctx_decrypt_modified_tag() {
+    let mut ctx = CipherContext::new(Cipher::Aes128CtrHmac);
+
+    ctx.copy_from_slice(
+        35,
+        &[
+            146, 140, 10, 189, 189, 71, 164, 142, 216, 50, 162, 191, 225, 7, 42, 191, 124, 151, 44,
+            158, 142, 160, 227, 219, 182, 221, 90, 27, 192, 163, 49, 11, 206, 57, 243,
+        ],
+    );
+
+    let err = ctx.decrypt(&KEY[..KEY_LEN], &IV).unwrap_err();
+    assert!(matches!(err, CipherError::MacMismatch));
+}
+
+#[test]
+fn ctx_decrypt_modified_ad() {
+    let mut ctx = CipherContext::new(Cipher::Aes128CtrHmac);
+
+    ctx.copy_from_slice(3, &[1, 2, 3]);
+    let ctext = ctx
+        .encrypt_with_ad(&KEY[..KEY_LEN], &IV, &[1])
+        .unwrap()
+        .to_vec();
+
+    ctx.copy_from_slice(ctext.len(), &ctext);
+    assert_eq!(
+        ctx.decrypt_with_ad(&KEY[..KEY_LEN], &IV, &[1]).unwrap(),
+        [1, 2, 3]
+    );
+
+    ctx.copy_from_slice(ctext.len(), &ctext);
+    let err = ctx.decrypt_with_ad(&KEY[..KEY_LEN], &IV, &[2]).unwrap_err();
+    assert!(matches!(err, CipherError::MacMismatch));
+
+    ctx.copy_from_slice(ctext.len(), &ctext);
+    let err = ctx.decrypt(&KEY[..KEY_LEN], &IV).unwrap_err();
+    assert!(matches!(err, CipherError::MacMismatch));
+}
+
+#[test]
+fn ctx_decrypt_truncated() {
+    let mut ctx = CipherContext::new(Cipher::Aes128CtrHmac);
+
+    for len in [0, 1, 31] {
+        ctx.copy_from_slice(len, &[0; 32]);
+
+        let err = ctx.decrypt(&KEY[..KEY_LEN], &IV).unwrap_err();
+        assert!(matches!(err, CipherError::MacMismatch));
+    }
+}
+
+#[test]
+fn ctx_decrypt_tag_only() {
+    let mut ctx = CipherContext::new(Cipher::Aes128CtrHmac);
+
+    ctx.copy_from_slice(0, &[]);
+    let ctext = ctx.encrypt(&KEY[..KEY_LEN], &IV).unwrap().to_vec();
+    assert_eq!(ctext.len(), 32);
+
+    ctx.copy_from_slice(ctext.len(), &ctext);
+    assert!(ctx.decrypt(&KEY[..KEY_LEN], &IV).unwrap().is_empty());
+
+    ctx.copy_from_slice(ctext.len(), &[0; 32]);
+    let err = ctx.decrypt(&KEY[..KEY_LEN], &IV).unwrap_err();
+    assert!(matches!(err, CipherError::MacMismatch));
+}
+
+ctx_test!(ctx_decrypt, Aes128CtrHmac.decrypt, 35, [146, 140, 10, 189, 189, 71, 164, 142, 216, 50, 162, 191, 225, 7, 42, 191, 124, 151, 44, 158, 142, 160, 227, 219, 182, 221, 90, 27, 192, 163, 49, 11, 206, 57, 242] -> [1, 2, 3]);
+
+#[test]
+fn ctx_encrypt_inval_key() {
+    let mut ctx = CipherContext::new(Cipher::Aes128CtrHmac);
+
+    ctx.copy_from_slice(3, &[1, 2, 3]);
+
+    let err = ctx.encrypt(&KEY[..KEY_LEN - 1], &IV).unwrap_err();
+    assert!(matches!(err, CipherError::InvalidKey));
+}
+
+#[test]
+fn ctx_encrypt_inval_iv() {
+    let mut ctx = CipherContext::new(Cipher::Aes128CtrHmac);
+
+    ctx.copy_from_slice(3, &[1, 2, 3]);
+
+    let err = ctx.encrypt(&KEY[..KEY_LEN], &IV[..15]).unwrap_err();
+    assert!(matches!(err, CipherError::InvalidIv));
+}
+
+ctx_test!(ctx_encrypt, Aes128CtrHmac.encrypt, 3, [1, 2, 3] -> [146, 140, 10, 189, 189, 71, 164, 142, 216, 50, 162, 191, 225, 7, 42, 191, 124, 151, 44, 158, 142, 160, 227, 219, 182, 221, 90, 27, 192, 163, 49, 11, 206, 57, 242]);
@@ -0,0 +1,141 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use crate::cipher::{Cipher, CipherContext, CipherError};
+use crate::digest::Digest;
+
+use super::{ctx_test, IV, KEY};
+
+const KEY_LEN: usize = 24 + 32;
+
+#[test]
+fn block_size() {
+    assert_eq!(Cipher::Aes192CtrHmac.block_size(), 1);
+}
+
+#[test]
+fn key_len() {
+    assert_eq!(Cipher::Aes192CtrHmac.key_len(), KEY_LEN);
+}
+
+#[test]
+fn iv_len() {
+    assert_eq!(Cipher::Aes192CtrHmac.iv_len(), 16);
+}
+
+#[test]
+fn tag_size() {
+    assert_eq!(Cipher::Aes192CtrHmac.tag_size(), 32);
+}
+
+#[test]
+fn mac_digest() {
+    assert_eq!(Cipher::Aes192CtrHmac.mac_digest(), Some(Digest::Sha256));
+}
+
+#[test]
+fn ctx_decrypt_inval_key() {
+    let mut ctx = CipherContext::new(Cipher::Aes192CtrHmac);
+
+    ctx.copy_from_slice(
+        35,
+        &[
+            85, 128, 31, 215, 23, 198, 234, 244, 160, 158, 170, 150, 122, 198, 46, 36, 52, 156,
+            215, 10, 128, 250, 63, 173, 90, 35, 117, 52, 203, 111, 228, 168, 8, 167, 61,
+        ],
+    );
+
+    let err = ctx.decrypt(&KEY[..KEY_LEN - 1], &IV).unwrap_err();
+    assert!(matches!(err, CipherError::InvalidKey));
+}
+
+#[test]
+fn ctx_decrypt_inval_iv() {
+    let mut ctx = CipherContext::new(Cipher::Aes192CtrHmac);
+
+    ctx.copy_from_slice(
+        35,
+        &[
+            85, 128, 31, 215, 23, 198, 234, 244, 160, 158, 170, 150, 122, 198, 46, 36, 52, 156,
+            215, 10, 128, 250, 63, 173, 90, 35, 117, 52, 203, 111, 228, 168, 8, 167, 61,
+        ],
+    );
+
+    let err = ctx.decrypt(&KEY[..KEY_LEN], &IV[..15]).unwrap_err();
+    assert!(matches!(err, CipherError::InvalidIv));
+}
+
+#[test]
+fn ctx_decrypt_modified_ctext() {
+    let mut ctx = CipherContext::new(Cipher::Aes192CtrHmac);
+
+    ctx.copy_from_slice(
+        35,
+        &[
+            84, 128, 31, 215, 23, 198, 234, 244, 160, 158, 170, 150, 122, 198, 46, 36, 52, 156,
+            215, 10, 128, 250, 63, 173, 90, 35, 117, 52, 203, 111, 228, 168, 8, 167, 61,
+        ],
+    );
+
+    let err = ctx.decrypt(&KEY[..KEY_LEN], &IV).unwrap_err();
+    assert!(matches!(err, CipherError::MacMismatch));
+}
+
+#[test]
+fn ctx_decrypt_modified_tag() {
+    let mut ctx = CipherContext::new(Cipher::Aes192CtrHmac);
+
+    ctx.copy_from_slice(
+        35,
+        &[
+            85, 128, 31, 215, 23, 198, 234, 244, 160, 158, 170, 150, 122, 198, 46, 36, 52, 156,
+            215, 10, 128, 250, 63, 173, 90, 35, 117, 52, 203, 111, 228, 168, 8, 167, 60,
+        ],
+    );
+
+    let err = ctx.decrypt(&KEY[..KEY_LEN], &IV).unwrap_err();
+    assert!(matches!(err, CipherError::MacMismatch));
+}
+
+ctx_test!(ctx_decrypt, Aes192CtrHmac.decrypt, 35, [85, 128, 31, 215, 23, 198, 234, 244, 160, 158, 170, 150, 122, 198, 46, 36, 52, 156, 215, 10, 128, 250, 63, 173, 90, 35, 117, 52, 203, 111, 228, 168, 8, 167, 61] -> [1, 2, 3]);
+
+#[test]
+fn ctx_encrypt_inval_key() {
+    let mut ctx = CipherContext::new(Cipher::Aes192CtrHmac);
+
+    ctx.copy_from_slice(3, &[1, 2, 3]);
+
+    let err = ctx.encrypt(&KEY[..KEY_LEN - 1], &IV).unwrap_err();
+    assert!(matches!(err, CipherError::InvalidKey));
+}
+
+#[test]
+fn ctx_encrypt_inval_iv() {
+    let mut ctx = CipherContext::new(Cipher::Aes192CtrHmac);
+
+    ctx.copy_from_slice(3, &[1, 2, 3]);
+
+    let err = ctx.encrypt(&KEY[..KEY_LEN], &IV[..15]).unwrap_err();
+    assert!(matches!(err, CipherError::InvalidIv));
+}
+
+ctx_test!(ctx_encrypt, Aes192CtrHmac.encrypt, 3, [1, 2, 3] -> [85, 128, 31, 215, 23, 198, 234, 244, 160, 158, 170, 150, 122, 198, 46, 36, 52, 156, 215, 10, 128, 250, 63, 173, 90, 35, 117, 52, 203, 111, 228, 168, 8, 167, 61]);
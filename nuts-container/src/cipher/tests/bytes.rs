@@ -86,6 +86,33 @@ fn de_aes256_gcm() {
     );
 }
 
+#[test]
+fn de_aes128_ctr_hmac() {
+    let buf = [0x00, 0x00, 0x00, 0x07];
+    assert_eq!(
+        Cipher::get_from_buffer(&mut &buf[..]).unwrap(),
+        Cipher::Aes128CtrHmac
+    );
+}
+
+#[test]
+fn de_aes192_ctr_hmac() {
+    let buf = [0x00, 0x00, 0x00, 0x08];
+    assert_eq!(
+        Cipher::get_from_buffer(&mut &buf[..]).unwrap(),
+        Cipher::Aes192CtrHmac
+    );
+}
+
+#[test]
+fn de_aes256_ctr_hmac() {
+    let buf = [0x00, 0x00, 0x00, 0x09];
+    assert_eq!(
+        Cipher::get_from_buffer(&mut &buf[..]).unwrap(),
+        Cipher::Aes256CtrHmac
+    );
+}
+
 #[test]
 fn de_eof() {
     let buf = [0x00, 0x00, 0x00];
@@ -96,10 +123,10 @@ fn de_eof() {
 
 #[test]
 fn de_invalid() {
-    let buf = [0x00, 0x00, 0x00, 0x07];
+    let buf = [0x00, 0x00, 0x00, 0x0a];
     let err = Cipher::get_from_buffer(&mut &buf[..]).unwrap_err();
 
-    assert_eq!(err.to_string(), "no Cipher at 7");
+    assert_eq!(err.to_string(), "no Cipher at 10");
 }
 
 #[test]
@@ -165,3 +192,27 @@ fn ser_write_zero() {
 
     assert!(matches!(err, BufferError::WriteZero));
 }
+
+#[test]
+fn ser_aes128_ctr_hmac() {
+    let mut buf = vec![];
+
+    Cipher::Aes128CtrHmac.put_into_buffer(&mut buf).unwrap();
+    assert_eq!(buf, [0x00, 0x00, 0x00, 0x07]);
+}
+
+#[test]
+fn ser_aes192_ctr_hmac() {
+    let mut buf = vec![];
+
+    Cipher::Aes192CtrHmac.put_into_buffer(&mut buf).unwrap();
+    assert_eq!(buf, [0x00, 0x00, 0x00, 0x08]);
+}
+
+#[test]
+fn ser_aes256_ctr_hmac() {
+    let mut buf = vec![];
+
+    Cipher::Aes256CtrHmac.put_into_buffer(&mut buf).unwrap();
+    assert_eq!(buf, [0x00, 0x00, 0x00, 0x09]);
+}
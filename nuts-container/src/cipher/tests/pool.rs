@@ -0,0 +1,62 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use crate::cipher::{Cipher, CipherPool};
+
+use super::{IV, KEY};
+
+#[test]
+fn reuse() {
+    let pool = CipherPool::new(Cipher::Aes128Ctr);
+
+    {
+        let mut ctx = pool.get();
+
+        ctx.copy_from_slice(3, &[1, 2, 3]);
+        ctx.encrypt(&KEY[..16], &IV).unwrap();
+    }
+
+    assert_eq!(pool.contexts.lock().unwrap().len(), 1);
+
+    let ctx = pool.get();
+
+    assert!(pool.contexts.lock().unwrap().is_empty());
+
+    // buffers are wiped, but the memory is kept
+    assert!(ctx.inp.is_empty());
+    assert!(ctx.outp.is_empty());
+    assert!(ctx.inp.capacity() >= 3);
+    assert!(ctx.outp.capacity() >= 3);
+}
+
+#[test]
+fn new_context() {
+    let pool = CipherPool::new(Cipher::Aes128Ctr);
+
+    let ctx1 = pool.get();
+    let ctx2 = pool.get();
+
+    drop(ctx1);
+    drop(ctx2);
+
+    assert_eq!(pool.contexts.lock().unwrap().len(), 2);
+}
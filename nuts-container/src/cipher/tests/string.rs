@@ -57,6 +57,30 @@ fn from_str_aes256_gcm() {
     assert_eq!("aes256-gcm".parse::<Cipher>().unwrap(), Cipher::Aes256Gcm);
 }
 
+#[test]
+fn from_str_aes128_ctr_hmac() {
+    assert_eq!(
+        "aes128-ctr-hmac".parse::<Cipher>().unwrap(),
+        Cipher::Aes128CtrHmac
+    );
+}
+
+#[test]
+fn from_str_aes192_ctr_hmac() {
+    assert_eq!(
+        "aes192-ctr-hmac".parse::<Cipher>().unwrap(),
+        Cipher::Aes192CtrHmac
+    );
+}
+
+#[test]
+fn from_str_aes256_ctr_hmac() {
+    assert_eq!(
+        "aes256-ctr-hmac".parse::<Cipher>().unwrap(),
+        Cipher::Aes256CtrHmac
+    );
+}
+
 #[test]
 fn from_str_invalid() {
     "xxx".parse::<Cipher>().unwrap_err();
@@ -96,3 +120,18 @@ fn to_string_aes192_gcm() {
 fn to_string_aes256_gcm() {
     assert_eq!(Cipher::Aes256Gcm.to_string(), "aes256-gcm");
 }
+
+#[test]
+fn to_string_aes128_ctr_hmac() {
+    assert_eq!(Cipher::Aes128CtrHmac.to_string(), "aes128-ctr-hmac");
+}
+
+#[test]
+fn to_string_aes192_ctr_hmac() {
+    assert_eq!(Cipher::Aes192CtrHmac.to_string(), "aes192-ctr-hmac");
+}
+
+#[test]
+fn to_string_aes256_ctr_hmac() {
+    assert_eq!(Cipher::Aes256CtrHmac.to_string(), "aes256-ctr-hmac");
+}
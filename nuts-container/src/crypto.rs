@@ -0,0 +1,42 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(not(any(feature = "openssl", feature = "rust-crypto")))]
+compile_error!("one of the features `openssl` or `rust-crypto` must be enabled");
+
+// OpenSSL takes precedence, if both features are enabled. It is built anyway
+// in this case.
+#[cfg(feature = "openssl")]
+mod openssl_impl;
+#[cfg(all(feature = "rust-crypto", not(feature = "openssl")))]
+mod rust_impl;
+
+#[cfg(feature = "openssl")]
+pub use openssl_impl::{
+    ctr, gcm_decrypt, gcm_encrypt, hash, hmac, memeq, pbkdf2, rand_bytes, x25519_derive,
+    x25519_public, CryptoError,
+};
+#[cfg(all(feature = "rust-crypto", not(feature = "openssl")))]
+pub use rust_impl::{
+    ctr, gcm_decrypt, gcm_encrypt, hash, hmac, memeq, pbkdf2, rand_bytes, x25519_derive,
+    x25519_public, CryptoError,
+};
@@ -0,0 +1,163 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use openssl::cipher::{Cipher as OsslCipher, CipherRef};
+use openssl::cipher_ctx::CipherCtx;
+use openssl::derive::Deriver;
+use openssl::hash::{self, MessageDigest};
+use openssl::memcmp;
+use openssl::pkcs5::pbkdf2_hmac;
+use openssl::pkey::{Id, PKey};
+use openssl::sign::Signer;
+
+use crate::cipher::Cipher;
+use crate::digest::Digest;
+use crate::svec::SecureVec;
+
+/// An error in the OpenSSL library.
+pub type CryptoError = openssl::error::ErrorStack;
+
+fn message_digest(digest: Digest) -> MessageDigest {
+    match digest {
+        Digest::Sha1 => MessageDigest::sha1(),
+        Digest::Sha224 => MessageDigest::sha224(),
+        Digest::Sha256 => MessageDigest::sha256(),
+        Digest::Sha384 => MessageDigest::sha384(),
+        Digest::Sha512 => MessageDigest::sha512(),
+    }
+}
+
+fn cipher_ref(cipher: Cipher) -> &'static CipherRef {
+    match cipher {
+        Cipher::Aes128Ctr | Cipher::Aes128CtrHmac => OsslCipher::aes_128_ctr(),
+        Cipher::Aes192Ctr | Cipher::Aes192CtrHmac => OsslCipher::aes_192_ctr(),
+        Cipher::Aes256Ctr | Cipher::Aes256CtrHmac => OsslCipher::aes_256_ctr(),
+        Cipher::Aes128Gcm => OsslCipher::aes_128_gcm(),
+        Cipher::Aes192Gcm => OsslCipher::aes_192_gcm(),
+        Cipher::Aes256Gcm => OsslCipher::aes_256_gcm(),
+        Cipher::None => unreachable!("no openssl cipher for {}", cipher),
+    }
+}
+
+pub fn rand_bytes(buf: &mut [u8]) -> Result<(), CryptoError> {
+    openssl::rand::rand_bytes(buf)
+}
+
+pub fn hash(digest: Digest, buf: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    Ok(hash::hash(message_digest(digest), buf)?.to_vec())
+}
+
+pub fn hmac(digest: Digest, key: &[u8], bufs: &[&[u8]]) -> Result<Vec<u8>, CryptoError> {
+    let pkey = PKey::hmac(key)?;
+    let mut signer = Signer::new(message_digest(digest), &pkey)?;
+
+    for buf in bufs {
+        signer.update(buf)?;
+    }
+
+    signer.sign_to_vec()
+}
+
+pub fn pbkdf2(
+    digest: Digest,
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    key: &mut [u8],
+) -> Result<(), CryptoError> {
+    pbkdf2_hmac(
+        password,
+        salt,
+        iterations as usize,
+        message_digest(digest),
+        key,
+    )
+}
+
+pub fn memeq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && memcmp::eq(a, b)
+}
+
+pub fn ctr(
+    cipher: Cipher,
+    key: &[u8],
+    iv: &[u8],
+    inp: &[u8],
+    outp: &mut [u8],
+) -> Result<(), CryptoError> {
+    let mut ctx = CipherCtx::new()?;
+
+    ctx.encrypt_init(Some(cipher_ref(cipher)), Some(key), Some(iv))?;
+    ctx.set_padding(false);
+    ctx.cipher_update(inp, Some(outp))?;
+
+    Ok(())
+}
+
+pub fn gcm_encrypt(
+    cipher: Cipher,
+    key: &[u8],
+    iv: &[u8],
+    inp: &[u8],
+    outp: &mut [u8],
+    tag: &mut [u8],
+) -> Result<(), CryptoError> {
+    let mut ctx = CipherCtx::new()?;
+
+    ctx.encrypt_init(Some(cipher_ref(cipher)), Some(key), Some(iv))?;
+    ctx.set_padding(false);
+    ctx.cipher_update(inp, Some(outp))?;
+    ctx.cipher_final(&mut [])?;
+    ctx.tag(tag)
+}
+
+pub fn gcm_decrypt(
+    cipher: Cipher,
+    key: &[u8],
+    iv: &[u8],
+    inp: &[u8],
+    outp: &mut [u8],
+    tag: &[u8],
+) -> Result<bool, CryptoError> {
+    let mut ctx = CipherCtx::new()?;
+
+    ctx.decrypt_init(Some(cipher_ref(cipher)), Some(key), Some(iv))?;
+    ctx.set_padding(false);
+    ctx.cipher_update(inp, Some(outp))?;
+    ctx.set_tag(tag)?;
+
+    Ok(ctx.cipher_final(&mut []).is_ok())
+}
+
+pub fn x25519_public(private: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    PKey::private_key_from_raw_bytes(private, Id::X25519)?.raw_public_key()
+}
+
+pub fn x25519_derive(private: &[u8], public: &[u8]) -> Result<SecureVec, CryptoError> {
+    let private = PKey::private_key_from_raw_bytes(private, Id::X25519)?;
+    let public = PKey::public_key_from_raw_bytes(public, Id::X25519)?;
+
+    let mut deriver = Deriver::new(&private)?;
+    deriver.set_peer(&public)?;
+
+    Ok(deriver.derive_to_vec()?.into())
+}
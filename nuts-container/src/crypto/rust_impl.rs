@@ -0,0 +1,250 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use aes::{Aes128, Aes192, Aes256};
+use aes_gcm::aead::consts::U12;
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::AeadInPlace;
+use aes_gcm::AesGcm;
+use ctr::cipher::{KeyInit, KeyIvInit, StreamCipher};
+use hmac::{Hmac, Mac};
+use sha2::Digest as _;
+use std::convert::TryFrom;
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::cipher::Cipher;
+use crate::digest::Digest;
+use crate::svec::SecureVec;
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+type Aes192Ctr = ctr::Ctr128BE<Aes192>;
+type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+type Aes128Gcm = AesGcm<Aes128, U12>;
+type Aes192Gcm = AesGcm<Aes192, U12>;
+type Aes256Gcm = AesGcm<Aes256, U12>;
+
+/// An error in the RustCrypto crates.
+#[derive(Debug, Error)]
+#[error("{0}")]
+pub struct CryptoError(String);
+
+impl CryptoError {
+    fn new<T: ToString>(msg: T) -> CryptoError {
+        CryptoError(msg.to_string())
+    }
+}
+
+/// Evaluates `$body` with `$ty` bound to the hash type of `$digest`.
+macro_rules! with_digest {
+    ($digest:expr, $ty:ident => $body:expr) => {
+        match $digest {
+            Digest::Sha1 => {
+                type $ty = sha1::Sha1;
+                $body
+            }
+            Digest::Sha224 => {
+                type $ty = sha2::Sha224;
+                $body
+            }
+            Digest::Sha256 => {
+                type $ty = sha2::Sha256;
+                $body
+            }
+            Digest::Sha384 => {
+                type $ty = sha2::Sha384;
+                $body
+            }
+            Digest::Sha512 => {
+                type $ty = sha2::Sha512;
+                $body
+            }
+        }
+    };
+}
+
+pub fn rand_bytes(buf: &mut [u8]) -> Result<(), CryptoError> {
+    getrandom::getrandom(buf).map_err(CryptoError::new)
+}
+
+pub fn hash(digest: Digest, buf: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    Ok(with_digest!(digest, D => D::digest(buf).to_vec()))
+}
+
+pub fn hmac(digest: Digest, key: &[u8], bufs: &[&[u8]]) -> Result<Vec<u8>, CryptoError> {
+    with_digest!(digest, D => {
+        let mut mac = <Hmac<D> as Mac>::new_from_slice(key).map_err(CryptoError::new)?;
+
+        for buf in bufs {
+            mac.update(buf);
+        }
+
+        Ok(mac.finalize().into_bytes().to_vec())
+    })
+}
+
+pub fn pbkdf2(
+    digest: Digest,
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    key: &mut [u8],
+) -> Result<(), CryptoError> {
+    with_digest!(digest, D => pbkdf2::pbkdf2_hmac::<D>(password, salt, iterations, key));
+
+    Ok(())
+}
+
+pub fn memeq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+fn apply_keystream<C: KeyIvInit + StreamCipher>(
+    key: &[u8],
+    iv: &[u8],
+    inp: &[u8],
+    outp: &mut [u8],
+) -> Result<(), CryptoError> {
+    let mut ctx = C::new_from_slices(key, iv).map_err(CryptoError::new)?;
+
+    outp.copy_from_slice(inp);
+    ctx.apply_keystream(outp);
+
+    Ok(())
+}
+
+pub fn ctr(
+    cipher: Cipher,
+    key: &[u8],
+    iv: &[u8],
+    inp: &[u8],
+    outp: &mut [u8],
+) -> Result<(), CryptoError> {
+    match cipher {
+        Cipher::Aes128Ctr | Cipher::Aes128CtrHmac => {
+            apply_keystream::<Aes128Ctr>(key, iv, inp, outp)
+        }
+        Cipher::Aes192Ctr | Cipher::Aes192CtrHmac => {
+            apply_keystream::<Aes192Ctr>(key, iv, inp, outp)
+        }
+        Cipher::Aes256Ctr | Cipher::Aes256CtrHmac => {
+            apply_keystream::<Aes256Ctr>(key, iv, inp, outp)
+        }
+        _ => unreachable!("not a CTR cipher: {}", cipher),
+    }
+}
+
+fn seal<C: KeyInit + AeadInPlace>(
+    key: &[u8],
+    iv: &[u8],
+    inp: &[u8],
+    outp: &mut [u8],
+    tag: &mut [u8],
+) -> Result<(), CryptoError> {
+    let ctx = C::new_from_slice(key).map_err(CryptoError::new)?;
+
+    outp.copy_from_slice(inp);
+
+    let result = ctx
+        .encrypt_in_place_detached(GenericArray::from_slice(iv), &[], outp)
+        .map_err(CryptoError::new)?;
+
+    tag.copy_from_slice(&result);
+
+    Ok(())
+}
+
+fn open<C: KeyInit + AeadInPlace>(
+    key: &[u8],
+    iv: &[u8],
+    inp: &[u8],
+    outp: &mut [u8],
+    tag: &[u8],
+) -> Result<bool, CryptoError> {
+    let ctx = C::new_from_slice(key).map_err(CryptoError::new)?;
+
+    outp.copy_from_slice(inp);
+
+    let result = ctx.decrypt_in_place_detached(
+        GenericArray::from_slice(iv),
+        &[],
+        outp,
+        GenericArray::from_slice(tag),
+    );
+
+    Ok(result.is_ok())
+}
+
+pub fn gcm_encrypt(
+    cipher: Cipher,
+    key: &[u8],
+    iv: &[u8],
+    inp: &[u8],
+    outp: &mut [u8],
+    tag: &mut [u8],
+) -> Result<(), CryptoError> {
+    match cipher {
+        Cipher::Aes128Gcm => seal::<Aes128Gcm>(key, iv, inp, outp, tag),
+        Cipher::Aes192Gcm => seal::<Aes192Gcm>(key, iv, inp, outp, tag),
+        Cipher::Aes256Gcm => seal::<Aes256Gcm>(key, iv, inp, outp, tag),
+        _ => unreachable!("not a GCM cipher: {}", cipher),
+    }
+}
+
+pub fn gcm_decrypt(
+    cipher: Cipher,
+    key: &[u8],
+    iv: &[u8],
+    inp: &[u8],
+    outp: &mut [u8],
+    tag: &[u8],
+) -> Result<bool, CryptoError> {
+    match cipher {
+        Cipher::Aes128Gcm => open::<Aes128Gcm>(key, iv, inp, outp, tag),
+        Cipher::Aes192Gcm => open::<Aes192Gcm>(key, iv, inp, outp, tag),
+        Cipher::Aes256Gcm => open::<Aes256Gcm>(key, iv, inp, outp, tag),
+        _ => unreachable!("not a GCM cipher: {}", cipher),
+    }
+}
+
+fn x25519_key(bytes: &[u8]) -> Result<[u8; 32], CryptoError> {
+    <[u8; 32]>::try_from(bytes).map_err(|_| CryptoError::new("invalid X25519 key length"))
+}
+
+pub fn x25519_public(private: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let secret = StaticSecret::from(x25519_key(private)?);
+
+    Ok(PublicKey::from(&secret).as_bytes().to_vec())
+}
+
+pub fn x25519_derive(private: &[u8], public: &[u8]) -> Result<SecureVec, CryptoError> {
+    let secret = StaticSecret::from(x25519_key(private)?);
+    let shared = secret.diffie_hellman(&PublicKey::from(x25519_key(public)?));
+
+    // like OpenSSL, reject an all-zero shared secret
+    if shared.was_contributory() {
+        Ok(shared.as_bytes().as_slice().into())
+    } else {
+        Err(CryptoError::new("invalid X25519 peer key"))
+    }
+}
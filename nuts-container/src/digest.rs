@@ -23,7 +23,6 @@
 #[cfg(test)]
 mod tests;
 
-use openssl::hash::MessageDigest;
 use std::fmt;
 use std::str::FromStr;
 
@@ -115,14 +114,4 @@ impl Digest {
 
         buf.put_u32(b)
     }
-
-    pub(crate) fn as_openssl(&self) -> MessageDigest {
-        match self {
-            Digest::Sha1 => MessageDigest::sha1(),
-            Digest::Sha224 => MessageDigest::sha224(),
-            Digest::Sha256 => MessageDigest::sha256(),
-            Digest::Sha384 => MessageDigest::sha384(),
-            Digest::Sha512 => MessageDigest::sha512(),
-        }
-    }
 }
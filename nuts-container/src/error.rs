@@ -23,8 +23,11 @@
 use nuts_backend::Backend;
 use thiserror::Error as ThisError;
 
+use crate::backup::BackupError;
 use crate::cipher::CipherError;
 use crate::header::HeaderError;
+use crate::integrity::IntegrityError;
+use crate::journal::JournalError;
 
 /// Error type used by this module.
 #[derive(Debug, ThisError)]
@@ -40,6 +43,23 @@ pub enum Error<B: Backend> {
     /// Errors coming from header evaluation.
     #[error(transparent)]
     Header(#[from] HeaderError),
+
+    /// Errors coming from a transaction.
+    #[error(transparent)]
+    Journal(#[from] JournalError),
+
+    /// Errors coming from the rollback protection.
+    #[error(transparent)]
+    Integrity(#[from] IntegrityError),
+
+    /// Errors coming from a header backup.
+    #[error(transparent)]
+    Backup(#[from] BackupError),
+
+    /// The container is [opened read-only](crate::OpenOptionsBuilder::read_only)
+    /// and cannot be modified.
+    #[error("the container is opened read-only")]
+    ReadOnly,
 }
 
 pub type ContainerResult<T, B> = Result<T, Error<B>>;
@@ -26,24 +26,27 @@ mod revision;
 mod tests;
 
 use log::{debug, error};
-use nuts_backend::Backend;
-use openssl::error::ErrorStack;
-use plain_secret::PlainSecret;
+use nuts_backend::{Backend, IdSize, HEADER_MAX_SIZE};
+use plain_secret::{PlainSecret, ServiceEntry};
 use std::fmt;
-use std::ops::DerefMut;
 use thiserror::Error;
 
 use crate::buffer::{BufferError, ToBuffer};
 use crate::cipher::{Cipher, CipherContext, CipherError};
-use crate::header::revision::{Data, Revision};
+use crate::crypto::CryptoError;
+use crate::header::revision::{Data, RecipientSlot, RecoverySlot, Revision};
+use crate::integrity::{self, Root};
 use crate::kdf::{Kdf, KdfError};
 use crate::migrate::{MigrationError, Migrator};
 use crate::options::CreateOptions;
 use crate::ossl;
 use crate::password::{PasswordError, PasswordStore};
+use crate::recipient::{self, KeyError, PrivateKey, PublicKey};
+use crate::recovery::RecoveryKey;
 use crate::svec::SecureVec;
+use crate::wrapping_key::WrappingKey;
 
-pub const LATEST_REVISION: u32 = 2;
+pub const LATEST_REVISION: u32 = 3;
 
 /// Header related errors.
 #[derive(Debug, Error)]
@@ -64,6 +67,34 @@ pub enum HeaderError {
     #[error("the password is wrong")]
     WrongPassword,
 
+    /// The recovery key is wrong.
+    #[error("the recovery key is wrong")]
+    WrongRecoveryKey,
+
+    /// The recovery key is malformed.
+    #[error("invalid recovery key")]
+    InvalidRecoveryKey,
+
+    /// The container has no recovery key.
+    #[error("the container has no recovery key")]
+    NoRecoveryKey,
+
+    /// The wrapping key does not unlock the container.
+    #[error("the wrapping key is wrong")]
+    WrongWrappingKey,
+
+    /// The private key does not belong to the recipient of the container.
+    #[error("the private key does not match the recipient")]
+    WrongPrivateKey,
+
+    /// The container has no recipient.
+    #[error("the container has no recipient")]
+    NoRecipient,
+
+    /// A key related error.
+    #[error(transparent)]
+    Key(#[from] KeyError),
+
     /// Invalid header revision
     #[error("invalid header revision, expected {0} but got {1}")]
     InvalidRevision(u32, u32),
@@ -76,6 +107,16 @@ pub enum HeaderError {
     #[error("invalid header")]
     InvalidHeader,
 
+    /// The header does not fit into the space reserved by the backend.
+    ///
+    /// The header needs the given number of bytes, but not more than
+    /// [`HEADER_MAX_SIZE`] bytes are available.
+    #[error(
+        "the header needs {0} bytes, but only {} bytes are available",
+        HEADER_MAX_SIZE
+    )]
+    TooLarge(usize),
+
     /// Invalid service identifeir (sid)
     #[error("invalid sid")]
     InvalidSid,
@@ -89,6 +130,14 @@ pub enum HeaderError {
         got: Option<u32>,
     },
 
+    /// A service with the given sid is already attached to the container.
+    #[error("the service {0} already exists")]
+    ServiceExists(u32),
+
+    /// No service with the given sid is attached to the container.
+    #[error("no such service: {0}")]
+    NoSuchService(u32),
+
     /// Invalid settings, could not parse backend settings from header.
     #[error("invalid settings")]
     InvalidSettings,
@@ -97,13 +146,21 @@ pub enum HeaderError {
     #[error("invalid top-id")]
     InvalidTopId,
 
+    /// Invalid journal-id, could not parse the id of the journal from header.
+    #[error("invalid journal-id")]
+    InvalidJournalId,
+
+    /// Invalid id of the integrity tree, could not parse the id from header.
+    #[error("invalid id of the integrity tree")]
+    InvalidIntegrityId,
+
     /// Error while (de-) serializing binary data.
     #[error(transparent)]
     Buffer(#[from] BufferError),
 
-    /// An error in the OpenSSL library occured.
+    /// An error in the crypto provider occured.
     #[error(transparent)]
-    OpenSSL(#[from] ErrorStack),
+    Crypto(#[from] CryptoError),
 
     /// Errors coming from a migration
     #[error(transparent)]
@@ -115,6 +172,10 @@ pub struct Header<'a, B: Backend> {
     migrator: Migrator<'a>,
     cipher: Cipher,
     kdf: Kdf,
+    recovery: Option<Kdf>,
+    recipient: Option<Vec<u8>>,
+    wrapping_key: Option<SecureVec>,
+    reserve_integrity: bool,
     data: PlainSecret<B>,
 }
 
@@ -133,13 +194,21 @@ impl<'a, B: Backend> Header<'a, B> {
         let kdf = options.kdf.build()?;
         let (revision, plain_secret) = PlainSecret::create_latest(key.into(), iv.into(), settings)?;
 
-        Ok(Header {
+        let header = Header {
             revision,
             migrator: Migrator::default(),
             cipher,
             kdf,
+            recovery: None,
+            recipient: None,
+            wrapping_key: None,
+            reserve_integrity: options.rollback_protection,
             data: plain_secret,
-        })
+        };
+
+        header.check_size()?;
+
+        Ok(header)
     }
 
     pub fn read(
@@ -147,19 +216,147 @@ impl<'a, B: Backend> Header<'a, B> {
         migrator: Migrator<'a>,
         store: &mut PasswordStore,
     ) -> Result<Header<'a, B>, HeaderError> {
-        match Revision::get_from_buffer(&mut &buf[..])? {
-            Revision::Rev0(data) => Self::read_rev0(data, migrator, store),
-            Revision::Rev1(data) => Self::read_rev1(data, migrator, store),
-            Revision::Rev2(data) => Self::read_rev2(data, migrator, store),
+        let revision = Revision::get_from_buffer(&mut &buf[..])?;
+        let data = revision.data();
+        let key = Self::create_key(data.cipher, &data.kdf, store)?;
+
+        Self::unlock(revision, migrator, key)
+    }
+
+    /// Reads the header and unlocks it with a wrapping key.
+    ///
+    /// The wrapping key was taken from a header, which was unlocked before,
+    /// see [`Header::wrapping_key`]. The key derivation function is not
+    /// evaluated.
+    pub fn read_wrapping_key(
+        buf: &[u8],
+        migrator: Migrator<'a>,
+        key: &WrappingKey,
+    ) -> Result<Header<'a, B>, HeaderError> {
+        let revision = Revision::get_from_buffer(&mut &buf[..])?;
+
+        if key.as_bytes().len() < revision.data().cipher.key_len() {
+            return Err(HeaderError::WrongWrappingKey);
+        }
+
+        match Self::unlock(revision, migrator, key.as_bytes().into()) {
+            Err(HeaderError::WrongPassword)
+            | Err(HeaderError::Cipher(CipherError::NotTrustworthy | CipherError::MacMismatch)) => {
+                Err(HeaderError::WrongWrappingKey)
+            }
+            result => result,
+        }
+    }
+
+    /// Reads the header and unlocks it with a recovery key.
+    ///
+    /// The recovery key is taken from the `store`. The wrapping key of the
+    /// secret is decrypted from the recovery slot and kept in the header, so
+    /// the header can be written without a password.
+    pub fn read_recovery(
+        buf: &[u8],
+        migrator: Migrator<'a>,
+        store: &mut PasswordStore,
+    ) -> Result<Header<'a, B>, HeaderError> {
+        let (data, slot, recipient) = match Revision::get_from_buffer(&mut &buf[..])? {
+            Revision::Rev3(data, Some(slot), recipient) => (data, slot, recipient),
+            _ => return Err(HeaderError::NoRecoveryKey),
+        };
+
+        let recovery_key =
+            RecoveryKey::parse(store.value()?).ok_or(HeaderError::InvalidRecoveryKey)?;
+        let recovery_wrapping_key = slot
+            .kdf
+            .create_key(recovery_key.as_bytes(), data.cipher.key_len())?;
+
+        let (key, plain_secret) = Self::unwrap_secret(
+            &data,
+            &recovery_wrapping_key,
+            &slot.iv,
+            &slot.secret,
+            || HeaderError::WrongRecoveryKey,
+        )?;
+
+        Ok(Header {
+            revision: 3,
+            migrator,
+            cipher: data.cipher,
+            kdf: data.kdf,
+            recovery: Some(slot.kdf),
+            recipient: recipient.map(|slot| slot.ephemeral),
+            wrapping_key: Some(key),
+            reserve_integrity: false,
+            data: plain_secret,
+        })
+    }
+
+    /// Reads the header and unlocks it with the private key of the
+    /// recipient.
+    ///
+    /// The wrapping key of the secret is decrypted from the recipient slot
+    /// and kept in the header, so the header can be written without a
+    /// password.
+    pub fn read_private_key(
+        buf: &[u8],
+        migrator: Migrator<'a>,
+        private_key: &PrivateKey,
+    ) -> Result<Header<'a, B>, HeaderError> {
+        let (data, recovery, slot) = match Revision::get_from_buffer(&mut &buf[..])? {
+            Revision::Rev3(data, recovery, Some(slot)) => (data, recovery, slot),
+            _ => return Err(HeaderError::NoRecipient),
+        };
+
+        let ephemeral = PublicKey::from_bytes(&slot.ephemeral)?;
+        let recipient = private_key.public_key()?.to_bytes()?;
+        let recipient_wrapping_key = recipient::derive_key(
+            private_key,
+            &ephemeral,
+            &slot.ephemeral,
+            &recipient,
+            data.cipher.key_len(),
+        )?;
+
+        let (key, plain_secret) = Self::unwrap_secret(
+            &data,
+            &recipient_wrapping_key,
+            &slot.iv,
+            &slot.secret,
+            || HeaderError::WrongPrivateKey,
+        )?;
+
+        Ok(Header {
+            revision: 3,
+            migrator,
+            cipher: data.cipher,
+            kdf: data.kdf,
+            recovery: recovery.map(|slot| slot.kdf),
+            recipient: Some(slot.ephemeral),
+            wrapping_key: Some(key),
+            reserve_integrity: false,
+            data: plain_secret,
+        })
+    }
+
+    fn unlock(
+        revision: Revision,
+        migrator: Migrator<'a>,
+        key: SecureVec,
+    ) -> Result<Header<'a, B>, HeaderError> {
+        match revision {
+            Revision::Rev0(data) => Self::read_rev0(data, migrator, key),
+            Revision::Rev1(data) => Self::read_rev1(data, migrator, key),
+            Revision::Rev2(data) => Self::read_rev2(data, migrator, key),
+            Revision::Rev3(data, recovery, recipient) => {
+                Self::read_rev3(data, recovery, recipient, migrator, key)
+            }
         }
     }
 
     fn read_rev0(
         data: Data,
         migrator: Migrator<'a>,
-        store: &mut PasswordStore,
+        key: SecureVec,
     ) -> Result<Header<'a, B>, HeaderError> {
-        let key = Self::create_key(data.cipher, &data.kdf, store)?;
         let mut ctx = Self::prepare_cipher_ctx(data.cipher, &data.secret);
 
         let pbuf = ctx.decrypt(&key, &data.iv)?;
@@ -170,6 +367,10 @@ impl<'a, B: Backend> Header<'a, B> {
             migrator,
             cipher: data.cipher,
             kdf: data.kdf,
+            recovery: None,
+            recipient: None,
+            wrapping_key: Some(key),
+            reserve_integrity: false,
             data: plain_secret,
         })
     }
@@ -177,9 +378,8 @@ impl<'a, B: Backend> Header<'a, B> {
     fn read_rev1(
         data: Data,
         migrator: Migrator<'a>,
-        store: &mut PasswordStore,
+        key: SecureVec,
     ) -> Result<Header<'a, B>, HeaderError> {
-        let key = Self::create_key(data.cipher, &data.kdf, store)?;
         let mut ctx = Self::prepare_cipher_ctx(data.cipher, &data.secret);
 
         let pbuf = ctx.decrypt(&key, &data.iv)?;
@@ -190,6 +390,10 @@ impl<'a, B: Backend> Header<'a, B> {
             migrator,
             cipher: data.cipher,
             kdf: data.kdf,
+            recovery: None,
+            recipient: None,
+            wrapping_key: Some(key),
+            reserve_integrity: false,
             data: plain_secret,
         })
     }
@@ -197,9 +401,8 @@ impl<'a, B: Backend> Header<'a, B> {
     fn read_rev2(
         data: Data,
         migrator: Migrator<'a>,
-        store: &mut PasswordStore,
+        key: SecureVec,
     ) -> Result<Header<'a, B>, HeaderError> {
-        let key = Self::create_key(data.cipher, &data.kdf, store)?;
         let mut ctx = Self::prepare_cipher_ctx(data.cipher, &data.secret);
 
         let pbuf = ctx.decrypt(&key, &data.iv)?;
@@ -210,6 +413,35 @@ impl<'a, B: Backend> Header<'a, B> {
             migrator,
             cipher: data.cipher,
             kdf: data.kdf,
+            recovery: None,
+            recipient: None,
+            wrapping_key: Some(key),
+            reserve_integrity: false,
+            data: plain_secret,
+        })
+    }
+
+    fn read_rev3(
+        data: Data,
+        recovery: Option<RecoverySlot>,
+        recipient: Option<RecipientSlot>,
+        migrator: Migrator<'a>,
+        key: SecureVec,
+    ) -> Result<Header<'a, B>, HeaderError> {
+        let mut ctx = Self::prepare_cipher_ctx(data.cipher, &data.secret);
+
+        let pbuf = ctx.decrypt(&key, &data.iv)?;
+        let plain_secret = PlainSecret::from_buffer_rev3(&mut &pbuf[..])?;
+
+        Ok(Header {
+            revision: 3,
+            migrator,
+            cipher: data.cipher,
+            kdf: data.kdf,
+            recovery: recovery.map(|slot| slot.kdf),
+            recipient: recipient.map(|slot| slot.ephemeral),
+            wrapping_key: Some(key),
+            reserve_integrity: false,
             data: plain_secret,
         })
     }
@@ -218,22 +450,167 @@ impl<'a, B: Backend> Header<'a, B> {
         let mut iv = vec![0; self.cipher.iv_len()];
         ossl::rand_bytes(&mut iv)?;
 
-        let mut pbuf: SecureVec = vec![].into();
-        self.data.to_buffer(pbuf.deref_mut())?;
+        let mut pbuf = SecureVec::new();
+        self.data.to_buffer(&mut pbuf)?;
 
-        let key = Self::create_key(self.cipher, &self.kdf, store)?;
+        let key = match self.wrapping_key.as_ref() {
+            Some(key) => key.clone(),
+            None => Self::create_key(self.cipher, &self.kdf, store)?,
+        };
         let mut ctx = Self::prepare_cipher_ctx(self.cipher, &pbuf);
 
         let cbuf = ctx.encrypt(&key, &iv)?;
         let secret = cbuf.to_vec();
 
+        let rev = self.build_revision(
+            iv,
+            secret,
+            |kdf, recovery_wrapping_key| {
+                self.create_recovery_slot(kdf, recovery_wrapping_key, &key)
+            },
+            |ephemeral, recipient_wrapping_key| {
+                self.create_recipient_slot(ephemeral, recipient_wrapping_key, &key)
+            },
+        )?;
+
+        let mut hbuf = vec![];
+        rev.put_into_buffer(&mut hbuf)?;
+
+        match buf.get_mut(..hbuf.len()) {
+            Some(target) => {
+                target.copy_from_slice(&hbuf);
+                Ok(())
+            }
+            None => Err(HeaderError::TooLarge(hbuf.len())),
+        }
+    }
+
+    /// Returns the number of bytes needed to [write](Self::write) the
+    /// header.
+    pub fn size(&self) -> Result<usize, HeaderError> {
+        let mut pbuf = SecureVec::new();
+        self.data.to_buffer(&mut pbuf)?;
+
+        // the ciphertext is extended by the tag
+        let tag_size = self.cipher.tag_size() as usize;
+        let iv = vec![0; self.cipher.iv_len()];
+        let secret = vec![0; pbuf.len() + tag_size];
+        let slot_secret = vec![0; self.wrapping_key_len() + tag_size];
+
+        let rev = self.build_revision(
+            iv.clone(),
+            secret,
+            |kdf, _| {
+                Ok(RecoverySlot {
+                    kdf: kdf.clone(),
+                    iv: iv.clone(),
+                    secret: slot_secret.clone(),
+                })
+            },
+            |ephemeral, _| {
+                Ok(RecipientSlot {
+                    ephemeral: ephemeral.to_vec(),
+                    iv: iv.clone(),
+                    secret: slot_secret.clone(),
+                })
+            },
+        )?;
+
+        let mut hbuf = vec![];
+        rev.put_into_buffer(&mut hbuf)?;
+
+        Ok(hbuf.len())
+    }
+
+    /// Returns the length of the wrapping key, which is stored in the slots.
+    fn wrapping_key_len(&self) -> usize {
+        match self.wrapping_key.as_ref() {
+            Some(key) => key.len(),
+            None if self.cipher.key_len() > 0 => self.kdf.key_len(self.cipher.key_len()),
+            None => 0,
+        }
+    }
+
+    /// Tests whether the header fits into [`HEADER_MAX_SIZE`] bytes.
+    ///
+    /// Space is reserved for values, which are stored later, while the
+    /// container is in use: The first service, the id of the journal and the
+    /// root of the integrity tree, if rollback protection is enabled.
+    /// Otherwise the container can become unusable, once the header cannot
+    /// be written anymore.
+    ///
+    /// Returns an [`HeaderError::TooLarge`] error, if the header does not
+    /// fit.
+    pub fn check_size(&self) -> Result<(), HeaderError> {
+        let mut size = self.size()?;
+
+        if let PlainSecret::Rev3(rev3) = &self.data {
+            if rev3.services.is_empty() {
+                size += 4 + 1 + B::Id::size();
+            }
+
+            if rev3.journal.is_none() {
+                size += B::Id::size();
+            }
+
+            if rev3.integrity.is_none() && self.reserve_integrity {
+                size += B::Id::size() + 1 + integrity::DIGEST.size();
+            }
+        }
+
+        if size > HEADER_MAX_SIZE {
+            Err(HeaderError::TooLarge(size))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Creates the revision, which is written into the header.
+    ///
+    /// The slots of the latest revision are created with `recovery_slot` and
+    /// `recipient_slot`.
+    fn build_revision<R, P>(
+        &self,
+        iv: Vec<u8>,
+        secret: Vec<u8>,
+        recovery_slot: R,
+        recipient_slot: P,
+    ) -> Result<Revision, HeaderError>
+    where
+        R: FnOnce(&Kdf, &[u8]) -> Result<RecoverySlot, HeaderError>,
+        P: FnOnce(&[u8], &[u8]) -> Result<RecipientSlot, HeaderError>,
+    {
         let rev = match self.data {
             PlainSecret::Rev0(_) => Revision::new_rev0(self.cipher, iv, self.kdf.clone(), secret),
             PlainSecret::Rev1(_) => Revision::new_rev1(self.cipher, iv, self.kdf.clone(), secret),
             PlainSecret::Rev2(_) => Revision::new_rev2(self.cipher, iv, self.kdf.clone(), secret),
+            PlainSecret::Rev3(ref rev3) => {
+                let recovery = match (self.recovery.as_ref(), rev3.recovery.as_ref()) {
+                    (Some(kdf), Some(recovery_wrapping_key)) => {
+                        Some(recovery_slot(kdf, recovery_wrapping_key)?)
+                    }
+                    _ => None,
+                };
+
+                let recipient = match (self.recipient.as_ref(), rev3.recipient.as_ref()) {
+                    (Some(ephemeral), Some(recipient_wrapping_key)) => {
+                        Some(recipient_slot(ephemeral, recipient_wrapping_key)?)
+                    }
+                    _ => None,
+                };
+
+                Revision::new_rev3(
+                    self.cipher,
+                    iv,
+                    self.kdf.clone(),
+                    secret,
+                    recovery,
+                    recipient,
+                )
+            }
         };
 
-        rev.put_into_buffer(&mut &mut buf[..])
+        Ok(rev)
     }
 
     pub fn migrate(&mut self) -> Result<(), HeaderError> {
@@ -278,6 +655,7 @@ impl<'a, B: Backend> Header<'a, B> {
             PlainSecret::Rev0(rev0) => &rev0.settings,
             PlainSecret::Rev1(rev1) => &rev1.settings,
             PlainSecret::Rev2(rev2) => &rev2.settings,
+            PlainSecret::Rev3(rev3) => &rev3.settings,
         }
     }
 
@@ -286,6 +664,7 @@ impl<'a, B: Backend> Header<'a, B> {
             PlainSecret::Rev0(rev0) => &rev0.key,
             PlainSecret::Rev1(rev1) => &rev1.key,
             PlainSecret::Rev2(rev2) => &rev2.key,
+            PlainSecret::Rev3(rev3) => &rev3.key,
         }
     }
 
@@ -294,14 +673,21 @@ impl<'a, B: Backend> Header<'a, B> {
             PlainSecret::Rev0(rev0) => &rev0.iv,
             PlainSecret::Rev1(rev1) => &rev1.iv,
             PlainSecret::Rev2(rev2) => &rev2.iv,
+            PlainSecret::Rev3(rev3) => &rev3.iv,
         }
     }
 
-    pub fn accept_sid_for_create(&self) -> Result<(), HeaderError> {
+    pub fn accept_sid_for_create(&self, sid: u32) -> Result<(), HeaderError> {
         let sid_opt = match &self.data {
             PlainSecret::Rev0(rev0) => rev0.sid,
             PlainSecret::Rev1(_) => None,
             PlainSecret::Rev2(rev2) => rev2.sid,
+            PlainSecret::Rev3(rev3) => {
+                return match rev3.service(sid) {
+                    Some(_) => Err(HeaderError::ServiceExists(sid)),
+                    None => Ok(()),
+                }
+            }
         };
 
         if sid_opt.is_none() {
@@ -344,38 +730,264 @@ impl<'a, B: Backend> Header<'a, B> {
                 Ok(())
             }
             PlainSecret::Rev2(rev2) => accecpt(rev2.sid),
+            PlainSecret::Rev3(rev3) => match rev3.service(sid) {
+                Some(_) => {
+                    debug!("service {} found", sid);
+                    Ok(())
+                }
+                None => {
+                    error!("no such service: {}", sid);
+                    Err(HeaderError::NoSuchService(sid))
+                }
+            },
         }
     }
 
-    pub fn set_sid(&mut self, sid: u32) -> Result<(), HeaderError> {
+    pub fn add_service(&mut self, sid: u32, top_id: Option<B::Id>) -> Result<(), HeaderError> {
         match &mut self.data {
-            PlainSecret::Rev0(_) => panic!("storing a sid into a rev0 header is not supported"),
-            PlainSecret::Rev1(_) => panic!("storing a sid into a rev1 header is not supported"),
-            PlainSecret::Rev2(rev2) => {
-                if sid > 0 {
-                    rev2.sid = Some(sid);
-                    Ok(())
+            PlainSecret::Rev0(_) => panic!("adding a service to a rev0 header is not supported"),
+            PlainSecret::Rev1(_) => panic!("adding a service to a rev1 header is not supported"),
+            PlainSecret::Rev2(_) => panic!("adding a service to a rev2 header is not supported"),
+            PlainSecret::Rev3(rev3) => {
+                if sid == 0 {
+                    return Err(HeaderError::InvalidSid);
+                } else if rev3.service(sid).is_some() {
+                    return Err(HeaderError::ServiceExists(sid));
                 } else {
-                    Err(HeaderError::InvalidSid)
+                    rev3.services.push(ServiceEntry { sid, top_id });
                 }
             }
         }
+
+        // the service is rejected, if the header cannot store it
+        if let Err(err) = self.check_size() {
+            if let PlainSecret::Rev3(rev3) = &mut self.data {
+                rev3.services.pop();
+            }
+
+            return Err(err);
+        }
+
+        Ok(())
     }
 
-    pub fn top_id(&self) -> Option<&B::Id> {
+    /// Returns the sids of all services attached to the container.
+    pub fn services(&self) -> Vec<u32> {
+        match &self.data {
+            PlainSecret::Rev0(rev0) => rev0.sid.into_iter().collect(),
+            PlainSecret::Rev1(_) => vec![],
+            PlainSecret::Rev2(rev2) => rev2.sid.into_iter().collect(),
+            PlainSecret::Rev3(rev3) => rev3.services.iter().map(|entry| entry.sid).collect(),
+        }
+    }
+
+    /// Returns the top-id of the service with the given `sid`.
+    ///
+    /// Headers prior to revision 3 can hold only one service, the `sid` is
+    /// ignored for them.
+    pub fn top_id(&self, sid: Option<u32>) -> Option<&B::Id> {
         match &self.data {
             PlainSecret::Rev0(rev0) => rev0.top_id.as_ref(),
             PlainSecret::Rev1(rev1) => rev1.top_id.as_ref(),
             PlainSecret::Rev2(rev2) => rev2.top_id.as_ref(),
+            PlainSecret::Rev3(rev3) => sid
+                .and_then(|sid| rev3.service(sid))
+                .and_then(|entry| entry.top_id.as_ref()),
+        }
+    }
+
+    /// Returns the id of the first journal block of a pending transaction.
+    pub fn journal(&self) -> Option<&B::Id> {
+        match &self.data {
+            PlainSecret::Rev0(_) | PlainSecret::Rev1(_) | PlainSecret::Rev2(_) => None,
+            PlainSecret::Rev3(rev3) => rev3.journal.as_ref(),
+        }
+    }
+
+    /// Updates the id of the first journal block.
+    ///
+    /// Only a header of the latest revision can store a journal, for all other
+    /// revisions an [`HeaderError::InvalidRevision`] error is returned.
+    pub fn set_journal(&mut self, id: Option<B::Id>) -> Result<bool, HeaderError> {
+        match &mut self.data {
+            PlainSecret::Rev0(_) | PlainSecret::Rev1(_) | PlainSecret::Rev2(_) => {
+                Err(HeaderError::InvalidRevision(LATEST_REVISION, self.revision))
+            }
+            PlainSecret::Rev3(rev3) => {
+                let changed = rev3.journal != id;
+
+                rev3.journal = id;
+
+                Ok(changed)
+            }
+        }
+    }
+
+    /// Returns the root of the integrity tree.
+    ///
+    /// The root is only available if rollback protection is enabled for the
+    /// container.
+    pub fn integrity(&self) -> Option<&Root<B>> {
+        match &self.data {
+            PlainSecret::Rev0(_) | PlainSecret::Rev1(_) | PlainSecret::Rev2(_) => None,
+            PlainSecret::Rev3(rev3) => rev3.integrity.as_ref(),
+        }
+    }
+
+    /// Updates the root of the integrity tree.
+    ///
+    /// Only a header of the latest revision can store an integrity tree, for
+    /// all other revisions an [`HeaderError::InvalidRevision`] error is
+    /// returned.
+    pub fn set_integrity(&mut self, root: Option<Root<B>>) -> Result<bool, HeaderError> {
+        match &mut self.data {
+            PlainSecret::Rev0(_) | PlainSecret::Rev1(_) | PlainSecret::Rev2(_) => {
+                Err(HeaderError::InvalidRevision(LATEST_REVISION, self.revision))
+            }
+            PlainSecret::Rev3(rev3) => {
+                let changed = rev3.integrity != root;
+
+                rev3.integrity = root;
+
+                Ok(changed)
+            }
+        }
+    }
+
+    /// Tests whether a recovery key is assigned to the container.
+    pub fn has_recovery_key(&self) -> bool {
+        self.recovery.is_some()
+    }
+
+    /// Generates a recovery key for the container.
+    ///
+    /// The key derived from the recovery key with the given `kdf` is stored in
+    /// the secret. On each [write](Self::write) it encrypts the wrapping key
+    /// of the secret into the recovery slot of the header.
+    ///
+    /// Only a header of the latest revision can store a recovery key, for all
+    /// other revisions an [`HeaderError::InvalidRevision`] error is returned.
+    pub fn generate_recovery_key(&mut self, kdf: Kdf) -> Result<RecoveryKey, HeaderError> {
+        let recovery_key = match &mut self.data {
+            PlainSecret::Rev0(_) | PlainSecret::Rev1(_) | PlainSecret::Rev2(_) => {
+                return Err(HeaderError::InvalidRevision(LATEST_REVISION, self.revision))
+            }
+            PlainSecret::Rev3(rev3) => {
+                let recovery_key = RecoveryKey::generate()?;
+                let key = kdf.create_key(recovery_key.as_bytes(), self.cipher.key_len())?;
+
+                rev3.recovery = Some(key);
+                self.recovery = Some(kdf);
+
+                recovery_key
+            }
+        };
+
+        // the slot is rejected, if the header cannot store it
+        if let Err(err) = self.check_size() {
+            if let PlainSecret::Rev3(rev3) = &mut self.data {
+                rev3.recovery = None;
+            }
+
+            self.recovery = None;
+
+            return Err(err);
         }
+
+        Ok(recovery_key)
+    }
+
+    /// Tests whether a recipient is assigned to the container.
+    pub fn has_recipient(&self) -> bool {
+        self.recipient.is_some()
     }
 
-    pub fn set_top_id(&mut self, id: B::Id) {
+    /// Assigns a recipient to the container.
+    ///
+    /// An ephemeral X25519 key is generated, the key derived from the key
+    /// agreement with the `recipient` is stored in the secret. On each
+    /// [write](Self::write) it encrypts the wrapping key of the secret into
+    /// the recipient slot of the header. Only the ephemeral public key is
+    /// stored in the slot, the container is unlocked with the private key of
+    /// the recipient.
+    ///
+    /// Only a header of the latest revision can store a recipient, for all
+    /// other revisions an [`HeaderError::InvalidRevision`] error is returned.
+    pub fn set_recipient(&mut self, recipient: &PublicKey) -> Result<(), HeaderError> {
         match &mut self.data {
-            PlainSecret::Rev0(_) => panic!("storing a top-id into a rev0 header is not supported"),
-            PlainSecret::Rev1(_) => panic!("storing a top-id into a rev1 header is not supported"),
-            PlainSecret::Rev2(rev2) => rev2.top_id = Some(id),
+            PlainSecret::Rev0(_) | PlainSecret::Rev1(_) | PlainSecret::Rev2(_) => {
+                return Err(HeaderError::InvalidRevision(LATEST_REVISION, self.revision))
+            }
+            PlainSecret::Rev3(rev3) => {
+                let ephemeral_key = PrivateKey::generate()?;
+                let ephemeral = ephemeral_key.public_key()?.to_bytes()?;
+                let key = recipient::derive_key(
+                    &ephemeral_key,
+                    recipient,
+                    &ephemeral,
+                    &recipient.to_bytes()?,
+                    self.cipher.key_len(),
+                )?;
+
+                rev3.recipient = Some(key);
+                self.recipient = Some(ephemeral);
+            }
+        }
+
+        // the slot is rejected, if the header cannot store it
+        if let Err(err) = self.check_size() {
+            if let PlainSecret::Rev3(rev3) = &mut self.data {
+                rev3.recipient = None;
+            }
+
+            self.recipient = None;
+
+            return Err(err);
         }
+
+        Ok(())
+    }
+
+    /// Generates a random wrapping key for the secret.
+    ///
+    /// Used for containers without a password, which are only unlocked with
+    /// a [recipient](Self::set_recipient).
+    pub fn generate_wrapping_key(&mut self) -> Result<(), HeaderError> {
+        let mut key = vec![0; self.cipher.key_len()];
+        ossl::rand_bytes(&mut key)?;
+
+        self.wrapping_key = Some(key.into());
+
+        Ok(())
+    }
+
+    /// Returns the wrapping key of the secret.
+    ///
+    /// This is the key, which unlocked the header or which was generated
+    /// randomly. Returns [`None`] if the key is not known yet.
+    pub fn wrapping_key(&self) -> Option<&[u8]> {
+        self.wrapping_key.as_deref()
+    }
+
+    /// Derives the wrapping key from the password, if it is not known yet.
+    ///
+    /// Subsequent [writes](Self::write) reuse the key, thus the key
+    /// derivation function runs only once.
+    pub fn derive_wrapping_key(&mut self, store: &mut PasswordStore) -> Result<(), HeaderError> {
+        if self.wrapping_key.is_none() {
+            self.wrapping_key = Some(Self::create_key(self.cipher, &self.kdf, store)?);
+        }
+
+        Ok(())
+    }
+
+    /// Drops the wrapping key, which unlocked the header or which was
+    /// generated randomly.
+    ///
+    /// The next [write](Self::write) derives the wrapping key from the
+    /// password again.
+    pub fn reset_wrapping_key(&mut self) {
+        self.wrapping_key = None;
     }
 
     pub fn set_migrator(&mut self, migrator: Migrator<'a>) {
@@ -383,7 +995,84 @@ impl<'a, B: Backend> Header<'a, B> {
     }
 
     pub fn convert_to_latest(&mut self, sid: u32) -> bool {
-        self.data.convert_to_latest(sid)
+        let changed = self.data.convert_to_latest(sid);
+
+        if changed {
+            self.revision = LATEST_REVISION;
+        }
+
+        changed
+    }
+
+    fn create_recipient_slot(
+        &self,
+        ephemeral: &[u8],
+        recipient_wrapping_key: &[u8],
+        key: &[u8],
+    ) -> Result<RecipientSlot, HeaderError> {
+        let mut iv = vec![0; self.cipher.iv_len()];
+        ossl::rand_bytes(&mut iv)?;
+
+        let mut ctx = Self::prepare_cipher_ctx(self.cipher, key);
+        let secret = ctx.encrypt(recipient_wrapping_key, &iv)?.to_vec();
+
+        Ok(RecipientSlot {
+            ephemeral: ephemeral.to_vec(),
+            iv,
+            secret,
+        })
+    }
+
+    fn create_recovery_slot(
+        &self,
+        kdf: &Kdf,
+        recovery_wrapping_key: &[u8],
+        key: &[u8],
+    ) -> Result<RecoverySlot, HeaderError> {
+        let mut iv = vec![0; self.cipher.iv_len()];
+        ossl::rand_bytes(&mut iv)?;
+
+        let mut ctx = Self::prepare_cipher_ctx(self.cipher, key);
+        let secret = ctx.encrypt(recovery_wrapping_key, &iv)?.to_vec();
+
+        Ok(RecoverySlot {
+            kdf: kdf.clone(),
+            iv,
+            secret,
+        })
+    }
+
+    /// Decrypts the wrapping key from a slot and the secret with the
+    /// wrapping key.
+    ///
+    /// If the slot cannot be decrypted with `slot_key`, the error returned by
+    /// `wrong` is raised.
+    fn unwrap_secret<F: Fn() -> HeaderError>(
+        data: &Data,
+        slot_key: &[u8],
+        slot_iv: &[u8],
+        slot_secret: &[u8],
+        wrong: F,
+    ) -> Result<(SecureVec, PlainSecret<B>), HeaderError> {
+        let mut ctx = Self::prepare_cipher_ctx(data.cipher, slot_secret);
+        let key: SecureVec = match ctx.decrypt(slot_key, slot_iv) {
+            Ok(key) => key.to_vec().into(),
+            Err(CipherError::NotTrustworthy | CipherError::MacMismatch) => return Err(wrong()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut ctx = Self::prepare_cipher_ctx(data.cipher, &data.secret);
+        let pbuf = match ctx.decrypt(&key, &data.iv) {
+            Ok(pbuf) => pbuf,
+            Err(CipherError::NotTrustworthy | CipherError::MacMismatch) => return Err(wrong()),
+            Err(err) => return Err(err.into()),
+        };
+
+        match PlainSecret::from_buffer_rev3(&mut &pbuf[..]) {
+            Ok(plain_secret) => Ok((key, plain_secret)),
+            Err(HeaderError::WrongPassword) => Err(wrong()),
+            Err(err) => Err(err),
+        }
     }
 
     fn prepare_cipher_ctx(cipher: Cipher, input: &[u8]) -> CipherContext {
@@ -415,6 +1104,8 @@ impl<'a, B: Backend> fmt::Debug for Header<'a, B> {
             .field("migrator", &self.migrator)
             .field("cipher", &self.cipher)
             .field("kdf", &self.kdf)
+            .field("recovery", &self.recovery)
+            .field("recipient", &self.recipient)
             .field("data", &self.data)
             .finish()
     }
@@ -24,11 +24,12 @@
 mod tests;
 
 use nuts_backend::{Backend, Binary};
-use openssl::error::ErrorStack;
 use std::fmt::{self, Write};
 
 use crate::buffer::{Buffer, BufferError, BufferMut, ToBuffer};
+use crate::crypto::CryptoError;
 use crate::header::HeaderError;
+use crate::integrity::Root;
 use crate::migrate::Migrator;
 use crate::ossl;
 use crate::svec::SecureVec;
@@ -70,12 +71,20 @@ fn fmt_key_iv(key: &[u8], iv: &[u8]) -> Result<(String, String), fmt::Error> {
 // * rev 2
 //
 // - sid inserted
+//
+// * rev 3
+//
+// - sid and top_id replaced by a table of services
+// - journal inserted
+// - root of the integrity tree inserted
+// - key of the recovery slot inserted
+// - key of the recipient slot inserted
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Magics([u32; 2]);
 
 impl Magics {
-    fn generate() -> Result<Magics, ErrorStack> {
+    fn generate() -> Result<Magics, CryptoError> {
         ossl::rand_u32().map(|magic| Magics([magic, magic]))
     }
 
@@ -233,11 +242,123 @@ impl<B: Backend> fmt::Debug for PlainRev2<B> {
     }
 }
 
+/// A service attached to the container.
+pub struct ServiceEntry<B: Backend> {
+    pub sid: u32,
+    pub top_id: Option<B::Id>,
+}
+
+impl<B: Backend> ServiceEntry<B> {
+    fn get_from_buffer<T: Buffer>(buf: &mut T) -> Result<ServiceEntry<B>, HeaderError> {
+        let sid = buf.get_u32()?;
+        let top_id_bytes: SecureVec = buf.get_vec::<1>()?.into();
+
+        if sid == 0 {
+            return Err(HeaderError::InvalidSid);
+        }
+
+        let top_id = if !top_id_bytes.is_empty() {
+            Some(Binary::from_bytes(&top_id_bytes).ok_or(HeaderError::InvalidTopId)?)
+        } else {
+            None
+        };
+
+        Ok(ServiceEntry { sid, top_id })
+    }
+
+    fn put<T: BufferMut>(&self, buf: &mut T) -> Result<(), BufferError> {
+        buf.put_u32(self.sid)?;
+
+        match self.top_id.as_ref() {
+            Some(id) => buf.put_vec::<1>(&id.as_bytes()),
+            None => buf.put_vec::<1>(&[]),
+        }
+    }
+}
+
+impl<B: Backend> Clone for ServiceEntry<B> {
+    fn clone(&self) -> Self {
+        ServiceEntry {
+            sid: self.sid,
+            top_id: self.top_id.clone(),
+        }
+    }
+}
+
+impl<B: Backend> PartialEq for ServiceEntry<B> {
+    fn eq(&self, other: &ServiceEntry<B>) -> bool {
+        self.sid == other.sid && self.top_id == other.top_id
+    }
+}
+
+impl<B: Backend> fmt::Debug for ServiceEntry<B> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("ServiceEntry")
+            .field("sid", &self.sid)
+            .field("top_id", &self.top_id.as_ref().map(ToString::to_string))
+            .finish()
+    }
+}
+
+pub struct PlainRev3<B: Backend> {
+    pub magics: Magics,
+    pub key: SecureVec,
+    pub iv: SecureVec,
+    pub services: Vec<ServiceEntry<B>>,
+    pub journal: Option<B::Id>,
+    pub integrity: Option<Root<B>>,
+    pub recovery: Option<SecureVec>,
+    pub recipient: Option<SecureVec>,
+    pub settings: B::Settings,
+}
+
+impl<B: Backend> PlainRev3<B> {
+    pub fn service(&self, sid: u32) -> Option<&ServiceEntry<B>> {
+        self.services.iter().find(|entry| entry.sid == sid)
+    }
+}
+
+impl<B: Backend> PartialEq for PlainRev3<B> {
+    fn eq(&self, other: &PlainRev3<B>) -> bool {
+        let lhs_settings_bytes = self.settings.as_bytes();
+        let rhs_settings_bytes = other.settings.as_bytes();
+
+        self.magics == other.magics
+            && self.key == other.key
+            && self.iv == other.iv
+            && self.services == other.services
+            && self.journal == other.journal
+            && self.integrity == other.integrity
+            && self.recovery == other.recovery
+            && self.recipient == other.recipient
+            && lhs_settings_bytes == rhs_settings_bytes
+    }
+}
+
+impl<B: Backend> fmt::Debug for PlainRev3<B> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let (key, iv) = fmt_key_iv(&self.key, &self.iv)?;
+
+        fmt.debug_struct("PlainRev3")
+            .field("magics", &self.magics)
+            .field("key", &key)
+            .field("iv", &iv)
+            .field("services", &self.services)
+            .field("journal", &self.journal.as_ref().map(|id| id.to_string()))
+            .field("integrity", &self.integrity)
+            .field("recovery", &self.recovery.as_ref().map(|_| "***"))
+            .field("recipient", &self.recipient.as_ref().map(|_| "***"))
+            .field("settings", &self.settings.as_bytes())
+            .finish()
+    }
+}
+
 #[derive(PartialEq)]
 pub enum PlainSecret<B: Backend> {
     Rev0(PlainRev0<B>),
     Rev1(PlainRev1<B>),
     Rev2(PlainRev2<B>),
+    Rev3(PlainRev3<B>),
 }
 
 impl<B: Backend> PlainSecret<B> {
@@ -313,52 +434,141 @@ impl<B: Backend> PlainSecret<B> {
         }))
     }
 
+    pub fn from_buffer_rev3<T: Buffer>(buf: &mut T) -> Result<PlainSecret<B>, HeaderError> {
+        let magics = Magics::get_and_validate(buf)?;
+        let key = buf.get_vec::<1>()?.into();
+        let iv = buf.get_vec::<1>()?.into();
+        let nservices = buf.get_u32()?;
+        let mut services = Vec::with_capacity(nservices as usize);
+
+        for _ in 0..nservices {
+            services.push(ServiceEntry::get_from_buffer(buf)?);
+        }
+
+        let journal_bytes: SecureVec = buf.get_vec::<1>()?.into();
+        let journal = if !journal_bytes.is_empty() {
+            Some(Binary::from_bytes(&journal_bytes).ok_or(HeaderError::InvalidJournalId)?)
+        } else {
+            None
+        };
+
+        let integrity_bytes: SecureVec = buf.get_vec::<1>()?.into();
+        let integrity = if !integrity_bytes.is_empty() {
+            let id = Binary::from_bytes(&integrity_bytes).ok_or(HeaderError::InvalidIntegrityId)?;
+            let hash = buf.get_vec::<1>()?;
+
+            Some(Root { id, hash })
+        } else {
+            None
+        };
+
+        let recovery_bytes: SecureVec = buf.get_vec::<1>()?.into();
+        let recovery = if !recovery_bytes.is_empty() {
+            Some(recovery_bytes)
+        } else {
+            None
+        };
+
+        let recipient_bytes: SecureVec = buf.get_vec::<1>()?.into();
+        let recipient = if !recipient_bytes.is_empty() {
+            Some(recipient_bytes)
+        } else {
+            None
+        };
+
+        let settings_bytes: SecureVec = buf.get_vec::<2>()?.into();
+        let settings = Binary::from_bytes(&settings_bytes).ok_or(HeaderError::InvalidSettings)?;
+
+        Ok(PlainSecret::Rev3(PlainRev3 {
+            magics,
+            key,
+            iv,
+            services,
+            journal,
+            integrity,
+            recovery,
+            recipient,
+            settings,
+        }))
+    }
+
     pub fn create_latest(
         key: SecureVec,
         iv: SecureVec,
         settings: B::Settings,
-    ) -> Result<(u32, PlainSecret<B>), ErrorStack> {
-        let rev = Self::Rev2(PlainRev2 {
+    ) -> Result<(u32, PlainSecret<B>), CryptoError> {
+        let rev = Self::Rev3(PlainRev3 {
             magics: Magics::generate()?,
             key,
             iv,
-            sid: None,
-            top_id: None,
+            services: vec![],
+            journal: None,
+            integrity: None,
+            recovery: None,
+            recipient: None,
             settings,
         });
 
-        Ok((2, rev))
+        Ok((3, rev))
     }
 
     pub fn convert_to_latest(&mut self, sid: u32) -> bool {
+        let entry = |top_id: &Option<B::Id>| ServiceEntry {
+            sid,
+            top_id: top_id.clone(),
+        };
+
         match self {
             PlainSecret::Rev0(rev0) => {
                 assert_eq!(rev0.sid, Some(sid));
 
-                *self = Self::Rev2(PlainRev2 {
+                *self = Self::Rev3(PlainRev3 {
                     magics: rev0.magics.clone(),
                     key: rev0.key.clone(),
                     iv: rev0.iv.clone(),
-                    sid: rev0.sid,
-                    top_id: rev0.top_id.clone(),
+                    services: vec![entry(&rev0.top_id)],
+                    journal: None,
+                    integrity: None,
+                    recovery: None,
+                    recipient: None,
                     settings: rev0.settings.clone(),
                 });
 
                 true
             }
             PlainSecret::Rev1(rev1) => {
-                *self = Self::Rev2(PlainRev2 {
+                *self = Self::Rev3(PlainRev3 {
                     magics: rev1.magics.clone(),
                     key: rev1.key.clone(),
                     iv: rev1.iv.clone(),
-                    sid: Some(sid),
-                    top_id: rev1.top_id.clone(),
+                    services: vec![entry(&rev1.top_id)],
+                    journal: None,
+                    integrity: None,
+                    recovery: None,
+                    recipient: None,
                     settings: rev1.settings.clone(),
                 });
 
                 true
             }
-            PlainSecret::Rev2(_) => false,
+            PlainSecret::Rev2(rev2) => {
+                assert_eq!(rev2.sid, Some(sid));
+
+                *self = Self::Rev3(PlainRev3 {
+                    magics: rev2.magics.clone(),
+                    key: rev2.key.clone(),
+                    iv: rev2.iv.clone(),
+                    services: vec![entry(&rev2.top_id)],
+                    journal: None,
+                    integrity: None,
+                    recovery: None,
+                    recipient: None,
+                    settings: rev2.settings.clone(),
+                });
+
+                true
+            }
+            PlainSecret::Rev3(_) => false,
         }
     }
 }
@@ -402,6 +612,41 @@ impl<B: Backend> ToBuffer for PlainSecret<B> {
 
                 buf.put_vec::<2>(&rev2.settings.as_bytes())?;
             }
+            PlainSecret::Rev3(rev3) => {
+                rev3.magics.put(buf)?;
+                buf.put_vec::<1>(&rev3.key)?;
+                buf.put_vec::<1>(&rev3.iv)?;
+                buf.put_u32(rev3.services.len() as u32)?;
+
+                for entry in rev3.services.iter() {
+                    entry.put(buf)?;
+                }
+
+                match rev3.journal.as_ref() {
+                    Some(id) => buf.put_vec::<1>(&id.as_bytes())?,
+                    None => buf.put_vec::<1>(&[])?,
+                }
+
+                match rev3.integrity.as_ref() {
+                    Some(root) => {
+                        buf.put_vec::<1>(&root.id.as_bytes())?;
+                        buf.put_vec::<1>(&root.hash)?;
+                    }
+                    None => buf.put_vec::<1>(&[])?,
+                }
+
+                match rev3.recovery.as_ref() {
+                    Some(key) => buf.put_vec::<1>(key)?,
+                    None => buf.put_vec::<1>(&[])?,
+                }
+
+                match rev3.recipient.as_ref() {
+                    Some(key) => buf.put_vec::<1>(key)?,
+                    None => buf.put_vec::<1>(&[])?,
+                }
+
+                buf.put_vec::<2>(&rev3.settings.as_bytes())?;
+            }
         }
 
         Ok(())
@@ -414,6 +659,7 @@ impl<B: Backend> fmt::Debug for PlainSecret<B> {
             Self::Rev0(rev0) => fmt.debug_tuple("Rev0").field(rev0).finish(),
             Self::Rev1(rev1) => fmt.debug_tuple("Rev1").field(rev1).finish(),
             Self::Rev2(rev2) => fmt.debug_tuple("Rev2").field(rev2).finish(),
+            Self::Rev3(rev3) => fmt.debug_tuple("Rev3").field(rev3).finish(),
         }
     }
 }
@@ -27,7 +27,9 @@ mod to_buffer;
 
 use nuts_memory::{MemoryBackend, Settings};
 
-use crate::header::plain_secret::{Magics, PlainRev0, PlainRev1, PlainRev2, PlainSecret};
+use crate::header::plain_secret::{
+    Magics, PlainRev0, PlainRev1, PlainRev2, PlainRev3, PlainSecret, ServiceEntry,
+};
 use crate::migrate::Migration;
 
 const REV0: [u8; 49] = [
@@ -87,6 +89,104 @@ const REV2_NONE: [u8; 22] = [
     0, 0, // settings
 ];
 
+const REV3_NONE: [u8; 25] = [
+    0x00, 0x00, 0x12, 0x67, // magic1
+    0x00, 0x00, 0x12, 0x67, // magic2
+    2, 1, 2, // key
+    3, 3, 4, 5, // iv
+    0, 0, 0, 0, // number of services
+    0, // journal
+    0, // integrity
+    0, // recovery
+    0, // recipient
+    0, 0, // settings
+];
+
+const REV3_SERVICES: [u8; 39] = [
+    0x00, 0x00, 0x12, 0x67, // magic1
+    0x00, 0x00, 0x12, 0x67, // magic2
+    2, 1, 2, // key
+    3, 3, 4, 5, // iv
+    0, 0, 0, 2, // number of services
+    0, 0, 0x12, 0x67, // service 1: sid
+    4, 0, 0, 2, 154, // service 1: top-id
+    0, 0, 0x02, 0x9a, // service 2: sid
+    0,    // service 2: top-id
+    0,    // journal
+    0,    // integrity
+    0,    // recovery
+    0,    // recipient
+    0, 0, // settings
+];
+
+const REV3_INVAL_SID: [u8; 30] = [
+    0x00, 0x00, 0x12, 0x67, // magic1
+    0x00, 0x00, 0x12, 0x67, // magic2
+    2, 1, 2, // key
+    3, 3, 4, 5, // iv
+    0, 0, 0, 1, // number of services
+    0, 0, 0, 0, // service 1: sid
+    0, // service 1: top-id
+    0, // journal
+    0, // integrity
+    0, // recovery
+    0, // recipient
+    0, 0, // settings
+];
+
+const REV3_JOURNAL: [u8; 29] = [
+    0x00, 0x00, 0x12, 0x67, // magic1
+    0x00, 0x00, 0x12, 0x67, // magic2
+    2, 1, 2, // key
+    3, 3, 4, 5, // iv
+    0, 0, 0, 0, // number of services
+    4, 0, 0, 0x12, 0x67, // journal
+    0,    // integrity
+    0,    // recovery
+    0,    // recipient
+    0, 0, // settings
+];
+
+const REV3_INTEGRITY: [u8; 33] = [
+    0x00, 0x00, 0x12, 0x67, // magic1
+    0x00, 0x00, 0x12, 0x67, // magic2
+    2, 1, 2, // key
+    3, 3, 4, 5, // iv
+    0, 0, 0, 0, // number of services
+    0, // journal
+    4, 0, 0, 0x12, 0x67, // integrity: id
+    3, 1, 2, 3, // integrity: hash
+    0, // recovery
+    0, // recipient
+    0, 0, // settings
+];
+
+const REV3_RECOVERY: [u8; 28] = [
+    0x00, 0x00, 0x12, 0x67, // magic1
+    0x00, 0x00, 0x12, 0x67, // magic2
+    2, 1, 2, // key
+    3, 3, 4, 5, // iv
+    0, 0, 0, 0, // number of services
+    0, // journal
+    0, // integrity
+    3, 6, 7, 8, // recovery
+    0, // recipient
+    0, 0, // settings
+];
+
+const REV3_RECIPIENT: [u8; 28] = [
+    0x00, 0x00, 0x12, 0x67, // magic1
+    0x00, 0x00, 0x12, 0x67, // magic2
+    2, 1, 2, // key
+    3, 3, 4, 5, // iv
+    0, 0, 0, 0, // number of services
+    0, // journal
+    0, // integrity
+    0, // recovery
+    3, 6, 7, 8, // recipient
+    0, 0, // settings
+];
+
 fn rev0() -> PlainRev0<MemoryBackend> {
     PlainRev0 {
         magics: Magics([4711, 4711]),
@@ -127,6 +227,26 @@ fn rev2(sid: Option<u32>, top_id: Option<&str>) -> PlainRev2<MemoryBackend> {
     }
 }
 
+fn rev3(services: &[(u32, Option<&str>)]) -> PlainRev3<MemoryBackend> {
+    PlainRev3 {
+        magics: Magics([4711, 4711]),
+        key: vec![1, 2].into(),
+        iv: vec![3, 4, 5].into(),
+        services: services
+            .iter()
+            .map(|(sid, top_id)| ServiceEntry {
+                sid: *sid,
+                top_id: top_id.map(|id| id.parse().unwrap()),
+            })
+            .collect(),
+        journal: None,
+        integrity: None,
+        recovery: None,
+        recipient: None,
+        settings: Settings,
+    }
+}
+
 struct SampleMigration;
 
 impl Migration for SampleMigration {
@@ -150,15 +270,18 @@ fn create_latest() {
         PlainSecret::<MemoryBackend>::create_latest(vec![1].into(), vec![2, 3].into(), Settings)
             .unwrap();
 
-    let expected = PlainRev2::<MemoryBackend> {
+    let expected = PlainRev3::<MemoryBackend> {
         magics: Magics([0x91C0B2CF; 2]),
         key: vec![1].into(),
         iv: vec![2, 3].into(),
-        sid: None,
-        top_id: None,
+        services: vec![],
+        journal: None,
+        integrity: None,
+        recovery: None,
+        recipient: None,
         settings: Settings,
     };
 
-    assert_eq!(revision, 2);
-    assert!(matches!(plain_secret, PlainSecret::Rev2(data) if data == expected));
+    assert_eq!(revision, 3);
+    assert!(matches!(plain_secret, PlainSecret::Rev3(data) if data == expected));
 }
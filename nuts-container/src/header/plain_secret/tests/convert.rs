@@ -22,8 +22,8 @@
 
 use nuts_memory::Settings;
 
-use crate::header::plain_secret::tests::{rev0, rev1, rev2};
-use crate::header::plain_secret::{PlainRev0, PlainRev1, PlainSecret};
+use crate::header::plain_secret::tests::{rev0, rev1, rev2, rev3};
+use crate::header::plain_secret::{PlainRev0, PlainRev1, PlainSecret, ServiceEntry};
 
 #[test]
 fn rev0_no_top_id() {
@@ -34,13 +34,13 @@ fn rev0_no_top_id() {
 
     assert!(plain_secret.convert_to_latest(666));
 
-    assert!(matches!(plain_secret, PlainSecret::Rev2(rev2)
-        if rev2.magics == 4711.into() &&
-           *rev2.key == [1, 2] &&
-           *rev2.iv == [3, 4, 5] &&
-          rev2.sid == Some(666) &&
-          rev2.top_id.is_none() &&
-          rev2.settings == Settings));
+    assert!(matches!(plain_secret, PlainSecret::Rev3(rev3)
+        if rev3.magics == 4711.into() &&
+           *rev3.key == [1, 2] &&
+           *rev3.iv == [3, 4, 5] &&
+           rev3.services == [ServiceEntry { sid: 666, top_id: None }] &&
+           rev3.journal.is_none() &&
+           rev3.settings == Settings));
 }
 
 #[test]
@@ -53,13 +53,13 @@ fn rev0_top_id() {
 
     assert!(plain_secret.convert_to_latest(666));
 
-    assert!(matches!(plain_secret, PlainSecret::Rev2(rev2)
-        if rev2.magics == 4711.into() &&
-           *rev2.key == [1, 2] &&
-           *rev2.iv == [3, 4, 5] &&
-          rev2.sid == Some(666) &&
-          rev2.top_id.unwrap().to_string() == "4711" &&
-          rev2.settings == Settings));
+    assert!(matches!(plain_secret, PlainSecret::Rev3(rev3)
+        if rev3.magics == 4711.into() &&
+           *rev3.key == [1, 2] &&
+           *rev3.iv == [3, 4, 5] &&
+           rev3.services == [ServiceEntry { sid: 666, top_id: Some("4711".parse().unwrap()) }] &&
+           rev3.journal.is_none() &&
+           rev3.settings == Settings));
 }
 
 #[test]
@@ -71,13 +71,13 @@ fn rev1_no_top_id() {
 
     assert!(plain_secret.convert_to_latest(666));
 
-    assert!(matches!(plain_secret, PlainSecret::Rev2(rev2)
-        if rev2.magics == 4711.into() &&
-           *rev2.key == [1, 2] &&
-           *rev2.iv == [3, 4, 5] &&
-          rev2.sid == Some(666) &&
-          rev2.top_id.is_none() &&
-          rev2.settings == Settings));
+    assert!(matches!(plain_secret, PlainSecret::Rev3(rev3)
+        if rev3.magics == 4711.into() &&
+           *rev3.key == [1, 2] &&
+           *rev3.iv == [3, 4, 5] &&
+           rev3.services == [ServiceEntry { sid: 666, top_id: None }] &&
+           rev3.journal.is_none() &&
+           rev3.settings == Settings));
 }
 
 #[test]
@@ -86,19 +86,36 @@ fn rev1_top_id() {
 
     assert!(plain_secret.convert_to_latest(666));
 
-    assert!(matches!(plain_secret, PlainSecret::Rev2(rev2)
-        if rev2.magics == 4711.into() &&
-           *rev2.key == [1, 2] &&
-           *rev2.iv == [3, 4, 5] &&
-          rev2.sid == Some(666) &&
-          rev2.top_id.unwrap().to_string() == "666" &&
-          rev2.settings == Settings));
+    assert!(matches!(plain_secret, PlainSecret::Rev3(rev3)
+        if rev3.magics == 4711.into() &&
+           *rev3.key == [1, 2] &&
+           *rev3.iv == [3, 4, 5] &&
+           rev3.services == [ServiceEntry { sid: 666, top_id: Some("666".parse().unwrap()) }] &&
+           rev3.journal.is_none() &&
+           rev3.settings == Settings));
 }
 
 #[test]
-fn rev2_not_modified() {
-    let mut plain_secret = PlainSecret::Rev2(rev2(None, None));
+fn rev2_no_top_id() {
+    let mut plain_secret = PlainSecret::Rev2(rev2(Some(666), None));
+
+    assert!(plain_secret.convert_to_latest(666));
+    assert!(matches!(plain_secret, PlainSecret::Rev3(data) if data == rev3(&[(666, None)])));
+}
+
+#[test]
+fn rev2_top_id() {
+    let mut plain_secret = PlainSecret::Rev2(rev2(Some(666), Some("4711")));
+
+    assert!(plain_secret.convert_to_latest(666));
+    assert!(matches!(plain_secret, PlainSecret::Rev3(data)
+        if data == rev3(&[(666, Some("4711"))])));
+}
+
+#[test]
+fn rev3_not_modified() {
+    let mut plain_secret = PlainSecret::Rev3(rev3(&[(666, None)]));
 
     assert!(!plain_secret.convert_to_latest(666));
-    assert!(matches!(plain_secret, PlainSecret::Rev2(data) if data == rev2(None, None)));
+    assert!(matches!(plain_secret, PlainSecret::Rev3(data) if data == rev3(&[(666, None)])));
 }
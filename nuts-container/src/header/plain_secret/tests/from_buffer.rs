@@ -22,12 +22,14 @@
 
 use nuts_memory::MemoryBackend;
 
-use crate::header::plain_secret::tests::{rev0, rev1, rev1_no_top_id, rev2};
+use crate::header::plain_secret::tests::{rev0, rev1, rev1_no_top_id, rev2, rev3};
 use crate::header::plain_secret::tests::{
-    REV0, REV1, REV1_NO_TOP_ID, REV2_NONE, REV2_SID, REV2_TOP_ID,
+    REV0, REV1, REV1_NO_TOP_ID, REV2_NONE, REV2_SID, REV2_TOP_ID, REV3_INTEGRITY, REV3_INVAL_SID,
+    REV3_JOURNAL, REV3_NONE, REV3_RECIPIENT, REV3_RECOVERY, REV3_SERVICES,
 };
-use crate::header::plain_secret::PlainSecret;
+use crate::header::plain_secret::{PlainRev3, PlainSecret};
 use crate::header::HeaderError;
+use crate::integrity::Root;
 
 #[test]
 fn rev0_ok() {
@@ -102,3 +104,75 @@ fn rev2_inval() {
         Err(err) => assert!(matches!(err, HeaderError::WrongPassword)),
     }
 }
+
+#[test]
+fn rev3_none() {
+    let out = PlainSecret::<MemoryBackend>::from_buffer_rev3(&mut &REV3_NONE[..]).unwrap();
+
+    assert!(matches!(out, PlainSecret::Rev3(data) if data == rev3(&[])));
+}
+
+#[test]
+fn rev3_services() {
+    let out = PlainSecret::<MemoryBackend>::from_buffer_rev3(&mut &REV3_SERVICES[..]).unwrap();
+
+    assert!(matches!(out, PlainSecret::Rev3(data)
+        if data == rev3(&[(4711, Some("666")), (666, None)])));
+}
+
+#[test]
+fn rev3_inval_sid() {
+    match PlainSecret::<MemoryBackend>::from_buffer_rev3(&mut &REV3_INVAL_SID[..]) {
+        Ok(_) => panic!("unexpected result"),
+        Err(err) => assert!(matches!(err, HeaderError::InvalidSid)),
+    }
+}
+
+#[test]
+fn rev3_journal() {
+    let out = PlainSecret::<MemoryBackend>::from_buffer_rev3(&mut &REV3_JOURNAL[..]).unwrap();
+
+    assert!(matches!(out, PlainSecret::Rev3(data)
+        if data == PlainRev3 { journal: Some("4711".parse().unwrap()), ..rev3(&[]) }));
+}
+
+#[test]
+fn rev3_integrity() {
+    let out = PlainSecret::<MemoryBackend>::from_buffer_rev3(&mut &REV3_INTEGRITY[..]).unwrap();
+    let integrity = Some(Root {
+        id: "4711".parse().unwrap(),
+        hash: vec![1, 2, 3],
+    });
+
+    assert!(matches!(out, PlainSecret::Rev3(data)
+        if data == PlainRev3 { integrity, ..rev3(&[]) }));
+}
+
+#[test]
+fn rev3_inval() {
+    let mut vec = REV3_NONE.to_vec();
+    vec[0] += 1;
+
+    match PlainSecret::<MemoryBackend>::from_buffer_rev3(&mut vec.as_slice()) {
+        Ok(_) => panic!("unexpected result"),
+        Err(err) => assert!(matches!(err, HeaderError::WrongPassword)),
+    }
+}
+
+#[test]
+fn rev3_recovery() {
+    let out = PlainSecret::<MemoryBackend>::from_buffer_rev3(&mut &REV3_RECOVERY[..]).unwrap();
+    let recovery = Some(vec![6, 7, 8].into());
+
+    assert!(matches!(out, PlainSecret::Rev3(data)
+        if data == PlainRev3 { recovery, ..rev3(&[]) }));
+}
+
+#[test]
+fn rev3_recipient() {
+    let out = PlainSecret::<MemoryBackend>::from_buffer_rev3(&mut &REV3_RECIPIENT[..]).unwrap();
+    let recipient = Some(vec![6, 7, 8].into());
+
+    assert!(matches!(out, PlainSecret::Rev3(data)
+        if data == PlainRev3 { recipient, ..rev3(&[]) }));
+}
@@ -21,11 +21,13 @@
 // IN THE SOFTWARE.
 
 use crate::buffer::ToBuffer;
-use crate::header::plain_secret::tests::{rev0, rev1, rev1_no_top_id, rev2};
+use crate::header::plain_secret::tests::{rev0, rev1, rev1_no_top_id, rev2, rev3};
 use crate::header::plain_secret::tests::{
-    REV0, REV1, REV1_NO_TOP_ID, REV2_NONE, REV2_SID, REV2_TOP_ID,
+    REV0, REV1, REV1_NO_TOP_ID, REV2_NONE, REV2_SID, REV2_TOP_ID, REV3_INTEGRITY, REV3_JOURNAL,
+    REV3_NONE, REV3_RECIPIENT, REV3_RECOVERY, REV3_SERVICES,
 };
-use crate::header::plain_secret::PlainSecret;
+use crate::header::plain_secret::{PlainRev3, PlainSecret};
+use crate::integrity::Root;
 
 #[test]
 fn rev0_ok() {
@@ -82,3 +84,76 @@ fn rev2_none() {
         .unwrap();
     assert_eq!(buf, REV2_NONE);
 }
+
+#[test]
+fn rev3_none() {
+    let mut buf = vec![];
+
+    PlainSecret::Rev3(rev3(&[])).to_buffer(&mut buf).unwrap();
+    assert_eq!(buf, REV3_NONE);
+}
+
+#[test]
+fn rev3_services() {
+    let mut buf = vec![];
+
+    PlainSecret::Rev3(rev3(&[(4711, Some("666")), (666, None)]))
+        .to_buffer(&mut buf)
+        .unwrap();
+    assert_eq!(buf, REV3_SERVICES);
+}
+
+#[test]
+fn rev3_journal() {
+    let mut buf = vec![];
+
+    PlainSecret::Rev3(PlainRev3 {
+        journal: Some("4711".parse().unwrap()),
+        ..rev3(&[])
+    })
+    .to_buffer(&mut buf)
+    .unwrap();
+    assert_eq!(buf, REV3_JOURNAL);
+}
+
+#[test]
+fn rev3_integrity() {
+    let mut buf = vec![];
+
+    PlainSecret::Rev3(PlainRev3 {
+        integrity: Some(Root {
+            id: "4711".parse().unwrap(),
+            hash: vec![1, 2, 3],
+        }),
+        ..rev3(&[])
+    })
+    .to_buffer(&mut buf)
+    .unwrap();
+    assert_eq!(buf, REV3_INTEGRITY);
+}
+
+#[test]
+fn rev3_recovery() {
+    let mut buf = vec![];
+
+    PlainSecret::Rev3(PlainRev3 {
+        recovery: Some(vec![6, 7, 8].into()),
+        ..rev3(&[])
+    })
+    .to_buffer(&mut buf)
+    .unwrap();
+    assert_eq!(buf, REV3_RECOVERY);
+}
+
+#[test]
+fn rev3_recipient() {
+    let mut buf = vec![];
+
+    PlainSecret::Rev3(PlainRev3 {
+        recipient: Some(vec![6, 7, 8].into()),
+        ..rev3(&[])
+    })
+    .to_buffer(&mut buf)
+    .unwrap();
+    assert_eq!(buf, REV3_RECIPIENT);
+}
@@ -72,11 +72,101 @@ impl Data {
     }
 }
 
+/// The recovery slot of the header.
+///
+/// The slot contains the wrapping key of the secret, encrypted with a key
+/// derived from the recovery key.
+#[derive(Debug, PartialEq)]
+pub struct RecoverySlot {
+    pub kdf: Kdf,
+    pub iv: Vec<u8>,
+    pub secret: Vec<u8>,
+}
+
+impl RecoverySlot {
+    fn get_from_buffer<T: Buffer>(buf: &mut T) -> Result<Option<RecoverySlot>, HeaderError> {
+        if buf.get_u8()? == 0 {
+            return Ok(None);
+        }
+
+        let kdf = Kdf::get_from_buffer(buf)?;
+        let iv = buf.get_vec::<1>()?;
+        let secret = buf.get_vec::<1>()?;
+
+        Ok(Some(RecoverySlot { kdf, iv, secret }))
+    }
+
+    fn put_into_buffer<T: BufferMut>(
+        slot: Option<&RecoverySlot>,
+        buf: &mut T,
+    ) -> Result<(), HeaderError> {
+        match slot {
+            Some(slot) => {
+                buf.put_u8(1)?;
+                Kdf::put_into_buffer(&slot.kdf, buf)?;
+                buf.put_vec::<1>(&slot.iv)?;
+                buf.put_vec::<1>(&slot.secret)?;
+            }
+            None => buf.put_u8(0)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// The recipient slot of the header.
+///
+/// The slot contains the wrapping key of the secret, encrypted with a key
+/// derived from an X25519 key agreement between the `ephemeral` key and the
+/// public key of the recipient.
+#[derive(Debug, PartialEq)]
+pub struct RecipientSlot {
+    pub ephemeral: Vec<u8>,
+    pub iv: Vec<u8>,
+    pub secret: Vec<u8>,
+}
+
+impl RecipientSlot {
+    fn get_from_buffer<T: Buffer>(buf: &mut T) -> Result<Option<RecipientSlot>, HeaderError> {
+        if buf.get_u8()? == 0 {
+            return Ok(None);
+        }
+
+        let ephemeral = buf.get_vec::<1>()?;
+        let iv = buf.get_vec::<1>()?;
+        let secret = buf.get_vec::<1>()?;
+
+        Ok(Some(RecipientSlot {
+            ephemeral,
+            iv,
+            secret,
+        }))
+    }
+
+    fn put_into_buffer<T: BufferMut>(
+        slot: Option<&RecipientSlot>,
+        buf: &mut T,
+    ) -> Result<(), HeaderError> {
+        match slot {
+            Some(slot) => {
+                buf.put_u8(1)?;
+                buf.put_vec::<1>(&slot.ephemeral)?;
+                buf.put_vec::<1>(&slot.iv)?;
+                buf.put_vec::<1>(&slot.secret)?;
+            }
+            None => buf.put_u8(0)?,
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub enum Revision {
     Rev0(Data),
     Rev1(Data),
     Rev2(Data),
+    Rev3(Data, Option<RecoverySlot>, Option<RecipientSlot>),
 }
 
 impl Revision {
@@ -92,6 +182,24 @@ impl Revision {
         Revision::Rev2(Data::new(cipher, iv, kdf, secret))
     }
 
+    pub fn new_rev3(
+        cipher: Cipher,
+        iv: Vec<u8>,
+        kdf: Kdf,
+        secret: Vec<u8>,
+        recovery: Option<RecoverySlot>,
+        recipient: Option<RecipientSlot>,
+    ) -> Revision {
+        Revision::Rev3(Data::new(cipher, iv, kdf, secret), recovery, recipient)
+    }
+
+    pub fn data(&self) -> &Data {
+        match self {
+            Revision::Rev0(data) | Revision::Rev1(data) | Revision::Rev2(data) => data,
+            Revision::Rev3(data, _, _) => data,
+        }
+    }
+
     pub fn get_from_buffer<T: Buffer>(buf: &mut T) -> Result<Revision, HeaderError> {
         let magic = buf.get_array()?;
 
@@ -105,6 +213,13 @@ impl Revision {
             0 => Data::get_from_buffer(buf).map(Revision::Rev0),
             1 => Data::get_from_buffer(buf).map(Revision::Rev1),
             2 => Data::get_from_buffer(buf).map(Revision::Rev2),
+            3 => {
+                let data = Data::get_from_buffer(buf)?;
+                let recovery = RecoverySlot::get_from_buffer(buf)?;
+                let recipient = RecipientSlot::get_from_buffer(buf)?;
+
+                Ok(Revision::Rev3(data, recovery, recipient))
+            }
             _ => Err(HeaderError::UnknownRevision(b)),
         }
     }
@@ -125,6 +240,12 @@ impl Revision {
                 buf.put_u32(2)?;
                 data.put_into_buffer(buf)
             }
+            Revision::Rev3(data, recovery, recipient) => {
+                buf.put_u32(3)?;
+                data.put_into_buffer(buf)?;
+                RecoverySlot::put_into_buffer(recovery.as_ref(), buf)?;
+                RecipientSlot::put_into_buffer(recipient.as_ref(), buf)
+            }
         }
     }
 }
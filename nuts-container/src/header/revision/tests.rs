@@ -21,7 +21,7 @@
 // IN THE SOFTWARE.
 
 use crate::cipher::Cipher;
-use crate::header::revision::{Data, Revision};
+use crate::header::revision::{Data, RecipientSlot, RecoverySlot, Revision};
 use crate::header::HeaderError;
 use crate::kdf::Kdf;
 
@@ -52,6 +52,45 @@ const REV2: [u8; 38] = [
     0x00, 0x00, 0x00, 0x0, 0x00, 0x00, 0x00, 0x03, 1, 2, 3, // secret
 ];
 
+const REV3: [u8; 40] = [
+    b'n', b'u', b't', b's', b'-', b'i', b'o', // magic
+    0x00, 0x00, 0x00, 0x03, // revision
+    0x00, 0x00, 0x00, 0x00, // cipher
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // iv,
+    0x00, 0x00, 0x00, 0x00, // kdf
+    0x00, 0x00, 0x00, 0x0, 0x00, 0x00, 0x00, 0x03, 1, 2, 3,    // secret
+    0x00, // recovery
+    0x00, // recipient
+];
+
+const REV3_RECOVERY: [u8; 50] = [
+    b'n', b'u', b't', b's', b'-', b'i', b'o', // magic
+    0x00, 0x00, 0x00, 0x03, // revision
+    0x00, 0x00, 0x00, 0x00, // cipher
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // iv,
+    0x00, 0x00, 0x00, 0x00, // kdf
+    0x00, 0x00, 0x00, 0x0, 0x00, 0x00, 0x00, 0x03, 1, 2, 3,    // secret
+    0x01, // recovery
+    0x00, 0x00, 0x00, 0x00, // recovery: kdf
+    0x01, 4, // recovery: iv
+    0x03, 5, 6, 7,    // recovery: secret
+    0x00, // recipient
+];
+
+const REV3_RECIPIENT: [u8; 48] = [
+    b'n', b'u', b't', b's', b'-', b'i', b'o', // magic
+    0x00, 0x00, 0x00, 0x03, // revision
+    0x00, 0x00, 0x00, 0x00, // cipher
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // iv,
+    0x00, 0x00, 0x00, 0x00, // kdf
+    0x00, 0x00, 0x00, 0x0, 0x00, 0x00, 0x00, 0x03, 1, 2, 3,    // secret
+    0x00, // recovery
+    0x01, // recipient
+    0x02, 4, 5, // recipient: ephemeral
+    0x01, 6, // recipient: iv
+    0x02, 7, 8, // recipient: secret
+];
+
 #[test]
 fn new_rev0() {
     let revision = Revision::new_rev0(Cipher::None, vec![1], Kdf::None, vec![2, 3]);
@@ -94,15 +133,29 @@ fn new_rev2() {
     assert!(matches!(revision, Revision::Rev2(data) if data == expected));
 }
 
+#[test]
+fn new_rev3() {
+    let revision = Revision::new_rev3(Cipher::None, vec![1], Kdf::None, vec![2, 3], None, None);
+
+    let expected = Data {
+        cipher: Cipher::None,
+        iv: vec![1],
+        kdf: Kdf::None,
+        secret: vec![2, 3],
+    };
+
+    assert!(matches!(revision, Revision::Rev3(data, None, None) if data == expected));
+}
+
 #[test]
 fn de_inval_revision() {
     let mut buf = REV0;
 
-    buf[10] = 3;
+    buf[10] = 4;
 
     let err = Revision::get_from_buffer(&mut &buf[..]).unwrap_err();
 
-    assert!(matches!(err, HeaderError::UnknownRevision(rev) if rev == 3));
+    assert!(matches!(err, HeaderError::UnknownRevision(rev) if rev == 4));
 }
 
 #[test]
@@ -116,6 +169,7 @@ fn de_rev0() {
         }
         Revision::Rev1(_) => panic!("invalid revision"),
         Revision::Rev2(_) => panic!("invalid revision"),
+        Revision::Rev3(..) => panic!("invalid revision"),
     }
 }
 
@@ -155,6 +209,7 @@ fn de_rev1() {
             assert_eq!(rev1.secret, [1, 2, 3]);
         }
         Revision::Rev2(_) => panic!("invalid revision"),
+        Revision::Rev3(..) => panic!("invalid revision"),
     }
 }
 
@@ -194,6 +249,7 @@ fn de_rev2() {
             assert_eq!(rev2.kdf, Kdf::None);
             assert_eq!(rev2.secret, [1, 2, 3]);
         }
+        Revision::Rev3(..) => panic!("invalid revision"),
     }
 }
 
@@ -221,3 +277,135 @@ fn ser_rev2() {
     inner.put_into_buffer(&mut buf).unwrap();
     assert_eq!(buf, REV2);
 }
+
+#[test]
+fn de_rev3() {
+    match Revision::get_from_buffer(&mut &REV3[..]).unwrap() {
+        Revision::Rev0(_) => panic!("invalid revision"),
+        Revision::Rev1(_) => panic!("invalid revision"),
+        Revision::Rev2(_) => panic!("invalid revision"),
+        Revision::Rev3(rev3, recovery, recipient) => {
+            assert_eq!(rev3.cipher, Cipher::None);
+            assert_eq!(rev3.iv, []);
+            assert_eq!(rev3.kdf, Kdf::None);
+            assert_eq!(rev3.secret, [1, 2, 3]);
+            assert!(recovery.is_none());
+            assert!(recipient.is_none());
+        }
+    }
+}
+
+#[test]
+fn de_rev3_recovery() {
+    match Revision::get_from_buffer(&mut &REV3_RECOVERY[..]).unwrap() {
+        Revision::Rev0(_) => panic!("invalid revision"),
+        Revision::Rev1(_) => panic!("invalid revision"),
+        Revision::Rev2(_) => panic!("invalid revision"),
+        Revision::Rev3(rev3, recovery, recipient) => {
+            assert_eq!(rev3.secret, [1, 2, 3]);
+            assert_eq!(
+                recovery.unwrap(),
+                RecoverySlot {
+                    kdf: Kdf::None,
+                    iv: vec![4],
+                    secret: vec![5, 6, 7],
+                }
+            );
+            assert!(recipient.is_none());
+        }
+    }
+}
+
+#[test]
+fn de_rev3_recipient() {
+    match Revision::get_from_buffer(&mut &REV3_RECIPIENT[..]).unwrap() {
+        Revision::Rev0(_) => panic!("invalid revision"),
+        Revision::Rev1(_) => panic!("invalid revision"),
+        Revision::Rev2(_) => panic!("invalid revision"),
+        Revision::Rev3(rev3, recovery, recipient) => {
+            assert_eq!(rev3.secret, [1, 2, 3]);
+            assert!(recovery.is_none());
+            assert_eq!(
+                recipient.unwrap(),
+                RecipientSlot {
+                    ephemeral: vec![4, 5],
+                    iv: vec![6],
+                    secret: vec![7, 8],
+                }
+            );
+        }
+    }
+}
+
+#[test]
+fn de_rev3_inval_magic() {
+    let mut buf = REV3;
+
+    buf[0] = b'x';
+
+    let err = Revision::get_from_buffer(&mut &buf[..]).unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidHeader));
+}
+
+#[test]
+fn ser_rev3() {
+    let mut buf = vec![];
+    let inner = Revision::Rev3(
+        Data {
+            cipher: Cipher::None,
+            iv: vec![],
+            kdf: Kdf::None,
+            secret: vec![1, 2, 3],
+        },
+        None,
+        None,
+    );
+
+    inner.put_into_buffer(&mut buf).unwrap();
+    assert_eq!(buf, REV3);
+}
+
+#[test]
+fn ser_rev3_recovery() {
+    let mut buf = vec![];
+    let inner = Revision::Rev3(
+        Data {
+            cipher: Cipher::None,
+            iv: vec![],
+            kdf: Kdf::None,
+            secret: vec![1, 2, 3],
+        },
+        Some(RecoverySlot {
+            kdf: Kdf::None,
+            iv: vec![4],
+            secret: vec![5, 6, 7],
+        }),
+        None,
+    );
+
+    inner.put_into_buffer(&mut buf).unwrap();
+    assert_eq!(buf, REV3_RECOVERY);
+}
+
+#[test]
+fn ser_rev3_recipient() {
+    let mut buf = vec![];
+    let inner = Revision::Rev3(
+        Data {
+            cipher: Cipher::None,
+            iv: vec![],
+            kdf: Kdf::None,
+            secret: vec![1, 2, 3],
+        },
+        None,
+        Some(RecipientSlot {
+            ephemeral: vec![4, 5],
+            iv: vec![6],
+            secret: vec![7, 8],
+        }),
+    );
+
+    inner.put_into_buffer(&mut buf).unwrap();
+    assert_eq!(buf, REV3_RECIPIENT);
+}
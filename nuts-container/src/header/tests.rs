@@ -25,12 +25,17 @@ use nuts_memory::{MemoryBackend, Settings};
 
 use crate::cipher::Cipher;
 use crate::digest::Digest;
-use crate::header::plain_secret::{PlainRev0, PlainRev1, PlainRev2, PlainSecret};
+use crate::header::plain_secret::{
+    PlainRev0, PlainRev1, PlainRev2, PlainRev3, PlainSecret, ServiceEntry,
+};
 use crate::header::{Header, HeaderError};
+use crate::integrity::Root;
 use crate::kdf::Kdf;
 use crate::migrate::Migrator;
 use crate::options::CreateOptionsBuilder;
 use crate::password::PasswordStore;
+use crate::recipient::PrivateKey;
+use crate::wrapping_key::WrappingKey;
 
 const REV0: [u8; 79] = [
     b'n', b'u', b't', b's', b'-', b'i', b'o', // magic
@@ -75,6 +80,30 @@ const REV2: [u8; 56] = [
     0, 0, // secret: settings
 ];
 
+const REV3: [u8; 71] = [
+    b'n', b'u', b't', b's', b'-', b'i', b'o', // magic
+    0, 0, 0, 3, // revision
+    0, 0, 0, 0, // cipher
+    0, 0, 0, 0, 0, 0, 0, 0, // iv
+    0, 0, 0, 0, // kdf
+    0, 0, 0, 0, 0, 0, 0, 34, // secret length
+    0x91, 0xc0, 0xb2, 0xcf, 0x91, 0xc0, 0xb2, 0xcf, // secret: magics
+    0,    // secret: key
+    0,    // secret: iv
+    0, 0, 0, 2, // secret: number of services
+    0x00, 0x00, 0x02, 0x9a, // secret: service 1: sid
+    4, 0x00, 0x00, 0x12, 0x67, // secret: service 1: top_id
+    0x00, 0x00, 0x12, 0x67, // secret: service 2: sid
+    0,    // secret: service 2: top_id
+    0,    // secret: journal
+    0,    // secret: integrity
+    0,    // secret: recovery
+    0,    // secret: recipient
+    0, 0, // secret: settings
+    0, // recovery
+    0, // recipient
+];
+
 fn rev0() -> PlainRev0<MemoryBackend> {
     PlainRev0 {
         magics: 0x91c0b2cf.into(),
@@ -108,12 +137,37 @@ fn rev2() -> PlainRev2<MemoryBackend> {
     }
 }
 
+fn rev3() -> PlainRev3<MemoryBackend> {
+    PlainRev3 {
+        magics: 0x91c0b2cf.into(),
+        key: vec![].into(),
+        iv: vec![].into(),
+        services: vec![],
+        journal: None,
+        integrity: None,
+        recovery: None,
+        recipient: None,
+        settings: Settings,
+    }
+}
+
+fn service(sid: u32, top_id: Option<&str>) -> ServiceEntry<MemoryBackend> {
+    ServiceEntry {
+        sid,
+        top_id: top_id.map(|id| id.parse().unwrap()),
+    }
+}
+
 fn header(data: PlainSecret<MemoryBackend>) -> Header<'static, MemoryBackend> {
     Header::<MemoryBackend> {
         revision: 1,
         migrator: Migrator::default(),
         cipher: Cipher::None,
         kdf: Kdf::None,
+        recovery: None,
+        recipient: None,
+        wrapping_key: None,
+        reserve_integrity: false,
         data,
     }
 }
@@ -125,10 +179,10 @@ fn create() {
         .unwrap();
     let header = Header::<MemoryBackend>::create(&options, Settings).unwrap();
 
-    assert_eq!(header.revision, 2);
+    assert_eq!(header.revision, 3);
     assert_eq!(header.cipher, Cipher::None);
     assert_eq!(header.kdf, Kdf::None);
-    assert_eq!(header.data, PlainSecret::Rev2(rev2()));
+    assert_eq!(header.data, PlainSecret::Rev3(rev3()));
 }
 
 #[test]
@@ -183,6 +237,25 @@ fn read_rev2() {
     );
 }
 
+#[test]
+fn read_rev3() {
+    let migrator = Migrator::default();
+    let mut store = PasswordStore::new(None);
+
+    let header = Header::<MemoryBackend>::read(&REV3, migrator, &mut store).unwrap();
+
+    assert_eq!(header.revision, 3);
+    assert_eq!(header.cipher, Cipher::None);
+    assert_eq!(header.kdf, Kdf::None);
+    assert_eq!(
+        header.data,
+        PlainSecret::Rev3(PlainRev3 {
+            services: vec![service(666, Some("4711")), service(4711, None)],
+            ..rev3()
+        })
+    );
+}
+
 #[test]
 fn write_rev0() {
     let mut buf = [b'x'; REV0.len()];
@@ -226,6 +299,21 @@ fn write_rev2() {
     assert_eq!(buf, REV2);
 }
 
+#[test]
+fn write_rev3() {
+    let mut buf = [b'x'; REV3.len()];
+    let mut store = PasswordStore::new(None);
+
+    let header = header(PlainSecret::Rev3(PlainRev3 {
+        services: vec![service(666, Some("4711")), service(4711, None)],
+        ..rev3()
+    }));
+
+    header.write(&mut buf, &mut store).unwrap();
+
+    assert_eq!(buf, REV3);
+}
+
 #[test]
 fn latest_revision_or_err_rev0() {
     let header = Header {
@@ -236,7 +324,7 @@ fn latest_revision_or_err_rev0() {
     let err = header.latest_revision_or_err().unwrap_err();
 
     assert!(matches!(err, HeaderError::InvalidRevision(expected, got)
-        if expected == 2 && got == 0))
+        if expected == 3 && got == 0))
 }
 
 #[test]
@@ -249,7 +337,7 @@ fn latest_revision_or_err_rev1() {
     let err = header.latest_revision_or_err().unwrap_err();
 
     assert!(matches!(err, HeaderError::InvalidRevision(expected, got)
-        if expected == 2 && got == 1))
+        if expected == 3 && got == 1))
 }
 
 #[test]
@@ -259,6 +347,19 @@ fn latest_revision_or_err_rev2() {
         ..header(PlainSecret::Rev2(rev2()))
     };
 
+    let err = header.latest_revision_or_err().unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRevision(expected, got)
+        if expected == 3 && got == 2))
+}
+
+#[test]
+fn latest_revision_or_err_rev3() {
+    let header = Header {
+        revision: 3,
+        ..header(PlainSecret::Rev3(rev3()))
+    };
+
     header.latest_revision_or_err().unwrap();
 }
 
@@ -331,6 +432,13 @@ fn settings_rev2() {
     assert_eq!(header.settings().as_bytes(), Settings.as_bytes());
 }
 
+#[test]
+fn settings_rev3() {
+    let header = header(PlainSecret::Rev3(rev3()));
+
+    assert_eq!(header.settings().as_bytes(), Settings.as_bytes());
+}
+
 #[test]
 fn key_rev0() {
     let header = header(PlainSecret::Rev0(PlainRev0 {
@@ -361,6 +469,16 @@ fn key_rev2() {
     assert_eq!(header.key(), [1, 2, 3]);
 }
 
+#[test]
+fn key_rev3() {
+    let header = header(PlainSecret::Rev3(PlainRev3 {
+        key: vec![1, 2, 3].into(),
+        ..rev3()
+    }));
+
+    assert_eq!(header.key(), [1, 2, 3]);
+}
+
 #[test]
 fn iv_rev0() {
     let header = header(PlainSecret::Rev0(PlainRev0 {
@@ -391,11 +509,21 @@ fn iv_rev2() {
     assert_eq!(header.iv(), [1, 2, 3]);
 }
 
+#[test]
+fn iv_rev3() {
+    let header = header(PlainSecret::Rev3(PlainRev3 {
+        iv: vec![1, 2, 3].into(),
+        ..rev3()
+    }));
+
+    assert_eq!(header.iv(), [1, 2, 3]);
+}
+
 #[test]
 fn accept_sid_for_create_rev0_none() {
     let header = header(PlainSecret::Rev0(rev0()));
 
-    header.accept_sid_for_create().unwrap();
+    header.accept_sid_for_create(666).unwrap();
 }
 
 #[test]
@@ -405,7 +533,7 @@ fn accept_sid_for_create_rev0_some() {
         ..rev0()
     }));
 
-    let err = header.accept_sid_for_create().unwrap_err();
+    let err = header.accept_sid_for_create(666).unwrap_err();
 
     assert!(matches!(err,HeaderError::UnexpectedSid { expected, got }
         if expected.is_none() && got == Some(666)));
@@ -415,14 +543,14 @@ fn accept_sid_for_create_rev0_some() {
 fn accept_sid_for_create_rev1_none() {
     let header = header(PlainSecret::Rev1(rev1()));
 
-    header.accept_sid_for_create().unwrap();
+    header.accept_sid_for_create(666).unwrap();
 }
 
 #[test]
 fn accept_sid_for_create_rev2_none() {
     let header = header(PlainSecret::Rev2(rev2()));
 
-    header.accept_sid_for_create().unwrap();
+    header.accept_sid_for_create(666).unwrap();
 }
 
 #[test]
@@ -432,12 +560,41 @@ fn accept_sid_for_create_rev2_some() {
         ..rev2()
     }));
 
-    let err = header.accept_sid_for_create().unwrap_err();
+    let err = header.accept_sid_for_create(666).unwrap_err();
 
     assert!(matches!(err,HeaderError::UnexpectedSid { expected, got }
         if expected.is_none() && got == Some(666)));
 }
 
+#[test]
+fn accept_sid_for_create_rev3_none() {
+    let header = header(PlainSecret::Rev3(rev3()));
+
+    header.accept_sid_for_create(666).unwrap();
+}
+
+#[test]
+fn accept_sid_for_create_rev3_other() {
+    let header = header(PlainSecret::Rev3(PlainRev3 {
+        services: vec![service(4711, None)],
+        ..rev3()
+    }));
+
+    header.accept_sid_for_create(666).unwrap();
+}
+
+#[test]
+fn accept_sid_for_create_rev3_exists() {
+    let header = header(PlainSecret::Rev3(PlainRev3 {
+        services: vec![service(4711, None), service(666, None)],
+        ..rev3()
+    }));
+
+    let err = header.accept_sid_for_create(666).unwrap_err();
+
+    assert!(matches!(err, HeaderError::ServiceExists(sid) if sid == 666));
+}
+
 #[test]
 fn accept_sid_for_open_rev0_none() {
     let header = header(PlainSecret::Rev0(rev0()));
@@ -512,51 +669,155 @@ fn accept_sid_for_open_rev2_some_neq() {
 }
 
 #[test]
-#[should_panic(expected = "storing a sid into a rev0 header is not supported")]
-fn set_sid_rev0() {
-    header(PlainSecret::Rev0(rev0())).set_sid(666).unwrap();
+fn accept_sid_for_open_rev3_none() {
+    let header = header(PlainSecret::Rev3(rev3()));
+
+    let err = header.accept_sid_for_open(666).unwrap_err();
+
+    assert!(matches!(err, HeaderError::NoSuchService(sid) if sid == 666));
 }
 
 #[test]
-#[should_panic(expected = "storing a sid into a rev0 header is not supported")]
-fn set_sid_rev0_inval() {
-    header(PlainSecret::Rev0(rev0())).set_sid(0).unwrap();
+fn accept_sid_for_open_rev3_found() {
+    let header = header(PlainSecret::Rev3(PlainRev3 {
+        services: vec![service(4711, None), service(666, None)],
+        ..rev3()
+    }));
+
+    header.accept_sid_for_open(666).unwrap();
+    header.accept_sid_for_open(4711).unwrap();
 }
 
 #[test]
-#[should_panic(expected = "storing a sid into a rev1 header is not supported")]
-fn set_sid_rev1() {
-    header(PlainSecret::Rev1(rev1())).set_sid(666).unwrap();
+fn accept_sid_for_open_rev3_not_found() {
+    let header = header(PlainSecret::Rev3(PlainRev3 {
+        services: vec![service(4711, None)],
+        ..rev3()
+    }));
+
+    let err = header.accept_sid_for_open(666).unwrap_err();
+
+    assert!(matches!(err, HeaderError::NoSuchService(sid) if sid == 666));
 }
 
 #[test]
-#[should_panic(expected = "storing a sid into a rev1 header is not supported")]
-fn set_sid_rev1_inval() {
-    header(PlainSecret::Rev1(rev1())).set_sid(0).unwrap();
+#[should_panic(expected = "adding a service to a rev0 header is not supported")]
+fn add_service_rev0() {
+    header(PlainSecret::Rev0(rev0()))
+        .add_service(666, None)
+        .unwrap();
 }
 
 #[test]
-fn set_sid_rev2() {
-    let mut header = header(PlainSecret::Rev2(rev2()));
+#[should_panic(expected = "adding a service to a rev1 header is not supported")]
+fn add_service_rev1() {
+    header(PlainSecret::Rev1(rev1()))
+        .add_service(666, None)
+        .unwrap();
+}
 
-    header.set_sid(666).unwrap();
+#[test]
+#[should_panic(expected = "adding a service to a rev2 header is not supported")]
+fn add_service_rev2() {
+    header(PlainSecret::Rev2(rev2()))
+        .add_service(666, None)
+        .unwrap();
+}
+
+#[test]
+fn add_service_rev3() {
+    let mut header = header(PlainSecret::Rev3(rev3()));
 
-    assert!(matches!(header.data, PlainSecret::Rev2(rev2) if rev2.sid == Some(666)));
+    header
+        .add_service(666, Some("4711".parse().unwrap()))
+        .unwrap();
+    header.add_service(4711, None).unwrap();
+
+    assert!(matches!(header.data, PlainSecret::Rev3(rev3)
+        if rev3.services == [service(666, Some("4711")), service(4711, None)]));
 }
 
 #[test]
-fn set_sid_rev2_inval() {
-    let mut header = header(PlainSecret::Rev2(rev2()));
-    let err = header.set_sid(0).unwrap_err();
+fn add_service_rev3_inval() {
+    let mut header = header(PlainSecret::Rev3(rev3()));
+    let err = header.add_service(0, None).unwrap_err();
 
     assert!(matches!(err, HeaderError::InvalidSid));
 }
 
+#[test]
+fn add_service_rev3_exists() {
+    let mut header = header(PlainSecret::Rev3(rev3()));
+
+    header.add_service(666, None).unwrap();
+    let err = header.add_service(666, None).unwrap_err();
+
+    assert!(matches!(err, HeaderError::ServiceExists(sid) if sid == 666));
+}
+
+#[test]
+fn add_service_rev3_too_large() {
+    let mut header = large_header(0);
+    let mut sid = 1;
+
+    let err = loop {
+        match header.add_service(sid, Some("1".parse().unwrap())) {
+            Ok(()) => sid += 1,
+            Err(err) => break err,
+        }
+    };
+
+    assert!(matches!(err, HeaderError::TooLarge(n) if n > 512));
+    assert!(
+        matches!(&header.data, PlainSecret::Rev3(rev3) if rev3.services.len() == sid as usize - 1)
+    );
+    header.check_size().unwrap();
+}
+
+#[test]
+fn services_rev0() {
+    let header = header(PlainSecret::Rev0(PlainRev0 {
+        sid: Some(666),
+        ..rev0()
+    }));
+
+    assert_eq!(header.services(), [666]);
+}
+
+#[test]
+fn services_rev1() {
+    let header = header(PlainSecret::Rev1(rev1()));
+
+    assert!(header.services().is_empty());
+}
+
+#[test]
+fn services_rev2() {
+    let none = header(PlainSecret::Rev2(rev2()));
+    assert!(none.services().is_empty());
+
+    let header = header(PlainSecret::Rev2(PlainRev2 {
+        sid: Some(666),
+        ..rev2()
+    }));
+    assert_eq!(header.services(), [666]);
+}
+
+#[test]
+fn services_rev3() {
+    let header = header(PlainSecret::Rev3(PlainRev3 {
+        services: vec![service(666, None), service(4711, None)],
+        ..rev3()
+    }));
+
+    assert_eq!(header.services(), [666, 4711]);
+}
+
 #[test]
 fn top_id_rev0_none() {
     let header = header(PlainSecret::Rev0(rev0()));
 
-    assert!(header.top_id().is_none());
+    assert!(header.top_id(None).is_none());
 }
 
 #[test]
@@ -566,14 +827,14 @@ fn top_id_rev0_some() {
         ..rev0()
     }));
 
-    assert_eq!(header.top_id().unwrap().to_string(), "4711");
+    assert_eq!(header.top_id(None).unwrap().to_string(), "4711");
 }
 
 #[test]
 fn top_id_rev1_none() {
     let header = header(PlainSecret::Rev1(rev1()));
 
-    assert!(header.top_id().is_none());
+    assert!(header.top_id(None).is_none());
 }
 
 #[test]
@@ -583,14 +844,14 @@ fn top_id_rev1_some() {
         ..rev1()
     }));
 
-    assert_eq!(header.top_id().unwrap().to_string(), "4711");
+    assert_eq!(header.top_id(None).unwrap().to_string(), "4711");
 }
 
 #[test]
 fn top_id_rev2_none() {
     let header = header(PlainSecret::Rev2(rev2()));
 
-    assert!(header.top_id().is_none());
+    assert!(header.top_id(None).is_none());
 }
 
 #[test]
@@ -599,28 +860,663 @@ fn top_id_rev2_some() {
         top_id: Some("4711".parse().unwrap()),
         ..rev2()
     }));
-    let top_id = header.top_id().unwrap();
+    let top_id = header.top_id(None).unwrap();
 
     assert_eq!(top_id.to_string(), "4711");
 }
 
 #[test]
-#[should_panic(expected = "storing a top-id into a rev0 header is not supported")]
-fn set_top_id_rev0() {
-    header(PlainSecret::Rev0(rev0())).set_top_id("4711".parse().unwrap());
+fn top_id_rev3_none() {
+    let header = header(PlainSecret::Rev3(PlainRev3 {
+        services: vec![service(666, None)],
+        ..rev3()
+    }));
+
+    assert!(header.top_id(None).is_none());
+    assert!(header.top_id(Some(666)).is_none());
+    assert!(header.top_id(Some(4711)).is_none());
+}
+
+#[test]
+fn top_id_rev3_some() {
+    let header = header(PlainSecret::Rev3(PlainRev3 {
+        services: vec![service(666, Some("1")), service(4711, Some("2"))],
+        ..rev3()
+    }));
+
+    assert!(header.top_id(None).is_none());
+    assert_eq!(header.top_id(Some(666)).unwrap().to_string(), "1");
+    assert_eq!(header.top_id(Some(4711)).unwrap().to_string(), "2");
+    assert!(header.top_id(Some(1)).is_none());
+}
+
+#[test]
+fn journal_rev0() {
+    let header = header(PlainSecret::Rev0(rev0()));
+
+    assert!(header.journal().is_none());
+}
+
+#[test]
+fn journal_rev1() {
+    let header = header(PlainSecret::Rev1(rev1()));
+
+    assert!(header.journal().is_none());
+}
+
+#[test]
+fn journal_rev2() {
+    let header = header(PlainSecret::Rev2(rev2()));
+
+    assert!(header.journal().is_none());
+}
+
+#[test]
+fn journal_rev3_none() {
+    let header = header(PlainSecret::Rev3(rev3()));
+
+    assert!(header.journal().is_none());
+}
+
+#[test]
+fn journal_rev3_some() {
+    let header = header(PlainSecret::Rev3(PlainRev3 {
+        journal: Some("4711".parse().unwrap()),
+        ..rev3()
+    }));
+
+    assert_eq!(header.journal().unwrap().to_string(), "4711");
+}
+
+#[test]
+fn set_journal_rev0() {
+    let mut header = header(PlainSecret::Rev0(rev0()));
+    let err = header
+        .set_journal(Some("4711".parse().unwrap()))
+        .unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRevision(3, 1)));
 }
 
 #[test]
-#[should_panic(expected = "storing a top-id into a rev1 header is not supported")]
-fn set_top_id_rev1() {
-    header(PlainSecret::Rev1(rev1())).set_top_id("4711".parse().unwrap());
+fn set_journal_rev1() {
+    let mut header = header(PlainSecret::Rev1(rev1()));
+    let err = header
+        .set_journal(Some("4711".parse().unwrap()))
+        .unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRevision(3, 1)));
 }
 
 #[test]
-fn set_top_id_rev2() {
+fn set_journal_rev2() {
     let mut header = header(PlainSecret::Rev2(rev2()));
+    let err = header
+        .set_journal(Some("4711".parse().unwrap()))
+        .unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRevision(3, 1)));
+}
+
+#[test]
+fn set_journal_rev3() {
+    let mut header = header(PlainSecret::Rev3(rev3()));
+
+    assert!(header.set_journal(Some("4711".parse().unwrap())).unwrap());
+    assert_eq!(header.journal().unwrap().to_string(), "4711");
+
+    assert!(!header.set_journal(Some("4711".parse().unwrap())).unwrap());
+    assert_eq!(header.journal().unwrap().to_string(), "4711");
+
+    assert!(header.set_journal(None).unwrap());
+    assert!(header.journal().is_none());
+
+    assert!(!header.set_journal(None).unwrap());
+    assert!(header.journal().is_none());
+}
+
+fn root(id: &str) -> Root<MemoryBackend> {
+    Root {
+        id: id.parse().unwrap(),
+        hash: vec![1, 2, 3],
+    }
+}
+
+#[test]
+fn integrity_rev0() {
+    let header = header(PlainSecret::Rev0(rev0()));
+
+    assert!(header.integrity().is_none());
+}
+
+#[test]
+fn integrity_rev1() {
+    let header = header(PlainSecret::Rev1(rev1()));
+
+    assert!(header.integrity().is_none());
+}
+
+#[test]
+fn integrity_rev2() {
+    let header = header(PlainSecret::Rev2(rev2()));
+
+    assert!(header.integrity().is_none());
+}
+
+#[test]
+fn integrity_rev3_none() {
+    let header = header(PlainSecret::Rev3(rev3()));
+
+    assert!(header.integrity().is_none());
+}
+
+#[test]
+fn integrity_rev3_some() {
+    let header = header(PlainSecret::Rev3(PlainRev3 {
+        integrity: Some(root("4711")),
+        ..rev3()
+    }));
+
+    assert_eq!(*header.integrity().unwrap(), root("4711"));
+}
+
+#[test]
+fn set_integrity_rev0() {
+    let mut header = header(PlainSecret::Rev0(rev0()));
+    let err = header.set_integrity(Some(root("4711"))).unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRevision(3, 1)));
+}
+
+#[test]
+fn set_integrity_rev1() {
+    let mut header = header(PlainSecret::Rev1(rev1()));
+    let err = header.set_integrity(Some(root("4711"))).unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRevision(3, 1)));
+}
+
+#[test]
+fn set_integrity_rev2() {
+    let mut header = header(PlainSecret::Rev2(rev2()));
+    let err = header.set_integrity(Some(root("4711"))).unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRevision(3, 1)));
+}
+
+#[test]
+fn set_integrity_rev3() {
+    let mut header = header(PlainSecret::Rev3(rev3()));
+
+    assert!(header.set_integrity(Some(root("4711"))).unwrap());
+    assert_eq!(*header.integrity().unwrap(), root("4711"));
+
+    assert!(!header.set_integrity(Some(root("4711"))).unwrap());
+    assert_eq!(*header.integrity().unwrap(), root("4711"));
+
+    assert!(header.set_integrity(None).unwrap());
+    assert!(header.integrity().is_none());
+
+    assert!(!header.set_integrity(None).unwrap());
+    assert!(header.integrity().is_none());
+}
+
+#[test]
+fn generate_recovery_key_rev0() {
+    let mut header = header(PlainSecret::Rev0(rev0()));
+    let err = header
+        .generate_recovery_key(Kdf::pbkdf2(Digest::Sha1, 1, b"123"))
+        .unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRevision(3, 1)));
+    assert!(!header.has_recovery_key());
+}
+
+#[test]
+fn generate_recovery_key_rev1() {
+    let mut header = header(PlainSecret::Rev1(rev1()));
+    let err = header
+        .generate_recovery_key(Kdf::pbkdf2(Digest::Sha1, 1, b"123"))
+        .unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRevision(3, 1)));
+    assert!(!header.has_recovery_key());
+}
+
+#[test]
+fn generate_recovery_key_rev2() {
+    let mut header = header(PlainSecret::Rev2(rev2()));
+    let err = header
+        .generate_recovery_key(Kdf::pbkdf2(Digest::Sha1, 1, b"123"))
+        .unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRevision(3, 1)));
+    assert!(!header.has_recovery_key());
+}
+
+fn recovery_header() -> (Header<'static, MemoryBackend>, String) {
+    let mut header = Header {
+        revision: 3,
+        cipher: Cipher::Aes128Gcm,
+        kdf: Kdf::pbkdf2(Digest::Sha1, 1, b"123"),
+        ..header(PlainSecret::Rev3(rev3()))
+    };
+
+    let recovery_key = header
+        .generate_recovery_key(Kdf::pbkdf2(Digest::Sha1, 1, b"456"))
+        .unwrap();
+
+    (header, recovery_key.to_string())
+}
+
+#[test]
+fn generate_recovery_key_rev3() {
+    let (header, _) = recovery_header();
+
+    assert!(header.has_recovery_key());
+    assert!(header.wrapping_key.is_none());
+    assert_eq!(header.recovery, Some(Kdf::pbkdf2(Digest::Sha1, 1, b"456")));
+    assert!(matches!(&header.data, PlainSecret::Rev3(rev3) if rev3.recovery.is_some()));
+}
+
+fn large_header(nservices: u32) -> Header<'static, MemoryBackend> {
+    let data = PlainRev3 {
+        key: vec![1; 64].into(),
+        iv: vec![2; 16].into(),
+        services: (1..=nservices).map(|sid| service(sid, Some("1"))).collect(),
+        ..rev3()
+    };
+
+    Header {
+        revision: 3,
+        cipher: Cipher::Aes256CtrHmac,
+        kdf: Kdf::pbkdf2(Digest::Sha1, 1, b"123"),
+        ..header(PlainSecret::Rev3(data))
+    }
+}
+
+#[test]
+fn generate_recovery_key_too_large() {
+    let mut header = large_header(24);
+
+    header.check_size().unwrap();
+
+    let err = header
+        .generate_recovery_key(Kdf::pbkdf2(Digest::Sha1, 1, b"456"))
+        .unwrap_err();
+
+    assert!(matches!(err, HeaderError::TooLarge(n) if n > 512));
+    assert!(!header.has_recovery_key());
+    assert!(matches!(&header.data, PlainSecret::Rev3(rev3) if rev3.recovery.is_none()));
+}
+
+#[test]
+fn size() {
+    let (header, _) = recovery_header();
+    let size = header.size().unwrap();
+    let mut buf = vec![0; size];
+
+    header
+        .write(&mut buf, &mut PasswordStore::with_value(b"abc"))
+        .unwrap();
+
+    let err = header
+        .write(&mut buf[..size - 1], &mut PasswordStore::with_value(b"abc"))
+        .unwrap_err();
+    assert!(matches!(err, HeaderError::TooLarge(n) if n == size));
+}
+
+#[test]
+fn write_too_large() {
+    let header = large_header(64);
+    let mut buf = [0; 512];
+
+    let err = header
+        .write(&mut buf, &mut PasswordStore::with_value(b"abc"))
+        .unwrap_err();
+    assert!(matches!(err, HeaderError::TooLarge(n) if n > 512));
+}
+
+#[test]
+fn read_with_recovery_slot() {
+    let (header, _) = recovery_header();
+    let mut buf = [0; 512];
+
+    header
+        .write(&mut buf, &mut PasswordStore::with_value(b"abc"))
+        .unwrap();
+
+    let out = Header::<MemoryBackend>::read(
+        &buf,
+        Migrator::default(),
+        &mut PasswordStore::with_value(b"abc"),
+    )
+    .unwrap();
+
+    assert!(out.has_recovery_key());
+    assert!(out.wrapping_key.is_some());
+    assert_eq!(out.data, header.data);
+}
+
+#[test]
+fn read_recovery() {
+    let (header, recovery_key) = recovery_header();
+    let mut buf = [0; 512];
+
+    header
+        .write(&mut buf, &mut PasswordStore::with_value(b"abc"))
+        .unwrap();
+
+    let out = Header::<MemoryBackend>::read_recovery(
+        &buf,
+        Migrator::default(),
+        &mut PasswordStore::with_value(recovery_key.as_bytes()),
+    )
+    .unwrap();
+
+    assert!(out.has_recovery_key());
+    assert!(out.wrapping_key.is_some());
+    assert_eq!(out.data, header.data);
+}
+
+#[test]
+fn read_recovery_no_slot() {
+    let err = Header::<MemoryBackend>::read_recovery(
+        &REV3,
+        Migrator::default(),
+        &mut PasswordStore::new(None),
+    )
+    .unwrap_err();
+
+    assert!(matches!(err, HeaderError::NoRecoveryKey));
+}
+
+#[test]
+fn read_recovery_invalid_key() {
+    let (header, _) = recovery_header();
+    let mut buf = [0; 512];
+
+    header
+        .write(&mut buf, &mut PasswordStore::with_value(b"abc"))
+        .unwrap();
+
+    let err = Header::<MemoryBackend>::read_recovery(
+        &buf,
+        Migrator::default(),
+        &mut PasswordStore::with_value(b"xxx"),
+    )
+    .unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRecoveryKey));
+}
+
+#[test]
+fn write_with_wrapping_key() {
+    let (header, recovery_key) = recovery_header();
+    let mut buf = [0; 512];
+
+    header
+        .write(&mut buf, &mut PasswordStore::with_value(b"abc"))
+        .unwrap();
+
+    let mut header = Header::<MemoryBackend>::read_recovery(
+        &buf,
+        Migrator::default(),
+        &mut PasswordStore::with_value(recovery_key.as_bytes()),
+    )
+    .unwrap();
+
+    // no password available, the wrapping key is used
+    header
+        .write(&mut buf, &mut PasswordStore::new(None))
+        .unwrap();
+
+    Header::<MemoryBackend>::read(
+        &buf,
+        Migrator::default(),
+        &mut PasswordStore::with_value(b"abc"),
+    )
+    .unwrap();
+
+    header.reset_wrapping_key();
+
+    let err = header
+        .write(&mut buf, &mut PasswordStore::new(None))
+        .unwrap_err();
+    assert!(matches!(err, HeaderError::Password(_)));
+}
+
+fn wrapping_key_header() -> (Header<'static, MemoryBackend>, [u8; 512]) {
+    let header = Header {
+        revision: 3,
+        cipher: Cipher::Aes128Gcm,
+        kdf: Kdf::pbkdf2(Digest::Sha1, 1, b"123"),
+        ..header(PlainSecret::Rev3(rev3()))
+    };
+    let mut buf = [0; 512];
+
+    header
+        .write(&mut buf, &mut PasswordStore::with_value(b"abc"))
+        .unwrap();
+
+    (header, buf)
+}
+
+#[test]
+fn read_wrapping_key() {
+    let (header, buf) = wrapping_key_header();
+
+    let header1 = Header::<MemoryBackend>::read(
+        &buf,
+        Migrator::default(),
+        &mut PasswordStore::with_value(b"abc"),
+    )
+    .unwrap();
+    let key = WrappingKey::from_bytes(header1.wrapping_key().unwrap());
+
+    let header2 =
+        Header::<MemoryBackend>::read_wrapping_key(&buf, Migrator::default(), &key).unwrap();
+
+    assert_eq!(header2.wrapping_key(), Some(key.as_bytes()));
+    assert_eq!(header2.data, header.data);
+}
+
+#[test]
+fn read_wrapping_key_wrong() {
+    let (_, buf) = wrapping_key_header();
+    let key = WrappingKey::from_bytes(&[1; 20]);
+
+    let err =
+        Header::<MemoryBackend>::read_wrapping_key(&buf, Migrator::default(), &key).unwrap_err();
+
+    assert!(matches!(err, HeaderError::WrongWrappingKey));
+}
+
+#[test]
+fn read_wrapping_key_too_short() {
+    let (_, buf) = wrapping_key_header();
+    let key = WrappingKey::from_bytes(&[1; 15]);
+
+    let err =
+        Header::<MemoryBackend>::read_wrapping_key(&buf, Migrator::default(), &key).unwrap_err();
+
+    assert!(matches!(err, HeaderError::WrongWrappingKey));
+}
+
+#[test]
+fn derive_wrapping_key() {
+    let (mut header, _) = wrapping_key_header();
+    let mut buf = [0; 512];
+
+    assert!(header.wrapping_key().is_none());
+
+    header
+        .derive_wrapping_key(&mut PasswordStore::with_value(b"abc"))
+        .unwrap();
+    assert!(header.wrapping_key().is_some());
+
+    // the derived key is used, no password needed anymore
+    header
+        .write(&mut buf, &mut PasswordStore::new(None))
+        .unwrap();
+
+    Header::<MemoryBackend>::read(
+        &buf,
+        Migrator::default(),
+        &mut PasswordStore::with_value(b"abc"),
+    )
+    .unwrap();
+}
+
+#[test]
+fn set_recipient_rev0() {
+    let recipient = PrivateKey::generate().unwrap().public_key().unwrap();
+    let mut header = header(PlainSecret::Rev0(rev0()));
+    let err = header.set_recipient(&recipient).unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRevision(3, 1)));
+    assert!(!header.has_recipient());
+}
+
+#[test]
+fn set_recipient_rev1() {
+    let recipient = PrivateKey::generate().unwrap().public_key().unwrap();
+    let mut header = header(PlainSecret::Rev1(rev1()));
+    let err = header.set_recipient(&recipient).unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRevision(3, 1)));
+    assert!(!header.has_recipient());
+}
+
+#[test]
+fn set_recipient_rev2() {
+    let recipient = PrivateKey::generate().unwrap().public_key().unwrap();
+    let mut header = header(PlainSecret::Rev2(rev2()));
+    let err = header.set_recipient(&recipient).unwrap_err();
+
+    assert!(matches!(err, HeaderError::InvalidRevision(3, 1)));
+    assert!(!header.has_recipient());
+}
+
+fn recipient_header() -> (Header<'static, MemoryBackend>, PrivateKey) {
+    let private_key = PrivateKey::generate().unwrap();
+    let mut header = Header {
+        revision: 3,
+        cipher: Cipher::Aes128Gcm,
+        kdf: Kdf::pbkdf2(Digest::Sha1, 1, b"123"),
+        ..header(PlainSecret::Rev3(rev3()))
+    };
+
+    header
+        .set_recipient(&private_key.public_key().unwrap())
+        .unwrap();
+
+    (header, private_key)
+}
+
+#[test]
+fn set_recipient_rev3() {
+    let (header, _) = recipient_header();
+
+    assert!(header.has_recipient());
+    assert!(header.wrapping_key.is_none());
+    assert_eq!(header.recipient.as_ref().unwrap().len(), 32);
+    assert!(matches!(&header.data, PlainSecret::Rev3(rev3) if rev3.recipient.is_some()));
+}
+
+#[test]
+fn set_recipient_too_large() {
+    let private_key = PrivateKey::generate().unwrap();
+    let mut header = large_header(24);
+
+    header.check_size().unwrap();
+
+    let err = header
+        .set_recipient(&private_key.public_key().unwrap())
+        .unwrap_err();
+
+    assert!(matches!(err, HeaderError::TooLarge(n) if n > 512));
+    assert!(!header.has_recipient());
+    assert!(matches!(&header.data, PlainSecret::Rev3(rev3) if rev3.recipient.is_none()));
+}
+
+#[test]
+fn read_with_recipient_slot() {
+    let (header, _) = recipient_header();
+    let mut buf = [0; 512];
+
+    header
+        .write(&mut buf, &mut PasswordStore::with_value(b"abc"))
+        .unwrap();
+
+    let out = Header::<MemoryBackend>::read(
+        &buf,
+        Migrator::default(),
+        &mut PasswordStore::with_value(b"abc"),
+    )
+    .unwrap();
+
+    assert!(out.has_recipient());
+    assert!(out.wrapping_key.is_some());
+    assert_eq!(out.recipient, header.recipient);
+    assert_eq!(out.data, header.data);
+}
+
+#[test]
+fn read_private_key() {
+    let (header, private_key) = recipient_header();
+    let mut buf = [0; 512];
+
+    header
+        .write(&mut buf, &mut PasswordStore::with_value(b"abc"))
+        .unwrap();
+
+    let out =
+        Header::<MemoryBackend>::read_private_key(&buf, Migrator::default(), &private_key).unwrap();
+
+    assert!(out.has_recipient());
+    assert!(out.wrapping_key.is_some());
+    assert_eq!(out.data, header.data);
+}
+
+#[test]
+fn read_private_key_no_slot() {
+    let private_key = PrivateKey::generate().unwrap();
+    let err = Header::<MemoryBackend>::read_private_key(&REV3, Migrator::default(), &private_key)
+        .unwrap_err();
+
+    assert!(matches!(err, HeaderError::NoRecipient));
+}
+
+#[test]
+fn read_private_key_wrong_key() {
+    let (header, _) = recipient_header();
+    let private_key = PrivateKey::generate().unwrap();
+    let mut buf = [0; 512];
+
+    header
+        .write(&mut buf, &mut PasswordStore::with_value(b"abc"))
+        .unwrap();
+
+    let err = Header::<MemoryBackend>::read_private_key(&buf, Migrator::default(), &private_key)
+        .unwrap_err();
+
+    assert!(matches!(err, HeaderError::WrongPrivateKey));
+}
+
+#[test]
+fn write_with_generated_wrapping_key() {
+    let (mut header, private_key) = recipient_header();
+    let mut buf = [0; 512];
+
+    header.generate_wrapping_key().unwrap();
+    header
+        .write(&mut buf, &mut PasswordStore::new(None))
+        .unwrap();
 
-    header.set_top_id("4711".parse().unwrap());
+    let out =
+        Header::<MemoryBackend>::read_private_key(&buf, Migrator::default(), &private_key).unwrap();
 
-    assert_eq!(header.top_id().unwrap().to_string(), "4711");
+    assert_eq!(out.wrapping_key.as_deref(), header.wrapping_key.as_deref());
+    assert_eq!(out.data, header.data);
 }
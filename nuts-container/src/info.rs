@@ -40,6 +40,21 @@ pub struct Info<B: Backend> {
     /// The key derivation function.
     pub kdf: Kdf,
 
+    /// The identifiers (sid) of the services attached to the container.
+    pub services: Vec<u32>,
+
+    /// Whether [rollback protection](crate::CreateOptionsBuilder::with_rollback_protection)
+    /// is enabled for the container.
+    pub rollback_protection: bool,
+
+    /// Whether a [recovery key](crate::CreateOptionsBuilder::with_recovery_key)
+    /// is assigned to the container.
+    pub recovery_key: bool,
+
+    /// Whether a [recipient](crate::CreateOptionsBuilder::with_recipient) is
+    /// assigned to the container.
+    pub recipient: bool,
+
     /// The gross block size is the block size specified by the
     /// [backend](Backend::block_size).
     ///
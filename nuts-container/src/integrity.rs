@@ -0,0 +1,516 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use nuts_backend::{Backend, Binary, IdSize};
+use std::collections::{BTreeSet, HashMap};
+use std::{cmp, fmt};
+use thiserror::Error;
+
+use crate::buffer::{Buffer, BufferError, BufferMut};
+use crate::crypto::{self, CryptoError};
+use crate::digest::Digest;
+
+const MAGIC_LEAF: u32 = 0x6d6b_6c66; // mklf
+const MAGIC_NODE: u32 = 0x6d6b_6e64; // mknd
+pub const DIGEST: Digest = Digest::Sha256;
+
+/// Errors coming from the rollback protection.
+#[derive(Debug, Error)]
+pub enum IntegrityError {
+    /// A block does not match its entry in the integrity tree.
+    ///
+    /// The block was rolled back to an older version or was modified.
+    #[error("block {0} is stale or was modified")]
+    StaleBlock(String),
+
+    /// The integrity tree does not match the root stored in the header.
+    #[error("the integrity tree is corrupted")]
+    Corrupted,
+
+    /// Invalid block of the integrity tree, could not validate magic.
+    #[error("invalid integrity block")]
+    InvalidBlock,
+
+    /// Invalid block id, could not parse an id from an integrity block.
+    #[error("invalid block id in integrity tree")]
+    InvalidId,
+
+    /// The block size is too small to store the integrity tree.
+    #[error("the block size is too small for an integrity tree")]
+    BlockSizeTooSmall,
+
+    /// The operation is not available for a container with rollback
+    /// protection.
+    #[error("not supported for a container with rollback protection")]
+    Unsupported,
+
+    /// Error while (de-) serializing binary data.
+    #[error(transparent)]
+    Buffer(#[from] BufferError),
+
+    /// An error in the crypto provider occured.
+    #[error(transparent)]
+    Crypto(#[from] CryptoError),
+}
+
+/// Calculates the hash of the given buffer.
+pub fn hash(buf: &[u8]) -> Result<Vec<u8>, IntegrityError> {
+    Ok(crypto::hash(DIGEST, buf)?)
+}
+
+/// The root of the integrity tree as stored in the header.
+pub struct Root<B: Backend> {
+    /// Id of the root block.
+    pub id: B::Id,
+
+    /// Hash of the root block.
+    pub hash: Vec<u8>,
+}
+
+impl<B: Backend> Clone for Root<B> {
+    fn clone(&self) -> Self {
+        Root {
+            id: self.id.clone(),
+            hash: self.hash.clone(),
+        }
+    }
+}
+
+impl<B: Backend> PartialEq for Root<B> {
+    fn eq(&self, other: &Root<B>) -> bool {
+        self.id == other.id && self.hash == other.hash
+    }
+}
+
+impl<B: Backend> fmt::Debug for Root<B> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Root")
+            .field("id", &self.id.to_string())
+            .field("hash", &self.hash)
+            .finish()
+    }
+}
+
+/// An entry of the integrity tree.
+///
+/// In a leaf block the entry refers to a data block together with its version
+/// counter and the hash of its ciphertext. In an inner node the entry refers
+/// to a child block and the hash of its content, the version is not used.
+pub struct Entry<B: Backend> {
+    pub id: B::Id,
+    pub version: u64,
+    pub hash: Vec<u8>,
+}
+
+impl<B: Backend> Entry<B> {
+    fn get_from_buffer<T: Buffer>(buf: &mut T) -> Result<Entry<B>, IntegrityError> {
+        let id_bytes = buf.get_vec::<1>()?;
+        let id = Binary::from_bytes(&id_bytes).ok_or(IntegrityError::InvalidId)?;
+        let version = buf.get_u64()?;
+        let hash = buf.get_vec::<1>()?;
+
+        Ok(Entry { id, version, hash })
+    }
+
+    fn put<T: BufferMut>(&self, buf: &mut T) -> Result<(), IntegrityError> {
+        buf.put_vec::<1>(&self.id.as_bytes())?;
+        buf.put_u64(self.version)?;
+        buf.put_vec::<1>(&self.hash)?;
+
+        Ok(())
+    }
+}
+
+impl<B: Backend> Clone for Entry<B> {
+    fn clone(&self) -> Self {
+        Entry {
+            id: self.id.clone(),
+            version: self.version,
+            hash: self.hash.clone(),
+        }
+    }
+}
+
+impl<B: Backend> PartialEq for Entry<B> {
+    fn eq(&self, other: &Entry<B>) -> bool {
+        self.id == other.id && self.version == other.version && self.hash == other.hash
+    }
+}
+
+impl<B: Backend> fmt::Debug for Entry<B> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Entry")
+            .field("id", &self.id.to_string())
+            .field("version", &self.version)
+            .field("hash", &self.hash)
+            .finish()
+    }
+}
+
+/// A block of the integrity tree as stored in the container.
+#[derive(Debug, PartialEq)]
+pub struct Node<B: Backend> {
+    pub leaf: bool,
+    pub entries: Vec<Entry<B>>,
+}
+
+impl<B: Backend> Node<B> {
+    /// Returns the number of entries, which fit into a block of the given
+    /// size.
+    pub fn capacity(block_size: usize) -> usize {
+        let entry_size = 1 + B::Id::size() + 8 + 1 + DIGEST.size();
+        let overhead = 4 + 4; // magic, number of entries
+
+        block_size.saturating_sub(overhead) / entry_size
+    }
+
+    pub fn get_from_buffer<T: Buffer>(buf: &mut T) -> Result<Node<B>, IntegrityError> {
+        let leaf = match buf.get_u32()? {
+            MAGIC_LEAF => true,
+            MAGIC_NODE => false,
+            _ => return Err(IntegrityError::InvalidBlock),
+        };
+
+        let nentries = buf.get_u32()?;
+        let mut entries = Vec::with_capacity(nentries as usize);
+
+        for _ in 0..nentries {
+            entries.push(Entry::get_from_buffer(buf)?);
+        }
+
+        Ok(Node { leaf, entries })
+    }
+
+    fn put_entries<T: BufferMut>(
+        buf: &mut T,
+        leaf: bool,
+        entries: &[Entry<B>],
+    ) -> Result<(), IntegrityError> {
+        buf.put_u32(if leaf { MAGIC_LEAF } else { MAGIC_NODE })?;
+        buf.put_u32(entries.len() as u32)?;
+
+        for entry in entries {
+            entry.put(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The integrity tree.
+///
+/// The tree is a Merkle tree over all data blocks of the container. The
+/// leaves of the tree are the data blocks together with their version
+/// counters and the hashes of their ciphertexts. The leaves are stored in leaf
+/// blocks, inner nodes reference their child blocks together with the hash of
+/// the child blocks. The hash of the root block is stored in the header of the
+/// container.
+///
+/// The tree is kept in memory, modifications are collected until the tree is
+/// [flushed](Tree::flush). Modified blocks of the tree are never overwritten,
+/// they are stored in new blocks instead.
+pub struct Tree<B: Backend> {
+    leaves: Vec<Entry<B>>,
+    positions: HashMap<Vec<u8>, usize>,
+    levels: Vec<Vec<(B::Id, Vec<u8>)>>,
+    dirty: BTreeSet<usize>,
+    modified: bool,
+}
+
+impl<B: Backend> Tree<B> {
+    /// Creates a new, empty tree.
+    pub fn new() -> Tree<B> {
+        Tree {
+            leaves: vec![],
+            positions: HashMap::new(),
+            levels: vec![],
+            dirty: BTreeSet::new(),
+            modified: true,
+        }
+    }
+
+    /// Loads the tree starting at the given `root`.
+    ///
+    /// The `read` function reads the plain content of a block of the tree.
+    /// Every block is validated against the hash stored in its parent block
+    /// resp. in the `root`.
+    pub fn load<E, F>(root: &Root<B>, block_size: usize, mut read: F) -> Result<Tree<B>, E>
+    where
+        E: From<IntegrityError>,
+        F: FnMut(&B::Id, &mut [u8]) -> Result<(), E>,
+    {
+        let mut buf = vec![0; block_size];
+        let mut levels = vec![];
+        let mut current = vec![(root.id.clone(), root.hash.clone())];
+
+        let leaves = loop {
+            let mut leaf = None;
+            let mut children = vec![];
+
+            for (id, expected) in current.iter() {
+                read(id, &mut buf)?;
+
+                if hash(&buf)? != *expected {
+                    return Err(IntegrityError::Corrupted.into());
+                }
+
+                let node = Node::<B>::get_from_buffer(&mut buf.as_slice())?;
+
+                // all blocks of a level are either leaf blocks or inner nodes
+                if *leaf.get_or_insert(node.leaf) != node.leaf {
+                    return Err(IntegrityError::Corrupted.into());
+                }
+
+                children.extend(node.entries);
+            }
+
+            levels.push(current);
+
+            if leaf == Some(true) {
+                break children;
+            }
+
+            current = children
+                .into_iter()
+                .map(|entry| (entry.id, entry.hash))
+                .collect();
+        };
+
+        levels.reverse();
+
+        let positions = leaves
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| (entry.id.as_bytes(), idx))
+            .collect();
+
+        Ok(Tree {
+            leaves,
+            positions,
+            levels,
+            dirty: BTreeSet::new(),
+            modified: false,
+        })
+    }
+
+    /// Returns the leaf of the block with the given `id`.
+    pub fn get(&self, id: &B::Id) -> Option<&Entry<B>> {
+        self.positions
+            .get(&id.as_bytes())
+            .map(|idx| &self.leaves[*idx])
+    }
+
+    /// Returns the ids of all blocks in the tree.
+    pub fn ids(&self) -> impl Iterator<Item = &B::Id> {
+        self.leaves.iter().map(|entry| &entry.id)
+    }
+
+    /// Updates the leaf of the block with the given `id`.
+    ///
+    /// The version counter of the block is incremented and the `hash` of its
+    /// new ciphertext is assigned. An unknown block is inserted into the tree.
+    pub fn update(&mut self, id: &B::Id, hash: Vec<u8>) {
+        let key = id.as_bytes();
+
+        let idx = match self.positions.get(&key) {
+            Some(idx) => {
+                let entry = &mut self.leaves[*idx];
+
+                entry.version += 1;
+                entry.hash = hash;
+
+                *idx
+            }
+            None => {
+                self.leaves.push(Entry {
+                    id: id.clone(),
+                    version: 0,
+                    hash,
+                });
+                self.positions.insert(key, self.leaves.len() - 1);
+
+                self.leaves.len() - 1
+            }
+        };
+
+        self.dirty.insert(idx);
+        self.modified = true;
+    }
+
+    /// Removes the leaf of the block with the given `id`.
+    pub fn remove(&mut self, id: &B::Id) {
+        if let Some(idx) = self.positions.remove(&id.as_bytes()) {
+            let last = self.leaves.len() - 1;
+
+            self.leaves.swap_remove(idx);
+
+            if let Some(entry) = self.leaves.get(idx) {
+                self.positions.insert(entry.id.as_bytes(), idx);
+            }
+
+            self.dirty.insert(idx);
+            self.dirty.insert(last);
+            self.modified = true;
+        }
+    }
+
+    /// Verifies the `ctext` of the block with the given `id`.
+    ///
+    /// An [`IntegrityError::StaleBlock`] error is returned, if the hash of
+    /// `ctext` does not match the hash stored in the tree.
+    pub fn verify(&self, id: &B::Id, ctext: &[u8]) -> Result<(), IntegrityError> {
+        match self.get(id) {
+            Some(entry) if entry.hash == hash(ctext)? => Ok(()),
+            _ => Err(IntegrityError::StaleBlock(id.to_string())),
+        }
+    }
+
+    /// Writes the modified blocks of the tree.
+    ///
+    /// The `write` function stores the plain content of a block in a new
+    /// block and returns its id.
+    ///
+    /// Returns the new root of the tree together with the blocks, which are
+    /// not part of the tree anymore. Once the new root is stored in the
+    /// header, these blocks can be released. If the tree is not modified,
+    /// [`None`] is returned.
+    #[allow(clippy::type_complexity)]
+    pub fn flush<E, F>(
+        &mut self,
+        block_size: usize,
+        mut write: F,
+    ) -> Result<Option<(Root<B>, Vec<B::Id>)>, E>
+    where
+        E: From<IntegrityError>,
+        F: FnMut(&[u8]) -> Result<B::Id, E>,
+    {
+        if !self.modified {
+            return Ok(None);
+        }
+
+        let capacity = Node::<B>::capacity(block_size);
+
+        if capacity < 2 {
+            return Err(IntegrityError::BlockSizeTooSmall.into());
+        }
+
+        let mut buf = vec![0; block_size];
+        let mut levels = vec![];
+        let mut garbage = vec![];
+        let mut upper = vec![];
+        let mut dirty = self
+            .dirty
+            .iter()
+            .map(|idx| idx / capacity)
+            .collect::<BTreeSet<_>>();
+
+        loop {
+            let level = levels.len();
+            let entries = if level == 0 { &self.leaves } else { &upper };
+            let nblocks = entries.len() / capacity + usize::from(entries.len() % capacity > 0);
+            let nblocks = cmp::max(1, nblocks);
+            let old = self.levels.get(level).map_or(&[][..], |v| v.as_slice());
+            let mut blocks = Vec::with_capacity(nblocks);
+            let mut parents = BTreeSet::new();
+
+            for idx in 0..nblocks {
+                match old.get(idx) {
+                    Some(block) if !dirty.contains(&idx) => blocks.push(block.clone()),
+                    _ => {
+                        let start = idx * capacity;
+                        let end = cmp::min(start + capacity, entries.len());
+
+                        buf.iter_mut().for_each(|n| *n = 0);
+                        Node::put_entries(
+                            &mut buf.as_mut_slice(),
+                            level == 0,
+                            &entries[start..end],
+                        )?;
+
+                        let hash = hash(&buf)?;
+                        let id = write(&buf)?;
+
+                        if let Some((old_id, _)) = old.get(idx) {
+                            garbage.push(old_id.clone());
+                        }
+
+                        blocks.push((id, hash));
+                        parents.insert(idx / capacity);
+                    }
+                }
+            }
+
+            // blocks, which are not needed anymore, change their parents
+            for (idx, (id, _)) in old.iter().enumerate().skip(nblocks) {
+                garbage.push(id.clone());
+                parents.insert(idx / capacity);
+            }
+
+            upper = blocks
+                .iter()
+                .map(|(id, hash)| Entry {
+                    id: id.clone(),
+                    version: 0,
+                    hash: hash.clone(),
+                })
+                .collect();
+            dirty = parents;
+            levels.push(blocks);
+
+            if nblocks == 1 {
+                break;
+            }
+        }
+
+        // levels above the new root are not needed anymore
+        for blocks in self.levels.iter().skip(levels.len()) {
+            garbage.extend(blocks.iter().map(|(id, _)| id.clone()));
+        }
+
+        let (id, hash) = levels[levels.len() - 1][0].clone();
+
+        self.levels = levels;
+        self.dirty.clear();
+        self.modified = false;
+
+        Ok(Some((Root { id, hash }, garbage)))
+    }
+}
+
+impl<B: Backend> fmt::Debug for Tree<B> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let levels = self
+            .levels
+            .iter()
+            .map(|blocks| blocks.iter().map(|(id, _)| id.to_string()).collect())
+            .collect::<Vec<Vec<_>>>();
+
+        fmt.debug_struct("Tree")
+            .field("leaves", &self.leaves.len())
+            .field("levels", &levels)
+            .field("modified", &self.modified)
+            .finish()
+    }
+}
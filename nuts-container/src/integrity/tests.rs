@@ -0,0 +1,334 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_memory::MemoryBackend;
+use std::collections::HashMap;
+
+use crate::integrity::{hash, Entry, IntegrityError, Node, Root, Tree};
+
+const LEAF: [u8; 30] = [
+    0x6d, 0x6b, 0x6c, 0x66, // magic
+    0, 0, 0, 1, // number of entries
+    4, 0, 0, 0, 1, // entry 1: id
+    0, 0, 0, 0, 0, 0, 0, 7, // entry 1: version
+    3, 1, 2, 3, // entry 1: hash
+    0, 0, 0, 0, 0, // padding
+];
+
+const NODE: [u8; 21] = [
+    0x6d, 0x6b, 0x6e, 0x64, // magic
+    0, 0, 0, 1, // number of entries
+    4, 0, 0, 0, 2, // entry 1: id
+    0, 0, 0, 0, 0, 0, 0, 0, // entry 1: version
+];
+
+const BLOCK_SIZE: usize = 100; // two entries per block
+
+type Id = <MemoryBackend as nuts_backend::Backend>::Id;
+
+fn id(s: &str) -> Id {
+    s.parse().unwrap()
+}
+
+/// In-memory store for the blocks of a tree.
+#[derive(Default)]
+struct Store {
+    blocks: HashMap<String, Vec<u8>>,
+    next: u32,
+}
+
+impl Store {
+    fn flush(&mut self, tree: &mut Tree<MemoryBackend>) -> Option<(Root<MemoryBackend>, Vec<Id>)> {
+        tree.flush::<IntegrityError, _>(BLOCK_SIZE, |buf| {
+            self.next += 1;
+            self.blocks.insert(self.next.to_string(), buf.to_vec());
+
+            Ok(id(&self.next.to_string()))
+        })
+        .unwrap()
+    }
+
+    fn load(&self, root: &Root<MemoryBackend>) -> Result<Tree<MemoryBackend>, IntegrityError> {
+        Tree::load(root, BLOCK_SIZE, |id, buf| {
+            buf.copy_from_slice(&self.blocks[&id.to_string()]);
+            Ok(())
+        })
+    }
+}
+
+fn tree_with_leaves(n: u32) -> Tree<MemoryBackend> {
+    let mut tree = Tree::new();
+
+    for i in 1..=n {
+        tree.update(&id(&i.to_string()), hash(&[i as u8]).unwrap());
+    }
+
+    tree
+}
+
+#[test]
+fn capacity() {
+    assert_eq!(Node::<MemoryBackend>::capacity(0), 0);
+    assert_eq!(Node::<MemoryBackend>::capacity(53), 0);
+    assert_eq!(Node::<MemoryBackend>::capacity(54), 1);
+    assert_eq!(Node::<MemoryBackend>::capacity(99), 1);
+    assert_eq!(Node::<MemoryBackend>::capacity(100), 2);
+    assert_eq!(Node::<MemoryBackend>::capacity(512), 10);
+}
+
+#[test]
+fn de_leaf() {
+    let node = Node::<MemoryBackend>::get_from_buffer(&mut &LEAF[..]).unwrap();
+
+    assert!(node.leaf);
+    assert_eq!(
+        node.entries,
+        [Entry {
+            id: id("1"),
+            version: 7,
+            hash: vec![1, 2, 3]
+        }]
+    );
+}
+
+#[test]
+fn de_node() {
+    let mut buf = NODE.to_vec();
+    buf.push(0); // hash
+
+    let node = Node::<MemoryBackend>::get_from_buffer(&mut buf.as_slice()).unwrap();
+
+    assert!(!node.leaf);
+    assert_eq!(
+        node.entries,
+        [Entry {
+            id: id("2"),
+            version: 0,
+            hash: vec![]
+        }]
+    );
+}
+
+#[test]
+fn de_inval_magic() {
+    let mut buf = LEAF;
+    buf[0] += 1;
+
+    let err = Node::<MemoryBackend>::get_from_buffer(&mut &buf[..]).unwrap_err();
+    assert!(matches!(err, IntegrityError::InvalidBlock));
+}
+
+#[test]
+fn de_eof() {
+    let err = Node::<MemoryBackend>::get_from_buffer(&mut &NODE[..]).unwrap_err();
+    assert!(matches!(err, IntegrityError::Buffer(_)));
+}
+
+#[test]
+fn put_leaf() {
+    let entries = [Entry::<MemoryBackend> {
+        id: id("1"),
+        version: 7,
+        hash: vec![1, 2, 3],
+    }];
+    let mut buf = [0; 30];
+
+    Node::put_entries(&mut &mut buf[..], true, &entries).unwrap();
+    assert_eq!(buf, LEAF);
+}
+
+#[test]
+fn update() {
+    let mut tree = tree_with_leaves(1);
+
+    assert_eq!(tree.get(&id("1")).unwrap().version, 0);
+
+    tree.update(&id("1"), hash(&[2]).unwrap());
+
+    let entry = tree.get(&id("1")).unwrap();
+    assert_eq!(entry.version, 1);
+    assert_eq!(entry.hash, hash(&[2]).unwrap());
+}
+
+#[test]
+fn verify() {
+    let tree = tree_with_leaves(2);
+
+    tree.verify(&id("1"), &[1]).unwrap();
+    tree.verify(&id("2"), &[2]).unwrap();
+
+    let err = tree.verify(&id("1"), &[2]).unwrap_err();
+    assert!(matches!(err, IntegrityError::StaleBlock(id) if id == "1"));
+
+    let err = tree.verify(&id("3"), &[3]).unwrap_err();
+    assert!(matches!(err, IntegrityError::StaleBlock(id) if id == "3"));
+}
+
+#[test]
+fn remove() {
+    let mut tree = tree_with_leaves(3);
+
+    tree.remove(&id("1"));
+    tree.remove(&id("4"));
+
+    assert!(tree.get(&id("1")).is_none());
+    tree.verify(&id("2"), &[2]).unwrap();
+    tree.verify(&id("3"), &[3]).unwrap();
+}
+
+#[test]
+fn ids() {
+    let mut tree = tree_with_leaves(3);
+
+    tree.remove(&id("1"));
+
+    let mut ids = tree.ids().map(|id| id.to_string()).collect::<Vec<_>>();
+    ids.sort();
+    assert_eq!(ids, ["2", "3"]);
+}
+
+#[test]
+fn flush_empty() {
+    let mut store = Store::default();
+    let mut tree = Tree::new();
+
+    let (root, garbage) = store.flush(&mut tree).unwrap();
+
+    assert_eq!(root.id, id("1"));
+    assert!(garbage.is_empty());
+    assert!(store.flush(&mut tree).is_none());
+
+    let tree = store.load(&root).unwrap();
+    assert!(tree.get(&id("1")).is_none());
+}
+
+#[test]
+fn flush_load() {
+    for n in 0..20 {
+        let mut store = Store::default();
+        let mut tree = tree_with_leaves(n);
+
+        let (root, garbage) = store.flush(&mut tree).unwrap();
+        assert!(garbage.is_empty());
+
+        let tree = store.load(&root).unwrap();
+
+        for i in 1..=n {
+            tree.verify(&id(&i.to_string()), &[i as u8]).unwrap();
+        }
+    }
+}
+
+#[test]
+fn flush_update() {
+    let mut store = Store::default();
+    let mut tree = tree_with_leaves(8); // 4 + 2 + 1 blocks
+
+    store.flush(&mut tree).unwrap();
+    assert_eq!(store.blocks.len(), 7);
+
+    // the path from the leaf to the root is replaced
+    tree.update(&id("1"), hash(&[11]).unwrap());
+
+    let (root, garbage) = store.flush(&mut tree).unwrap();
+    assert_eq!(garbage.len(), 3);
+    assert_eq!(store.blocks.len(), 10);
+
+    let tree = store.load(&root).unwrap();
+    tree.verify(&id("1"), &[11]).unwrap();
+    assert_eq!(tree.get(&id("1")).unwrap().version, 1);
+}
+
+#[test]
+fn flush_grow_shrink() {
+    let mut store = Store::default();
+    let mut tree = tree_with_leaves(2);
+
+    let (_, garbage) = store.flush(&mut tree).unwrap();
+    assert!(garbage.is_empty());
+
+    for i in 3..=9 {
+        tree.update(&id(&i.to_string()), hash(&[i as u8]).unwrap());
+
+        let (root, garbage) = store.flush(&mut tree).unwrap();
+        let tree = store.load(&root).unwrap();
+
+        for j in 1..=i {
+            tree.verify(&id(&j.to_string()), &[j as u8]).unwrap();
+        }
+
+        garbage.iter().for_each(|id| {
+            store.blocks.remove(&id.to_string());
+        });
+    }
+
+    assert_eq!(store.blocks.len(), 11); // 5 + 3 + 2 + 1
+
+    for i in 1..=8 {
+        tree.remove(&id(&i.to_string()));
+
+        let (root, garbage) = store.flush(&mut tree).unwrap();
+
+        garbage.iter().for_each(|id| {
+            store.blocks.remove(&id.to_string());
+        });
+
+        let tree = store.load(&root).unwrap();
+
+        for j in (i + 1)..=9 {
+            tree.verify(&id(&j.to_string()), &[j as u8]).unwrap();
+        }
+    }
+
+    assert_eq!(store.blocks.len(), 1);
+}
+
+#[test]
+fn load_corrupted() {
+    let mut store = Store::default();
+    let mut tree = tree_with_leaves(4);
+
+    let (root, _) = store.flush(&mut tree).unwrap();
+
+    let root_hash = Root {
+        hash: vec![1, 2, 3],
+        ..root.clone()
+    };
+    let err = store.load(&root_hash).unwrap_err();
+    assert!(matches!(err, IntegrityError::Corrupted));
+
+    // modify a leaf block
+    store.blocks.get_mut("1").unwrap()[20] ^= 1;
+
+    let err = store.load(&root).unwrap_err();
+    assert!(matches!(err, IntegrityError::Corrupted));
+}
+
+#[test]
+fn flush_block_size_too_small() {
+    let mut tree = Tree::<MemoryBackend>::new();
+
+    let err = tree
+        .flush::<IntegrityError, _>(99, |_| Ok(id("1")))
+        .unwrap_err();
+    assert!(matches!(err, IntegrityError::BlockSizeTooSmall));
+}
@@ -0,0 +1,279 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use nuts_backend::{Backend, Binary, IdSize};
+use std::collections::HashMap;
+use std::fmt;
+use thiserror::Error;
+
+use crate::buffer::{Buffer, BufferError, BufferMut};
+use crate::svec::SecureVec;
+
+const MAGIC: u32 = 0x6a72_6e6c; // jrnl
+
+/// Errors coming from a transaction.
+#[derive(Debug, Error)]
+pub enum JournalError {
+    /// A transaction is already active.
+    #[error("a transaction is already active")]
+    Active,
+
+    /// No transaction is active.
+    #[error("no transaction is active")]
+    NotActive,
+
+    /// Invalid journal block, could not validate magic.
+    #[error("invalid journal block")]
+    InvalidBlock,
+
+    /// Invalid block id, could not parse an id from a journal block.
+    #[error("invalid block id in journal")]
+    InvalidId,
+
+    /// The block size is too small to store any journal entry.
+    #[error("the block size is too small for a journal")]
+    BlockSizeTooSmall,
+
+    /// Error while (de-) serializing binary data.
+    #[error(transparent)]
+    Buffer(#[from] BufferError),
+}
+
+fn get_id<B: Backend, T: Buffer>(buf: &mut T) -> Result<Option<B::Id>, JournalError> {
+    let bytes = buf.get_vec::<1>()?;
+
+    if bytes.is_empty() {
+        Ok(None)
+    } else {
+        Binary::from_bytes(&bytes)
+            .map(Some)
+            .ok_or(JournalError::InvalidId)
+    }
+}
+
+fn put_id<B: Backend, T: BufferMut>(buf: &mut T, id: Option<&B::Id>) -> Result<(), JournalError> {
+    match id {
+        Some(id) => buf.put_vec::<1>(&id.as_bytes())?,
+        None => buf.put_vec::<1>(&[])?,
+    }
+
+    Ok(())
+}
+
+/// Changes collected by an active transaction.
+///
+/// Writes into blocks, which exist outside of the transaction, are kept in
+/// memory until the transaction is committed. Blocks aquired during the
+/// transaction are not referenced by anyone else, they are written
+/// immediately.
+pub struct Journal<B: Backend> {
+    /// Pending writes, the block id together with the plain block data.
+    ///
+    /// The plaintext is kept in a [`SecureVec`], thus it is wiped once the
+    /// transaction is committed or rolled back.
+    pub writes: Vec<(B::Id, SecureVec)>,
+
+    /// Blocks aquired during the transaction.
+    pub aquired: Vec<B::Id>,
+
+    /// Number of blocks at the beginning of `aquired`, which are already
+    /// recorded in the journal blocks listed in `log`.
+    pub logged: usize,
+
+    /// Journal blocks, which record the aquired blocks. The header points to
+    /// the last one, so the blocks can be released, if the transaction is
+    /// interrupted.
+    pub log: Vec<B::Id>,
+
+    /// Blocks to be released once the transaction is committed.
+    pub released: Vec<B::Id>,
+
+    positions: HashMap<Vec<u8>, usize>,
+}
+
+impl<B: Backend> Journal<B> {
+    pub fn new() -> Journal<B> {
+        Journal {
+            writes: vec![],
+            aquired: vec![],
+            logged: 0,
+            log: vec![],
+            released: vec![],
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Tests whether the block with the given `id` was aquired during the
+    /// transaction.
+    pub fn is_aquired(&self, id: &B::Id) -> bool {
+        self.positions.contains_key(&id.as_bytes())
+    }
+
+    /// Returns the aquired blocks, which are not recorded in the journal
+    /// yet.
+    pub fn unlogged(&self) -> &[B::Id] {
+        &self.aquired[self.logged..]
+    }
+
+    pub fn get(&self, id: &B::Id) -> Option<&[u8]> {
+        self.writes
+            .iter()
+            .find(|(other, _)| other == id)
+            .map(|(_, buf)| buf.as_slice())
+    }
+
+    pub fn put(&mut self, id: &B::Id, buf: SecureVec) {
+        match self.writes.iter_mut().find(|(other, _)| other == id) {
+            Some((_, pending)) => *pending = buf,
+            None => self.writes.push((id.clone(), buf)),
+        }
+    }
+
+    pub fn aquire(&mut self, id: &B::Id) {
+        self.positions.insert(id.as_bytes(), self.aquired.len());
+        self.aquired.push(id.clone());
+    }
+
+    /// Records the release of the block with the given `id`.
+    ///
+    /// Returns `true` if the block was aquired during the transaction and is
+    /// not recorded in the journal yet. Such a block is not referenced
+    /// outside of the transaction and can be released immediately. A
+    /// recorded block is released on commit, otherwise it would be released
+    /// twice, if the transaction is interrupted.
+    pub fn release(&mut self, id: &B::Id) -> bool {
+        self.writes.retain(|(other, _)| other != id);
+
+        match self.positions.get(&id.as_bytes()).copied() {
+            Some(idx) if idx >= self.logged => {
+                self.positions.remove(&id.as_bytes());
+                self.aquired.swap_remove(idx);
+
+                if let Some(other) = self.aquired.get(idx) {
+                    self.positions.insert(other.as_bytes(), idx);
+                }
+
+                true
+            }
+            _ => {
+                self.released.push(id.clone());
+                false
+            }
+        }
+    }
+}
+
+impl<B: Backend> fmt::Debug for Journal<B> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let ids = |ids: &[B::Id]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        let writes = self
+            .writes
+            .iter()
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+
+        fmt.debug_struct("Journal")
+            .field("writes", &ids(&writes))
+            .field("aquired", &ids(&self.aquired))
+            .field("logged", &self.logged)
+            .field("log", &ids(&self.log))
+            .field("released", &ids(&self.released))
+            .finish()
+    }
+}
+
+/// A journal block as stored in the container.
+///
+/// It maps target blocks to the blocks, where a copy of the new content is
+/// stored. An entry without a copy refers to a block, which is released, once
+/// the journal is completed. Journal blocks are chained, `next` points to the
+/// following block.
+///
+/// The journal of an active transaction lists only the blocks aquired by the
+/// transaction, which are released, if the transaction is interrupted. A
+/// committed transaction replaces it with a journal, which contains the
+/// copies.
+pub struct Descriptor<B: Backend> {
+    pub next: Option<B::Id>,
+    pub entries: Vec<(B::Id, Option<B::Id>)>,
+}
+
+impl<B: Backend> Descriptor<B> {
+    /// Returns the number of entries, which fit into a block of the given
+    /// size.
+    pub fn capacity(block_size: usize) -> usize {
+        let id_size = 1 + B::Id::size();
+        let overhead = 4 + 4 + id_size; // magic, number of entries, next
+
+        block_size.saturating_sub(overhead) / (2 * id_size)
+    }
+
+    pub fn get_from_buffer<T: Buffer>(buf: &mut T) -> Result<Descriptor<B>, JournalError> {
+        if buf.get_u32()? != MAGIC {
+            return Err(JournalError::InvalidBlock);
+        }
+
+        let nentries = buf.get_u32()?;
+        let next = get_id::<B, T>(buf)?;
+        let mut entries = Vec::with_capacity(nentries as usize);
+
+        for _ in 0..nentries {
+            let target = get_id::<B, T>(buf)?.ok_or(JournalError::InvalidId)?;
+            let copy = get_id::<B, T>(buf)?;
+
+            entries.push((target, copy));
+        }
+
+        Ok(Descriptor { next, entries })
+    }
+
+    pub fn put<T: BufferMut>(&self, buf: &mut T) -> Result<(), JournalError> {
+        buf.put_u32(MAGIC)?;
+        buf.put_u32(self.entries.len() as u32)?;
+        put_id::<B, T>(buf, self.next.as_ref())?;
+
+        for (target, copy) in self.entries.iter() {
+            put_id::<B, T>(buf, Some(target))?;
+            put_id::<B, T>(buf, copy.as_ref())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<B: Backend> fmt::Debug for Descriptor<B> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let entries = self
+            .entries
+            .iter()
+            .map(|(target, copy)| (target.to_string(), copy.as_ref().map(|id| id.to_string())))
+            .collect::<Vec<_>>();
+
+        fmt.debug_struct("Descriptor")
+            .field("next", &self.next.as_ref().map(|id| id.to_string()))
+            .field("entries", &entries)
+            .finish()
+    }
+}
@@ -0,0 +1,249 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_memory::MemoryBackend;
+
+use crate::journal::{Descriptor, Journal, JournalError};
+
+const DESCRIPTOR: [u8; 33] = [
+    0x6a, 0x72, 0x6e, 0x6c, // magic
+    0, 0, 0, 2, // number of entries
+    4, 0, 0, 0, 3, // next
+    4, 0, 0, 0, 1, // entry 1: target
+    4, 0, 0, 0, 10, // entry 1: copy
+    4, 0, 0, 0, 2, // entry 2: target
+    4, 0, 0, 0, 11, // entry 2: copy
+];
+
+const DESCRIPTOR_LAST: [u8; 9] = [
+    0x6a, 0x72, 0x6e, 0x6c, // magic
+    0, 0, 0, 0, // number of entries
+    0, // next
+];
+
+const DESCRIPTOR_RELEASE: [u8; 21] = [
+    0x6a, 0x72, 0x6e, 0x6c, // magic
+    0, 0, 0, 2, // number of entries
+    0, // next
+    4, 0, 0, 0, 1, // entry 1: target
+    0, // entry 1: no copy
+    4, 0, 0, 0, 2, // entry 2: target
+    0, // entry 2: no copy
+];
+
+fn id(s: &str) -> <MemoryBackend as nuts_backend::Backend>::Id {
+    s.parse().unwrap()
+}
+
+fn descriptor() -> Descriptor<MemoryBackend> {
+    Descriptor {
+        next: Some(id("3")),
+        entries: vec![(id("1"), Some(id("10"))), (id("2"), Some(id("11")))],
+    }
+}
+
+#[test]
+fn capacity() {
+    assert_eq!(Descriptor::<MemoryBackend>::capacity(0), 0);
+    assert_eq!(Descriptor::<MemoryBackend>::capacity(22), 0);
+    assert_eq!(Descriptor::<MemoryBackend>::capacity(23), 1);
+    assert_eq!(Descriptor::<MemoryBackend>::capacity(32), 1);
+    assert_eq!(Descriptor::<MemoryBackend>::capacity(33), 2);
+    assert_eq!(Descriptor::<MemoryBackend>::capacity(512), 49);
+}
+
+#[test]
+fn de_descriptor() {
+    let descriptor = Descriptor::<MemoryBackend>::get_from_buffer(&mut &DESCRIPTOR[..]).unwrap();
+
+    assert_eq!(descriptor.next, Some(id("3")));
+    assert_eq!(
+        descriptor.entries,
+        [(id("1"), Some(id("10"))), (id("2"), Some(id("11")))]
+    );
+}
+
+#[test]
+fn de_descriptor_last() {
+    let descriptor =
+        Descriptor::<MemoryBackend>::get_from_buffer(&mut &DESCRIPTOR_LAST[..]).unwrap();
+
+    assert_eq!(descriptor.next, None);
+    assert!(descriptor.entries.is_empty());
+}
+
+#[test]
+fn de_descriptor_release() {
+    let descriptor =
+        Descriptor::<MemoryBackend>::get_from_buffer(&mut &DESCRIPTOR_RELEASE[..]).unwrap();
+
+    assert_eq!(descriptor.next, None);
+    assert_eq!(descriptor.entries, [(id("1"), None), (id("2"), None)]);
+}
+
+#[test]
+fn de_descriptor_inval_magic() {
+    let mut buf = DESCRIPTOR;
+    buf[0] += 1;
+
+    let err = Descriptor::<MemoryBackend>::get_from_buffer(&mut &buf[..]).unwrap_err();
+
+    assert!(matches!(err, JournalError::InvalidBlock));
+}
+
+#[test]
+fn de_descriptor_no_target() {
+    let mut buf = DESCRIPTOR;
+    buf[13] = 0;
+
+    let err = Descriptor::<MemoryBackend>::get_from_buffer(&mut &buf[..14]).unwrap_err();
+
+    assert!(matches!(err, JournalError::InvalidId));
+}
+
+#[test]
+fn ser_descriptor() {
+    let mut buf = [b'x'; DESCRIPTOR.len()];
+
+    descriptor().put(&mut &mut buf[..]).unwrap();
+    assert_eq!(buf, DESCRIPTOR);
+}
+
+#[test]
+fn ser_descriptor_last() {
+    let mut buf = [b'x'; DESCRIPTOR_LAST.len()];
+    let descriptor = Descriptor::<MemoryBackend> {
+        next: None,
+        entries: vec![],
+    };
+
+    descriptor.put(&mut &mut buf[..]).unwrap();
+    assert_eq!(buf, DESCRIPTOR_LAST);
+}
+
+#[test]
+fn ser_descriptor_release() {
+    let mut buf = [b'x'; DESCRIPTOR_RELEASE.len()];
+    let descriptor = Descriptor::<MemoryBackend> {
+        next: None,
+        entries: vec![(id("1"), None), (id("2"), None)],
+    };
+
+    descriptor.put(&mut &mut buf[..]).unwrap();
+    assert_eq!(buf, DESCRIPTOR_RELEASE);
+}
+
+#[test]
+fn ser_descriptor_too_small() {
+    let mut buf = [b'x'; DESCRIPTOR.len() - 1];
+    let err = descriptor().put(&mut &mut buf[..]).unwrap_err();
+
+    assert!(matches!(err, JournalError::Buffer(_)));
+}
+
+#[test]
+fn journal_put_get() {
+    let mut journal = Journal::<MemoryBackend>::new();
+
+    assert!(journal.get(&id("1")).is_none());
+
+    journal.put(&id("1"), vec![1].into());
+    journal.put(&id("2"), vec![2].into());
+    assert_eq!(journal.get(&id("1")).unwrap(), [1]);
+    assert_eq!(journal.get(&id("2")).unwrap(), [2]);
+
+    journal.put(&id("1"), vec![3].into());
+    assert_eq!(journal.get(&id("1")).unwrap(), [3]);
+    assert_eq!(journal.writes.len(), 2);
+}
+
+#[test]
+fn journal_release_aquired() {
+    let mut journal = Journal::<MemoryBackend>::new();
+
+    journal.aquire(&id("1"));
+    journal.put(&id("1"), vec![1].into());
+
+    assert!(journal.release(&id("1")));
+    assert!(journal.get(&id("1")).is_none());
+    assert!(journal.aquired.is_empty());
+    assert!(journal.released.is_empty());
+}
+
+#[test]
+fn journal_release_existing() {
+    let mut journal = Journal::<MemoryBackend>::new();
+
+    journal.put(&id("1"), vec![1].into());
+
+    assert!(!journal.release(&id("1")));
+    assert!(journal.get(&id("1")).is_none());
+    assert!(journal.aquired.is_empty());
+    assert_eq!(journal.released, [id("1")]);
+}
+
+#[test]
+fn journal_aquired() {
+    let mut journal = Journal::<MemoryBackend>::new();
+
+    journal.aquire(&id("1"));
+    journal.aquire(&id("2"));
+
+    assert!(journal.is_aquired(&id("1")));
+    assert!(journal.is_aquired(&id("2")));
+    assert!(!journal.is_aquired(&id("3")));
+    assert_eq!(journal.unlogged(), [id("1"), id("2")]);
+
+    journal.logged = 2;
+    journal.aquire(&id("3"));
+    assert_eq!(journal.unlogged(), [id("3")]);
+}
+
+#[test]
+fn journal_release_aquired_swapped() {
+    let mut journal = Journal::<MemoryBackend>::new();
+
+    journal.aquire(&id("1"));
+    journal.aquire(&id("2"));
+    journal.aquire(&id("3"));
+
+    assert!(journal.release(&id("1")));
+    assert_eq!(journal.aquired, [id("3"), id("2")]);
+    assert!(!journal.is_aquired(&id("1")));
+
+    assert!(journal.release(&id("3")));
+    assert_eq!(journal.aquired, [id("2")]);
+    assert!(journal.is_aquired(&id("2")));
+}
+
+#[test]
+fn journal_release_logged() {
+    let mut journal = Journal::<MemoryBackend>::new();
+
+    journal.aquire(&id("1"));
+    journal.logged = 1;
+
+    // recorded in the journal, released on commit
+    assert!(!journal.release(&id("1")));
+    assert_eq!(journal.aquired, [id("1")]);
+    assert_eq!(journal.released, [id("1")]);
+}
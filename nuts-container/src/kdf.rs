@@ -24,24 +24,28 @@
 mod tests;
 
 use log::{debug, trace};
-use openssl::error::ErrorStack;
-use openssl::pkcs5::pbkdf2_hmac;
-use std::fmt;
 use std::num::ParseIntError;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
+use std::{cmp, fmt};
 use thiserror::Error;
 
 use crate::buffer::{Buffer, BufferError, BufferMut};
+use crate::crypto::{self, CryptoError};
 use crate::digest::Digest;
 use crate::ossl;
 use crate::svec::SecureVec;
 
+const CALIBRATE_SALT_LEN: u32 = 16;
+const CALIBRATE_MIN_ITERATIONS: u32 = 1000;
+const CALIBRATE_MIN_PROBE: Duration = Duration::from_millis(100);
+
 /// [`Kdf`] related error codes.
 #[derive(Debug, Error)]
 pub enum KdfError {
-    /// An error in the OpenSSL library occured.
+    /// An error in the crypto provider occured.
     #[error(transparent)]
-    OpenSSL(#[from] ErrorStack),
+    Crypto(#[from] CryptoError),
 }
 
 /// Supported key derivation functions.
@@ -138,7 +142,7 @@ impl Kdf {
     ///
     /// # Errors
     ///
-    /// This method will return an [`Error::OpenSSL`] error if there was an
+    /// This method will return an [`KdfError::Crypto`] error if there was an
     /// error generating the random data.
     ///
     /// # Examples
@@ -163,7 +167,6 @@ impl Kdf {
     /// ```
     ///
     /// [`salt`]: #variant.Pbkdf2.field.salt
-    /// [`Error::OpenSSL`]: ../error/enum.Error.html#variant.OpenSSL
     pub fn generate_pbkdf2(
         digest: Digest,
         iterations: u32,
@@ -179,6 +182,73 @@ impl Kdf {
         })
     }
 
+    /// Generates a PBKDF2 `Kdf` instance calibrated for the local machine.
+    ///
+    /// The PBKDF2 algorithm with the given `digest` is benchmarked and the
+    /// number of iterations is chosen, so that deriving a wrapping key takes
+    /// about `target` on this machine. At least 1000 iterations are selected.
+    /// The salt is filled with 16 bytes of random data.
+    ///
+    /// Note that the benchmark itself takes a fraction of `target` (at most
+    /// 100ms).
+    ///
+    /// # Errors
+    ///
+    /// This method will return an [`KdfError::Crypto`] error if there was an
+    /// error running PBKDF2 or generating the random data.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nuts_container::*;
+    /// use std::time::Duration;
+    ///
+    /// let kdf = Kdf::calibrate(Digest::Sha256, Duration::from_millis(10)).unwrap();
+    ///
+    /// match kdf {
+    ///     Kdf::Pbkdf2 {
+    ///         digest,
+    ///         iterations,
+    ///         salt,
+    ///     } => {
+    ///         assert_eq!(digest, Digest::Sha256);
+    ///         assert!(iterations >= 1000);
+    ///         assert_eq!(salt.len(), 16);
+    ///     }
+    ///     _ => panic!("invalid kdf"),
+    /// }
+    /// ```
+    pub fn calibrate(digest: Digest, target: Duration) -> Result<Kdf, KdfError> {
+        let probe_time = target.min(CALIBRATE_MIN_PROBE);
+        let mut iterations = CALIBRATE_MIN_ITERATIONS;
+
+        let elapsed = loop {
+            let kdf = Kdf::generate_pbkdf2(digest, iterations, CALIBRATE_SALT_LEN)?;
+
+            let start = Instant::now();
+            kdf.create_key_internal(b"calibrate")?;
+            let elapsed = start.elapsed();
+
+            trace!(
+                "calibrate (probe): {} iterations in {:?}",
+                iterations,
+                elapsed
+            );
+
+            if elapsed >= probe_time || iterations > u32::MAX / 2 {
+                break elapsed;
+            }
+
+            iterations *= 2;
+        };
+
+        let iterations = scale_iterations(iterations, elapsed, target);
+
+        debug!("calibrate: {} iterations for {:?}", iterations, target);
+
+        Kdf::generate_pbkdf2(digest, iterations, CALIBRATE_SALT_LEN)
+    }
+
     fn create_key_internal(&self, password: &[u8]) -> Result<SecureVec, KdfError> {
         match self {
             Kdf::None => Ok(vec![].into()),
@@ -195,12 +265,11 @@ impl Kdf {
                     panic!("invalid salt, cannot be empty");
                 }
 
-                let md = digest.as_openssl();
-                let mut key = vec![0; digest.size()];
+                let mut key: SecureVec = vec![0; digest.size()].into();
 
-                pbkdf2_hmac(password, salt, *iterations as usize, md, &mut key)?;
+                crypto::pbkdf2(*digest, password, salt, *iterations, &mut key)?;
 
-                Ok(key.into())
+                Ok(key)
             }
         }
     }
@@ -215,7 +284,7 @@ impl Kdf {
         // ignore min_len for None
         while !self.is_none() && key.len() < min_len {
             let xxx = self.create_key_internal(&key)?;
-            key.extend(xxx.as_ref());
+            key.extend_from_slice(&xxx);
 
             trace!("create_key (step): len = {}", key.len());
         }
@@ -225,6 +294,18 @@ impl Kdf {
         Ok(key)
     }
 
+    /// Returns the length of a key created with [`Kdf::create_key`].
+    pub(crate) fn key_len(&self, min_len: usize) -> usize {
+        match self {
+            Kdf::None => 0,
+            Kdf::Pbkdf2 { digest, .. } => {
+                let nsteps = cmp::max(min_len.div_ceil(digest.size()), 1);
+
+                nsteps * digest.size()
+            }
+        }
+    }
+
     pub(crate) fn get_from_buffer<T: Buffer>(buf: &mut T) -> Result<Kdf, BufferError> {
         let b = buf.get_u32()?;
 
@@ -295,6 +376,14 @@ impl fmt::Debug for Kdf {
     }
 }
 
+fn scale_iterations(iterations: u32, elapsed: Duration, target: Duration) -> u32 {
+    let elapsed = elapsed.as_secs_f64().max(f64::EPSILON);
+    let scaled = iterations as f64 * target.as_secs_f64() / elapsed;
+
+    // the cast saturates at u32::MAX
+    (scaled as u32).max(CALIBRATE_MIN_ITERATIONS)
+}
+
 fn parse_none(v: &[&str]) -> Result<Kdf, ParseKdfNoneError> {
     if v.is_empty() {
         Ok(Kdf::None)
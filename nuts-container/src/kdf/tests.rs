@@ -21,6 +21,7 @@
 // IN THE SOFTWARE.
 
 mod bytes;
+mod calibrate;
 mod none;
 mod pbkdf2;
 mod string;
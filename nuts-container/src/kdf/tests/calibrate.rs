@@ -0,0 +1,98 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use std::time::Duration;
+
+use crate::digest::Digest;
+use crate::kdf::{scale_iterations, Kdf};
+use crate::tests::RND;
+
+#[test]
+fn calibrate() {
+    let kdf = Kdf::calibrate(Digest::Sha1, Duration::from_millis(10)).unwrap();
+
+    match kdf {
+        Kdf::Pbkdf2 {
+            digest,
+            iterations,
+            salt,
+        } => {
+            assert_eq!(digest, Digest::Sha1);
+            assert!(iterations >= 1000);
+            assert_eq!(salt, RND[..16]);
+        }
+        _ => panic!("invalid kdf"),
+    }
+}
+
+#[test]
+fn calibrate_zero() {
+    let kdf = Kdf::calibrate(Digest::Sha256, Duration::ZERO).unwrap();
+
+    assert_eq!(kdf, Kdf::pbkdf2(Digest::Sha256, 1000, &RND[..16]));
+}
+
+#[test]
+fn scale_same() {
+    let n = scale_iterations(2000, Duration::from_millis(10), Duration::from_millis(10));
+
+    assert_eq!(n, 2000);
+}
+
+#[test]
+fn scale_up() {
+    let n = scale_iterations(2000, Duration::from_millis(10), Duration::from_secs(1));
+
+    assert_eq!(n, 200000);
+}
+
+#[test]
+fn scale_down() {
+    let n = scale_iterations(
+        100000,
+        Duration::from_millis(100),
+        Duration::from_millis(10),
+    );
+
+    assert_eq!(n, 10000);
+}
+
+#[test]
+fn scale_min() {
+    let n = scale_iterations(2000, Duration::from_secs(1), Duration::from_millis(1));
+
+    assert_eq!(n, 1000);
+}
+
+#[test]
+fn scale_max() {
+    let n = scale_iterations(u32::MAX, Duration::from_millis(1), Duration::from_secs(1));
+
+    assert_eq!(n, u32::MAX);
+}
+
+#[test]
+fn scale_zero_elapsed() {
+    let n = scale_iterations(2000, Duration::ZERO, Duration::from_secs(1));
+
+    assert_eq!(n, u32::MAX);
+}
@@ -57,3 +57,9 @@ fn create_key_min_len() {
 
     assert_eq!(*key, []);
 }
+
+#[test]
+fn key_len() {
+    assert_eq!(Kdf::None.key_len(0), 0);
+    assert_eq!(Kdf::None.key_len(10), 0);
+}
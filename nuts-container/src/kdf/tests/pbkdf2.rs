@@ -172,3 +172,17 @@ fn create_key_min_len() {
         ]
     );
 }
+
+#[test]
+fn key_len() {
+    let kdf = Kdf::Pbkdf2 {
+        digest: Digest::Sha1,
+        iterations: 1,
+        salt: vec![1, 2, 3],
+    };
+
+    for min_len in [0, 1, 20, 21, 40, 41] {
+        let key = kdf.create_key(b"123", min_len).unwrap();
+        assert_eq!(kdf.key_len(min_len), key.len());
+    }
+}
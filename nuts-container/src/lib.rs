@@ -231,45 +231,80 @@
 //!
 //!   * _master-key_: The master-key is used for encryption of the blocks of
 //!     the container.
-//!   * _top-id_: The _top-id_ points to some kind of super-block. During
+//!   * _services_: A table of the services attached to the container. Each
+//!     service is identified by its [sid](Service::sid) and has a _top-id_,
+//!     which points to some kind of super-block. During
 //!     [service-creation](Container::create_service) the super-block is
 //!     aquired (if requested by the service) and its id (the _top-id_) is
 //!     stored in the _secret_.
 //!   * _settings of the backend_: The backend of the container stores its
 //!     runtime information in the secret. It gets it back when opening the
 //!     backend again. See [`Backend::Settings`] for more information.
+//!
+//! ## Crypto provider
+//!
+//! By default the cryptographic primitives are taken from OpenSSL (feature
+//! `openssl`). With the `rust-crypto` feature the pure-Rust [RustCrypto]
+//! crates are used instead, which removes the dependency to a system
+//! library. Both providers read and write the same containers.
+//!
+//! Disable the default features to use the `rust-crypto` feature, otherwise
+//! OpenSSL is still built and takes precedence.
+//!
+//! [RustCrypto]: https://github.com/RustCrypto
 
+#[cfg(feature = "async")]
+mod asynchronous;
+mod backup;
 mod buffer;
 mod cipher;
+mod crypto;
 mod digest;
 mod error;
 mod header;
 mod info;
+mod integrity;
+mod journal;
 mod kdf;
 mod migrate;
 mod options;
 mod ossl;
 mod password;
+mod recipient;
+mod recovery;
 mod service;
+mod shared;
 mod svec;
 #[cfg(test)]
 mod tests;
+mod wrapping_key;
 
-use log::debug;
-use nuts_backend::{Backend, Create, Open, ReceiveHeader, HEADER_MAX_SIZE};
+use log::{debug, error, warn};
+use nuts_backend::{
+    Backend, Binary, Create, LockMode, Open, ReceiveHeader, SharedRead, HEADER_MAX_SIZE,
+};
 use std::{any, cmp};
 
-use crate::cipher::CipherContext;
+use crate::cipher::{CipherContext, CipherPool};
 use crate::header::Header;
+use crate::integrity::{Root, Tree};
+use crate::journal::{Descriptor, Journal};
 use crate::migrate::Migrator;
 use crate::password::PasswordStore;
+use crate::svec::SecureVec;
 
+#[cfg(feature = "async")]
+pub use asynchronous::AsyncContainer;
+pub use backup::BackupError;
 pub use buffer::BufferError;
 pub use cipher::{Cipher, CipherError};
+pub use crypto::CryptoError;
 pub use digest::Digest;
 pub use error::{ContainerResult, Error};
 pub use header::{HeaderError, LATEST_REVISION};
 pub use info::Info;
+pub use integrity::IntegrityError;
+pub use journal::JournalError;
 pub use kdf::{Kdf, KdfError};
 pub use migrate::{Migration, MigrationError};
 pub use options::{
@@ -277,7 +312,11 @@ pub use options::{
     OpenOptionsBuilder,
 };
 pub use password::PasswordError;
+pub use recipient::{KeyError, PrivateKey, PublicKey};
+pub use recovery::RecoveryKey;
 pub use service::{Service, ServiceFactory};
+pub use shared::SharedContainer;
+pub use wrapping_key::WrappingKey;
 
 macro_rules! map_err {
     ($result:expr) => {
@@ -285,6 +324,13 @@ macro_rules! map_err {
     };
 }
 
+/// A journal block: its id together with the (target, copy) pairs stored in
+/// the block.
+type JournalBlock<B> = (
+    <B as Backend>::Id,
+    Vec<(<B as Backend>::Id, Option<<B as Backend>::Id>)>,
+);
+
 /// The Container type.
 ///
 /// A `Container` acts like an encrypted block device, where you can read and
@@ -296,10 +342,16 @@ macro_rules! map_err {
 /// the container resp. write data into the container.
 #[derive(Debug)]
 pub struct Container<B: Backend> {
-    backend: B,
+    backend: Option<B>,
     store: PasswordStore,
     header: Header<'static, B>,
-    ctx: CipherContext,
+    ciphers: CipherPool,
+    sid: Option<u32>,
+    journal: Option<Journal<B>>,
+    integrity: Option<Tree<B>>,
+    recovery_key: Option<RecoveryKey>,
+    read_only: bool,
+    recovered: Option<Journal<B>>,
 }
 
 impl<B: Backend> Container<B> {
@@ -332,14 +384,32 @@ impl<B: Backend> Container<B> {
     ) -> ContainerResult<Container<B>, B> {
         let mut header_bytes = [0; HEADER_MAX_SIZE];
         let settings = backend_options.settings();
-        let header = Header::create(&options, settings)?;
+        let mut header = Header::create(&options, settings)?;
+
+        let recovery_key = if options.recovery_key {
+            let kdf = options.kdf.build().map_err(HeaderError::Kdf)?;
+            Some(header.generate_recovery_key(kdf)?)
+        } else {
+            None
+        };
+
+        if let Some(recipient) = options.recipient.as_ref() {
+            header.set_recipient(recipient)?;
+
+            if options.callback.is_none() {
+                header.generate_wrapping_key()?;
+            }
+        }
 
         let callback = options.callback.clone();
         let mut store = PasswordStore::new(callback);
 
+        header.derive_wrapping_key(&mut store)?;
         header.write(&mut header_bytes, &mut store)?;
 
-        let backend = map_err!(backend_options.build(header_bytes, options.overwrite))?;
+        let mut backend = map_err!(backend_options.build(header_bytes, options.overwrite))?;
+        map_err!(backend.lock(LockMode::Exclusive))?;
+        map_err!(backend.write_backup_header(&header_bytes))?;
 
         debug!(
             "Container created, backend: {}, header: {:?}",
@@ -347,14 +417,25 @@ impl<B: Backend> Container<B> {
             header
         );
 
-        let ctx = CipherContext::new(header.cipher());
-
-        Ok(Container {
-            backend,
+        let mut container = Container {
+            backend: Some(backend),
             store,
+            ciphers: CipherPool::new(header.cipher()),
             header,
-            ctx,
-        })
+            sid: None,
+            journal: None,
+            integrity: None,
+            recovery_key,
+            read_only: false,
+            recovered: None,
+        };
+
+        if options.rollback_protection {
+            container.integrity = Some(Tree::new());
+            container.sync_integrity()?;
+        }
+
+        Ok(container)
     }
 
     /// Creates a [service](Service) running on top of the given `container`.
@@ -362,9 +443,13 @@ impl<B: Backend> Container<B> {
     /// Basically, this method performs the following tasks:
     ///
     /// 1. The super-block is created, if [requested by the service](Service::need_top_id).
-    /// 2. Uses [`ServiceFactory::create`] to create and return the service
+    /// 2. Registers the service in the header of the container.
+    /// 3. Uses [`ServiceFactory::create`] to create and return the service
     ///    instance.
     ///
+    /// A container can host several services side by side, but each
+    /// [service identifier](Service::sid) only once.
+    ///
     /// This should be the preferred way to create a nuts-service!
     pub fn create_service<F: ServiceFactory<B>>(
         mut container: Container<B>,
@@ -375,10 +460,10 @@ impl<B: Backend> Container<B> {
             .latest_revision_or_err()
             .map_err(Error::<B>::Header)?;
 
-        // ensure that the container does not already have a service
+        // ensure that the container does not already have this service
         container
             .header
-            .accept_sid_for_create()
+            .accept_sid_for_create(F::Service::sid())
             .map_err(Error::<B>::Header)?;
 
         // aquire top-id (if requested)
@@ -388,15 +473,23 @@ impl<B: Backend> Container<B> {
             None
         };
 
-        container.update_header(|header| {
-            header.set_sid(F::Service::sid())?;
+        let result = container.update_header(|header| {
+            header.add_service(F::Service::sid(), top_id.clone())?;
+            Ok(true)
+        });
 
+        if let Err(err) = result {
+            // the service is not registered, release its super-block again
             if let Some(id) = top_id {
-                header.set_top_id(id);
+                if let Err(err) = container.release(id) {
+                    error!("failed to release the top-id: {}", err);
+                }
             }
 
-            Ok(true)
-        })?;
+            return Err(err.into());
+        }
+
+        container.sid = Some(F::Service::sid());
 
         F::create(container)
     }
@@ -416,6 +509,22 @@ impl<B: Backend> Container<B> {
     /// [password callback](OpenOptionsBuilder::with_password_callback). The
     /// returned password is then used to decrypt the secure part of the header.
     ///
+    /// If the container was interrupted while [committing](Container::commit)
+    /// a transaction, the transaction is completed now.
+    ///
+    /// If [rollback protection](CreateOptionsBuilder::with_rollback_protection)
+    /// is enabled, the integrity tree is loaded and validated against the
+    /// root stored in the header.
+    ///
+    /// A container [opened read-only](OpenOptionsBuilder::read_only) is never
+    /// modified. An interrupted transaction is completed in memory only.
+    ///
+    /// The backend is [locked](Backend::lock) as long as the container is
+    /// open: A read-only container takes a [shared](LockMode::Shared) lock,
+    /// otherwise an [exclusive](LockMode::Exclusive) lock is taken. If the
+    /// header was modified by another process in the meantime, it is read
+    /// again.
+    ///
     /// # Errors
     ///
     /// Errors are listed in the [`Error`] type.
@@ -423,13 +532,59 @@ impl<B: Backend> Container<B> {
         mut backend_options: O,
         options: OpenOptions,
     ) -> ContainerResult<Container<B>, B> {
+        let read_only = options.read_only;
+        backend_options.set_read_only(read_only);
+
         let callback = options.callback.clone();
         let mut store = PasswordStore::new(callback);
-        let migrator = Migrator::default();
+        let mut recovery_store = options
+            .recovery
+            .clone()
+            .map(|callback| PasswordStore::new(Some(callback)));
+
+        let mut read = |buf: &[u8]| {
+            let migrator = Migrator::default();
+
+            if let Some(private_key) = options.private_key.as_ref() {
+                Header::read_private_key(buf, migrator, private_key)
+            } else if let Some(wrapping_key) = options.wrapping_key.as_ref() {
+                Header::read_wrapping_key(buf, migrator, wrapping_key)
+            } else if let Some(recovery_store) = recovery_store.as_mut() {
+                Header::read_recovery(buf, migrator, recovery_store)
+            } else {
+                Header::read(buf, migrator, &mut store)
+            }
+        };
 
-        let mut header = Self::read_header(&mut backend_options, migrator, &mut store)?;
+        let (header, header_bytes, backup) = Self::read_header(&mut backend_options, &mut read)?;
         let settings = header.settings().clone();
-        let backend = map_err!(backend_options.build(settings))?;
+        let mut backend = map_err!(backend_options.build(settings))?;
+
+        let mode = if read_only {
+            LockMode::Shared
+        } else {
+            LockMode::Exclusive
+        };
+        map_err!(backend.lock(mode))?;
+
+        // Another process might have modified the header before the lock was
+        // taken, the header must be read again then
+        let (mut header, header_bytes, backup) =
+            if Self::header_changed(&mut backend, &header_bytes) {
+                debug!("header changed while locking, read it again");
+                Self::read_header(&mut backend, &mut read)?
+            } else {
+                (header, header_bytes, backup)
+            };
+
+        if backup {
+            if read_only {
+                warn!("header is not usable, using backup header");
+            } else {
+                warn!("header is not usable, restored from backup header");
+                map_err!(backend.write_header(&header_bytes))?;
+            }
+        }
 
         header.migrate()?;
 
@@ -439,20 +594,107 @@ impl<B: Backend> Container<B> {
             header
         );
 
-        let ctx = CipherContext::new(header.cipher());
+        let mut container = Container {
+            backend: Some(backend),
+            store,
+            ciphers: CipherPool::new(header.cipher()),
+            header,
+            sid: None,
+            journal: None,
+            integrity: None,
+            recovery_key: None,
+            read_only,
+            recovered: None,
+        };
 
-        Ok(Container {
-            backend,
+        container.load_integrity()?;
+        container.recover()?;
+
+        Ok(container)
+    }
+
+    /// Restores the header of a container from a backup.
+    ///
+    /// The `backup` was created with [`Container::backup_header`]. Its
+    /// checksum is validated and the header is decrypted with the password
+    /// returned by the
+    /// [password callback](OpenOptionsBuilder::with_password_callback) before
+    /// the header is written back into the backend.
+    ///
+    /// Unlike [`Container::open`] the header is not read from the backend,
+    /// thus you can restore a header, which is corrupted in the backend. On
+    /// success the opened container is returned.
+    ///
+    /// # Errors
+    ///
+    /// A backup with an invalid checksum is rejected with a
+    /// [`BackupError::InvalidChecksum`] error. Restoring the header modifies
+    /// the container, thus [read-only](OpenOptionsBuilder::read_only)
+    /// `options` are rejected with an [`Error::ReadOnly`] error. Further
+    /// errors are listed in the [`Error`] type.
+    pub fn restore_header<O: Open<B>>(
+        backend_options: O,
+        options: OpenOptions,
+        backup: &[u8],
+    ) -> ContainerResult<Container<B>, B> {
+        if options.read_only {
+            return Err(Error::ReadOnly);
+        }
+
+        let header_bytes = backup::decode(backup)?;
+
+        let callback = options.callback.clone();
+        let mut store = PasswordStore::new(callback);
+        let migrator = Migrator::default();
+
+        let mut header = if let Some(private_key) = options.private_key.as_ref() {
+            Header::<B>::read_private_key(&header_bytes, migrator, private_key)?
+        } else if let Some(wrapping_key) = options.wrapping_key.as_ref() {
+            Header::<B>::read_wrapping_key(&header_bytes, migrator, wrapping_key)?
+        } else if let Some(callback) = options.recovery.clone() {
+            let mut recovery_store = PasswordStore::new(Some(callback));
+            Header::<B>::read_recovery(&header_bytes, migrator, &mut recovery_store)?
+        } else {
+            Header::<B>::read(&header_bytes, migrator, &mut store)?
+        };
+        let settings = header.settings().clone();
+        let mut backend = map_err!(backend_options.build(settings))?;
+
+        map_err!(backend.lock(LockMode::Exclusive))?;
+        map_err!(backend.write_header(&header_bytes))?;
+        map_err!(backend.write_backup_header(&header_bytes))?;
+        header.migrate()?;
+
+        debug!(
+            "Container header restored, backend: {}, header: {:?}",
+            any::type_name::<B>(),
+            header
+        );
+
+        let mut container = Container {
+            backend: Some(backend),
             store,
+            ciphers: CipherPool::new(header.cipher()),
             header,
-            ctx,
-        })
+            sid: None,
+            journal: None,
+            integrity: None,
+            recovery_key: None,
+            read_only: false,
+            recovered: None,
+        };
+
+        container.load_integrity()?;
+        container.recover()?;
+
+        Ok(container)
     }
 
     /// Opens a [service](Service) running on top of an existing container.
     ///
-    /// Basically, this method uses [`ServiceFactory::open`] to open and return
-    /// the service instance.
+    /// Basically, this method looks for the service in the header of the
+    /// container and uses [`ServiceFactory::open`] to open and return the
+    /// service instance.
     ///
     /// This should be the preferred way to open a nuts-service!
     pub fn open_service<F: ServiceFactory<B>>(
@@ -476,17 +718,84 @@ impl<B: Backend> Container<B> {
             })?;
         }
 
+        container.sid = Some(F::Service::sid());
+
         F::open(container)
     }
 
     /// Returns the backend of this container.
     pub fn backend(&self) -> &B {
-        &self.backend
+        self.backend.as_ref().unwrap()
+    }
+
+    fn backend_mut(&mut self) -> &mut B {
+        self.backend.as_mut().unwrap()
     }
 
     /// Consumes this container, returning the inner backend.
-    pub fn into_backend(self) -> B {
-        self.backend
+    ///
+    /// A modified integrity tree is written before, see
+    /// [`Container::flush`]. Flush the container yourself, if you want to
+    /// handle a failure, here it is only logged.
+    pub fn into_backend(mut self) -> B {
+        if let Err(err) = self.sync_integrity() {
+            error!("failed to write the integrity tree: {}", err);
+        }
+
+        self.backend.take().unwrap()
+    }
+
+    /// Tests whether the container is
+    /// [opened read-only](OpenOptionsBuilder::read_only).
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Creates a backup of the header.
+    ///
+    /// The encrypted header is read from the backend and returned together
+    /// with a checksum. Keep the backup at a safe place, you can
+    /// [restore](Container::restore_header) the header from it, if the header
+    /// stored in the backend is corrupted.
+    ///
+    /// Note that the backup is protected by the password, which was active
+    /// when the backup was created.
+    ///
+    /// # Errors
+    ///
+    /// Errors are listed in the [`Error`] type.
+    pub fn backup_header(&mut self) -> ContainerResult<Vec<u8>, B> {
+        let mut header_bytes = [0; HEADER_MAX_SIZE];
+
+        map_err!(self.backend_mut().get_header_bytes(&mut header_bytes))?;
+
+        Ok(backup::encode(&header_bytes)?)
+    }
+
+    /// Returns the recovery key of the container.
+    ///
+    /// The recovery key is only available right after the container was
+    /// [created](Container::create) with
+    /// [`CreateOptionsBuilder::with_recovery_key`]. It cannot be retrieved
+    /// later.
+    pub fn recovery_key(&self) -> Option<&RecoveryKey> {
+        self.recovery_key.as_ref()
+    }
+
+    /// Returns the wrapping key of the container.
+    ///
+    /// The secret part of the header is encrypted with the wrapping key.
+    /// Pass it to [`OpenOptionsBuilder::with_wrapping_key`] to open the
+    /// container again without the password.
+    ///
+    /// Returns [`None`] if encryption is disabled. After the
+    /// [password was changed](ModifyOptionsBuilder::change_password) the key
+    /// derived from the new password is returned.
+    pub fn wrapping_key(&self) -> Option<WrappingKey> {
+        self.header
+            .wrapping_key()
+            .filter(|key| !key.is_empty())
+            .map(WrappingKey::from_bytes)
     }
 
     /// Returns information from the container.
@@ -495,27 +804,35 @@ impl<B: Backend> Container<B> {
     ///
     /// Errors are listed in the [`Error`] type.
     pub fn info(&self) -> ContainerResult<Info<B>, B> {
-        let backend = map_err!(self.backend.info())?;
+        let backend = map_err!(self.backend().info())?;
 
         Ok(Info {
             backend,
             revision: self.header.revision(),
             cipher: self.header.cipher(),
             kdf: self.header.kdf().clone(),
-            bsize_gross: self.backend.block_size(),
+            services: self.header.services(),
+            rollback_protection: self.integrity.is_some(),
+            recovery_key: self.header.has_recovery_key(),
+            recipient: self.header.has_recipient(),
+            bsize_gross: self.backend().block_size(),
             bsize_net: self.block_size(),
         })
     }
 
     /// Returns the _top-id_ of the container.
     ///
+    /// Each service has its own _top-id_. This method returns the _top-id_ of
+    /// the service, which was [created](Container::create_service) or
+    /// [opened](Container::open_service) on top of this container.
+    ///
     /// A service (running on top of the container) can use the _top-id_ as a
     /// starting point or some kind of _super-block_. The _top-id_ is stored
     /// encrypted in the header of the container. Calling this method will
     /// neither fetch nor create the _top-id_. It returns an entry, where you
     /// can decide what to do.
     pub fn top_id(&self) -> Option<&B::Id> {
-        self.header.top_id()
+        self.header.top_id(self.sid)
     }
 
     /// The (net) block size specifies the number of userdata bytes you can
@@ -527,7 +844,7 @@ impl<B: Backend> Container<B> {
     /// additionally. Such data must be substracted from the gross block size
     /// and results into the net block size.
     pub fn block_size(&self) -> u32 {
-        self.backend
+        self.backend()
             .block_size()
             .saturating_sub(self.header.cipher().tag_size())
     }
@@ -541,16 +858,22 @@ impl<B: Backend> Container<B> {
     ///
     /// Errors are listed in the [`Error`] type.
     pub fn modify(&mut self, options: ModifyOptions) -> ContainerResult<(), B> {
+        self.writable()?;
+
         let mut changed = false;
 
         if options.password.is_some() {
             self.store = PasswordStore::new(options.password.clone());
+            self.header.reset_wrapping_key();
             changed = true;
         }
 
         self.update_header(|header| {
             if let Some(kdf) = options.kdf {
-                changed |= header.set_kdf(kdf);
+                if header.set_kdf(kdf) {
+                    header.reset_wrapping_key();
+                    changed = true;
+                }
             }
 
             Ok(changed)
@@ -563,7 +886,13 @@ impl<B: Backend> Container<B> {
     /// [write](Container::write) from/to it.
     ///
     /// By default an aquired block, which is not written yet, returns an
-    /// all-zero buffer.
+    /// all-zero buffer. For an `*-hmac` cipher the backend stores an all-zero
+    /// placeholder until the block is written the first time.
+    ///
+    /// Within a [transaction](Container::begin) the aquired blocks are
+    /// recorded in the journal, whenever a journal block is filled. If the
+    /// transaction is interrupted, the recorded blocks are released the next
+    /// time the container is [opened](Container::open).
     ///
     /// Returns the [id](Backend::Id) of the block.
     ///
@@ -571,13 +900,76 @@ impl<B: Backend> Container<B> {
     ///
     /// Errors are listed in the [`Error`] type.
     pub fn aquire(&mut self) -> ContainerResult<B::Id, B> {
+        self.writable()?;
+
+        let id = self.aquire_block()?;
+
+        if let Some(journal) = self.journal.as_mut() {
+            journal.aquire(&id);
+        }
+
+        self.log_aquired()?;
+        self.save_integrity()?;
+
+        Ok(id)
+    }
+
+    fn aquire_block(&mut self) -> ContainerResult<B::Id, B> {
+        let key = self.header.key();
+        let iv = self.header.iv();
+
+        let mut ctx = self.ciphers.get();
+
+        let ctext = if self.header.cipher().mac_digest().is_some() {
+            // The MAC of an `*-hmac` cipher covers the id of the block, which
+            // is not known yet. A zero placeholder is stored instead, it reads
+            // as an empty block until the block is written.
+            ctx.inp_mut(self.backend().block_size() as usize)
+        } else {
+            ctx.copy_from_slice(self.block_size() as usize, &[]);
+            ctx.encrypt(key, iv)?
+        };
+
+        let id = map_err!(self.backend_mut().aquire(ctext))?;
+
+        if let Some(tree) = self.integrity.as_mut() {
+            tree.update(&id, integrity::hash(ctext)?);
+        }
+
+        Ok(id)
+    }
+
+    /// Aquires a new block for a node of the integrity tree.
+    ///
+    /// The node is authenticated by the hash stored in its parent, up to the
+    /// root stored in the header. Thus, the MAC of an `*-hmac` cipher does
+    /// not need to cover the id of the block, which is assigned by the
+    /// backend. See [`Container::read_node`].
+    fn aquire_node(&mut self, buf: &[u8]) -> ContainerResult<B::Id, B> {
         let key = self.header.key();
         let iv = self.header.iv();
 
-        self.ctx.copy_from_slice(self.block_size() as usize, &[]);
-        let ctext = self.ctx.encrypt(key, iv)?;
+        let mut ctx = self.ciphers.get();
+
+        ctx.copy_from_slice(self.block_size() as usize, buf);
+        let ctext = ctx.encrypt(key, iv)?;
+
+        map_err!(self.backend_mut().aquire(ctext))
+    }
+
+    /// Reads a node of the integrity tree, see [`Container::aquire_node`].
+    fn read_node(&mut self, id: &B::Id, buf: &mut [u8]) -> ContainerResult<(), B> {
+        let mut ctx = self.ciphers.get();
+
+        let ctext = ctx.inp_mut(self.backend().block_size() as usize);
+        map_err!(self.backend_mut().read(id, ctext))?;
 
-        map_err!(self.backend.aquire(ctext))
+        let ptext = ctx.decrypt(self.header.key(), self.header.iv())?;
+
+        let n = cmp::min(ptext.len(), buf.len());
+        buf[..n].copy_from_slice(&ptext[..n]);
+
+        Ok(())
     }
 
     /// Releases a block again.
@@ -586,11 +978,34 @@ impl<B: Backend> Container<B> {
     /// [written](Container::write), the [id](Backend::Id) cannot be used
     /// afterwards.
     ///
+    /// Within a [transaction](Container::begin) the block is released when
+    /// the transaction is committed. Blocks, which were aquired in the same
+    /// transaction, are released immediately.
+    ///
     /// # Errors
     ///
     /// Errors are listed in the [`Error`] type.
     pub fn release(&mut self, id: B::Id) -> ContainerResult<(), B> {
-        map_err!(self.backend.release(id))
+        self.writable()?;
+
+        let release = match self.journal.as_mut() {
+            Some(journal) => journal.release(&id),
+            None => true,
+        };
+
+        if release {
+            self.release_block(id)?;
+        }
+
+        self.save_integrity()
+    }
+
+    fn release_block(&mut self, id: B::Id) -> ContainerResult<(), B> {
+        if let Some(tree) = self.integrity.as_mut() {
+            tree.remove(&id);
+        }
+
+        map_err!(self.backend_mut().release(id))
     }
 
     /// Reads a block from the container.
@@ -608,15 +1023,144 @@ impl<B: Backend> Container<B> {
     ///
     /// # Errors
     ///
-    /// Errors are listed in the [`Error`] type.
+    /// If [rollback protection](CreateOptionsBuilder::with_rollback_protection)
+    /// is enabled and the block does not match the integrity tree, an
+    /// [`IntegrityError::StaleBlock`] error is returned.
+    ///
+    /// Further errors are listed in the [`Error`] type.
     pub fn read(&mut self, id: &B::Id, buf: &mut [u8]) -> ContainerResult<usize, B> {
-        let ctext = self.ctx.inp_mut(self.backend.block_size() as usize);
-        map_err!(self.backend.read(id, ctext))?;
+        if let Some(n) = self.read_pending(id, buf) {
+            return Ok(n);
+        }
 
+        self.read_block(id, buf)
+    }
+
+    /// Reads a block from the container through a shared reference.
+    ///
+    /// The method has the same semantic as [`Container::read`], but is only
+    /// available for backends, which are able to [read concurrently](SharedRead).
+    ///
+    /// # Errors
+    ///
+    /// Errors are listed in the [`Error`] type.
+    pub fn read_shared(&self, id: &B::Id, buf: &mut [u8]) -> ContainerResult<usize, B>
+    where
+        B: SharedRead,
+    {
+        if let Some(n) = self.read_pending(id, buf) {
+            return Ok(n);
+        }
+
+        let mut ctx = self.ciphers.get();
+
+        let ctext = ctx.inp_mut(self.backend().block_size() as usize);
+        map_err!(self.backend().read_shared(id, ctext))?;
+        self.verify_block(id, ctext)?;
+
+        self.decrypt_block(&mut ctx, id, buf)
+    }
+
+    /// Reads several blocks from the container.
+    ///
+    /// Reads the blocks with the given `ids` and returns the decrypted data
+    /// in the same order. Each entry holds the data of one block, which
+    /// cannot be larger than the [block-size](Container::block_size).
+    ///
+    /// The blocks are fetched from the backend with a single
+    /// [`Backend::read_many()`] call. Blocks, which were written in an active
+    /// [transaction](Container::begin), are taken from the transaction.
+    ///
+    /// # Errors
+    ///
+    /// If [rollback protection](CreateOptionsBuilder::with_rollback_protection)
+    /// is enabled and a block does not match the integrity tree, an
+    /// [`IntegrityError::StaleBlock`] error is returned.
+    ///
+    /// Further errors are listed in the [`Error`] type.
+    pub fn read_many(&mut self, ids: &[B::Id]) -> ContainerResult<Vec<Vec<u8>>, B> {
+        let block_size = self.block_size() as usize;
+        let mut blocks = vec![vec![0; block_size]; ids.len()];
+        let mut missing = vec![];
+
+        for (idx, (id, buf)) in ids.iter().zip(blocks.iter_mut()).enumerate() {
+            match self.read_pending(id, buf) {
+                Some(n) => buf.truncate(n),
+                None => missing.push(idx),
+            }
+        }
+
+        if missing.is_empty() {
+            return Ok(blocks);
+        }
+
+        let missing_ids: Vec<B::Id> = missing.iter().map(|idx| ids[*idx].clone()).collect();
+        let ctexts = map_err!(self.backend_mut().read_many(&missing_ids))?;
+        let ctext_size = self.backend().block_size() as usize;
+        let mut ctx = self.ciphers.get();
+
+        for (idx, mut ctext) in missing.into_iter().zip(ctexts) {
+            ctext.resize(ctext_size, 0);
+            self.verify_block(&ids[idx], &ctext)?;
+
+            ctx.copy_from_slice(ctext_size, &ctext);
+
+            let n = self.decrypt_block(&mut ctx, &ids[idx], &mut blocks[idx])?;
+            blocks[idx].truncate(n);
+        }
+
+        Ok(blocks)
+    }
+
+    fn read_pending(&self, id: &B::Id, buf: &mut [u8]) -> Option<usize> {
+        let ptext = self
+            .journal
+            .iter()
+            .chain(self.recovered.iter())
+            .find_map(|journal| journal.get(id))?;
+
+        let n = cmp::min(ptext.len(), buf.len());
+        buf[..n].copy_from_slice(&ptext[..n]);
+
+        Some(n)
+    }
+
+    fn read_block(&mut self, id: &B::Id, buf: &mut [u8]) -> ContainerResult<usize, B> {
+        let mut ctx = self.ciphers.get();
+
+        let ctext = ctx.inp_mut(self.backend().block_size() as usize);
+        map_err!(self.backend_mut().read(id, ctext))?;
+        self.verify_block(id, ctext)?;
+
+        self.decrypt_block(&mut ctx, id, buf)
+    }
+
+    fn verify_block(&self, id: &B::Id, ctext: &[u8]) -> ContainerResult<(), B> {
+        match self.integrity.as_ref() {
+            Some(tree) => Ok(tree.verify(id, ctext)?),
+            None => Ok(()),
+        }
+    }
+
+    fn decrypt_block(
+        &self,
+        ctx: &mut CipherContext,
+        id: &B::Id,
+        buf: &mut [u8],
+    ) -> ContainerResult<usize, B> {
         let key = self.header.key();
         let iv = self.header.iv();
 
-        let ptext = self.ctx.decrypt(key, iv)?;
+        // an aquired block, which was never written, see aquire_block()
+        if self.header.cipher().mac_digest().is_some() && ctx.is_zero() {
+            let n = cmp::min(self.block_size() as usize, buf.len());
+
+            buf[..n].fill(0);
+
+            return Ok(n);
+        }
+
+        let ptext = ctx.decrypt_with_ad(key, iv, &id.as_bytes())?;
 
         let n = cmp::min(ptext.len(), buf.len());
         buf[..n].copy_from_slice(&ptext[..n]);
@@ -640,43 +1184,636 @@ impl<B: Backend> Container<B> {
     ///
     /// The method returns the number of bytes actually written.
     ///
+    /// Within a [transaction](Container::begin) the data are collected in
+    /// memory and written when the transaction is committed. A block aquired
+    /// in the same transaction is written immediately, it is not referenced
+    /// outside of the transaction.
+    ///
     /// # Errors
     ///
     /// Errors are listed in the [`Error`] type.
     pub fn write(&mut self, id: &B::Id, buf: &[u8]) -> ContainerResult<usize, B> {
-        let len = self.ctx.copy_from_slice(self.block_size() as usize, buf);
+        self.writable()?;
+
+        let block_size = self.block_size() as usize;
+
+        if let Some(journal) = self.journal.as_mut().filter(|j| !j.is_aquired(id)) {
+            // Same semantic as for a direct write: pad with zeros, truncate if
+            // the buffer is larger than a block
+            let n = cmp::min(block_size, buf.len());
+            let mut ptext = SecureVec::with_capacity(block_size);
+
+            ptext.extend_from_slice(&buf[..n]);
+            ptext.resize(block_size, 0);
+            journal.put(id, ptext);
+
+            return Ok(n);
+        }
+
+        let n = self.write_block(id, buf)?;
+
+        self.save_integrity()?;
+
+        Ok(n)
+    }
+
+    /// Writes several blocks into the container.
+    ///
+    /// Each entry of `blocks` contains the id of the block and the plain data
+    /// to be written. The same rules as for [`Container::write()`] apply to
+    /// each block. The method returns the number of bytes actually written
+    /// for each block, in the same order.
+    ///
+    /// The encrypted blocks are passed to the backend with a single
+    /// [`Backend::write_many()`] call. Within a
+    /// [transaction](Container::begin) the data are collected in memory and
+    /// written when the transaction is committed, blocks aquired in the same
+    /// transaction are written immediately.
+    ///
+    /// # Errors
+    ///
+    /// Errors are listed in the [`Error`] type.
+    pub fn write_many(&mut self, blocks: &[(B::Id, &[u8])]) -> ContainerResult<Vec<usize>, B> {
+        self.writable()?;
+
+        let block_size = self.block_size() as usize;
+        let mut nbytes = vec![0; blocks.len()];
+        let mut direct = Vec::with_capacity(blocks.len());
+
+        for (idx, (id, buf)) in blocks.iter().enumerate() {
+            match self.journal.as_mut().filter(|j| !j.is_aquired(id)) {
+                Some(journal) => {
+                    let n = cmp::min(block_size, buf.len());
+                    let mut ptext = SecureVec::with_capacity(block_size);
+
+                    ptext.extend_from_slice(&buf[..n]);
+                    ptext.resize(block_size, 0);
+                    journal.put(id, ptext);
+
+                    nbytes[idx] = n;
+                }
+                None => direct.push(idx),
+            }
+        }
+
+        if direct.is_empty() {
+            return Ok(nbytes);
+        }
+
+        let mut ctexts = Vec::with_capacity(direct.len());
+        let mut ctx = self.ciphers.get();
+
+        for idx in direct.iter() {
+            let (id, buf) = &blocks[*idx];
+
+            nbytes[*idx] = ctx.copy_from_slice(block_size, buf);
+            ctexts.push(
+                ctx.encrypt_with_ad(self.header.key(), self.header.iv(), &id.as_bytes())?
+                    .to_vec(),
+            );
+        }
+
+        let ctext_blocks: Vec<(B::Id, &[u8])> = direct
+            .iter()
+            .zip(ctexts.iter())
+            .map(|(idx, ctext)| (blocks[*idx].0.clone(), ctext.as_slice()))
+            .collect();
+
+        map_err!(self.backend_mut().write_many(&ctext_blocks))?;
+
+        if let Some(tree) = self.integrity.as_mut() {
+            for (id, ctext) in ctext_blocks.iter() {
+                tree.update(id, integrity::hash(ctext)?);
+            }
+        }
+
+        self.save_integrity()?;
+
+        Ok(nbytes)
+    }
+
+    fn write_block(&mut self, id: &B::Id, buf: &[u8]) -> ContainerResult<usize, B> {
+        let mut ctx = self.ciphers.get();
+        let len = ctx.copy_from_slice(self.block_size() as usize, buf);
 
         let key = self.header.key();
         let iv = self.header.iv();
 
-        let ctext = self.ctx.encrypt(key, iv)?;
+        let ctext = ctx.encrypt_with_ad(key, iv, &id.as_bytes())?;
+
+        map_err!(self.backend_mut().write(id, ctext))?;
+
+        if let Some(tree) = self.integrity.as_mut() {
+            tree.update(id, integrity::hash(ctext)?);
+        }
+
+        Ok(len)
+    }
+
+    /// Flushes the container.
+    ///
+    /// If [rollback protection](CreateOptionsBuilder::with_rollback_protection)
+    /// is enabled, the modified integrity tree is written and its new root is
+    /// stored in the header. Outside of a transaction the tree is already
+    /// written after each modification.
+    ///
+    /// Afterwards the backend is asked to make all previous modifications
+    /// durable, see [`Backend::flush()`]. A [committed](Container::commit)
+    /// transaction is flushed automatically.
+    ///
+    /// # Errors
+    ///
+    /// Errors are listed in the [`Error`] type.
+    pub fn flush(&mut self) -> ContainerResult<(), B> {
+        self.sync_integrity()?;
+        self.flush_backend()
+    }
+
+    fn flush_backend(&mut self) -> ContainerResult<(), B> {
+        map_err!(self.backend_mut().flush())
+    }
+
+    /// Starts a new transaction.
+    ///
+    /// Until the transaction is [committed](Container::commit) or
+    /// [rolled back](Container::rollback), all [writes](Container::write) are
+    /// collected in memory and [released](Container::release) blocks are kept.
+    /// [Reading](Container::read) a block returns the data written in the
+    /// transaction. Only blocks [aquired](Container::aquire) in the
+    /// transaction are written immediately. They are recorded in the journal
+    /// and released again, if the transaction is interrupted.
+    ///
+    /// Transactions cannot be nested.
+    ///
+    /// # Errors
+    ///
+    /// If a transaction is already active, a [`JournalError::Active`] error
+    /// is returned. Transactions are only available for containers with a
+    /// header of the [latest revision](LATEST_REVISION).
+    ///
+    /// Further errors are listed in the [`Error`] type.
+    pub fn begin(&mut self) -> ContainerResult<(), B> {
+        self.writable()?;
+
+        if self.journal.is_some() {
+            return Err(JournalError::Active.into());
+        }
+
+        self.header.latest_revision_or_err()?;
+        self.journal = Some(Journal::new());
+
+        Ok(())
+    }
+
+    /// Tests whether a transaction is active.
+    pub fn in_transaction(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Commits the active transaction.
+    ///
+    /// The changes of the transaction are applied atomically: Either all
+    /// changes are written into the container, or none of them.
+    ///
+    /// First, a copy of every modified block is written into a journal, which
+    /// is stored in blocks of the container. The journal is registered in the
+    /// header, which marks the transaction as committed. Next the modified
+    /// blocks are written to their final destination and finally the journal
+    /// is removed again. If this sequence is interrupted after the header was
+    /// updated, the transaction is completed the next time the container is
+    /// [opened](Container::open).
+    ///
+    /// The transaction is closed, even if the commit fails.
+    ///
+    /// # Errors
+    ///
+    /// If no transaction is active, a [`JournalError::NotActive`] error is
+    /// returned.
+    ///
+    /// Further errors are listed in the [`Error`] type.
+    pub fn commit(&mut self) -> ContainerResult<(), B> {
+        let journal = self.journal.take().ok_or(JournalError::NotActive)?;
+
+        debug!("commit: {:?}", journal);
+
+        // Released once the commit is completed, the blocks recording the
+        // aquired blocks are not needed anymore.
+        let released = journal
+            .released
+            .iter()
+            .chain(journal.log.iter())
+            .map(|id| (id.clone(), None));
+
+        if !journal.writes.is_empty() {
+            let mut entries = Vec::with_capacity(journal.writes.len());
+
+            for (id, buf) in journal.writes.iter() {
+                let copy = self.aquire_block()?;
+
+                self.write_block(&copy, buf)?;
+                entries.push((id.clone(), Some(copy)));
+            }
+
+            entries.extend(released);
+
+            let first = self.write_journal(entries, None)?.remove(0);
+            self.flush_backend()?;
+
+            // The transaction is committed once the header points to the
+            // journal. The header also refers to the integrity tree, where
+            // the copies are registered, thus they are readable, if the
+            // commit is completed on open.
+            self.update_header(|header| header.set_journal(Some(first)))?;
+            self.flush_backend()?;
+
+            for (id, buf) in journal.writes.iter() {
+                self.write_block(id, buf)?;
+            }
+
+            self.flush_backend()?;
+            self.finish_journal()?;
+        } else {
+            if !journal.log.is_empty() {
+                self.update_header(|header| header.set_journal(None))?;
+            }
 
-        map_err!(self.backend.write(id, ctext)).map(|_| len)
+            for (id, _) in released {
+                self.release_block(id)?;
+            }
+        }
+
+        self.flush()
     }
 
-    fn read_header<H: ReceiveHeader<B>>(
+    /// Rolls back the active transaction.
+    ///
+    /// All changes of the transaction are discarded. Blocks aquired during
+    /// the transaction are released again.
+    ///
+    /// # Errors
+    ///
+    /// If no transaction is active, a [`JournalError::NotActive`] error is
+    /// returned.
+    ///
+    /// Further errors are listed in the [`Error`] type.
+    pub fn rollback(&mut self) -> ContainerResult<(), B> {
+        let journal = self.journal.take().ok_or(JournalError::NotActive)?;
+
+        debug!("rollback: {:?}", journal);
+
+        // A failure from here on leaves only some unused blocks behind
+        if !journal.log.is_empty() {
+            self.update_header(|header| header.set_journal(None))?;
+        }
+
+        for id in journal.aquired.into_iter().chain(journal.log) {
+            self.release_block(id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `entries` into new journal blocks.
+    ///
+    /// The last block is chained to `next`. Returns the ids of the blocks,
+    /// the first one is the head of the journal.
+    fn write_journal(
+        &mut self,
+        entries: Vec<(B::Id, Option<B::Id>)>,
+        next: Option<B::Id>,
+    ) -> ContainerResult<Vec<B::Id>, B> {
+        let capacity = Descriptor::<B>::capacity(self.block_size() as usize);
+
+        if capacity == 0 {
+            return Err(JournalError::BlockSizeTooSmall.into());
+        }
+
+        let chunks = entries
+            .chunks(capacity)
+            .map(|chunk| chunk.to_vec())
+            .collect::<Vec<_>>();
+        let ids = chunks
+            .iter()
+            .map(|_| self.aquire_block())
+            .collect::<ContainerResult<Vec<_>, B>>()?;
+        let mut buf = vec![0; self.block_size() as usize];
+
+        for (idx, entries) in chunks.into_iter().enumerate() {
+            let descriptor = Descriptor::<B> {
+                next: ids.get(idx + 1).cloned().or_else(|| next.clone()),
+                entries,
+            };
+
+            buf.iter_mut().for_each(|n| *n = 0);
+            descriptor.put(&mut buf.as_mut_slice())?;
+
+            self.write_block(&ids[idx], &buf)?;
+        }
+
+        Ok(ids)
+    }
+
+    /// Records the aquired blocks of the active transaction in the journal,
+    /// once they fill a journal block.
+    ///
+    /// The new journal block is chained to the previous one and the header
+    /// points to it. If the transaction is interrupted, at most the blocks,
+    /// which did not fill a journal block yet, are left behind.
+    fn log_aquired(&mut self) -> ContainerResult<(), B> {
+        let capacity = Descriptor::<B>::capacity(self.block_size() as usize);
+
+        let (entries, next) = match self.journal.as_ref() {
+            Some(journal) if journal.unlogged().len() >= cmp::max(capacity, 1) => {
+                let entries = journal
+                    .unlogged()
+                    .iter()
+                    .map(|id| (id.clone(), None))
+                    .collect();
+
+                (entries, journal.log.first().cloned())
+            }
+            _ => return Ok(()),
+        };
+
+        let ids = self.write_journal(entries, next)?;
+        let head = ids[0].clone();
+
+        self.update_header(|header| header.set_journal(Some(head)))?;
+
+        if let Some(journal) = self.journal.as_mut() {
+            journal.logged = journal.aquired.len();
+            journal.log.splice(0..0, ids);
+        }
+
+        Ok(())
+    }
+
+    fn read_journal(&mut self) -> ContainerResult<Vec<JournalBlock<B>>, B> {
+        let mut next = self.header.journal().cloned();
+        let mut buf = vec![0; self.block_size() as usize];
+        let mut descriptors = vec![];
+
+        while let Some(id) = next {
+            self.read_block(&id, &mut buf)?;
+
+            let descriptor = Descriptor::<B>::get_from_buffer(&mut buf.as_slice())?;
+
+            next = descriptor.next;
+            descriptors.push((id, descriptor.entries));
+        }
+
+        Ok(descriptors)
+    }
+
+    /// Removes the journal from the header and releases all its blocks.
+    fn finish_journal(&mut self) -> ContainerResult<(), B> {
+        let descriptors = self.read_journal()?;
+
+        self.update_header(|header| header.set_journal(None))?;
+
+        // A failure from here on leaves only some unused blocks behind
+        for (id, entries) in descriptors {
+            for (target, copy) in entries {
+                self.release_block(copy.unwrap_or(target))?;
+            }
+
+            self.release_block(id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Completes a transaction, which was interrupted during commit.
+    ///
+    /// If the transaction was interrupted before it was committed, the
+    /// journal only lists the blocks aquired by the transaction, which are
+    /// released now.
+    ///
+    /// A read-only container keeps the blocks of the transaction in memory.
+    fn recover(&mut self) -> ContainerResult<(), B> {
+        if self.header.journal().is_none() {
+            return Ok(());
+        }
+
+        let descriptors = self.read_journal()?;
+        let mut buf = SecureVec::with_capacity(self.block_size() as usize);
+
+        buf.resize(self.block_size() as usize, 0);
+
+        debug!("recover: {} journal block(s)", descriptors.len());
+
+        if self.read_only {
+            let mut journal = Journal::new();
+
+            for (_, entries) in descriptors.iter() {
+                for (target, copy) in entries.iter() {
+                    if let Some(copy) = copy {
+                        self.read_block(copy, &mut buf)?;
+                        journal.put(target, buf.clone());
+                    }
+                }
+            }
+
+            self.recovered = Some(journal);
+
+            return Ok(());
+        }
+
+        for (_, entries) in descriptors.iter() {
+            for (target, copy) in entries.iter() {
+                if let Some(copy) = copy {
+                    self.read_block(copy, &mut buf)?;
+                    self.write_block(target, &buf)?;
+                }
+            }
+        }
+
+        self.finish_journal()?;
+        self.flush()
+    }
+
+    /// Loads the integrity tree, if rollback protection is enabled.
+    fn load_integrity(&mut self) -> ContainerResult<(), B> {
+        if let Some(root) = self.header.integrity().cloned() {
+            let block_size = self.block_size() as usize;
+            let tree = Tree::load(&root, block_size, |id, buf| self.read_node(id, buf))?;
+
+            debug!("integrity tree loaded: {:?}", tree);
+
+            self.integrity = Some(tree);
+        }
+
+        Ok(())
+    }
+
+    /// Writes the modified blocks of the integrity tree and stores the new
+    /// root in the header, see [`Container::update_header`].
+    fn sync_integrity(&mut self) -> ContainerResult<(), B> {
+        self.update_header(|_| Ok(false))
+    }
+
+    /// Saves the integrity tree after a modification.
+    ///
+    /// Within a transaction the tree is saved, when the transaction is
+    /// committed.
+    fn save_integrity(&mut self) -> ContainerResult<(), B> {
+        if self.in_transaction() {
+            Ok(())
+        } else {
+            self.sync_integrity()
+        }
+    }
+
+    /// Repairs the integrity tree.
+    ///
+    /// If the container crashed after a block was written, but before the
+    /// integrity tree was saved, the block is reported as
+    /// [stale](IntegrityError::StaleBlock). The method accepts the current
+    /// content of each block in the tree and saves the repaired tree.
+    ///
+    /// Note that a block, which was rolled back on purpose, is accepted as
+    /// well! Repair the tree only after an interrupted write. Without
+    /// [rollback protection](CreateOptionsBuilder::with_rollback_protection)
+    /// nothing is done.
+    ///
+    /// Returns the ids of the repaired blocks.
+    ///
+    /// # Errors
+    ///
+    /// If the container has an active [transaction](Container::begin), a
+    /// [`JournalError::Active`] error is returned.
+    ///
+    /// Further errors are listed in the [`Error`] type.
+    pub fn repair_integrity(&mut self) -> ContainerResult<Vec<B::Id>, B> {
+        self.writable()?;
+
+        if self.in_transaction() {
+            return Err(JournalError::Active.into());
+        }
+
+        let ids = match self.integrity.as_ref() {
+            Some(tree) => tree.ids().cloned().collect::<Vec<_>>(),
+            None => return Ok(vec![]),
+        };
+
+        let mut ctx = self.ciphers.get();
+        let mut repaired = vec![];
+
+        for id in ids {
+            let ctext = ctx.inp_mut(self.backend().block_size() as usize);
+            map_err!(self.backend_mut().read(&id, ctext))?;
+
+            if let Some(tree) = self.integrity.as_mut() {
+                if tree.verify(&id, ctext).is_err() {
+                    warn!("repairing stale block {}", id);
+
+                    tree.update(&id, integrity::hash(ctext)?);
+                    repaired.push(id);
+                }
+            }
+        }
+
+        self.flush()?;
+
+        Ok(repaired)
+    }
+
+    /// Writes the modified blocks of the integrity tree.
+    ///
+    /// Returns the new root of the tree together with the blocks, which were
+    /// replaced. If rollback protection is disabled or the tree is not
+    /// modified, [`None`] is returned.
+    #[allow(clippy::type_complexity)]
+    fn write_integrity(&mut self) -> ContainerResult<Option<(Root<B>, Vec<B::Id>)>, B> {
+        let mut tree = match self.integrity.take() {
+            Some(tree) => tree,
+            None => return Ok(None),
+        };
+
+        let block_size = self.block_size() as usize;
+        let result = tree.flush(block_size, |buf| self.aquire_node(buf));
+
+        self.integrity = Some(tree);
+
+        result
+    }
+
+    /// Reads the header from `reader`.
+    ///
+    /// Returns the header together with the bytes it was read from. If the
+    /// header is not usable, the backup header is read. In this case the
+    /// returned flag is set, so the caller can repair the header with the
+    /// bytes of the backup header.
+    fn read_header<H, F>(
         reader: &mut H,
-        migrator: Migrator<'static>,
-        store: &mut PasswordStore,
-    ) -> ContainerResult<Header<'static, B>, B> {
+        mut read: F,
+    ) -> ContainerResult<(Header<'static, B>, [u8; HEADER_MAX_SIZE], bool), B>
+    where
+        H: ReceiveHeader<B>,
+        F: FnMut(&[u8]) -> Result<Header<'static, B>, HeaderError>,
+    {
         let mut buf = [0; HEADER_MAX_SIZE];
 
-        match reader.get_header_bytes(&mut buf) {
+        let err = match reader.get_header_bytes(&mut buf) {
             Ok(_) => {
                 debug!("got {} header bytes", buf.len());
-                Ok(Header::read(&buf, migrator, store)?)
+
+                match read(&buf) {
+                    Ok(header) => return Ok((header, buf, false)),
+                    Err(err) => Error::Header(err),
+                }
             }
-            Err(cause) => Err(Error::Backend(cause)),
+            Err(cause) => Error::Backend(cause),
+        };
+
+        match reader.get_backup_header_bytes(&mut buf) {
+            Ok(true) => {
+                debug!("header not usable, trying backup header: {}", err);
+
+                match read(&buf) {
+                    Ok(header) => Ok((header, buf, true)),
+                    Err(_) => Err(err),
+                }
+            }
+            Ok(false) => Err(err),
+            Err(_) => Err(err),
+        }
+    }
+
+    /// Tests whether the header stored in the backend differs from
+    /// `header_bytes`.
+    fn header_changed(backend: &mut B, header_bytes: &[u8; HEADER_MAX_SIZE]) -> bool {
+        let mut buf = [0; HEADER_MAX_SIZE];
+
+        match backend.get_header_bytes(&mut buf) {
+            Ok(()) => buf != *header_bytes,
+            Err(_) => true,
         }
     }
 
+    /// Updates the header with `f` and writes it, if it was changed.
+    ///
+    /// Modified blocks of the integrity tree are written before, the header
+    /// then refers to the new root of the tree. Blocks of the tree, which
+    /// were replaced, are released afterwards. A failure from there on leaves
+    /// only some unused blocks behind.
     fn update_header<F: FnOnce(&mut Header<B>) -> Result<bool, HeaderError>>(
         &mut self,
         f: F,
     ) -> ContainerResult<(), B> {
         debug!("header before update: {:?}", self.header);
 
-        let changed = f(&mut self.header)?;
+        let mut changed = f(&mut self.header)?;
+        let garbage = match self.write_integrity()? {
+            Some((root, garbage)) => {
+                // the header must not refer to blocks, which are not durable
+                self.flush_backend()?;
+                changed |= self.header.set_integrity(Some(root))?;
+
+                garbage
+            }
+            None => vec![],
+        };
 
         debug!(
             "header after update: {:?}, changed: {}",
@@ -684,19 +1821,51 @@ impl<B: Backend> Container<B> {
         );
 
         if changed {
+            self.writable()?;
+
             let mut header_bytes = [0; HEADER_MAX_SIZE];
 
+            // the key derivation function runs only once
+            self.header.derive_wrapping_key(&mut self.store)?;
             self.header.write(&mut header_bytes, &mut self.store)?;
-            map_err!(self.backend.write_header(&header_bytes))?;
+            map_err!(self.backend_mut().write_header(&header_bytes))?;
+            map_err!(self.backend_mut().write_backup_header(&header_bytes))?;
+        }
+
+        for id in garbage {
+            map_err!(self.backend_mut().release(id))?;
         }
 
         Ok(())
     }
 
+    fn writable(&self) -> ContainerResult<(), B> {
+        if self.read_only {
+            Err(Error::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
     /// Deletes the entire container and all traces.
     ///
     /// The method must not fail!
-    pub fn delete(self) {
-        self.backend.delete()
+    pub fn delete(mut self) {
+        if let Some(backend) = self.backend.take() {
+            backend.delete();
+        }
+    }
+}
+
+impl<B: Backend> Drop for Container<B> {
+    fn drop(&mut self) {
+        if self.backend.is_none() {
+            return;
+        }
+
+        // last resort, the container is usually flushed before
+        if let Err(err) = self.sync_integrity() {
+            error!("failed to write the integrity tree: {}", err);
+        }
     }
 }
@@ -49,10 +49,10 @@ pub trait Migration {
 }
 
 #[derive(Default)]
-pub struct Migrator<'a>(Option<Box<dyn Migration + 'a>>);
+pub struct Migrator<'a>(Option<Box<dyn Migration + Send + Sync + 'a>>);
 
 impl<'a> Migrator<'a> {
-    pub fn with_migration<M: 'a + Migration>(mut self, migration: M) -> Self {
+    pub fn with_migration<M: 'a + Migration + Send + Sync>(mut self, migration: M) -> Self {
         self.0 = Some(Box::new(migration));
         self
     }
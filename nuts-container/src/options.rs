@@ -21,13 +21,15 @@
 // IN THE SOFTWARE.
 
 use nuts_backend::Backend;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::cipher::Cipher;
 use crate::digest::Digest;
 use crate::error::ContainerResult;
 use crate::kdf::{Kdf, KdfError};
 use crate::password::CallbackFn;
+use crate::recipient::{PrivateKey, PublicKey};
+use crate::wrapping_key::WrappingKey;
 #[cfg(doc)]
 use crate::{error::Error, Container};
 
@@ -53,10 +55,13 @@ impl KdfBuilder {
 /// Use the [`CreateOptionsBuilder`] utility to create a `CreateOptions`
 /// instance.
 pub struct CreateOptions {
-    pub(crate) callback: Option<Rc<CallbackFn>>,
+    pub(crate) callback: Option<Arc<CallbackFn>>,
     pub(crate) cipher: Cipher,
     pub(crate) kdf: KdfBuilder,
     pub(crate) overwrite: bool,
+    pub(crate) rollback_protection: bool,
+    pub(crate) recovery_key: bool,
+    pub(crate) recipient: Option<PublicKey>,
 }
 
 /// Utility used to create a [`CreateOptions`] instance.
@@ -78,6 +83,9 @@ impl CreateOptionsBuilder {
             cipher,
             kdf,
             overwrite: false,
+            rollback_protection: false,
+            recovery_key: false,
+            recipient: None,
         })
     }
 
@@ -96,11 +104,11 @@ impl CreateOptionsBuilder {
     /// returned.
     ///
     /// [`Error::NoPassword`]: enum.Error.html#variant.NoPassword
-    pub fn with_password_callback<Cb: Fn() -> Result<Vec<u8>, String> + 'static>(
+    pub fn with_password_callback<Cb: Fn() -> Result<Vec<u8>, String> + Send + Sync + 'static>(
         mut self,
         callback: Cb,
     ) -> Self {
-        self.0.callback = Some(Rc::new(callback));
+        self.0.callback = Some(Arc::new(callback));
         self
     }
 
@@ -126,6 +134,72 @@ impl CreateOptionsBuilder {
         self
     }
 
+    /// Enables the rollback protection for the container.
+    ///
+    /// With rollback protection the container maintains a hash tree over all
+    /// blocks and their version counters. The root of the tree is stored in
+    /// the encrypted header. [Reading](Container::read) a block, which was
+    /// rolled back to an older version, results into an
+    /// [`IntegrityError::StaleBlock`] error.
+    ///
+    /// A leaf of the tree pins the hash of the current ciphertext of a block,
+    /// thus any older version of a single block is detected, unless it has
+    /// the very same content. A rollback of the whole container, including
+    /// its header, cannot be detected.
+    ///
+    /// The tree and the header are written after each modification. Within
+    /// a [transaction](Container::begin) they are written once, when the
+    /// transaction is [committed](Container::commit).
+    ///
+    /// If the container crashes after a block was written, but before the
+    /// tree was written, the block is reported as stale. Such a container is
+    /// fixed with [`Container::repair_integrity`], which accepts the current
+    /// content of all blocks. Rollback protection is disabled by default.
+    ///
+    /// [`IntegrityError::StaleBlock`]: crate::IntegrityError::StaleBlock
+    pub fn with_rollback_protection(mut self, enabled: bool) -> Self {
+        self.0.rollback_protection = enabled;
+        self
+    }
+
+    /// Generates a recovery key for the container.
+    ///
+    /// The recovery key is an alternative secret, which unlocks the container
+    /// if the password is lost. It is generated with high entropy and
+    /// available with [`Container::recovery_key`] right after the container
+    /// was created. It is not stored in plain anywhere, so make sure to keep
+    /// it at a safe place.
+    ///
+    /// Open the container with
+    /// [`OpenOptionsBuilder::with_recovery_key_callback`] to unlock it with
+    /// the recovery key.
+    ///
+    /// If the cipher is set to [`Cipher::None`], then the setting is
+    /// discarded; there is no password, which can be lost.
+    pub fn with_recovery_key(mut self, enabled: bool) -> Self {
+        self.0.recovery_key = enabled && self.0.cipher != Cipher::None;
+        self
+    }
+
+    /// Assigns a recipient to the container.
+    ///
+    /// The container can be unlocked with the [`PrivateKey`] matching the
+    /// `recipient`, see [`OpenOptionsBuilder::with_private_key`]. The private
+    /// key is never needed to create the container.
+    ///
+    /// If no [password callback](Self::with_password_callback) is assigned,
+    /// the container can only be unlocked with the private key.
+    ///
+    /// If the cipher is set to [`Cipher::None`], then the setting is
+    /// discarded; there is nothing to unlock.
+    pub fn with_recipient(mut self, recipient: PublicKey) -> Self {
+        if self.0.cipher != Cipher::None {
+            self.0.recipient = Some(recipient);
+        }
+
+        self
+    }
+
     /// Creates the [`CreateOptions`] instance.
     ///
     /// Before the [`CreateOptions`] instance is created all options passed to
@@ -143,7 +217,11 @@ impl CreateOptionsBuilder {
 ///
 /// Use the [`OpenOptionsBuilder`] utility to create a `OpenOptions` instance.
 pub struct OpenOptions {
-    pub(crate) callback: Option<Rc<CallbackFn>>,
+    pub(crate) callback: Option<Arc<CallbackFn>>,
+    pub(crate) recovery: Option<Arc<CallbackFn>>,
+    pub(crate) private_key: Option<PrivateKey>,
+    pub(crate) wrapping_key: Option<WrappingKey>,
+    pub(crate) read_only: bool,
 }
 
 /// Utility used to create a [`OpenOptions`] instance.
@@ -152,7 +230,13 @@ pub struct OpenOptionsBuilder(OpenOptions);
 impl OpenOptionsBuilder {
     /// Creates a builder instance.
     pub fn new() -> Self {
-        OpenOptionsBuilder(OpenOptions { callback: None })
+        OpenOptionsBuilder(OpenOptions {
+            callback: None,
+            recovery: None,
+            private_key: None,
+            wrapping_key: None,
+            read_only: false,
+        })
     }
 
     /// Assigns a password callback to the container.
@@ -170,11 +254,70 @@ impl OpenOptionsBuilder {
     /// returned.
     ///
     /// [`Error::NoPassword`]: enum.Error.html#variant.NoPassword
-    pub fn with_password_callback<Cb: Fn() -> Result<Vec<u8>, String> + 'static>(
+    pub fn with_password_callback<Cb: Fn() -> Result<Vec<u8>, String> + Send + Sync + 'static>(
         mut self,
         callback: Cb,
     ) -> Self {
-        self.0.callback = Some(Rc::new(callback));
+        self.0.callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Assigns a recovery key callback to the container.
+    ///
+    /// If assigned, the header of the container is unlocked with the
+    /// [recovery key](CreateOptionsBuilder::with_recovery_key) returned by
+    /// the callback instead of the password. Use it to
+    /// [change the password](ModifyOptionsBuilder::change_password) of a
+    /// container, whose password is lost.
+    ///
+    /// On success the callback returns the recovery key (represented as an
+    /// [`Vec<u8>`](`Vec`)) wrapped into an [`Ok`](`Result::Ok`). On any
+    /// failure an [`Err`](`Result::Err`) with an error message must be
+    /// returned.
+    pub fn with_recovery_key_callback<
+        Cb: Fn() -> Result<Vec<u8>, String> + Send + Sync + 'static,
+    >(
+        mut self,
+        callback: Cb,
+    ) -> Self {
+        self.0.recovery = Some(Arc::new(callback));
+        self
+    }
+
+    /// Assigns the private key of a recipient to the container.
+    ///
+    /// If assigned, the header of the container is unlocked with the
+    /// `private_key` instead of the password. The container must be
+    /// [created](CreateOptionsBuilder::with_recipient) for the matching
+    /// public key.
+    pub fn with_private_key(mut self, private_key: PrivateKey) -> Self {
+        self.0.private_key = Some(private_key);
+        self
+    }
+
+    /// Assigns the wrapping key of the container.
+    ///
+    /// If assigned, the header of the container is unlocked with the
+    /// `wrapping_key` instead of the password. The wrapping key is taken from
+    /// a container, which was opened before, see [`Container::wrapping_key`].
+    pub fn with_wrapping_key(mut self, wrapping_key: WrappingKey) -> Self {
+        self.0.wrapping_key = Some(wrapping_key);
+        self
+    }
+
+    /// Opens the container read-only.
+    ///
+    /// A read-only container is never modified: [`Container::write`],
+    /// [`Container::aquire`], [`Container::release`], [`Container::modify`]
+    /// and transactions are rejected with an [`Error::ReadOnly`] error. The
+    /// header is not repaired from the backup header and an interrupted
+    /// transaction is completed in memory only. The flag is passed to the
+    /// backend with [`Open::set_read_only`], so it can open its resources
+    /// read-only.
+    ///
+    /// [`Open::set_read_only`]: nuts_backend::Open::set_read_only
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.0.read_only = read_only;
         self
     }
 
@@ -203,7 +346,7 @@ impl Default for OpenOptionsBuilder {
 /// instance.
 pub struct ModifyOptions {
     pub(crate) kdf: Option<Kdf>,
-    pub(crate) password: Option<Rc<CallbackFn>>,
+    pub(crate) password: Option<Arc<CallbackFn>>,
 }
 
 /// Utility used to create a [`ModifyOptions`] instance.
@@ -242,11 +385,11 @@ impl ModifyOptionsBuilder {
     /// [`Vec<u8>`](`Vec`)) wrapped into an [`Ok`](`Result::Ok`). On any
     /// failure an [`Err`](`Result::Err`) with an error message must be
     /// returned.
-    pub fn change_password<Cb: Fn() -> Result<Vec<u8>, String> + 'static>(
+    pub fn change_password<Cb: Fn() -> Result<Vec<u8>, String> + Send + Sync + 'static>(
         mut self,
         callback: Cb,
     ) -> Self {
-        self.0.password = Some(Rc::new(callback));
+        self.0.password = Some(Arc::new(callback));
         self
     }
 
@@ -20,7 +20,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
-use openssl::error::ErrorStack;
+use crate::crypto::CryptoError;
 
 #[cfg(test)]
 pub use test::rand_bytes;
@@ -28,7 +28,7 @@ pub use test::rand_bytes;
 #[cfg(not(test))]
 pub use production::rand_bytes;
 
-pub fn rand_u32() -> Result<u32, ErrorStack> {
+pub fn rand_u32() -> Result<u32, CryptoError> {
     let mut bytes = [0; 4];
 
     rand_bytes(&mut bytes)?;
@@ -38,20 +38,19 @@ pub fn rand_u32() -> Result<u32, ErrorStack> {
 
 #[cfg(not(test))]
 mod production {
-    use openssl::error::ErrorStack;
+    use crate::crypto::{self, CryptoError};
 
-    pub fn rand_bytes(buf: &mut [u8]) -> Result<(), ErrorStack> {
-        openssl::rand::rand_bytes(buf)
+    pub fn rand_bytes(buf: &mut [u8]) -> Result<(), CryptoError> {
+        crypto::rand_bytes(buf)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use openssl::error::ErrorStack;
-
+    use crate::crypto::CryptoError;
     use crate::tests::RND;
 
-    pub fn rand_bytes(buf: &mut [u8]) -> Result<(), ErrorStack> {
+    pub fn rand_bytes(buf: &mut [u8]) -> Result<(), CryptoError> {
         assert!(buf.len() <= RND.len());
         buf.clone_from_slice(&RND[..buf.len()]);
         Ok(())
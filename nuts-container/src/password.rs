@@ -24,7 +24,7 @@
 mod tests;
 
 use std::fmt;
-use std::rc::Rc;
+use std::sync::Arc;
 use thiserror::Error;
 
 use crate::svec::SecureVec;
@@ -43,15 +43,15 @@ pub enum PasswordError {
     PasswordCallback(String),
 }
 
-pub type CallbackFn = dyn Fn() -> Result<Vec<u8>, String>;
+pub type CallbackFn = dyn Fn() -> Result<Vec<u8>, String> + Send + Sync;
 
 pub struct PasswordStore {
-    callback: Option<Rc<CallbackFn>>,
+    callback: Option<Arc<CallbackFn>>,
     value: Option<SecureVec>,
 }
 
 impl PasswordStore {
-    pub fn new(callback: Option<Rc<CallbackFn>>) -> PasswordStore {
+    pub fn new(callback: Option<Arc<CallbackFn>>) -> PasswordStore {
         PasswordStore {
             callback,
             value: None,
@@ -0,0 +1,278 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+use thiserror::Error;
+
+use crate::crypto::{self, CryptoError};
+use crate::digest::Digest;
+use crate::svec::SecureVec;
+
+const DIGEST: Digest = Digest::Sha512;
+const KEY_LEN: usize = 32;
+
+// DER prefixes of X25519 keys (RFC 8410), followed by the raw key.
+const PRIVATE_KEY_DER: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x6e, 0x04, 0x22, 0x04, 0x20,
+];
+const PUBLIC_KEY_DER: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x6e, 0x03, 0x21, 0x00,
+];
+const PRIVATE_KEY_LABEL: &str = "PRIVATE KEY";
+const PUBLIC_KEY_LABEL: &str = "PUBLIC KEY";
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const PEM_LINE_LEN: usize = 64;
+
+/// Errors related to public and private keys.
+#[derive(Debug, Error)]
+pub enum KeyError {
+    /// The key is not a (PEM encoded) X25519 key.
+    #[error("not an X25519 key")]
+    InvalidKey,
+
+    /// An error in the crypto provider occured.
+    #[error(transparent)]
+    Crypto(#[from] CryptoError),
+}
+
+/// The public key of a recipient.
+///
+/// A container [created](crate::CreateOptionsBuilder::with_recipient) for a
+/// recipient can be opened with the matching [`PrivateKey`]. Only X25519
+/// keys are supported.
+#[derive(Clone)]
+pub struct PublicKey(Vec<u8>);
+
+impl PublicKey {
+    /// Reads a public key from a PEM encoded buffer.
+    ///
+    /// Use `openssl pkey -in <PRIVATE-KEY> -pubout` to create such a
+    /// buffer.
+    pub fn from_pem(pem: &[u8]) -> Result<PublicKey, KeyError> {
+        let der = pem_decode(pem, PUBLIC_KEY_LABEL)?;
+
+        PublicKey::from_bytes(strip_der_prefix(&der, &PUBLIC_KEY_DER)?)
+    }
+
+    /// Encodes the public key as PEM.
+    pub fn to_pem(&self) -> Result<Vec<u8>, KeyError> {
+        let mut der = PUBLIC_KEY_DER.to_vec();
+        der.extend_from_slice(&self.0);
+
+        Ok(pem_encode(&der, PUBLIC_KEY_LABEL))
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<PublicKey, KeyError> {
+        if bytes.len() == KEY_LEN {
+            Ok(PublicKey(bytes.to_vec()))
+        } else {
+            Err(KeyError::InvalidKey)
+        }
+    }
+
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>, KeyError> {
+        Ok(self.0.clone())
+    }
+}
+
+impl fmt::Debug for PublicKey {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("PublicKey").field(&self.0).finish()
+    }
+}
+
+/// The private key of a recipient.
+///
+/// The private key opens a container, which was
+/// [created](crate::CreateOptionsBuilder::with_recipient) for the matching
+/// [`PublicKey`]. Only X25519 keys are supported.
+#[derive(Clone)]
+pub struct PrivateKey(SecureVec);
+
+impl PrivateKey {
+    /// Generates a new X25519 private key.
+    pub fn generate() -> Result<PrivateKey, KeyError> {
+        let mut key: SecureVec = vec![0; KEY_LEN].into();
+
+        crypto::rand_bytes(&mut key)?;
+
+        Ok(PrivateKey(key))
+    }
+
+    /// Reads a private key from a PEM encoded buffer.
+    ///
+    /// Use `openssl genpkey -algorithm x25519` to create such a buffer.
+    pub fn from_pem(pem: &[u8]) -> Result<PrivateKey, KeyError> {
+        let der: SecureVec = pem_decode(pem, PRIVATE_KEY_LABEL)?.into();
+        let key = strip_der_prefix(&der, &PRIVATE_KEY_DER)?;
+
+        if key.len() == KEY_LEN {
+            Ok(PrivateKey(key.into()))
+        } else {
+            Err(KeyError::InvalidKey)
+        }
+    }
+
+    /// Encodes the private key as PEM (PKCS#8).
+    pub fn to_pem(&self) -> Result<Vec<u8>, KeyError> {
+        let mut der: SecureVec = PRIVATE_KEY_DER.as_slice().into();
+        der.extend_from_slice(&self.0);
+
+        Ok(pem_encode(&der, PRIVATE_KEY_LABEL))
+    }
+
+    /// Returns the public key, which belongs to this private key.
+    pub fn public_key(&self) -> Result<PublicKey, KeyError> {
+        PublicKey::from_bytes(&crypto::x25519_public(&self.0)?)
+    }
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("PrivateKey").field(&"***").finish()
+    }
+}
+
+fn strip_der_prefix<'a>(der: &'a [u8], prefix: &[u8]) -> Result<&'a [u8], KeyError> {
+    if der.starts_with(prefix) {
+        Ok(&der[prefix.len()..])
+    } else {
+        Err(KeyError::InvalidKey)
+    }
+}
+
+fn pem_encode(der: &[u8], label: &str) -> Vec<u8> {
+    let encoded = base64_encode(der);
+    let mut pem = format!("-----BEGIN {}-----\n", label).into_bytes();
+
+    for line in encoded.chunks(PEM_LINE_LEN) {
+        pem.extend_from_slice(line);
+        pem.push(b'\n');
+    }
+
+    pem.extend_from_slice(format!("-----END {}-----\n", label).as_bytes());
+
+    pem
+}
+
+fn pem_decode(pem: &[u8], label: &str) -> Result<Vec<u8>, KeyError> {
+    let pem = std::str::from_utf8(pem).map_err(|_| KeyError::InvalidKey)?;
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+
+    let mut lines = pem.lines().map(str::trim).skip_while(|l| *l != begin);
+    let mut body = String::new();
+
+    lines.next().ok_or(KeyError::InvalidKey)?;
+
+    for line in lines {
+        if line == end {
+            return base64_decode(body.as_bytes());
+        }
+
+        body.push_str(line);
+    }
+
+    Err(KeyError::InvalidKey)
+}
+
+fn base64_encode(buf: &[u8]) -> Vec<u8> {
+    let mut out = vec![];
+
+    for chunk in buf.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i)) as usize & 0x3f]);
+            } else {
+                out.push(b'=');
+            }
+        }
+    }
+
+    out
+}
+
+fn base64_decode(buf: &[u8]) -> Result<Vec<u8>, KeyError> {
+    let chunks = buf.chunks_exact(4);
+
+    if !chunks.remainder().is_empty() {
+        return Err(KeyError::InvalidKey);
+    }
+
+    let mut out = vec![];
+    let nchunks = chunks.len();
+
+    for (idx, chunk) in chunks.enumerate() {
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+
+        if padding > 2 || (padding > 0 && idx + 1 < nchunks) {
+            return Err(KeyError::InvalidKey);
+        }
+
+        let mut n = 0u32;
+
+        for (i, c) in chunk[..4 - padding].iter().enumerate() {
+            let value = BASE64
+                .iter()
+                .position(|b| b == c)
+                .ok_or(KeyError::InvalidKey)?;
+
+            n |= (value as u32) << (18 - 6 * i);
+        }
+
+        out.extend_from_slice(&n.to_be_bytes()[1..4 - padding]);
+    }
+
+    Ok(out)
+}
+
+/// Derives a key of `len` bytes from an X25519 key agreement.
+///
+/// The shared secret of `private` and `peer` is hashed together with the
+/// `ephemeral` and `recipient` public keys, which binds the key to both
+/// parties.
+pub(crate) fn derive_key(
+    private: &PrivateKey,
+    peer: &PublicKey,
+    ephemeral: &[u8],
+    recipient: &[u8],
+    len: usize,
+) -> Result<SecureVec, KeyError> {
+    assert!(len <= DIGEST.size());
+
+    let mut input = crypto::x25519_derive(&private.0, &peer.0)?;
+    input.extend_from_slice(ephemeral);
+    input.extend_from_slice(recipient);
+
+    let digest: SecureVec = crypto::hash(DIGEST, &input)?.into();
+
+    Ok(digest[..len].into())
+}
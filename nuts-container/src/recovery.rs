@@ -0,0 +1,123 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt::{self, Write};
+
+use crate::crypto::CryptoError;
+use crate::ossl;
+use crate::svec::SecureVec;
+
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const KEY_LEN: usize = 20;
+const GROUP_LEN: usize = 4;
+
+/// A recovery key of a container.
+///
+/// The recovery key is generated when the container is created with
+/// [`CreateOptionsBuilder::with_recovery_key`]. It is an alternative secret
+/// to the password and unlocks the container if the password is lost.
+///
+/// The key is displayed as groups of base32 encoded characters separated by
+/// dashes, e.g. `ABCD-EFGH-...`. When parsing a key, dashes, whitespace and
+/// lowercase letters are accepted.
+///
+/// [`CreateOptionsBuilder::with_recovery_key`]: crate::CreateOptionsBuilder::with_recovery_key
+#[derive(Clone, PartialEq)]
+pub struct RecoveryKey(SecureVec);
+
+impl RecoveryKey {
+    pub(crate) fn generate() -> Result<RecoveryKey, CryptoError> {
+        let mut key = vec![0; KEY_LEN];
+
+        ossl::rand_bytes(&mut key)?;
+
+        Ok(RecoveryKey(key.into()))
+    }
+
+    /// Parses the textual representation of a recovery key.
+    ///
+    /// Returns [`None`] if `s` is not a valid recovery key.
+    pub(crate) fn parse(s: &[u8]) -> Option<RecoveryKey> {
+        let mut bits = 0u32;
+        let mut nbits = 0;
+        let mut key = SecureVec::with_capacity(KEY_LEN);
+
+        for c in s.iter().filter(|c| **c != b'-' && !c.is_ascii_whitespace()) {
+            let c = c.to_ascii_uppercase();
+            let value = ALPHABET.iter().position(|n| *n == c)? as u32;
+
+            bits = (bits << 5) | value;
+            nbits += 5;
+
+            if nbits >= 8 {
+                nbits -= 8;
+                key.push((bits >> nbits) as u8);
+                bits &= (1 << nbits) - 1;
+            }
+        }
+
+        if key.len() == KEY_LEN && nbits == 0 {
+            Some(RecoveryKey(key))
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for RecoveryKey {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bits = 0u32;
+        let mut nbits = 0;
+        let mut nchars = 0;
+
+        for n in self.0.iter() {
+            bits = (bits << 8) | *n as u32;
+            nbits += 8;
+
+            while nbits >= 5 {
+                if nchars > 0 && nchars % GROUP_LEN == 0 {
+                    fmt.write_str("-")?;
+                }
+
+                nbits -= 5;
+                fmt.write_char(ALPHABET[(bits >> nbits) as usize & 0x1f] as char)?;
+                bits &= (1 << nbits) - 1;
+                nchars += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for RecoveryKey {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("RecoveryKey").field(&"***").finish()
+    }
+}
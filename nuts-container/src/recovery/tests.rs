@@ -0,0 +1,88 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use crate::recovery::RecoveryKey;
+use crate::tests::RND;
+
+const KEY: &str = "SHAL-FT7H-2EPO-GGIX-YREP-VVJP-GD5E-UHU4";
+
+#[test]
+fn generate() {
+    let key = RecoveryKey::generate().unwrap();
+
+    assert_eq!(key.as_bytes(), &RND[..20]);
+}
+
+#[test]
+fn to_string() {
+    let key = RecoveryKey::generate().unwrap();
+
+    assert_eq!(key.to_string(), KEY);
+}
+
+#[test]
+fn debug() {
+    let key = RecoveryKey::generate().unwrap();
+
+    assert_eq!(format!("{:?}", key), r#"RecoveryKey("***")"#);
+}
+
+#[test]
+fn parse() {
+    let key = RecoveryKey::parse(KEY.as_bytes()).unwrap();
+
+    assert_eq!(key.as_bytes(), &RND[..20]);
+}
+
+#[test]
+fn parse_lowercase() {
+    let key = RecoveryKey::parse(KEY.to_lowercase().as_bytes()).unwrap();
+
+    assert_eq!(key.as_bytes(), &RND[..20]);
+}
+
+#[test]
+fn parse_whitespace() {
+    let key = RecoveryKey::parse(b" SHALFT7H 2EPOGGIX\tYREPVVJP GD5EUHU4\n").unwrap();
+
+    assert_eq!(key.as_bytes(), &RND[..20]);
+}
+
+#[test]
+fn parse_invalid_char() {
+    assert!(RecoveryKey::parse(b"SHAL-FT7H-2EPO-GGIX-YREP-VVJP-GD5E-UHU1").is_none());
+}
+
+#[test]
+fn parse_too_short() {
+    assert!(RecoveryKey::parse(b"SHAL-FT7H-2EPO-GGIX-YREP-VVJP-GD5E-UHU").is_none());
+}
+
+#[test]
+fn parse_too_long() {
+    assert!(RecoveryKey::parse(b"SHAL-FT7H-2EPO-GGIX-YREP-VVJP-GD5E-UHU4-A").is_none());
+}
+
+#[test]
+fn parse_empty() {
+    assert!(RecoveryKey::parse(b"").is_none());
+}
@@ -33,7 +33,7 @@ use crate::Container;
 /// [`Container::create_service`].
 pub trait Service<B: Backend> {
     /// The migration assiciated with this service.
-    type Migration: Migration + 'static;
+    type Migration: Migration + Send + Sync + 'static;
 
     /// The service identifier.
     ///
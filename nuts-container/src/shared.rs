@@ -0,0 +1,131 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::{Backend, SharedRead};
+use std::sync::{Arc, PoisonError, RwLock, RwLockWriteGuard};
+
+use crate::error::ContainerResult;
+use crate::Container;
+
+/// A cloneable, thread-safe handle of a [`Container`].
+///
+/// All clones of a `SharedContainer` refer to the same container and can be
+/// sent to other threads. If the backend is able to
+/// [read concurrently](SharedRead), blocks are [read](SharedContainer::read)
+/// in parallel. All other operations get exclusive access to the container.
+///
+/// Use [`SharedContainer::lock`] for operations, which are not directly
+/// provided by the handle.
+#[derive(Debug)]
+pub struct SharedContainer<B: Backend>(Arc<RwLock<Container<B>>>);
+
+impl<B: Backend> SharedContainer<B> {
+    /// Creates a new handle, which wraps the given `container`.
+    pub fn new(container: Container<B>) -> SharedContainer<B> {
+        SharedContainer(Arc::new(RwLock::new(container)))
+    }
+
+    /// Returns the (net) block size of the container.
+    ///
+    /// See [`Container::block_size`] for details.
+    pub fn block_size(&self) -> u32 {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .block_size()
+    }
+
+    /// Aquires a new block in the container.
+    ///
+    /// See [`Container::aquire`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Errors are listed in the [`Error`](crate::Error) type.
+    pub fn aquire(&self) -> ContainerResult<B::Id, B> {
+        self.lock().aquire()
+    }
+
+    /// Releases a block again.
+    ///
+    /// See [`Container::release`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Errors are listed in the [`Error`](crate::Error) type.
+    pub fn release(&self, id: B::Id) -> ContainerResult<(), B> {
+        self.lock().release(id)
+    }
+
+    /// Writes a block into the container.
+    ///
+    /// See [`Container::write`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Errors are listed in the [`Error`](crate::Error) type.
+    pub fn write(&self, id: &B::Id, buf: &[u8]) -> ContainerResult<usize, B> {
+        self.lock().write(id, buf)
+    }
+
+    /// Gets exclusive access to the container.
+    ///
+    /// All other users of the container are blocked until the returned guard
+    /// is dropped.
+    pub fn lock(&self) -> RwLockWriteGuard<'_, Container<B>> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Consumes this handle, returning the inner container.
+    ///
+    /// This only succeeds for the last handle of the container, otherwise
+    /// the handle is passed back in the [`Err`] variant.
+    pub fn into_container(self) -> Result<Container<B>, SharedContainer<B>> {
+        match Arc::try_unwrap(self.0) {
+            Ok(lock) => Ok(lock.into_inner().unwrap_or_else(PoisonError::into_inner)),
+            Err(arc) => Err(SharedContainer(arc)),
+        }
+    }
+}
+
+impl<B: SharedRead> SharedContainer<B> {
+    /// Reads a block from the container.
+    ///
+    /// Several threads can read at the same time. See [`Container::read`] for
+    /// details.
+    ///
+    /// # Errors
+    ///
+    /// Errors are listed in the [`Error`](crate::Error) type.
+    pub fn read(&self, id: &B::Id, buf: &mut [u8]) -> ContainerResult<usize, B> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .read_shared(id, buf)
+    }
+}
+
+impl<B: Backend> Clone for SharedContainer<B> {
+    fn clone(&self) -> Self {
+        SharedContainer(Arc::clone(&self.0))
+    }
+}
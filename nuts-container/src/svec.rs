@@ -20,45 +20,168 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
+mod mem;
+#[cfg(test)]
+mod tests;
+
 use std::fmt;
 use std::ops::{Deref, DerefMut};
 
-#[derive(Clone, PartialEq)]
-pub struct SecureVec(Vec<u8>);
+use crate::buffer::{BufferError, BufferMut};
+use crate::svec::mem::Mem;
+
+/// A byte buffer for sensitive data like keys, IVs and passwords.
+///
+/// The bytes are stored in memory, which is locked into RAM and excluded
+/// from core dumps (if supported by the platform). The memory is zeroed
+/// when it is released, this includes the old memory when the buffer
+/// grows.
+pub struct SecureVec {
+    mem: Mem,
+    len: usize,
+}
+
+impl SecureVec {
+    pub fn new() -> SecureVec {
+        SecureVec {
+            mem: Mem::empty(),
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> SecureVec {
+        SecureVec {
+            mem: Mem::alloc(capacity),
+            len: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.mem.capacity()
+    }
+
+    #[cfg(test)]
+    pub fn is_locked(&self) -> bool {
+        self.mem.is_locked()
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.mem.as_slice()[..self.len]
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.mem.as_mut_slice()[..self.len]
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.mem.as_mut_slice()[len..self.len].fill(0);
+            self.len = len;
+        }
+    }
+
+    pub fn resize(&mut self, new_len: usize, value: u8) {
+        if new_len > self.len {
+            self.reserve(new_len - self.len);
+            self.mem.as_mut_slice()[self.len..new_len].fill(value);
+            self.len = new_len;
+        } else {
+            self.truncate(new_len);
+        }
+    }
+
+    pub fn push(&mut self, value: u8) {
+        self.extend_from_slice(&[value]);
+    }
+
+    pub fn extend_from_slice(&mut self, other: &[u8]) {
+        self.reserve(other.len());
+        self.mem.as_mut_slice()[self.len..self.len + other.len()].copy_from_slice(other);
+        self.len += other.len();
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        let min_cap = self.len.checked_add(additional).expect("capacity overflow");
+
+        if min_cap > self.capacity() {
+            let mut mem = Mem::alloc(min_cap.max(2 * self.capacity()));
+
+            mem.as_mut_slice()[..self.len].copy_from_slice(self.as_slice());
+
+            // the old memory is zeroed on drop
+            self.mem = mem;
+        }
+    }
+}
+
+impl Default for SecureVec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for SecureVec {
+    fn clone(&self) -> Self {
+        SecureVec::from(self.as_slice())
+    }
+}
+
+impl PartialEq for SecureVec {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
 
 impl AsRef<[u8]> for SecureVec {
     fn as_ref(&self) -> &[u8] {
-        &self.0
+        self.as_slice()
     }
 }
 
 impl Deref for SecureVec {
-    type Target = Vec<u8>;
+    type Target = [u8];
 
-    fn deref(&self) -> &Vec<u8> {
-        &self.0
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
     }
 }
 
 impl DerefMut for SecureVec {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
     }
 }
 
-impl From<Vec<u8>> for SecureVec {
-    fn from(inner: Vec<u8>) -> Self {
-        SecureVec(inner)
+impl From<&[u8]> for SecureVec {
+    fn from(buf: &[u8]) -> Self {
+        let mut svec = SecureVec::with_capacity(buf.len());
+
+        svec.extend_from_slice(buf);
+
+        svec
     }
 }
 
-impl Drop for SecureVec {
-    fn drop(&mut self) {
-        self.0.resize(self.0.capacity(), 0);
+impl From<Vec<u8>> for SecureVec {
+    fn from(mut inner: Vec<u8>) -> Self {
+        let svec = SecureVec::from(inner.as_slice());
 
-        for elem in self.0.iter_mut() {
-            *elem = 0;
-        }
+        // the vector is not locked, at least do not leave a copy behind
+        inner.resize(inner.capacity(), 0);
+        inner.iter_mut().for_each(|n| *n = 0);
+
+        svec
+    }
+}
+
+impl BufferMut for SecureVec {
+    fn put_chunk(&mut self, buf: &[u8]) -> Result<(), BufferError> {
+        self.extend_from_slice(buf);
+        Ok(())
     }
 }
 
@@ -67,9 +190,9 @@ impl fmt::Debug for SecureVec {
         let mut dbg = fmt.debug_tuple("SecureVec");
 
         if cfg!(feature = "debug-plain-keys") {
-            dbg.field(&format!("<{} bytes>", self.0.len())).finish()
+            dbg.field(&format!("<{} bytes>", self.len)).finish()
         } else {
-            dbg.field(&self.0).finish()
+            dbg.field(&self.as_slice()).finish()
         }
     }
 }
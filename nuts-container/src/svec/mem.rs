@@ -0,0 +1,223 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use log::warn;
+use std::alloc::{self, Layout};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static LOCK_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// A zeroed, page-aligned allocation of `cap` bytes.
+///
+/// The pages are excluded from core dumps (`MADV_DONTDUMP`) and locked into
+/// RAM (`mlock`). If locking fails, e.g. because `RLIMIT_MEMLOCK` is too low,
+/// the memory is used unlocked and a warning is logged once. The pages are
+/// still excluded from core dumps.
+///
+/// Both flags are reverted before the memory is released, thus the allocator
+/// does not reuse pages, which still carry them.
+pub struct Mem {
+    ptr: NonNull<u8>,
+    cap: usize,
+    locked: bool,
+}
+
+impl Mem {
+    /// Creates an empty instance without an allocation.
+    pub const fn empty() -> Mem {
+        Mem {
+            ptr: NonNull::dangling(),
+            cap: 0,
+            locked: false,
+        }
+    }
+
+    /// Allocates at least `min_cap` bytes.
+    ///
+    /// The capacity is rounded up to a multiple of the page size.
+    pub fn alloc(min_cap: usize) -> Mem {
+        if min_cap == 0 {
+            return Self::empty();
+        }
+
+        let page_size = page_size();
+        let cap = min_cap
+            .checked_add(page_size - 1)
+            .expect("capacity overflow")
+            / page_size
+            * page_size;
+        let layout = Self::layout(cap);
+
+        // SAFETY: the layout has a non-zero size.
+        let ptr = unsafe { alloc::alloc_zeroed(layout) };
+        let ptr = NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+
+        sys::dont_dump(ptr.as_ptr(), cap);
+
+        let locked = match sys::lock(ptr.as_ptr(), cap) {
+            Ok(()) => true,
+            Err(err) => {
+                if !LOCK_WARNED.swap(true, Ordering::Relaxed) {
+                    warn!(
+                        "failed to lock memory, key material might be swapped to disk: {}",
+                        err
+                    );
+                }
+
+                false
+            }
+        };
+
+        Mem { ptr, cap, locked }
+    }
+
+    /// Returns the number of allocated bytes.
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Tests whether the memory is locked into RAM.
+    #[cfg(test)]
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: ptr is valid for cap bytes (or dangling with cap 0).
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.cap) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        // SAFETY: ptr is valid for cap bytes (or dangling with cap 0) and
+        // uniquely owned.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.cap) }
+    }
+
+    /// Overwrites the first `len` bytes with zeros.
+    pub fn wipe(&mut self, len: usize) {
+        let buf = &mut self.as_mut_slice()[..len];
+
+        for elem in buf.iter_mut() {
+            // SAFETY: elem is a valid, aligned reference.
+            unsafe { std::ptr::write_volatile(elem, 0) };
+        }
+    }
+
+    fn layout(cap: usize) -> Layout {
+        Layout::from_size_align(cap, page_size()).expect("invalid layout")
+    }
+}
+
+impl Drop for Mem {
+    fn drop(&mut self) {
+        if self.cap == 0 {
+            return;
+        }
+
+        self.wipe(self.cap);
+        sys::do_dump(self.ptr.as_ptr(), self.cap);
+
+        if self.locked {
+            sys::unlock(self.ptr.as_ptr(), self.cap);
+        }
+
+        // SAFETY: ptr was allocated with the same layout.
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), Self::layout(self.cap)) };
+    }
+}
+
+// SAFETY: Mem uniquely owns its allocation, just like a Vec<u8>.
+unsafe impl Send for Mem {}
+unsafe impl Sync for Mem {}
+
+fn page_size() -> usize {
+    sys::page_size()
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::io;
+
+    pub fn page_size() -> usize {
+        // SAFETY: sysconf has no preconditions.
+        match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+            n if n > 0 => n as usize,
+            _ => 4096,
+        }
+    }
+
+    pub fn lock(ptr: *mut u8, len: usize) -> io::Result<()> {
+        // SAFETY: ptr is a page-aligned allocation of len bytes.
+        if unsafe { libc::mlock(ptr as *const libc::c_void, len) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    pub fn unlock(ptr: *mut u8, len: usize) {
+        // SAFETY: ptr is a page-aligned allocation of len bytes.
+        unsafe { libc::munlock(ptr as *const libc::c_void, len) };
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn dont_dump(ptr: *mut u8, len: usize) {
+        // SAFETY: ptr is a page-aligned allocation of len bytes. A failure is
+        // not fatal, the memory is still usable.
+        unsafe { libc::madvise(ptr as *mut libc::c_void, len, libc::MADV_DONTDUMP) };
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn do_dump(ptr: *mut u8, len: usize) {
+        // SAFETY: ptr is a page-aligned allocation of len bytes.
+        unsafe { libc::madvise(ptr as *mut libc::c_void, len, libc::MADV_DODUMP) };
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn dont_dump(_ptr: *mut u8, _len: usize) {}
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn do_dump(_ptr: *mut u8, _len: usize) {}
+}
+
+#[cfg(not(unix))]
+mod sys {
+    use std::io;
+
+    pub fn page_size() -> usize {
+        4096
+    }
+
+    pub fn lock(_ptr: *mut u8, _len: usize) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "memory locking is not supported",
+        ))
+    }
+
+    pub fn unlock(_ptr: *mut u8, _len: usize) {}
+
+    pub fn dont_dump(_ptr: *mut u8, _len: usize) {}
+
+    pub fn do_dump(_ptr: *mut u8, _len: usize) {}
+}
@@ -0,0 +1,180 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use crate::buffer::BufferMut;
+use crate::svec::SecureVec;
+
+#[test]
+fn new() {
+    let svec = SecureVec::new();
+
+    assert!(svec.is_empty());
+    assert_eq!(svec.capacity(), 0);
+    assert!(!svec.is_locked());
+}
+
+#[test]
+fn with_capacity() {
+    let svec = SecureVec::with_capacity(1);
+
+    assert!(svec.is_empty());
+    assert!(svec.capacity() >= 1);
+    assert_eq!(svec.capacity() % 4096, 0);
+}
+
+#[test]
+fn from_slice() {
+    let svec = SecureVec::from([1, 2, 3].as_slice());
+
+    assert_eq!(svec.as_slice(), [1, 2, 3]);
+}
+
+#[test]
+fn from_vec() {
+    let svec = SecureVec::from(vec![1, 2, 3]);
+
+    assert_eq!(svec.as_slice(), [1, 2, 3]);
+}
+
+#[test]
+fn push() {
+    let mut svec = SecureVec::new();
+
+    svec.push(1);
+    svec.push(2);
+
+    assert_eq!(svec.as_slice(), [1, 2]);
+}
+
+#[test]
+fn extend_from_slice() {
+    let mut svec = SecureVec::from(vec![1]);
+
+    svec.extend_from_slice(&[2, 3]);
+
+    assert_eq!(svec.as_slice(), [1, 2, 3]);
+}
+
+#[test]
+fn grow() {
+    let mut svec = SecureVec::from(vec![1, 2, 3]);
+    let cap = svec.capacity();
+
+    svec.resize(cap + 1, 4);
+
+    assert!(svec.capacity() > cap);
+    assert_eq!(svec.len(), cap + 1);
+    assert_eq!(svec[..4], [1, 2, 3, 4]);
+    assert!(svec[3..].iter().all(|n| *n == 4));
+}
+
+#[test]
+fn resize_shrink() {
+    let mut svec = SecureVec::from(vec![1, 2, 3]);
+
+    svec.resize(1, 0);
+    assert_eq!(svec.as_slice(), [1]);
+
+    // the truncated bytes are zeroed
+    svec.resize(3, 9);
+    assert_eq!(svec.as_slice(), [1, 9, 9]);
+}
+
+#[test]
+fn clear() {
+    let mut svec = SecureVec::from(vec![1, 2, 3]);
+    let cap = svec.capacity();
+
+    svec.clear();
+
+    assert!(svec.is_empty());
+    assert_eq!(svec.capacity(), cap);
+}
+
+#[test]
+fn deref_mut() {
+    let mut svec = SecureVec::from(vec![1, 2, 3]);
+
+    svec[1] = 7;
+
+    assert_eq!(svec.as_slice(), [1, 7, 3]);
+}
+
+#[test]
+fn clone() {
+    let svec = SecureVec::from(vec![1, 2, 3]);
+    let other = svec.clone();
+
+    assert_eq!(svec, other);
+    assert_ne!(svec.as_ptr(), other.as_ptr());
+}
+
+#[test]
+fn put_chunk() {
+    let mut svec = SecureVec::new();
+
+    svec.put_u8(1).unwrap();
+    svec.put_u32(2).unwrap();
+
+    assert_eq!(svec.as_slice(), [1, 0, 0, 0, 2]);
+}
+
+#[test]
+fn debug() {
+    let svec = SecureVec::from(vec![1, 2, 3]);
+
+    if cfg!(feature = "debug-plain-keys") {
+        assert_eq!(format!("{:?}", svec), "SecureVec(\"<3 bytes>\")");
+    } else {
+        assert_eq!(format!("{:?}", svec), "SecureVec([1, 2, 3])");
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn dont_dump() {
+    let svec = SecureVec::with_capacity(1);
+    let addr = svec.as_ptr() as usize;
+    let smaps = std::fs::read_to_string("/proc/self/smaps").unwrap();
+    let mut in_range = false;
+
+    for line in smaps.lines() {
+        if let Some(flags) = line.strip_prefix("VmFlags:") {
+            if in_range {
+                // excluded from core dumps, even if not locked
+                assert!(flags.split_whitespace().any(|flag| flag == "dd"));
+                return;
+            }
+        } else if let Some((range, _)) = line.split_once(' ') {
+            if let Some((start, end)) = range.split_once('-') {
+                if let (Ok(start), Ok(end)) = (
+                    usize::from_str_radix(start, 16),
+                    usize::from_str_radix(end, 16),
+                ) {
+                    in_range = start <= addr && addr < end;
+                }
+            }
+        }
+    }
+
+    panic!("no mapping for {:#x}", addr);
+}
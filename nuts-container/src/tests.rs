@@ -21,7 +21,10 @@
 // IN THE SOFTWARE.
 
 mod info;
+mod integrity;
+mod lock;
 mod read;
+mod transaction;
 mod write;
 
 const CTEXT_AES128_CTR: [u8; 512] = [
@@ -94,6 +97,41 @@ const CTEXT_AES128_GCM: [u8; 512] = [
     0x4c, 0xe5, 0xba, 0xd8, 0x18, 0x6c, 0xdf, 0xaa, 0xfa, 0xe6, 0xa7, 0xc9, 0x60, 0xa1, 0xcd, 0x56,
 ];
 
+const CTEXT_AES128_CTR_HMAC: [u8; 512] = [
+    0xfb, 0x7b, 0x8e, 0xef, 0x3e, 0xff, 0xde, 0x50, 0x70, 0xaa, 0x93, 0x9c, 0xf1, 0x44, 0xa1, 0x12,
+    0xad, 0x3f, 0x96, 0xb8, 0x26, 0xab, 0xb4, 0xd, 0x51, 0xad, 0x11, 0xdf, 0x16, 0x5d, 0x79, 0x5a,
+    0xf4, 0x99, 0x3, 0x2a, 0x8c, 0x8d, 0x1, 0x2a, 0x7f, 0x67, 0x65, 0x9d, 0xab, 0xab, 0x9d, 0x5c,
+    0x13, 0xcc, 0xba, 0x19, 0x22, 0x54, 0x8, 0x95, 0x9e, 0x5f, 0xb2, 0xa7, 0x4e, 0x79, 0x41, 0xaa,
+    0xa1, 0xd5, 0x5a, 0xe6, 0xc5, 0xd5, 0x78, 0x92, 0xed, 0x35, 0x94, 0x61, 0x58, 0xfa, 0x9a, 0x78,
+    0x1c, 0xf5, 0x7f, 0xc1, 0xd1, 0xf6, 0xf3, 0xd1, 0xea, 0x79, 0x82, 0xa7, 0xa, 0x44, 0x49, 0xe0,
+    0xcf, 0x96, 0xa4, 0x4d, 0xc7, 0x7f, 0x14, 0xda, 0x68, 0x88, 0xd1, 0xa0, 0x55, 0xf2, 0xf7, 0x6,
+    0x8c, 0x38, 0x8d, 0x3f, 0x10, 0xc0, 0xdf, 0x2e, 0xf7, 0x23, 0xa4, 0x73, 0xa3, 0x62, 0x39, 0xc6,
+    0xbc, 0x2c, 0x10, 0xeb, 0x41, 0x36, 0x1d, 0x73, 0x66, 0x67, 0x97, 0xa1, 0x3, 0x54, 0xec, 0xa,
+    0x4c, 0xff, 0xb8, 0xc2, 0x83, 0x73, 0xe6, 0xc7, 0x4f, 0x5b, 0xb7, 0xf0, 0xa3, 0xe, 0xfa, 0xc0,
+    0x6, 0x51, 0x3d, 0xae, 0x87, 0x53, 0xdf, 0xd2, 0x91, 0xa, 0x2c, 0x14, 0x5a, 0xbd, 0x95, 0x37,
+    0x62, 0x3c, 0x56, 0x3b, 0xc4, 0x5d, 0xc6, 0x74, 0xa3, 0x8e, 0xaa, 0x66, 0xbb, 0x21, 0x98, 0xf7,
+    0x1b, 0x65, 0x33, 0x1b, 0xd0, 0xf6, 0x13, 0x9a, 0x24, 0xb0, 0xb0, 0x6, 0x39, 0x67, 0xc2, 0x6d,
+    0x76, 0xea, 0x48, 0xc4, 0xc2, 0xa6, 0xa0, 0x36, 0x58, 0x86, 0xa8, 0xaa, 0xf6, 0x7b, 0xf9, 0xa5,
+    0xa1, 0x34, 0x20, 0x1a, 0x6f, 0xff, 0x98, 0xe8, 0x9b, 0x24, 0x48, 0x55, 0x38, 0xc8, 0xa9, 0x55,
+    0xd9, 0xb7, 0xaa, 0xd0, 0x2, 0xa4, 0x4e, 0x28, 0xce, 0x79, 0x29, 0xc, 0x95, 0x3, 0x64, 0x1,
+    0x1a, 0xe1, 0x61, 0x36, 0x52, 0xc9, 0xf, 0x7e, 0x2b, 0xef, 0xfb, 0x31, 0x8d, 0x2b, 0x6e, 0xe2,
+    0xb, 0x2d, 0xa0, 0x4f, 0x4b, 0xef, 0x21, 0xb3, 0x3c, 0x86, 0xb0, 0x11, 0x6d, 0xdc, 0x1e, 0x5e,
+    0x25, 0x35, 0x51, 0xd4, 0xef, 0xc6, 0xc4, 0x86, 0xff, 0x2e, 0xdd, 0x2, 0x16, 0xe, 0x8a, 0x7d,
+    0xc5, 0x2c, 0x43, 0xfe, 0x4a, 0xf7, 0x2a, 0x3f, 0x72, 0xba, 0x4b, 0x45, 0xe2, 0x6f, 0x71, 0xdb,
+    0x0, 0x29, 0xed, 0xb5, 0xa, 0x3b, 0x62, 0x69, 0xff, 0x68, 0x56, 0xed, 0x18, 0xcd, 0x6c, 0x62,
+    0x8e, 0x7e, 0x38, 0x52, 0xa6, 0x7d, 0xe8, 0xfa, 0x16, 0x96, 0x72, 0x9c, 0x75, 0x15, 0xac, 0x78,
+    0x15, 0xf5, 0xb8, 0x7f, 0x3e, 0x47, 0x2c, 0x3b, 0x16, 0x68, 0xc0, 0x6e, 0x8d, 0xa1, 0x6, 0x60,
+    0x7d, 0x5, 0xaa, 0xfd, 0x68, 0xd2, 0xf1, 0x14, 0x4e, 0x1d, 0xb8, 0xf6, 0x1, 0xcc, 0x10, 0x58,
+    0x3, 0x2a, 0xac, 0xbe, 0xc5, 0xc2, 0xe9, 0xeb, 0xb3, 0xf7, 0xe2, 0xcc, 0xe1, 0x1d, 0x7f, 0x76,
+    0x80, 0x97, 0xd7, 0x42, 0xbf, 0x5e, 0x49, 0x6, 0x7f, 0x5c, 0x5b, 0x2d, 0xbe, 0x9f, 0x7d, 0xa4,
+    0x77, 0xbe, 0x1, 0x7e, 0xd4, 0xa7, 0x54, 0xbe, 0xbb, 0xa3, 0xd2, 0xdd, 0x94, 0x2, 0x82, 0x4d,
+    0x55, 0x9e, 0xb4, 0xbb, 0x1, 0x50, 0x24, 0xab, 0x5a, 0xca, 0x34, 0x3a, 0x92, 0xc7, 0xd5, 0xff,
+    0x58, 0x9c, 0xe6, 0x9c, 0x30, 0x7f, 0x33, 0x10, 0xf7, 0x8a, 0xd3, 0xc3, 0x2d, 0xff, 0x12, 0x9b,
+    0x51, 0x8a, 0xa2, 0x5, 0xb4, 0x90, 0x66, 0xec, 0x4e, 0x0, 0x8c, 0x33, 0xcf, 0x1b, 0xae, 0xb7,
+    0xa7, 0xc8, 0xc8, 0x86, 0x39, 0x6f, 0x9f, 0xde, 0x31, 0xf7, 0x9a, 0xe7, 0xdc, 0xd, 0x27, 0xbe,
+    0xb3, 0x78, 0x1b, 0x56, 0x9, 0x2a, 0x94, 0xec, 0x1e, 0x44, 0x87, 0xeb, 0xea, 0x3e, 0x21, 0x38,
+];
+
 pub const RND: [u8; 1536] = [
     0x91, 0xc0, 0xb2, 0xcf, 0xe7, 0xd1, 0x1e, 0xe3, 0x19, 0x17, 0xc4, 0x48, 0xfa, 0xd5, 0x2f, 0x30,
     0xfa, 0x4a, 0x1e, 0x9c, 0xd5, 0xa4, 0x9d, 0xbe, 0x00, 0xcc, 0x42, 0x01, 0x18, 0xb5, 0xbe, 0x0f,
@@ -35,9 +35,13 @@ fn none() {
         container.info().unwrap(),
         Info {
             backend: (),
-            revision: 2,
+            revision: 3,
             cipher: Cipher::None,
             kdf: Kdf::None,
+            services: vec![],
+            rollback_protection: false,
+            recovery_key: false,
+            recipient: false,
             bsize_gross: 512,
             bsize_net: 512,
         }
@@ -58,9 +62,13 @@ fn aes128_ctr() {
         container.info().unwrap(),
         Info {
             backend: (),
-            revision: 2,
+            revision: 3,
             cipher: Cipher::Aes128Ctr,
             kdf,
+            services: vec![],
+            rollback_protection: false,
+            recovery_key: false,
+            recipient: false,
             bsize_gross: 512,
             bsize_net: 512,
         }
@@ -81,11 +89,42 @@ fn aes128_gcm() {
         container.info().unwrap(),
         Info {
             backend: (),
-            revision: 2,
+            revision: 3,
             cipher: Cipher::Aes128Gcm,
             kdf,
+            services: vec![],
+            rollback_protection: false,
+            recovery_key: false,
+            recipient: false,
             bsize_gross: 512,
             bsize_net: 496,
         }
     );
 }
+
+#[test]
+fn aes128_ctr_hmac() {
+    let kdf = Kdf::pbkdf2(Digest::Sha1, 65536, b"123");
+    let options = CreateOptionsBuilder::new(Cipher::Aes128CtrHmac)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .with_kdf(kdf.clone())
+        .build::<MemoryBackend>()
+        .unwrap();
+    let container = Container::<MemoryBackend>::create(MemoryBackend::new(), options).unwrap();
+
+    assert_eq!(
+        container.info().unwrap(),
+        Info {
+            backend: (),
+            revision: 3,
+            cipher: Cipher::Aes128CtrHmac,
+            kdf,
+            services: vec![],
+            rollback_protection: false,
+            recovery_key: false,
+            recipient: false,
+            bsize_gross: 512,
+            bsize_net: 480,
+        }
+    );
+}
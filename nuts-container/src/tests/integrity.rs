@@ -0,0 +1,106 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::{ReceiveHeader, HEADER_MAX_SIZE};
+use nuts_memory::MemoryBackend;
+
+use crate::{Cipher, Container, CreateOptionsBuilder};
+
+fn setup_container() -> Container<MemoryBackend> {
+    let options = CreateOptionsBuilder::new(Cipher::None)
+        .with_rollback_protection(true)
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::create(MemoryBackend::new(), options).unwrap()
+}
+
+fn header_bytes(container: &mut Container<MemoryBackend>) -> [u8; HEADER_MAX_SIZE] {
+    let mut buf = [0; HEADER_MAX_SIZE];
+
+    container.backend_mut().get_header_bytes(&mut buf).unwrap();
+
+    buf
+}
+
+#[test]
+fn synced_on_write() {
+    let mut container = setup_container();
+    let header = header_bytes(&mut container);
+
+    let id1 = container.aquire().unwrap();
+    assert_ne!(header_bytes(&mut container), header);
+    let header = header_bytes(&mut container);
+
+    container.write(&id1, b"abc").unwrap();
+    assert_ne!(header_bytes(&mut container), header);
+    let header = header_bytes(&mut container);
+
+    container.release(id1).unwrap();
+    assert_ne!(header_bytes(&mut container), header);
+    let header = header_bytes(&mut container);
+
+    // nothing modified since the last write
+    container.flush().unwrap();
+    assert_eq!(header_bytes(&mut container), header);
+}
+
+#[test]
+fn not_synced_in_transaction() {
+    let mut container = setup_container();
+    let header = header_bytes(&mut container);
+
+    container.begin().unwrap();
+
+    let id1 = container.aquire().unwrap();
+    let id2 = container.aquire().unwrap();
+    container.write(&id1, b"abc").unwrap();
+    container.release(id2).unwrap();
+
+    // the integrity tree is only modified in memory
+    assert!(container.integrity.as_ref().unwrap().get(&id1).is_some());
+    assert!(container.integrity.as_ref().unwrap().get(&id2).is_none());
+    assert_eq!(header_bytes(&mut container), header);
+
+    container.commit().unwrap();
+    assert_ne!(header_bytes(&mut container), header);
+}
+
+#[test]
+fn synced_on_commit() {
+    let mut container = setup_container();
+    let id = container.aquire().unwrap();
+    container.flush().unwrap();
+
+    let header = header_bytes(&mut container);
+
+    container.begin().unwrap();
+    container.write(&id, b"abc").unwrap();
+    container.commit().unwrap();
+
+    assert_ne!(header_bytes(&mut container), header);
+    let header = header_bytes(&mut container);
+
+    // the commit left nothing to flush
+    container.flush().unwrap();
+    assert_eq!(header_bytes(&mut container), header);
+}
@@ -0,0 +1,108 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::{LockMode, Open, ReceiveHeader, HEADER_MAX_SIZE};
+use nuts_memory::{Error as MemoryError, MemoryBackend, Settings};
+
+use crate::{Cipher, Container, CreateOptionsBuilder, OpenOptionsBuilder};
+
+/// Open builder, which hands out an outdated header.
+///
+/// Simulates another process, which modified the header before the
+/// container was locked.
+struct StaleOpen {
+    header: [u8; HEADER_MAX_SIZE],
+    backend: MemoryBackend,
+}
+
+impl ReceiveHeader<MemoryBackend> for StaleOpen {
+    fn get_header_bytes(&mut self, bytes: &mut [u8; HEADER_MAX_SIZE]) -> Result<(), MemoryError> {
+        bytes.copy_from_slice(&self.header);
+        Ok(())
+    }
+}
+
+impl Open<MemoryBackend> for StaleOpen {
+    fn build(self, _settings: Settings) -> Result<MemoryBackend, MemoryError> {
+        Ok(self.backend)
+    }
+}
+
+fn setup_container() -> MemoryBackend {
+    let options = CreateOptionsBuilder::new(Cipher::None)
+        .build::<MemoryBackend>()
+        .unwrap();
+    let container = Container::create(MemoryBackend::new(), options).unwrap();
+
+    assert_eq!(container.backend().lock_mode(), Some(LockMode::Exclusive));
+
+    container.into_backend()
+}
+
+#[test]
+fn open_exclusive() {
+    let options = OpenOptionsBuilder::new().build::<MemoryBackend>().unwrap();
+    let container = Container::open(setup_container(), options).unwrap();
+
+    assert_eq!(container.backend().lock_mode(), Some(LockMode::Exclusive));
+}
+
+#[test]
+fn open_shared() {
+    let options = OpenOptionsBuilder::new()
+        .read_only(true)
+        .build::<MemoryBackend>()
+        .unwrap();
+    let container = Container::open(setup_container(), options).unwrap();
+
+    assert_eq!(container.backend().lock_mode(), Some(LockMode::Shared));
+}
+
+#[test]
+fn header_changed() {
+    let options = CreateOptionsBuilder::new(Cipher::None)
+        .with_rollback_protection(true)
+        .build::<MemoryBackend>()
+        .unwrap();
+    let mut container = Container::create(MemoryBackend::new(), options).unwrap();
+    let mut header = [0; HEADER_MAX_SIZE];
+
+    // the header is outdated, once the integrity tree changes
+    container
+        .backend_mut()
+        .get_header_bytes(&mut header)
+        .unwrap();
+
+    let id = container.aquire().unwrap();
+    container.write(&id, &[1; 512]).unwrap();
+
+    let stale = StaleOpen {
+        header,
+        backend: container.into_backend(),
+    };
+    let options = OpenOptionsBuilder::new().build::<MemoryBackend>().unwrap();
+    let mut container = Container::open(stale, options).unwrap();
+    let mut buf = [0; 512];
+
+    container.read(&id, &mut buf).unwrap();
+    assert_eq!(buf, [1; 512]);
+}
@@ -154,3 +154,91 @@ mod aes128_gcm {
         assert!(matches!(err, Error::Cipher(CipherError::NotTrustworthy)));
     }
 }
+
+mod aes128_ctr_hmac {
+    use nuts_backend::Backend;
+    use nuts_memory::{Error as MemoryError, Id, MemoryBackend};
+
+    use crate::tests::{CTEXT_AES128_CTR_HMAC, RND};
+    use crate::{Cipher, CipherError, Container, CreateOptionsBuilder, Digest, Error, Kdf};
+
+    fn setup_container(data: &[u8]) -> (Container<MemoryBackend>, Id) {
+        let mut backend = MemoryBackend::new();
+
+        let id = backend.insert_data(data).unwrap();
+
+        let options = CreateOptionsBuilder::new(Cipher::Aes128CtrHmac)
+            .with_password_callback(|| Ok(b"abc".to_vec()))
+            .with_kdf(Kdf::pbkdf2(Digest::Sha1, 65536, b"123"))
+            .build::<MemoryBackend>()
+            .unwrap();
+        let container = Container::<MemoryBackend>::create(backend, options).unwrap();
+
+        (container, id)
+    }
+
+    read_tests!(480, &CTEXT_AES128_CTR_HMAC);
+
+    #[test]
+    fn mac_mismatch() {
+        let data = [&[CTEXT_AES128_CTR_HMAC[0] + 1], &CTEXT_AES128_CTR_HMAC[1..]].concat();
+        let (mut container, id) = setup_container(&data);
+        let mut buf = [0; 480];
+
+        let err = container.read(&id, &mut buf).unwrap_err();
+        assert!(matches!(err, Error::Cipher(CipherError::MacMismatch)));
+    }
+
+    #[test]
+    fn aquired() {
+        let (mut container, _) = setup_container(&CTEXT_AES128_CTR_HMAC);
+        let mut buf = [b'x'; 480];
+
+        let id = container.aquire().unwrap();
+
+        let n = container.read(&id, &mut buf).unwrap();
+        assert_eq!(n, 480);
+        assert_eq!(buf, [0; 480]);
+    }
+
+    #[test]
+    fn aquired_placeholder() {
+        let (mut container, _) = setup_container(&CTEXT_AES128_CTR_HMAC);
+        let mut buf = [b'x'; 512];
+
+        let id = container.aquire().unwrap();
+
+        container.backend_mut().read(&id, &mut buf).unwrap();
+        assert_eq!(buf, [0; 512]);
+
+        container.write(&id, b"abc").unwrap();
+
+        container.backend_mut().read(&id, &mut buf).unwrap();
+        assert_ne!(buf, [0; 512]);
+    }
+
+    #[test]
+    fn swapped_blocks() {
+        let (mut container, _) = setup_container(&CTEXT_AES128_CTR_HMAC);
+        let mut buf1 = [0; 512];
+        let mut buf2 = [0; 512];
+
+        let id1 = container.aquire().unwrap();
+        let id2 = container.aquire().unwrap();
+
+        container.write(&id1, b"abc").unwrap();
+        container.write(&id2, b"xyz").unwrap();
+
+        // swap the encrypted blocks
+        container.backend_mut().read(&id1, &mut buf1).unwrap();
+        container.backend_mut().read(&id2, &mut buf2).unwrap();
+        container.backend_mut().write(&id1, &buf2).unwrap();
+        container.backend_mut().write(&id2, &buf1).unwrap();
+
+        let err = container.read(&id1, &mut [0; 480]).unwrap_err();
+        assert!(matches!(err, Error::Cipher(CipherError::MacMismatch)));
+
+        let err = container.read(&id2, &mut [0; 480]).unwrap_err();
+        assert!(matches!(err, Error::Cipher(CipherError::MacMismatch)));
+    }
+}
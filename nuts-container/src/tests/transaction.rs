@@ -0,0 +1,312 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_memory::{Id, MemoryBackend};
+
+use crate::{Cipher, Container, CreateOptionsBuilder, OpenOptionsBuilder};
+
+fn setup_container() -> (Container<MemoryBackend>, Id, Id) {
+    let options = CreateOptionsBuilder::new(Cipher::None)
+        .build::<MemoryBackend>()
+        .unwrap();
+    let mut container = Container::create(MemoryBackend::new(), options).unwrap();
+
+    let id1 = container.aquire().unwrap();
+    let id2 = container.aquire().unwrap();
+
+    container.write(&id1, &[1; 512]).unwrap();
+    container.write(&id2, &[2; 512]).unwrap();
+
+    (container, id1, id2)
+}
+
+fn reopen(container: Container<MemoryBackend>) -> Container<MemoryBackend> {
+    let options = OpenOptionsBuilder::new().build::<MemoryBackend>().unwrap();
+
+    Container::open(container.into_backend(), options).unwrap()
+}
+
+/// Performs the first half of a commit: the journal is written and
+/// registered in the header, but the blocks are not updated yet.
+fn interrupted_commit(container: &mut Container<MemoryBackend>, writes: &[(&Id, u8)]) -> Id {
+    let mut entries = vec![];
+
+    for (id, n) in writes {
+        let copy = container.aquire().unwrap();
+
+        container.write_block(&copy, &[*n; 512]).unwrap();
+        entries.push((**id, Some(copy)));
+    }
+
+    let first = container.write_journal(entries, None).unwrap().remove(0);
+
+    container
+        .update_header(|header| header.set_journal(Some(first)))
+        .unwrap();
+
+    first
+}
+
+#[test]
+fn recover_none() {
+    let (container, id1, id2) = setup_container();
+    let container = reopen(container);
+
+    assert!(container.header.journal().is_none());
+    assert_eq!(container.backend().get(&id1).unwrap(), [1; 512]);
+    assert_eq!(container.backend().get(&id2).unwrap(), [2; 512]);
+}
+
+#[test]
+fn recover_interrupted() {
+    let (mut container, id1, id2) = setup_container();
+
+    let journal_id = interrupted_commit(&mut container, &[(&id1, 3), (&id2, 4)]);
+
+    assert_eq!(container.backend().get(&id1).unwrap(), [1; 512]);
+    assert_eq!(container.backend().get(&id2).unwrap(), [2; 512]);
+
+    let container = reopen(container);
+
+    assert!(container.header.journal().is_none());
+    assert!(container.backend().get(&journal_id).is_none());
+    assert_eq!(container.backend().get(&id1).unwrap(), [3; 512]);
+    assert_eq!(container.backend().get(&id2).unwrap(), [4; 512]);
+}
+
+#[test]
+fn recover_partially_applied() {
+    let (mut container, id1, id2) = setup_container();
+
+    interrupted_commit(&mut container, &[(&id1, 3), (&id2, 4)]);
+    container.write_block(&id1, &[3; 512]).unwrap();
+
+    let container = reopen(container);
+
+    assert!(container.header.journal().is_none());
+    assert_eq!(container.backend().get(&id1).unwrap(), [3; 512]);
+    assert_eq!(container.backend().get(&id2).unwrap(), [4; 512]);
+}
+
+#[test]
+fn recover_chained() {
+    let (mut container, id1, _) = setup_container();
+    let ids = (0..60)
+        .map(|_| container.aquire().unwrap())
+        .collect::<Vec<_>>();
+    let writes = ids
+        .iter()
+        .enumerate()
+        .map(|(idx, id)| (id, idx as u8))
+        .chain([(&id1, 0xff)])
+        .collect::<Vec<_>>();
+
+    interrupted_commit(&mut container, &writes);
+
+    let next = {
+        let mut buf = [0; 512];
+        let id = *container.header.journal().unwrap();
+
+        container.read_block(&id, &mut buf).unwrap();
+        crate::journal::Descriptor::<MemoryBackend>::get_from_buffer(&mut &buf[..])
+            .unwrap()
+            .next
+    };
+    assert!(next.is_some());
+
+    let container = reopen(container);
+
+    assert!(container.header.journal().is_none());
+    assert_eq!(container.backend().get(&id1).unwrap(), [0xff; 512]);
+
+    for (idx, id) in ids.iter().enumerate() {
+        assert_eq!(container.backend().get(id).unwrap(), [idx as u8; 512]);
+    }
+}
+
+#[test]
+fn recover_read_only() {
+    let (mut container, id1, id2) = setup_container();
+
+    let journal_id = interrupted_commit(&mut container, &[(&id1, 3), (&id2, 4)]);
+
+    let options = OpenOptionsBuilder::new()
+        .read_only(true)
+        .build::<MemoryBackend>()
+        .unwrap();
+    let mut container = Container::open(container.into_backend(), options).unwrap();
+
+    assert_eq!(container.header.journal(), Some(&journal_id));
+    assert_eq!(container.backend().get(&id1).unwrap(), [1; 512]);
+    assert_eq!(container.backend().get(&id2).unwrap(), [2; 512]);
+
+    let mut buf = [0; 512];
+
+    container.read(&id1, &mut buf).unwrap();
+    assert_eq!(buf, [3; 512]);
+
+    container.read(&id2, &mut buf).unwrap();
+    assert_eq!(buf, [4; 512]);
+
+    assert!(!container.in_transaction());
+}
+
+#[test]
+fn write_aquired() {
+    let (mut container, id1, _) = setup_container();
+
+    container.begin().unwrap();
+
+    let id = container.aquire().unwrap();
+
+    container.write(&id, &[3; 512]).unwrap();
+    container.write(&id1, &[4; 512]).unwrap();
+
+    // written immediately, not referenced outside of the transaction
+    assert_eq!(container.backend().get(&id).unwrap(), [3; 512]);
+    assert_eq!(container.backend().get(&id1).unwrap(), [1; 512]);
+
+    container.commit().unwrap();
+
+    assert_eq!(container.backend().get(&id).unwrap(), [3; 512]);
+    assert_eq!(container.backend().get(&id1).unwrap(), [4; 512]);
+}
+
+#[test]
+fn log_aquired() {
+    let (mut container, _, _) = setup_container();
+
+    container.begin().unwrap();
+
+    let ids = (0..50)
+        .map(|_| container.aquire().unwrap())
+        .collect::<Vec<_>>();
+
+    // the first 49 blocks fill a journal block
+    let journal = container.journal.as_ref().unwrap();
+    assert_eq!(journal.logged, 49);
+    assert_eq!(journal.log.len(), 1);
+    assert_eq!(container.header.journal(), journal.log.first());
+
+    let log = journal.log.clone();
+
+    container.commit().unwrap();
+
+    assert!(container.header.journal().is_none());
+    assert!(container.backend().get(&log[0]).is_none());
+
+    for id in ids {
+        assert!(container.backend().get(&id).is_some());
+    }
+}
+
+#[test]
+fn log_aquired_rollback() {
+    let (mut container, _, _) = setup_container();
+
+    container.begin().unwrap();
+
+    let ids = (0..50)
+        .map(|_| container.aquire().unwrap())
+        .collect::<Vec<_>>();
+    let log = container.journal.as_ref().unwrap().log.clone();
+
+    container.rollback().unwrap();
+
+    assert!(container.header.journal().is_none());
+    assert!(container.backend().get(&log[0]).is_none());
+
+    for id in ids {
+        assert!(container.backend().get(&id).is_none());
+    }
+}
+
+#[test]
+fn recover_aquired() {
+    let (mut container, id1, _) = setup_container();
+
+    container.begin().unwrap();
+    container.write(&id1, &[3; 512]).unwrap();
+
+    let ids = (0..100)
+        .map(|_| container.aquire().unwrap())
+        .collect::<Vec<_>>();
+    let log = container.journal.as_ref().unwrap().log.clone();
+    assert_eq!(log.len(), 2);
+
+    // interrupted before commit
+    let container = reopen(container);
+
+    assert!(container.header.journal().is_none());
+    assert_eq!(container.backend().get(&id1).unwrap(), [1; 512]);
+
+    for id in log.iter().chain(ids[..98].iter()) {
+        assert!(container.backend().get(id).is_none());
+    }
+
+    // not recorded yet
+    for id in ids[98..].iter() {
+        assert!(container.backend().get(id).is_some());
+    }
+}
+
+#[test]
+fn recover_released() {
+    let (mut container, id1, id2) = setup_container();
+
+    container.begin().unwrap();
+    container.write(&id1, &[3; 512]).unwrap();
+    container.release(id2).unwrap();
+
+    for _ in 0..49 {
+        container.aquire().unwrap();
+    }
+
+    let log = container.journal.as_ref().unwrap().log.clone();
+
+    // commit interrupted after the header points to the journal
+    let journal = container.journal.take().unwrap();
+    let copy = container.aquire_block().unwrap();
+
+    container.write_block(&copy, &[3; 512]).unwrap();
+
+    let entries = vec![(id1, Some(copy)), (id2, None), (log[0], None)];
+    let first = container.write_journal(entries, None).unwrap().remove(0);
+
+    container
+        .update_header(|header| header.set_journal(Some(first)))
+        .unwrap();
+
+    let container = reopen(container);
+
+    assert!(container.header.journal().is_none());
+    assert_eq!(container.backend().get(&id1).unwrap(), [3; 512]);
+
+    for id in [id2, log[0], copy, first].iter() {
+        assert!(container.backend().get(id).is_none());
+    }
+
+    // owned by the committed transaction
+    for id in journal.aquired.iter() {
+        assert!(container.backend().get(id).is_some());
+    }
+}
@@ -0,0 +1,65 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+
+use crate::svec::SecureVec;
+
+/// The wrapping key of a container.
+///
+/// The secret part of the header is encrypted with the wrapping key. Usually
+/// the wrapping key is derived from the password by the [`Kdf`]. Once a
+/// container is opened, its wrapping key is available with
+/// [`Container::wrapping_key`]. Pass it to
+/// [`OpenOptionsBuilder::with_wrapping_key`] to open the container again
+/// without asking for the password and without running the key derivation
+/// function.
+///
+/// The wrapping key unlocks the container just like the password, so keep
+/// it secret!
+///
+/// [`Kdf`]: crate::Kdf
+/// [`Container::wrapping_key`]: crate::Container::wrapping_key
+/// [`OpenOptionsBuilder::with_wrapping_key`]: crate::OpenOptionsBuilder::with_wrapping_key
+#[derive(Clone, PartialEq)]
+pub struct WrappingKey(SecureVec);
+
+impl WrappingKey {
+    /// Creates a wrapping key from its raw bytes.
+    pub fn from_bytes(bytes: &[u8]) -> WrappingKey {
+        WrappingKey(bytes.into())
+    }
+
+    /// Returns the raw bytes of the wrapping key.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for WrappingKey {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("WrappingKey").field(&"***").finish()
+    }
+}
@@ -0,0 +1,120 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#![cfg(feature = "async")]
+
+use nuts_container::{
+    AsyncContainer, Cipher, Container, CreateOptionsBuilder, Error, IntegrityError, JournalError,
+};
+use nuts_memory::{Error as MemoryError, MemoryBackend};
+
+fn setup_container() -> Container<MemoryBackend> {
+    let options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::create(MemoryBackend::new(), options).unwrap()
+}
+
+#[tokio::test]
+async fn aquire() {
+    let mut container = AsyncContainer::new(setup_container()).unwrap();
+    let mut buf = vec![0xff; container.block_size() as usize];
+
+    let id = container.aquire().await.unwrap();
+
+    assert_eq!(container.read(&id, &mut buf).await.unwrap(), buf.len());
+    assert!(buf.iter().all(|b| *b == 0));
+}
+
+#[tokio::test]
+async fn write_read() {
+    let mut container = AsyncContainer::new(setup_container()).unwrap();
+    let mut buf = vec![0; container.block_size() as usize];
+
+    let id = container.aquire().await.unwrap();
+
+    assert_eq!(container.write(&id, b"abc").await.unwrap(), 3);
+    assert_eq!(container.read(&id, &mut buf).await.unwrap(), buf.len());
+    assert_eq!(buf[..3], *b"abc");
+    assert!(buf[3..].iter().all(|b| *b == 0));
+
+    // The sync container reads the same data
+    let mut container = container.into_container();
+    let mut buf2 = vec![0; container.block_size() as usize];
+
+    container.read(&id, &mut buf2).unwrap();
+    assert_eq!(buf, buf2);
+}
+
+#[tokio::test]
+async fn release() {
+    let mut container = AsyncContainer::new(setup_container()).unwrap();
+    let mut buf = vec![0; container.block_size() as usize];
+
+    let id = container.aquire().await.unwrap();
+    container.release(id).await.unwrap();
+
+    let err = container.read(&id, &mut buf).await.unwrap_err();
+    assert!(matches!(err, Error::Backend(MemoryError::NoSuchId(n)) if n == id));
+}
+
+#[tokio::test]
+async fn spawn() {
+    let mut container = AsyncContainer::new(setup_container()).unwrap();
+
+    let handle = tokio::spawn(async move {
+        let id = container.aquire().await.unwrap();
+        container.write(&id, b"abc").await.unwrap();
+
+        let mut buf = vec![0; 3];
+        container.read(&id, &mut buf).await.unwrap();
+
+        buf
+    });
+
+    assert_eq!(handle.await.unwrap(), b"abc");
+}
+
+#[test]
+fn new_in_transaction() {
+    let mut container = setup_container();
+
+    container.begin().unwrap();
+
+    let err = AsyncContainer::new(container).unwrap_err();
+    assert!(matches!(err, Error::Journal(JournalError::Active)));
+}
+
+#[test]
+fn new_with_rollback_protection() {
+    let options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .with_rollback_protection(true)
+        .build::<MemoryBackend>()
+        .unwrap();
+    let container = Container::create(MemoryBackend::new(), options).unwrap();
+
+    let err = AsyncContainer::new(container).unwrap_err();
+    assert!(matches!(err, Error::Integrity(IntegrityError::Unsupported)));
+}
@@ -0,0 +1,129 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::{Backend, HEADER_MAX_SIZE};
+use nuts_container::{
+    BackupError, Cipher, CipherError, Container, CreateOptionsBuilder, Error, HeaderError,
+    OpenOptionsBuilder,
+};
+use nuts_memory::{Id, MemoryBackend};
+
+fn setup_container() -> (Container<MemoryBackend>, Id) {
+    let options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    let mut container = Container::create(MemoryBackend::new(), options).unwrap();
+    let id = container.aquire().unwrap();
+
+    container.write(&id, b"abc").unwrap();
+
+    (container, id)
+}
+
+fn corrupt_header(container: Container<MemoryBackend>) -> MemoryBackend {
+    let mut backend = container.into_backend();
+
+    backend.write_header(&[0xff; HEADER_MAX_SIZE]).unwrap();
+
+    backend
+}
+
+fn open(
+    backend: MemoryBackend,
+    password: &'static [u8],
+) -> Result<Container<MemoryBackend>, Error<MemoryBackend>> {
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(move || Ok(password.to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::open(backend, options)
+}
+
+fn restore(
+    backend: MemoryBackend,
+    password: &'static [u8],
+    backup: &[u8],
+) -> Result<Container<MemoryBackend>, Error<MemoryBackend>> {
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(move || Ok(password.to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::restore_header(backend, options, backup)
+}
+
+#[test]
+fn backup_restore() {
+    let (mut container, id) = setup_container();
+    let backup = container.backup_header().unwrap();
+    let backend = corrupt_header(container);
+
+    let mut container = restore(backend, b"abc", &backup).unwrap();
+    let mut buf = [0; 3];
+
+    container.read(&id, &mut buf).unwrap();
+    assert_eq!(buf, *b"abc");
+
+    // the header is written back into the backend
+    let mut container = open(container.into_backend(), b"abc").unwrap();
+    let mut buf = [0; 3];
+
+    container.read(&id, &mut buf).unwrap();
+    assert_eq!(buf, *b"abc");
+}
+
+#[test]
+fn restore_wrong_password() {
+    let (mut container, _) = setup_container();
+    let backup = container.backup_header().unwrap();
+    let backend = corrupt_header(container);
+
+    let err = restore(backend, b"xxx", &backup).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Header(HeaderError::Cipher(CipherError::NotTrustworthy))
+    ));
+}
+
+#[test]
+fn restore_inval_checksum() {
+    let (mut container, _) = setup_container();
+    let mut backup = container.backup_header().unwrap();
+    let backend = corrupt_header(container);
+
+    backup[100] ^= 0xff;
+
+    let err = restore(backend, b"abc", &backup).unwrap_err();
+    assert!(matches!(err, Error::Backup(BackupError::InvalidChecksum)));
+}
+
+#[test]
+fn restore_inval_backup() {
+    let (container, _) = setup_container();
+    let backend = corrupt_header(container);
+
+    let err = restore(backend, b"abc", b"xxx").unwrap_err();
+    assert!(matches!(err, Error::Backup(BackupError::InvalidBackup)));
+}
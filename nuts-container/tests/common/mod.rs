@@ -88,3 +88,42 @@ impl ServiceFactory<MemoryBackend> for SampleService {
         Ok(SampleService(container))
     }
 }
+
+#[derive(Debug)]
+pub struct OtherService(Container<MemoryBackend>);
+
+impl OtherService {
+    #[allow(dead_code)]
+    pub fn into_container(self) -> Container<MemoryBackend> {
+        self.0
+    }
+}
+
+impl Service<MemoryBackend> for OtherService {
+    type Migration = SampleMigration;
+
+    fn sid() -> u32 {
+        4711
+    }
+
+    fn need_top_id() -> bool {
+        true
+    }
+
+    fn migration() -> SampleMigration {
+        SampleMigration
+    }
+}
+
+impl ServiceFactory<MemoryBackend> for OtherService {
+    type Service = Self;
+    type Err = SampleError;
+
+    fn create(container: Container<MemoryBackend>) -> Result<Self::Service, Self::Err> {
+        Ok(OtherService(container))
+    }
+
+    fn open(container: Container<MemoryBackend>) -> Result<Self::Service, Self::Err> {
+        Ok(OtherService(container))
+    }
+}
@@ -28,7 +28,7 @@ use nuts_container::{
 use nuts_memory::MemoryBackend;
 use std::fs::File;
 
-use crate::common::{fixture_path, SampleService};
+use crate::common::{fixture_path, OtherService, SampleService};
 
 #[test]
 fn inval_revision() {
@@ -45,7 +45,7 @@ fn inval_revision() {
 
     assert!(matches!(err.0, Error::Header(cause)
         if matches!(cause,HeaderError::InvalidRevision(expected, got)
-            if expected == 2 && got == 0)));
+            if expected == 3 && got == 0)));
 }
 
 #[test]
@@ -65,6 +65,43 @@ fn already_attached() {
     let err = Container::create_service::<SampleService>(container).unwrap_err();
 
     assert!(matches!(err.0, Error::Header(cause)
-        if matches!(cause,HeaderError::UnexpectedSid { expected, got }
-            if expected.is_none() && got.unwrap() == 666)));
+        if matches!(cause,HeaderError::ServiceExists(sid) if sid == 666)));
+}
+
+#[test]
+fn multiple_services() {
+    let backend = {
+        let options = CreateOptionsBuilder::new(Cipher::None)
+            .build::<MemoryBackend>()
+            .unwrap();
+        let container = Container::create(MemoryBackend::new(), options).unwrap();
+        let service = Container::create_service::<SampleService>(container).unwrap();
+        let container = service.into_container();
+
+        assert!(container.top_id().is_none());
+
+        let service = Container::create_service::<OtherService>(container).unwrap();
+        let container = service.into_container();
+
+        assert_eq!(container.top_id().unwrap().to_string(), "1");
+        assert_eq!(container.info().unwrap().services, [666, 4711]);
+
+        container.into_backend()
+    };
+
+    let options = OpenOptionsBuilder::new().build::<MemoryBackend>().unwrap();
+    let container = Container::open(backend, options).unwrap();
+
+    assert!(container.top_id().is_none());
+    assert_eq!(container.info().unwrap().services, [666, 4711]);
+
+    let service = Container::open_service::<OtherService>(container, false).unwrap();
+    let container = service.into_container();
+
+    assert_eq!(container.top_id().unwrap().to_string(), "1");
+
+    let service = Container::open_service::<SampleService>(container, false).unwrap();
+    let container = service.into_container();
+
+    assert!(container.top_id().is_none());
 }
@@ -0,0 +1,144 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::{Backend, ReceiveHeader, HEADER_MAX_SIZE};
+use nuts_container::{
+    Cipher, Container, CreateOptionsBuilder, Error, ModifyOptionsBuilder, OpenOptionsBuilder,
+};
+use nuts_memory::{Id, MemoryBackend};
+
+fn setup_container() -> (MemoryBackend, Id) {
+    let options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    let mut container = Container::create(MemoryBackend::new(), options).unwrap();
+    let id = container.aquire().unwrap();
+
+    container.write(&id, b"abc").unwrap();
+
+    (container.into_backend(), id)
+}
+
+fn open(
+    backend: MemoryBackend,
+    password: &'static [u8],
+) -> Result<Container<MemoryBackend>, Error<MemoryBackend>> {
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(move || Ok(password.to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::open(backend, options)
+}
+
+fn read(container: &mut Container<MemoryBackend>, id: &Id) -> [u8; 3] {
+    let mut buf = [0; 3];
+
+    container.read(id, &mut buf).unwrap();
+
+    buf
+}
+
+#[test]
+fn create() {
+    let (mut backend, _) = setup_container();
+    let mut header = [0; HEADER_MAX_SIZE];
+    let mut backup = [0; HEADER_MAX_SIZE];
+
+    backend.get_header_bytes(&mut header).unwrap();
+    assert!(backend.get_backup_header_bytes(&mut backup).unwrap());
+    assert_eq!(header, backup);
+}
+
+#[test]
+fn update() {
+    let (backend, _) = setup_container();
+    let mut container = open(backend, b"abc").unwrap();
+
+    let options = ModifyOptionsBuilder::default()
+        .change_password(|| Ok(b"xxx".to_vec()))
+        .build();
+
+    container.modify(options).unwrap();
+
+    let mut backend = container.into_backend();
+    let mut header = [0; HEADER_MAX_SIZE];
+    let mut backup = [0; HEADER_MAX_SIZE];
+
+    backend.get_header_bytes(&mut header).unwrap();
+    assert!(backend.get_backup_header_bytes(&mut backup).unwrap());
+    assert_eq!(header, backup);
+}
+
+#[test]
+fn corrupted_header() {
+    let (mut backend, id) = setup_container();
+
+    backend.write_header(&[0xff; HEADER_MAX_SIZE]).unwrap();
+
+    let mut container = open(backend, b"abc").unwrap();
+    assert_eq!(read(&mut container, &id), *b"abc");
+
+    // the header was repaired from the backup
+    let mut backend = container.into_backend();
+    let mut header = [0; HEADER_MAX_SIZE];
+    let mut backup = [0; HEADER_MAX_SIZE];
+
+    backend.get_header_bytes(&mut header).unwrap();
+    assert!(backend.get_backup_header_bytes(&mut backup).unwrap());
+    assert_eq!(header, backup);
+}
+
+#[test]
+fn corrupted_backup() {
+    let (mut backend, id) = setup_container();
+
+    backend
+        .write_backup_header(&[0xff; HEADER_MAX_SIZE])
+        .unwrap();
+
+    let mut container = open(backend, b"abc").unwrap();
+    assert_eq!(read(&mut container, &id), *b"abc");
+}
+
+#[test]
+fn corrupted_both() {
+    let (mut backend, _) = setup_container();
+
+    backend.write_header(&[0xff; HEADER_MAX_SIZE]).unwrap();
+    backend
+        .write_backup_header(&[0xff; HEADER_MAX_SIZE])
+        .unwrap();
+
+    let err = open(backend, b"abc").unwrap_err();
+    assert!(matches!(err, Error::Header(_)));
+}
+
+#[test]
+fn wrong_password() {
+    let (backend, _) = setup_container();
+
+    let err = open(backend, b"xxx").unwrap_err();
+    assert!(matches!(err, Error::Header(_)));
+}
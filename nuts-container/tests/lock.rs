@@ -0,0 +1,110 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#![cfg(unix)]
+
+use nuts_container::{Cipher, Container, CreateOptionsBuilder, Error, OpenOptionsBuilder};
+use nuts_directory::{CreateOptions, DirectoryBackend, Error as DirectoryError, OpenOptions};
+use std::path::{Path, PathBuf};
+use tempfile::{Builder, TempDir};
+
+type OpenResult =
+    std::result::Result<Container<DirectoryBackend<PathBuf>>, Error<DirectoryBackend<PathBuf>>>;
+
+fn setup() -> TempDir {
+    let dir = Builder::new().prefix("nuts-container").tempdir().unwrap();
+
+    let backend_options = CreateOptions::for_path(dir.path().to_owned());
+    let options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<DirectoryBackend<PathBuf>>()
+        .unwrap();
+
+    Container::create(backend_options, options).unwrap();
+
+    dir
+}
+
+/// Opens the container with a new backend instance, thus the lock is taken
+/// on another file descriptor.
+fn open(path: &Path, read_only: bool) -> OpenResult {
+    let backend_options = OpenOptions::for_path(path.to_owned());
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .read_only(read_only)
+        .build::<DirectoryBackend<PathBuf>>()
+        .unwrap();
+
+    Container::open(backend_options, options)
+}
+
+fn assert_locked(result: OpenResult) {
+    let err = result.unwrap_err();
+    assert!(matches!(err, Error::Backend(DirectoryError::Locked)));
+}
+
+#[test]
+fn read_write() {
+    let dir = setup();
+
+    let container = open(dir.path(), false).unwrap();
+
+    assert_locked(open(dir.path(), false));
+    assert_locked(open(dir.path(), true));
+
+    drop(container);
+
+    open(dir.path(), false).unwrap();
+}
+
+#[test]
+fn read_only() {
+    let dir = setup();
+
+    let container1 = open(dir.path(), true).unwrap();
+    let container2 = open(dir.path(), true).unwrap();
+
+    assert_locked(open(dir.path(), false));
+
+    drop(container1);
+    drop(container2);
+
+    open(dir.path(), false).unwrap();
+}
+
+#[test]
+fn create() {
+    let dir = Builder::new().prefix("nuts-container").tempdir().unwrap();
+
+    let backend_options = CreateOptions::for_path(dir.path().to_owned());
+    let options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<DirectoryBackend<PathBuf>>()
+        .unwrap();
+    let container = Container::create(backend_options, options).unwrap();
+
+    assert_locked(open(dir.path(), false));
+
+    drop(container);
+
+    open(dir.path(), false).unwrap();
+}
@@ -0,0 +1,134 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::Backend;
+use nuts_container::{
+    Cipher, Container, CreateOptionsBuilder, Error, IntegrityError, OpenOptionsBuilder,
+};
+use nuts_memory::MemoryBackend;
+
+fn create(rollback_protection: bool) -> Container<MemoryBackend> {
+    let options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .with_rollback_protection(rollback_protection)
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::create(MemoryBackend::new(), options).unwrap()
+}
+
+fn reopen(container: Container<MemoryBackend>) -> Container<MemoryBackend> {
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::open(container.into_backend(), options).unwrap()
+}
+
+#[test]
+fn read_many() {
+    let mut container = create(false);
+    let id1 = container.aquire().unwrap();
+    let id2 = container.aquire().unwrap();
+
+    container.write(&id1, b"abc").unwrap();
+    container.write(&id2, b"xyz").unwrap();
+
+    let blocks = container.read_many(&[id2, id1, id2]).unwrap();
+    let block_size = container.block_size() as usize;
+
+    assert_eq!(blocks.len(), 3);
+    assert!(blocks.iter().all(|buf| buf.len() == block_size));
+    assert_eq!(blocks[0][..4], *b"xyz\0");
+    assert_eq!(blocks[1][..4], *b"abc\0");
+    assert_eq!(blocks[2][..4], *b"xyz\0");
+
+    assert!(container.read_many(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn write_many() {
+    let mut container = create(true);
+    let id1 = container.aquire().unwrap();
+    let id2 = container.aquire().unwrap();
+
+    let nbytes = container
+        .write_many(&[(id1, b"abc".as_slice()), (id2, b"xyz".as_slice())])
+        .unwrap();
+    assert_eq!(nbytes, [3, 3]);
+
+    let mut container = reopen(container);
+    let blocks = container.read_many(&[id1, id2]).unwrap();
+
+    assert_eq!(blocks[0][..4], *b"abc\0");
+    assert_eq!(blocks[1][..4], *b"xyz\0");
+}
+
+#[test]
+fn write_many_transaction() {
+    let mut container = create(false);
+    let id1 = container.aquire().unwrap();
+    let id2 = container.aquire().unwrap();
+
+    container.write(&id1, b"abc").unwrap();
+
+    container.begin().unwrap();
+    container.write_many(&[(id2, b"xyz".as_slice())]).unwrap();
+
+    let blocks = container.read_many(&[id1, id2]).unwrap();
+    assert_eq!(blocks[0][..4], *b"abc\0");
+    assert_eq!(blocks[1][..4], *b"xyz\0");
+
+    container.rollback().unwrap();
+
+    let blocks = container.read_many(&[id1, id2]).unwrap();
+    assert_eq!(blocks[0][..4], *b"abc\0");
+    assert_eq!(blocks[1][..4], *b"\0\0\0\0");
+}
+
+#[test]
+fn read_many_rolled_back() {
+    let mut container = create(true);
+    let id1 = container.aquire().unwrap();
+    let id2 = container.aquire().unwrap();
+
+    container.write(&id1, b"abc").unwrap();
+    container.write(&id2, b"abc").unwrap();
+    let old = container.backend().get(&id2).unwrap().to_vec();
+    container.write(&id2, b"xyz").unwrap();
+
+    let mut backend = container.into_backend();
+    backend.write(&id2, &old).unwrap();
+
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+    let mut container = Container::open(backend, options).unwrap();
+
+    let err = container.read_many(&[id1, id2]).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Integrity(IntegrityError::StaleBlock(id)) if id == id2.to_string()
+    ));
+}
@@ -22,11 +22,13 @@
 
 mod common;
 
-use nuts_container::{Container, OpenOptionsBuilder};
+use nuts_container::{
+    Cipher, Container, CreateOptionsBuilder, Error, HeaderError, OpenOptionsBuilder,
+};
 use nuts_memory::MemoryBackend;
 use std::fs::File;
 
-use crate::common::{fixture_path, SampleService};
+use crate::common::{fixture_path, OtherService, SampleService};
 
 fn open_backend_from_fixture(dir: &str, name: &str) -> MemoryBackend {
     let path = fixture_path(dir, name);
@@ -65,10 +67,38 @@ macro_rules! t {
 }
 
 t!(open_0_6_8_no_migration(false), "0.6.8.json", 0 -> 0);
-t!(open_0_6_8_migration(true), "0.6.8.json", 0 -> 2);
+t!(open_0_6_8_migration(true), "0.6.8.json", 0 -> 3);
 t!(open_0_7_0_no_migration(false), "0.7.0.json", 1 -> 1);
-t!(open_0_7_0_migration(true), "0.7.0.json", 1 -> 2);
+t!(open_0_7_0_migration(true), "0.7.0.json", 1 -> 3);
 t!(open_0_7_1_no_migration(false), "0.7.1.json", 1 -> 1);
-t!(open_0_7_1_migration(true), "0.7.1.json", 1 -> 2);
+t!(open_0_7_1_migration(true), "0.7.1.json", 1 -> 3);
 t!(open_0_7_3_no_migration(false), "0.7.3.json", 2 -> 2);
-t!(open_0_7_3_migration(true), "0.7.3.json", 2 -> 2);
+t!(open_0_7_3_migration(true), "0.7.3.json", 2 -> 3);
+
+#[test]
+fn no_such_service() {
+    let options = CreateOptionsBuilder::new(Cipher::None)
+        .build::<MemoryBackend>()
+        .unwrap();
+    let container = Container::create(MemoryBackend::new(), options).unwrap();
+    let service = Container::create_service::<SampleService>(container).unwrap();
+    let container = service.into_container();
+
+    let err = Container::open_service::<OtherService>(container, false).unwrap_err();
+
+    assert!(matches!(err.0, Error::Header(cause)
+        if matches!(cause, HeaderError::NoSuchService(sid) if sid == 4711)));
+}
+
+#[test]
+fn add_service_after_migration() {
+    let backend = open_backend_from_fixture("service", "0.7.3.json");
+    let container = open_container(backend);
+    let service = Container::open_service::<SampleService>(container, true).unwrap();
+
+    let service = Container::create_service::<OtherService>(service.into_container()).unwrap();
+    let container = service.into_container();
+
+    assert_eq!(container.info().unwrap().revision, 3);
+    assert_eq!(container.info().unwrap().services, [666, 4711]);
+}
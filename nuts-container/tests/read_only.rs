@@ -0,0 +1,152 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::{Backend, ReceiveHeader, HEADER_MAX_SIZE};
+use nuts_container::{
+    Cipher, Container, CreateOptionsBuilder, Error, ModifyOptionsBuilder, OpenOptionsBuilder,
+};
+use nuts_memory::{Id, MemoryBackend};
+
+fn setup_container() -> (MemoryBackend, Id) {
+    let options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+    let mut container = Container::create(MemoryBackend::new(), options).unwrap();
+    let id = container.aquire().unwrap();
+
+    container.write(&id, b"abc").unwrap();
+
+    (container.into_backend(), id)
+}
+
+fn open_read_only(backend: MemoryBackend) -> Container<MemoryBackend> {
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .read_only(true)
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::open(backend, options).unwrap()
+}
+
+#[test]
+fn read() {
+    let (backend, id) = setup_container();
+    let mut container = open_read_only(backend);
+    let mut buf = [0; 3];
+
+    assert!(container.is_read_only());
+    assert_eq!(container.read(&id, &mut buf).unwrap(), 3);
+    assert_eq!(buf, *b"abc");
+}
+
+#[test]
+fn write() {
+    let (backend, id) = setup_container();
+    let mut container = open_read_only(backend);
+    let mut buf = [0; 3];
+
+    let err = container.write(&id, b"xyz").unwrap_err();
+    assert!(matches!(err, Error::ReadOnly));
+
+    container.read(&id, &mut buf).unwrap();
+    assert_eq!(buf, *b"abc");
+}
+
+#[test]
+fn aquire() {
+    let (backend, _) = setup_container();
+    let mut container = open_read_only(backend);
+
+    let err = container.aquire().unwrap_err();
+    assert!(matches!(err, Error::ReadOnly));
+}
+
+#[test]
+fn release() {
+    let (backend, id) = setup_container();
+    let mut container = open_read_only(backend);
+
+    let err = container.release(id).unwrap_err();
+    assert!(matches!(err, Error::ReadOnly));
+    assert!(container.backend().get(&id).is_some());
+}
+
+#[test]
+fn modify() {
+    let (backend, _) = setup_container();
+    let mut container = open_read_only(backend);
+    let options = ModifyOptionsBuilder::default()
+        .change_password(|| Ok(b"xyz".to_vec()))
+        .build();
+
+    let err = container.modify(options).unwrap_err();
+    assert!(matches!(err, Error::ReadOnly));
+}
+
+#[test]
+fn begin() {
+    let (backend, _) = setup_container();
+    let mut container = open_read_only(backend);
+
+    let err = container.begin().unwrap_err();
+    assert!(matches!(err, Error::ReadOnly));
+    assert!(!container.in_transaction());
+}
+
+#[test]
+fn backup_header_not_restored() {
+    let (backend, id) = setup_container();
+    let mut container = Container::open(
+        backend,
+        OpenOptionsBuilder::new()
+            .with_password_callback(|| Ok(b"abc".to_vec()))
+            .build::<MemoryBackend>()
+            .unwrap(),
+    )
+    .unwrap();
+    let backup = container.backup_header().unwrap();
+    let mut backend = container.into_backend();
+
+    backend.write_header(&[0xff; HEADER_MAX_SIZE]).unwrap();
+
+    let mut container = open_read_only(backend);
+    let mut buf = [0; 3];
+
+    container.read(&id, &mut buf).unwrap();
+    assert_eq!(buf, *b"abc");
+
+    let mut header = [0; HEADER_MAX_SIZE];
+    let mut backend = container.into_backend();
+
+    backend.get_header_bytes(&mut header).unwrap();
+    assert_eq!(header, [0xff; HEADER_MAX_SIZE]);
+
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .read_only(true)
+        .build::<MemoryBackend>()
+        .unwrap();
+    let err = Container::restore_header(backend, options, &backup).unwrap_err();
+    assert!(matches!(err, Error::ReadOnly));
+}
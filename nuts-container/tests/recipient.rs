@@ -0,0 +1,259 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_container::{
+    Cipher, CipherError, Container, CreateOptionsBuilder, Error, HeaderError, ModifyOptionsBuilder,
+    OpenOptionsBuilder, PrivateKey,
+};
+use nuts_memory::{Id, MemoryBackend};
+
+const CIPHERS: [Cipher; 3] = [Cipher::Aes128Ctr, Cipher::Aes128Gcm, Cipher::Aes128CtrHmac];
+
+fn setup_container(
+    cipher: Cipher,
+    recipient: Option<&PrivateKey>,
+    password: bool,
+) -> (MemoryBackend, Id) {
+    let mut builder = CreateOptionsBuilder::new(cipher).with_rollback_protection(true);
+
+    if let Some(key) = recipient {
+        builder = builder.with_recipient(key.public_key().unwrap());
+    }
+
+    if password {
+        builder = builder.with_password_callback(|| Ok(b"abc".to_vec()));
+    }
+
+    let options = builder.build::<MemoryBackend>().unwrap();
+    let mut container = Container::create(MemoryBackend::new(), options).unwrap();
+    let id = container.aquire().unwrap();
+
+    container.write(&id, b"abc").unwrap();
+
+    (container.into_backend(), id)
+}
+
+fn open(
+    backend: MemoryBackend,
+    password: &'static [u8],
+) -> Result<Container<MemoryBackend>, Error<MemoryBackend>> {
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(move || Ok(password.to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::open(backend, options)
+}
+
+fn open_private_key(
+    backend: MemoryBackend,
+    private_key: &PrivateKey,
+) -> Result<Container<MemoryBackend>, Error<MemoryBackend>> {
+    let options = OpenOptionsBuilder::new()
+        .with_private_key(private_key.clone())
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::open(backend, options)
+}
+
+fn read(container: &mut Container<MemoryBackend>, id: &Id) -> [u8; 3] {
+    let mut buf = [0; 3];
+
+    container.read(id, &mut buf).unwrap();
+
+    buf
+}
+
+#[test]
+fn no_recipient() {
+    let (backend, _) = setup_container(Cipher::Aes128Gcm, None, true);
+
+    let container = open(backend, b"abc").unwrap();
+    assert!(!container.info().unwrap().recipient);
+
+    let key = PrivateKey::generate().unwrap();
+    let err = open_private_key(container.into_backend(), &key).unwrap_err();
+    assert!(matches!(err, Error::Header(HeaderError::NoRecipient)));
+}
+
+#[test]
+fn cipher_none() {
+    let key = PrivateKey::generate().unwrap();
+    let (backend, _) = setup_container(Cipher::None, Some(&key), false);
+
+    let container = open(backend, b"").unwrap();
+    assert!(!container.info().unwrap().recipient);
+}
+
+#[test]
+fn info() {
+    let key = PrivateKey::generate().unwrap();
+    let (backend, _) = setup_container(Cipher::Aes128Gcm, Some(&key), true);
+
+    let container = open(backend, b"abc").unwrap();
+    assert!(container.info().unwrap().recipient);
+}
+
+#[test]
+fn open_with_private_key() {
+    for cipher in CIPHERS {
+        let key = PrivateKey::generate().unwrap();
+        let (backend, id) = setup_container(cipher, Some(&key), true);
+
+        let mut container = open_private_key(backend, &key).unwrap();
+        assert_eq!(read(&mut container, &id), *b"abc");
+
+        let mut container = open(container.into_backend(), b"abc").unwrap();
+        assert_eq!(read(&mut container, &id), *b"abc");
+    }
+}
+
+#[test]
+fn open_with_private_key_pem() {
+    let key = PrivateKey::generate().unwrap();
+    let (backend, id) = setup_container(Cipher::Aes128Gcm, Some(&key), true);
+
+    let key = PrivateKey::from_pem(&key.to_pem().unwrap()).unwrap();
+    let mut container = open_private_key(backend, &key).unwrap();
+    assert_eq!(read(&mut container, &id), *b"abc");
+}
+
+#[test]
+fn wrong_private_key() {
+    for cipher in CIPHERS {
+        let key = PrivateKey::generate().unwrap();
+        let other_key = PrivateKey::generate().unwrap();
+        let (backend, _) = setup_container(cipher, Some(&key), true);
+
+        let err = open_private_key(backend, &other_key).unwrap_err();
+        assert!(matches!(err, Error::Header(HeaderError::WrongPrivateKey)));
+    }
+}
+
+#[test]
+fn without_password() {
+    for cipher in CIPHERS {
+        let key = PrivateKey::generate().unwrap();
+        let (backend, id) = setup_container(cipher, Some(&key), false);
+
+        let mut container = open_private_key(backend, &key).unwrap();
+        assert_eq!(read(&mut container, &id), *b"abc");
+
+        let err = open(container.into_backend(), b"abc").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Header(HeaderError::WrongPassword)
+                | Error::Header(HeaderError::Cipher(CipherError::NotTrustworthy))
+                | Error::Header(HeaderError::Cipher(CipherError::MacMismatch))
+        ));
+    }
+}
+
+#[test]
+fn write_with_private_key() {
+    let key = PrivateKey::generate().unwrap();
+    let (backend, id) = setup_container(Cipher::Aes128Gcm, Some(&key), true);
+
+    // rollback protection updates the header on each write
+    let mut container = open_private_key(backend, &key).unwrap();
+    container.write(&id, b"xyz").unwrap();
+
+    let mut container = open(container.into_backend(), b"abc").unwrap();
+    assert_eq!(read(&mut container, &id), *b"xyz");
+
+    let mut container = open_private_key(container.into_backend(), &key).unwrap();
+    assert_eq!(read(&mut container, &id), *b"xyz");
+}
+
+#[test]
+fn change_password() {
+    for cipher in CIPHERS {
+        let key = PrivateKey::generate().unwrap();
+        let (backend, id) = setup_container(cipher, Some(&key), false);
+
+        let mut container = open_private_key(backend, &key).unwrap();
+        let options = ModifyOptionsBuilder::default()
+            .change_password(|| Ok(b"xxx".to_vec()))
+            .build();
+        container.modify(options).unwrap();
+
+        let mut container = open(container.into_backend(), b"xxx").unwrap();
+        assert_eq!(read(&mut container, &id), *b"abc");
+
+        let mut container = open_private_key(container.into_backend(), &key).unwrap();
+        assert_eq!(read(&mut container, &id), *b"abc");
+    }
+}
+
+#[test]
+fn header_too_large() {
+    let key = PrivateKey::generate().unwrap();
+    let options = CreateOptionsBuilder::new(Cipher::Aes256CtrHmac)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .with_rollback_protection(true)
+        .with_recovery_key(true)
+        .with_recipient(key.public_key().unwrap())
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    let err = Container::create(MemoryBackend::new(), options).unwrap_err();
+    assert!(matches!(err, Error::Header(HeaderError::TooLarge(n)) if n > 512));
+}
+
+#[test]
+fn all_features() {
+    let key = PrivateKey::generate().unwrap();
+
+    for (rollback_protection, recovery_key, recipient) in [
+        (true, true, false),
+        (true, false, true),
+        (false, true, true),
+        (true, true, true),
+    ] {
+        let mut builder = CreateOptionsBuilder::new(Cipher::Aes256CtrHmac)
+            .with_password_callback(|| Ok(b"abc".to_vec()))
+            .with_rollback_protection(rollback_protection)
+            .with_recovery_key(recovery_key);
+
+        if recipient {
+            builder = builder.with_recipient(key.public_key().unwrap());
+        }
+
+        let options = builder.build::<MemoryBackend>().unwrap();
+        let mut container = match Container::create(MemoryBackend::new(), options) {
+            Ok(container) => container,
+            Err(Error::Header(HeaderError::TooLarge(_))) => continue,
+            Err(err) => panic!("unexpected error: {}", err),
+        };
+
+        // journal and integrity tree are stored in the header
+        let id = container.aquire().unwrap();
+
+        container.begin().unwrap();
+        container.write(&id, b"abc").unwrap();
+        container.commit().unwrap();
+
+        let mut container = open(container.into_backend(), b"abc").unwrap();
+        assert_eq!(read(&mut container, &id), *b"abc");
+    }
+}
@@ -0,0 +1,192 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_container::{
+    Cipher, Container, CreateOptionsBuilder, Error, HeaderError, ModifyOptionsBuilder,
+    OpenOptionsBuilder,
+};
+use nuts_memory::{Id, MemoryBackend};
+
+const CIPHERS: [Cipher; 3] = [Cipher::Aes128Ctr, Cipher::Aes128Gcm, Cipher::Aes128CtrHmac];
+
+fn setup_container(cipher: Cipher, recovery_key: bool) -> (MemoryBackend, Id, Option<String>) {
+    let options = CreateOptionsBuilder::new(cipher)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .with_recovery_key(recovery_key)
+        .with_rollback_protection(true)
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    let mut container = Container::create(MemoryBackend::new(), options).unwrap();
+    let recovery_key = container.recovery_key().map(ToString::to_string);
+    let id = container.aquire().unwrap();
+
+    container.write(&id, b"abc").unwrap();
+
+    (container.into_backend(), id, recovery_key)
+}
+
+fn open(
+    backend: MemoryBackend,
+    password: &'static [u8],
+) -> Result<Container<MemoryBackend>, Error<MemoryBackend>> {
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(move || Ok(password.to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::open(backend, options)
+}
+
+fn open_recovery(
+    backend: MemoryBackend,
+    recovery_key: &str,
+) -> Result<Container<MemoryBackend>, Error<MemoryBackend>> {
+    let recovery_key = recovery_key.as_bytes().to_vec();
+    let options = OpenOptionsBuilder::new()
+        .with_recovery_key_callback(move || Ok(recovery_key.clone()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::open(backend, options)
+}
+
+fn read(container: &mut Container<MemoryBackend>, id: &Id) -> [u8; 3] {
+    let mut buf = [0; 3];
+
+    container.read(id, &mut buf).unwrap();
+
+    buf
+}
+
+#[test]
+fn no_recovery_key() {
+    let (backend, _, recovery_key) = setup_container(Cipher::Aes128Gcm, false);
+
+    assert!(recovery_key.is_none());
+
+    let container = open(backend, b"abc").unwrap();
+    assert!(!container.info().unwrap().recovery_key);
+
+    let err = open_recovery(container.into_backend(), "xxx").unwrap_err();
+    assert!(matches!(err, Error::Header(HeaderError::NoRecoveryKey)));
+}
+
+#[test]
+fn cipher_none() {
+    let (_, _, recovery_key) = setup_container(Cipher::None, true);
+
+    assert!(recovery_key.is_none());
+}
+
+#[test]
+fn info() {
+    let (backend, _, _) = setup_container(Cipher::Aes128Gcm, true);
+    let container = open(backend, b"abc").unwrap();
+
+    assert!(container.info().unwrap().recovery_key);
+}
+
+#[test]
+fn open_with_recovery_key() {
+    for cipher in CIPHERS {
+        let (backend, id, recovery_key) = setup_container(cipher, true);
+        let recovery_key = recovery_key.unwrap();
+
+        let mut container = open_recovery(backend, &recovery_key).unwrap();
+        assert_eq!(read(&mut container, &id), *b"abc");
+
+        let backend = container.into_backend();
+        let mut container = open_recovery(backend, &recovery_key.to_lowercase()).unwrap();
+        assert_eq!(read(&mut container, &id), *b"abc");
+    }
+}
+
+#[test]
+fn wrong_recovery_key() {
+    for cipher in CIPHERS {
+        let (backend, _, _) = setup_container(cipher, true);
+        let (_, _, other_key) = setup_container(cipher, true);
+
+        let err = open_recovery(backend, &other_key.unwrap()).unwrap_err();
+        assert!(matches!(err, Error::Header(HeaderError::WrongRecoveryKey)));
+    }
+}
+
+#[test]
+fn invalid_recovery_key() {
+    let (backend, _, _) = setup_container(Cipher::Aes128Gcm, true);
+
+    let err = open_recovery(backend, "xxx").unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Header(HeaderError::InvalidRecoveryKey)
+    ));
+}
+
+#[test]
+fn write_with_recovery_key() {
+    let (backend, id, recovery_key) = setup_container(Cipher::Aes128Gcm, true);
+    let recovery_key = recovery_key.unwrap();
+
+    // rollback protection updates the header on each write
+    let mut container = open_recovery(backend, &recovery_key).unwrap();
+    container.write(&id, b"xyz").unwrap();
+
+    let mut container = open(container.into_backend(), b"abc").unwrap();
+    assert_eq!(read(&mut container, &id), *b"xyz");
+
+    let mut container = open_recovery(container.into_backend(), &recovery_key).unwrap();
+    assert_eq!(read(&mut container, &id), *b"xyz");
+}
+
+#[test]
+fn change_password() {
+    for cipher in CIPHERS {
+        let (backend, id, recovery_key) = setup_container(cipher, true);
+        let recovery_key = recovery_key.unwrap();
+
+        let mut container = open_recovery(backend, &recovery_key).unwrap();
+        let options = ModifyOptionsBuilder::default()
+            .change_password(|| Ok(b"xxx".to_vec()))
+            .build();
+        container.modify(options).unwrap();
+
+        let mut container = open(container.into_backend(), b"xxx").unwrap();
+        assert_eq!(read(&mut container, &id), *b"abc");
+
+        let mut container = open_recovery(container.into_backend(), &recovery_key).unwrap();
+        assert_eq!(read(&mut container, &id), *b"abc");
+    }
+}
+
+#[test]
+fn change_kdf_without_password() {
+    let (backend, _, recovery_key) = setup_container(Cipher::Aes128Gcm, true);
+
+    let mut container = open_recovery(backend, &recovery_key.unwrap()).unwrap();
+    let kdf = "pbkdf2:sha1:1024:8".parse().unwrap();
+    let options = ModifyOptionsBuilder::default().change_kdf(kdf).build();
+
+    let err = container.modify(options).unwrap_err();
+    assert!(matches!(err, Error::Header(HeaderError::Password(_))));
+}
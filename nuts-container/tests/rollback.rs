@@ -0,0 +1,298 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::Backend;
+use nuts_container::{
+    Cipher, Container, CreateOptionsBuilder, Error, IntegrityError, JournalError,
+    OpenOptionsBuilder,
+};
+use nuts_memory::{Id, MemoryBackend};
+
+fn create(rollback_protection: bool) -> Container<MemoryBackend> {
+    create_with(Cipher::Aes128Gcm, rollback_protection)
+}
+
+fn create_with(cipher: Cipher, rollback_protection: bool) -> Container<MemoryBackend> {
+    let options = CreateOptionsBuilder::new(cipher)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .with_rollback_protection(rollback_protection)
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::create(MemoryBackend::new(), options).unwrap()
+}
+
+fn open(backend: MemoryBackend) -> Result<Container<MemoryBackend>, Error<MemoryBackend>> {
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::open(backend, options)
+}
+
+fn read(container: &mut Container<MemoryBackend>, id: &Id) -> Vec<u8> {
+    let mut buf = [0; 3];
+
+    container.read(id, &mut buf).map(|_| buf.to_vec()).unwrap()
+}
+
+/// Writes `abc` and then `xyz` into a block, but afterwards the block is
+/// rolled back to `abc` in the backend.
+fn rolled_back(rollback_protection: bool) -> (MemoryBackend, Id) {
+    let mut container = create(rollback_protection);
+    let id = container.aquire().unwrap();
+
+    container.write(&id, b"abc").unwrap();
+    let old = container.backend().get(&id).unwrap().to_vec();
+    container.write(&id, b"xyz").unwrap();
+
+    let mut backend = container.into_backend();
+    backend.write(&id, &old).unwrap();
+
+    (backend, id)
+}
+
+#[test]
+fn info() {
+    assert!(!create(false).info().unwrap().rollback_protection);
+    assert!(create(true).info().unwrap().rollback_protection);
+
+    let container = open(create(true).into_backend()).unwrap();
+    assert!(container.info().unwrap().rollback_protection);
+}
+
+#[test]
+fn write_read() {
+    let mut container = create(true);
+    let ids = (0..32)
+        .map(|_| container.aquire().unwrap())
+        .collect::<Vec<_>>();
+
+    for (idx, id) in ids.iter().enumerate() {
+        container.write(id, &[idx as u8; 3]).unwrap();
+    }
+
+    for id in ids.iter().step_by(2) {
+        container.release(*id).unwrap();
+    }
+
+    let mut container = open(container.into_backend()).unwrap();
+
+    for (idx, id) in ids.iter().enumerate().skip(1).step_by(2) {
+        assert_eq!(read(&mut container, id), [idx as u8; 3]);
+    }
+}
+
+#[test]
+fn write_read_hmac() {
+    let mut container = create_with(Cipher::Aes128CtrHmac, true);
+    let ids = (0..32)
+        .map(|_| container.aquire().unwrap())
+        .collect::<Vec<_>>();
+
+    for (idx, id) in ids.iter().enumerate().step_by(2) {
+        container.write(id, &[idx as u8; 3]).unwrap();
+    }
+
+    let mut container = open(container.into_backend()).unwrap();
+
+    for (idx, id) in ids.iter().enumerate() {
+        let expected = if idx % 2 == 0 { [idx as u8; 3] } else { [0; 3] };
+        assert_eq!(read(&mut container, id), expected);
+    }
+}
+
+#[test]
+fn placeholder_detected() {
+    let mut container = create_with(Cipher::Aes128CtrHmac, true);
+    let id = container.aquire().unwrap();
+
+    container.write(&id, b"abc").unwrap();
+
+    // replace the block with the placeholder of an aquired block
+    let mut backend = container.into_backend();
+    backend.write(&id, &[0; 512]).unwrap();
+
+    let mut container = open(backend).unwrap();
+    let mut buf = [0; 3];
+
+    let err = container.read(&id, &mut buf).unwrap_err();
+    assert!(matches!(err, Error::Integrity(IntegrityError::StaleBlock(n)) if n == id.to_string()));
+}
+
+#[test]
+fn rollback_detected() {
+    let (backend, id) = rolled_back(true);
+    let mut container = open(backend).unwrap();
+    let mut buf = [0; 3];
+
+    let err = container.read(&id, &mut buf).unwrap_err();
+    assert!(matches!(err, Error::Integrity(IntegrityError::StaleBlock(n)) if n == id.to_string()));
+}
+
+#[test]
+fn repair() {
+    let (backend, id) = rolled_back(true);
+    let mut container = open(backend).unwrap();
+
+    assert_eq!(container.repair_integrity().unwrap(), [id]);
+    assert_eq!(read(&mut container, &id), b"abc");
+    assert!(container.repair_integrity().unwrap().is_empty());
+
+    let mut container = open(container.into_backend()).unwrap();
+    assert_eq!(read(&mut container, &id), b"abc");
+}
+
+#[test]
+fn repair_disabled() {
+    let (backend, _) = rolled_back(false);
+    let mut container = open(backend).unwrap();
+
+    assert!(container.repair_integrity().unwrap().is_empty());
+}
+
+#[test]
+fn repair_transaction() {
+    let mut container = create(true);
+
+    container.begin().unwrap();
+
+    let err = container.repair_integrity().unwrap_err();
+    assert!(matches!(err, Error::Journal(JournalError::Active)));
+}
+
+#[test]
+fn rollback_not_detected() {
+    let (backend, id) = rolled_back(false);
+    let mut container = open(backend).unwrap();
+
+    assert_eq!(read(&mut container, &id), b"abc");
+}
+
+#[test]
+fn transaction() {
+    let mut container = create(true);
+    let id1 = container.aquire().unwrap();
+    let id2 = container.aquire().unwrap();
+
+    container.begin().unwrap();
+    container.write(&id1, b"abc").unwrap();
+    container.write(&id2, b"xyz").unwrap();
+    container.commit().unwrap();
+
+    container.begin().unwrap();
+    container.write(&id1, b"123").unwrap();
+    container.rollback().unwrap();
+
+    let mut container = open(container.into_backend()).unwrap();
+
+    assert_eq!(read(&mut container, &id1), b"abc");
+    assert_eq!(read(&mut container, &id2), b"xyz");
+}
+
+#[test]
+fn drop_without_flush() {
+    use nuts_directory::{CreateOptions, DirectoryBackend, OpenOptions};
+    use std::path::PathBuf;
+    use tempfile::Builder;
+
+    let dir = Builder::new().prefix("nuts-container").tempdir().unwrap();
+
+    let backend_options = CreateOptions::for_path(dir.path().to_owned());
+    let options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .with_rollback_protection(true)
+        .build::<DirectoryBackend<PathBuf>>()
+        .unwrap();
+    let mut container = Container::create(backend_options, options).unwrap();
+
+    let id = container.aquire().unwrap();
+    container.write(&id, b"abc").unwrap();
+
+    // the integrity tree is written when the container is dropped
+    drop(container);
+
+    let backend_options = OpenOptions::for_path(dir.path().to_owned());
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<DirectoryBackend<PathBuf>>()
+        .unwrap();
+    let mut container = Container::open(backend_options, options).unwrap();
+    let mut buf = [0; 3];
+
+    container.read(&id, &mut buf).unwrap();
+    assert_eq!(buf, *b"abc");
+}
+
+fn copy_dir(from: &std::path::Path, to: &std::path::Path) {
+    for entry in std::fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let dest = to.join(entry.file_name());
+
+        if entry.file_type().unwrap().is_dir() {
+            std::fs::create_dir(&dest).unwrap();
+            copy_dir(&entry.path(), &dest);
+        } else {
+            std::fs::copy(entry.path(), dest).unwrap();
+        }
+    }
+}
+
+#[test]
+fn crash_without_flush() {
+    use nuts_directory::{CreateOptions, DirectoryBackend, OpenOptions};
+    use std::path::PathBuf;
+    use tempfile::Builder;
+
+    let dir = Builder::new().prefix("nuts-container").tempdir().unwrap();
+
+    let backend_options = CreateOptions::for_path(dir.path().to_owned());
+    let options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .with_rollback_protection(true)
+        .build::<DirectoryBackend<PathBuf>>()
+        .unwrap();
+    let mut container = Container::create(backend_options, options).unwrap();
+
+    let id = container.aquire().unwrap();
+    container.write(&id, b"abc").unwrap();
+
+    // snapshot of the directory, as if the process crashed here
+    let crashed = Builder::new().prefix("nuts-container").tempdir().unwrap();
+
+    copy_dir(dir.path(), crashed.path());
+
+    drop(container);
+
+    // the integrity tree was already saved with the write
+    let backend_options = OpenOptions::for_path(crashed.path().to_owned());
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<DirectoryBackend<PathBuf>>()
+        .unwrap();
+    let mut container = Container::open(backend_options, options).unwrap();
+    let mut buf = [0; 3];
+
+    container.read(&id, &mut buf).unwrap();
+    assert_eq!(buf, *b"abc");
+}
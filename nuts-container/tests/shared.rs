@@ -0,0 +1,131 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_container::{Cipher, Container, CreateOptionsBuilder, SharedContainer};
+use nuts_memory::MemoryBackend;
+use std::thread;
+
+fn setup_container() -> SharedContainer<MemoryBackend> {
+    let options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    SharedContainer::new(Container::create(MemoryBackend::new(), options).unwrap())
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn send_sync() {
+    assert_send_sync::<Container<MemoryBackend>>();
+    assert_send_sync::<SharedContainer<MemoryBackend>>();
+}
+
+#[test]
+fn concurrent_read() {
+    let container = setup_container();
+    let ids = (0..8u8)
+        .map(|n| {
+            let id = container.aquire().unwrap();
+            container.write(&id, &[n; 3]).unwrap();
+            (id, n)
+        })
+        .collect::<Vec<_>>();
+
+    let handles = (0..4)
+        .map(|_| {
+            let container = container.clone();
+            let ids = ids.clone();
+
+            thread::spawn(move || {
+                let mut buf = vec![0; container.block_size() as usize];
+
+                for (id, n) in ids {
+                    assert_eq!(container.read(&id, &mut buf).unwrap(), buf.len());
+                    assert_eq!(buf[..3], [n; 3]);
+                    assert!(buf[3..].iter().all(|b| *b == 0));
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn concurrent_write() {
+    let container = setup_container();
+
+    let handles = (0..4u8)
+        .map(|n| {
+            let container = container.clone();
+
+            thread::spawn(move || {
+                let id = container.aquire().unwrap();
+                container.write(&id, &[n; 3]).unwrap();
+                (id, n)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut buf = vec![0; container.block_size() as usize];
+
+    for handle in handles {
+        let (id, n) = handle.join().unwrap();
+
+        container.read(&id, &mut buf).unwrap();
+        assert_eq!(buf[..3], [n; 3]);
+    }
+}
+
+#[test]
+fn lock() {
+    let container = setup_container();
+    let id = container.aquire().unwrap();
+
+    {
+        let mut guard = container.lock();
+        guard.begin().unwrap();
+        guard.write(&id, b"abc").unwrap();
+        guard.commit().unwrap();
+    }
+
+    let mut buf = vec![0; container.block_size() as usize];
+
+    container.read(&id, &mut buf).unwrap();
+    assert_eq!(buf[..3], *b"abc");
+}
+
+#[test]
+fn into_container() {
+    let container = setup_container();
+    let other = container.clone();
+
+    let container = container.into_container().unwrap_err();
+    drop(other);
+
+    let container = container.into_container().unwrap();
+    assert_eq!(container.block_size(), 496);
+}
@@ -0,0 +1,229 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+mod common;
+
+use nuts_container::{
+    Cipher, Container, CreateOptionsBuilder, Error, HeaderError, JournalError, OpenOptionsBuilder,
+};
+use nuts_memory::{Error as MemoryError, Id, MemoryBackend};
+use std::fs::File;
+
+use crate::common::fixture_path;
+
+fn setup_container() -> Container<MemoryBackend> {
+    let options = CreateOptionsBuilder::new(Cipher::Aes128Gcm)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::create(MemoryBackend::new(), options).unwrap()
+}
+
+fn reopen(container: Container<MemoryBackend>) -> Container<MemoryBackend> {
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::open(container.into_backend(), options).unwrap()
+}
+
+fn read(container: &mut Container<MemoryBackend>, id: &Id) -> Vec<u8> {
+    let mut buf = vec![0; container.block_size() as usize];
+
+    container.read(id, &mut buf).unwrap();
+
+    buf
+}
+
+#[test]
+fn commit() {
+    let mut container = setup_container();
+    let id1 = container.aquire().unwrap();
+    let id2 = container.aquire().unwrap();
+
+    container.begin().unwrap();
+    assert!(container.in_transaction());
+
+    container.write(&id1, b"abc").unwrap();
+    container.write(&id2, b"xyz").unwrap();
+    container.write(&id2, b"123").unwrap();
+
+    container.commit().unwrap();
+    assert!(!container.in_transaction());
+
+    let mut container = reopen(container);
+
+    assert_eq!(read(&mut container, &id1)[..4], *b"abc\0");
+    assert_eq!(read(&mut container, &id2)[..4], *b"123\0");
+}
+
+#[test]
+fn commit_empty() {
+    let mut container = setup_container();
+
+    container.begin().unwrap();
+    container.commit().unwrap();
+
+    assert!(!container.in_transaction());
+}
+
+#[test]
+fn commit_not_active() {
+    let mut container = setup_container();
+    let err = container.commit().unwrap_err();
+
+    assert!(matches!(err, Error::Journal(JournalError::NotActive)));
+}
+
+#[test]
+fn rollback() {
+    let mut container = setup_container();
+    let id = container.aquire().unwrap();
+
+    container.write(&id, b"abc").unwrap();
+
+    container.begin().unwrap();
+    container.write(&id, b"xyz").unwrap();
+    container.rollback().unwrap();
+
+    assert!(!container.in_transaction());
+    assert_eq!(read(&mut container, &id)[..4], *b"abc\0");
+}
+
+#[test]
+fn rollback_aquired() {
+    let mut container = setup_container();
+
+    container.begin().unwrap();
+
+    let id = container.aquire().unwrap();
+
+    container.write(&id, b"abc").unwrap();
+    container.rollback().unwrap();
+
+    let err = container.write(&id, b"xyz").unwrap_err();
+    assert!(matches!(err, Error::Backend(MemoryError::NoSuchId(_))));
+}
+
+#[test]
+fn rollback_not_active() {
+    let mut container = setup_container();
+    let err = container.rollback().unwrap_err();
+
+    assert!(matches!(err, Error::Journal(JournalError::NotActive)));
+}
+
+#[test]
+fn read_pending() {
+    let mut container = setup_container();
+    let id = container.aquire().unwrap();
+
+    container.write(&id, b"abc").unwrap();
+
+    container.begin().unwrap();
+    container.write(&id, b"xyz").unwrap();
+
+    let buf = read(&mut container, &id);
+
+    assert_eq!(buf[..4], *b"xyz\0");
+    assert!(buf[4..].iter().all(|n| *n == 0));
+
+    container.rollback().unwrap();
+
+    assert_eq!(read(&mut container, &id)[..4], *b"abc\0");
+}
+
+#[test]
+fn release_deferred() {
+    let mut container = setup_container();
+    let id = container.aquire().unwrap();
+
+    container.begin().unwrap();
+    container.release(id).unwrap();
+
+    // still available until commit
+    assert!(container.backend().get(&id).is_some());
+
+    container.commit().unwrap();
+
+    assert!(container.backend().get(&id).is_none());
+}
+
+#[test]
+fn release_rollback() {
+    let mut container = setup_container();
+    let id = container.aquire().unwrap();
+
+    container.write(&id, b"abc").unwrap();
+
+    container.begin().unwrap();
+    container.release(id).unwrap();
+    container.rollback().unwrap();
+
+    assert_eq!(read(&mut container, &id)[..4], *b"abc\0");
+}
+
+#[test]
+fn release_aquired() {
+    let mut container = setup_container();
+
+    container.begin().unwrap();
+
+    let id = container.aquire().unwrap();
+
+    container.write(&id, b"abc").unwrap();
+    container.release(id).unwrap();
+
+    assert!(container.backend().get(&id).is_none());
+
+    container.commit().unwrap();
+}
+
+#[test]
+fn begin_active() {
+    let mut container = setup_container();
+
+    container.begin().unwrap();
+
+    let err = container.begin().unwrap_err();
+
+    assert!(matches!(err, Error::Journal(JournalError::Active)));
+    assert!(container.in_transaction());
+}
+
+#[test]
+fn begin_inval_revision() {
+    let file = File::open(fixture_path("compat", "0.7.3-none.json")).unwrap();
+    let backend: MemoryBackend = serde_json::from_reader(file).unwrap();
+    let options = OpenOptionsBuilder::new().build::<MemoryBackend>().unwrap();
+    let mut container = Container::open(backend, options).unwrap();
+
+    let err = container.begin().unwrap_err();
+
+    assert!(matches!(
+        err,
+        Error::Header(HeaderError::InvalidRevision(3, 2))
+    ));
+    assert!(!container.in_transaction());
+}
@@ -0,0 +1,159 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_container::{
+    Cipher, Container, CreateOptionsBuilder, Error, HeaderError, ModifyOptionsBuilder,
+    OpenOptionsBuilder, WrappingKey,
+};
+use nuts_memory::{Id, MemoryBackend};
+
+const CIPHERS: [Cipher; 3] = [Cipher::Aes128Ctr, Cipher::Aes128Gcm, Cipher::Aes128CtrHmac];
+
+fn setup_container(cipher: Cipher) -> (MemoryBackend, Id) {
+    let options = CreateOptionsBuilder::new(cipher)
+        .with_password_callback(|| Ok(b"abc".to_vec()))
+        .with_rollback_protection(true)
+        .build::<MemoryBackend>()
+        .unwrap();
+    let mut container = Container::create(MemoryBackend::new(), options).unwrap();
+    let id = container.aquire().unwrap();
+
+    container.write(&id, b"abc").unwrap();
+
+    (container.into_backend(), id)
+}
+
+fn open(
+    backend: MemoryBackend,
+    password: &'static [u8],
+) -> Result<Container<MemoryBackend>, Error<MemoryBackend>> {
+    let options = OpenOptionsBuilder::new()
+        .with_password_callback(move || Ok(password.to_vec()))
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::open(backend, options)
+}
+
+fn open_wrapping_key(
+    backend: MemoryBackend,
+    wrapping_key: &WrappingKey,
+) -> Result<Container<MemoryBackend>, Error<MemoryBackend>> {
+    let options = OpenOptionsBuilder::new()
+        .with_wrapping_key(wrapping_key.clone())
+        .build::<MemoryBackend>()
+        .unwrap();
+
+    Container::open(backend, options)
+}
+
+fn read(container: &mut Container<MemoryBackend>, id: &Id) -> [u8; 3] {
+    let mut buf = [0; 3];
+
+    container.read(id, &mut buf).unwrap();
+
+    buf
+}
+
+#[test]
+fn cipher_none() {
+    let options = CreateOptionsBuilder::new(Cipher::None)
+        .build::<MemoryBackend>()
+        .unwrap();
+    let container = Container::create(MemoryBackend::new(), options).unwrap();
+    assert!(container.wrapping_key().is_none());
+
+    let container = open(container.into_backend(), b"").unwrap();
+    assert!(container.wrapping_key().is_none());
+}
+
+#[test]
+fn open_with_wrapping_key() {
+    for cipher in CIPHERS {
+        let (backend, id) = setup_container(cipher);
+
+        let container = open(backend, b"abc").unwrap();
+        let key = container.wrapping_key().unwrap();
+        assert!(key.as_bytes().len() >= cipher.key_len());
+
+        let mut container = open_wrapping_key(container.into_backend(), &key).unwrap();
+        assert_eq!(read(&mut container, &id), *b"abc");
+        assert_eq!(container.wrapping_key().unwrap(), key);
+    }
+}
+
+#[test]
+fn wrong_wrapping_key() {
+    for cipher in CIPHERS {
+        let (backend, _) = setup_container(cipher);
+        let key = WrappingKey::from_bytes(&vec![1; cipher.key_len()]);
+
+        let err = open_wrapping_key(backend, &key).unwrap_err();
+        assert!(matches!(err, Error::Header(HeaderError::WrongWrappingKey)));
+    }
+}
+
+#[test]
+fn wrapping_key_wrong_len() {
+    let (backend, _) = setup_container(Cipher::Aes128Gcm);
+    let key = WrappingKey::from_bytes(&[1, 2, 3]);
+
+    let err = open_wrapping_key(backend, &key).unwrap_err();
+    assert!(matches!(err, Error::Header(HeaderError::WrongWrappingKey)));
+}
+
+#[test]
+fn write_with_wrapping_key() {
+    let (backend, id) = setup_container(Cipher::Aes128Gcm);
+
+    let container = open(backend, b"abc").unwrap();
+    let key = container.wrapping_key().unwrap();
+
+    // rollback protection updates the header, once the container is flushed
+    let mut container = open_wrapping_key(container.into_backend(), &key).unwrap();
+    container.write(&id, b"xyz").unwrap();
+
+    let mut container = open(container.into_backend(), b"abc").unwrap();
+    assert_eq!(read(&mut container, &id), *b"xyz");
+}
+
+#[test]
+fn change_password() {
+    let (backend, _) = setup_container(Cipher::Aes128Gcm);
+
+    let mut container = open(backend, b"abc").unwrap();
+    let key = container.wrapping_key().unwrap();
+
+    let options = ModifyOptionsBuilder::default()
+        .change_password(|| Ok(b"xxx".to_vec()))
+        .build();
+    container.modify(options).unwrap();
+
+    // derived from the new password
+    let new_key = container.wrapping_key().unwrap();
+    assert_ne!(new_key, key);
+
+    let container = open_wrapping_key(container.into_backend(), &new_key).unwrap();
+
+    let err = open_wrapping_key(container.into_backend(), &key).unwrap_err();
+    assert!(matches!(err, Error::Header(HeaderError::WrongWrappingKey)));
+}
@@ -0,0 +1,179 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use log::warn;
+use nuts_backend::{AsyncBackend, BoxFuture};
+use std::cmp;
+use std::io::ErrorKind;
+use std::path::Path;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::durability::Syncer;
+use crate::error::{Error, Result};
+use crate::{check_block_path, DirectoryBackend, Id};
+
+async fn read_block(path: &Path, id: &Id, bsize: u32, buf: &mut [u8]) -> Result<usize> {
+    let path = id.to_pathbuf(path);
+    let mut fh = fs::OpenOptions::new().read(true).open(path).await?;
+
+    let len = cmp::min(buf.len(), bsize as usize);
+    let target = &mut buf[..len];
+
+    fh.read_exact(target).await?;
+
+    Ok(len)
+}
+
+async fn sync_dir(path: &Path) -> Result<()> {
+    if cfg!(unix) {
+        fs::File::open(path).await?.sync_all().await?;
+    }
+
+    Ok(())
+}
+
+async fn write_block(
+    root: &Path,
+    id: &Id,
+    aquire: bool,
+    header: bool,
+    bsize: u32,
+    syncer: &mut Syncer,
+    buf: &[u8],
+) -> Result<usize> {
+    let path = id.to_pathbuf(root);
+    let mut created = false;
+
+    if let Some(dir) = path.parent() {
+        if !matches!(fs::metadata(dir).await, Ok(md) if md.is_dir()) {
+            fs::create_dir_all(dir).await?;
+            created = true;
+        }
+    }
+
+    let is_file = match fs::metadata(&path).await {
+        Ok(md) => Some(md.is_file()),
+        Err(err) if err.kind() == ErrorKind::NotFound => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    check_block_path(&path, id, aquire, header, is_file)?;
+
+    let tmp_path = path.with_extension("tmp");
+
+    let mut fh = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp_path)
+        .await?;
+
+    let len = cmp::min(buf.len(), bsize as usize);
+    let pad_len = bsize as usize - len;
+
+    fh.write_all(&buf[..len]).await?;
+    fh.write_all(&vec![0; pad_len]).await?;
+    fh.flush().await?;
+
+    if syncer.sync_file() {
+        fh.sync_all().await?;
+    }
+
+    fs::rename(tmp_path, &path).await?;
+
+    for dir in syncer.written(root, &path, created) {
+        sync_dir(&dir).await?;
+    }
+
+    Ok(len)
+}
+
+impl<P: AsRef<Path> + Send> AsyncBackend for DirectoryBackend<P> {
+    fn aquire_async<'a>(&'a mut self, buf: &'a [u8]) -> BoxFuture<'a, Result<Id>> {
+        const MAX: u8 = 3;
+
+        let path = self.path.as_ref().to_path_buf();
+        let bsize = self.bsize;
+        let writable = self.writable();
+        let syncer = &mut self.syncer;
+
+        Box::pin(async move {
+            writable?;
+
+            for n in 0..MAX {
+                let id = Id::generate()?;
+
+                match write_block(&path, &id, true, false, bsize, syncer, buf).await {
+                    Ok(_) => return Ok(id),
+                    Err(Error::Io(err)) => {
+                        if err.kind() == ErrorKind::AlreadyExists {
+                            warn!("Id {} already exists try again ({}/{})", id, n + 1, MAX);
+                        } else {
+                            return Err(err.into());
+                        }
+                    }
+                    Err(err) => return Err(err),
+                };
+            }
+
+            Err(Error::UniqueId)
+        })
+    }
+
+    fn release_async(&mut self, id: Id) -> BoxFuture<'_, Result<()>> {
+        let path = id.to_pathbuf(self.path.as_ref());
+        let writable = self.writable();
+        let syncer = &mut self.syncer;
+
+        Box::pin(async move {
+            writable?;
+
+            fs::remove_file(&path).await?;
+
+            for dir in syncer.removed(&path) {
+                sync_dir(&dir).await?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn read_async<'a>(&'a mut self, id: &'a Id, buf: &'a mut [u8]) -> BoxFuture<'a, Result<usize>> {
+        let path = self.path.as_ref().to_path_buf();
+        let bsize = self.bsize;
+
+        Box::pin(async move { read_block(&path, id, bsize, buf).await })
+    }
+
+    fn write_async<'a>(&'a mut self, id: &'a Id, buf: &'a [u8]) -> BoxFuture<'a, Result<usize>> {
+        let path = self.path.as_ref().to_path_buf();
+        let bsize = self.bsize;
+
+        let writable = self.writable();
+        let syncer = &mut self.syncer;
+
+        Box::pin(async move {
+            writable?;
+            write_block(&path, id, false, false, bsize, syncer, buf).await
+        })
+    }
+}
@@ -0,0 +1,206 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::{fmt, fs};
+
+use crate::error::Result;
+
+/// Controls when written blocks are synced to disk.
+///
+/// The durability is part of the [settings](crate::Settings) of the backend
+/// and assigned with [`CreateOptions::with_durability()`].
+///
+/// [`CreateOptions::with_durability()`]: crate::CreateOptions::with_durability
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Durability {
+    /// Blocks are never synced explicitly, the operating system decides when
+    /// the data reach the disk. This is the default.
+    #[default]
+    None,
+
+    /// Every block is synced to disk before the write returns.
+    Write,
+
+    /// Modified blocks are collected and synced to disk on
+    /// [`Backend::flush()`](nuts_backend::Backend::flush).
+    Flush,
+}
+
+impl Durability {
+    pub(crate) fn from_u8(n: u8) -> Option<Durability> {
+        match n {
+            0 => Some(Durability::None),
+            1 => Some(Durability::Write),
+            2 => Some(Durability::Flush),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_u8(&self) -> u8 {
+        match self {
+            Durability::None => 0,
+            Durability::Write => 1,
+            Durability::Flush => 2,
+        }
+    }
+}
+
+impl fmt::Display for Durability {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Durability::None => "none",
+            Durability::Write => "write",
+            Durability::Flush => "flush",
+        };
+
+        fmt.write_str(s)
+    }
+}
+
+impl FromStr for Durability {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        match s {
+            "none" => Ok(Durability::None),
+            "write" => Ok(Durability::Write),
+            "flush" => Ok(Durability::Flush),
+            _ => Err(format!("invalid durability: {}", s)),
+        }
+    }
+}
+
+/// Syncs a directory, thus modifications of its entries are durable.
+#[cfg(unix)]
+pub(crate) fn sync_dir(path: &Path) -> Result<()> {
+    fs::File::open(path)?.sync_all()?;
+    Ok(())
+}
+
+/// Directories cannot be synced on this platform.
+#[cfg(not(unix))]
+pub(crate) fn sync_dir(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Keeps track of the paths, which must be synced according to the
+/// [`Durability`].
+#[derive(Debug)]
+pub(crate) struct Syncer {
+    durability: Durability,
+    pending: HashSet<PathBuf>,
+}
+
+impl Syncer {
+    pub fn new(durability: Durability) -> Syncer {
+        Syncer {
+            durability,
+            pending: HashSet::new(),
+        }
+    }
+
+    pub fn durability(&self) -> Durability {
+        self.durability
+    }
+
+    /// Tests whether a written file must be synced before it is renamed.
+    pub fn sync_file(&self) -> bool {
+        self.durability == Durability::Write
+    }
+
+    /// Registers the `file` below `root`, which was written.
+    ///
+    /// If `created` is set, the directories between `root` and `file` were
+    /// created. Returns the directories, which must be synced now.
+    pub fn written(&mut self, root: &Path, file: &Path, created: bool) -> Vec<PathBuf> {
+        let dirs = file
+            .ancestors()
+            .skip(1)
+            .take(if created { usize::MAX } else { 1 })
+            .take_while(|dir| dir.starts_with(root))
+            .map(Path::to_path_buf)
+            .collect();
+
+        if self.durability == Durability::Flush {
+            self.pending.insert(file.to_path_buf());
+        }
+
+        self.defer_or_return(dirs)
+    }
+
+    /// Registers the `file`, which was removed.
+    ///
+    /// Returns the directories, which must be synced now.
+    pub fn removed(&mut self, file: &Path) -> Vec<PathBuf> {
+        self.pending.remove(file);
+
+        let dirs = file.parent().map(Path::to_path_buf).into_iter().collect();
+
+        self.defer_or_return(dirs)
+    }
+
+    fn defer_or_return(&mut self, dirs: Vec<PathBuf>) -> Vec<PathBuf> {
+        match self.durability {
+            Durability::None => vec![],
+            Durability::Write => dirs,
+            Durability::Flush => {
+                self.pending.extend(dirs);
+                vec![]
+            }
+        }
+    }
+
+    /// Discards all pending paths.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Syncs all pending paths.
+    ///
+    /// Files are synced before the directories, which contains them.
+    pub fn flush(&mut self) -> Result<()> {
+        let (dirs, files): (Vec<_>, Vec<_>) = self.pending.iter().partition(|path| path.is_dir());
+
+        for path in files {
+            match fs::File::open(path) {
+                Ok(fh) => fh.sync_all()?,
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        for path in dirs {
+            sync_dir(path)?;
+        }
+
+        self.pending.clear();
+
+        Ok(())
+    }
+}
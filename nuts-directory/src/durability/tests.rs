@@ -0,0 +1,150 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+use crate::durability::{Durability, Syncer};
+
+#[test]
+fn default() {
+    assert_eq!(Durability::default(), Durability::None);
+}
+
+#[test]
+fn u8() {
+    for (n, durability) in [
+        (0, Durability::None),
+        (1, Durability::Write),
+        (2, Durability::Flush),
+    ] {
+        assert_eq!(durability.as_u8(), n);
+        assert_eq!(Durability::from_u8(n), Some(durability));
+    }
+
+    assert_eq!(Durability::from_u8(3), None);
+}
+
+#[test]
+fn display() {
+    assert_eq!(Durability::None.to_string(), "none");
+    assert_eq!(Durability::Write.to_string(), "write");
+    assert_eq!(Durability::Flush.to_string(), "flush");
+}
+
+#[test]
+fn from_str() {
+    assert_eq!("none".parse::<Durability>().unwrap(), Durability::None);
+    assert_eq!("write".parse::<Durability>().unwrap(), Durability::Write);
+    assert_eq!("flush".parse::<Durability>().unwrap(), Durability::Flush);
+
+    let err = "xxx".parse::<Durability>().unwrap_err();
+    assert_eq!(err, "invalid durability: xxx");
+}
+
+#[test]
+fn written_none() {
+    let mut syncer = Syncer::new(Durability::None);
+
+    let dirs = syncer.written(Path::new("/r"), Path::new("/r/aa/bb/cc"), true);
+
+    assert!(dirs.is_empty());
+    assert!(syncer.pending.is_empty());
+}
+
+#[test]
+fn written_write() {
+    let mut syncer = Syncer::new(Durability::Write);
+
+    let dirs = syncer.written(Path::new("/r"), Path::new("/r/aa/bb/cc"), false);
+    assert_eq!(dirs, [Path::new("/r/aa/bb")]);
+
+    let dirs = syncer.written(Path::new("/r"), Path::new("/r/aa/bb/cc"), true);
+    assert_eq!(
+        dirs,
+        [Path::new("/r/aa/bb"), Path::new("/r/aa"), Path::new("/r")]
+    );
+
+    assert!(syncer.pending.is_empty());
+}
+
+#[test]
+fn written_flush() {
+    let mut syncer = Syncer::new(Durability::Flush);
+
+    let dirs = syncer.written(Path::new("/r"), Path::new("/r/aa/bb/cc"), true);
+    assert!(dirs.is_empty());
+
+    let dirs = syncer.written(Path::new("/r"), Path::new("/r/aa/bb/dd"), false);
+    assert!(dirs.is_empty());
+
+    assert_eq!(
+        syncer.pending,
+        vec!["/r/aa/bb/cc", "/r/aa/bb/dd", "/r/aa/bb", "/r/aa", "/r"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect::<HashSet<_>>()
+    );
+}
+
+#[test]
+fn removed_write() {
+    let mut syncer = Syncer::new(Durability::Write);
+
+    let dirs = syncer.removed(Path::new("/r/aa/bb/cc"));
+    assert_eq!(dirs, [Path::new("/r/aa/bb")]);
+}
+
+#[test]
+fn removed_flush() {
+    let mut syncer = Syncer::new(Durability::Flush);
+
+    syncer.written(Path::new("/r"), Path::new("/r/aa/bb/cc"), false);
+
+    let dirs = syncer.removed(Path::new("/r/aa/bb/cc"));
+    assert!(dirs.is_empty());
+
+    assert_eq!(
+        syncer.pending,
+        vec![PathBuf::from("/r/aa/bb")].into_iter().collect()
+    );
+}
+
+#[test]
+fn flush() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("aa").join("bb");
+    let mut syncer = Syncer::new(Durability::Flush);
+
+    fs::create_dir(dir.path().join("aa")).unwrap();
+    fs::write(&file, b"xxx").unwrap();
+
+    syncer.written(dir.path(), &file, true);
+    // already removed, but not registered
+    syncer.pending.insert(dir.path().join("aa").join("cc"));
+    assert_eq!(syncer.pending.len(), 4);
+
+    syncer.flush().unwrap();
+    assert!(syncer.pending.is_empty());
+}
@@ -40,6 +40,12 @@ pub enum Error {
     /// The block size passed to [CreateOptions](crate::CreateOptions) is
     /// invalid.
     InvalidBlockSize(u32),
+
+    /// The backend is opened read-only and cannot be modified.
+    ReadOnly,
+
+    /// The backend is locked by another process.
+    Locked,
 }
 
 impl fmt::Display for Error {
@@ -50,6 +56,8 @@ impl fmt::Display for Error {
             Error::UniqueId => write!(fmt, "could not generate a unique id"),
             Error::InvalidId(id) => write!(fmt, "The id '{}' is invalid", id),
             Error::InvalidBlockSize(n) => write!(fmt, "The block-size is invalid: {}", n),
+            Error::ReadOnly => write!(fmt, "The backend is opened read-only"),
+            Error::Locked => write!(fmt, "The container is locked by another process"),
         }
     }
 }
@@ -58,9 +66,12 @@ impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             Error::Io(cause) => Some(cause),
-            Error::Exists | Error::UniqueId | Error::InvalidId(_) | Error::InvalidBlockSize(_) => {
-                None
-            }
+            Error::Exists
+            | Error::UniqueId
+            | Error::InvalidId(_)
+            | Error::InvalidBlockSize(_)
+            | Error::ReadOnly
+            | Error::Locked => None,
         }
     }
 }
@@ -94,6 +94,10 @@ impl Id {
         Id([u8::MIN; SIZE])
     }
 
+    pub(crate) fn max() -> Id {
+        Id([u8::MAX; SIZE])
+    }
+
     fn as_hex(&self) -> String {
         let mut target = String::with_capacity(2 * SIZE);
 
@@ -42,6 +42,12 @@ fn min() {
     assert_eq!(id.0, [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
 }
 
+#[test]
+fn max() {
+    let id = Id::max();
+    assert_eq!(id.0, [0xff; 16]);
+}
+
 #[test]
 fn as_hex() {
     let id = Id::generate().unwrap();
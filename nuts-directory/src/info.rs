@@ -20,9 +20,14 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
+use crate::durability::Durability;
+
 /// [Information](nuts_backend::Backend::Info) from the backend.
 #[derive(Debug)]
 pub struct Info {
     /// The block size.
     pub bsize: u32,
+
+    /// The durability of written blocks.
+    pub durability: Durability,
 }
@@ -36,7 +36,18 @@
 //!    `<first two chars>/<next two chars>/<remaining chars>`
 //!
 //! The header of the container is stored in the file
-//! `00/00/0000000000000000000000000000`.
+//! `00/00/0000000000000000000000000000`, a backup of the header in the file
+//! `ff/ff/ffffffffffffffffffffffffffff`.
+//!
+//! The backend is [locked](nuts_backend::Backend::lock) with `flock(2)` on
+//! the backend directory, thus only a single process can modify the container
+//! at the same time. There is no separate lock file, so a read-only backend
+//! is locked without creating a file.
+//!
+//! A block is written into a temporary `.tmp` file, which is renamed
+//! afterwards. The [`Durability`] controls, when the data are synced to disk.
+//! Temporary files left behind by an interrupted write are removed, once the
+//! backend is locked exclusively.
 //!
 //! # Create a new backend instance
 //!
@@ -55,22 +66,31 @@
 //! [`Container::create`]: https://docs.rs/nuts-container/latest/nuts_container/container/struct.Container.html#method.create
 //! [`Container::open`]: https://docs.rs/nuts-container/latest/nuts_container/container/struct.Container.html#method.open
 
+#[cfg(feature = "async")]
+mod asynchronous;
+mod durability;
 mod error;
 mod id;
 mod info;
+mod lock;
 mod options;
+#[cfg(test)]
+mod tests;
 
 use log::{error, warn};
-use nuts_backend::{Backend, ReceiveHeader, HEADER_MAX_SIZE};
+use nuts_backend::{Backend, LockMode, ReceiveHeader, SharedRead, HEADER_MAX_SIZE};
+use std::ffi::OsStr;
 use std::io::{self, ErrorKind, Read, Write};
 use std::path::Path;
 use std::{cmp, fs};
 
+pub use durability::Durability;
 pub use error::Error;
 pub use id::Id;
 pub use info::Info;
 pub use options::{CreateOptions, OpenOptions, Settings};
 
+use crate::durability::{sync_dir, Syncer};
 use crate::error::Result;
 
 fn read_block(path: &Path, id: &Id, bsize: u32, buf: &mut [u8]) -> Result<usize> {
@@ -85,23 +105,20 @@ fn read_block(path: &Path, id: &Id, bsize: u32, buf: &mut [u8]) -> Result<usize>
     Ok(len)
 }
 
-fn write_block(
+/// Checks whether the block file at `path` can be written.
+///
+/// `is_file` is [`None`] if nothing exists at `path`, otherwise it tells
+/// whether `path` is a regular file.
+fn check_block_path(
     path: &Path,
     id: &Id,
     aquire: bool,
     header: bool,
-    bsize: u32,
-    buf: &[u8],
-) -> Result<usize> {
-    let path = id.to_pathbuf(path);
-
-    if let Some(dir) = path.parent() {
-        fs::create_dir_all(dir)?;
-    }
-
+    is_file: Option<bool>,
+) -> Result<()> {
     if aquire {
         // A block is aquired. Allow only to create non-existing files.
-        if path.exists() {
+        if is_file.is_some() {
             return Err(io::Error::new(
                 ErrorKind::Other,
                 format!("cannot aquire {}, already stored in {}", id, path.display()),
@@ -112,7 +129,7 @@ fn write_block(
         // * The header block can be created even if it does not exist.
         // * Any other block must be aquired before, thus open should fail if the
         //   file does not exist.
-        if !header && !path.is_file() {
+        if !header && is_file != Some(true) {
             return Err(io::Error::new(
                 ErrorKind::Other,
                 format!("cannot open {}, no related file {}", id, path.display()),
@@ -121,6 +138,36 @@ fn write_block(
         }
     }
 
+    Ok(())
+}
+
+fn write_block(
+    root: &Path,
+    id: &Id,
+    aquire: bool,
+    header: bool,
+    bsize: u32,
+    syncer: &mut Syncer,
+    buf: &[u8],
+) -> Result<usize> {
+    let path = id.to_pathbuf(root);
+    let mut created = false;
+
+    if let Some(dir) = path.parent() {
+        if !dir.is_dir() {
+            fs::create_dir_all(dir)?;
+            created = true;
+        }
+    }
+
+    let is_file = if path.exists() {
+        Some(path.is_file())
+    } else {
+        None
+    };
+
+    check_block_path(&path, id, aquire, header, is_file)?;
+
     let tmp_path = path.with_extension("tmp");
 
     let mut fh = fs::OpenOptions::new()
@@ -135,29 +182,86 @@ fn write_block(
     fh.write_all(&vec![0; pad_len])?;
     fh.flush()?;
 
-    fs::rename(tmp_path, path)?;
+    if syncer.sync_file() {
+        fh.sync_all()?;
+    }
+
+    fs::rename(tmp_path, &path)?;
+
+    for dir in syncer.written(root, &path, created) {
+        sync_dir(&dir)?;
+    }
 
     Ok(len)
 }
 
+/// Removes the `.tmp` files, which are left behind by an interrupted write.
+///
+/// The block files are located `depth` directories below `path`.
+fn remove_tmp_files(path: &Path, depth: u8) -> Result<()> {
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+
+        if depth > 0 {
+            if path.is_dir() {
+                remove_tmp_files(&path, depth - 1)?;
+            }
+        } else if path.extension() == Some(OsStr::new("tmp")) && path.is_file() {
+            warn!("removing stray file {}", path.display());
+            fs::remove_file(&path)?;
+        }
+    }
+
+    Ok(())
+}
+
 fn read_header(path: &Path, buf: &mut [u8]) -> Result<()> {
     read_block(path, &Id::min(), HEADER_MAX_SIZE as u32, buf).map(|_| ())
 }
 
-fn write_header(path: &Path, bsize: u32, buf: &[u8]) -> Result<()> {
-    write_block(path, &Id::min(), false, true, bsize, buf).map(|_| ())
+fn write_header(path: &Path, bsize: u32, syncer: &mut Syncer, buf: &[u8]) -> Result<()> {
+    write_block(path, &Id::min(), false, true, bsize, syncer, buf).map(|_| ())
+}
+
+fn read_backup_header(path: &Path, buf: &mut [u8]) -> Result<bool> {
+    match read_block(path, &Id::max(), HEADER_MAX_SIZE as u32, buf) {
+        Ok(_) => Ok(true),
+        Err(Error::Io(err)) if err.kind() == ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+fn write_backup_header(path: &Path, bsize: u32, syncer: &mut Syncer, buf: &[u8]) -> Result<()> {
+    write_block(path, &Id::max(), false, true, bsize, syncer, buf).map(|_| ())
 }
 
 #[derive(Debug)]
 pub struct DirectoryBackend<P: AsRef<Path>> {
     bsize: u32,
     path: P,
+    read_only: bool,
+    lock: Option<fs::File>,
+    syncer: Syncer,
+}
+
+impl<P: AsRef<Path>> DirectoryBackend<P> {
+    fn writable(&self) -> Result<()> {
+        if self.read_only {
+            Err(Error::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
 }
 
 impl<P: AsRef<Path>> ReceiveHeader<Self> for DirectoryBackend<P> {
     fn get_header_bytes(&mut self, bytes: &mut [u8; HEADER_MAX_SIZE]) -> Result<()> {
         read_header(self.path.as_ref(), bytes)
     }
+
+    fn get_backup_header_bytes(&mut self, bytes: &mut [u8; HEADER_MAX_SIZE]) -> Result<bool> {
+        read_backup_header(self.path.as_ref(), bytes)
+    }
 }
 
 impl<P: AsRef<Path>> Backend for DirectoryBackend<P> {
@@ -167,7 +271,10 @@ impl<P: AsRef<Path>> Backend for DirectoryBackend<P> {
     type Info = Info;
 
     fn info(&self) -> Result<Info> {
-        Ok(Info { bsize: self.bsize })
+        Ok(Info {
+            bsize: self.bsize,
+            durability: self.syncer.durability(),
+        })
     }
 
     fn block_size(&self) -> u32 {
@@ -177,10 +284,20 @@ impl<P: AsRef<Path>> Backend for DirectoryBackend<P> {
     fn aquire(&mut self, buf: &[u8]) -> Result<Self::Id> {
         const MAX: u8 = 3;
 
+        self.writable()?;
+
         for n in 0..MAX {
             let id = Id::generate()?;
 
-            match write_block(self.path.as_ref(), &id, true, false, self.bsize, buf) {
+            match write_block(
+                self.path.as_ref(),
+                &id,
+                true,
+                false,
+                self.bsize,
+                &mut self.syncer,
+                buf,
+            ) {
                 Ok(_) => return Ok(id),
                 Err(Error::Io(err)) => {
                     if err.kind() == ErrorKind::AlreadyExists {
@@ -197,9 +314,17 @@ impl<P: AsRef<Path>> Backend for DirectoryBackend<P> {
     }
 
     fn release(&mut self, id: Self::Id) -> Result<()> {
+        self.writable()?;
+
         let path = id.to_pathbuf(self.path.as_ref());
 
-        Ok(fs::remove_file(path)?)
+        fs::remove_file(&path)?;
+
+        for dir in self.syncer.removed(&path) {
+            sync_dir(&dir)?;
+        }
+
+        Ok(())
     }
 
     fn read(&mut self, id: &Id, buf: &mut [u8]) -> Result<usize> {
@@ -207,16 +332,72 @@ impl<P: AsRef<Path>> Backend for DirectoryBackend<P> {
     }
 
     fn write(&mut self, id: &Id, buf: &[u8]) -> Result<usize> {
-        write_block(self.path.as_ref(), id, false, false, self.bsize, buf)
+        self.writable()?;
+        write_block(
+            self.path.as_ref(),
+            id,
+            false,
+            false,
+            self.bsize,
+            &mut self.syncer,
+            buf,
+        )
     }
 
     fn write_header(&mut self, buf: &[u8; HEADER_MAX_SIZE]) -> Result<()> {
-        write_header(self.path.as_ref(), self.bsize, buf)
+        self.writable()?;
+        write_header(self.path.as_ref(), self.bsize, &mut self.syncer, buf)
+    }
+
+    fn write_backup_header(&mut self, buf: &[u8; HEADER_MAX_SIZE]) -> Result<()> {
+        self.writable()?;
+        write_backup_header(self.path.as_ref(), self.bsize, &mut self.syncer, buf)
     }
 
-    fn delete(self) {
-        if let Err(err) = fs::remove_dir_all(self.path) {
+    fn flush(&mut self) -> Result<()> {
+        self.syncer.flush()
+    }
+
+    fn lock(&mut self, mode: LockMode) -> Result<()> {
+        // convert a previous lock, the same file must not be locked twice
+        match self.lock.as_ref() {
+            Some(file) => lock::relock(file, mode)?,
+            None => self.lock = lock::lock(self.path.as_ref(), mode)?,
+        }
+
+        // no one else writes into the backend
+        if mode == LockMode::Exclusive && !self.read_only {
+            remove_tmp_files(self.path.as_ref(), 2)?;
+        }
+
+        Ok(())
+    }
+
+    fn delete(mut self) {
+        if self.read_only {
+            error!("cannot delete a read-only backend instance");
+            return;
+        }
+
+        // nothing to sync anymore
+        self.syncer.clear();
+
+        if let Err(err) = fs::remove_dir_all(self.path.as_ref()) {
             error!("failed to delete backend instance: {}", err);
         }
     }
 }
+
+impl<P: AsRef<Path>> Drop for DirectoryBackend<P> {
+    fn drop(&mut self) {
+        if let Err(err) = self.syncer.flush() {
+            error!("failed to sync the backend: {}", err);
+        }
+    }
+}
+
+impl<P: AsRef<Path>> SharedRead for DirectoryBackend<P> {
+    fn read_shared(&self, id: &Id, buf: &mut [u8]) -> Result<usize> {
+        read_block(self.path.as_ref(), id, self.bsize, buf)
+    }
+}
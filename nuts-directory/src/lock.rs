@@ -0,0 +1,106 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+//! Locking of the backend.
+//!
+//! The lock is taken with `flock(2)` on the backend directory itself, there
+//! is no separate lock file. This is intentional: a lock file had to be
+//! created, but a read-only backend, e.g. on read-only media or in a
+//! directory owned by another user, must not create any files. The directory
+//! exists for every backend, thus it can always be opened and locked.
+//!
+//! `flock(2)` locks are bound to the open file description. A second
+//! [`lock()`] on the same directory conflicts, even from the same process, as
+//! long as the first returned [`File`] is open. Locks are advisory, they only
+//! protect against other `nuts` processes.
+
+#[cfg(all(test, unix))]
+mod tests;
+
+#[cfg(unix)]
+use log::debug;
+#[cfg(not(unix))]
+use log::warn;
+use nuts_backend::LockMode;
+use std::fs::File;
+#[cfg(unix)]
+use std::io::{self, ErrorKind};
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+#[cfg(unix)]
+use crate::error::Error;
+use crate::error::Result;
+
+/// Locks the backend stored at `path`.
+///
+/// The lock is taken with `flock(2)` on the directory of the backend itself,
+/// thus a read-only backend is locked without creating a file. The returned
+/// file keeps the lock, it is released again when the file is closed.
+///
+/// Fails with [`Error::Locked`](crate::Error::Locked), if another process
+/// holds a conflicting lock.
+#[cfg(unix)]
+pub fn lock(path: &Path, mode: LockMode) -> Result<Option<File>> {
+    let file = File::open(path)?;
+
+    relock(&file, mode)?;
+    debug!("{:?} lock taken on {}", mode, path.display());
+
+    Ok(Some(file))
+}
+
+/// Converts the lock held by `file` into `mode`.
+///
+/// Fails with [`Error::Locked`](crate::Error::Locked), if another process
+/// holds a conflicting lock.
+#[cfg(unix)]
+pub fn relock(file: &File, mode: LockMode) -> Result<()> {
+    let operation = match mode {
+        LockMode::Shared => libc::LOCK_SH,
+        LockMode::Exclusive => libc::LOCK_EX,
+    };
+
+    if unsafe { libc::flock(file.as_raw_fd(), operation | libc::LOCK_NB) } == 0 {
+        Ok(())
+    } else {
+        let err = io::Error::last_os_error();
+
+        if err.kind() == ErrorKind::WouldBlock {
+            Err(Error::Locked)
+        } else {
+            Err(err.into())
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn lock(_path: &Path, _mode: LockMode) -> Result<Option<File>> {
+    warn!("locking is not supported on this platform");
+    Ok(None)
+}
+
+#[cfg(not(unix))]
+pub fn relock(_file: &File, _mode: LockMode) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,77 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::LockMode;
+use std::fs;
+use tempfile::TempDir;
+
+use crate::error::Error;
+use crate::lock::{lock, relock};
+
+#[test]
+fn exclusive() {
+    let dir = TempDir::new().unwrap();
+
+    let file = lock(dir.path(), LockMode::Exclusive).unwrap();
+    assert!(file.is_some());
+
+    for mode in [LockMode::Exclusive, LockMode::Shared].iter() {
+        let err = lock(dir.path(), *mode).unwrap_err();
+        assert!(matches!(err, Error::Locked));
+    }
+
+    drop(file);
+
+    assert!(lock(dir.path(), LockMode::Exclusive).unwrap().is_some());
+}
+
+#[test]
+fn shared() {
+    let dir = TempDir::new().unwrap();
+
+    let file1 = lock(dir.path(), LockMode::Shared).unwrap();
+    let file2 = lock(dir.path(), LockMode::Shared).unwrap();
+    assert!(file1.is_some());
+    assert!(file2.is_some());
+
+    let err = lock(dir.path(), LockMode::Exclusive).unwrap_err();
+    assert!(matches!(err, Error::Locked));
+}
+
+#[test]
+fn relock_shared() {
+    let dir = TempDir::new().unwrap();
+
+    let file = lock(dir.path(), LockMode::Exclusive).unwrap().unwrap();
+    relock(&file, LockMode::Shared).unwrap();
+
+    assert!(lock(dir.path(), LockMode::Shared).unwrap().is_some());
+}
+
+#[test]
+fn no_file_created() {
+    let dir = TempDir::new().unwrap();
+
+    let file = lock(dir.path(), LockMode::Shared).unwrap();
+    assert!(file.is_some());
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+}
@@ -22,7 +22,7 @@
 
 use log::error;
 use nuts_backend::Backend;
-use nuts_directory::{CreateOptions, DirectoryBackend, Info, OpenOptions};
+use nuts_directory::{CreateOptions, DirectoryBackend, Durability, Info, OpenOptions};
 use nuts_tool_api::plugin::clap_prelude::*;
 use nuts_tool_api::plugin::cli::{CreateArgs, OpenArgs, SizeArg};
 use nuts_tool_api::plugin::{PluginHandler, PluginRunner};
@@ -36,6 +36,19 @@ struct ExtraArgs {
     /// Set the block-size to SIZE
     #[clap(short, long, id = "SIZE", default_value = "512")]
     block_size: SizeArg<u32>,
+
+    /// Specifies when written blocks are synced to disk: never explicitly
+    /// (none), on every write (write) or when the container is flushed (flush)
+    #[clap(long, id = "DURABILITY", default_value = "none")]
+    durability: Durability,
+}
+
+fn info_to_hash(info: Info) -> HashMap<String, String> {
+    [
+        ("block_size".to_string(), info.bsize.to_string()),
+        ("durability".to_string(), info.durability.to_string()),
+    ]
+    .into()
 }
 
 struct DirectoryPluginInformation;
@@ -50,7 +63,7 @@ impl PluginHandler<DirectoryBackend<PathBuf>> for DirectoryPluginInformation {
     }
 
     fn info_to_hash(&self, info: Info) -> Option<HashMap<String, String>> {
-        Some([("block_size".to_string(), info.bsize.to_string())].into())
+        Some(info_to_hash(info))
     }
 
     fn open_builder(&self, args: &OpenArgs) -> Option<OpenOptions<PathBuf>> {
@@ -65,7 +78,11 @@ impl PluginHandler<DirectoryBackend<PathBuf>> for DirectoryPluginInformation {
 
     fn create_builder(&self, args: &CreateArgs<ExtraArgs>) -> Option<CreateOptions<PathBuf>> {
         match container_dir_for(&args.name) {
-            Ok(path) => Some(CreateOptions::for_path(path).with_bsize(*args.extra.block_size)),
+            Ok(path) => Some(
+                CreateOptions::for_path(path)
+                    .with_bsize(*args.extra.block_size)
+                    .with_durability(args.extra.durability),
+            ),
             Err(err) => {
                 error!("could not detect container dir for {}: {}", args.name, err);
                 None
@@ -78,7 +95,7 @@ impl PluginHandler<DirectoryBackend<PathBuf>> for DirectoryPluginInformation {
         backend: &DirectoryBackend<PathBuf>,
     ) -> Result<HashMap<String, String>, ErrorResponse> {
         match backend.info() {
-            Ok(info) => Ok([("block_size".to_string(), info.bsize.to_string())].into()),
+            Ok(info) => Ok(info_to_hash(info)),
             Err(err) => Err(ErrorResponse::backend::<DirectoryBackend<PathBuf>>(err)),
         }
     }
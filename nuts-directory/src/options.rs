@@ -23,13 +23,16 @@
 #[cfg(test)]
 mod tests;
 
-use nuts_backend::{Binary, Create, Open, ReceiveHeader, HEADER_MAX_SIZE};
+use nuts_backend::{Binary, Create, LockMode, Open, ReceiveHeader, HEADER_MAX_SIZE};
 use std::convert::TryInto;
+use std::fs;
 use std::path::Path;
 
+use crate::durability::{Durability, Syncer};
 use crate::error::{Error, Result};
 use crate::id::Id;
-use crate::{read_header, write_header, DirectoryBackend};
+use crate::lock;
+use crate::{read_backup_header, read_header, write_header, DirectoryBackend};
 
 const BLOCK_MIN_SIZE: u32 = 512;
 
@@ -43,10 +46,13 @@ const BLOCK_MIN_SIZE: u32 = 512;
 /// * [`CreateOptions::with_bsize()`]: Specifies the block size of the backend.
 ///   This is the number of bytes, which can  be stored in an individual block.
 ///   The minimum block size is 512 bytes. The default is `512`.
+/// * [`CreateOptions::with_durability()`]: Specifies when written blocks are
+///   synced to disk. The default is [`Durability::None`].
 #[derive(Clone, Debug)]
 pub struct CreateOptions<P: AsRef<Path>> {
     path: P,
     bsize: u32,
+    durability: Durability,
 }
 
 impl<P: AsRef<Path>> CreateOptions<P> {
@@ -60,6 +66,7 @@ impl<P: AsRef<Path>> CreateOptions<P> {
         CreateOptions {
             path,
             bsize: BLOCK_MIN_SIZE,
+            durability: Durability::default(),
         }
     }
 
@@ -72,6 +79,14 @@ impl<P: AsRef<Path>> CreateOptions<P> {
         self
     }
 
+    /// Assigns a new durability to the options.
+    ///
+    /// Specifies when written blocks are synced to disk.
+    pub fn with_durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
+    }
+
     fn validate(&self) -> Result<()> {
         if self.bsize >= BLOCK_MIN_SIZE {
             Ok(())
@@ -83,12 +98,20 @@ impl<P: AsRef<Path>> CreateOptions<P> {
 
 impl<P: AsRef<Path>> Create<DirectoryBackend<P>> for CreateOptions<P> {
     fn settings(&self) -> Settings {
-        Settings { bsize: self.bsize }
+        Settings {
+            bsize: self.bsize,
+            durability: self.durability,
+        }
     }
 
     fn build(self, header: [u8; HEADER_MAX_SIZE], overwrite: bool) -> Result<DirectoryBackend<P>> {
         self.validate()?;
 
+        // lock before the header is touched, a concurrent create must not
+        // clobber it
+        fs::create_dir_all(self.path.as_ref())?;
+        let lock = lock::lock(self.path.as_ref(), LockMode::Exclusive)?;
+
         if !overwrite {
             let header_path = Id::min().to_pathbuf(self.path.as_ref());
 
@@ -97,11 +120,18 @@ impl<P: AsRef<Path>> Create<DirectoryBackend<P>> for CreateOptions<P> {
             }
         }
 
-        write_header(self.path.as_ref(), self.bsize, &header)?;
+        // a new container is durable at once
+        let mut syncer = Syncer::new(self.durability);
+
+        write_header(self.path.as_ref(), self.bsize, &mut syncer, &header)?;
+        syncer.flush()?;
 
         Ok(DirectoryBackend {
             bsize: self.bsize,
             path: self.path,
+            read_only: false,
+            lock,
+            syncer,
         })
     }
 }
@@ -110,8 +140,12 @@ impl<P: AsRef<Path>> Create<DirectoryBackend<P>> for CreateOptions<P> {
 ///
 /// You must pass the path, where the directory tree is stored, to
 /// [`OpenOptions::for_path()`], if creating a `OpenOptions` instance.
+///
+/// If the container is opened read-only, the backend rejects any
+/// modification and never creates a file in the directory tree.
 pub struct OpenOptions<P: AsRef<Path>> {
     path: P,
+    read_only: bool,
 }
 
 impl<P: AsRef<Path>> OpenOptions<P> {
@@ -120,7 +154,10 @@ impl<P: AsRef<Path>> OpenOptions<P> {
     /// You must pass the `path`, where the directory tree should is stored, to
     /// the function.
     pub fn for_path(path: P) -> OpenOptions<P> {
-        OpenOptions { path }
+        OpenOptions {
+            path,
+            read_only: false,
+        }
     }
 }
 
@@ -128,35 +165,59 @@ impl<P: AsRef<Path>> ReceiveHeader<DirectoryBackend<P>> for OpenOptions<P> {
     fn get_header_bytes(&mut self, bytes: &mut [u8; HEADER_MAX_SIZE]) -> Result<()> {
         read_header(self.path.as_ref(), bytes)
     }
+
+    fn get_backup_header_bytes(&mut self, bytes: &mut [u8; HEADER_MAX_SIZE]) -> Result<bool> {
+        read_backup_header(self.path.as_ref(), bytes)
+    }
 }
 
 impl<P: AsRef<Path>> Open<DirectoryBackend<P>> for OpenOptions<P> {
+    fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
     fn build(self, settings: Settings) -> Result<DirectoryBackend<P>> {
         Ok(DirectoryBackend {
             bsize: settings.bsize,
             path: self.path,
+            read_only: self.read_only,
+            lock: None,
+            syncer: Syncer::new(settings.durability),
         })
     }
 }
 
 /// [Settings](nuts_backend::Backend::Settings) used by the backend.
+///
+/// The durability is only encoded, if it differs from
+/// [`Durability::None`], thus the settings of such a backend are still
+/// readable by older versions.
 #[derive(Clone, Debug)]
 pub struct Settings {
     bsize: u32,
+    durability: Durability,
 }
 
 impl Binary for Settings {
     fn from_bytes(bytes: &[u8]) -> Option<Settings> {
-        match bytes.try_into() {
-            Ok(bytes) => {
-                let bsize = u32::from_be_bytes(bytes);
-                Some(Settings { bsize })
-            }
-            Err(_) => None,
-        }
+        let (bsize, durability) = match bytes.len() {
+            4 => (bytes, Durability::None),
+            5 => (&bytes[..4], Durability::from_u8(bytes[4])?),
+            _ => return None,
+        };
+
+        let bsize = u32::from_be_bytes(bsize.try_into().ok()?);
+
+        Some(Settings { bsize, durability })
     }
 
     fn as_bytes(&self) -> Vec<u8> {
-        self.bsize.to_be_bytes().to_vec()
+        let mut bytes = self.bsize.to_be_bytes().to_vec();
+
+        if self.durability != Durability::None {
+            bytes.push(self.durability.as_u8());
+        }
+
+        bytes
     }
 }
@@ -22,3 +22,4 @@
 
 mod create;
 mod open;
+mod settings;
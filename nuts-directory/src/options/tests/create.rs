@@ -20,7 +20,16 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
+#[cfg(unix)]
+use nuts_backend::LockMode;
+use nuts_backend::{Create, HEADER_MAX_SIZE};
+use tempfile::TempDir;
+
+use crate::durability::Durability;
 use crate::error::Error;
+use crate::id::Id;
+#[cfg(unix)]
+use crate::lock::lock;
 use crate::options::CreateOptions;
 
 #[test]
@@ -31,6 +40,7 @@ fn valid_default() {
 
     assert_eq!(options.path, "foo");
     assert_eq!(options.bsize, 512);
+    assert_eq!(options.durability, Durability::None);
 }
 
 #[test]
@@ -53,3 +63,47 @@ fn valid_bsize() {
         assert_eq!(options.bsize, n);
     }
 }
+
+#[test]
+fn durability() {
+    for durability in [Durability::None, Durability::Write, Durability::Flush] {
+        let options = CreateOptions::for_path("foo").with_durability(durability);
+
+        options.validate().unwrap();
+
+        assert_eq!(options.durability, durability);
+        assert_eq!(options.settings().durability, durability);
+    }
+}
+
+#[test]
+fn build() {
+    let dir = TempDir::new().unwrap();
+
+    let backend = CreateOptions::for_path(dir.path())
+        .build([1; HEADER_MAX_SIZE], false)
+        .unwrap();
+    assert!(Id::min().to_pathbuf(dir.path()).exists());
+
+    drop(backend);
+
+    let err = CreateOptions::for_path(dir.path())
+        .build([2; HEADER_MAX_SIZE], false)
+        .unwrap_err();
+    assert!(matches!(err, Error::Exists));
+}
+
+#[cfg(unix)]
+#[test]
+fn build_locked() {
+    let dir = TempDir::new().unwrap();
+    let _file = lock(dir.path(), LockMode::Shared).unwrap();
+
+    let err = CreateOptions::for_path(dir.path())
+        .build([1; HEADER_MAX_SIZE], true)
+        .unwrap_err();
+    assert!(matches!(err, Error::Locked));
+
+    // the header is not touched
+    assert!(!Id::min().to_pathbuf(dir.path()).exists());
+}
@@ -20,6 +20,8 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
+use nuts_backend::Open;
+
 use crate::options::OpenOptions;
 
 #[test]
@@ -27,4 +29,13 @@ fn for_path() {
     let options = OpenOptions::for_path("foo");
 
     assert_eq!(options.path, "foo");
+    assert!(!options.read_only);
+}
+
+#[test]
+fn set_read_only() {
+    let mut options = OpenOptions::for_path("foo");
+
+    options.set_read_only(true);
+    assert!(options.read_only);
 }
@@ -0,0 +1,85 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::Binary;
+
+use crate::durability::Durability;
+use crate::options::Settings;
+
+#[test]
+fn as_bytes_none() {
+    let settings = Settings {
+        bsize: 1024,
+        durability: Durability::None,
+    };
+
+    assert_eq!(settings.as_bytes(), [0, 0, 4, 0]);
+}
+
+#[test]
+fn as_bytes_write() {
+    let settings = Settings {
+        bsize: 1024,
+        durability: Durability::Write,
+    };
+
+    assert_eq!(settings.as_bytes(), [0, 0, 4, 0, 1]);
+}
+
+#[test]
+fn as_bytes_flush() {
+    let settings = Settings {
+        bsize: 1024,
+        durability: Durability::Flush,
+    };
+
+    assert_eq!(settings.as_bytes(), [0, 0, 4, 0, 2]);
+}
+
+#[test]
+fn from_bytes_without_durability() {
+    let settings = Settings::from_bytes(&[0, 0, 4, 0]).unwrap();
+
+    assert_eq!(settings.bsize, 1024);
+    assert_eq!(settings.durability, Durability::None);
+}
+
+#[test]
+fn from_bytes_with_durability() {
+    for (n, durability) in [
+        (0, Durability::None),
+        (1, Durability::Write),
+        (2, Durability::Flush),
+    ] {
+        let settings = Settings::from_bytes(&[0, 0, 4, 0, n]).unwrap();
+
+        assert_eq!(settings.bsize, 1024);
+        assert_eq!(settings.durability, durability);
+    }
+}
+
+#[test]
+fn from_bytes_invalid() {
+    assert!(Settings::from_bytes(&[0, 0, 4]).is_none());
+    assert!(Settings::from_bytes(&[0, 0, 4, 0, 3]).is_none());
+    assert!(Settings::from_bytes(&[0, 0, 4, 0, 1, 0]).is_none());
+}
@@ -0,0 +1,100 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::{Backend, Create, LockMode, Open, HEADER_MAX_SIZE};
+use std::fs;
+use std::io::ErrorKind;
+use tempfile::TempDir;
+
+use crate::{CreateOptions, DirectoryBackend, Durability, Error, OpenOptions};
+
+fn create(dir: &TempDir, durability: Durability) -> DirectoryBackend<&std::path::Path> {
+    CreateOptions::for_path(dir.path())
+        .with_durability(durability)
+        .build([1; HEADER_MAX_SIZE], false)
+        .unwrap()
+}
+
+#[test]
+fn durability() {
+    for durability in [Durability::None, Durability::Write, Durability::Flush] {
+        let dir = TempDir::new().unwrap();
+        let options = CreateOptions::for_path(dir.path()).with_durability(durability);
+        let settings = options.settings();
+
+        let mut backend = options.build([1; HEADER_MAX_SIZE], false).unwrap();
+        assert_eq!(backend.info().unwrap().durability, durability);
+
+        let id = backend.aquire(b"abc").unwrap();
+        backend.write(&id, b"xyz").unwrap();
+        backend.flush().unwrap();
+        backend.release(id).unwrap();
+        backend.flush().unwrap();
+        drop(backend);
+
+        let backend = OpenOptions::for_path(dir.path()).build(settings).unwrap();
+        assert_eq!(backend.info().unwrap().durability, durability);
+    }
+}
+
+#[test]
+fn remove_tmp_files() {
+    let dir = TempDir::new().unwrap();
+    let mut backend = create(&dir, Durability::None);
+
+    let id = backend.aquire(b"abc").unwrap();
+    let path = id.to_pathbuf(dir.path());
+    let tmp_path = path.with_extension("tmp");
+
+    // left behind by an interrupted write
+    fs::write(&tmp_path, b"xyz").unwrap();
+
+    let err = backend.write(&id, b"xyz").unwrap_err();
+    assert!(matches!(err, Error::Io(cause) if cause.kind() == ErrorKind::AlreadyExists));
+
+    backend.lock(LockMode::Exclusive).unwrap();
+    assert!(!tmp_path.exists());
+    assert!(path.is_file());
+
+    backend.write(&id, b"xyz").unwrap();
+}
+
+#[test]
+fn keep_tmp_files_read_only() {
+    let dir = TempDir::new().unwrap();
+    let options = CreateOptions::for_path(dir.path());
+    let settings = options.settings();
+    let mut backend = options.build([1; HEADER_MAX_SIZE], false).unwrap();
+
+    let id = backend.aquire(b"abc").unwrap();
+    let tmp_path = id.to_pathbuf(dir.path()).with_extension("tmp");
+
+    fs::write(&tmp_path, b"xyz").unwrap();
+    drop(backend);
+
+    let mut options = OpenOptions::for_path(dir.path());
+    options.set_read_only(true);
+
+    let mut backend = options.build(settings).unwrap();
+    backend.lock(LockMode::Shared).unwrap();
+    assert!(tmp_path.is_file());
+}
@@ -0,0 +1,149 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use crate::layout::Layout;
+
+/// The allocation state of the blocks.
+///
+/// Keeps a copy of the bitmap blocks stored in the image. Block `n` of a
+/// group is represented by bit `n % 8` of byte `n / 8` of the bitmap.
+///
+/// Blocks are allocated from a free-list first, which is built from the holes
+/// in the bitmaps and receives every released block. The image only grows if
+/// the free-list is empty.
+#[derive(Debug)]
+pub struct Bitmap {
+    layout: Layout,
+    groups: Vec<Vec<u8>>,
+    next: u64,
+    free: Vec<u64>,
+    allocated: u64,
+}
+
+impl Bitmap {
+    /// Creates a bitmap from the bitmap blocks of the image.
+    pub fn new(layout: Layout, groups: Vec<Vec<u8>>) -> Bitmap {
+        let mut bitmap = Bitmap {
+            layout,
+            groups,
+            next: 0,
+            free: vec![],
+            allocated: 0,
+        };
+
+        let nblocks = bitmap.groups.len() as u64 * layout.blocks_per_group();
+
+        for n in 0..nblocks {
+            if bitmap.is_allocated(n) {
+                bitmap.next = n + 1;
+                bitmap.allocated += 1;
+            }
+        }
+
+        // lowest block on top of the list
+        bitmap.free = (0..bitmap.next)
+            .rev()
+            .filter(|n| !bitmap.is_allocated(*n))
+            .collect();
+
+        bitmap
+    }
+
+    /// Tests whether block `n` is allocated.
+    pub fn is_allocated(&self, n: u64) -> bool {
+        let (group, idx) = self.layout.group_of(n);
+
+        match self.groups.get(group as usize) {
+            Some(bits) => bits[idx as usize / 8] & (1 << (idx % 8)) != 0,
+            None => false,
+        }
+    }
+
+    /// Returns the number of allocated blocks.
+    pub fn allocated(&self) -> u64 {
+        self.allocated
+    }
+
+    /// Returns the block, which is allocated next.
+    pub fn peek(&self) -> u64 {
+        self.free.last().copied().unwrap_or(self.next)
+    }
+
+    /// Returns the byte of the bitmap, which holds the bit of block `n`.
+    ///
+    /// The bit is updated with `value`, the bitmap itself is not modified.
+    /// Returns the group, the offset of the byte in the bitmap block and the
+    /// updated byte.
+    pub fn entry(&self, n: u64, value: bool) -> (u64, u64, u8) {
+        let (group, idx) = self.layout.group_of(n);
+        let offs = idx / 8;
+        let mask = 1 << (idx % 8);
+        let byte = self
+            .groups
+            .get(group as usize)
+            .map(|bits| bits[offs as usize])
+            .unwrap_or(0);
+
+        if value {
+            (group, offs, byte | mask)
+        } else {
+            (group, offs, byte & !mask)
+        }
+    }
+
+    /// Marks block `n` as allocated.
+    ///
+    /// `n` must be the block returned by [`Bitmap::peek()`].
+    pub fn allocate(&mut self, n: u64) {
+        if self.free.last() == Some(&n) {
+            self.free.pop();
+        } else {
+            self.next = n + 1;
+        }
+
+        self.update(n, true);
+    }
+
+    /// Marks block `n` as free and puts it into the free-list.
+    pub fn release(&mut self, n: u64) {
+        self.free.push(n);
+        self.update(n, false);
+    }
+
+    fn update(&mut self, n: u64, value: bool) {
+        let (group, offs, byte) = self.entry(n, value);
+        while self.groups.len() <= group as usize {
+            self.groups.push(vec![0; self.layout.bsize() as usize]);
+        }
+
+        self.groups[group as usize][offs as usize] = byte;
+
+        if value {
+            self.allocated += 1;
+        } else {
+            self.allocated -= 1;
+        }
+    }
+}
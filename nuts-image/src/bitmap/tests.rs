@@ -0,0 +1,140 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use crate::bitmap::Bitmap;
+use crate::layout::Layout;
+
+fn empty() -> Bitmap {
+    Bitmap::new(Layout::new(512), vec![])
+}
+
+#[test]
+fn new_empty() {
+    let bitmap = empty();
+
+    assert!(bitmap.groups.is_empty());
+    assert_eq!(bitmap.next, 0);
+    assert!(bitmap.free.is_empty());
+    assert_eq!(bitmap.allocated(), 0);
+    assert_eq!(bitmap.peek(), 0);
+}
+
+#[test]
+fn new_holes() {
+    let mut group0 = vec![0; 512];
+    let mut group1 = vec![0; 512];
+
+    group0[0] = 0b1010_0101;
+    group1[1] = 0b0000_0001;
+
+    let bitmap = Bitmap::new(Layout::new(512), vec![group0, group1]);
+
+    assert_eq!(bitmap.next, 4096 + 8 + 1);
+    assert_eq!(bitmap.allocated(), 5);
+    assert_eq!(bitmap.free.len(), 4096 + 8 + 1 - 5);
+    assert_eq!(bitmap.free[bitmap.free.len() - 3..], [4, 3, 1]);
+    assert_eq!(bitmap.peek(), 1);
+
+    for n in [0, 2, 5, 7, 4096 + 8].iter() {
+        assert!(bitmap.is_allocated(*n));
+    }
+
+    for n in [1, 3, 4, 6, 8, 4096, 4096 + 9, 8192, 100000].iter() {
+        assert!(!bitmap.is_allocated(*n));
+    }
+}
+
+#[test]
+fn allocate() {
+    let mut bitmap = empty();
+
+    for n in 0..3 {
+        assert_eq!(bitmap.peek(), n);
+        bitmap.allocate(n);
+    }
+
+    assert_eq!(
+        bitmap.groups,
+        [{
+            let mut bits = vec![0; 512];
+            bits[0] = 0b111;
+            bits
+        }]
+    );
+    assert_eq!(bitmap.next, 3);
+    assert_eq!(bitmap.allocated(), 3);
+}
+
+#[test]
+fn allocate_next_group() {
+    let mut bitmap = empty();
+
+    for n in 0..4097 {
+        bitmap.allocate(n);
+    }
+
+    assert_eq!(bitmap.groups.len(), 2);
+    assert_eq!(bitmap.groups[0], [0xff; 512]);
+    assert_eq!(bitmap.groups[1][0], 1);
+    assert_eq!(bitmap.allocated(), 4097);
+}
+
+#[test]
+fn release() {
+    let mut bitmap = empty();
+
+    for n in 0..4 {
+        bitmap.allocate(n);
+    }
+
+    bitmap.release(1);
+    bitmap.release(2);
+
+    assert_eq!(bitmap.groups[0][0], 0b1001);
+    assert_eq!(bitmap.allocated(), 2);
+
+    // the free-list is used before the image grows
+    assert_eq!(bitmap.peek(), 2);
+    bitmap.allocate(2);
+    assert_eq!(bitmap.peek(), 1);
+    bitmap.allocate(1);
+    assert_eq!(bitmap.peek(), 4);
+
+    assert_eq!(bitmap.groups[0][0], 0b1111);
+    assert_eq!(bitmap.allocated(), 4);
+}
+
+#[test]
+fn entry() {
+    let mut bitmap = empty();
+
+    assert_eq!(bitmap.entry(4096 + 9, true), (1, 1, 0b10));
+
+    bitmap.allocate(0);
+    bitmap.allocate(1);
+
+    assert_eq!(bitmap.entry(2, true), (0, 0, 0b111));
+    assert_eq!(bitmap.entry(1, false), (0, 0, 0b01));
+
+    // not modified
+    assert_eq!(bitmap.groups[0][0], 0b11);
+}
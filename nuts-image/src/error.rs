@@ -0,0 +1,98 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use std::{error, fmt, io, result};
+
+use crate::id::Id;
+
+/// The error type for the image backend.
+#[derive(Debug)]
+pub enum Error {
+    /// An I/O error occured.
+    Io(io::Error),
+
+    /// You are creating a new backend which already exists.
+    Exists,
+
+    /// The [id](crate::Id) is invalid, is not a number.
+    InvalidId(String),
+
+    /// The block size passed to [CreateOptions](crate::CreateOptions) is
+    /// invalid.
+    InvalidBlockSize(u32),
+
+    /// The capacity passed to [CreateOptions](crate::CreateOptions) is
+    /// invalid.
+    InvalidCapacity(u64),
+
+    /// The block with the given [id](crate::Id) is not allocated.
+    NotAllocated(Id),
+
+    /// All blocks of a preallocated image are in use.
+    Full,
+
+    /// The backend is opened read-only and cannot be modified.
+    ReadOnly,
+
+    /// The backend is locked by another process.
+    Locked,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(cause) => fmt::Display::fmt(cause, fmt),
+            Error::Exists => write!(fmt, "The container already exists"),
+            Error::InvalidId(id) => write!(fmt, "The id '{}' is invalid", id),
+            Error::InvalidBlockSize(n) => write!(fmt, "The block-size is invalid: {}", n),
+            Error::InvalidCapacity(n) => write!(fmt, "The capacity is invalid: {}", n),
+            Error::NotAllocated(id) => write!(fmt, "The block {} is not allocated", id),
+            Error::Full => write!(fmt, "No free block left in the image"),
+            Error::ReadOnly => write!(fmt, "The backend is opened read-only"),
+            Error::Locked => write!(fmt, "The container is locked by another process"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(cause) => Some(cause),
+            Error::Exists
+            | Error::InvalidId(_)
+            | Error::InvalidBlockSize(_)
+            | Error::InvalidCapacity(_)
+            | Error::NotAllocated(_)
+            | Error::Full
+            | Error::ReadOnly
+            | Error::Locked => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(cause: io::Error) -> Self {
+        Error::Io(cause)
+    }
+}
+
+pub type Result<T> = result::Result<T, Error>;
@@ -0,0 +1,103 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use std::fs::File;
+use std::io::{self, ErrorKind};
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
+
+#[cfg(unix)]
+fn write_at(file: &File, buf: &[u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::write_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn write_at(file: &File, buf: &[u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_write(file, buf, offset)
+}
+
+/// Reads from `file` at `offset` until `buf` is full or the end of the file
+/// is reached.
+///
+/// Returns the number of bytes read.
+pub fn fill_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    let mut pos = 0;
+
+    while pos < buf.len() {
+        match read_at(file, &mut buf[pos..], offset + pos as u64) {
+            Ok(0) => break,
+            Ok(n) => pos += n,
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(pos)
+}
+
+/// Writes the whole `buf` into `file` at `offset`.
+pub fn write_all_at(file: &File, buf: &[u8], offset: u64) -> io::Result<()> {
+    let mut pos = 0;
+
+    while pos < buf.len() {
+        match write_at(file, &buf[pos..], offset + pos as u64) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(n) => pos += n,
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// Reserves disk space for the first `len` bytes of `file`.
+///
+/// The space is allocated with `posix_fallocate(3)`, thus writing into the
+/// reserved range cannot fail because the disk is full. On other platforms
+/// the file is only extended, which might create a sparse file.
+#[cfg(target_os = "linux")]
+pub fn allocate(file: &File, len: u64) -> io::Result<()> {
+    use std::convert::TryFrom;
+    use std::os::unix::io::AsRawFd;
+
+    let len = libc::off_t::try_from(len)
+        .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "image too large"))?;
+
+    match unsafe { libc::posix_fallocate(file.as_raw_fd(), 0, len) } {
+        0 => Ok(()),
+        errno => Err(io::Error::from_raw_os_error(errno)),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn allocate(file: &File, len: u64) -> io::Result<()> {
+    file.set_len(len)
+}
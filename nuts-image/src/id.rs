@@ -0,0 +1,90 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use nuts_backend::{Binary, IdSize};
+use std::convert::TryInto;
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+
+/// The [id](nuts_backend::Backend::Id) of the backend.
+///
+/// The id is the index of the block in the image, the first block has the
+/// id `0`.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Id(u64);
+
+impl Id {
+    pub(crate) fn new(n: u64) -> Id {
+        Id(n)
+    }
+
+    pub(crate) fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Binary for Id {
+    fn from_bytes(bytes: &[u8]) -> Option<Id> {
+        match bytes.try_into() {
+            Ok(buf) => Some(Id(u64::from_be_bytes(buf))),
+            Err(_) => None,
+        }
+    }
+
+    fn as_bytes(&self) -> Vec<u8> {
+        self.0.to_be_bytes().to_vec()
+    }
+}
+
+impl IdSize for Id {
+    fn size() -> usize {
+        mem::size_of::<u64>()
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, fmt)
+    }
+}
+
+impl fmt::Debug for Id {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("Id").field(&self.0).finish()
+    }
+}
+
+impl FromStr for Id {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        s.parse::<u64>()
+            .map(Id)
+            .map_err(|_| Error::InvalidId(s.to_string()))
+    }
+}
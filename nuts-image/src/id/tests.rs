@@ -0,0 +1,70 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::{Binary, IdSize};
+
+use crate::id::Id;
+
+#[test]
+fn as_bytes() {
+    assert_eq!(Id::new(0).as_bytes(), [0; 8]);
+    assert_eq!(
+        Id::new(0x0102030405060708).as_bytes(),
+        [1, 2, 3, 4, 5, 6, 7, 8]
+    );
+}
+
+#[test]
+fn from_bytes() {
+    let id = Id::from_bytes(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+    assert_eq!(id.as_u64(), 0x0102030405060708);
+}
+
+#[test]
+fn from_bytes_inval_len() {
+    assert!(Id::from_bytes(&[1, 2, 3, 4, 5, 6, 7]).is_none());
+    assert!(Id::from_bytes(&[1, 2, 3, 4, 5, 6, 7, 8, 9]).is_none());
+}
+
+#[test]
+fn size() {
+    assert_eq!(Id::size(), 8);
+}
+
+#[test]
+fn display() {
+    assert_eq!(Id::new(4711).to_string(), "4711");
+}
+
+#[test]
+fn from_str() {
+    let id = "4711".parse::<Id>().unwrap();
+    assert_eq!(id.as_u64(), 4711);
+}
+
+#[test]
+fn from_str_invalid() {
+    for s in ["", "-1", "xxx", "18446744073709551616"].iter() {
+        let err = s.parse::<Id>().unwrap_err();
+        assert_eq!(format!("{}", err), format!("The id '{}' is invalid", s));
+    }
+}
@@ -0,0 +1,108 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use nuts_backend::HEADER_MAX_SIZE;
+
+/// Offset of the header in the image.
+pub const HEADER_OFFSET: u64 = 0;
+
+/// Offset of the backup header in the image.
+pub const BACKUP_HEADER_OFFSET: u64 = HEADER_MAX_SIZE as u64;
+
+/// Offset of the first group in the image.
+pub const GROUP_OFFSET: u64 = 2 * HEADER_MAX_SIZE as u64;
+
+/// Calculates the position of blocks in the image.
+///
+/// The blocks are organized in groups, which are stored one after another
+/// behind the header region. A group starts with a bitmap block, followed by
+/// the data blocks covered by the bitmap. Each bit of the bitmap tells
+/// whether the related block is allocated.
+#[derive(Clone, Copy, Debug)]
+pub struct Layout {
+    bsize: u32,
+}
+
+impl Layout {
+    pub fn new(bsize: u32) -> Layout {
+        Layout { bsize }
+    }
+
+    /// Returns the block size.
+    pub fn bsize(&self) -> u32 {
+        self.bsize
+    }
+
+    /// Returns the number of data blocks covered by a single bitmap block.
+    pub fn blocks_per_group(&self) -> u64 {
+        self.bsize as u64 * 8
+    }
+
+    /// Returns the number of bytes occupied by a group.
+    pub fn group_size(&self) -> u64 {
+        (1 + self.blocks_per_group()) * self.bsize as u64
+    }
+
+    /// Returns the group of block `n` and the index of the block in the
+    /// group.
+    pub fn group_of(&self, n: u64) -> (u64, u64) {
+        (n / self.blocks_per_group(), n % self.blocks_per_group())
+    }
+
+    /// Returns the offset of the bitmap block of the given `group`.
+    pub fn bitmap_offset(&self, group: u64) -> u64 {
+        GROUP_OFFSET + group * self.group_size()
+    }
+
+    /// Returns the offset of block `n`.
+    pub fn block_offset(&self, n: u64) -> u64 {
+        let (group, idx) = self.group_of(n);
+
+        self.bitmap_offset(group) + (1 + idx) * self.bsize as u64
+    }
+
+    /// Returns the number of groups stored in an image of `len` bytes.
+    pub fn groups(&self, len: u64) -> u64 {
+        let len = len.saturating_sub(GROUP_OFFSET);
+        let groups = len / self.group_size();
+
+        // the last group is incomplete
+        if groups * self.group_size() < len {
+            groups + 1
+        } else {
+            groups
+        }
+    }
+
+    /// Returns the size of an image, which is able to store `nblocks`
+    /// blocks.
+    pub fn image_size(&self, nblocks: u64) -> u64 {
+        if nblocks > 0 {
+            self.block_offset(nblocks - 1) + self.bsize as u64
+        } else {
+            GROUP_OFFSET
+        }
+    }
+}
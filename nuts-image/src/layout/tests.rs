@@ -0,0 +1,83 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use crate::layout::{Layout, GROUP_OFFSET};
+
+#[test]
+fn blocks_per_group() {
+    assert_eq!(Layout::new(512).blocks_per_group(), 4096);
+    assert_eq!(Layout::new(1024).blocks_per_group(), 8192);
+}
+
+#[test]
+fn group_size() {
+    assert_eq!(Layout::new(512).group_size(), 4097 * 512);
+}
+
+#[test]
+fn group_of() {
+    let layout = Layout::new(512);
+
+    assert_eq!(layout.group_of(0), (0, 0));
+    assert_eq!(layout.group_of(4095), (0, 4095));
+    assert_eq!(layout.group_of(4096), (1, 0));
+    assert_eq!(layout.group_of(8193), (2, 1));
+}
+
+#[test]
+fn bitmap_offset() {
+    let layout = Layout::new(512);
+
+    assert_eq!(layout.bitmap_offset(0), GROUP_OFFSET);
+    assert_eq!(layout.bitmap_offset(1), GROUP_OFFSET + 4097 * 512);
+}
+
+#[test]
+fn block_offset() {
+    let layout = Layout::new(512);
+
+    assert_eq!(layout.block_offset(0), GROUP_OFFSET + 512);
+    assert_eq!(layout.block_offset(1), GROUP_OFFSET + 1024);
+    assert_eq!(layout.block_offset(4095), GROUP_OFFSET + 4096 * 512);
+    assert_eq!(layout.block_offset(4096), GROUP_OFFSET + 4098 * 512);
+}
+
+#[test]
+fn groups() {
+    let layout = Layout::new(512);
+
+    assert_eq!(layout.groups(0), 0);
+    assert_eq!(layout.groups(GROUP_OFFSET), 0);
+    assert_eq!(layout.groups(GROUP_OFFSET + 1), 1);
+    assert_eq!(layout.groups(GROUP_OFFSET + 4097 * 512), 1);
+    assert_eq!(layout.groups(GROUP_OFFSET + 4097 * 512 + 1), 2);
+}
+
+#[test]
+fn image_size() {
+    let layout = Layout::new(512);
+
+    assert_eq!(layout.image_size(0), GROUP_OFFSET);
+    assert_eq!(layout.image_size(1), GROUP_OFFSET + 1024);
+    assert_eq!(layout.image_size(4096), GROUP_OFFSET + 4097 * 512);
+    assert_eq!(layout.image_size(4097), GROUP_OFFSET + 4099 * 512);
+}
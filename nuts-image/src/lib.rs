@@ -0,0 +1,313 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+//! Nuts backend implementation where the blocks of the container are stored
+//! in a single image file.
+//!
+//! # Introduction
+//!
+//! The _nuts-image_ crate implements a [nuts] backend where all blocks of the
+//! container are stored in a single file, the image. Each block is identified
+//! by an [id](Id), which is the index of the block in the image.
+//!
+//! The image starts with the header region: the header of the container is
+//! stored at offset `0`, followed by a slot for the backup of the header.
+//! Both occupy [`HEADER_MAX_SIZE`] bytes.
+//!
+//! The blocks are organized in groups, which follow the header region. A
+//! group starts with a bitmap block, where each bit tells whether the related
+//! block of the group is allocated. The bitmap block is followed by the data
+//! blocks of the group, a bitmap block of `n` bytes covers `8 * n` blocks.
+//!
+//! By default the image grows as needed. An image can also be preallocated
+//! for a fixed number of blocks, see
+//! [`CreateOptions::with_capacity()`]. Released blocks are put into a
+//! free-list and are reused before the image grows.
+//!
+//! The backend is [locked](nuts_backend::Backend::lock) with `flock(2)` on
+//! the image, thus only a single process can modify the container at the
+//! same time.
+//!
+//! # Create a new backend instance
+//!
+//! The [`CreateOptions`] type is used to create a new backend instance, which
+//! is passed to the [`Container::create`] method. You need at least the path
+//! of the image file. See the [`CreateOptions`] documentation for further
+//! options.
+//!
+//! # Open an existing backend
+//!
+//! The [`OpenOptions`] type is used to open a backend instance, which is
+//! passed to the [`Container::open`] method. You need the path of the image
+//! file.
+//!
+//! [nuts]: https://crates.io/crates/nuts-container
+//! [`Container::create`]: https://docs.rs/nuts-container/latest/nuts_container/container/struct.Container.html#method.create
+//! [`Container::open`]: https://docs.rs/nuts-container/latest/nuts_container/container/struct.Container.html#method.open
+
+mod bitmap;
+mod error;
+mod file;
+mod id;
+mod info;
+mod layout;
+mod lock;
+mod options;
+#[cfg(test)]
+mod tests;
+
+use log::error;
+use nuts_backend::{Backend, LockMode, ReceiveHeader, SharedRead, HEADER_MAX_SIZE};
+use std::cmp;
+use std::fs::{self, File};
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+pub use error::Error;
+pub use id::Id;
+pub use info::Info;
+pub use options::{CreateOptions, OpenOptions, Settings};
+
+use crate::bitmap::Bitmap;
+use crate::error::Result;
+use crate::file::{fill_at, write_all_at};
+use crate::layout::{Layout, BACKUP_HEADER_OFFSET, HEADER_OFFSET};
+
+fn open_image(path: &Path, read_only: bool) -> Result<File> {
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .write(!read_only)
+        .open(path)?;
+
+    Ok(file)
+}
+
+fn load_bitmap(file: &File, layout: Layout) -> Result<Bitmap> {
+    let len = file.metadata()?.len();
+    let mut groups = vec![];
+
+    for group in 0..layout.groups(len) {
+        let mut bits = vec![0; layout.bsize() as usize];
+
+        // the bitmap block might not be written completely
+        fill_at(file, &mut bits, layout.bitmap_offset(group))?;
+        groups.push(bits);
+    }
+
+    Ok(Bitmap::new(layout, groups))
+}
+
+fn read_header(file: &File, buf: &mut [u8]) -> Result<()> {
+    if fill_at(file, buf, HEADER_OFFSET)? == buf.len() {
+        Ok(())
+    } else {
+        Err(io::Error::new(ErrorKind::UnexpectedEof, "the image has no header").into())
+    }
+}
+
+fn write_header(file: &File, buf: &[u8]) -> Result<()> {
+    Ok(write_all_at(file, buf, HEADER_OFFSET)?)
+}
+
+fn read_backup_header(file: &File, buf: &mut [u8]) -> Result<bool> {
+    let n = fill_at(file, buf, BACKUP_HEADER_OFFSET)?;
+
+    // the slot is zeroed as long as no backup was written
+    Ok(n == buf.len() && buf.iter().any(|b| *b != 0))
+}
+
+fn write_backup_header(file: &File, buf: &[u8]) -> Result<()> {
+    Ok(write_all_at(file, buf, BACKUP_HEADER_OFFSET)?)
+}
+
+#[derive(Debug)]
+pub struct ImageBackend<P: AsRef<Path>> {
+    path: P,
+    file: File,
+    layout: Layout,
+    capacity: Option<u64>,
+    bitmap: Bitmap,
+    read_only: bool,
+}
+
+impl<P: AsRef<Path>> ImageBackend<P> {
+    fn writable(&self) -> Result<()> {
+        if self.read_only {
+            Err(Error::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_allocated(&self, id: &Id) -> Result<()> {
+        if self.bitmap.is_allocated(id.as_u64()) {
+            Ok(())
+        } else {
+            Err(Error::NotAllocated(*id))
+        }
+    }
+
+    fn read_block(&self, id: &Id, buf: &mut [u8]) -> Result<usize> {
+        self.check_allocated(id)?;
+
+        let len = cmp::min(buf.len(), self.block_size() as usize);
+        let target = &mut buf[..len];
+        let n = fill_at(&self.file, target, self.layout.block_offset(id.as_u64()))?;
+
+        // beyond the end of a preallocated image
+        target[n..].iter_mut().for_each(|b| *b = 0);
+
+        Ok(len)
+    }
+
+    fn write_block(&self, id: &Id, buf: &[u8]) -> Result<usize> {
+        let len = cmp::min(buf.len(), self.block_size() as usize);
+        let mut block = vec![0; self.block_size() as usize];
+
+        block[..len].copy_from_slice(&buf[..len]);
+        write_all_at(&self.file, &block, self.layout.block_offset(id.as_u64()))?;
+
+        Ok(len)
+    }
+
+    fn write_bitmap(&self, id: &Id, value: bool) -> Result<()> {
+        let (group, offs, byte) = self.bitmap.entry(id.as_u64(), value);
+        let offset = self.layout.bitmap_offset(group) + offs;
+
+        Ok(write_all_at(&self.file, &[byte], offset)?)
+    }
+}
+
+impl<P: AsRef<Path>> ReceiveHeader<Self> for ImageBackend<P> {
+    fn get_header_bytes(&mut self, bytes: &mut [u8; HEADER_MAX_SIZE]) -> Result<()> {
+        read_header(&self.file, bytes)
+    }
+
+    fn get_backup_header_bytes(&mut self, bytes: &mut [u8; HEADER_MAX_SIZE]) -> Result<bool> {
+        read_backup_header(&self.file, bytes)
+    }
+}
+
+impl<P: AsRef<Path>> Backend for ImageBackend<P> {
+    type Settings = Settings;
+    type Err = Error;
+    type Id = Id;
+    type Info = Info;
+
+    fn info(&self) -> Result<Info> {
+        Ok(Info {
+            bsize: self.block_size(),
+            capacity: self.capacity,
+            blocks: self.bitmap.allocated(),
+        })
+    }
+
+    fn block_size(&self) -> u32 {
+        self.layout.bsize()
+    }
+
+    fn aquire(&mut self, buf: &[u8]) -> Result<Self::Id> {
+        self.writable()?;
+
+        let id = Id::new(self.bitmap.peek());
+
+        if let Some(capacity) = self.capacity {
+            if id.as_u64() >= capacity {
+                return Err(Error::Full);
+            }
+        }
+
+        // the block is allocated once its content is stored
+        self.write_block(&id, buf)?;
+        self.write_bitmap(&id, true)?;
+        self.bitmap.allocate(id.as_u64());
+
+        Ok(id)
+    }
+
+    fn release(&mut self, id: Self::Id) -> Result<()> {
+        self.writable()?;
+        self.check_allocated(&id)?;
+
+        self.write_bitmap(&id, false)?;
+        self.bitmap.release(id.as_u64());
+
+        Ok(())
+    }
+
+    fn read(&mut self, id: &Id, buf: &mut [u8]) -> Result<usize> {
+        self.read_block(id, buf)
+    }
+
+    fn write(&mut self, id: &Id, buf: &[u8]) -> Result<usize> {
+        self.writable()?;
+        self.check_allocated(id)?;
+        self.write_block(id, buf)
+    }
+
+    fn write_header(&mut self, buf: &[u8; HEADER_MAX_SIZE]) -> Result<()> {
+        self.writable()?;
+        write_header(&self.file, buf)
+    }
+
+    fn write_backup_header(&mut self, buf: &[u8; HEADER_MAX_SIZE]) -> Result<()> {
+        self.writable()?;
+        write_backup_header(&self.file, buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if self.read_only {
+            Ok(())
+        } else {
+            Ok(self.file.sync_data()?)
+        }
+    }
+
+    fn lock(&mut self, mode: LockMode) -> Result<()> {
+        lock::lock(&self.file, mode)
+    }
+
+    fn delete(self) {
+        if self.read_only {
+            error!("cannot delete a read-only backend instance");
+            return;
+        }
+
+        if let Err(err) = fs::remove_file(self.path.as_ref()) {
+            error!("failed to delete backend instance: {}", err);
+        }
+    }
+}
+
+impl<P: AsRef<Path>> Drop for ImageBackend<P> {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            error!("failed to sync the backend: {}", err);
+        }
+    }
+}
+
+impl<P: AsRef<Path>> SharedRead for ImageBackend<P> {
+    fn read_shared(&self, id: &Id, buf: &mut [u8]) -> Result<usize> {
+        self.read_block(id, buf)
+    }
+}
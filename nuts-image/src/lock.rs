@@ -0,0 +1,73 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(all(test, unix))]
+mod tests;
+
+#[cfg(unix)]
+use log::debug;
+#[cfg(not(unix))]
+use log::warn;
+use nuts_backend::LockMode;
+use std::fs::File;
+#[cfg(unix)]
+use std::io::{self, ErrorKind};
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+
+#[cfg(unix)]
+use crate::error::Error;
+use crate::error::Result;
+
+/// Locks the image opened as `file`.
+///
+/// The lock is taken with `flock(2)` on the image itself. It is released
+/// again when the file is closed, a previous lock is replaced.
+///
+/// Fails with [`Error::Locked`](crate::Error::Locked), if another process
+/// holds a conflicting lock.
+#[cfg(unix)]
+pub fn lock(file: &File, mode: LockMode) -> Result<()> {
+    let operation = match mode {
+        LockMode::Shared => libc::LOCK_SH,
+        LockMode::Exclusive => libc::LOCK_EX,
+    };
+
+    if unsafe { libc::flock(file.as_raw_fd(), operation | libc::LOCK_NB) } == 0 {
+        debug!("{:?} lock taken", mode);
+        Ok(())
+    } else {
+        let err = io::Error::last_os_error();
+
+        if err.kind() == ErrorKind::WouldBlock {
+            Err(Error::Locked)
+        } else {
+            Err(err.into())
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn lock(_file: &File, _mode: LockMode) -> Result<()> {
+    warn!("locking is not supported on this platform");
+    Ok(())
+}
@@ -0,0 +1,73 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::LockMode;
+use std::fs::File;
+use tempfile::NamedTempFile;
+
+use crate::error::Error;
+use crate::lock::lock;
+
+fn open(file: &NamedTempFile) -> File {
+    File::open(file.path()).unwrap()
+}
+
+#[test]
+fn exclusive() {
+    let image = NamedTempFile::new().unwrap();
+
+    let file = open(&image);
+    lock(&file, LockMode::Exclusive).unwrap();
+
+    for mode in [LockMode::Exclusive, LockMode::Shared].iter() {
+        let err = lock(&open(&image), *mode).unwrap_err();
+        assert!(matches!(err, Error::Locked));
+    }
+
+    drop(file);
+
+    lock(&open(&image), LockMode::Exclusive).unwrap();
+}
+
+#[test]
+fn shared() {
+    let image = NamedTempFile::new().unwrap();
+
+    let file1 = open(&image);
+    let file2 = open(&image);
+    lock(&file1, LockMode::Shared).unwrap();
+    lock(&file2, LockMode::Shared).unwrap();
+
+    let err = lock(&open(&image), LockMode::Exclusive).unwrap_err();
+    assert!(matches!(err, Error::Locked));
+}
+
+#[test]
+fn relock() {
+    let image = NamedTempFile::new().unwrap();
+
+    let file = open(&image);
+    lock(&file, LockMode::Exclusive).unwrap();
+    lock(&file, LockMode::Shared).unwrap();
+
+    lock(&open(&image), LockMode::Shared).unwrap();
+}
@@ -0,0 +1,132 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use log::error;
+use nuts_backend::Backend;
+use nuts_image::{CreateOptions, ImageBackend, Info, OpenOptions};
+use nuts_tool_api::plugin::clap_prelude::*;
+use nuts_tool_api::plugin::cli::{CreateArgs, OpenArgs, SizeArg};
+use nuts_tool_api::plugin::{PluginHandler, PluginRunner};
+use nuts_tool_api::{container_dir_for, ErrorResponse, PluginInfo};
+use std::{collections::HashMap, io, path::PathBuf, process};
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Name of the image file in the container directory.
+const IMAGE_FILE: &str = "image";
+
+#[derive(Args, Debug)]
+struct ExtraArgs {
+    /// Set the block-size to SIZE
+    #[clap(short, long, id = "SIZE", default_value = "512")]
+    block_size: SizeArg<u32>,
+
+    /// Preallocates the image for BLOCKS blocks. Without this option the
+    /// image grows as needed
+    #[clap(long, id = "BLOCKS")]
+    capacity: Option<u64>,
+}
+
+fn image_for(name: &str) -> io::Result<PathBuf> {
+    container_dir_for(name).map(|dir| dir.join(IMAGE_FILE))
+}
+
+fn info_to_hash(info: Info) -> HashMap<String, String> {
+    let capacity = match info.capacity {
+        Some(n) => n.to_string(),
+        None => "none".to_string(),
+    };
+
+    [
+        ("block_size".to_string(), info.bsize.to_string()),
+        ("capacity".to_string(), capacity),
+        ("blocks".to_string(), info.blocks.to_string()),
+    ]
+    .into()
+}
+
+struct ImagePluginInformation;
+
+impl PluginHandler<ImageBackend<PathBuf>> for ImagePluginInformation {
+    type CreateArgs = ExtraArgs;
+    type Create = CreateOptions<PathBuf>;
+    type Open = OpenOptions<PathBuf>;
+
+    fn plugin_info(&self) -> PluginInfo {
+        PluginInfo::new("image", VERSION)
+    }
+
+    fn info_to_hash(&self, info: Info) -> Option<HashMap<String, String>> {
+        Some(info_to_hash(info))
+    }
+
+    fn open_builder(&self, args: &OpenArgs) -> Option<OpenOptions<PathBuf>> {
+        match image_for(&args.name) {
+            Ok(path) => Some(OpenOptions::for_path(path)),
+            Err(err) => {
+                error!("could not detect container dir for {}: {}", args.name, err);
+                None
+            }
+        }
+    }
+
+    fn create_builder(&self, args: &CreateArgs<ExtraArgs>) -> Option<CreateOptions<PathBuf>> {
+        match image_for(&args.name) {
+            Ok(path) => {
+                let options = CreateOptions::for_path(path).with_bsize(*args.extra.block_size);
+
+                match args.extra.capacity {
+                    Some(capacity) => Some(options.with_capacity(capacity)),
+                    None => Some(options),
+                }
+            }
+            Err(err) => {
+                error!("could not detect container dir for {}: {}", args.name, err);
+                None
+            }
+        }
+    }
+
+    fn handle_info(
+        &self,
+        backend: &ImageBackend<PathBuf>,
+    ) -> Result<HashMap<String, String>, ErrorResponse> {
+        match backend.info() {
+            Ok(info) => Ok(info_to_hash(info)),
+            Err(err) => Err(ErrorResponse::backend::<ImageBackend<PathBuf>>(err)),
+        }
+    }
+}
+
+fn main() {
+    let mut runner = PluginRunner::new(ImagePluginInformation);
+
+    runner.configure_logging();
+
+    process::exit(match runner.run() {
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("{}", err);
+            1
+        }
+    })
+}
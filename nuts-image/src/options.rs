@@ -0,0 +1,271 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use nuts_backend::{Binary, Create, LockMode, Open, ReceiveHeader, HEADER_MAX_SIZE};
+use std::convert::TryInto;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::file::allocate;
+use crate::layout::Layout;
+use crate::lock::lock;
+use crate::{
+    load_bitmap, open_image, read_backup_header, read_header, write_backup_header, write_header,
+    ImageBackend,
+};
+
+const BLOCK_MIN_SIZE: u32 = 512;
+
+/// [Options](nuts_backend::Create) needed to create the backend.
+///
+/// You must pass the path of the image file to [`CreateOptions::for_path()`],
+/// if creating a `CreateOptions` instance.
+///
+/// Furthermore the following options can be specified:
+///
+/// * [`CreateOptions::with_bsize()`]: Specifies the block size of the backend.
+///   This is the number of bytes, which can  be stored in an individual block.
+///   The minimum block size is 512 bytes. The default is `512`.
+/// * [`CreateOptions::with_capacity()`]: Preallocates the image for the given
+///   number of blocks. By default the image grows as needed.
+#[derive(Clone, Debug)]
+pub struct CreateOptions<P: AsRef<Path>> {
+    path: P,
+    bsize: u32,
+    capacity: Option<u64>,
+}
+
+impl<P: AsRef<Path>> CreateOptions<P> {
+    /// Creates a new `CreateOptions` instance.
+    ///
+    /// You must pass the `path` of the image file to the function.
+    ///
+    /// For further options default values are applied.
+    pub fn for_path(path: P) -> Self {
+        CreateOptions {
+            path,
+            bsize: BLOCK_MIN_SIZE,
+            capacity: None,
+        }
+    }
+
+    /// Assigns a new block size to the options.
+    ///
+    /// This is the number of bytes, which can  be stored in an individual
+    /// block.
+    pub fn with_bsize(mut self, bsize: u32) -> Self {
+        self.bsize = bsize;
+        self
+    }
+
+    /// Assigns a capacity to the options.
+    ///
+    /// The disk space of the image is reserved, when the backend is created,
+    /// and the image can store at most `capacity` blocks. The space is only
+    /// reserved on Linux, on other platforms the image is extended to its
+    /// final size, which might create a sparse file.
+    pub fn with_capacity(mut self, capacity: u64) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.bsize < BLOCK_MIN_SIZE {
+            return Err(Error::InvalidBlockSize(self.bsize));
+        }
+
+        if self.capacity == Some(0) {
+            return Err(Error::InvalidCapacity(0));
+        }
+
+        Ok(())
+    }
+}
+
+impl<P: AsRef<Path>> Create<ImageBackend<P>> for CreateOptions<P> {
+    fn settings(&self) -> Settings {
+        Settings {
+            bsize: self.bsize,
+            capacity: self.capacity,
+        }
+    }
+
+    fn build(self, header: [u8; HEADER_MAX_SIZE], overwrite: bool) -> Result<ImageBackend<P>> {
+        self.validate()?;
+
+        let path = self.path.as_ref();
+
+        if let Some(dir) = path.parent() {
+            if !dir.as_os_str().is_empty() && !dir.is_dir() {
+                fs::create_dir_all(dir)?;
+            }
+        }
+
+        // truncated only after the lock is taken, see below
+        let result = if overwrite {
+            fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)
+        } else {
+            fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(path)
+        };
+
+        let file = match result {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => return Err(Error::Exists),
+            Err(err) => return Err(err.into()),
+        };
+
+        // lock before the header is touched, a concurrent create must not
+        // clobber it
+        lock(&file, LockMode::Exclusive)?;
+
+        if overwrite {
+            file.set_len(0)?;
+        }
+
+        let layout = Layout::new(self.bsize);
+
+        write_header(&file, &header)?;
+        write_backup_header(&file, &[0; HEADER_MAX_SIZE])?;
+
+        if let Some(capacity) = self.capacity {
+            allocate(&file, layout.image_size(capacity))?;
+        }
+
+        // a new container is durable at once
+        file.sync_all()?;
+
+        let bitmap = load_bitmap(&file, layout)?;
+
+        Ok(ImageBackend {
+            path: self.path,
+            file,
+            layout,
+            capacity: self.capacity,
+            bitmap,
+            read_only: false,
+        })
+    }
+}
+
+/// [Options](nuts_backend::Open) needed to open the backend.
+///
+/// You must pass the path of the image file to [`OpenOptions::for_path()`],
+/// if creating a `OpenOptions` instance.
+///
+/// If the container is opened read-only, the image is opened read-only and
+/// the backend rejects any modification.
+pub struct OpenOptions<P: AsRef<Path>> {
+    path: P,
+    read_only: bool,
+}
+
+impl<P: AsRef<Path>> OpenOptions<P> {
+    /// Creates a new `OpenOptions` instance.
+    ///
+    /// You must pass the `path` of the image file to the function.
+    pub fn for_path(path: P) -> OpenOptions<P> {
+        OpenOptions {
+            path,
+            read_only: false,
+        }
+    }
+}
+
+impl<P: AsRef<Path>> ReceiveHeader<ImageBackend<P>> for OpenOptions<P> {
+    fn get_header_bytes(&mut self, bytes: &mut [u8; HEADER_MAX_SIZE]) -> Result<()> {
+        read_header(&open_image(self.path.as_ref(), true)?, bytes)
+    }
+
+    fn get_backup_header_bytes(&mut self, bytes: &mut [u8; HEADER_MAX_SIZE]) -> Result<bool> {
+        read_backup_header(&open_image(self.path.as_ref(), true)?, bytes)
+    }
+}
+
+impl<P: AsRef<Path>> Open<ImageBackend<P>> for OpenOptions<P> {
+    fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    fn build(self, settings: Settings) -> Result<ImageBackend<P>> {
+        let file = open_image(self.path.as_ref(), self.read_only)?;
+        let layout = Layout::new(settings.bsize);
+        let bitmap = load_bitmap(&file, layout)?;
+
+        Ok(ImageBackend {
+            path: self.path,
+            file,
+            layout,
+            capacity: settings.capacity,
+            bitmap,
+            read_only: self.read_only,
+        })
+    }
+}
+
+/// [Settings](nuts_backend::Backend::Settings) used by the backend.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    bsize: u32,
+    capacity: Option<u64>,
+}
+
+impl Binary for Settings {
+    fn from_bytes(bytes: &[u8]) -> Option<Settings> {
+        if bytes.len() != 12 {
+            return None;
+        }
+
+        let bsize = u32::from_be_bytes(bytes[..4].try_into().ok()?);
+
+        if bsize < BLOCK_MIN_SIZE {
+            return None;
+        }
+        let capacity = match u64::from_be_bytes(bytes[4..].try_into().ok()?) {
+            0 => None,
+            n => Some(n),
+        };
+
+        Some(Settings { bsize, capacity })
+    }
+
+    fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.bsize.to_be_bytes().to_vec();
+
+        bytes.extend_from_slice(&self.capacity.unwrap_or(0).to_be_bytes());
+
+        bytes
+    }
+}
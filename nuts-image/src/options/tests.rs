@@ -0,0 +1,201 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::{Backend, Binary, Create, Open, HEADER_MAX_SIZE};
+use tempfile::TempDir;
+
+use crate::error::Error;
+use crate::options::{CreateOptions, OpenOptions, Settings};
+#[cfg(unix)]
+use crate::read_header;
+
+#[test]
+fn create_defaults() {
+    let options = CreateOptions::for_path("foo");
+
+    assert_eq!(options.path, "foo");
+    assert_eq!(options.bsize, 512);
+    assert_eq!(options.capacity, None);
+}
+
+#[test]
+fn create_with() {
+    let options = CreateOptions::for_path("foo")
+        .with_bsize(1024)
+        .with_capacity(100);
+
+    assert_eq!(options.bsize, 1024);
+    assert_eq!(options.capacity, Some(100));
+
+    let settings = options.settings();
+    assert_eq!(settings.bsize, 1024);
+    assert_eq!(settings.capacity, Some(100));
+}
+
+#[test]
+fn create_invalid_bsize() {
+    let dir = TempDir::new().unwrap();
+    let err = CreateOptions::for_path(dir.path().join("image"))
+        .with_bsize(511)
+        .build([0; HEADER_MAX_SIZE], false)
+        .unwrap_err();
+
+    assert!(matches!(err, Error::InvalidBlockSize(511)));
+}
+
+#[test]
+fn create_invalid_capacity() {
+    let dir = TempDir::new().unwrap();
+    let err = CreateOptions::for_path(dir.path().join("image"))
+        .with_capacity(0)
+        .build([0; HEADER_MAX_SIZE], false)
+        .unwrap_err();
+
+    assert!(matches!(err, Error::InvalidCapacity(0)));
+}
+
+#[test]
+fn create_growable() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("a").join("image");
+
+    let backend = CreateOptions::for_path(&path)
+        .build([1; HEADER_MAX_SIZE], false)
+        .unwrap();
+
+    assert_eq!(backend.block_size(), 512);
+    assert_eq!(path.metadata().unwrap().len(), 1024);
+}
+
+#[test]
+fn create_preallocated() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("image");
+
+    CreateOptions::for_path(&path)
+        .with_capacity(3)
+        .build([1; HEADER_MAX_SIZE], false)
+        .unwrap();
+
+    assert_eq!(path.metadata().unwrap().len(), 1024 + 4 * 512);
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        // the space is reserved, the image is not sparse
+        assert!(path.metadata().unwrap().blocks() * 512 >= 1024 + 4 * 512);
+    }
+}
+
+#[test]
+fn create_exists() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("image");
+
+    CreateOptions::for_path(&path)
+        .build([1; HEADER_MAX_SIZE], false)
+        .unwrap();
+
+    let err = CreateOptions::for_path(&path)
+        .build([2; HEADER_MAX_SIZE], false)
+        .unwrap_err();
+    assert!(matches!(err, Error::Exists));
+
+    CreateOptions::for_path(&path)
+        .build([2; HEADER_MAX_SIZE], true)
+        .unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn create_locked() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("image");
+
+    let backend = CreateOptions::for_path(&path)
+        .build([1; HEADER_MAX_SIZE], false)
+        .unwrap();
+
+    let err = CreateOptions::for_path(&path)
+        .build([2; HEADER_MAX_SIZE], true)
+        .unwrap_err();
+    assert!(matches!(err, Error::Locked));
+
+    // the header is not clobbered
+    let mut header = [0; HEADER_MAX_SIZE];
+    read_header(&backend.file, &mut header).unwrap();
+    assert_eq!(header, [1; HEADER_MAX_SIZE]);
+}
+
+#[test]
+fn open_for_path() {
+    let options = OpenOptions::for_path("foo");
+
+    assert_eq!(options.path, "foo");
+    assert!(!options.read_only);
+}
+
+#[test]
+fn open_set_read_only() {
+    let mut options = OpenOptions::for_path("foo");
+
+    options.set_read_only(true);
+    assert!(options.read_only);
+}
+
+#[test]
+fn settings_as_bytes() {
+    let settings = Settings {
+        bsize: 512,
+        capacity: None,
+    };
+    assert_eq!(settings.as_bytes(), [0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+    let settings = Settings {
+        bsize: 1024,
+        capacity: Some(258),
+    };
+    assert_eq!(settings.as_bytes(), [0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 1, 2]);
+}
+
+#[test]
+fn settings_from_bytes() {
+    let settings = Settings::from_bytes(&[0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+    assert_eq!(settings.bsize, 512);
+    assert_eq!(settings.capacity, None);
+
+    let settings = Settings::from_bytes(&[0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 1, 2]).unwrap();
+    assert_eq!(settings.bsize, 1024);
+    assert_eq!(settings.capacity, Some(258));
+}
+
+#[test]
+fn settings_from_bytes_invalid() {
+    assert!(Settings::from_bytes(&[0, 0, 2, 0]).is_none());
+    assert!(Settings::from_bytes(&[0; 11]).is_none());
+    assert!(Settings::from_bytes(&[0; 13]).is_none());
+
+    // block size below 512
+    assert!(Settings::from_bytes(&[0; 12]).is_none());
+    assert!(Settings::from_bytes(&[0, 0, 1, 255, 0, 0, 0, 0, 0, 0, 0, 0]).is_none());
+}
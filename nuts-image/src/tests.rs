@@ -0,0 +1,235 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use nuts_backend::{Backend, Create, LockMode, Open, ReceiveHeader, SharedRead, HEADER_MAX_SIZE};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+use crate::{CreateOptions, Error, Id, ImageBackend, OpenOptions};
+
+fn setup(capacity: Option<u64>) -> (TempDir, ImageBackend<PathBuf>) {
+    let dir = TempDir::new().unwrap();
+    let mut options = CreateOptions::for_path(dir.path().join("image"));
+
+    if let Some(capacity) = capacity {
+        options = options.with_capacity(capacity);
+    }
+
+    let backend = options.build([1; HEADER_MAX_SIZE], false).unwrap();
+
+    (dir, backend)
+}
+
+fn reopen(dir: &TempDir, backend: ImageBackend<PathBuf>, read_only: bool) -> ImageBackend<PathBuf> {
+    let settings = CreateOptions::for_path(dir.path())
+        .with_bsize(backend.block_size())
+        .settings();
+    let mut options = OpenOptions::for_path(dir.path().join("image"));
+
+    drop(backend);
+    options.set_read_only(read_only);
+    options.build(settings).unwrap()
+}
+
+#[test]
+fn header() {
+    let (dir, mut backend) = setup(None);
+    let mut buf = [0; HEADER_MAX_SIZE];
+
+    backend.get_header_bytes(&mut buf).unwrap();
+    assert_eq!(buf, [1; HEADER_MAX_SIZE]);
+    assert!(!backend.get_backup_header_bytes(&mut buf).unwrap());
+
+    backend.write_header(&[2; HEADER_MAX_SIZE]).unwrap();
+    backend.write_backup_header(&[3; HEADER_MAX_SIZE]).unwrap();
+
+    let mut options = OpenOptions::for_path(dir.path().join("image"));
+
+    options.get_header_bytes(&mut buf).unwrap();
+    assert_eq!(buf, [2; HEADER_MAX_SIZE]);
+    assert!(options.get_backup_header_bytes(&mut buf).unwrap());
+    assert_eq!(buf, [3; HEADER_MAX_SIZE]);
+}
+
+#[test]
+fn aquire_read_write() {
+    let (_dir, mut backend) = setup(None);
+    let mut buf = [0; 512];
+
+    let id1 = backend.aquire(&[1, 2, 3]).unwrap();
+    let id2 = backend.aquire(&[4, 5, 6]).unwrap();
+    assert_eq!(id1, Id::new(0));
+    assert_eq!(id2, Id::new(1));
+
+    assert_eq!(backend.read(&id1, &mut buf).unwrap(), 512);
+    assert_eq!(buf[..4], [1, 2, 3, 0]);
+
+    assert_eq!(backend.write(&id2, &[7; 1000]).unwrap(), 512);
+    assert_eq!(backend.read_shared(&id2, &mut buf[..4]).unwrap(), 4);
+    assert_eq!(buf[..4], [7; 4]);
+
+    assert_eq!(backend.info().unwrap().blocks, 2);
+}
+
+#[test]
+fn not_allocated() {
+    let (_dir, mut backend) = setup(None);
+    let mut buf = [0; 512];
+
+    backend.aquire(&[]).unwrap();
+
+    let err = backend.read(&Id::new(1), &mut buf).unwrap_err();
+    assert!(matches!(err, Error::NotAllocated(id) if id == Id::new(1)));
+
+    let err = backend.write(&Id::new(1), &buf).unwrap_err();
+    assert!(matches!(err, Error::NotAllocated(id) if id == Id::new(1)));
+
+    let err = backend.release(Id::new(1)).unwrap_err();
+    assert!(matches!(err, Error::NotAllocated(id) if id == Id::new(1)));
+}
+
+#[test]
+fn release_reuse() {
+    let (_dir, mut backend) = setup(None);
+    let mut buf = [0; 512];
+
+    for _ in 0..3 {
+        backend.aquire(&[]).unwrap();
+    }
+
+    backend.release(Id::new(1)).unwrap();
+    assert_eq!(backend.info().unwrap().blocks, 2);
+
+    let err = backend.read(&Id::new(1), &mut buf).unwrap_err();
+    assert!(matches!(err, Error::NotAllocated(_)));
+
+    assert_eq!(backend.aquire(&[9]).unwrap(), Id::new(1));
+    assert_eq!(backend.aquire(&[]).unwrap(), Id::new(3));
+
+    backend.read(&Id::new(1), &mut buf).unwrap();
+    assert_eq!(buf[0], 9);
+}
+
+#[test]
+fn grow_next_group() {
+    let (dir, mut backend) = setup(None);
+
+    for _ in 0..4097 {
+        backend.aquire(&[]).unwrap();
+    }
+
+    let id = backend.aquire(&[1]).unwrap();
+    assert_eq!(id, Id::new(4097));
+
+    let len = dir.path().join("image").metadata().unwrap().len();
+    assert_eq!(len, 1024 + 4097 * 512 + 3 * 512);
+
+    let backend = reopen(&dir, backend, true);
+    assert_eq!(backend.info().unwrap().blocks, 4098);
+}
+
+#[test]
+fn reopen_bitmap() {
+    let (dir, mut backend) = setup(None);
+
+    for _ in 0..4 {
+        backend.aquire(&[]).unwrap();
+    }
+
+    backend.release(Id::new(2)).unwrap();
+    backend.release(Id::new(0)).unwrap();
+
+    let mut backend = reopen(&dir, backend, false);
+
+    assert_eq!(backend.info().unwrap().blocks, 2);
+    assert_eq!(backend.aquire(&[]).unwrap(), Id::new(0));
+    assert_eq!(backend.aquire(&[]).unwrap(), Id::new(2));
+    assert_eq!(backend.aquire(&[]).unwrap(), Id::new(4));
+}
+
+#[test]
+fn preallocated() {
+    let (dir, mut backend) = setup(Some(2));
+    let len = dir.path().join("image").metadata().unwrap().len();
+
+    backend.aquire(&[]).unwrap();
+    backend.aquire(&[]).unwrap();
+
+    let err = backend.aquire(&[]).unwrap_err();
+    assert!(matches!(err, Error::Full));
+
+    backend.release(Id::new(0)).unwrap();
+    assert_eq!(backend.aquire(&[]).unwrap(), Id::new(0));
+
+    // the image does not grow
+    assert_eq!(dir.path().join("image").metadata().unwrap().len(), len);
+}
+
+#[test]
+fn read_only() {
+    let (dir, mut backend) = setup(None);
+    let id = backend.aquire(&[1]).unwrap();
+    let mut backend = reopen(&dir, backend, true);
+    let mut buf = [0; 1];
+
+    backend.read(&id, &mut buf).unwrap();
+    assert_eq!(buf, [1]);
+
+    assert!(matches!(backend.aquire(&[]).unwrap_err(), Error::ReadOnly));
+    assert!(matches!(
+        backend.write(&id, &[]).unwrap_err(),
+        Error::ReadOnly
+    ));
+    assert!(matches!(backend.release(id).unwrap_err(), Error::ReadOnly));
+    assert!(matches!(
+        backend.write_header(&[0; HEADER_MAX_SIZE]).unwrap_err(),
+        Error::ReadOnly
+    ));
+
+    backend.lock(LockMode::Shared).unwrap();
+    backend.flush().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn locked() {
+    let (dir, mut backend) = setup(None);
+    backend.lock(LockMode::Exclusive).unwrap();
+
+    let settings = CreateOptions::for_path(dir.path()).settings();
+    let mut other = OpenOptions::for_path(dir.path().join("image"));
+    other.set_read_only(true);
+
+    let mut other = other.build(settings).unwrap();
+    assert!(matches!(
+        other.lock(LockMode::Shared).unwrap_err(),
+        Error::Locked
+    ));
+}
+
+#[test]
+fn delete() {
+    let (dir, backend) = setup(None);
+
+    backend.delete();
+    assert!(!dir.path().join("image").exists());
+}
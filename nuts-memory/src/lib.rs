@@ -30,12 +30,18 @@
 //! the [`Id`](nuts_backend::Backend::Id) of this backend, where the
 //! [id](nuts_backend::Backend::Id) is a simple `u32` value.
 
-use nuts_backend::{Backend, Binary, Create, IdSize, Open, ReceiveHeader, HEADER_MAX_SIZE};
+#[cfg(feature = "async")]
+use nuts_backend::{AsyncBackend, BoxFuture};
+use nuts_backend::{
+    Backend, Binary, Create, IdSize, LockMode, Open, ReceiveHeader, SharedRead, HEADER_MAX_SIZE,
+};
 use nuts_bytes::{FromBytes, ToBytes};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::convert::TryInto;
+#[cfg(feature = "async")]
+use std::future;
 use std::num::ParseIntError;
 use std::str::FromStr;
 use std::{cmp, fmt, mem};
@@ -148,6 +154,15 @@ pub struct MemoryBackend {
         serialize_with = "serialize_header"
     )]
     header: Option<[u8; HEADER_MAX_SIZE]>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_header",
+        serialize_with = "serialize_header"
+    )]
+    backup_header: Option<[u8; HEADER_MAX_SIZE]>,
+    #[serde(skip)]
+    lock: Option<LockMode>,
 }
 
 impl MemoryBackend {
@@ -165,6 +180,8 @@ impl MemoryBackend {
             bsize,
             blocks: HashMap::new(),
             header: None,
+            backup_header: None,
+            lock: None,
         }
     }
 
@@ -173,6 +190,16 @@ impl MemoryBackend {
         self.bsize
     }
 
+    /// Returns the mode of the last [lock](Backend::lock) taken on the
+    /// backend.
+    ///
+    /// The backend is not shared with other processes, thus the lock is only
+    /// recorded but nothing is locked. Returns [`None`] if the backend was
+    /// never locked.
+    pub fn lock_mode(&self) -> Option<LockMode> {
+        self.lock
+    }
+
     /// Receives the content of the block with the given `id`.
     ///
     /// Returns [`None`] if the block does not exist.
@@ -230,6 +257,19 @@ impl ReceiveHeader<Self> for MemoryBackend {
             None => Err(Error::NoHeader),
         }
     }
+
+    fn get_backup_header_bytes(
+        &mut self,
+        bytes: &mut [u8; HEADER_MAX_SIZE],
+    ) -> Result<bool, Error> {
+        match self.backup_header.as_ref() {
+            Some(source) => {
+                bytes.copy_from_slice(source);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
 }
 
 impl Create<Self> for MemoryBackend {
@@ -277,19 +317,7 @@ impl Backend for MemoryBackend {
     }
 
     fn read(&mut self, id: &Id, buf: &mut [u8]) -> Result<usize, Error> {
-        match self.blocks.get(&id.0) {
-            Some(src) => {
-                let len = cmp::min(src.len(), buf.len());
-
-                let source = &src[..len];
-                let target = &mut buf[..len];
-
-                target.copy_from_slice(source);
-
-                Ok(len)
-            }
-            None => Err(Error::NoSuchId(*id)),
-        }
+        self.read_shared(id, buf)
     }
 
     fn write(&mut self, id: &Id, buf: &[u8]) -> Result<usize, Error> {
@@ -316,7 +344,62 @@ impl Backend for MemoryBackend {
         Ok(())
     }
 
+    fn write_backup_header(&mut self, buf: &[u8; HEADER_MAX_SIZE]) -> Result<(), Error> {
+        self.backup_header = Some(*buf);
+        Ok(())
+    }
+
+    fn lock(&mut self, mode: LockMode) -> Result<(), Error> {
+        self.lock = Some(mode);
+        Ok(())
+    }
+
     fn delete(self) {
         // noop
     }
 }
+
+impl SharedRead for MemoryBackend {
+    fn read_shared(&self, id: &Id, buf: &mut [u8]) -> Result<usize, Error> {
+        match self.blocks.get(&id.0) {
+            Some(src) => {
+                let len = cmp::min(src.len(), buf.len());
+
+                let source = &src[..len];
+                let target = &mut buf[..len];
+
+                target.copy_from_slice(source);
+
+                Ok(len)
+            }
+            None => Err(Error::NoSuchId(*id)),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncBackend for MemoryBackend {
+    fn aquire_async<'a>(&'a mut self, buf: &'a [u8]) -> BoxFuture<'a, Result<Id, Error>> {
+        Box::pin(future::ready(self.aquire(buf)))
+    }
+
+    fn release_async(&mut self, id: Id) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(future::ready(self.release(id)))
+    }
+
+    fn read_async<'a>(
+        &'a mut self,
+        id: &'a Id,
+        buf: &'a mut [u8],
+    ) -> BoxFuture<'a, Result<usize, Error>> {
+        Box::pin(future::ready(self.read(id, buf)))
+    }
+
+    fn write_async<'a>(
+        &'a mut self,
+        id: &'a Id,
+        buf: &'a [u8],
+    ) -> BoxFuture<'a, Result<usize, Error>> {
+        Box::pin(future::ready(self.write(id, buf)))
+    }
+}
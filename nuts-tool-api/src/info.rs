@@ -40,7 +40,24 @@ use serde::{Deserialize, Deserializer, Serialize};
 /// [`crate::OkResponse::Map`] response of a [`crate::Request::PluginInfo`]
 /// request now contains a `revision` key. Therfore, without the `revision` key
 /// in the response you will have a revision `0`.
-pub const CURRENT_REVISION: u32 = 1;
+///
+/// ## Revision 2
+///
+/// The [`crate::Request::ReadBackupHeader`] and
+/// [`crate::Request::WriteBackupHeader`] requests were added. They are only
+/// sent to plugins with at least this revision.
+///
+/// ## Revision 3
+///
+/// The following requests were added. They are only sent to plugins with at
+/// least this revision.
+///
+/// * [`crate::Request::OpenReadOnly`] opens the container read-only.
+/// * [`crate::Request::Lock`] locks the backend.
+/// * [`crate::Request::ReadMany`] reads several blocks at once.
+/// * [`crate::Request::WriteMany`] writes several blocks at once.
+/// * [`crate::Request::Flush`] makes all previous modifications durable.
+pub const CURRENT_REVISION: u32 = 3;
 
 fn de_revision<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
     let rev: u32 = Deserialize::deserialize(deserializer)?;
@@ -69,7 +69,7 @@ fn ser() {
     assert_eq!(doc.len(), 3);
     assert_eq!(doc.get_str("name").unwrap(), "foo");
     assert_eq!(doc.get_str("version").unwrap(), "xxx");
-    assert_eq!(doc.get_i64("revision").unwrap(), 1);
+    assert_eq!(doc.get_i64("revision").unwrap(), 3);
 }
 
 #[test]
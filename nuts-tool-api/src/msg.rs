@@ -91,6 +91,16 @@ impl<'a> fmt::Debug for VecDebug<'a> {
     }
 }
 
+struct VecListDebug<'a>(&'a Vec<Vec<u8>>);
+
+impl<'a> fmt::Debug for VecListDebug<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_list()
+            .entries(self.0.iter().map(VecDebug))
+            .finish()
+    }
+}
+
 /// The request message.
 #[derive(Deserialize, Serialize)]
 #[serde(tag = "op", content = "args", rename_all = "kebab-case")]
@@ -133,6 +143,13 @@ pub enum Request {
     /// * The response must be a [`OkResponse::Void`] variant.
     Open(Vec<u8>),
 
+    /// Request to open a backend-instance in read-only mode.
+    ///
+    /// * The argument contains binary data of the settings of the backend.
+    /// * The response must be a [`OkResponse::Void`] variant.
+    /// * Only sent to plugins with at least revision 3.
+    OpenReadOnly(Vec<u8>),
+
     /// Request to create a new backend-instance.
     ///
     /// * The first argument contains the binary data of the header.
@@ -168,12 +185,55 @@ pub enum Request {
     /// * The response must be a [`OkResponse::Void`] variant.
     WriteHeader(Vec<u8>),
 
+    /// Request to read the backup header of the backend.
+    ///
+    /// * The response must be a [`OkResponse::Bytes`] variant. The bytes are
+    ///   empty, if the backend has no backup header.
+    ReadBackupHeader,
+
+    /// Request to write the backup header of the backend.
+    ///
+    /// * The argument contains the header data to be written.
+    /// * The response must be a [`OkResponse::Void`] variant.
+    WriteBackupHeader(Vec<u8>),
+
     /// Request to read a block in the backend.
     ///
     /// * The argument contains the binary data of the id to read.
     /// * The response must be a [`OkResponse::Bytes`] variant.
     Read(Vec<u8>),
 
+    /// Request to read several blocks in the backend.
+    ///
+    /// * The argument contains the binary data of the ids to read.
+    /// * The response must be a [`OkResponse::BytesList`] variant with an
+    ///   entry for each id in the same order.
+    /// * Only sent to plugins with at least revision 3.
+    ReadMany(Vec<Vec<u8>>),
+
+    /// Request to write several blocks in the backend.
+    ///
+    /// * The argument contains a pair of the binary data of the id and the
+    ///   data to be written for each block.
+    /// * The response must be a [`OkResponse::UsizeList`] variant with an
+    ///   entry for each block in the same order.
+    /// * Only sent to plugins with at least revision 3.
+    WriteMany(Vec<(Vec<u8>, Vec<u8>)>),
+
+    /// Request to flush the backend.
+    ///
+    /// * The response must be a [`OkResponse::Void`] variant.
+    /// * Only sent to plugins with at least revision 3.
+    Flush,
+
+    /// Request to lock the backend.
+    ///
+    /// * The argument is `true` for an exclusive lock, `false` for a shared
+    ///   lock.
+    /// * The response must be a [`OkResponse::Void`] variant.
+    /// * Only sent to plugins with at least revision 3.
+    Lock(bool),
+
     /// Request to write a block in the backend.
     ///
     /// * The first argument contains the binary data of the id to read.
@@ -200,14 +260,21 @@ impl Request {
     as_into_impls!(as_id_to_bytes + into_id_to_bytes => IdToBytes(arg1: String));
     as_into_impls!(as_id_to_string + into_id_to_string => IdToString(arg1: Vec<u8>));
     as_into_impls!(as_open + into_open => Open (arg1: Vec<u8>));
+    as_into_impls!(as_open_read_only + into_open_read_only => OpenReadOnly (arg1: Vec<u8>));
     as_into_impls!(as_create + into_create => Create (arg1: Vec<u8>, args: bool));
     as_into_impls!(as_info + into_info => Info);
     as_into_impls!(as_aquire + into_aquire => Aquire (arg1: Vec<u8>));
     as_into_impls!(as_release + into_release => Release (arg1: Vec<u8>));
     as_into_impls!(as_read_header + into_read_header => ReadHeader);
     as_into_impls!(as_write_header + into_write_header => WriteHeader (arg1: Vec<u8>));
+    as_into_impls!(as_read_backup_header + into_read_backup_header => ReadBackupHeader);
+    as_into_impls!(as_write_backup_header + into_write_backup_header => WriteBackupHeader (arg1: Vec<u8>));
     as_into_impls!(as_read + into_read => Read (arg1: Vec<u8>));
     as_into_impls!(as_write + into_write => Write (arg1: Vec<u8>, arg2: Vec<u8>));
+    as_into_impls!(as_read_many + into_read_many => ReadMany (arg1: Vec<Vec<u8>>));
+    as_into_impls!(as_write_many + into_write_many => WriteMany (arg1: Vec<(Vec<u8>, Vec<u8>)>));
+    as_into_impls!(as_flush + into_flush => Flush);
+    as_into_impls!(as_lock + into_lock => Lock (arg1: bool));
     as_into_impls!(as_delete + into_delete => Delete);
     as_into_impls!(as_quit + into_quit => Quit);
 }
@@ -222,6 +289,10 @@ impl fmt::Debug for Request {
             Self::IdToBytes(arg) => fmt.debug_tuple("IdToBytes").field(arg).finish(),
             Self::IdToString(arg) => fmt.debug_tuple("IdToString").field(&VecDebug(arg)).finish(),
             Self::Open(arg) => fmt.debug_tuple("Open").field(&VecDebug(arg)).finish(),
+            Self::OpenReadOnly(arg) => fmt
+                .debug_tuple("OpenReadOnly")
+                .field(&VecDebug(arg))
+                .finish(),
             Self::Create(arg1, arg2) => fmt
                 .debug_tuple("Create")
                 .field(&VecDebug(arg1))
@@ -235,12 +306,31 @@ impl fmt::Debug for Request {
                 .debug_tuple("WriteHeader")
                 .field(&VecDebug(arg))
                 .finish(),
+            Self::ReadBackupHeader => write!(fmt, "ReadBackupHeader"),
+            Self::WriteBackupHeader(arg) => fmt
+                .debug_tuple("WriteBackupHeader")
+                .field(&VecDebug(arg))
+                .finish(),
             Self::Read(arg) => fmt.debug_tuple("Read").field(&VecDebug(arg)).finish(),
             Self::Write(arg1, arg2) => fmt
                 .debug_tuple("Write")
                 .field(&VecDebug(arg1))
                 .field(&VecDebug(arg2))
                 .finish(),
+            Self::ReadMany(arg) => fmt
+                .debug_tuple("ReadMany")
+                .field(&VecListDebug(arg))
+                .finish(),
+            Self::WriteMany(arg) => {
+                let list: Vec<_> = arg
+                    .iter()
+                    .map(|(id, bytes)| (VecDebug(id), VecDebug(bytes)))
+                    .collect();
+
+                fmt.debug_tuple("WriteMany").field(&list).finish()
+            }
+            Self::Flush => write!(fmt, "Flush"),
+            Self::Lock(arg) => fmt.debug_tuple("Lock").field(arg).finish(),
             Self::Delete => write!(fmt, "Delete"),
             Self::Quit => write!(fmt, "Quit"),
         }
@@ -284,6 +374,18 @@ impl Response {
         Self::Ok(OkResponse::String(value))
     }
 
+    /// Creates a successful response with an attached
+    /// [`OkResponse::BytesList`].
+    pub fn ok_bytes_list(value: Vec<Vec<u8>>) -> Response {
+        Self::Ok(OkResponse::BytesList(value))
+    }
+
+    /// Creates a successful response with an attached
+    /// [`OkResponse::UsizeList`].
+    pub fn ok_usize_list(value: Vec<usize>) -> Response {
+        Self::Ok(OkResponse::UsizeList(value))
+    }
+
     /// Creates a successful response with an attached [`OkResponse::Map`].
     pub fn ok_map(value: HashMap<String, String>) -> Response {
         Self::Ok(OkResponse::Map(value))
@@ -325,6 +427,12 @@ pub enum OkResponse {
     /// A successful response with an attached [`String`].
     String(String),
 
+    /// A successful response with an attached list of [`Vec<u8>`].
+    BytesList(Vec<Vec<u8>>),
+
+    /// A successful response with an attached list of [`usize`].
+    UsizeList(Vec<usize>),
+
     /// A successful response with an attached [`HashMap`].
     Map(HashMap<String, String>),
 }
@@ -337,6 +445,11 @@ impl fmt::Debug for OkResponse {
             Self::Usize(arg) => fmt.debug_tuple("Usize").field(arg).finish(),
             Self::Bytes(arg) => fmt.debug_tuple("Bytes").field(&VecDebug(arg)).finish(),
             Self::String(arg) => fmt.debug_tuple("String").field(arg).finish(),
+            Self::BytesList(arg) => fmt
+                .debug_tuple("BytesList")
+                .field(&VecListDebug(arg))
+                .finish(),
+            Self::UsizeList(arg) => fmt.debug_tuple("UsizeList").field(arg).finish(),
             Self::Map(arg) => fmt.debug_tuple("Map").field(arg).finish(),
         }
     }
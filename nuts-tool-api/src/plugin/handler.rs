@@ -22,7 +22,9 @@
 
 use clap::Args;
 use log::debug;
-use nuts_backend::{Backend, Binary, Create, IdSize, Open, ReceiveHeader, HEADER_MAX_SIZE};
+use nuts_backend::{
+    Backend, Binary, Create, IdSize, LockMode, Open, ReceiveHeader, HEADER_MAX_SIZE,
+};
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::marker::PhantomData;
@@ -118,6 +120,25 @@ pub trait PluginHandler<B: Backend> {
         }
     }
 
+    /// Handles the [`Request::OpenReadOnly`] command.
+    ///
+    /// The request is only sent to plugins with at least revision 3. The
+    /// builder is switched to read-only mode with [`Open::set_read_only()`].
+    fn handle_open_read_only(&self, args: &OpenArgs, settings: &[u8]) -> Result<B, ErrorResponse> {
+        let settings = <B::Settings as Binary>::from_bytes(settings)
+            .ok_or(ErrorResponse::InvalidSettingsData)?;
+
+        match self.open_builder(args) {
+            Some(mut builder) => {
+                builder.set_read_only(true);
+                builder
+                    .build(settings)
+                    .map_err(|err| ErrorResponse::backend::<B>(err))
+            }
+            None => Err(ErrorResponse::message("unable to make an open-builder")),
+        }
+    }
+
     /// Handles the [`Request::Create`] command.
     fn handle_create(
         &self,
@@ -202,6 +223,31 @@ pub trait PluginHandler<B: Backend> {
         B::write_header(backend, &header).map_err(|err| ErrorResponse::backend::<B>(err))
     }
 
+    /// Handles the [`Request::ReadBackupHeader`] command.
+    fn handle_read_backup_header<T: ReceiveHeader<B>>(
+        &self,
+        header: &mut T,
+    ) -> Result<Vec<u8>, ErrorResponse> {
+        let mut bytes = [0; HEADER_MAX_SIZE];
+
+        match header.get_backup_header_bytes(&mut bytes) {
+            Ok(true) => Ok(bytes.to_vec()),
+            Ok(false) => Ok(vec![]),
+            Err(err) => Err(ErrorResponse::backend::<B>(err)),
+        }
+    }
+
+    /// Handles the [`Request::WriteBackupHeader`] command.
+    fn handle_write_backup_header(
+        &self,
+        backend: &mut B,
+        header: &[u8],
+    ) -> Result<(), ErrorResponse> {
+        let header = into_header_bytes(header)?;
+
+        B::write_backup_header(backend, &header).map_err(|err| ErrorResponse::backend::<B>(err))
+    }
+
     /// Handles the [`Request::Read`] command.
     fn handle_read(&self, backend: &mut B, id: &[u8]) -> Result<Vec<u8>, ErrorResponse> {
         let id = <B::Id as Binary>::from_bytes(id).ok_or(ErrorResponse::InvalidIdData)?;
@@ -230,6 +276,56 @@ pub trait PluginHandler<B: Backend> {
         B::write(backend, &id, bytes).map_err(|err| ErrorResponse::backend::<B>(err))
     }
 
+    /// Handles the [`Request::ReadMany`] command.
+    fn handle_read_many(
+        &self,
+        backend: &mut B,
+        ids: &[Vec<u8>],
+    ) -> Result<Vec<Vec<u8>>, ErrorResponse> {
+        let ids = ids
+            .iter()
+            .map(|id| <B::Id as Binary>::from_bytes(id).ok_or(ErrorResponse::InvalidIdData))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        B::read_many(backend, &ids).map_err(|err| ErrorResponse::backend::<B>(err))
+    }
+
+    /// Handles the [`Request::WriteMany`] command.
+    fn handle_write_many(
+        &self,
+        backend: &mut B,
+        blocks: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<Vec<usize>, ErrorResponse> {
+        let bsize = B::block_size(backend) as usize;
+        let blocks = blocks
+            .iter()
+            .map(|(id, bytes)| {
+                let id = <B::Id as Binary>::from_bytes(id).ok_or(ErrorResponse::InvalidIdData)?;
+                let nbytes = cmp::min(bytes.len(), bsize);
+
+                Ok((id, &bytes[..nbytes]))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        B::write_many(backend, &blocks).map_err(|err| ErrorResponse::backend::<B>(err))
+    }
+
+    /// Handles the [`Request::Flush`] command.
+    fn handle_flush(&self, backend: &mut B) -> Result<(), ErrorResponse> {
+        B::flush(backend).map_err(|err| ErrorResponse::backend::<B>(err))
+    }
+
+    /// Handles the [`Request::Lock`] command.
+    fn handle_lock(&self, backend: &mut B, exclusive: bool) -> Result<(), ErrorResponse> {
+        let mode = if exclusive {
+            LockMode::Exclusive
+        } else {
+            LockMode::Shared
+        };
+
+        B::lock(backend, mode).map_err(|err| ErrorResponse::backend::<B>(err))
+    }
+
     fn handle_delete(&self, backend: B) -> Result<(), ErrorResponse> {
         B::delete(backend);
         Ok(())
@@ -315,6 +411,7 @@ impl<'a, B: Backend, T: PluginHandler<B>> OpenCreateHandler<'a, B, T> {
                         Request::PluginInfo => self.on_plugin_info(),
                         Request::Settings => self.on_settings(),
                         Request::Open(ref settings) => self.on_open(settings),
+                        Request::OpenReadOnly(ref settings) => self.on_open_read_only(settings),
                         Request::Create(ref header, overwrite) => self.on_create(header, overwrite),
                         Request::IdSize => self.on_id_size(),
                         Request::BlockSize => self.on_block_size(),
@@ -325,8 +422,16 @@ impl<'a, B: Backend, T: PluginHandler<B>> OpenCreateHandler<'a, B, T> {
                         Request::Release(ref id) => self.on_release(id),
                         Request::ReadHeader => self.on_read_header(),
                         Request::WriteHeader(ref header) => self.on_write_header(header),
+                        Request::ReadBackupHeader => self.on_read_backup_header(),
+                        Request::WriteBackupHeader(ref header) => {
+                            self.on_write_backup_header(header)
+                        }
                         Request::Read(ref id) => self.on_read(id),
                         Request::Write(ref id, ref bytes) => self.on_write(id, bytes),
+                        Request::ReadMany(ref ids) => self.on_read_many(ids),
+                        Request::WriteMany(ref blocks) => self.on_write_many(blocks),
+                        Request::Flush => self.on_flush(),
+                        Request::Lock(exclusive) => self.on_lock(exclusive),
                         Request::Delete => self.on_delete(),
                         Request::Quit => self.on_quit(),
                     };
@@ -378,6 +483,20 @@ impl<'a, B: Backend, T: PluginHandler<B>> OpenCreateHandler<'a, B, T> {
         }
     }
 
+    fn on_open_read_only(&mut self, settings: &[u8]) -> Response {
+        if let Some(args) = self.command.as_open() {
+            match self.handler.handle_open_read_only(args, settings) {
+                Ok(backend) => {
+                    self.backend = Some(backend);
+                    Response::ok_void()
+                }
+                Err(err) => Response::Err(err),
+            }
+        } else {
+            Response::err_not_applicable()
+        }
+    }
+
     fn on_create(&mut self, header: &[u8], overwrite: bool) -> Response {
         if let Some(args) = self.command.as_create() {
             match self.handler.handle_create(args, header, overwrite) {
@@ -488,6 +607,37 @@ impl<'a, B: Backend, T: PluginHandler<B>> OpenCreateHandler<'a, B, T> {
         }
     }
 
+    fn on_read_backup_header(&mut self) -> Response {
+        if let Some(args) = self.command.as_open() {
+            if let Some(mut builder) = self.handler.open_builder(args) {
+                match self.handler.handle_read_backup_header(&mut builder) {
+                    Ok(header) => Response::ok_bytes(header),
+                    Err(err) => Response::Err(err),
+                }
+            } else {
+                Response::err_message("unable to build an open-builder")
+            }
+        } else if let Some(backend) = self.backend.as_mut() {
+            match self.handler.handle_read_backup_header(backend) {
+                Ok(header) => Response::ok_bytes(header),
+                Err(err) => Response::Err(err),
+            }
+        } else {
+            Response::err_not_applicable()
+        }
+    }
+
+    fn on_write_backup_header(&mut self, header: &[u8]) -> Response {
+        if let Some(backend) = self.backend.as_mut() {
+            match self.handler.handle_write_backup_header(backend, header) {
+                Ok(()) => Response::ok_void(),
+                Err(err) => Response::Err(err),
+            }
+        } else {
+            Response::err_not_applicable()
+        }
+    }
+
     fn on_read(&mut self, id: &[u8]) -> Response {
         if let Some(backend) = self.backend.as_mut() {
             match self.handler.handle_read(backend, id) {
@@ -510,6 +660,50 @@ impl<'a, B: Backend, T: PluginHandler<B>> OpenCreateHandler<'a, B, T> {
         }
     }
 
+    fn on_read_many(&mut self, ids: &[Vec<u8>]) -> Response {
+        if let Some(backend) = self.backend.as_mut() {
+            match self.handler.handle_read_many(backend, ids) {
+                Ok(blocks) => Response::ok_bytes_list(blocks),
+                Err(err) => Response::Err(err),
+            }
+        } else {
+            Response::err_not_applicable()
+        }
+    }
+
+    fn on_write_many(&mut self, blocks: &[(Vec<u8>, Vec<u8>)]) -> Response {
+        if let Some(backend) = self.backend.as_mut() {
+            match self.handler.handle_write_many(backend, blocks) {
+                Ok(nbytes) => Response::ok_usize_list(nbytes),
+                Err(err) => Response::Err(err),
+            }
+        } else {
+            Response::err_not_applicable()
+        }
+    }
+
+    fn on_flush(&mut self) -> Response {
+        if let Some(backend) = self.backend.as_mut() {
+            match self.handler.handle_flush(backend) {
+                Ok(()) => Response::ok_void(),
+                Err(err) => Response::Err(err),
+            }
+        } else {
+            Response::err_not_applicable()
+        }
+    }
+
+    fn on_lock(&mut self, exclusive: bool) -> Response {
+        if let Some(backend) = self.backend.as_mut() {
+            match self.handler.handle_lock(backend, exclusive) {
+                Ok(()) => Response::ok_void(),
+                Err(err) => Response::Err(err),
+            }
+        } else {
+            Response::err_not_applicable()
+        }
+    }
+
     fn on_delete(&mut self) -> Response {
         if let Some(backend) = self.backend.take() {
             match self.handler.handle_delete(backend) {
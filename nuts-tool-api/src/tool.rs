@@ -21,6 +21,7 @@
 // IN THE SOFTWARE.
 
 mod connection;
+mod inbox;
 mod plugin;
 
 use log::error;
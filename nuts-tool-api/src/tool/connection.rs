@@ -23,14 +23,18 @@
 use log::{debug, error, info, log, trace, warn, Level};
 use std::collections::HashMap;
 use std::convert::TryInto;
+#[cfg(feature = "async")]
+use std::future;
 use std::io::{BufRead, BufReader};
 use std::panic;
 use std::process::{Child, ChildStderr, ChildStdin, ChildStdout};
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 
 use crate::bson::{BsonReader, BsonWriter};
 use crate::msg::{OkResponse, Request, Response};
+use crate::tool::inbox::Inbox;
 use crate::tool::{PluginError, PluginResult};
 use crate::PluginInfo;
 
@@ -48,14 +52,24 @@ fn stdin_thread(stdin: ChildStdin, rx_in: Receiver<Request>) -> PluginResult<()>
     Ok(())
 }
 
-fn stdout_thread(stdout: ChildStdout, tx_out: Sender<Response>) -> PluginResult<()> {
+/// Closes the inbox when the stdout-thread is left, even on a panic.
+struct CloseGuard(Arc<Inbox>);
+
+impl Drop for CloseGuard {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+fn stdout_thread(stdout: ChildStdout, inbox: Arc<Inbox>) -> PluginResult<()> {
+    let inbox = CloseGuard(inbox);
     let mut reader = BsonReader::new(stdout);
 
     loop {
         match reader.read() {
             Ok(Some(response)) => {
                 trace!("stdout: received {:?}", response);
-                tx_out.send(response)?;
+                inbox.0.push(response);
             }
             Ok(None) => {
                 trace!("stdout: end of stream");
@@ -110,7 +124,7 @@ fn stderr_thread(stderr: ChildStderr) -> PluginResult<()> {
 }
 
 macro_rules! handshake_func {
-    ($name:ident ( $( $argn:ident : $argt:ty ),* ) -> $ty:ty, $req:expr, $variant:pat => $ret:expr) => {
+    ($name:ident, $async_name:ident ( $( $argn:ident : $argt:ty ),* ) -> $ty:ty, $req:expr, $variant:pat => $ret:expr) => {
         pub fn $name(&mut self, $($argn: $argt),*) -> PluginResult<$ty> {
             let response = self.handshake($req).map_err(|err| {
                 error!("failed message handshake: {}", err);
@@ -129,6 +143,26 @@ macro_rules! handshake_func {
 
             result
         }
+
+        #[cfg(feature = "async")]
+        pub async fn $async_name(&mut self, $($argn: $argt),*) -> PluginResult<$ty> {
+            let response = self.handshake_async($req).await.map_err(|err| {
+                error!("failed message handshake: {}", err);
+                err
+            })?;
+
+            let result = match response {
+                Response::Ok($variant) => $ret,
+                Response::Ok(_) => Err(PluginError::InvalidResponse),
+                Response::Err(err) => Err(PluginError::Response(err)),
+            };
+
+            if result.is_err() {
+                self.shutdown();
+            }
+
+            result
+        }
     };
 }
 
@@ -150,7 +184,8 @@ macro_rules! join_thread {
 pub struct PluginConnection {
     child: Child,
     tx_in: Option<Sender<Request>>,
-    rx_out: Option<Receiver<Response>>,
+    inbox: Option<Arc<Inbox>>,
+    pending: bool,
     t_stdin: Option<JoinHandle<Result<(), PluginError>>>,
     t_stdout: Option<JoinHandle<Result<(), PluginError>>>,
     t_stderr: Option<JoinHandle<Result<(), PluginError>>>,
@@ -167,11 +202,14 @@ impl PluginConnection {
             (None, None)
         };
 
-        let (rx_out, t_stdout) = if let Some(stdout) = child.stdout.take() {
-            let (tx, rx) = mpsc::channel();
-            let thr = thread::spawn(move || stdout_thread(stdout, tx));
+        let (inbox, t_stdout) = if let Some(stdout) = child.stdout.take() {
+            let inbox = Arc::new(Inbox::default());
+            let thr = thread::spawn({
+                let inbox = inbox.clone();
+                move || stdout_thread(stdout, inbox)
+            });
 
-            (Some(rx), Some(thr))
+            (Some(inbox), Some(thr))
         } else {
             (None, None)
         };
@@ -187,29 +225,37 @@ impl PluginConnection {
         PluginConnection {
             child,
             tx_in,
-            rx_out,
+            inbox,
+            pending: false,
             t_stdin,
             t_stdout,
             t_stderr,
         }
     }
 
-    handshake_func!(plugin_info() -> PluginInfo, Request::PluginInfo, OkResponse::Map(info) => info.try_into());
-    handshake_func!(id_string_to_bytes(str: String) -> Vec<u8>, Request::IdToBytes(str), OkResponse::Bytes(bytes) => Ok(bytes));
-    handshake_func!(id_bytes_to_string(bytes: Vec<u8>) -> String, Request::IdToString(bytes), OkResponse::String(str) => Ok(str));
-    handshake_func!(settings() -> Vec<u8>, Request::Settings, OkResponse::Bytes(bytes) => Ok(bytes));
-    handshake_func!(id_size() -> usize, Request::IdSize, OkResponse::Usize(num) => Ok(num));
-    handshake_func!(block_size() -> u32, Request::BlockSize, OkResponse::U32(num) => Ok(num));
-    handshake_func!(open(settings: Vec<u8>) -> (), Request::Open(settings), OkResponse::Void => Ok(()));
-    handshake_func!(create(header: Vec<u8>, overwrite: bool) -> (), Request::Create(header, overwrite), OkResponse::Void => Ok(()));
-    handshake_func!(info() -> HashMap<String, String>, Request::Info, OkResponse::Map(map) => Ok(map));
-    handshake_func!(aquire(bytes: Vec<u8>) -> Vec<u8>, Request::Aquire(bytes), OkResponse::Bytes(bytes) => Ok(bytes));
-    handshake_func!(release(id: Vec<u8>) -> (), Request::Release(id), OkResponse::Void => Ok(()));
-    handshake_func!(read_header() -> Vec<u8>, Request::ReadHeader, OkResponse::Bytes(bytes) => Ok(bytes));
-    handshake_func!(write_header(bytes: Vec<u8>) -> (), Request::WriteHeader(bytes), OkResponse::Void => Ok(()));
-    handshake_func!(read(id: Vec<u8>) -> Vec<u8>, Request::Read(id), OkResponse::Bytes(bytes) => Ok(bytes));
-    handshake_func!(write(id: Vec<u8>, bytes: Vec<u8>) -> usize, Request::Write(id, bytes), OkResponse::Usize(num) => Ok(num));
-    handshake_func!(delete() -> (), Request::Delete, OkResponse::Void => Ok(()));
+    handshake_func!(plugin_info, plugin_info_async() -> PluginInfo, Request::PluginInfo, OkResponse::Map(info) => info.try_into());
+    handshake_func!(id_string_to_bytes, id_string_to_bytes_async(str: String) -> Vec<u8>, Request::IdToBytes(str), OkResponse::Bytes(bytes) => Ok(bytes));
+    handshake_func!(id_bytes_to_string, id_bytes_to_string_async(bytes: Vec<u8>) -> String, Request::IdToString(bytes), OkResponse::String(str) => Ok(str));
+    handshake_func!(settings, settings_async() -> Vec<u8>, Request::Settings, OkResponse::Bytes(bytes) => Ok(bytes));
+    handshake_func!(id_size, id_size_async() -> usize, Request::IdSize, OkResponse::Usize(num) => Ok(num));
+    handshake_func!(block_size, block_size_async() -> u32, Request::BlockSize, OkResponse::U32(num) => Ok(num));
+    handshake_func!(open, open_async(settings: Vec<u8>) -> (), Request::Open(settings), OkResponse::Void => Ok(()));
+    handshake_func!(open_read_only, open_read_only_async(settings: Vec<u8>) -> (), Request::OpenReadOnly(settings), OkResponse::Void => Ok(()));
+    handshake_func!(create, create_async(header: Vec<u8>, overwrite: bool) -> (), Request::Create(header, overwrite), OkResponse::Void => Ok(()));
+    handshake_func!(info, info_async() -> HashMap<String, String>, Request::Info, OkResponse::Map(map) => Ok(map));
+    handshake_func!(aquire, aquire_async(bytes: Vec<u8>) -> Vec<u8>, Request::Aquire(bytes), OkResponse::Bytes(bytes) => Ok(bytes));
+    handshake_func!(release, release_async(id: Vec<u8>) -> (), Request::Release(id), OkResponse::Void => Ok(()));
+    handshake_func!(read_header, read_header_async() -> Vec<u8>, Request::ReadHeader, OkResponse::Bytes(bytes) => Ok(bytes));
+    handshake_func!(write_header, write_header_async(bytes: Vec<u8>) -> (), Request::WriteHeader(bytes), OkResponse::Void => Ok(()));
+    handshake_func!(read_backup_header, read_backup_header_async() -> Vec<u8>, Request::ReadBackupHeader, OkResponse::Bytes(bytes) => Ok(bytes));
+    handshake_func!(write_backup_header, write_backup_header_async(bytes: Vec<u8>) -> (), Request::WriteBackupHeader(bytes), OkResponse::Void => Ok(()));
+    handshake_func!(read, read_async(id: Vec<u8>) -> Vec<u8>, Request::Read(id), OkResponse::Bytes(bytes) => Ok(bytes));
+    handshake_func!(write, write_async(id: Vec<u8>, bytes: Vec<u8>) -> usize, Request::Write(id, bytes), OkResponse::Usize(num) => Ok(num));
+    handshake_func!(read_many, read_many_async(ids: Vec<Vec<u8>>) -> Vec<Vec<u8>>, Request::ReadMany(ids), OkResponse::BytesList(blocks) => Ok(blocks));
+    handshake_func!(write_many, write_many_async(blocks: Vec<(Vec<u8>, Vec<u8>)>) -> Vec<usize>, Request::WriteMany(blocks), OkResponse::UsizeList(nbytes) => Ok(nbytes));
+    handshake_func!(flush, flush_async() -> (), Request::Flush, OkResponse::Void => Ok(()));
+    handshake_func!(lock, lock_async(exclusive: bool) -> (), Request::Lock(exclusive), OkResponse::Void => Ok(()));
+    handshake_func!(delete, delete_async() -> (), Request::Delete, OkResponse::Void => Ok(()));
 
     pub fn quit(&mut self) -> PluginResult<()> {
         let response = match self.handshake(Request::Quit) {
@@ -240,14 +286,55 @@ impl PluginConnection {
     fn handshake(&mut self, request: Request) -> PluginResult<Response> {
         debug!("handshake requested for {:?}", request);
 
+        let inbox = self.inbox.clone().ok_or(PluginError::ChannelClosed)?;
+
+        if self.pending {
+            // The response of an aborted asynchronous handshake is still
+            // pending, skip it
+            self.pending = inbox.recv().is_some();
+        }
+
+        self.send(request)?;
+
+        self.received(inbox.recv())
+    }
+
+    #[cfg(feature = "async")]
+    async fn handshake_async(&mut self, request: Request) -> PluginResult<Response> {
+        debug!("async handshake requested for {:?}", request);
+
+        let inbox = self.inbox.clone().ok_or(PluginError::ChannelClosed)?;
+
+        if self.pending {
+            // The response of an aborted asynchronous handshake is still
+            // pending, skip it
+            self.pending = future::poll_fn(|cx| inbox.poll_recv(cx)).await.is_some();
+        }
+
+        self.send(request)?;
+
+        // If the future is dropped while waiting, the response remains
+        // pending and is skipped by the next handshake.
+        let response = future::poll_fn(|cx| inbox.poll_recv(cx)).await;
+
+        self.received(response)
+    }
+
+    fn send(&mut self, request: Request) -> PluginResult<()> {
         let tx = self.tx_in.as_mut().ok_or(PluginError::ChannelClosed)?;
-        let rx = self.rx_out.as_mut().ok_or(PluginError::ChannelClosed)?;
 
         tx.send(request)?;
+        self.pending = true;
 
-        match rx.recv() {
-            Ok(response) => Ok(response),
-            Err(_) => {
+        Ok(())
+    }
+
+    fn received(&mut self, response: Option<Response>) -> PluginResult<Response> {
+        self.pending = false;
+
+        match response {
+            Some(response) => Ok(response),
+            None => {
                 self.shutdown();
                 Err(PluginError::ChannelClosed)
             }
@@ -0,0 +1,115 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::task::Waker;
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
+
+use crate::msg::Response;
+
+#[derive(Debug, Default)]
+struct State {
+    queue: VecDeque<Response>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+/// Queue of responses received from the plugin.
+///
+/// The stdout-thread pushes the responses into the inbox, the connection
+/// receives them either blocking or asynchronously.
+#[derive(Debug, Default)]
+pub struct Inbox {
+    state: Mutex<State>,
+    cond: Condvar,
+}
+
+impl Inbox {
+    pub fn push(&self, response: Response) {
+        let mut state = self.lock();
+
+        state.queue.push_back(response);
+        self.notify(&mut state);
+    }
+
+    pub fn close(&self) {
+        let mut state = self.lock();
+
+        state.closed = true;
+        self.notify(&mut state);
+    }
+
+    /// Waits for the next response.
+    ///
+    /// Returns [`None`] if the inbox is closed and no more responses are
+    /// available.
+    pub fn recv(&self) -> Option<Response> {
+        let mut state = self.lock();
+
+        loop {
+            if let Some(response) = state.queue.pop_front() {
+                return Some(response);
+            }
+
+            if state.closed {
+                return None;
+            }
+
+            state = self
+                .cond
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Asynchronous flavor of [`Inbox::recv`].
+    #[cfg(feature = "async")]
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<Response>> {
+        let mut state = self.lock();
+
+        if let Some(response) = state.queue.pop_front() {
+            Poll::Ready(Some(response))
+        } else if state.closed {
+            Poll::Ready(None)
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn notify(&self, state: &mut State) {
+        self.cond.notify_all();
+
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use std::sync::Arc;
+use std::thread;
+
+use crate::msg::{OkResponse, Response};
+use crate::tool::inbox::Inbox;
+
+#[test]
+fn recv() {
+    let inbox = Inbox::default();
+
+    inbox.push(Response::ok_u32(1));
+    inbox.push(Response::ok_u32(2));
+
+    assert!(matches!(
+        inbox.recv(),
+        Some(Response::Ok(OkResponse::U32(1)))
+    ));
+    assert!(matches!(
+        inbox.recv(),
+        Some(Response::Ok(OkResponse::U32(2)))
+    ));
+}
+
+#[test]
+fn recv_closed() {
+    let inbox = Inbox::default();
+
+    inbox.push(Response::ok_u32(1));
+    inbox.close();
+
+    assert!(matches!(
+        inbox.recv(),
+        Some(Response::Ok(OkResponse::U32(1)))
+    ));
+    assert!(inbox.recv().is_none());
+}
+
+#[test]
+fn recv_wait() {
+    let inbox = Arc::new(Inbox::default());
+
+    let handle = thread::spawn({
+        let inbox = inbox.clone();
+        move || inbox.push(Response::ok_u32(1))
+    });
+
+    assert!(matches!(
+        inbox.recv(),
+        Some(Response::Ok(OkResponse::U32(1)))
+    ));
+    handle.join().unwrap();
+}
+
+#[cfg(feature = "async")]
+mod poll {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    use crate::msg::{OkResponse, Response};
+    use crate::tool::inbox::Inbox;
+
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn pending_then_ready() {
+        let inbox = Inbox::default();
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        assert!(inbox.poll_recv(&mut cx).is_pending());
+        assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+
+        inbox.push(Response::ok_u32(1));
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+
+        assert!(matches!(
+            inbox.poll_recv(&mut cx),
+            Poll::Ready(Some(Response::Ok(OkResponse::U32(1))))
+        ));
+    }
+
+    #[test]
+    fn closed() {
+        let inbox = Inbox::default();
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        assert!(inbox.poll_recv(&mut cx).is_pending());
+
+        inbox.close();
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+
+        assert!(matches!(inbox.poll_recv(&mut cx), Poll::Ready(None)));
+    }
+}
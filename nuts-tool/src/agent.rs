@@ -0,0 +1,463 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use log::{debug, warn};
+use nuts_container::WrappingKey;
+use nuts_tool_api::{tool_dir, BsonError, BsonReader, BsonWriter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, DirBuilder};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{fmt, io, thread};
+use thiserror::Error;
+
+/// Connections to the agent time out after this duration.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Expired keys are removed from the agent in this interval.
+const PURGE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Error type of the agent.
+#[derive(Debug, Error)]
+pub enum AgentError {
+    /// An error in the communication with the agent occured.
+    #[error(transparent)]
+    Bson(#[from] BsonError),
+
+    /// An IO-error occured.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// Another agent is already listening on the socket.
+    #[error("an agent is already running at {0}")]
+    AlreadyRunning(PathBuf),
+
+    /// The peer closed the connection.
+    #[error("the agent closed the connection")]
+    Closed,
+
+    /// The agent sent a response, which does not match the request.
+    #[error("unexpected response from the agent")]
+    UnexpectedResponse,
+}
+
+/// Returns the default path of the agent socket.
+///
+/// The socket is located in a directory, which is only accessible by the
+/// user. An existing directory must be owned by the user and must have the
+/// mode `0700`, otherwise an error is returned.
+pub fn default_socket_path() -> io::Result<PathBuf> {
+    let dir = tool_dir()?.join("agent");
+
+    match fs::symlink_metadata(&dir) {
+        Ok(_) => {}
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            debug!("creating agent dir {}", dir.display());
+            DirBuilder::new().mode(0o700).create(&dir)?;
+        }
+        Err(err) => return Err(err),
+    }
+
+    let metadata = fs::symlink_metadata(&dir)?;
+    let uid = unsafe { libc::getuid() };
+
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o777 != 0o700 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "{} must be a directory owned by you with mode 0700",
+                dir.display()
+            ),
+        ));
+    }
+
+    Ok(dir.join("agent.sock"))
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "op", content = "args", rename_all = "kebab-case")]
+enum Request {
+    Get(String),
+    Add(String, Vec<u8>, Option<u64>),
+    Remove(String),
+    Lock,
+    List,
+    Stop,
+}
+
+impl fmt::Debug for Request {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Get(name) => fmt.debug_tuple("Get").field(name).finish(),
+            Self::Add(name, _, lifetime) => fmt
+                .debug_tuple("Add")
+                .field(name)
+                .field(&"***")
+                .field(lifetime)
+                .finish(),
+            Self::Remove(name) => fmt.debug_tuple("Remove").field(name).finish(),
+            Self::Lock => write!(fmt, "Lock"),
+            Self::List => write!(fmt, "List"),
+            Self::Stop => write!(fmt, "Stop"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "type", content = "args", rename_all = "kebab-case")]
+enum Response {
+    Void,
+    Key(Option<Vec<u8>>),
+    Removed(bool),
+    List(Vec<(String, u64)>),
+}
+
+impl fmt::Debug for Response {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Void => write!(fmt, "Void"),
+            Self::Key(key) => fmt
+                .debug_tuple("Key")
+                .field(&key.as_ref().map(|_| "***"))
+                .finish(),
+            Self::Removed(flag) => fmt.debug_tuple("Removed").field(flag).finish(),
+            Self::List(entries) => fmt.debug_tuple("List").field(entries).finish(),
+        }
+    }
+}
+
+/// A client of a running agent.
+///
+/// Each request is sent over a new connection, so the agent is never
+/// blocked by an idle client.
+pub struct AgentClient {
+    path: PathBuf,
+}
+
+impl AgentClient {
+    /// Connects to the agent listening on `path`.
+    pub fn connect(path: &Path) -> Result<AgentClient, AgentError> {
+        UnixStream::connect(path)?;
+
+        debug!("agent is running at {}", path.display());
+
+        Ok(AgentClient {
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Connects to the agent listening on `path`.
+    ///
+    /// Returns [`None`] if no agent is running.
+    pub fn connect_if_running(path: &Path) -> Result<Option<AgentClient>, AgentError> {
+        match Self::connect(path) {
+            Ok(client) => Ok(Some(client)),
+            Err(AgentError::Io(err))
+                if err.kind() == io::ErrorKind::NotFound
+                    || err.kind() == io::ErrorKind::ConnectionRefused =>
+            {
+                debug!("no agent running at {}", path.display());
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns the wrapping key of the container `name`.
+    pub fn get(&mut self, name: &str) -> Result<Option<WrappingKey>, AgentError> {
+        match self.request(Request::Get(name.to_string()))? {
+            Response::Key(key) => Ok(key.map(|key| WrappingKey::from_bytes(&key))),
+            _ => Err(AgentError::UnexpectedResponse),
+        }
+    }
+
+    /// Passes the wrapping key of the container `name` to the agent.
+    ///
+    /// The agent forgets the key after `lifetime`. If not set, the default
+    /// lifetime of the agent is used.
+    pub fn add(
+        &mut self,
+        name: &str,
+        key: &WrappingKey,
+        lifetime: Option<Duration>,
+    ) -> Result<(), AgentError> {
+        let request = Request::Add(
+            name.to_string(),
+            key.as_bytes().to_vec(),
+            lifetime.map(|d| d.as_secs()),
+        );
+
+        match self.request(request)? {
+            Response::Void => Ok(()),
+            _ => Err(AgentError::UnexpectedResponse),
+        }
+    }
+
+    /// Removes the wrapping key of the container `name` from the agent.
+    ///
+    /// Returns `true` if the agent knew the key.
+    pub fn remove(&mut self, name: &str) -> Result<bool, AgentError> {
+        match self.request(Request::Remove(name.to_string()))? {
+            Response::Removed(flag) => Ok(flag),
+            _ => Err(AgentError::UnexpectedResponse),
+        }
+    }
+
+    /// Removes all keys from the agent.
+    pub fn lock(&mut self) -> Result<(), AgentError> {
+        match self.request(Request::Lock)? {
+            Response::Void => Ok(()),
+            _ => Err(AgentError::UnexpectedResponse),
+        }
+    }
+
+    /// Lists the names of the containers known by the agent together with
+    /// the remaining lifetime of their keys.
+    pub fn list(&mut self) -> Result<Vec<(String, Duration)>, AgentError> {
+        match self.request(Request::List)? {
+            Response::List(entries) => Ok(entries
+                .into_iter()
+                .map(|(name, secs)| (name, Duration::from_secs(secs)))
+                .collect()),
+            _ => Err(AgentError::UnexpectedResponse),
+        }
+    }
+
+    /// Stops the agent.
+    pub fn stop(&mut self) -> Result<(), AgentError> {
+        match self.request(Request::Stop)? {
+            Response::Void => Ok(()),
+            _ => Err(AgentError::UnexpectedResponse),
+        }
+    }
+
+    fn request(&mut self, request: Request) -> Result<Response, AgentError> {
+        let stream = UnixStream::connect(&self.path)?;
+
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+        let mut reader = BsonReader::new(stream.try_clone()?);
+        let mut writer = BsonWriter::new(stream);
+
+        writer.write(request)?;
+        reader.read()?.ok_or(AgentError::Closed)
+    }
+}
+
+struct Entry {
+    key: WrappingKey,
+    expires: Instant,
+}
+
+type Entries = Arc<Mutex<HashMap<String, Entry>>>;
+
+fn purge(entries: &Entries) {
+    let now = Instant::now();
+    let mut entries = entries.lock().unwrap();
+
+    entries.retain(|name, entry| {
+        let keep = entry.expires > now;
+
+        if !keep {
+            debug!("key of {} expired", name);
+        }
+
+        keep
+    });
+}
+
+/// The agent, which keeps the wrapping keys of containers.
+///
+/// The keys are stored in memory, which is locked into RAM and excluded
+/// from core dumps. Each key is forgotten after its lifetime.
+pub struct Agent {
+    path: PathBuf,
+    listener: UnixListener,
+    lifetime: Duration,
+    entries: Entries,
+}
+
+impl Agent {
+    /// Creates the socket of the agent at `path`.
+    ///
+    /// Keys are kept for `lifetime` unless a different lifetime is requested
+    /// when adding the key.
+    pub fn bind(path: &Path, lifetime: Duration) -> Result<Agent, AgentError> {
+        if UnixStream::connect(path).is_ok() {
+            return Err(AgentError::AlreadyRunning(path.to_path_buf()));
+        }
+
+        match fs::remove_file(path) {
+            Ok(()) => debug!("removed stale socket {}", path.display()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        // the socket is created with mode 0600, there is no window where
+        // another user can connect
+        let mask = unsafe { libc::umask(0o177) };
+        let listener = UnixListener::bind(path);
+        unsafe { libc::umask(mask) };
+
+        let listener = listener?;
+
+        debug!("agent listening at {}", path.display());
+
+        Ok(Agent {
+            path: path.to_path_buf(),
+            listener,
+            lifetime,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Serves requests until the agent is stopped.
+    pub fn run(&self) -> Result<(), AgentError> {
+        disable_dumps();
+
+        let entries = self.entries.clone();
+        thread::spawn(move || loop {
+            thread::sleep(PURGE_INTERVAL);
+            purge(&entries);
+        });
+
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    warn!("failed to accept connection: {}", err);
+                    continue;
+                }
+            };
+
+            match self.serve(stream) {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err(err) => warn!("connection aborted: {}", err),
+            }
+        }
+
+        debug!("agent stopped");
+
+        Ok(())
+    }
+
+    /// Serves the requests of a single connection.
+    ///
+    /// Returns `true` if the agent was asked to stop.
+    fn serve(&self, stream: UnixStream) -> Result<bool, AgentError> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+        let mut reader = BsonReader::new(stream.try_clone()?);
+        let mut writer = BsonWriter::new(stream);
+
+        while let Some(request) = reader.read::<Request>()? {
+            let stop = matches!(request, Request::Stop);
+
+            writer.write(self.handle(request))?;
+
+            if stop {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn handle(&self, request: Request) -> Response {
+        purge(&self.entries);
+
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+
+        match request {
+            Request::Get(name) => {
+                Response::Key(entries.get(&name).map(|e| e.key.as_bytes().to_vec()))
+            }
+            Request::Add(name, mut key, lifetime) => {
+                let lifetime = lifetime.map_or(self.lifetime, Duration::from_secs);
+                let entry = Entry {
+                    key: WrappingKey::from_bytes(&key),
+                    expires: now + lifetime,
+                };
+
+                key.iter_mut().for_each(|n| *n = 0);
+                entries.insert(name, entry);
+
+                Response::Void
+            }
+            Request::Remove(name) => Response::Removed(entries.remove(&name).is_some()),
+            Request::Lock => {
+                entries.clear();
+                Response::Void
+            }
+            Request::List => {
+                let mut list: Vec<(String, u64)> = entries
+                    .iter()
+                    .map(|(name, e)| {
+                        (
+                            name.clone(),
+                            e.expires.saturating_duration_since(now).as_secs(),
+                        )
+                    })
+                    .collect();
+
+                list.sort();
+
+                Response::List(list)
+            }
+            Request::Stop => {
+                entries.clear();
+                Response::Void
+            }
+        }
+    }
+}
+
+impl Drop for Agent {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.path) {
+            warn!("failed to remove {}: {}", self.path.display(), err);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn disable_dumps() {
+    // Prevents core dumps and ptrace-attaching by other processes of the
+    // same user.
+    if unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0, 0, 0, 0) } != 0 {
+        warn!(
+            "failed to disable core dumps: {}",
+            io::Error::last_os_error()
+        );
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn disable_dumps() {}
@@ -168,6 +168,8 @@ pub fn append_recursive(archive: &mut Archive<PluginBackend>, path: &Path) -> Re
                 break;
             }
         }
+
+        entry.flush()?;
     } else if metadata.is_dir() {
         let mut builder = archive.append_directory(path.to_string_lossy());
 
@@ -21,7 +21,9 @@
 // IN THE SOFTWARE.
 
 use log::error;
-use nuts_backend::{Backend, Binary, Create, IdSize, Open, ReceiveHeader, HEADER_MAX_SIZE};
+use nuts_backend::{
+    Backend, Binary, Create, IdSize, LockMode, Open, ReceiveHeader, HEADER_MAX_SIZE,
+};
 use nuts_tool_api::tool::{Plugin, PluginConnection, PluginError};
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -31,12 +33,30 @@ use std::{cmp, fmt};
 thread_local! {
     static ID_SIZE: RefCell<usize> = RefCell::new(0);
     static CONN: RefCell<Option<PluginConnection>> = RefCell::new(None);
+    static REVISION: RefCell<u32> = const { RefCell::new(0) };
 }
 
+/// Plugins starting with this revision support a backup header.
+const BACKUP_HEADER_REVISION: u32 = 2;
+
+/// Plugins starting with this revision can be opened read-only.
+const READ_ONLY_REVISION: u32 = 3;
+
+/// Plugins starting with this revision can be locked.
+const LOCK_REVISION: u32 = 3;
+
+/// Plugins starting with this revision can be flushed.
+const FLUSH_REVISION: u32 = 3;
+
+/// Plugins starting with this revision read and write several blocks at once.
+const MANY_REVISION: u32 = 3;
+
 fn setup_connection(mut connection: PluginConnection) -> Result<(), PluginError> {
     let id_size = connection.id_size()?;
+    let revision = connection.plugin_info()?.revision();
 
     ID_SIZE.with(|size| *size.borrow_mut() = id_size);
+    REVISION.with(|rev| *rev.borrow_mut() = revision);
     CONN.with(|cell| *cell.borrow_mut() = Some(connection));
 
     Ok(())
@@ -51,6 +71,54 @@ fn with_connection<T, F: FnOnce(&mut PluginConnection) -> Result<T, PluginError>
     })
 }
 
+fn has_backup_header() -> bool {
+    REVISION.with(|rev| *rev.borrow() >= BACKUP_HEADER_REVISION)
+}
+
+fn has_read_only() -> bool {
+    REVISION.with(|rev| *rev.borrow() >= READ_ONLY_REVISION)
+}
+
+fn has_lock() -> bool {
+    REVISION.with(|rev| *rev.borrow() >= LOCK_REVISION)
+}
+
+fn has_flush() -> bool {
+    REVISION.with(|rev| *rev.borrow() >= FLUSH_REVISION)
+}
+
+fn has_many() -> bool {
+    REVISION.with(|rev| *rev.borrow() >= MANY_REVISION)
+}
+
+/// Copies the `header` received from the plugin into `bytes`.
+///
+/// The response of the plugin is not trusted, a header, which is shorter than
+/// [`HEADER_MAX_SIZE`] is rejected.
+fn copy_header(bytes: &mut [u8; HEADER_MAX_SIZE], header: &[u8]) -> Result<(), PluginError> {
+    match header.get(..HEADER_MAX_SIZE) {
+        Some(header) => {
+            bytes.copy_from_slice(header);
+            Ok(())
+        }
+        None => Err(PluginError::InvalidResponse),
+    }
+}
+
+fn read_backup_header(bytes: &mut [u8; HEADER_MAX_SIZE]) -> Result<bool, PluginError> {
+    if !has_backup_header() {
+        return Ok(false);
+    }
+
+    let header = with_connection(|conn| conn.read_backup_header())?;
+
+    if header.is_empty() {
+        Ok(false)
+    } else {
+        copy_header(bytes, &header).map(|()| true)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct PluginSettings(Vec<u8>);
 
@@ -102,7 +170,9 @@ impl fmt::Display for PluginId {
     }
 }
 
-pub struct PluginBackendOpenBuilder;
+pub struct PluginBackendOpenBuilder {
+    read_only: bool,
+}
 
 impl PluginBackendOpenBuilder {
     pub fn new(
@@ -112,7 +182,7 @@ impl PluginBackendOpenBuilder {
     ) -> Result<PluginBackendOpenBuilder, PluginError> {
         setup_connection(plugin.open(name, verbose)?)?;
 
-        Ok(PluginBackendOpenBuilder)
+        Ok(PluginBackendOpenBuilder { read_only: false })
     }
 }
 
@@ -120,15 +190,30 @@ impl ReceiveHeader<PluginBackend> for PluginBackendOpenBuilder {
     fn get_header_bytes(&mut self, bytes: &mut [u8; HEADER_MAX_SIZE]) -> Result<(), PluginError> {
         let header = with_connection(|conn| conn.read_header())?;
 
-        bytes.copy_from_slice(&header[..HEADER_MAX_SIZE]);
+        copy_header(bytes, &header)
+    }
 
-        Ok(())
+    fn get_backup_header_bytes(
+        &mut self,
+        bytes: &mut [u8; HEADER_MAX_SIZE],
+    ) -> Result<bool, PluginError> {
+        read_backup_header(bytes)
     }
 }
 
 impl Open<PluginBackend> for PluginBackendOpenBuilder {
+    fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
     fn build(self, settings: PluginSettings) -> Result<PluginBackend, PluginError> {
-        with_connection(|conn| conn.open(settings.0.clone()))?;
+        // Older plugins are opened in read-write mode, writes are still
+        // rejected by the container.
+        if self.read_only && has_read_only() {
+            with_connection(|conn| conn.open_read_only(settings.0.clone()))?;
+        } else {
+            with_connection(|conn| conn.open(settings.0.clone()))?;
+        }
 
         PluginBackend::new()
     }
@@ -186,9 +271,14 @@ impl ReceiveHeader<PluginBackend> for PluginBackend {
     fn get_header_bytes(&mut self, bytes: &mut [u8; HEADER_MAX_SIZE]) -> Result<(), PluginError> {
         let header = with_connection(|conn| conn.read_header())?;
 
-        bytes.copy_from_slice(&header[..HEADER_MAX_SIZE]);
+        copy_header(bytes, &header)
+    }
 
-        Ok(())
+    fn get_backup_header_bytes(
+        &mut self,
+        bytes: &mut [u8; HEADER_MAX_SIZE],
+    ) -> Result<bool, PluginError> {
+        read_backup_header(bytes)
     }
 }
 
@@ -229,10 +319,82 @@ impl Backend for PluginBackend {
         with_connection(|conn| conn.write(id.0.clone(), buf.to_vec()))
     }
 
+    fn read_many(&mut self, ids: &[PluginId]) -> Result<Vec<Vec<u8>>, PluginError> {
+        if !has_many() {
+            let bsize = self.block_size() as usize;
+
+            return ids
+                .iter()
+                .map(|id| {
+                    let mut buf = vec![0; bsize];
+                    let n = self.read(id, &mut buf)?;
+
+                    buf.truncate(n);
+
+                    Ok(buf)
+                })
+                .collect();
+        }
+
+        let nblocks = ids.len();
+        let ids = ids.iter().map(|id| id.0.clone()).collect();
+        let blocks = with_connection(|conn| conn.read_many(ids))?;
+
+        if blocks.len() == nblocks {
+            Ok(blocks)
+        } else {
+            Err(PluginError::InvalidResponse)
+        }
+    }
+
+    fn write_many(&mut self, blocks: &[(PluginId, &[u8])]) -> Result<Vec<usize>, PluginError> {
+        if !has_many() {
+            return blocks.iter().map(|(id, buf)| self.write(id, buf)).collect();
+        }
+
+        let nblocks = blocks.len();
+        let blocks = blocks
+            .iter()
+            .map(|(id, buf)| (id.0.clone(), buf.to_vec()))
+            .collect();
+        let nbytes = with_connection(|conn| conn.write_many(blocks))?;
+
+        if nbytes.len() == nblocks {
+            Ok(nbytes)
+        } else {
+            Err(PluginError::InvalidResponse)
+        }
+    }
+
     fn write_header(&mut self, buf: &[u8; HEADER_MAX_SIZE]) -> Result<(), PluginError> {
         with_connection(|conn| conn.write_header(buf.to_vec()))
     }
 
+    fn write_backup_header(&mut self, buf: &[u8; HEADER_MAX_SIZE]) -> Result<(), PluginError> {
+        if has_backup_header() {
+            with_connection(|conn| conn.write_backup_header(buf.to_vec()))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), PluginError> {
+        if has_flush() {
+            with_connection(|conn| conn.flush())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn lock(&mut self, mode: LockMode) -> Result<(), PluginError> {
+        // Older plugins cannot be locked.
+        if has_lock() {
+            with_connection(|conn| conn.lock(mode == LockMode::Exclusive))
+        } else {
+            Ok(())
+        }
+    }
+
     fn delete(self) {
         if let Err(err) = with_connection(|conn| conn.delete()) {
             error!("failed to delete backend instance: {}", err);
@@ -20,6 +20,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
+pub mod agent;
 pub mod archive;
 pub mod container;
 pub mod error;
@@ -27,19 +28,25 @@ pub mod global;
 pub mod password;
 pub mod plugin;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use clap::{crate_version, Parser, Subcommand};
 use env_logger::Builder;
-use log::LevelFilter;
-use nuts_container::{Container, OpenOptionsBuilder};
+use log::{debug, warn, LevelFilter};
+use nuts_container::{
+    Container, Error, HeaderError, OpenOptions, OpenOptionsBuilder, PrivateKey, WrappingKey,
+};
 use nuts_tool_api::tool::Plugin;
 use rprompt::prompt_reply;
+use std::fs;
+use std::path::PathBuf;
 
+use crate::agent::{default_socket_path, AgentClient};
 use crate::backend::{PluginBackend, PluginBackendOpenBuilder};
+use crate::cli::agent::AgentArgs;
 use crate::cli::archive::ArchiveArgs;
 use crate::cli::container::ContainerArgs;
 use crate::cli::global::{GlobalArgs, GLOBALS};
-use crate::cli::password::password_from_source;
+use crate::cli::password::{password_from_source, recovery_key_from_source};
 use crate::cli::plugin::PluginArgs;
 use crate::config::{ContainerConfig, PluginConfig};
 
@@ -83,6 +90,9 @@ pub enum Commands {
 
     /// An archive on top of the container
     Archive(ArchiveArgs),
+
+    /// Caches the keys of unlocked containers
+    Agent(AgentArgs),
 }
 
 impl Commands {
@@ -91,11 +101,16 @@ impl Commands {
             Self::Plugin(args) => args.run(),
             Self::Container(args) => args.run(),
             Self::Archive(args) => args.run(),
+            Self::Agent(args) => args.run(),
         }
     }
 }
 
-fn open_container(name: &str) -> Result<Container<PluginBackend>> {
+fn open_builder(
+    name: &str,
+    recovery: bool,
+    wrapping_key: Option<WrappingKey>,
+) -> Result<(PluginBackendOpenBuilder, OpenOptions)> {
     let container_config = ContainerConfig::load()?;
     let plugin_config = PluginConfig::load()?;
     let verbose = GLOBALS.with_borrow(|g| g.verbose);
@@ -108,12 +123,144 @@ fn open_container(name: &str) -> Result<Container<PluginBackend>> {
     let plugin = Plugin::new(&exe);
     let plugin_builder = PluginBackendOpenBuilder::new(plugin, name, verbose)?;
 
-    let builder = OpenOptionsBuilder::new().with_password_callback(password_from_source);
+    let read_only = GLOBALS.with_borrow(|g| g.read_only);
+    let mut builder = OpenOptionsBuilder::new()
+        .with_password_callback(password_from_source)
+        .read_only(read_only);
+
+    if recovery {
+        builder = builder.with_recovery_key_callback(recovery_key_from_source);
+    }
+
+    if let Some(key) = wrapping_key {
+        builder = builder.with_wrapping_key(key);
+    }
+
+    if let Some(path) = GLOBALS.with_borrow(|g| g.private_key.clone()) {
+        debug!("read private key from {}", path.display());
+
+        let pem = fs::read(&path)
+            .with_context(|| format!("failed to read private key from {}", path.display()))?;
+        builder = builder.with_private_key(PrivateKey::from_pem(&pem)?);
+    }
+
     let options = builder.build::<PluginBackend>()?;
 
+    Ok((plugin_builder, options))
+}
+
+fn agent_socket() -> Result<PathBuf> {
+    match GLOBALS.with_borrow(|g| g.agent_socket.clone()) {
+        Some(path) => Ok(path),
+        None => Ok(default_socket_path()?),
+    }
+}
+
+/// Connects to the agent, if it is running.
+///
+/// The agent is optional, errors are logged only.
+fn connect_agent() -> Option<AgentClient> {
+    let path = match agent_socket() {
+        Ok(path) => path,
+        Err(err) => {
+            warn!("failed to locate the agent: {}", err);
+            return None;
+        }
+    };
+
+    AgentClient::connect_if_running(&path).unwrap_or_else(|err| {
+        warn!("failed to connect to the agent: {}", err);
+        None
+    })
+}
+
+/// Removes the wrapping key of the container `name` from the agent.
+fn forget_wrapping_key(name: &str) {
+    if let Some(mut agent) = connect_agent() {
+        match agent.remove(name) {
+            Ok(removed) => debug!("key of {} removed from agent: {}", name, removed),
+            Err(err) => warn!(
+                "failed to remove the key of {} from the agent: {}",
+                name, err
+            ),
+        }
+    }
+}
+
+fn open_container(name: &str) -> Result<Container<PluginBackend>> {
+    let has_private_key = GLOBALS.with_borrow(|g| g.private_key.is_some());
+    let mut agent = if has_private_key {
+        None
+    } else {
+        connect_agent()
+    };
+
+    if let Some(agent) = agent.as_mut() {
+        let key = agent.get(name).unwrap_or_else(|err| {
+            warn!("failed to query the agent: {}", err);
+            None
+        });
+
+        if let Some(key) = key {
+            debug!("unlocking {} with the key from the agent", name);
+
+            let (plugin_builder, options) = open_builder(name, false, Some(key))?;
+
+            match Container::open(plugin_builder, options) {
+                Ok(container) => return Ok(container),
+                Err(Error::Header(HeaderError::WrongWrappingKey)) => {
+                    debug!("the agent has an outdated key of {}", name);
+
+                    if let Err(err) = agent.remove(name) {
+                        warn!(
+                            "failed to remove the key of {} from the agent: {}",
+                            name, err
+                        );
+                    }
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    let (plugin_builder, options) = open_builder(name, false, None)?;
+    let container = Container::open(plugin_builder, options)?;
+
+    // the key is cached only on request, see `--agent`
+    if !GLOBALS.with_borrow(|g| g.agent) {
+        return Ok(container);
+    }
+
+    if let (Some(agent), Some(key)) = (agent.as_mut(), container.wrapping_key()) {
+        if let Err(err) = agent.add(name, &key, None) {
+            warn!("failed to pass the key of {} to the agent: {}", name, err);
+        }
+    }
+
+    Ok(container)
+}
+
+/// Opens the container without asking the agent for the key.
+///
+/// Used by commands, which need the password itself.
+fn open_container_without_agent(name: &str) -> Result<Container<PluginBackend>> {
+    let (plugin_builder, options) = open_builder(name, false, None)?;
+
+    Container::open(plugin_builder, options).map_err(|err| err.into())
+}
+
+fn open_container_with_recovery_key(name: &str) -> Result<Container<PluginBackend>> {
+    let (plugin_builder, options) = open_builder(name, true, None)?;
+
     Container::open(plugin_builder, options).map_err(|err| err.into())
 }
 
+fn restore_container(name: &str, backup: &[u8]) -> Result<Container<PluginBackend>> {
+    let (plugin_builder, options) = open_builder(name, false, None)?;
+
+    Container::restore_header(plugin_builder, options, backup).map_err(|err| err.into())
+}
+
 pub fn prompt_yes_no(prompt: &str, force: bool) -> Result<bool> {
     let ok = force || {
         let msg = format!("{} [yes/NO] ", prompt);
@@ -0,0 +1,81 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+pub mod add;
+pub mod list;
+pub mod lock;
+pub mod start;
+pub mod stop;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use crate::cli::agent::add::AgentAddArgs;
+use crate::cli::agent::list::AgentListArgs;
+use crate::cli::agent::lock::AgentLockArgs;
+use crate::cli::agent::start::AgentStartArgs;
+use crate::cli::agent::stop::AgentStopArgs;
+
+#[derive(Debug, Args)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_required = true)]
+pub struct AgentArgs {
+    #[clap(subcommand)]
+    command: Option<AgentCommand>,
+}
+
+impl AgentArgs {
+    pub fn run(&self) -> Result<()> {
+        self.command
+            .as_ref()
+            .map_or(Ok(()), |command| command.run())
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AgentCommand {
+    /// Starts the agent
+    Start(AgentStartArgs),
+
+    /// Stops the agent
+    Stop(AgentStopArgs),
+
+    /// Unlocks a container and passes its key to the agent
+    Add(AgentAddArgs),
+
+    /// Lists the containers known by the agent
+    List(AgentListArgs),
+
+    /// Removes keys from the agent
+    Lock(AgentLockArgs),
+}
+
+impl AgentCommand {
+    pub fn run(&self) -> Result<()> {
+        match self {
+            Self::Start(args) => args.run(),
+            Self::Stop(args) => args.run(),
+            Self::Add(args) => args.run(),
+            Self::List(args) => args.run(),
+            Self::Lock(args) => args.run(),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use anyhow::{anyhow, Result};
+use clap::{ArgAction, Args};
+use log::debug;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use std::{env, thread};
+
+use crate::agent::{Agent, AgentClient};
+use crate::cli::agent_socket;
+use crate::say;
+
+/// Time to wait for a background agent to come up.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Args, Debug)]
+pub struct AgentStartArgs {
+    /// Keys are removed from the agent after TIME (e.g. 15m, 1h)
+    #[clap(
+        short,
+        long,
+        value_name = "TIME",
+        value_parser = humantime::parse_duration,
+        default_value = "15m"
+    )]
+    timeout: Duration,
+
+    /// Runs the agent in the foreground
+    #[clap(short, long, action = ArgAction::SetTrue)]
+    foreground: bool,
+}
+
+impl AgentStartArgs {
+    pub fn run(&self) -> Result<()> {
+        debug!("args: {:?}", self);
+
+        let path = agent_socket()?;
+
+        if self.foreground {
+            return Ok(Agent::bind(&path, self.timeout)?.run()?);
+        }
+
+        if AgentClient::connect_if_running(&path)?.is_some() {
+            return Err(anyhow!("an agent is already running at {}", path.display()));
+        }
+
+        let timeout = humantime::format_duration(self.timeout).to_string();
+        let mut child = Command::new(env::current_exe()?)
+            .arg("--agent-socket")
+            .arg(&path)
+            .args(["agent", "start", "--foreground", "--timeout", &timeout])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .process_group(0)
+            .spawn()?;
+
+        let started = Instant::now();
+
+        while AgentClient::connect_if_running(&path)?.is_none() {
+            if let Some(status) = child.try_wait()? {
+                return Err(anyhow!("the agent terminated: {}", status));
+            }
+
+            if started.elapsed() > STARTUP_TIMEOUT {
+                return Err(anyhow!("the agent did not start"));
+            }
+
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        say!("agent started, pid {}", child.id());
+
+        Ok(())
+    }
+}
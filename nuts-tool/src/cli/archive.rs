@@ -21,11 +21,16 @@
 // IN THE SOFTWARE.
 
 pub mod add;
+pub mod chmod;
 pub mod create;
+pub mod find;
 pub mod get;
 pub mod info;
 pub mod list;
 pub mod migrate;
+pub mod mv;
+pub mod snapshot;
+pub mod touch;
 
 use anyhow::Result;
 use clap::{Args, Subcommand};
@@ -34,11 +39,16 @@ use nuts_container::Container;
 
 use crate::backend::PluginBackend;
 use crate::cli::archive::add::ArchiveAddArgs;
+use crate::cli::archive::chmod::ArchiveChmodArgs;
 use crate::cli::archive::create::ArchiveCreateArgs;
+use crate::cli::archive::find::ArchiveFindArgs;
 use crate::cli::archive::get::ArchiveGetArgs;
 use crate::cli::archive::info::ArchiveInfoArgs;
 use crate::cli::archive::list::ArchiveListArgs;
 use crate::cli::archive::migrate::ArchiveMigrateArgs;
+use crate::cli::archive::mv::ArchiveMvArgs;
+use crate::cli::archive::snapshot::ArchiveSnapshotArgs;
+use crate::cli::archive::touch::ArchiveTouchArgs;
 use crate::cli::open_container;
 
 #[derive(Debug, Args)]
@@ -61,9 +71,15 @@ pub enum ArchiveCommand {
     /// Adds a new entry at the end of the archive
     Add(ArchiveAddArgs),
 
+    /// Changes the permissions of an entry
+    Chmod(ArchiveChmodArgs),
+
     /// Creates a new archive
     Create(ArchiveCreateArgs),
 
+    /// Searches for entries in the archive
+    Find(ArchiveFindArgs),
+
     /// Retrieve the content of an entry
     Get(ArchiveGetArgs),
 
@@ -75,17 +91,31 @@ pub enum ArchiveCommand {
 
     /// Performs migration tasks
     Migrate(ArchiveMigrateArgs),
+
+    /// Renames an entry
+    Mv(ArchiveMvArgs),
+
+    /// Manages snapshots of the archive
+    Snapshot(ArchiveSnapshotArgs),
+
+    /// Changes the timestamps of an entry
+    Touch(ArchiveTouchArgs),
 }
 
 impl ArchiveCommand {
     pub fn run(&self) -> Result<()> {
         match self {
             Self::Add(args) => args.run(),
+            Self::Chmod(args) => args.run(),
             Self::Create(args) => args.run(),
+            Self::Find(args) => args.run(),
             Self::Get(args) => args.run(),
             Self::Info(args) => args.run(),
             Self::List(args) => args.run(),
             Self::Migrate(args) => args.run(),
+            Self::Mv(args) => args.run(),
+            Self::Snapshot(args) => args.run(),
+            Self::Touch(args) => args.run(),
         }
     }
 }
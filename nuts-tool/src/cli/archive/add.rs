@@ -39,7 +39,7 @@ use crate::cli::archive::add::file::ArchiveAddFileArgs;
 use crate::cli::archive::add::symlink::ArchiveAddSymlinkArgs;
 use crate::cli::archive::open_archive;
 
-const TSTAMP_HELP: &str = "\x1B[1m\x1B[4mTimestamps:\x1B[0m
+pub const TSTAMP_HELP: &str = "\x1B[1m\x1B[4mTimestamps:\x1B[0m
 
 A <TIMESTAMP> argument is of the form \"YYYY-MM-DDThh:mm:ss[tz]\" where the letters represent the following:
 
@@ -69,7 +69,7 @@ fn tstamp_error(cmd: &Command, arg: Option<&Arg>, value: &OsStr) -> clap::Error
 }
 
 #[derive(Clone, Debug)]
-struct Timestamp;
+pub struct Timestamp;
 
 impl TypedValueParser for Timestamp {
     type Value = DateTime<Utc>;
@@ -113,21 +113,21 @@ impl ValueParserFactory for Timestamp {
 }
 
 #[derive(Args, Debug)]
-struct TimestampArgs {
+pub struct TimestampArgs {
     /// Change the creation time to the specified date time instead of the
     /// current time of day
     #[clap(short = 'r', long, value_parser = value_parser!(Timestamp), value_name = "TIMESTAMP")]
-    created: Option<DateTime<Utc>>,
+    pub created: Option<DateTime<Utc>>,
 
     /// Change the changed time to the specified date time instead of the
     /// current time of day
     #[clap(short = 'n', long, value_parser = value_parser!(Timestamp), value_name = "TIMESTAMP")]
-    changed: Option<DateTime<Utc>>,
+    pub changed: Option<DateTime<Utc>>,
 
     /// Change the modified time to the specified date time instead of
     /// he current time of day
     #[clap(short = 'm', long, value_parser = value_parser!(Timestamp), value_name = "TIMESTAMP")]
-    modified: Option<DateTime<Utc>>,
+    pub modified: Option<DateTime<Utc>>,
 }
 
 #[derive(Args, Debug)]
@@ -165,6 +165,8 @@ impl ArchiveAddArgs {
             append_recursive(&mut archive, path)?;
         }
 
+        archive.flush()?;
+
         Ok(())
     }
 }
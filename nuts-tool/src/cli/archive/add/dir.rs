@@ -64,6 +64,8 @@ impl ArchiveAddDirectoryArgs {
             builder.set_modified(modified);
         }
 
-        builder.build().map_err(Into::into)
+        builder.build()?;
+
+        archive.flush().map_err(Into::into)
     }
 }
@@ -80,6 +80,11 @@ impl ArchiveAddFileArgs {
             }
         }
 
+        entry.flush()?;
+        drop(entry);
+
+        archive.flush()?;
+
         Ok(())
     }
 }
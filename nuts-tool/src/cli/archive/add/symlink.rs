@@ -67,6 +67,8 @@ impl ArchiveAddSymlinkArgs {
             builder.set_modified(modified);
         }
 
-        builder.build().map_err(Into::into)
+        builder.build()?;
+
+        archive.flush().map_err(Into::into)
     }
 }
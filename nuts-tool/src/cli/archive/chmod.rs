@@ -0,0 +1,228 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use anyhow::{anyhow, Result};
+use clap::{ArgAction, Args};
+use log::debug;
+use nuts_archive::{EntryHandle, Group};
+use std::str::FromStr;
+
+use crate::backend::PluginBackend;
+use crate::cli::archive::open_archive;
+
+const MODE_HELP: &str = "\x1B[1m\x1B[4mModes:\x1B[0m
+
+The <MODE> is either an octal number or a symbolic mode.
+
+An octal mode consists of three octal digits for the user, group and others. The digits are built from 4 (read), 2 (write) and 1 (execute).
+
+A symbolic mode is a comma separated list of clauses of the form \"[ugoa][+-=][rwx]\":
+
+    \x1B[4mugoa\x1B[0m  Selects user, group, others or all of them. Defaults to all.
+    \x1B[4m+-=\x1B[0m   Adds, removes or sets the permissions.
+    \x1B[4mrwx\x1B[0m   The read, write and execute permission.";
+
+const GROUPS: [Group; 3] = [Group::User, Group::Group, Group::Other];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Add,
+    Remove,
+    Set,
+}
+
+#[derive(Clone, Debug)]
+struct Clause {
+    who: [bool; 3],
+    op: Op,
+    perms: [bool; 3],
+}
+
+impl Clause {
+    fn apply(&self, handle: &mut EntryHandle<PluginBackend>) {
+        for (group, _) in GROUPS.iter().zip(self.who).filter(|(_, who)| *who) {
+            let [read, write, execute] = self.perms;
+
+            match self.op {
+                Op::Add => {
+                    if read {
+                        handle.set_readable(*group, true);
+                    }
+
+                    if write {
+                        handle.set_writable(*group, true);
+                    }
+
+                    if execute {
+                        handle.set_executable(*group, true);
+                    }
+                }
+                Op::Remove => {
+                    if read {
+                        handle.set_readable(*group, false);
+                    }
+
+                    if write {
+                        handle.set_writable(*group, false);
+                    }
+
+                    if execute {
+                        handle.set_executable(*group, false);
+                    }
+                }
+                Op::Set => {
+                    handle.set_readable(*group, read);
+                    handle.set_writable(*group, write);
+                    handle.set_executable(*group, execute);
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Clause {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let idx = s
+            .find(['+', '-', '='])
+            .ok_or_else(|| format!("missing operator in {}", s))?;
+        let (who_str, rest) = s.split_at(idx);
+
+        let mut who = [who_str.is_empty(); 3];
+
+        for c in who_str.chars() {
+            match c {
+                'u' => who[0] = true,
+                'g' => who[1] = true,
+                'o' => who[2] = true,
+                'a' => who = [true; 3],
+                _ => return Err(format!("invalid group {} in {}", c, s)),
+            }
+        }
+
+        let op = match &rest[..1] {
+            "+" => Op::Add,
+            "-" => Op::Remove,
+            _ => Op::Set,
+        };
+
+        let mut perms = [false; 3];
+
+        for c in rest[1..].chars() {
+            match c {
+                'r' => perms[0] = true,
+                'w' => perms[1] = true,
+                'x' => perms[2] = true,
+                _ => return Err(format!("invalid permission {} in {}", c, s)),
+            }
+        }
+
+        Ok(Clause { who, op, perms })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Mode(Vec<Clause>);
+
+impl Mode {
+    fn from_octal(s: &str) -> Result<Mode, String> {
+        let digits = s
+            .chars()
+            .map(|c| c.to_digit(8))
+            .collect::<Option<Vec<u32>>>()
+            .filter(|digits| digits.len() == 3)
+            .ok_or_else(|| format!("invalid octal mode: {}", s))?;
+
+        let clauses = digits
+            .iter()
+            .enumerate()
+            .map(|(idx, digit)| {
+                let mut who = [false; 3];
+
+                who[idx] = true;
+
+                Clause {
+                    who,
+                    op: Op::Set,
+                    perms: [digit & 4 != 0, digit & 2 != 0, digit & 1 != 0],
+                }
+            })
+            .collect();
+
+        Ok(Mode(clauses))
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        if s.starts_with(|c: char| c.is_ascii_digit()) {
+            Self::from_octal(s)
+        } else {
+            s.split(',')
+                .map(|clause| clause.parse())
+                .collect::<Result<Vec<Clause>, String>>()
+                .map(Mode)
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+#[clap(after_help(MODE_HELP))]
+pub struct ArchiveChmodArgs {
+    /// The new mode of the entry
+    mode: Mode,
+
+    /// The name of the entry to be changed
+    name: String,
+
+    /// Starts the migration when the container/archive is opened
+    #[clap(long, action = ArgAction::SetTrue)]
+    pub migrate: bool,
+
+    /// Specifies the name of the container
+    #[clap(short, long, env = "NUTS_CONTAINER")]
+    container: String,
+}
+
+impl ArchiveChmodArgs {
+    pub fn run(&self) -> Result<()> {
+        debug!("args: {:?}", self);
+
+        let mut archive = open_archive(&self.container, self.migrate)?;
+        let mut handle = archive
+            .lookup_mut(&self.name)
+            .ok_or_else(|| anyhow!("no such entry: {}", self.name))??;
+
+        for clause in self.mode.0.iter() {
+            clause.apply(&mut handle);
+        }
+
+        handle.flush()?;
+
+        archive.flush()?;
+
+        Ok(())
+    }
+}
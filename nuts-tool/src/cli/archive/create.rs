@@ -26,10 +26,27 @@ use log::debug;
 use nuts_archive::ArchiveFactory;
 use nuts_container::Container;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use crate::archive::append_recursive;
 use crate::cli::open_container;
 
+#[derive(Clone, Debug)]
+struct Property(String, String);
+
+impl FromStr for Property {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.split_once('=') {
+            Some((key, value)) if !key.is_empty() => {
+                Ok(Property(key.to_string(), value.to_string()))
+            }
+            _ => Err(format!("expected <KEY>=<VALUE>, got {}", s)),
+        }
+    }
+}
+
 #[derive(Args, Debug)]
 pub struct ArchiveCreateArgs {
     /// Path to files/directories to be added to the archive. If PATHS contains
@@ -42,6 +59,11 @@ pub struct ArchiveCreateArgs {
     #[clap(short, long, action = ArgAction::SetTrue)]
     force: bool,
 
+    /// Attaches the property <KEY>=<VALUE> to the archive. Can be specified
+    /// several times.
+    #[clap(short, long, value_name = "KEY=VALUE")]
+    property: Vec<Property>,
+
     /// Specifies the name of the container
     #[clap(short, long, env = "NUTS_CONTAINER")]
     container: String,
@@ -54,10 +76,16 @@ impl ArchiveCreateArgs {
         let container = open_container(&self.container)?;
         let mut archive = Container::create_service::<ArchiveFactory>(container)?;
 
+        for Property(key, value) in self.property.iter() {
+            archive.set_property(key, value)?;
+        }
+
         for path in self.paths.iter() {
             append_recursive(&mut archive, path)?;
         }
 
+        archive.flush()?;
+
         Ok(())
     }
 }
@@ -0,0 +1,229 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::{value_parser, ArgAction, Args, ValueEnum};
+use log::debug;
+use nuts_archive::{EntryType, Query};
+use std::cmp::Ordering;
+use std::ops::Bound;
+use std::str::FromStr;
+
+use crate::cli::archive::add::{Timestamp, TSTAMP_HELP};
+use crate::cli::archive::open_archive;
+use crate::say;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum FindType {
+    /// A file entry
+    #[value(alias = "f")]
+    File,
+
+    /// A directory entry
+    #[value(alias = "d")]
+    Directory,
+
+    /// A symlink entry
+    #[value(alias = "l")]
+    Symlink,
+}
+
+impl From<FindType> for EntryType {
+    fn from(value: FindType) -> Self {
+        match value {
+            FindType::File => EntryType::File,
+            FindType::Directory => EntryType::Directory,
+            FindType::Symlink => EntryType::Symlink,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum FindTime {
+    /// Time when the entry was appended to the archive
+    Appended,
+
+    /// Creation time of the entry
+    Created,
+
+    /// Changed time of the entry
+    Changed,
+
+    /// Modification time of the entry
+    Modified,
+}
+
+#[derive(Clone, Debug)]
+struct Size(Ordering, u64);
+
+impl FromStr for Size {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (ordering, s) = match s.strip_prefix('+') {
+            Some(s) => (Ordering::Greater, s),
+            None => match s.strip_prefix('-') {
+                Some(s) => (Ordering::Less, s),
+                None => (Ordering::Equal, s),
+            },
+        };
+
+        let (s, factor) = match s.chars().last() {
+            Some('k') => (&s[..s.len() - 1], 1024),
+            Some('M') => (&s[..s.len() - 1], 1024 * 1024),
+            Some('G') => (&s[..s.len() - 1], 1024 * 1024 * 1024),
+            _ => (s, 1),
+        };
+
+        let size = s
+            .parse::<u64>()
+            .ok()
+            .and_then(|n| n.checked_mul(factor))
+            .ok_or_else(|| format!("invalid size: {}", s))?;
+
+        Ok(Size(ordering, size))
+    }
+}
+
+const FIND_HELP: &str = "\x1B[1m\x1B[4mSizes:\x1B[0m
+
+A <SIZE> argument is of the form \"[+-]N[kMG]\". With a leading + the size of the entry must be greater than N, with a leading - it must be less than N. Otherwise the size must be exactly N. The suffixes k, M and G multiply N by 1024, 1024^2 and 1024^3.";
+
+#[derive(Args, Debug)]
+#[clap(after_help(format!("{}\n\n{}", FIND_HELP, TSTAMP_HELP)))]
+pub struct ArchiveFindArgs {
+    /// Selects entries, which name matches the glob <PATTERN>. Can be
+    /// specified several times, an entry must match at least one pattern.
+    #[clap(long, value_name = "PATTERN")]
+    name: Vec<String>,
+
+    /// Selects entries of the given type. Can be specified several times, an
+    /// entry must match at least one type.
+    #[clap(long = "type", value_name = "TYPE")]
+    types: Vec<FindType>,
+
+    /// Selects entries by their size. Can be specified several times, an
+    /// entry must match all sizes.
+    #[clap(long, value_name = "SIZE", allow_hyphen_values = true)]
+    size: Vec<Size>,
+
+    /// Selects entries, which are newer than <TIMESTAMP>
+    #[clap(long, value_parser = value_parser!(Timestamp), value_name = "TIMESTAMP")]
+    newer: Option<DateTime<Utc>>,
+
+    /// Selects entries, which are older than <TIMESTAMP>
+    #[clap(long, value_parser = value_parser!(Timestamp), value_name = "TIMESTAMP")]
+    older: Option<DateTime<Utc>>,
+
+    /// The timestamp of the entry compared by --newer and --older
+    #[clap(long, value_name = "TIME", default_value = "modified")]
+    time: FindTime,
+
+    /// Searches the snapshot with the given name
+    #[clap(short, long, value_name = "NAME")]
+    snapshot: Option<String>,
+
+    /// Starts the migration when the container/archive is opened
+    #[clap(long, action = ArgAction::SetTrue)]
+    pub migrate: bool,
+
+    /// Specifies the name of the container
+    #[clap(short, long, env = "NUTS_CONTAINER")]
+    container: String,
+}
+
+impl ArchiveFindArgs {
+    fn build_query(&self) -> Query {
+        let mut query = Query::new();
+
+        for name in self.name.iter() {
+            query = query.with_name(name);
+        }
+
+        for t in self.types.iter() {
+            query = query.with_type((*t).into());
+        }
+
+        if !self.size.is_empty() {
+            let (mut min, mut max) = (0, u64::MAX);
+
+            for Size(ordering, n) in self.size.iter() {
+                match ordering {
+                    Ordering::Greater => min = min.max(n.saturating_add(1)),
+                    Ordering::Less => match n.checked_sub(1) {
+                        Some(n) => max = max.min(n),
+                        None => (min, max) = (1, 0),
+                    },
+                    Ordering::Equal => (min, max) = (min.max(*n), max.min(*n)),
+                }
+            }
+
+            query = query.with_size(min..=max);
+        }
+
+        if self.newer.is_some() || self.older.is_some() {
+            let range = (
+                self.newer.map_or(Bound::Unbounded, Bound::Excluded),
+                self.older.map_or(Bound::Unbounded, Bound::Excluded),
+            );
+
+            query = match self.time {
+                FindTime::Appended => query.with_appended(range),
+                FindTime::Created => query.with_created(range),
+                FindTime::Changed => query.with_changed(range),
+                FindTime::Modified => query.with_modified(range),
+            };
+        }
+
+        query
+    }
+
+    pub fn run(&self) -> Result<()> {
+        debug!("args: {:?}", self);
+
+        let query = self.build_query();
+        let mut archive = open_archive(&self.container, self.migrate)?;
+
+        let mut snapshot;
+        let mut entry_opt = match self.snapshot.as_ref() {
+            Some(name) => {
+                snapshot = archive.open_snapshot(name)?;
+                snapshot.find(&query)
+            }
+            None => archive.find(&query),
+        };
+
+        loop {
+            match entry_opt {
+                Some(Ok(entry)) => {
+                    say!("{}", entry.name());
+                    entry_opt = entry.find_next(&query);
+                }
+                Some(Err(err)) => return Err(err.into()),
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -37,6 +37,10 @@ pub struct ArchiveGetArgs {
     #[clap(short, long, value_parser, default_value = "raw")]
     format: Format,
 
+    /// Reads the entry from the snapshot with the given name
+    #[clap(short, long, value_name = "NAME")]
+    snapshot: Option<String>,
+
     /// Starts the migration when the container/archive is opened
     #[clap(long, action = ArgAction::SetTrue)]
     pub migrate: bool,
@@ -53,7 +57,16 @@ impl ArchiveGetArgs {
         let mut archive = open_archive(&self.container, self.migrate)?;
         let block_size = archive.as_ref().block_size() as usize;
 
-        let entry = match archive.lookup(&self.name) {
+        let mut snapshot;
+        let entry_opt = match self.snapshot.as_ref() {
+            Some(name) => {
+                snapshot = archive.open_snapshot(name)?;
+                snapshot.lookup(&self.name)
+            }
+            None => archive.lookup(&self.name),
+        };
+
+        let entry = match entry_opt {
             Some(Ok(e)) => e,
             Some(Err(err)) => return Err(err.into()),
             None => return Err(anyhow!("no such entry: {}", self.name)),
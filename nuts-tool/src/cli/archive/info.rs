@@ -64,6 +64,10 @@ impl ArchiveInfoArgs {
         say!("blocks:   {}", info.blocks);
         say!("files:    {}", info.files);
 
+        for (key, value) in archive.properties() {
+            say!("property.{}: {}", key, value);
+        }
+
         Ok(())
     }
 }
@@ -125,6 +125,10 @@ pub struct ArchiveListArgs {
     )]
     time_format: TimeFormat,
 
+    /// Lists the content of the snapshot with the given name
+    #[clap(short, long, value_name = "NAME")]
+    snapshot: Option<String>,
+
     /// Starts the migration when the container/archive is opened
     #[clap(long, action = ArgAction::SetTrue)]
     pub migrate: bool,
@@ -172,7 +176,14 @@ impl ArchiveListArgs {
 
         let mut archive = open_archive(&self.container, self.migrate)?;
 
-        let mut entry_opt = archive.first();
+        let mut snapshot;
+        let mut entry_opt = match self.snapshot.as_ref() {
+            Some(name) => {
+                snapshot = archive.open_snapshot(name)?;
+                snapshot.first()
+            }
+            None => archive.first(),
+        };
         let mut ctx_opt = None;
 
         loop {
@@ -55,14 +55,13 @@ impl ArchiveMigrateArgs {
         let archive = open_archive(&self.container, false)?;
         let info = archive.as_ref().info()?;
 
-        match info.revision.cmp(&LATEST_REVISION) {
+        let migrate_container = match info.revision.cmp(&LATEST_REVISION) {
             Ordering::Equal => {
                 say!(
                     "container revision: {}, no migration necessary",
                     info.revision
                 );
-
-                Ok(())
+                false
             }
             Ordering::Less => {
                 say!(
@@ -70,21 +69,39 @@ impl ArchiveMigrateArgs {
                     info.revision,
                     LATEST_REVISION
                 );
-
-                if self.verify {
-                    Err(ExitOnly::new(1).into())
-                } else if prompt_yes_no("Do you really want to start the migration?", self.yes)? {
-                    open_archive(&self.container, true).map(|_| ())
-                } else {
-                    say!("aborted");
-                    Ok(())
-                }
+                true
+            }
+            Ordering::Greater => {
+                return Err(anyhow!(
+                    "invalid container revision {}, cannot be greater than {}",
+                    info.revision,
+                    LATEST_REVISION
+                ))
             }
-            Ordering::Greater => Err(anyhow!(
-                "invalid container revision {}, cannot be greater than {}",
-                info.revision,
-                LATEST_REVISION
-            )),
+        };
+
+        let migrate_archive = archive.needs_migration();
+
+        if migrate_archive {
+            say!("archive layout: outdated, migration required");
+        } else {
+            say!("archive layout: current, no migration necessary");
+        }
+
+        if !migrate_container && !migrate_archive {
+            Ok(())
+        } else if self.verify {
+            Err(ExitOnly::new(1).into())
+        } else if prompt_yes_no("Do you really want to start the migration?", self.yes)? {
+            drop(archive);
+
+            let mut archive = open_archive(&self.container, migrate_container)?;
+
+            archive.migrate()?;
+            archive.flush().map_err(|err| err.into())
+        } else {
+            say!("aborted");
+            Ok(())
         }
     }
 }
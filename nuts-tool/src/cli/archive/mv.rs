@@ -0,0 +1,67 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use anyhow::{anyhow, bail, Result};
+use clap::{ArgAction, Args};
+use log::debug;
+
+use crate::cli::archive::open_archive;
+
+#[derive(Args, Debug)]
+pub struct ArchiveMvArgs {
+    /// The name of the entry to be renamed
+    source: String,
+
+    /// The new name of the entry
+    target: String,
+
+    /// Starts the migration when the container/archive is opened
+    #[clap(long, action = ArgAction::SetTrue)]
+    pub migrate: bool,
+
+    /// Specifies the name of the container
+    #[clap(short, long, env = "NUTS_CONTAINER")]
+    container: String,
+}
+
+impl ArchiveMvArgs {
+    pub fn run(&self) -> Result<()> {
+        debug!("args: {:?}", self);
+
+        let mut archive = open_archive(&self.container, self.migrate)?;
+
+        if archive.lookup(&self.target).transpose()?.is_some() {
+            bail!("the entry {} already exists", self.target);
+        }
+
+        let mut handle = archive
+            .lookup_mut(&self.source)
+            .ok_or_else(|| anyhow!("no such entry: {}", self.source))??;
+
+        handle.set_name(&self.target);
+        handle.flush()?;
+
+        archive.flush()?;
+
+        Ok(())
+    }
+}
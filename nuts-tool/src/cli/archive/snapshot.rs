@@ -0,0 +1,67 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+pub mod create;
+pub mod list;
+pub mod remove;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use crate::cli::archive::snapshot::create::ArchiveSnapshotCreateArgs;
+use crate::cli::archive::snapshot::list::ArchiveSnapshotListArgs;
+use crate::cli::archive::snapshot::remove::ArchiveSnapshotRemoveArgs;
+
+#[derive(Args, Debug)]
+pub struct ArchiveSnapshotArgs {
+    #[clap(subcommand)]
+    command: ArchiveSnapshotCommand,
+}
+
+impl ArchiveSnapshotArgs {
+    pub fn run(&self) -> Result<()> {
+        self.command.run()
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ArchiveSnapshotCommand {
+    /// Takes a new snapshot of the archive
+    Create(ArchiveSnapshotCreateArgs),
+
+    /// Lists all snapshots of the archive
+    List(ArchiveSnapshotListArgs),
+
+    /// Removes a snapshot from the archive
+    #[clap(name = "rm", alias = "remove")]
+    Remove(ArchiveSnapshotRemoveArgs),
+}
+
+impl ArchiveSnapshotCommand {
+    pub fn run(&self) -> Result<()> {
+        match self {
+            Self::Create(args) => args.run(),
+            Self::List(args) => args.run(),
+            Self::Remove(args) => args.run(),
+        }
+    }
+}
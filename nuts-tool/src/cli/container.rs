@@ -25,27 +25,33 @@ pub mod attach;
 pub mod change;
 pub mod create;
 pub mod delete;
+pub mod header;
 pub mod info;
 pub mod list;
 pub mod read;
 pub mod release;
+pub mod repair;
 pub mod write;
 
 use anyhow::Result;
 use clap::builder::PossibleValue;
 use clap::{Args, Subcommand, ValueEnum};
-use nuts_container::Cipher;
+use log::debug;
+use nuts_container::{Cipher, Digest, Kdf};
 use std::ops::Deref;
+use std::time::Duration;
 
 use crate::cli::container::aquire::ContainerAquireArgs;
 use crate::cli::container::attach::ContainerAttachArgs;
 use crate::cli::container::change::ContainerChangeArgs;
 use crate::cli::container::create::ContainerCreateArgs;
 use crate::cli::container::delete::ContainerDeleteArgs;
+use crate::cli::container::header::ContainerHeaderArgs;
 use crate::cli::container::info::ContainerInfoArgs;
 use crate::cli::container::list::ContainerListArgs;
 use crate::cli::container::read::ContainerReadArgs;
 use crate::cli::container::release::ContainerReleaseArgs;
+use crate::cli::container::repair::ContainerRepairArgs;
 use crate::cli::container::write::ContainerWriteArgs;
 
 const AES128_GCM: &str = "aes128-gcm";
@@ -54,6 +60,9 @@ const AES256_GCM: &str = "aes256-gcm";
 const AES128_CTR: &str = "aes128-ctr";
 const AES192_CTR: &str = "aes192-ctr";
 const AES256_CTR: &str = "aes256-ctr";
+const AES128_CTR_HMAC: &str = "aes128-ctr-hmac";
+const AES192_CTR_HMAC: &str = "aes192-ctr-hmac";
+const AES256_CTR_HMAC: &str = "aes256-ctr-hmac";
 const NONE: &str = "none";
 
 #[derive(Clone, Debug)]
@@ -82,6 +91,9 @@ impl ValueEnum for CliCipher {
             CliCipher(Cipher::Aes192Ctr),
             CliCipher(Cipher::Aes256Ctr),
             CliCipher(Cipher::Aes128Ctr),
+            CliCipher(Cipher::Aes128CtrHmac),
+            CliCipher(Cipher::Aes192CtrHmac),
+            CliCipher(Cipher::Aes256CtrHmac),
             CliCipher(Cipher::None),
         ]
     }
@@ -95,12 +107,34 @@ impl ValueEnum for CliCipher {
             Cipher::Aes128Gcm => AES128_GCM,
             Cipher::Aes192Gcm => AES192_GCM,
             Cipher::Aes256Gcm => AES256_GCM,
+            Cipher::Aes128CtrHmac => AES128_CTR_HMAC,
+            Cipher::Aes192CtrHmac => AES192_CTR_HMAC,
+            Cipher::Aes256CtrHmac => AES256_CTR_HMAC,
         };
 
         Some(PossibleValue::new(value))
     }
 }
 
+/// Calibrates the iterations of `kdf`, so that unlocking takes about `time`.
+///
+/// Digest and salt are taken from `kdf`. Without a `kdf` PBKDF2 with SHA256
+/// is calibrated.
+fn calibrate_kdf(kdf: Option<&Kdf>, time: Duration) -> Result<Kdf> {
+    let kdf = match kdf {
+        None => Kdf::calibrate(Digest::Sha256, time)?,
+        Some(Kdf::None) => Kdf::None,
+        Some(Kdf::Pbkdf2 { digest, salt, .. }) => match Kdf::calibrate(*digest, time)? {
+            Kdf::Pbkdf2 { iterations, .. } => Kdf::pbkdf2(*digest, iterations, salt),
+            Kdf::None => Kdf::None,
+        },
+    };
+
+    debug!("calibrated kdf: {:?}", kdf);
+
+    Ok(kdf)
+}
+
 #[derive(Debug, Args)]
 #[clap(args_conflicts_with_subcommands = true, subcommand_required = true)]
 pub struct ContainerArgs {
@@ -133,6 +167,9 @@ pub enum ContainerCommand {
     /// Removes a container again
     Delete(ContainerDeleteArgs),
 
+    /// Backup and restore of the container header
+    Header(ContainerHeaderArgs),
+
     /// Prints general information about the container
     Info(ContainerInfoArgs),
 
@@ -145,6 +182,9 @@ pub enum ContainerCommand {
     /// Releases a block again
     Release(ContainerReleaseArgs),
 
+    /// Repairs the rollback protection after an interrupted write
+    Repair(ContainerRepairArgs),
+
     /// Writes a block into the container
     Write(ContainerWriteArgs),
 }
@@ -157,10 +197,12 @@ impl ContainerCommand {
             Self::Change(args) => args.run(),
             Self::Create(args) => args.run(),
             Self::Delete(args) => args.run(),
+            Self::Header(args) => args.run(),
             Self::Info(args) => args.run(),
             Self::List(args) => args.run(),
             Self::Read(args) => args.run(),
             Self::Release(args) => args.run(),
+            Self::Repair(args) => args.run(),
             Self::Write(args) => args.run(),
         }
     }
@@ -40,6 +40,7 @@ impl ContainerAquireArgs {
 
         let mut container = open_container(&self.container)?;
         let id = container.aquire()?;
+        container.flush()?;
 
         say!("aquired: {}", id);
 
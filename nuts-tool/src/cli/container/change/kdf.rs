@@ -24,8 +24,10 @@ use anyhow::Result;
 use clap::Args;
 use log::debug;
 use nuts_container::{Kdf, ModifyOptionsBuilder};
+use std::time::Duration;
 
-use crate::cli::open_container;
+use crate::cli::container::calibrate_kdf;
+use crate::cli::{forget_wrapping_key, open_container_without_agent};
 
 #[derive(Args, Debug)]
 pub struct ContainerChangeKdfArgs {
@@ -44,7 +46,14 @@ pub struct ContainerChangeKdfArgs {
     /// Selects PBKDF2 with the given digest (default: sha256),
     /// the given number of iterations (default: 65536) and salt
     /// length (default: 16).
-    kdf: Kdf,
+    #[clap(required_unless_present = "kdf_time")]
+    kdf: Option<Kdf>,
+
+    /// Calibrates the key derivation function for the local machine, so that
+    /// unlocking the container takes about TIME (e.g. 1s, 500ms). Replaces
+    /// the iterations of KDF.
+    #[clap(long, value_name = "TIME", value_parser = humantime::parse_duration)]
+    kdf_time: Option<Duration>,
 
     /// Specifies the name of the container
     #[clap(short, long, env = "NUTS_CONTAINER")]
@@ -55,12 +64,18 @@ impl ContainerChangeKdfArgs {
     pub fn run(&self) -> Result<()> {
         debug!("args: {:?}", self);
 
-        let mut container = open_container(&self.container)?;
-        let options = ModifyOptionsBuilder::default()
-            .change_kdf(self.kdf.clone())
-            .build();
+        let kdf = match (self.kdf.as_ref(), self.kdf_time) {
+            (kdf, Some(time)) => calibrate_kdf(kdf, time)?,
+            (Some(kdf), None) => kdf.clone(),
+            (None, None) => unreachable!("KDF is required without --kdf-time"),
+        };
+
+        // the password is needed to derive the new wrapping key
+        let mut container = open_container_without_agent(&self.container)?;
+        let options = ModifyOptionsBuilder::default().change_kdf(kdf).build();
 
         container.modify(options)?;
+        forget_wrapping_key(&self.container);
 
         Ok(())
     }
@@ -21,7 +21,7 @@
 // IN THE SOFTWARE.
 
 use anyhow::Result;
-use clap::{ArgGroup, Args};
+use clap::{ArgAction, ArgGroup, Args};
 use log::debug;
 use nuts_container::ModifyOptionsBuilder;
 use std::cell::RefCell;
@@ -29,8 +29,8 @@ use std::os::fd::RawFd;
 use std::path::PathBuf;
 
 use crate::cli::global::PasswordSource;
-use crate::cli::open_container;
 use crate::cli::password::password_from_source_twice;
+use crate::cli::{forget_wrapping_key, open_container, open_container_with_recovery_key};
 
 thread_local! {
     static SOURCE: RefCell<PasswordSource> = RefCell::new(Default::default());
@@ -56,6 +56,12 @@ pub struct ContainerChangePasswordArgs {
     /// the first line until a `\n` is read.
     #[clap(long, group = "new_password", value_name = "PATH")]
     new_password_from_file: Option<PathBuf>,
+
+    /// Unlocks the container with the recovery key instead of the old
+    /// password. The recovery key is read from the same source as the
+    /// password.
+    #[clap(long, action = ArgAction::SetTrue)]
+    recovery: bool,
 }
 
 impl ContainerChangePasswordArgs {
@@ -69,12 +75,17 @@ impl ContainerChangePasswordArgs {
             )
         });
 
-        let mut container = open_container(&self.container)?;
+        let mut container = if self.recovery {
+            open_container_with_recovery_key(&self.container)?
+        } else {
+            open_container(&self.container)?
+        };
         let options = ModifyOptionsBuilder::default()
             .change_password(password_callback)
             .build();
 
         container.modify(options)?;
+        forget_wrapping_key(&self.container);
 
         Ok(())
     }
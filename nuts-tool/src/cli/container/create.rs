@@ -20,20 +20,23 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
-use anyhow::{ensure, Result};
+use anyhow::{ensure, Context, Result};
 use clap::{value_parser, ArgAction, Args};
 use log::debug;
-use nuts_container::{Cipher, Container, CreateOptionsBuilder, Kdf};
+use nuts_container::{Cipher, Container, CreateOptionsBuilder, Kdf, PublicKey};
 use nuts_tool_api::tool::Plugin;
 use std::cell::RefCell;
+use std::fs;
 use std::os::fd::RawFd;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use crate::backend::{PluginBackend, PluginBackendCreateBuilder};
-use crate::cli::container::{CliCipher, AES256_GCM};
+use crate::cli::container::{calibrate_kdf, CliCipher, AES256_GCM};
 use crate::cli::global::PasswordSource;
 use crate::cli::password::password_from_source_twice;
 use crate::config::{ContainerConfig, PluginConfig};
+use crate::say;
 
 thread_local! {
     static SOURCE: RefCell<PasswordSource> = RefCell::new(Default::default());
@@ -74,10 +77,37 @@ pub struct ContainerCreateArgs {
     #[clap(short, long, value_parser)]
     kdf: Option<Kdf>,
 
+    /// Calibrates the key derivation function for the local machine, so that
+    /// unlocking the container takes about TIME (e.g. 1s, 500ms). Replaces
+    /// the iterations of --kdf.
+    #[clap(long, value_name = "TIME", value_parser = humantime::parse_duration)]
+    kdf_time: Option<Duration>,
+
     /// If set, overwrites an existing container
     #[clap(short, long, action = ArgAction::SetTrue)]
     overwrite: bool,
 
+    /// If set, protects the container against the rollback of single blocks
+    #[clap(long, action = ArgAction::SetTrue)]
+    rollback_protection: bool,
+
+    /// If set, generates a recovery key, which unlocks the container if the
+    /// password is lost. The recovery key is printed only once, keep it at a
+    /// safe place!
+    #[clap(long, action = ArgAction::SetTrue)]
+    recovery_key: bool,
+
+    /// Assigns the public key stored in the file <PATH> as a recipient. The
+    /// container can be unlocked with the matching private key (see
+    /// `--private-key`).
+    #[clap(long, value_name = "PATH")]
+    recipient: Option<PathBuf>,
+
+    /// If set, no password is assigned to the container. The container can
+    /// only be unlocked with the private key of the recipient.
+    #[clap(long, action = ArgAction::SetTrue, requires = "recipient")]
+    no_password: bool,
+
     /// Arguments passed to the plugin
     #[clap(value_name = "PLUGIN ARGS")]
     plugin_args: Vec<String>,
@@ -116,21 +146,41 @@ impl ContainerCreateArgs {
         let backend_options =
             PluginBackendCreateBuilder::new(plugin, &self.name, self.verbose, &self.plugin_args)?;
         let mut builder = CreateOptionsBuilder::new(*self.cipher)
-            .with_password_callback(password_callback)
-            .with_overwrite(self.overwrite);
+            .with_overwrite(self.overwrite)
+            .with_rollback_protection(self.rollback_protection)
+            .with_recovery_key(self.recovery_key);
+
+        if !self.no_password {
+            builder = builder.with_password_callback(password_callback);
+        }
+
+        if let Some(path) = self.recipient.as_ref() {
+            let pem = fs::read(path)
+                .with_context(|| format!("failed to read public key from {}", path.display()))?;
+            builder = builder.with_recipient(PublicKey::from_pem(&pem)?);
+        }
 
         if self.cipher != Cipher::None {
-            if let Some(kdf) = self.kdf.clone() {
+            let kdf = match self.kdf_time {
+                Some(time) => Some(calibrate_kdf(self.kdf.as_ref(), time)?),
+                None => self.kdf.clone(),
+            };
+
+            if let Some(kdf) = kdf {
                 debug!("kdf: {:?}", kdf);
                 builder = builder.with_kdf(kdf);
             }
         }
 
         let options = builder.build::<PluginBackend>()?;
-        Container::<PluginBackend>::create(backend_options, options)?;
+        let container = Container::<PluginBackend>::create(backend_options, options)?;
 
         container_config.save()?;
 
+        if let Some(recovery_key) = container.recovery_key() {
+            say!("recovery key: {}", recovery_key);
+        }
+
         Ok(())
     }
 }
@@ -26,7 +26,7 @@ use log::debug;
 use nuts_tool_api::container_dir_for;
 use std::fs;
 
-use crate::cli::{open_container, prompt_yes_no};
+use crate::cli::{forget_wrapping_key, open_container, prompt_yes_no};
 use crate::config::ContainerConfig;
 use crate::{say, say_warn};
 
@@ -75,6 +75,7 @@ impl ContainerDeleteArgs {
         }
 
         container_config.save()?;
+        forget_wrapping_key(&self.container);
 
         Ok(())
     }
@@ -0,0 +1,60 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+pub mod backup;
+pub mod restore;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use crate::cli::container::header::backup::ContainerHeaderBackupArgs;
+use crate::cli::container::header::restore::ContainerHeaderRestoreArgs;
+
+#[derive(Args, Debug)]
+pub struct ContainerHeaderArgs {
+    #[clap(subcommand)]
+    command: ContainerHeaderCommand,
+}
+
+impl ContainerHeaderArgs {
+    pub fn run(&self) -> Result<()> {
+        self.command.run()
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ContainerHeaderCommand {
+    /// Writes a backup of the container header into a file
+    Backup(ContainerHeaderBackupArgs),
+
+    /// Restores the container header from a backup
+    Restore(ContainerHeaderRestoreArgs),
+}
+
+impl ContainerHeaderCommand {
+    pub fn run(&self) -> Result<()> {
+        match self {
+            Self::Backup(args) => args.run(),
+            Self::Restore(args) => args.run(),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use anyhow::Result;
+use clap::{ArgAction, Args};
+use log::debug;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cli::{prompt_yes_no, restore_container};
+use crate::say;
+
+#[derive(Args, Debug)]
+pub struct ContainerHeaderRestoreArgs {
+    /// Path of the backup file
+    file: PathBuf,
+
+    /// Specifies the name of the container
+    #[clap(short, long, env = "NUTS_CONTAINER")]
+    container: String,
+
+    /// Say yes, don't prompt for restoring
+    #[clap(short, long, action = ArgAction::SetTrue)]
+    yes: bool,
+}
+
+impl ContainerHeaderRestoreArgs {
+    pub fn run(&self) -> Result<()> {
+        debug!("args: {:?}", self);
+
+        let backup = fs::read(&self.file)?;
+
+        if !prompt_yes_no(
+            "Do you really want to overwrite the header of the container?",
+            self.yes,
+        )? {
+            say!("aborted");
+            return Ok(());
+        }
+
+        restore_container(&self.container, &backup)?;
+
+        Ok(())
+    }
+}
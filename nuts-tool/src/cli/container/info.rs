@@ -23,13 +23,32 @@
 use anyhow::Result;
 use clap::Args;
 use log::debug;
+use nuts_archive::Archive;
+use nuts_container::Service;
 use std::cmp;
 
+use crate::backend::PluginBackend;
 use crate::cli::open_container;
 use crate::config::ContainerConfig;
 use crate::format::Format;
 use crate::say;
 
+fn service_name(sid: u32) -> String {
+    if sid == <Archive<PluginBackend> as Service<PluginBackend>>::sid() {
+        "archive".to_string()
+    } else {
+        sid.to_string()
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
 #[derive(Args, Debug)]
 pub struct ContainerInfoArgs {
     /// Specifies the format of the userdata dump
@@ -50,7 +69,7 @@ impl ContainerInfoArgs {
         let plugin = container_config.get_plugin(&self.container).unwrap_or("?");
         let info = container.info()?;
 
-        let key_width = 19;
+        let key_width = 20;
         let key_width = info
             .backend
             .iter()
@@ -60,8 +79,27 @@ impl ContainerInfoArgs {
         say!("{:<key_width$} {}", "revision:", info.revision);
         say!("{:<key_width$} {}", "cipher:", info.cipher);
         say!("{:<key_width$} {}", "kdf:", info.kdf.to_string());
+
+        if info.services.is_empty() {
+            say!("{:<key_width$} none", "services:");
+        } else {
+            let services: Vec<String> = info.services.iter().copied().map(service_name).collect();
+            say!("{:<key_width$} {}", "services:", services.join(", "));
+        }
+
         say!("{:<key_width$} {}", "block size (gross):", info.bsize_gross);
         say!("{:<key_width$} {}", "block size (net):", info.bsize_net);
+        say!(
+            "{:<key_width$} {}",
+            "rollback protection:",
+            yes_no(info.rollback_protection)
+        );
+        say!(
+            "{:<key_width$} {}",
+            "recovery key:",
+            yes_no(info.recovery_key)
+        );
+        say!("{:<key_width$} {}", "recipient:", yes_no(info.recipient));
 
         say!("");
 
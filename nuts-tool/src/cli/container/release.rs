@@ -44,6 +44,7 @@ impl ContainerReleaseArgs {
         let id = self.id.parse()?;
 
         container.release(id)?;
+        container.flush()?;
 
         Ok(())
     }
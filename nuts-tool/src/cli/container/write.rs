@@ -90,6 +90,7 @@ impl ContainerWriteArgs {
         debug!("{} bytes read from stdin", n);
 
         container.write(&id, &buf[..n])?;
+        container.flush()?;
 
         println!("{} bytes written into {}", n, id);
         Ok(())
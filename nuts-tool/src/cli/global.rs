@@ -37,6 +37,10 @@ pub struct GlobalValues {
     pub verbose: u8,
     pub say: Say,
     pub password_source: PasswordSource,
+    pub private_key: Option<PathBuf>,
+    pub agent_socket: Option<PathBuf>,
+    pub agent: bool,
+    pub read_only: bool,
 }
 
 impl GlobalValues {
@@ -98,6 +102,27 @@ pub struct GlobalArgs {
     /// first line until a `\n` is read.
     #[clap(long, group = "password", global = true, value_name = "PATH")]
     pub password_from_file: Option<PathBuf>,
+
+    /// Unlocks the container with the private key stored in the specified
+    /// file <PATH> instead of a password. The container must be created for
+    /// the matching public key (see `container create --recipient`).
+    #[clap(long, global = true, value_name = "PATH")]
+    pub private_key: Option<PathBuf>,
+
+    /// The socket of the agent, which caches the keys of unlocked
+    /// containers. Defaults to `~/.nuts/agent/agent.sock`.
+    #[clap(long, global = true, env = "NUTS_AGENT_SOCK", value_name = "PATH")]
+    pub agent_socket: Option<PathBuf>,
+
+    /// Passes the key of a container, which was unlocked with a password, to
+    /// the agent. Without this flag keys are cached only with `agent add`.
+    #[clap(long, action = ArgAction::SetTrue, global = true)]
+    pub agent: bool,
+
+    /// Opens the container read-only. Any modification of the container is
+    /// rejected.
+    #[clap(long, action = ArgAction::SetTrue, global = true)]
+    pub read_only: bool,
 }
 
 impl GlobalArgs {
@@ -106,6 +131,10 @@ impl GlobalArgs {
             g.verbose = self.verbose;
             g.say.set_quiet(self.quiet);
             g.init_password_source(self);
+            g.private_key = self.private_key.clone();
+            g.agent_socket = self.agent_socket.clone();
+            g.agent = self.agent;
+            g.read_only = self.read_only;
         });
     }
 }
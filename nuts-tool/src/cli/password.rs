@@ -87,6 +87,16 @@ pub fn password_from_source() -> Result<Vec<u8>, String> {
     GLOBALS.with_borrow(|g| password_from_source_or(&g.password_source, ask_for_password))
 }
 
+pub fn recovery_key_from_source() -> Result<Vec<u8>, String> {
+    GLOBALS.with_borrow(|g| {
+        password_from_source_or(&g.password_source, || {
+            prompt_password("Enter the recovery key: ")
+                .map(|s| s.into_bytes())
+                .map_err(|err| err.to_string())
+        })
+    })
+}
+
 pub fn password_from_source_twice(
     source: &PasswordSource,
     prompt: &str,
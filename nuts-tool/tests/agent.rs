@@ -0,0 +1,345 @@
+// MIT License
+//
+// Copyright (c) 2024 Robin Doer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+#[allow(dead_code)]
+mod common;
+#[allow(dead_code)]
+mod predicates_ext;
+
+use assert_cmd::Command;
+use std::fs::{self, DirBuilder, Permissions};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::common::{container_create, handle_password_args, nuts_tool, setup};
+
+fn agent<'a, I: IntoIterator<Item = &'a str>>(home: &Path, args: I) -> Command {
+    let mut cmd = nuts_tool(home, ["agent"]);
+
+    cmd.args(args);
+
+    cmd
+}
+
+fn container_info(home: &Path, name: &str, pass: Option<&[u8]>) -> Command {
+    let cmd = nuts_tool(home, ["container", "info", "--container", name]);
+
+    handle_password_args(cmd, pass)
+}
+
+/// Stops the agent, even if the test fails.
+struct AgentGuard<'a>(&'a Path);
+
+impl<'a> AgentGuard<'a> {
+    fn start(home: &'a Path, timeout: &str) -> AgentGuard<'a> {
+        agent(home, ["start", "--timeout", timeout])
+            .assert()
+            .success()
+            .stdout(predicates::str::starts_with("agent started, pid "))
+            .stderr("");
+
+        AgentGuard(home)
+    }
+}
+
+impl<'a> Drop for AgentGuard<'a> {
+    fn drop(&mut self) {
+        let _ = agent(self.0, ["stop"]).output();
+    }
+}
+
+#[test]
+fn start_stop() {
+    let tmp_dir = setup();
+
+    agent(&tmp_dir, ["list"])
+        .assert()
+        .code(1)
+        .stdout(predicates::str::starts_with("no agent running at "))
+        .stderr("");
+
+    let guard = AgentGuard::start(&tmp_dir, "15m");
+
+    agent(&tmp_dir, ["start"])
+        .assert()
+        .code(1)
+        .stdout(predicates::str::starts_with(
+            "an agent is already running at ",
+        ))
+        .stderr("");
+    agent(&tmp_dir, ["list"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    agent(&tmp_dir, ["stop"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    agent(&tmp_dir, ["list"]).assert().code(1);
+
+    drop(guard);
+}
+
+#[test]
+fn permissions() {
+    let tmp_dir = setup();
+    let dir = tmp_dir.join(".nuts").join("agent");
+
+    let _guard = AgentGuard::start(&tmp_dir, "15m");
+
+    let mode = fs::metadata(&dir).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o700);
+
+    let mode = fs::metadata(dir.join("agent.sock"))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[test]
+fn insecure_dir() {
+    let tmp_dir = setup();
+    let dir = tmp_dir.join(".nuts").join("agent");
+
+    DirBuilder::new().mode(0o700).create(&dir).unwrap();
+    fs::set_permissions(&dir, Permissions::from_mode(0o755)).unwrap();
+
+    agent(&tmp_dir, ["start"])
+        .assert()
+        .code(1)
+        .stdout(format!(
+            "{} must be a directory owned by you with mode 0700\n",
+            dir.display()
+        ))
+        .stderr("");
+}
+
+#[test]
+fn cache_key() {
+    let tmp_dir = setup();
+
+    container_create(&tmp_dir, "sample", "directory", Some(b"123"))
+        .assert()
+        .success();
+
+    let _guard = AgentGuard::start(&tmp_dir, "15m");
+
+    // no key yet, the password is needed
+    container_info(&tmp_dir, "sample", None).assert().code(1);
+
+    // the key is not cached implicitly
+    container_info(&tmp_dir, "sample", Some(b"123"))
+        .assert()
+        .success();
+    agent(&tmp_dir, ["list"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    container_info(&tmp_dir, "sample", None).assert().code(1);
+
+    container_info(&tmp_dir, "sample", Some(b"123"))
+        .arg("--agent")
+        .assert()
+        .success();
+
+    agent(&tmp_dir, ["list"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("sample "))
+        .stderr("");
+    container_info(&tmp_dir, "sample", None).assert().success();
+}
+
+#[test]
+fn cipher_none() {
+    let tmp_dir = setup();
+
+    container_create(&tmp_dir, "sample", "directory", None)
+        .args(["--cipher", "none"])
+        .assert()
+        .success();
+
+    let _guard = AgentGuard::start(&tmp_dir, "15m");
+
+    container_info(&tmp_dir, "sample", None).assert().success();
+    agent(&tmp_dir, ["list"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    agent(&tmp_dir, ["add", "--container", "sample"])
+        .assert()
+        .code(1)
+        .stdout("the container sample is not encrypted\n")
+        .stderr("");
+}
+
+#[test]
+fn add() {
+    let tmp_dir = setup();
+
+    container_create(&tmp_dir, "sample", "directory", Some(b"123"))
+        .assert()
+        .success();
+
+    agent(&tmp_dir, ["add", "--container", "sample"])
+        .assert()
+        .code(1)
+        .stdout(predicates::str::starts_with("no agent running at "))
+        .stderr("");
+
+    let _guard = AgentGuard::start(&tmp_dir, "15m");
+
+    handle_password_args(
+        agent(&tmp_dir, ["add", "--container", "sample"]),
+        Some(b"xxx"),
+    )
+    .assert()
+    .code(1);
+    handle_password_args(
+        agent(&tmp_dir, ["add", "--container", "sample"]),
+        Some(b"123"),
+    )
+    .assert()
+    .success()
+    .stdout("")
+    .stderr("");
+
+    container_info(&tmp_dir, "sample", None).assert().success();
+}
+
+#[test]
+fn lock() {
+    let tmp_dir = setup();
+
+    container_create(&tmp_dir, "sample1", "directory", Some(b"123"))
+        .assert()
+        .success();
+    container_create(&tmp_dir, "sample2", "directory", Some(b"456"))
+        .assert()
+        .success();
+
+    let _guard = AgentGuard::start(&tmp_dir, "15m");
+
+    container_info(&tmp_dir, "sample1", Some(b"123"))
+        .arg("--agent")
+        .assert()
+        .success();
+    container_info(&tmp_dir, "sample2", Some(b"456"))
+        .arg("--agent")
+        .assert()
+        .success();
+
+    agent(&tmp_dir, ["lock", "--container", "sample1"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    container_info(&tmp_dir, "sample1", None).assert().code(1);
+    container_info(&tmp_dir, "sample2", None).assert().success();
+
+    agent(&tmp_dir, ["lock", "--container", "sample1"])
+        .assert()
+        .success()
+        .stdout("the agent has no key of sample1\n")
+        .stderr("");
+
+    agent(&tmp_dir, ["lock"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    container_info(&tmp_dir, "sample2", None).assert().code(1);
+}
+
+#[test]
+fn timeout() {
+    let tmp_dir = setup();
+
+    container_create(&tmp_dir, "sample", "directory", Some(b"123"))
+        .assert()
+        .success();
+
+    let _guard = AgentGuard::start(&tmp_dir, "1s");
+
+    container_info(&tmp_dir, "sample", Some(b"123"))
+        .arg("--agent")
+        .assert()
+        .success();
+    container_info(&tmp_dir, "sample", None).assert().success();
+
+    thread::sleep(Duration::from_secs(3));
+
+    container_info(&tmp_dir, "sample", None).assert().code(1);
+}
+
+#[test]
+fn change_password() {
+    let tmp_dir = setup();
+
+    container_create(&tmp_dir, "sample", "directory", Some(b"123"))
+        .assert()
+        .success();
+
+    let _guard = AgentGuard::start(&tmp_dir, "15m");
+
+    container_info(&tmp_dir, "sample", Some(b"123"))
+        .arg("--agent")
+        .assert()
+        .success();
+
+    // unlocked by the agent, the new password is read from the file
+    let path = tmp_dir.join("new-password.txt");
+    std::fs::write(&path, b"456").unwrap();
+
+    nuts_tool(
+        &tmp_dir,
+        [
+            "container",
+            "change",
+            "password",
+            "--container",
+            "sample",
+            "--new-password-from-file",
+            path.to_str().unwrap(),
+        ],
+    )
+    .assert()
+    .success()
+    .stdout("")
+    .stderr("");
+
+    agent(&tmp_dir, ["list"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    container_info(&tmp_dir, "sample", Some(b"456"))
+        .assert()
+        .success();
+}
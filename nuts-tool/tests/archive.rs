@@ -41,12 +41,32 @@ fn archive_add(home: &Path, name: &str, pass: Option<&[u8]>) -> Command {
     handle_password_args(cmd, pass)
 }
 
+fn archive_add_directory(home: &Path, name: &str, pass: Option<&[u8]>) -> Command {
+    let mut cmd = nuts_tool(home, ["archive", "add", "directory"]);
+
+    cmd.env("NUTS_CONTAINER", name);
+
+    handle_password_args(cmd, pass)
+}
+
+fn archive_chmod(home: &Path, name: &str, pass: Option<&[u8]>) -> Command {
+    let cmd = nuts_tool(home, ["archive", "chmod", "--container", name]);
+
+    handle_password_args(cmd, pass)
+}
+
 fn archive_create(home: &Path, name: &str, pass: Option<&[u8]>) -> Command {
     let cmd = nuts_tool(home, ["archive", "create", "--container", name]);
 
     handle_password_args(cmd, pass)
 }
 
+fn archive_find(home: &Path, name: &str, pass: Option<&[u8]>) -> Command {
+    let cmd = nuts_tool(home, ["archive", "find", "--container", name]);
+
+    handle_password_args(cmd, pass)
+}
+
 fn archive_info(home: &Path, name: &str, pass: Option<&[u8]>) -> Command {
     let cmd = nuts_tool(home, ["archive", "info", "--container", name]);
 
@@ -59,6 +79,24 @@ fn archive_list(home: &Path, name: &str, pass: Option<&[u8]>) -> Command {
     handle_password_args(cmd, pass)
 }
 
+fn archive_mv(home: &Path, name: &str, pass: Option<&[u8]>) -> Command {
+    let cmd = nuts_tool(home, ["archive", "mv", "--container", name]);
+
+    handle_password_args(cmd, pass)
+}
+
+fn archive_snapshot(home: &Path, command: &str, name: &str, pass: Option<&[u8]>) -> Command {
+    let cmd = nuts_tool(home, ["archive", "snapshot", command, "--container", name]);
+
+    handle_password_args(cmd, pass)
+}
+
+fn archive_touch(home: &Path, name: &str, pass: Option<&[u8]>) -> Command {
+    let cmd = nuts_tool(home, ["archive", "touch", "--container", name]);
+
+    handle_password_args(cmd, pass)
+}
+
 fn setup_archive() -> TempDir {
     let tmp_dir = setup();
 
@@ -81,11 +119,18 @@ fn help() {
         ["archive", "add", "file", "--help"].as_slice(),
         ["archive", "add", "directory", "--help"].as_slice(),
         ["archive", "add", "symlink", "--help"].as_slice(),
+        ["archive", "chmod", "--help"].as_slice(),
         ["archive", "create", "--help"].as_slice(),
+        ["archive", "find", "--help"].as_slice(),
         ["archive", "get", "--help"].as_slice(),
         ["archive", "info", "--help"].as_slice(),
         ["archive", "list", "--help"].as_slice(),
         ["archive", "migrate", "--help"].as_slice(),
+        ["archive", "mv", "--help"].as_slice(),
+        ["archive", "snapshot", "create", "--help"].as_slice(),
+        ["archive", "snapshot", "list", "--help"].as_slice(),
+        ["archive", "snapshot", "rm", "--help"].as_slice(),
+        ["archive", "touch", "--help"].as_slice(),
     ] {
         let password_from_fd = predicates::str::contains("--password-from-fd");
         let password_from_file = predicates::str::contains("--password-from-file");
@@ -207,6 +252,46 @@ fn add_directory() {}
 #[ignore]
 fn add_symlink() {}
 
+#[test]
+fn chmod() {
+    let tmp_dir = setup_archive();
+
+    archive_add_directory(&tmp_dir, "sample", Some(b"123"))
+        .arg("d1")
+        .assert()
+        .success();
+
+    for (mode, expected) in [
+        ("640", "drw-r----- "),
+        ("u+x,o=w", "drwxr---w- "),
+        ("a-w", "dr-xr----- "),
+        ("=x", "d--x--x--x "),
+    ] {
+        archive_chmod(&tmp_dir, "sample", Some(b"123"))
+            .args([mode, "d1"])
+            .assert()
+            .success()
+            .stdout("")
+            .stderr("");
+        archive_list(&tmp_dir, "sample", Some(b"123"))
+            .arg("--long")
+            .assert()
+            .success()
+            .stdout(predicates::str::starts_with(expected));
+    }
+
+    archive_chmod(&tmp_dir, "sample", Some(b"123"))
+        .args(["u+z", "d1"])
+        .assert()
+        .code(2);
+    archive_chmod(&tmp_dir, "sample", Some(b"123"))
+        .args(["644", "d2"])
+        .assert()
+        .code(1)
+        .stdout("no such entry: d2\n")
+        .stderr("");
+}
+
 #[test]
 fn create() {
     let tmp_dir = setup();
@@ -259,9 +344,33 @@ fn create() {
     archive_create(&tmp_dir, "sample", Some(b"123"))
         .assert()
         .code(1)
-        .stdout("unexpected sid, expected none but got 1634886504\n")
+        .stdout("the service 1634886504 already exists\n")
         .stderr("");
 
+    container_create(&tmp_dir, "sample4", "directory", Some(b"123"))
+        .assert()
+        .success();
+    archive_create(&tmp_dir, "sample4", Some(b"123"))
+        .args(["--property", "host=foo", "-p", "job=42", "-p", "empty="])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    archive_info(&tmp_dir, "sample4", Some(b"123"))
+        .assert()
+        .success()
+        .stdout(hash::contains([
+            ("files", "0"),
+            ("property.host", "foo"),
+            ("property.job", "42"),
+            ("property.empty", ""),
+        ]))
+        .stderr("");
+    archive_create(&tmp_dir, "sample4", Some(b"123"))
+        .args(["--force", "--property", "=foo"])
+        .assert()
+        .code(2);
+
     container_create(&tmp_dir, "sample1", "directory", Some(b"123"))
         .assert()
         .success();
@@ -314,6 +423,80 @@ fn create() {
         ]));
 }
 
+#[test]
+fn find() {
+    let tmp_dir = setup_archive();
+    let f1 = tmp_dir.join("f1.txt");
+    let f2 = tmp_dir.join("f2.dat");
+
+    for (f, size) in [(&f1, 10), (&f2, 2000)] {
+        let mut f = File::create(f).unwrap();
+        f.write_all(&vec![b'x'; size]).unwrap();
+        f.flush().unwrap();
+        f.sync_all().unwrap();
+    }
+
+    archive_add(&tmp_dir, "sample", Some(b"123"))
+        .args([f1.to_str().unwrap(), f2.to_str().unwrap()])
+        .assert()
+        .success();
+    archive_add_directory(&tmp_dir, "sample", Some(b"123"))
+        .args(["d1", "--modified", "2020-01-02T03:04:05Z"])
+        .assert()
+        .success();
+
+    let f1 = f1.to_str().unwrap();
+    let f2 = f2.to_str().unwrap();
+
+    for (args, expected) in [
+        ([].as_slice(), [f1, f2, "d1"].as_slice()),
+        (["--name", "*.txt"].as_slice(), [f1].as_slice()),
+        (
+            ["--name", "*.txt", "--name", "d?"].as_slice(),
+            [f1, "d1"].as_slice(),
+        ),
+        (["--type", "f"].as_slice(), [f1, f2].as_slice()),
+        (["--type", "directory"].as_slice(), ["d1"].as_slice()),
+        (["--type", "l"].as_slice(), [].as_slice()),
+        (["--type", "f", "--size", "+1k"].as_slice(), [f2].as_slice()),
+        (["--type", "f", "--size", "-1k"].as_slice(), [f1].as_slice()),
+        (["--size", "10"].as_slice(), [f1].as_slice()),
+        (
+            ["--size", "+9", "--size", "-11"].as_slice(),
+            [f1].as_slice(),
+        ),
+        (
+            ["--older", "2021-01-01T00:00:00Z"].as_slice(),
+            ["d1"].as_slice(),
+        ),
+        (
+            ["--newer", "2021-01-01T00:00:00Z"].as_slice(),
+            [f1, f2].as_slice(),
+        ),
+        (
+            ["--newer", "2021-01-01T00:00:00Z", "--time", "appended"].as_slice(),
+            [f1, f2, "d1"].as_slice(),
+        ),
+    ] {
+        archive_find(&tmp_dir, "sample", Some(b"123"))
+            .args(args)
+            .assert()
+            .success()
+            .stdout(
+                expected
+                    .iter()
+                    .map(|s| format!("{}\n", s))
+                    .collect::<String>(),
+            )
+            .stderr("");
+    }
+
+    archive_find(&tmp_dir, "sample", Some(b"123"))
+        .args(["--size", "1x"])
+        .assert()
+        .code(2);
+}
+
 #[test]
 #[ignore]
 fn get() {}
@@ -329,3 +512,219 @@ fn list() {}
 #[test]
 #[ignore]
 fn migrate() {}
+
+#[test]
+fn mv() {
+    let tmp_dir = setup_archive();
+
+    for name in ["d1", "d2"] {
+        archive_add_directory(&tmp_dir, "sample", Some(b"123"))
+            .arg(name)
+            .assert()
+            .success();
+    }
+
+    archive_mv(&tmp_dir, "sample", Some(b"123"))
+        .args(["d1", "d3"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    archive_list(&tmp_dir, "sample", Some(b"123"))
+        .assert()
+        .success()
+        .stdout(list::eq(["d3", "d2"]));
+
+    archive_mv(&tmp_dir, "sample", Some(b"123"))
+        .args(["d3", "d2"])
+        .assert()
+        .code(1)
+        .stdout("the entry d2 already exists\n")
+        .stderr("");
+    archive_mv(&tmp_dir, "sample", Some(b"123"))
+        .args(["d1", "d4"])
+        .assert()
+        .code(1)
+        .stdout("no such entry: d1\n")
+        .stderr("");
+}
+
+#[test]
+fn read_only() {
+    let tmp_dir = setup_archive();
+
+    archive_add_directory(&tmp_dir, "sample", Some(b"123"))
+        .arg("d1")
+        .assert()
+        .success();
+
+    archive_add_directory(&tmp_dir, "sample", Some(b"123"))
+        .args(["--read-only", "d2"])
+        .assert()
+        .code(1)
+        .stdout("the container is opened read-only\n")
+        .stderr("");
+    archive_list(&tmp_dir, "sample", Some(b"123"))
+        .arg("--read-only")
+        .assert()
+        .success()
+        .stdout(list::eq(["d1"]))
+        .stderr("");
+}
+
+#[cfg(unix)]
+#[test]
+fn locked() {
+    use std::os::unix::io::AsRawFd;
+
+    let tmp_dir = setup_archive();
+    let lock_path = tmp_dir.join(".nuts/container.d/sample");
+
+    let lock_file = File::open(lock_path).unwrap();
+    assert_eq!(
+        unsafe { libc::flock(lock_file.as_raw_fd(), libc::LOCK_SH) },
+        0
+    );
+
+    archive_add_directory(&tmp_dir, "sample", Some(b"123"))
+        .arg("d1")
+        .assert()
+        .code(1)
+        .stdout("the backend created an error: The container is locked by another process\n")
+        .stderr("");
+    archive_list(&tmp_dir, "sample", Some(b"123"))
+        .arg("--read-only")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+
+    drop(lock_file);
+
+    archive_add_directory(&tmp_dir, "sample", Some(b"123"))
+        .arg("d1")
+        .assert()
+        .success();
+}
+
+#[test]
+fn snapshot() {
+    let tmp_dir = setup_archive();
+    let f1 = tmp_dir.join("f1.txt");
+    let f2 = tmp_dir.join("f2.txt");
+
+    for f in [&f1, &f2] {
+        let mut f = File::create(f).unwrap();
+        f.write_all(b"xxx").unwrap();
+        f.flush().unwrap();
+        f.sync_all().unwrap();
+    }
+
+    archive_add(&tmp_dir, "sample", Some(b"123"))
+        .arg(f1.to_str().unwrap())
+        .assert()
+        .success();
+    archive_snapshot(&tmp_dir, "create", "sample", Some(b"123"))
+        .arg("s1")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    archive_snapshot(&tmp_dir, "create", "sample", Some(b"123"))
+        .arg("s1")
+        .assert()
+        .code(1)
+        .stdout("the snapshot s1 already exists\n")
+        .stderr("");
+
+    archive_add(&tmp_dir, "sample", Some(b"123"))
+        .arg(f2.to_str().unwrap())
+        .assert()
+        .success();
+    archive_snapshot(&tmp_dir, "create", "sample", Some(b"123"))
+        .arg("s2")
+        .assert()
+        .success();
+
+    archive_snapshot(&tmp_dir, "list", "sample", Some(b"123"))
+        .assert()
+        .success()
+        .stdout(list::eq(["s1", "s2"]))
+        .stderr("");
+    archive_list(&tmp_dir, "sample", Some(b"123"))
+        .args(["--snapshot", "s1"])
+        .assert()
+        .success()
+        .stdout(list::eq([f1.to_str().unwrap()]));
+    archive_list(&tmp_dir, "sample", Some(b"123"))
+        .args(["--snapshot", "s2"])
+        .assert()
+        .success()
+        .stdout(list::eq([f1.to_str().unwrap(), f2.to_str().unwrap()]));
+
+    archive_snapshot(&tmp_dir, "rm", "sample", Some(b"123"))
+        .arg("s1")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    archive_snapshot(&tmp_dir, "rm", "sample", Some(b"123"))
+        .arg("s1")
+        .assert()
+        .code(1)
+        .stdout("no such snapshot: s1\n")
+        .stderr("");
+    archive_snapshot(&tmp_dir, "list", "sample", Some(b"123"))
+        .assert()
+        .success()
+        .stdout(list::eq(["s2"]));
+    archive_list(&tmp_dir, "sample", Some(b"123"))
+        .assert()
+        .success()
+        .stdout(list::eq([f1.to_str().unwrap(), f2.to_str().unwrap()]));
+}
+
+#[test]
+fn touch() {
+    let tmp_dir = setup_archive();
+
+    archive_add_directory(&tmp_dir, "sample", Some(b"123"))
+        .arg("d1")
+        .assert()
+        .success();
+
+    archive_touch(&tmp_dir, "sample", Some(b"123"))
+        .args(["d1", "--modified", "2020-01-02T03:04:05Z"])
+        .args(["--created", "2019-01-02T03:04:05Z"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    archive_list(&tmp_dir, "sample", Some(b"123"))
+        .args(["--long", "--time-format", "utc"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(" 02 Jan 03:04 d1\n"));
+    archive_list(&tmp_dir, "sample", Some(b"123"))
+        .args(["--long", "--created", "--time-format", "utc"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(" 02 Jan 03:04 d1\n"));
+
+    archive_touch(&tmp_dir, "sample", Some(b"123"))
+        .arg("d1")
+        .assert()
+        .success();
+    archive_list(&tmp_dir, "sample", Some(b"123"))
+        .args(["--long", "--time-format", "utc"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(" 02 Jan 03:04 d1\n").not());
+
+    archive_touch(&tmp_dir, "sample", Some(b"123"))
+        .arg("d2")
+        .assert()
+        .code(1)
+        .stdout("no such entry: d2\n")
+        .stderr("");
+}
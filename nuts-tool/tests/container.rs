@@ -29,6 +29,7 @@ use assert_cmd::assert::Assert;
 use assert_cmd::Command;
 use assert_fs::fixture::TempDir;
 use hex;
+use nuts_container::PrivateKey;
 use predicates::prelude::PredicateBooleanExt;
 use std::collections::HashMap;
 use std::fs::{self, File};
@@ -76,6 +77,38 @@ fn container_delete(home: &Path, name: &str, pass: Option<&[u8]>) -> Command {
     handle_password_args(cmd, pass)
 }
 
+fn container_header_backup(home: &Path, name: &str, file: &Path, pass: Option<&[u8]>) -> Command {
+    let cmd = nuts_tool(
+        home,
+        [
+            "container",
+            "header",
+            "backup",
+            "--container",
+            name,
+            file.to_str().unwrap(),
+        ],
+    );
+
+    handle_password_args(cmd, pass)
+}
+
+fn container_header_restore(home: &Path, name: &str, file: &Path, pass: Option<&[u8]>) -> Command {
+    let cmd = nuts_tool(
+        home,
+        [
+            "container",
+            "header",
+            "restore",
+            "--container",
+            name,
+            file.to_str().unwrap(),
+        ],
+    );
+
+    handle_password_args(cmd, pass)
+}
+
 fn container_info(home: &Path, name: &str, pass: Option<&[u8]>) -> Command {
     let cmd = nuts_tool(home, ["container", "info", "--container", name]);
 
@@ -98,6 +131,12 @@ fn container_release(home: &Path, name: &str, id: &str, pass: Option<&[u8]>) ->
     handle_password_args(cmd, pass)
 }
 
+fn container_repair(home: &Path, name: &str, pass: Option<&[u8]>) -> Command {
+    let cmd = nuts_tool(home, ["container", "repair", "--container", name]);
+
+    handle_password_args(cmd, pass)
+}
+
 fn container_write(
     home: &Path,
     name: &str,
@@ -121,12 +160,17 @@ fn container_write(
 fn default_info_with<'a>(values: HashMap<&'a str, &'a str>) -> HashMap<&'a str, &'a str> {
     let mut hash: HashMap<&str, &str> = [
         ("plugin", "directory"),
-        ("revision", "2"),
+        ("revision", "3"),
         ("cipher", "aes256-gcm"),
         ("kdf", "pbkdf2:sha256:65536:16"),
+        ("services", "none"),
         ("block size (gross)", "512"),
         ("block size (net)", "496"),
+        ("rollback protection", "no"),
+        ("recovery key", "no"),
+        ("recipient", "no"),
         ("block_size", "512"),
+        ("durability", "none"),
     ]
     .into();
 
@@ -187,9 +231,13 @@ fn help() {
         ["container", "change", "kdf", "--help"].as_slice(),
         ["container", "create", "--help"].as_slice(),
         ["container", "delete", "--help"].as_slice(),
+        ["container", "header", "--help"].as_slice(), // FIXME
+        ["container", "header", "backup", "--help"].as_slice(),
+        ["container", "header", "restore", "--help"].as_slice(),
         ["container", "info", "--help"].as_slice(),
         ["container", "read", "--help"].as_slice(),
         ["container", "release", "--help"].as_slice(),
+        ["container", "repair", "--help"].as_slice(),
         ["container", "write", "--help"].as_slice(),
     ] {
         let password_from_fd = predicates::str::contains("--password-from-fd");
@@ -359,6 +407,136 @@ fn change_password() {
         .success();
 }
 
+#[test]
+fn change_password_recovery() {
+    let tmp_dir = setup();
+    let password_file = tmp_dir.join("new_password.txt");
+
+    let mut f = File::create(&password_file).unwrap();
+    f.write_all(b"new_password").unwrap();
+    f.flush().unwrap();
+
+    container_create(&tmp_dir, "sample", "directory", Some(b"123"))
+        .assert()
+        .success();
+    container_change_password(&tmp_dir, "sample", Some(b"xxx"))
+        .arg("--recovery")
+        .assert()
+        .code(1)
+        .stdout("the container has no recovery key\n")
+        .stderr("");
+
+    let output = container_create(&tmp_dir, "sample2", "directory", Some(b"123"))
+        .arg("--recovery-key")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = str::from_utf8(&output.stdout).unwrap();
+    let recovery_key = stdout.strip_prefix("recovery key: ").unwrap().trim_end();
+    assert_eq!(recovery_key.len(), 39);
+
+    container_info(&tmp_dir, "sample2", Some(b"123"))
+        .assert()
+        .success()
+        .stdout(hash::contains([("recovery key", "yes")]));
+    container_change_password(&tmp_dir, "sample2", Some(b"123"))
+        .arg("--recovery")
+        .assert()
+        .code(1)
+        .stdout("invalid recovery key\n")
+        .stderr("");
+    container_change_password(
+        &tmp_dir,
+        "sample2",
+        Some(b"AAAA-AAAA-AAAA-AAAA-AAAA-AAAA-AAAA-AAAA"),
+    )
+    .arg("--recovery")
+    .assert()
+    .code(1)
+    .stdout("the recovery key is wrong\n")
+    .stderr("");
+
+    let mut cmd = container_change_password(&tmp_dir, "sample2", Some(recovery_key.as_bytes()));
+    cmd.arg("--recovery");
+    handle_password_file(
+        &tmp_dir,
+        cmd,
+        "--new-password-from-file",
+        Some(b"new_password"),
+    )
+    .assert()
+    .success()
+    .stdout("")
+    .stderr("");
+
+    container_info(&tmp_dir, "sample2", Some(b"new_password"))
+        .assert()
+        .success();
+}
+
+#[test]
+fn recipient() {
+    let tmp_dir = setup();
+    let private_key = PrivateKey::generate().unwrap();
+    let private_path = tmp_dir.join("private.pem");
+    let public_path = tmp_dir.join("public.pem");
+    let other_path = tmp_dir.join("other.pem");
+
+    fs::write(&private_path, private_key.to_pem().unwrap()).unwrap();
+    fs::write(
+        &public_path,
+        private_key.public_key().unwrap().to_pem().unwrap(),
+    )
+    .unwrap();
+    fs::write(
+        &other_path,
+        PrivateKey::generate().unwrap().to_pem().unwrap(),
+    )
+    .unwrap();
+
+    container_create(&tmp_dir, "sample", "directory", None)
+        .args([
+            "--recipient",
+            public_path.to_str().unwrap(),
+            "--no-password",
+        ])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+
+    container_info(&tmp_dir, "sample", None)
+        .args(["--private-key", private_path.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(hash::contains([("recipient", "yes")]));
+    container_info(&tmp_dir, "sample", None)
+        .args(["--private-key", other_path.to_str().unwrap()])
+        .assert()
+        .code(1)
+        .stdout("the private key does not match the recipient\n")
+        .stderr("");
+    container_info(&tmp_dir, "sample", None)
+        .args(["--private-key", public_path.to_str().unwrap()])
+        .assert()
+        .code(1);
+
+    container_create(&tmp_dir, "sample2", "directory", Some(b"123"))
+        .args(["--recipient", public_path.to_str().unwrap()])
+        .assert()
+        .success();
+    container_info(&tmp_dir, "sample2", Some(b"123"))
+        .assert()
+        .success()
+        .stdout(hash::contains([("recipient", "yes")]));
+    container_info(&tmp_dir, "sample2", None)
+        .args(["--private-key", private_path.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(hash::contains([("recipient", "yes")]));
+}
+
 #[test]
 fn change_kdf() {
     let tmp_dir = setup();
@@ -397,6 +575,88 @@ fn change_kdf() {
     }
 }
 
+fn kdf_from_info(home: &Path, name: &str) -> Vec<String> {
+    let output = container_info(home, name, Some(b"123")).output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = str::from_utf8(&output.stdout).unwrap();
+    let kdf = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("kdf:"))
+        .unwrap();
+
+    kdf.trim().split(':').map(ToString::to_string).collect()
+}
+
+#[test]
+fn kdf_time() {
+    let tmp_dir = setup();
+
+    container_create(&tmp_dir, "sample", "directory", Some(b"123"))
+        .args(["--kdf-time", "xxx"])
+        .assert()
+        .code(2);
+
+    container_create(&tmp_dir, "sample", "directory", Some(b"123"))
+        .args(["--kdf-time", "10ms"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+
+    let kdf = kdf_from_info(&tmp_dir, "sample");
+    assert_eq!(kdf[0], "pbkdf2");
+    assert_eq!(kdf[1], "sha256");
+    assert!(kdf[2].parse::<u32>().unwrap() >= 1000);
+    assert_eq!(kdf[3], "16");
+
+    container_create(&tmp_dir, "sample2", "directory", Some(b"123"))
+        .args(["--kdf", "pbkdf2:sha1:1:8", "--kdf-time", "10ms"])
+        .assert()
+        .success();
+
+    let kdf = kdf_from_info(&tmp_dir, "sample2");
+    assert_eq!(kdf[0], "pbkdf2");
+    assert_eq!(kdf[1], "sha1");
+    assert!(kdf[2].parse::<u32>().unwrap() >= 1000);
+    assert_eq!(kdf[3], "8");
+
+    container_change_kdf(&tmp_dir, "sample2", "pbkdf2:sha512::4", Some(b"123"))
+        .args(["--kdf-time", "10ms"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+
+    let kdf = kdf_from_info(&tmp_dir, "sample2");
+    assert_eq!(kdf[0], "pbkdf2");
+    assert_eq!(kdf[1], "sha512");
+    assert!(kdf[2].parse::<u32>().unwrap() >= 1000);
+    assert_eq!(kdf[3], "4");
+
+    let cmd = nuts_tool(
+        &tmp_dir,
+        [
+            "container",
+            "change",
+            "kdf",
+            "--container",
+            "sample",
+            "--kdf-time",
+            "10ms",
+        ],
+    );
+    handle_password_args(cmd, Some(b"123"))
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+
+    let kdf = kdf_from_info(&tmp_dir, "sample");
+    assert_eq!(kdf[1], "sha256");
+    assert_eq!(kdf[3], "16");
+}
+
 #[test]
 fn create() {
     let tmp_dir = setup();
@@ -440,6 +700,21 @@ fn create() {
             [("cipher", "aes192-gcm")].into(),
         ),
         (&["--cipher", "aes256-gcm"], Some(b"123"), [].into()),
+        (
+            &["--cipher", "aes128-ctr-hmac"],
+            Some(b"123"),
+            [("cipher", "aes128-ctr-hmac"), ("block size (net)", "480")].into(),
+        ),
+        (
+            &["--cipher", "aes192-ctr-hmac"],
+            Some(b"123"),
+            [("cipher", "aes192-ctr-hmac"), ("block size (net)", "480")].into(),
+        ),
+        (
+            &["--cipher", "aes256-ctr-hmac"],
+            Some(b"123"),
+            [("cipher", "aes256-ctr-hmac"), ("block size (net)", "480")].into(),
+        ),
         (&["--kdf", "pbkdf2"], Some(b"123"), [].into()),
         (&["--kdf", "pbkdf2:::"], Some(b"123"), [].into()),
         (
@@ -467,6 +742,16 @@ fn create() {
             ]
             .into(),
         ),
+        (
+            &["--", "--durability", "write"],
+            Some(b"123"),
+            [("durability", "write")].into(),
+        ),
+        (
+            &["--rollback-protection"],
+            Some(b"123"),
+            [("rollback protection", "yes")].into(),
+        ),
     ] {
         let name = format!("sample{idx}");
         idx += 1;
@@ -554,6 +839,121 @@ fn delete() {
     assert!(!tmp_dir.join(".nuts/container.d/sample").exists());
 }
 
+#[test]
+fn header_backup_restore() {
+    let tmp_dir = setup();
+    let backup = tmp_dir.join("header.bak");
+    let header = tmp_dir.join(".nuts/container.d/sample/00/00/0000000000000000000000000000");
+    let header_copy = tmp_dir.join(".nuts/container.d/sample/ff/ff/ffffffffffffffffffffffffffff");
+
+    container_header_backup(&tmp_dir, "sample", &backup, Some(b"123"))
+        .assert()
+        .code(1)
+        .stdout("no such container: sample\n")
+        .stderr("");
+
+    container_create(&tmp_dir, "sample", "directory", Some(b"123"))
+        .assert()
+        .success();
+    let assert = container_acquire(&tmp_dir, "sample", Some(b"123"))
+        .assert()
+        .success();
+    let id = id_from_acquire_stdout(assert);
+
+    container_write(&tmp_dir, "sample", Some(&id), b"abc", Some(b"123"))
+        .assert()
+        .success();
+    container_header_backup(&tmp_dir, "sample", &backup, Some(b"123"))
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+
+    // corrupt the header and its copy
+    fs::write(&header, [0xff; 512]).unwrap();
+    fs::write(&header_copy, [0xff; 512]).unwrap();
+    container_read(&tmp_dir, "sample", &id, Some(b"123"))
+        .assert()
+        .code(1);
+
+    container_header_restore(&tmp_dir, "sample", &backup, Some(b"xxx"))
+        .arg("--yes")
+        .assert()
+        .code(1)
+        .stdout("the plaintext is not trustworthy\n")
+        .stderr("");
+    container_header_restore(&tmp_dir, "sample", &backup, Some(b"123"))
+        .arg("--yes")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+
+    let mut data = b"abc".to_vec();
+    data.resize(496, 0);
+
+    container_read(&tmp_dir, "sample", &id, Some(b"123"))
+        .assert()
+        .success()
+        .stdout(data)
+        .stderr("");
+}
+
+#[test]
+fn header_copy() {
+    let tmp_dir = setup();
+    let header = tmp_dir.join(".nuts/container.d/sample/00/00/0000000000000000000000000000");
+
+    container_create(&tmp_dir, "sample", "directory", Some(b"123"))
+        .assert()
+        .success();
+    let assert = container_acquire(&tmp_dir, "sample", Some(b"123"))
+        .assert()
+        .success();
+    let id = id_from_acquire_stdout(assert);
+
+    container_write(&tmp_dir, "sample", Some(&id), b"abc", Some(b"123"))
+        .assert()
+        .success();
+
+    // corrupt the header, the copy is used instead
+    fs::write(&header, [0xff; 512]).unwrap();
+
+    let mut data = b"abc".to_vec();
+    data.resize(496, 0);
+
+    container_read(&tmp_dir, "sample", &id, Some(b"123"))
+        .assert()
+        .success()
+        .stdout(data)
+        .stderr("");
+    assert_ne!(fs::read(&header).unwrap(), [0xff; 512]);
+}
+
+#[test]
+fn header_restore_corrupted() {
+    let tmp_dir = setup();
+    let backup = tmp_dir.join("header.bak");
+
+    container_create(&tmp_dir, "sample", "directory", Some(b"123"))
+        .assert()
+        .success();
+    container_header_backup(&tmp_dir, "sample", &backup, Some(b"123"))
+        .assert()
+        .success();
+
+    let mut bytes = fs::read(&backup).unwrap();
+    bytes[100] ^= 0xff;
+    fs::write(&backup, bytes).unwrap();
+
+    container_header_restore(&tmp_dir, "sample", &backup, Some(b"123"))
+        .arg("--yes")
+        .assert()
+        .code(1)
+        .stdout("checksum mismatch, the header backup is corrupted\n")
+        .stderr("");
+}
+
 #[test]
 fn info() {
     let tmp_dir = setup();
@@ -579,6 +979,26 @@ fn info() {
         .stderr("");
 }
 
+#[test]
+fn info_services() {
+    let tmp_dir = setup();
+
+    container_create(&tmp_dir, "sample", "directory", Some(b"123"))
+        .assert()
+        .success();
+
+    let cmd = nuts_tool(&tmp_dir, ["archive", "create", "--container", "sample"]);
+    handle_password_args(cmd, Some(b"123")).assert().success();
+
+    container_info(&tmp_dir, "sample", Some(b"123"))
+        .assert()
+        .success()
+        .stdout(hash::eq(default_info_with(
+            [("services", "archive")].into(),
+        )))
+        .stderr("");
+}
+
 #[test]
 fn read() {
     let tmp_dir = setup();
@@ -775,3 +1195,151 @@ fn write() {
         .stdout(data[..496].to_vec())
         .stderr("");
 }
+
+#[test]
+fn write_rollback_protection() {
+    let tmp_dir = setup();
+    let data = [1, 2, 3];
+
+    container_create(&tmp_dir, "sample", "directory", Some(b"123"))
+        .arg("--rollback-protection")
+        .assert()
+        .success();
+
+    let assert = container_write(&tmp_dir, "sample", None, &data, Some(b"123"))
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("3 bytes written into "))
+        .stderr("");
+    let output = assert.get_output();
+    let id = str::from_utf8(output.stdout.split(|b| *b == b' ').nth(4).unwrap())
+        .unwrap()
+        .trim_end();
+
+    container_read(&tmp_dir, "sample", id, Some(b"123"))
+        .args(["--max-bytes", "3"])
+        .assert()
+        .success()
+        .stdout(data.to_vec())
+        .stderr("");
+
+    container_release(&tmp_dir, "sample", id, Some(b"123"))
+        .assert()
+        .success();
+    container_info(&tmp_dir, "sample", Some(b"123"))
+        .assert()
+        .success();
+}
+
+fn find_block(dir: &Path, id: &str) -> Option<std::path::PathBuf> {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+
+        if path.is_dir() {
+            if let Some(path) = find_block(&path, id) {
+                return Some(path);
+            }
+        } else if path.ends_with(Path::new(&id[..2]).join(&id[2..4]).join(&id[4..])) {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+#[test]
+fn repair() {
+    let tmp_dir = setup();
+
+    container_create(&tmp_dir, "sample", "directory", Some(b"123"))
+        .arg("--rollback-protection")
+        .assert()
+        .success();
+    let assert = container_acquire(&tmp_dir, "sample", Some(b"123"))
+        .assert()
+        .success();
+    let id = id_from_acquire_stdout(assert);
+
+    container_write(&tmp_dir, "sample", Some(&id), &[1, 2, 3], Some(b"123"))
+        .assert()
+        .success();
+    let path = find_block(&tmp_dir, &id).unwrap();
+    let old = fs::read(&path).unwrap();
+    container_write(&tmp_dir, "sample", Some(&id), &[4, 5, 6], Some(b"123"))
+        .assert()
+        .success();
+
+    // nothing to repair
+    container_repair(&tmp_dir, "sample", Some(b"123"))
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+
+    fs::write(&path, old).unwrap();
+
+    container_read(&tmp_dir, "sample", &id, Some(b"123"))
+        .assert()
+        .code(1)
+        .stdout(format!("block {id} is stale or was modified\n"))
+        .stderr("");
+    container_repair(&tmp_dir, "sample", Some(b"123"))
+        .assert()
+        .success()
+        .stdout(format!("repaired: {id}\n"))
+        .stderr("");
+    container_read(&tmp_dir, "sample", &id, Some(b"123"))
+        .args(["--max-bytes", "3"])
+        .assert()
+        .success()
+        .stdout([1, 2, 3].to_vec())
+        .stderr("");
+}
+
+#[test]
+fn image() {
+    let tmp_dir = setup();
+    let plugin = plugin_path("nuts-image");
+    let data = [1, 2, 3];
+
+    plugin_add(&tmp_dir, "image", plugin.to_str().unwrap())
+        .assert()
+        .success();
+    container_create(&tmp_dir, "sample", "image", Some(b"123"))
+        .args(["--", "--capacity", "16"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    assert!(tmp_dir.join(".nuts/container.d/sample/image").is_file());
+
+    let assert = container_acquire(&tmp_dir, "sample", Some(b"123"))
+        .assert()
+        .success();
+    let id = id_from_acquire_stdout(assert);
+    let mut out = vec![0; 496];
+
+    out[..3].copy_from_slice(&data);
+
+    container_write(&tmp_dir, "sample", Some(&id), &data, Some(b"123"))
+        .assert()
+        .success()
+        .stdout(format!("3 bytes written into {id}\n"))
+        .stderr("");
+    container_read(&tmp_dir, "sample", &id, Some(b"123"))
+        .assert()
+        .success()
+        .stdout(out)
+        .stderr("");
+
+    let mut infos = default_info_with([("plugin", "image")].into());
+
+    infos.remove("durability");
+    infos.insert("capacity", "16");
+    infos.insert("blocks", "1");
+
+    container_info(&tmp_dir, "sample", Some(b"123"))
+        .assert()
+        .success()
+        .stdout(hash::eq(infos));
+}
@@ -139,7 +139,7 @@ fn modify() {
         .success()
         .stdout(hash::eq([
             ("name", "directory"),
-            ("revision", "1"),
+            ("revision", "3"),
             ("version", crate_version!()),
             ("path", plugin.to_str().unwrap()),
         ]));
@@ -155,7 +155,7 @@ fn modify() {
         .success()
         .stdout(hash::eq([
             ("name", "directory"),
-            ("revision", "1"),
+            ("revision", "3"),
             ("version", crate_version!()),
             ("path", new_plugin.to_str().unwrap()),
         ]));
@@ -205,7 +205,7 @@ fn info() {
         .success()
         .stdout(hash::eq([
             ("name", "directory"),
-            ("revision", "1"),
+            ("revision", "3"),
             ("version", crate_version!()),
             ("path", plugin.to_str().unwrap()),
         ]));
@@ -9,6 +9,7 @@ PACKAGES="
   nuts-tool-api
   nuts-memory
   nuts-directory
+  nuts-image
   nuts-container
   nuts-archive
   nuts-tool